@@ -0,0 +1,117 @@
+//! Session file parse throughput
+//!
+//! Generates a session file of realistic user/assistant/tool entries and
+//! measures raw JSONL decoding and full turn aggregation over it. Compare
+//! the decoders by running with and without the `simd-json` feature:
+//!
+//! ```sh
+//! cargo bench --bench parse --no-default-features
+//! cargo bench --bench parse --no-default-features --features simd-json
+//! ```
+//!
+//! `IRONHIDE_BENCH_MB` sets the size of the generated file (default 32).
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ironhide_lib::parser::jsonl::parse_streaming;
+use ironhide_lib::parser::session::parse_session_to_turns;
+
+/// Write `target_bytes` worth of entries, one user prompt, a tool round-trip
+/// and an assistant reply per turn
+fn generate_session(path: &Path, target_bytes: usize) -> usize {
+    let file = std::fs::File::create(path).expect("create bench session file");
+    let mut out = std::io::BufWriter::new(file);
+    let filler = "Refactor the parser so it streams entries instead of buffering them. ".repeat(12);
+    let tool_output = "fn main() {\n    println!(\"hello\");\n}\n".repeat(40);
+    let mut written = 0;
+    let mut turn = 0u64;
+
+    while written < target_bytes {
+        let minute = turn % 60;
+        let hour = (turn / 60) % 24;
+        let ts = |second: u64| format!("2026-03-01T{:02}:{:02}:{:02}.000Z", hour, minute, second);
+        let lines = [
+            serde_json::json!({
+                "type": "user", "uuid": format!("u{}", turn), "sessionId": "bench",
+                "timestamp": ts(0), "cwd": "/home/me/dev/app", "version": "2.1.0",
+                "gitBranch": "main", "userType": "external",
+                "message": {"role": "user", "content": filler},
+            }),
+            serde_json::json!({
+                "type": "assistant", "uuid": format!("a{}", turn), "parentUuid": format!("u{}", turn),
+                "sessionId": "bench", "timestamp": ts(5),
+                "message": {
+                    "id": format!("msg_{}", turn), "role": "assistant", "model": "claude-opus-4-5-20251101",
+                    "content": [
+                        {"type": "thinking", "thinking": filler},
+                        {"type": "tool_use", "id": format!("toolu_{}", turn), "name": "Read",
+                         "input": {"file_path": "/home/me/dev/app/src/main.rs"}},
+                    ],
+                    "stop_reason": "tool_use",
+                    "usage": {"input_tokens": 1200, "output_tokens": 350, "cache_read_input_tokens": 48000,
+                              "cache_creation_input_tokens": 900},
+                },
+            }),
+            serde_json::json!({
+                "type": "user", "uuid": format!("r{}", turn), "parentUuid": format!("a{}", turn),
+                "sessionId": "bench", "timestamp": ts(6),
+                "message": {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": format!("toolu_{}", turn), "content": tool_output},
+                ]},
+            }),
+            serde_json::json!({
+                "type": "assistant", "uuid": format!("f{}", turn), "parentUuid": format!("r{}", turn),
+                "sessionId": "bench", "timestamp": ts(20),
+                "message": {
+                    "id": format!("msg_f{}", turn), "role": "assistant", "model": "claude-opus-4-5-20251101",
+                    "content": [{"type": "text", "text": filler}],
+                    "stop_reason": "end_turn",
+                    "usage": {"input_tokens": 2400, "output_tokens": 800, "cache_read_input_tokens": 52000,
+                              "cache_creation_input_tokens": 300},
+                },
+            }),
+        ];
+        for line in lines {
+            let line = line.to_string();
+            writeln!(out, "{}", line).expect("write bench session file");
+            written += line.len() + 1;
+        }
+        turn += 1;
+    }
+
+    out.flush().expect("flush bench session file");
+    written
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let megabytes: usize = std::env::var("IRONHIDE_BENCH_MB")
+        .ok()
+        .and_then(|mb| mb.parse().ok())
+        .unwrap_or(32);
+    let path: PathBuf = std::env::temp_dir().join("ironhide_bench_session.jsonl");
+    let bytes = generate_session(&path, megabytes * 1024 * 1024);
+    let decoder = if cfg!(feature = "simd-json") { "simd-json" } else { "serde_json" };
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.sample_size(10);
+
+    group.bench_function(BenchmarkId::new("entries", decoder), |b| {
+        b.iter(|| {
+            let mut count = 0usize;
+            parse_streaming(&path, |_| count += 1).expect("parse bench session");
+            count
+        })
+    });
+    group.bench_function(BenchmarkId::new("turns", decoder), |b| {
+        b.iter(|| parse_session_to_turns(&path).expect("aggregate bench session").len())
+    });
+    group.finish();
+
+    let _ = std::fs::remove_file(&path);
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
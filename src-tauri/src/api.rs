@@ -0,0 +1,1077 @@
+//! Optional HTTP API server
+//!
+//! Exposes the analytics commands as REST endpoints with JSON responses so
+//! a running instance can be queried from other machines. The server is
+//! compiled in with the `http-api` cargo feature, which doesn't need the
+//! GUI: the desktop app starts it when `IRONHIDE_HTTP_ADDR` is set, and
+//! `ironhide-cli serve` runs it headless.
+//!
+//! Requests must carry `Authorization: Bearer <token>` with the token in
+//! `IRONHIDE_HTTP_TOKEN` whenever it's set. It has to be set to bind to
+//! anything but a loopback address: without it, the server refuses to
+//! start rather than serve session data to the network. A loopback server
+//! without a token only answers requests addressed to `localhost`,
+//! `127.0.0.1` or `[::1]`, so a web page can't reach it through DNS
+//! rebinding.
+//!
+//! Handlers delegate to the same functions registered with the Tauri invoke
+//! handler, with the `AppState` of whichever of the two runs the server.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+
+use crate::commands::{
+    self, ActiveSession, AntiPatternHistoryPoint, CostAnomalyResponse, CostCurvePoint, CostTrendPoint,
+    DailyMetricsResponse, DashboardSummaryResponse, DeveloperPerformanceResponse, DiagnosticsReport,
+    EfficiencyTrendPoint, FileHistory, GitHubConfigResponse, ProjectMetricsResponse, ProjectWarmupStats,
+    SessionComparison,
+    SessionDetail, SessionHealth, SessionMetricsResponse, SessionSummary, SubagentSummary, TurnSummary,
+};
+use crate::db::jobs::Job;
+use crate::diagnostics::BackendError;
+use crate::db::team::{TeamDailyTrend, TeamDashboard, TeamMember};
+use crate::export::template::SummaryTemplate;
+use crate::metrics::api_errors::ApiErrorStats;
+use crate::metrics::benchmark::ProjectBenchmarks;
+use crate::metrics::cache::CacheBreakdown;
+use crate::metrics::concurrency::ConcurrencyReport;
+use crate::metrics::efficiency_curve::SessionEfficiencyCurve;
+use crate::metrics::hourly::HourlyUsage;
+use crate::metrics::stats::SessionDistributions;
+use crate::metrics::billing_blocks::BillingBlocks;
+use crate::metrics::burn_rate::BurnRateReport;
+use crate::metrics::thinking::ThinkingTrend;
+use crate::metrics::usage_windows::UsageWindow;
+use crate::metrics::rework::{CodeChange, FileChangeSummary};
+use crate::metrics::subagents::SubagentNode;
+use crate::metrics::tool_failures::ToolFailure;
+use crate::metrics::tool_usage::ToolUsageStats;
+use crate::metrics::mcp::McpStats;
+use crate::metrics::savings::{SavingsScenario, SavingsSimulation};
+use crate::models::turn::TurnAnnotation;
+use crate::parser::RawEntryPage;
+use crate::parser::transcript::{Transcript, TranscriptOptions};
+use crate::patterns::DetectedPattern;
+use crate::recommendations::RecommendationSummary;
+use crate::trends::digest::DailyDigest;
+use crate::trends::forecast::CostForecast;
+use crate::trends::periods::{DateRange, DateRangeComparison};
+use crate::trends::report::WeeklyReport;
+use crate::trends::{Segment, TrendSeries};
+use crate::{AppState, CommandError};
+
+/// Environment variable holding the address the HTTP API binds to
+pub const HTTP_ADDR_ENV: &str = "IRONHIDE_HTTP_ADDR";
+
+/// Environment variable holding the bearer token requests must carry
+pub const HTTP_TOKEN_ENV: &str = "IRONHIDE_HTTP_TOKEN";
+
+/// Read the configured bind address, if the HTTP API is enabled
+pub fn configured_addr() -> Option<SocketAddr> {
+    let raw = std::env::var(HTTP_ADDR_ENV).ok()?;
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+
+    match raw.parse() {
+        Ok(addr) => Some(addr),
+        Err(e) => {
+            tracing::error!("Invalid {} value '{}': {}", HTTP_ADDR_ENV, raw, e);
+            None
+        }
+    }
+}
+
+/// Read the configured bearer token, if any
+pub fn configured_token() -> Option<String> {
+    std::env::var(HTTP_TOKEN_ENV)
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Where handlers get the `AppState` from
+#[derive(Clone)]
+pub enum ApiState {
+    /// The desktop app's managed state
+    #[cfg(feature = "gui")]
+    App(tauri::AppHandle),
+    /// State owned by the server, as in the CLI
+    Owned(Arc<AppState>),
+}
+
+impl ApiState {
+    fn state(&self) -> &AppState {
+        match self {
+            #[cfg(feature = "gui")]
+            Self::App(app) => {
+                use tauri::Manager;
+                app.state::<AppState>().inner()
+            }
+            Self::Owned(state) => state,
+        }
+    }
+}
+
+/// Bind to `addr` and serve the API until the process exits
+///
+/// Requires the token from `IRONHIDE_HTTP_TOKEN` when it's set, and refuses
+/// to serve a non-loopback address without one. Without a token, requests
+/// must be addressed to a loopback host.
+pub async fn serve(state: ApiState, addr: SocketAddr) {
+    let token = configured_token();
+    if token.is_none() && !addr.ip().is_loopback() {
+        tracing::error!(
+            "Not serving the HTTP API on {}: set {} to bind to a non-loopback address",
+            addr,
+            HTTP_TOKEN_ENV
+        );
+        return;
+    }
+
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("Failed to bind HTTP API to {}: {}", addr, e);
+            return;
+        }
+    };
+
+    tracing::info!("HTTP API listening on http://{}", addr);
+
+    if let Err(e) = axum::serve(listener, router(state, token)).await {
+        tracing::error!("HTTP API server error: {}", e);
+    }
+}
+
+/// Build the API router; with a `token`, every route requires it, and
+/// without one every route requires a loopback `Host`
+pub fn router(state: ApiState, token: Option<String>) -> Router {
+    let router = Router::new()
+        .route("/api/health", get(health))
+        .route("/api/diagnostics", get(diagnostics))
+        .route("/api/diagnostics/errors", get(backend_errors))
+        // Sessions
+        .route("/api/sessions", get(list_sessions))
+        .route("/api/sessions/count", get(session_count))
+        .route("/api/sessions/active", get(active_session))
+        .route("/api/sessions/refresh", post(refresh_sessions))
+        .route("/api/sessions/preload", post(preload_sessions))
+        .route("/api/sessions/preload/cancel", post(cancel_preload))
+        .route("/api/sessions/{id}", get(session_detail))
+        .route("/api/sessions/{id}/recompute", post(recompute_session))
+        .route("/api/sessions/{id}/summary", get(summarize_session))
+        .route("/api/summary-templates", get(summary_templates))
+        .route("/api/sessions/{id}/metrics", get(session_metrics))
+        .route("/api/sessions/{id}/turns", get(session_turns))
+        .route("/api/sessions/{id}/transcript", get(session_transcript))
+        .route("/api/sessions/{id}/raw", get(session_raw_entries))
+        .route("/api/sessions/{id}/cost-curve", get(session_cost_curve))
+        .route("/api/sessions/{id}/annotations", get(session_annotations))
+        .route("/api/sessions/{id}/subagents", get(session_subagents))
+        .route("/api/sessions/{id}/subagent-tree", get(subagent_tree))
+        .route("/api/sessions/{id}/code-changes", get(session_code_changes))
+        .route("/api/sessions/{id}/tool-failures", get(session_tool_failures))
+        .route("/api/sessions/{id}/cache-breakdown", get(session_cache_breakdown))
+        .route("/api/sessions/{id}/efficiency-curve", get(session_efficiency_curve))
+        .route("/api/sessions/{id}/health", get(session_health))
+        .route("/api/projects/sessions", get(project_sessions))
+        .route("/api/projects/warm", post(warm_project))
+        .route("/api/files/history", get(file_history))
+        .route("/api/projects/file-changes", get(project_file_changes))
+        .route("/api/compare", get(compare_sessions))
+        // Dashboard aggregates
+        .route("/api/dashboard/summary", get(dashboard_summary))
+        .route("/api/metrics/daily", get(daily_metrics))
+        .route("/api/metrics/projects", get(project_metrics))
+        .route("/api/metrics/projects/benchmarks", get(project_benchmarks))
+        .route("/api/metrics/hourly", get(hourly_usage))
+        .route("/api/metrics/usage-windows", get(usage_windows))
+        .route("/api/metrics/billing-blocks", get(billing_blocks))
+        .route("/api/metrics/tool-usage", get(tool_usage))
+        .route("/api/metrics/mcp", get(mcp_stats))
+        .route("/api/metrics/savings", post(simulate_savings))
+        .route("/api/metrics/concurrency", get(concurrent_sessions))
+        .route("/api/metrics/errors", get(error_stats))
+        .route("/api/metrics/thinking", get(thinking_trend))
+        .route("/api/metrics/burn-rate", get(burn_rate))
+        .route("/api/metrics/distributions", get(session_distributions))
+        // Trends
+        .route("/api/trends", get(trends))
+        .route("/api/trends/cost", get(cost_trend))
+        .route("/api/trends/efficiency", get(efficiency_trend))
+        .route("/api/trends/anomalies", get(cost_anomalies))
+        .route("/api/trends/forecast", get(cost_forecast))
+        .route("/api/trends/compare", get(compare_periods))
+        .route("/api/trends/digest", get(daily_digest))
+        .route("/api/trends/weekly-report", get(weekly_report))
+        // Insights
+        .route("/api/recommendations", get(recommendations))
+        .route("/api/antipatterns", get(antipatterns))
+        .route("/api/antipatterns/history", get(antipattern_history))
+        // Developer performance
+        .route("/api/github/config", get(github_config))
+        .route("/api/developer", get(developer_metrics))
+        // Team roll-up
+        .route("/api/team/members", get(team_members))
+        .route("/api/team/dashboard", get(team_dashboard))
+        .route("/api/team/trends", get(team_trends))
+        // Background jobs
+        .route("/api/jobs", get(list_jobs))
+        .route("/api/jobs/{id}/cancel", post(cancel_job))
+        .with_state(state);
+
+    match token {
+        Some(token) => router.layer(middleware::from_fn_with_state(Arc::<str>::from(token), require_token)),
+        None => router.layer(middleware::from_fn(require_loopback_host)),
+    }
+}
+
+/// Reject requests whose `Host` isn't a loopback name
+async fn require_loopback_host(request: Request, next: Next) -> Response {
+    if !has_loopback_host(request.headers()) {
+        let error = ApiError {
+            status: StatusCode::FORBIDDEN,
+            message: format!("Requests without a token must be sent to localhost; set {}", HTTP_TOKEN_ENV),
+        };
+        return error.into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether `headers` carry a `Host` of localhost, 127.0.0.1 or [::1], with
+/// any port
+fn has_loopback_host(headers: &HeaderMap) -> bool {
+    let Some(host) = headers.get(header::HOST).and_then(|value| value.to_str().ok()) else {
+        return false;
+    };
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().map(|ip| format!("[{}]", ip)),
+        None => host.split(':').next().map(str::to_string),
+    };
+    matches!(
+        name.map(|name| name.to_ascii_lowercase()).as_deref(),
+        Some("localhost" | "127.0.0.1" | "[::1]")
+    )
+}
+
+/// Reject requests without the bearer token
+async fn require_token(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    if !has_token(request.headers(), &token) {
+        let error = ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            message: "Missing or invalid bearer token".to_string(),
+        };
+        return ([(header::WWW_AUTHENTICATE, "Bearer")], error).into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether `headers` carry `Authorization: Bearer <token>`
+fn has_token(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|presented| crate::ipc::constant_time_eq(presented.trim().as_bytes(), token.as_bytes()))
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Error returned by API handlers, rendered as `{"error": "..."}`
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<CommandError> for ApiError {
+    fn from(err: CommandError) -> Self {
+        let status = match err {
+            CommandError::SessionNotFound(_) => StatusCode::NOT_FOUND,
+            CommandError::NotInitialized => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self {
+            status,
+            message: err.to_string(),
+        }
+    }
+}
+
+// Some commands (trends, anti-patterns) report errors as plain strings
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+// ============================================================================
+// Query Parameters
+// ============================================================================
+
+#[derive(Debug, Default, Deserialize)]
+struct SessionsQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PageQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DaysQuery {
+    days: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DailyMetricsQuery {
+    days: Option<u32>,
+    /// all (default), user_only or subagent_only
+    segment: Option<Segment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SavingsRequest {
+    scenarios: Vec<SavingsScenario>,
+    days: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryQuery {
+    /// Template name or template text
+    template: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectQuery {
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileQuery {
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompareQuery {
+    /// Comma-separated session IDs
+    ids: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct EfficiencyCurveQuery {
+    /// Turns per rolling window
+    window: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DateRangeQuery {
+    start_date: Option<String>,
+    end_date: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BenchmarksQuery {
+    days: Option<u32>,
+    compare_projects: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DistributionsQuery {
+    days: Option<u32>,
+    buckets: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PeriodsQuery {
+    a_start: String,
+    a_end: String,
+    b_start: String,
+    b_end: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DigestQuery {
+    /// Defaults to yesterday
+    date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WeeklyReportQuery {
+    /// Defaults to the Monday of the last full week
+    week_start: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TrendsQuery {
+    start_date: Option<String>,
+    end_date: Option<String>,
+    granularity: Option<String>,
+    backfill: Option<bool>,
+    /// Language or framework of the projects to count
+    stack: Option<String>,
+    segment: Option<Segment>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChartQuery {
+    days: Option<u32>,
+    backfill: Option<bool>,
+    stack: Option<String>,
+    segment: Option<Segment>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ProjectMetricsQuery {
+    days: Option<u32>,
+    stack: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ForecastQuery {
+    days_ahead: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AnomaliesQuery {
+    days: Option<u32>,
+    sigma: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RecommendationsQuery {
+    session_id: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AntipatternsQuery {
+    session_id: Option<String>,
+    /// Comma-separated pattern types
+    patterns: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct JobsQuery {
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeveloperQuery {
+    github_username: String,
+    sprint_days: Option<u32>,
+    num_sprints: Option<u32>,
+}
+
+/// Split a comma-separated query value, dropping empty items
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+async fn diagnostics(State(api): State<ApiState>) -> ApiResult<DiagnosticsReport> {
+    Ok(Json(commands::get_diagnostics(api.state().into()).await?))
+}
+
+async fn backend_errors(State(api): State<ApiState>) -> ApiResult<Vec<BackendError>> {
+    Ok(Json(commands::get_backend_errors(api.state().into()).await?))
+}
+
+async fn list_sessions(
+    State(api): State<ApiState>,
+    Query(q): Query<SessionsQuery>,
+) -> ApiResult<Vec<SessionSummary>> {
+    let sessions = if q.start_date.is_some() || q.end_date.is_some() {
+        commands::get_sessions_filtered(
+            api.state().into(),
+            q.start_date,
+            q.end_date,
+            q.limit,
+            q.offset,
+        )
+        .await?
+    } else {
+        commands::get_sessions(api.state().into(), q.limit, q.offset).await?
+    };
+    Ok(Json(sessions))
+}
+
+async fn session_count(State(api): State<ApiState>) -> ApiResult<u32> {
+    Ok(Json(commands::get_session_count(api.state().into()).await?))
+}
+
+async fn active_session(State(api): State<ApiState>) -> ApiResult<Option<ActiveSession>> {
+    Ok(Json(
+        commands::get_active_session(api.state().into()).await?,
+    ))
+}
+
+async fn refresh_sessions() -> Result<StatusCode, ApiError> {
+    commands::refresh_sessions().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn preload_sessions(State(api): State<ApiState>) -> ApiResult<u32> {
+    Ok(Json(commands::preload_all_sessions(api.state().into()).await?))
+}
+
+async fn cancel_preload(State(api): State<ApiState>) -> ApiResult<bool> {
+    Ok(Json(commands::cancel_preload(api.state().into()).await?))
+}
+
+async fn session_detail(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<SessionDetail> {
+    commands::get_session(api.state().into(), id.clone())
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Session not found: {}", id)))
+}
+
+async fn recompute_session(State(api): State<ApiState>, Path(id): Path<String>) -> ApiResult<SessionDetail> {
+    Ok(Json(commands::recompute_session(api.state().into(), id).await?))
+}
+
+async fn summarize_session(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+    Query(q): Query<SummaryQuery>,
+) -> ApiResult<String> {
+    Ok(Json(commands::summarize_session(api.state().into(), id, q.template).await?))
+}
+
+async fn summary_templates() -> ApiResult<Vec<SummaryTemplate>> {
+    Ok(Json(commands::get_summary_templates().await?))
+}
+
+async fn session_metrics(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<SessionMetricsResponse> {
+    commands::get_session_metrics(api.state().into(), id.clone())
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Session not found: {}", id)))
+}
+
+async fn session_turns(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+    Query(q): Query<PageQuery>,
+) -> ApiResult<Vec<TurnSummary>> {
+    Ok(Json(
+        commands::get_turns(api.state().into(), id, q.limit, q.offset).await?,
+    ))
+}
+
+async fn session_raw_entries(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+    Query(q): Query<PageQuery>,
+) -> ApiResult<RawEntryPage> {
+    Ok(Json(
+        commands::get_session_raw_entries(api.state().into(), id, q.offset, q.limit).await?,
+    ))
+}
+
+async fn session_transcript(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+    Query(options): Query<TranscriptOptions>,
+) -> ApiResult<Transcript> {
+    Ok(Json(
+        commands::get_session_transcript(api.state().into(), id, Some(options)).await?,
+    ))
+}
+
+async fn session_cost_curve(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<CostCurvePoint>> {
+    Ok(Json(
+        commands::get_session_cost_curve(api.state().into(), id).await?,
+    ))
+}
+
+async fn session_annotations(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<TurnAnnotation>> {
+    Ok(Json(
+        commands::get_turn_annotations(api.state().into(), Some(id), None).await?,
+    ))
+}
+
+async fn session_subagents(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<SubagentSummary>> {
+    Ok(Json(
+        commands::get_session_subagents(api.state().into(), id).await?,
+    ))
+}
+
+async fn subagent_tree(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<SubagentNode> {
+    Ok(Json(
+        commands::get_subagent_tree(api.state().into(), id).await?,
+    ))
+}
+
+async fn session_code_changes(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<CodeChange>> {
+    Ok(Json(
+        commands::get_session_code_changes(api.state().into(), id).await?,
+    ))
+}
+
+async fn session_tool_failures(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<ToolFailure>> {
+    Ok(Json(
+        commands::get_tool_failures(api.state().into(), id).await?,
+    ))
+}
+
+async fn session_cache_breakdown(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<CacheBreakdown> {
+    Ok(Json(
+        commands::get_cache_breakdown(api.state().into(), id).await?,
+    ))
+}
+
+async fn session_efficiency_curve(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+    Query(q): Query<EfficiencyCurveQuery>,
+) -> ApiResult<SessionEfficiencyCurve> {
+    Ok(Json(
+        commands::get_session_efficiency_curve(api.state().into(), id, q.window).await?,
+    ))
+}
+
+async fn session_health(
+    State(api): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<SessionHealth> {
+    Ok(Json(
+        commands::get_session_health(api.state().into(), id).await?,
+    ))
+}
+
+async fn project_sessions(
+    State(api): State<ApiState>,
+    Query(q): Query<ProjectQuery>,
+) -> ApiResult<Vec<SessionSummary>> {
+    Ok(Json(
+        commands::get_sessions_by_project(api.state().into(), q.path).await?,
+    ))
+}
+
+async fn warm_project(
+    State(api): State<ApiState>,
+    Query(q): Query<ProjectQuery>,
+) -> ApiResult<ProjectWarmupStats> {
+    Ok(Json(
+        commands::warm_project_cache(api.state().into(), q.path).await?,
+    ))
+}
+
+async fn project_file_changes(
+    State(api): State<ApiState>,
+    Query(q): Query<ProjectQuery>,
+) -> ApiResult<Vec<FileChangeSummary>> {
+    Ok(Json(
+        commands::get_project_file_changes(api.state().into(), q.path).await?,
+    ))
+}
+
+async fn file_history(State(api): State<ApiState>, Query(q): Query<FileQuery>) -> ApiResult<FileHistory> {
+    Ok(Json(commands::get_file_history(api.state().into(), q.path).await?))
+}
+
+async fn compare_sessions(
+    State(api): State<ApiState>,
+    Query(q): Query<CompareQuery>,
+) -> ApiResult<SessionComparison> {
+    Ok(Json(
+        commands::compare_sessions(api.state().into(), split_list(&q.ids)).await?,
+    ))
+}
+
+async fn dashboard_summary(
+    State(api): State<ApiState>,
+    Query(q): Query<DaysQuery>,
+) -> ApiResult<DashboardSummaryResponse> {
+    Ok(Json(
+        commands::get_dashboard_summary(api.state().into(), q.days).await?,
+    ))
+}
+
+async fn daily_metrics(
+    State(api): State<ApiState>,
+    Query(q): Query<DailyMetricsQuery>,
+) -> ApiResult<Vec<DailyMetricsResponse>> {
+    Ok(Json(
+        commands::get_daily_metrics(api.state().into(), q.days, q.segment).await?,
+    ))
+}
+
+async fn project_metrics(
+    State(api): State<ApiState>,
+    Query(q): Query<ProjectMetricsQuery>,
+) -> ApiResult<Vec<ProjectMetricsResponse>> {
+    Ok(Json(
+        commands::get_project_metrics(api.state().into(), q.days, q.stack).await?,
+    ))
+}
+
+async fn project_benchmarks(
+    State(api): State<ApiState>,
+    Query(q): Query<BenchmarksQuery>,
+) -> ApiResult<ProjectBenchmarks> {
+    Ok(Json(
+        commands::get_project_benchmarks(api.state().into(), q.days, q.compare_projects)
+            .await?,
+    ))
+}
+
+async fn hourly_usage(
+    State(api): State<ApiState>,
+    Query(q): Query<DateRangeQuery>,
+) -> ApiResult<HourlyUsage> {
+    Ok(Json(
+        commands::get_hourly_usage(api.state().into(), q.start_date, q.end_date).await?,
+    ))
+}
+
+async fn usage_windows(State(api): State<ApiState>) -> ApiResult<Vec<UsageWindow>> {
+    Ok(Json(commands::get_usage_windows(api.state().into()).await?))
+}
+
+async fn billing_blocks(State(api): State<ApiState>, Query(q): Query<DaysQuery>) -> ApiResult<BillingBlocks> {
+    Ok(Json(commands::get_billing_blocks(api.state().into(), q.days).await?))
+}
+
+async fn tool_usage(State(api): State<ApiState>, Query(q): Query<DaysQuery>) -> ApiResult<ToolUsageStats> {
+    Ok(Json(commands::get_tool_usage(api.state().into(), q.days).await?))
+}
+
+async fn mcp_stats(State(api): State<ApiState>, Query(q): Query<DaysQuery>) -> ApiResult<McpStats> {
+    Ok(Json(commands::get_mcp_stats(api.state().into(), q.days).await?))
+}
+
+async fn simulate_savings(
+    State(api): State<ApiState>,
+    Json(body): Json<SavingsRequest>,
+) -> ApiResult<SavingsSimulation> {
+    Ok(Json(
+        commands::simulate_savings(api.state().into(), body.scenarios, body.days).await?,
+    ))
+}
+
+async fn concurrent_sessions(
+    State(api): State<ApiState>,
+    Query(q): Query<DaysQuery>,
+) -> ApiResult<ConcurrencyReport> {
+    Ok(Json(
+        commands::get_concurrent_sessions(api.state().into(), q.days).await?,
+    ))
+}
+
+async fn session_distributions(
+    State(api): State<ApiState>,
+    Query(q): Query<DistributionsQuery>,
+) -> ApiResult<SessionDistributions> {
+    Ok(Json(
+        commands::get_session_distributions(api.state().into(), q.days, q.buckets).await?,
+    ))
+}
+
+async fn error_stats(State(api): State<ApiState>, Query(q): Query<DaysQuery>) -> ApiResult<ApiErrorStats> {
+    Ok(Json(commands::get_error_stats(api.state().into(), q.days).await?))
+}
+
+async fn thinking_trend(State(api): State<ApiState>, Query(q): Query<DaysQuery>) -> ApiResult<ThinkingTrend> {
+    Ok(Json(commands::get_thinking_trend(api.state().into(), q.days).await?))
+}
+
+async fn burn_rate(State(api): State<ApiState>, Query(q): Query<DaysQuery>) -> ApiResult<BurnRateReport> {
+    Ok(Json(commands::get_burn_rate(api.state().into(), q.days).await?))
+}
+
+async fn trends(
+    State(api): State<ApiState>,
+    Query(q): Query<TrendsQuery>,
+) -> ApiResult<TrendSeries> {
+    Ok(Json(
+        commands::get_trends(
+            api.state().into(),
+            q.start_date,
+            q.end_date,
+            q.granularity,
+            q.backfill,
+            q.stack,
+            q.segment,
+        )
+        .await?,
+    ))
+}
+
+async fn cost_trend(
+    State(api): State<ApiState>,
+    Query(q): Query<ChartQuery>,
+) -> ApiResult<Vec<CostTrendPoint>> {
+    Ok(Json(
+        commands::get_cost_trend(api.state().into(), q.days, q.backfill, q.stack, q.segment).await?,
+    ))
+}
+
+async fn cost_anomalies(
+    State(api): State<ApiState>,
+    Query(q): Query<AnomaliesQuery>,
+) -> ApiResult<Vec<CostAnomalyResponse>> {
+    Ok(Json(
+        commands::get_cost_anomalies(api.state().into(), q.days, q.sigma).await?,
+    ))
+}
+
+async fn cost_forecast(
+    State(api): State<ApiState>,
+    Query(q): Query<ForecastQuery>,
+) -> ApiResult<CostForecast> {
+    Ok(Json(
+        commands::get_cost_forecast(api.state().into(), q.days_ahead).await?,
+    ))
+}
+
+async fn compare_periods(
+    State(api): State<ApiState>,
+    Query(q): Query<PeriodsQuery>,
+) -> ApiResult<DateRangeComparison> {
+    let range = |start_date, end_date| DateRange { start_date, end_date };
+    Ok(Json(
+        commands::compare_periods(
+            api.state().into(),
+            range(q.a_start, q.a_end),
+            range(q.b_start, q.b_end),
+        )
+        .await?,
+    ))
+}
+
+async fn daily_digest(State(api): State<ApiState>, Query(q): Query<DigestQuery>) -> ApiResult<DailyDigest> {
+    Ok(Json(
+        commands::generate_daily_digest(api.state().into(), q.date, None).await?,
+    ))
+}
+
+async fn weekly_report(
+    State(api): State<ApiState>,
+    Query(q): Query<WeeklyReportQuery>,
+) -> ApiResult<WeeklyReport> {
+    Ok(Json(
+        commands::generate_weekly_report(api.state().into(), q.week_start).await?,
+    ))
+}
+
+async fn efficiency_trend(
+    State(api): State<ApiState>,
+    Query(q): Query<ChartQuery>,
+) -> ApiResult<Vec<EfficiencyTrendPoint>> {
+    Ok(Json(
+        commands::get_efficiency_trend(api.state().into(), q.days, q.backfill, q.stack, q.segment).await?,
+    ))
+}
+
+async fn recommendations(
+    State(api): State<ApiState>,
+    Query(q): Query<RecommendationsQuery>,
+) -> ApiResult<RecommendationSummary> {
+    Ok(Json(
+        commands::get_recommendations(api.state().into(), q.session_id, q.limit).await?,
+    ))
+}
+
+async fn antipatterns(Query(q): Query<AntipatternsQuery>) -> ApiResult<Vec<DetectedPattern>> {
+    let patterns = q.patterns.as_deref().map(split_list);
+    Ok(Json(commands::detect_antipatterns(q.session_id, patterns).await?))
+}
+
+async fn antipattern_history(
+    State(api): State<ApiState>,
+    Query(q): Query<DaysQuery>,
+) -> ApiResult<Vec<AntiPatternHistoryPoint>> {
+    Ok(Json(
+        commands::get_antipattern_history(api.state().into(), q.days).await?,
+    ))
+}
+
+async fn github_config() -> ApiResult<GitHubConfigResponse> {
+    Ok(Json(commands::detect_github_config().await?))
+}
+
+async fn developer_metrics(
+    State(api): State<ApiState>,
+    Query(q): Query<DeveloperQuery>,
+) -> ApiResult<DeveloperPerformanceResponse> {
+    Ok(Json(
+        commands::get_developer_metrics(
+            api.state().into(),
+            q.github_username,
+            q.sprint_days,
+            q.num_sprints,
+        )
+        .await?,
+    ))
+}
+
+async fn team_members(State(api): State<ApiState>) -> ApiResult<Vec<TeamMember>> {
+    Ok(Json(commands::get_team_members(api.state().into()).await?))
+}
+
+async fn team_dashboard(
+    State(api): State<ApiState>,
+    Query(q): Query<DaysQuery>,
+) -> ApiResult<TeamDashboard> {
+    Ok(Json(
+        commands::get_team_dashboard(api.state().into(), q.days).await?,
+    ))
+}
+
+async fn team_trends(
+    State(api): State<ApiState>,
+    Query(q): Query<DaysQuery>,
+) -> ApiResult<Vec<TeamDailyTrend>> {
+    Ok(Json(
+        commands::get_team_trends(api.state().into(), q.days).await?,
+    ))
+}
+
+async fn list_jobs(State(api): State<ApiState>, Query(q): Query<JobsQuery>) -> ApiResult<Vec<Job>> {
+    Ok(Json(commands::list_jobs(api.state().into(), q.limit).await?))
+}
+
+async fn cancel_job(State(api): State<ApiState>, Path(id): Path<i64>) -> ApiResult<bool> {
+    Ok(Json(commands::cancel_job(api.state().into(), id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_list() {
+        assert_eq!(split_list("a,b,c"), vec!["a", "b", "c"]);
+        assert_eq!(split_list(" a , ,b,"), vec!["a", "b"]);
+        assert!(split_list("").is_empty());
+    }
+
+    #[test]
+    fn test_has_token() {
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::AUTHORIZATION, value.parse().unwrap());
+            headers
+        };
+        assert!(has_token(&headers("Bearer s3cret"), "s3cret"));
+        assert!(!has_token(&headers("Bearer s3cre"), "s3cret"));
+        assert!(!has_token(&headers("Basic s3cret"), "s3cret"));
+        assert!(!has_token(&headers("s3cret"), "s3cret"));
+        assert!(!has_token(&HeaderMap::new(), "s3cret"));
+    }
+
+    #[test]
+    fn test_has_loopback_host() {
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::HOST, value.parse().unwrap());
+            headers
+        };
+        assert!(has_loopback_host(&headers("localhost:8787")));
+        assert!(has_loopback_host(&headers("LocalHost")));
+        assert!(has_loopback_host(&headers("127.0.0.1:8787")));
+        assert!(has_loopback_host(&headers("[::1]:8787")));
+        assert!(!has_loopback_host(&headers("attacker.example:8787")));
+        assert!(!has_loopback_host(&headers("localhost.attacker.example")));
+        assert!(!has_loopback_host(&headers("[::2]")));
+        assert!(!has_loopback_host(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_api_error_status_mapping() {
+        let err = ApiError::from(CommandError::SessionNotFound("abc".to_string()));
+        assert_eq!(err.status, StatusCode::NOT_FOUND);
+
+        let err = ApiError::from(CommandError::NotInitialized);
+        assert_eq!(err.status, StatusCode::SERVICE_UNAVAILABLE);
+
+        let err = ApiError::from(CommandError::Internal("boom".to_string()));
+        assert_eq!(err.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.message, "Internal error: boom");
+
+        let err = ApiError::from("bad trend".to_string());
+        assert_eq!(err.status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
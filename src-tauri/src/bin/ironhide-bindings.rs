@@ -0,0 +1,30 @@
+//! Writes the TypeScript definitions of command responses and event payloads
+//!
+//! ```text
+//! cargo run --no-default-features --features bindings --bin ironhide-bindings [-- OUTPUT]
+//! ```
+//!
+//! OUTPUT defaults to `src/types/bindings.ts` of the frontend.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use ironhide_lib::bindings;
+
+fn main() -> ExitCode {
+    let output = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(bindings::OUTPUT_PATH));
+
+    match std::fs::write(&output, bindings::typescript()) {
+        Ok(()) => {
+            println!("Wrote {}", output.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error writing {}: {}", output.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Ironhide command-line interface
+//!
+//! Runs the desktop app's analytics commands headless, against the same
+//! session files and database, for use in CI or on servers without a GUI.
+//! Build without the GUI with `cargo build --bin ironhide-cli
+//! --no-default-features`.
+//!
+//! ```text
+//! ironhide-cli sessions list [--limit N] [--offset N] [--format table|json]
+//! ironhide-cli trends [--days N] [--granularity daily|weekly|monthly] [--stack NAME]
+//!                     [--segment all|user_only|subagent_only] [--format table|json]
+//! ironhide-cli export [--format csv|json|markdown|ical] [--sessions ID,...] [--days N] [--include-turns]
+//! ironhide-cli replay <SESSION_ID> [--output PATH]
+//! ironhide-cli serve [--addr HOST:PORT]
+//! ```
+//!
+//! `serve` runs the HTTP API (see `ironhide_lib::api`) and needs the
+//! `http-api` feature. It keeps the database up to date with the session
+//! files while it runs, as the desktop app does.
+
+use std::collections::HashMap;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use ironhide_lib::commands::{self, ExportJobState, SessionSummary};
+use ironhide_lib::export::ExportOptions;
+use ironhide_lib::trends::daily::days_ago;
+use ironhide_lib::trends::{Segment, TrendSeries};
+use ironhide_lib::{db, init_app_state, AppState, State};
+
+const USAGE: &str = "\
+Usage: ironhide-cli <command> [options]
+
+Commands:
+  sessions list   List sessions, most recent first
+                    --limit N (default 100), --offset N, --format table|json
+  trends          Cost and usage trends
+                    --days N (default 30), --granularity daily|weekly|monthly,
+                    --stack LANGUAGE|FRAMEWORK, --segment all|user_only|subagent_only,
+                    --format table|json
+  export          Export sessions to a file and print its path
+                    --format csv|json|markdown|ical (default csv), --sessions ID,...,
+                    --days N, --include-turns
+  replay ID       Write a session's HTML replay page and print its path
+                    --output PATH (default: timestamped file in Downloads)
+  serve           Serve the analytics over HTTP until stopped (http-api builds)
+                    --addr HOST:PORT (default: $IRONHIDE_HTTP_ADDR or 127.0.0.1:8787);
+                    other than loopback addresses need a $IRONHIDE_HTTP_TOKEN
+  help            Show this message
+
+Options:
+  --db PATH       Analytics database to use (default: $IRONHIDE_DB_PATH or the
+                  desktop app's)";
+
+/// Positional arguments and `--name [value]` options
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = match name.split_once('=') {
+                        Some((name, value)) => {
+                            parsed.options.insert(name.to_string(), Some(value.to_string()));
+                            continue;
+                        }
+                        None => args.next_if(|next| !next.starts_with("--")),
+                    };
+                    parsed.options.insert(name.to_string(), value);
+                }
+                None => parsed.positional.push(arg),
+            }
+        }
+        parsed
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options.get(name).and_then(|v| v.as_deref())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    fn number(&self, name: &str) -> Result<Option<i64>, String> {
+        self.value(name)
+            .map(|v| v.parse().map_err(|_| format!("--{} expects a number, got '{}'", name, v)))
+            .transpose()
+    }
+
+    fn json_output(&self) -> Result<bool, String> {
+        match self.value("format").unwrap_or("table") {
+            "table" => Ok(false),
+            "json" => Ok(true),
+            other => Err(format!("Unknown output format '{}'", other)),
+        }
+    }
+
+    fn segment(&self) -> Result<Option<Segment>, String> {
+        match self.value("segment") {
+            None | Some("all") => Ok(None),
+            Some("user_only") => Ok(Some(Segment::UserOnly)),
+            Some("subagent_only") => Ok(Some(Segment::SubagentOnly)),
+            Some(other) => Err(format!("Unknown segment '{}'", other)),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse(std::env::args().skip(1));
+    let command: Vec<&str> = args.positional.iter().map(String::as_str).collect();
+    if matches!(command.as_slice(), [] | ["help"]) || args.flag("help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    // Logs go to stderr so stdout stays machine-readable
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let db_path = args
+        .value("db")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(db::configured_db_path);
+    let state = Arc::new(init_app_state(&db_path));
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let result = runtime.block_on(async {
+        match command.as_slice() {
+            ["sessions", "list"] => list_sessions(&state, &args).await,
+            ["trends"] => trends(&state, &args).await,
+            ["export"] => export(&state, &args).await,
+            ["replay", session_id] => replay(&state, session_id, &args).await,
+            #[cfg(feature = "http-api")]
+            ["serve"] => serve(state.clone(), &args).await,
+            #[cfg(not(feature = "http-api"))]
+            ["serve"] => Err("This build has no HTTP API; rebuild with --features http-api".to_string()),
+            _ => Err(format!("Unknown command '{}'\n\n{}", command.join(" "), USAGE)),
+        }
+    });
+
+    match result {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn list_sessions(state: &AppState, args: &Args) -> Result<String, String> {
+    let json = args.json_output()?;
+    let sessions = commands::get_sessions(State::from(state), args.number("limit")?, args.number("offset")?)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if json {
+        return serde_json::to_string_pretty(&sessions).map_err(|e| e.to_string());
+    }
+    let rows = sessions.iter().map(session_row).collect();
+    Ok(table(&["ID", "PROJECT", "STARTED", "TURNS", "TOKENS", "COST"], rows))
+}
+
+fn session_row(s: &SessionSummary) -> Vec<String> {
+    vec![
+        s.id.clone(),
+        s.project_name.clone(),
+        s.started_at.chars().take(16).collect::<String>().replace('T', " "),
+        s.total_turns.to_string(),
+        s.total_tokens.to_string(),
+        format!("${:.2}", s.total_cost_including_subagents),
+    ]
+}
+
+async fn trends(state: &AppState, args: &Args) -> Result<String, String> {
+    let json = args.json_output()?;
+    let days = args.number("days")?.unwrap_or(30).max(1);
+    let series = commands::get_trends(
+        State::from(state),
+        Some(days_ago(days)),
+        Some(days_ago(0)),
+        args.value("granularity").map(String::from),
+        None,
+        args.value("stack").map(String::from),
+        args.segment()?,
+    )
+    .await?;
+
+    if json {
+        return serde_json::to_string_pretty(&series).map_err(|e| e.to_string());
+    }
+    let header = ["PERIOD", "SESSIONS", "TURNS", "TOKENS", "COST"];
+    let row = |period: &str, sessions: u32, turns: u32, tokens: u64, cost: f64| {
+        vec![
+            period.to_string(),
+            sessions.to_string(),
+            turns.to_string(),
+            tokens.to_string(),
+            format!("${:.2}", cost),
+        ]
+    };
+    let rows = match &series {
+        TrendSeries::Daily(days) => days
+            .iter()
+            .map(|d| row(&d.date, d.sessions, d.turns, d.total_tokens, d.total_cost))
+            .collect(),
+        TrendSeries::Weekly(weeks) => weeks
+            .iter()
+            .map(|w| row(&w.week_start, w.sessions, w.turns, w.total_tokens, w.total_cost))
+            .collect(),
+        TrendSeries::Monthly(months) => months
+            .iter()
+            .map(|m| row(&m.month, m.sessions, m.turns, m.total_tokens, m.total_cost))
+            .collect(),
+    };
+    Ok(table(&header, rows))
+}
+
+async fn export(state: &AppState, args: &Args) -> Result<String, String> {
+    let session_ids = args
+        .value("sessions")
+        .map(|ids| ids.split(',').map(|id| id.trim().to_string()).filter(|id| !id.is_empty()).collect());
+    let date_range = args
+        .number("days")?
+        .map(|days| (days_ago(days.max(1)), format!("{}T23:59:59Z", days_ago(0))));
+    let options = ExportOptions {
+        format: args.value("format").unwrap_or("csv").to_string(),
+        include_turns: args.flag("include-turns"),
+        include_metrics: true,
+        date_range,
+    };
+
+    let job_id = commands::export_sessions(State::from(state), session_ids, options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Wait for the background export job
+    loop {
+        let status = commands::get_export_status(State::from(state), job_id.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        match status.state {
+            ExportJobState::Running => tokio::time::sleep(std::time::Duration::from_millis(100)).await,
+            ExportJobState::Failed => return Err(status.error.unwrap_or_default()),
+            ExportJobState::Completed | ExportJobState::Cancelled => return Ok(status.path.unwrap_or_default()),
+        }
+    }
+}
+
+async fn replay(state: &AppState, session_id: &str, args: &Args) -> Result<String, String> {
+    commands::export_session_replay(
+        State::from(state),
+        session_id.to_string(),
+        args.value("output").map(String::from),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Serve the HTTP API; only returns if the server stops
+///
+/// Starts up like the desktop app first: resumes interrupted jobs, preloads
+/// the sessions and keeps the database in sync with the session files.
+#[cfg(feature = "http-api")]
+async fn serve(state: Arc<AppState>, args: &Args) -> Result<String, String> {
+    use ironhide_lib::api;
+
+    let addr = match args.value("addr") {
+        Some(addr) => addr.parse().map_err(|e| format!("Invalid --addr '{}': {}", addr, e))?,
+        None => api::configured_addr().unwrap_or_else(|| ([127, 0, 0, 1], 8787).into()),
+    };
+
+    commands::bootstrap_on_first_launch(&state);
+    commands::resume_jobs(&state);
+    eprintln!("Preloading sessions");
+    let count = commands::preload_all_sessions(State::from(&*state))
+        .await
+        .map_err(|e| e.to_string())?;
+    eprintln!("Loaded {} sessions", count);
+    {
+        let state = state.clone();
+        std::thread::spawn(move || ironhide_lib::headless_sync_task(state));
+    }
+
+    eprintln!("Serving the HTTP API on http://{}", addr);
+    api::serve(api::ApiState::Owned(state), addr).await;
+    Err(format!("The HTTP API on {} stopped", addr))
+}
+
+/// Left-aligned text table with a header row
+fn table(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![format_row(header.to_vec())];
+    lines.extend(rows.iter().map(|row| format_row(row.iter().map(String::as_str).collect())));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = Args::parse(
+            ["trends", "--days", "7", "--format=json", "--include-turns", "--granularity", "weekly"]
+                .map(String::from),
+        );
+        assert_eq!(args.positional, vec!["trends"]);
+        assert_eq!(args.number("days"), Ok(Some(7)));
+        assert_eq!(args.json_output(), Ok(true));
+        assert!(args.flag("include-turns"));
+        assert_eq!(args.value("include-turns"), None);
+        assert_eq!(args.value("granularity"), Some("weekly"));
+        assert!(Args::parse(["--days".to_string(), "x".to_string()]).number("days").is_err());
+
+        let output = table(&["A", "LONG"], vec![vec!["xyz".to_string(), "1".to_string()]]);
+        assert_eq!(output, "A    LONG\nxyz  1");
+    }
+}
@@ -0,0 +1,418 @@
+//! TypeScript definitions of the frontend contract
+//!
+//! The response type of every command and the payload of every event are
+//! listed here once. Their JSON schemas, derived with the `bindings` feature,
+//! are turned into TypeScript interfaces and aliases, plus a `Commands` map
+//! from command name to response type and an `Events` map from event name to
+//! payload type. `ironhide-bindings` writes the result to
+//! `src/types/bindings.ts`; regenerate it whenever a listed type changes:
+//!
+//! ```text
+//! cargo run --no-default-features --features bindings --bin ironhide-bindings
+//! ```
+
+use std::collections::BTreeSet;
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+
+use crate::commands::*;
+use crate::db::jobs::Job;
+use crate::diagnostics::BackendError;
+use crate::db::retention::{PruneStats, VacuumStats};
+use crate::db::snapshot::{BootstrapStats, SnapshotImportStats, SnapshotSyncReport};
+use crate::db::team::{TeamDailyTrend, TeamDashboard, TeamImportStats, TeamMember};
+use crate::export::template::SummaryTemplate;
+use crate::git::SessionGitContext;
+use crate::metrics::api_errors::ApiErrorStats;
+use crate::metrics::benchmark::ProjectBenchmarks;
+use crate::metrics::cache::CacheBreakdown;
+use crate::metrics::concurrency::ConcurrencyReport;
+use crate::metrics::context::ContextTimeline;
+use crate::metrics::efficiency_curve::SessionEfficiencyCurve;
+use crate::metrics::hourly::HourlyUsage;
+use crate::metrics::rework::{CodeChange, FileChangeSummary, ReworkAnalysis};
+use crate::metrics::stats::SessionDistributions;
+use crate::metrics::subagents::SubagentNode;
+use crate::metrics::billing_blocks::BillingBlocks;
+use crate::metrics::burn_rate::BurnRateReport;
+use crate::metrics::thinking::ThinkingTrend;
+use crate::metrics::tool_failures::ToolFailure;
+use crate::metrics::tool_usage::ToolUsageStats;
+use crate::metrics::mcp::McpStats;
+use crate::metrics::savings::SavingsSimulation;
+use crate::metrics::usage_windows::{UsageWindow, UsageWindowWarning};
+use crate::models::turn::TurnAnnotation;
+use crate::parser::registry::ProjectResolution;
+use crate::parser::transcript::Transcript;
+use crate::parser::{ParseDiagnostics, RawEntryPage};
+use crate::patterns::DetectedPattern;
+use crate::recommendations::types::{RecommendationFeedback, RecommendationSummary};
+use crate::settings::{AppSettings, CacheConfig};
+use crate::trends::digest::DailyDigest;
+use crate::trends::forecast::CostForecast;
+use crate::trends::periods::DateRangeComparison;
+use crate::trends::report::WeeklyReport;
+use crate::trends::TrendSeries;
+use crate::updates::{UpdateChannel, UpdateInfo};
+use crate::watcher::live::{ActiveSessionCost, TurnAppended};
+
+/// Where `ironhide-bindings` writes the definitions, from the crate root
+pub const OUTPUT_PATH: &str = "../src/types/bindings.ts";
+
+/// Response type of a command or payload type of an event, as TypeScript
+struct Entry {
+    name: &'static str,
+    ty: String,
+}
+
+/// Collects schemas and the `Commands` and `Events` maps
+struct Contract {
+    generator: SchemaGenerator,
+    commands: Vec<Entry>,
+    events: Vec<Entry>,
+}
+
+impl Contract {
+    fn command<T: schemars::JsonSchema>(&mut self, name: &'static str) {
+        let ty = ts_type(&self.generator.subschema_for::<T>());
+        self.commands.push(Entry { name, ty });
+    }
+
+    fn event<T: schemars::JsonSchema>(&mut self, name: &'static str) {
+        let ty = ts_type(&self.generator.subschema_for::<T>());
+        self.events.push(Entry { name, ty });
+    }
+}
+
+/// The commands registered with Tauri and the events emitted to the frontend
+fn contract() -> Contract {
+    let settings = SchemaSettings::draft07().with(|s| s.option_nullable = false);
+    let mut c = Contract {
+        generator: settings.into_generator(),
+        commands: Vec::new(),
+        events: Vec::new(),
+    };
+
+    // Sessions
+    c.command::<Vec<SessionSummary>>("get_sessions");
+    c.command::<Option<SessionDetail>>("get_session");
+    c.command::<Option<SessionMetricsResponse>>("get_session_metrics");
+    c.command::<u32>("get_session_count");
+    c.command::<Vec<SessionSummary>>("get_sessions_filtered");
+    c.command::<Vec<SessionSummary>>("get_sessions_by_project");
+    c.command::<u32>("preload_all_sessions");
+    c.command::<bool>("cancel_preload");
+    c.command::<ProjectWarmupStats>("warm_project_cache");
+    c.command::<u32>("backfill_database");
+    c.command::<bool>("cancel_backfill");
+    c.command::<Vec<Job>>("list_jobs");
+    c.command::<bool>("cancel_job");
+    c.command::<Vec<TurnSummary>>("get_turns");
+    c.command::<Vec<CostCurvePoint>>("get_session_cost_curve");
+    c.command::<TurnAnnotation>("annotate_turn");
+    c.command::<bool>("remove_turn_annotation");
+    c.command::<Vec<TurnAnnotation>>("get_turn_annotations");
+    c.command::<ParseDiagnostics>("get_parse_diagnostics");
+    c.command::<RawEntryPage>("get_session_raw_entries");
+    c.command::<ContextTimeline>("get_context_timeline");
+    c.command::<Transcript>("get_session_transcript");
+    c.command::<Vec<SubagentSummary>>("get_session_subagents");
+    c.command::<SubagentNode>("get_subagent_tree");
+    c.command::<SessionComparison>("compare_sessions");
+    c.command::<Vec<CodeChange>>("get_session_code_changes");
+    c.command::<FileHistory>("get_file_history");
+    c.command::<Vec<FileChangeSummary>>("get_project_file_changes");
+    c.command::<ReworkAnalysis>("get_session_rework");
+    c.command::<Vec<ToolFailure>>("get_tool_failures");
+    c.command::<CacheBreakdown>("get_cache_breakdown");
+    c.command::<SessionEfficiencyCurve>("get_session_efficiency_curve");
+    c.command::<SessionHealth>("get_session_health");
+    c.command::<Option<ActiveSession>>("get_active_session");
+    c.command::<()>("refresh_sessions");
+    c.command::<SessionDetail>("recompute_session");
+    c.command::<String>("summarize_session");
+    c.command::<Vec<SummaryTemplate>>("get_summary_templates");
+    c.command::<Vec<SessionSummary>>("scan_new_sessions");
+    c.command::<SessionGitContext>("get_session_git_context");
+
+    // Metrics and trends
+    c.command::<DashboardSummaryResponse>("get_dashboard_summary");
+    c.command::<Vec<DailyMetricsResponse>>("get_daily_metrics");
+    c.command::<Vec<ProjectMetricsResponse>>("get_project_metrics");
+    c.command::<ProjectBenchmarks>("get_project_benchmarks");
+    c.command::<ProjectTimeline>("get_project_timeline");
+    c.command::<HourlyUsage>("get_hourly_usage");
+    c.command::<Vec<UsageWindow>>("get_usage_windows");
+    c.command::<BillingBlocks>("get_billing_blocks");
+    c.command::<ToolUsageStats>("get_tool_usage");
+    c.command::<SavingsSimulation>("simulate_savings");
+    c.command::<McpStats>("get_mcp_stats");
+    c.command::<ConcurrencyReport>("get_concurrent_sessions");
+    c.command::<ApiErrorStats>("get_error_stats");
+    c.command::<ThinkingTrend>("get_thinking_trend");
+    c.command::<BurnRateReport>("get_burn_rate");
+    c.command::<SessionDistributions>("get_session_distributions");
+    c.command::<TrendSeries>("get_trends");
+    c.command::<Vec<CostAnomalyResponse>>("get_cost_anomalies");
+    c.command::<CostForecast>("get_cost_forecast");
+    c.command::<DateRangeComparison>("compare_periods");
+    c.command::<WeeklyReport>("generate_weekly_report");
+    c.command::<Vec<CostTrendPoint>>("get_cost_trend");
+    c.command::<Vec<EfficiencyTrendPoint>>("get_efficiency_trend");
+
+    // Export
+    c.command::<String>("export_sessions");
+    c.command::<ExportJobStatus>("get_export_status");
+    c.command::<bool>("cancel_export");
+    c.command::<String>("export_trends");
+    c.command::<String>("export_weekly_report");
+    c.command::<String>("export_session_replay");
+    c.command::<String>("export_session_bundle");
+
+    // Recommendations and anti-patterns
+    c.command::<RecommendationSummary>("get_recommendations");
+    c.command::<RecommendationFeedback>("dismiss_recommendation");
+    c.command::<Vec<RecommendationFeedback>>("list_dismissed");
+    c.command::<bool>("restore_recommendation");
+    c.command::<Vec<DetectedPattern>>("detect_antipatterns");
+    c.command::<Vec<AntiPatternHistoryPoint>>("get_antipattern_history");
+    c.command::<DailyDigest>("generate_daily_digest");
+
+    // GitHub and developer metrics
+    c.command::<GitHubConfigResponse>("detect_github_config");
+    c.command::<DeveloperPerformanceResponse>("get_developer_metrics");
+
+    // Pricing and settings
+    c.command::<Vec<ModelPricingResponse>>("get_model_pricing");
+    c.command::<()>("set_model_pricing");
+    c.command::<bool>("reset_model_pricing");
+    c.command::<Vec<String>>("get_unpriced_models");
+    c.command::<u32>("recompute_costs");
+    c.command::<AppSettings>("get_settings");
+    c.command::<AppSettings>("set_settings");
+    c.command::<CacheConfig>("reload_cache_config");
+    c.command::<UpdateChannel>("get_update_channel");
+    c.command::<UpdateChannel>("set_update_channel");
+    c.command::<UpdateInfo>("check_for_updates");
+    c.command::<IpcConnectionInfo>("create_ipc_token");
+    c.command::<bool>("revoke_ipc_token");
+
+    // Database maintenance, snapshots and teams
+    c.command::<String>("get_db_path");
+    c.command::<String>("migrate_db_to");
+    c.command::<PruneStats>("prune_sessions_older_than");
+    c.command::<VacuumStats>("vacuum_database");
+    c.command::<u32>("rebuild_daily_rollups");
+    c.command::<String>("export_snapshot");
+    c.command::<SnapshotImportStats>("import_snapshot");
+    c.command::<SnapshotSyncReport>("sync_snapshot_folder");
+    c.command::<BootstrapStats>("bootstrap_from_snapshot");
+    c.command::<TeamImportStats>("import_team_export");
+    c.command::<Vec<TeamMember>>("get_team_members");
+    c.command::<u32>("remove_team_member");
+    c.command::<TeamDashboard>("get_team_dashboard");
+    c.command::<Vec<TeamDailyTrend>>("get_team_trends");
+    c.command::<ArchiveReport>("archive_sessions_older_than");
+    c.command::<String>("restore_session");
+    c.command::<ImportResult>("import_sessions_from_path");
+    c.command::<ProjectResolution>("set_project_path_override");
+    c.command::<DiagnosticsReport>("get_diagnostics");
+    c.command::<Vec<BackendError>>("get_backend_errors");
+
+    c.event::<PreloadProgress>("preload-progress");
+    c.event::<ExportJobStatus>("export-progress");
+    c.event::<BackfillProgress>("backfill-progress");
+    c.event::<Job>("job-updated");
+    c.event::<ProjectWarmupProgress>("project-warmup-progress");
+    c.event::<TurnAppended>("turn-appended");
+    c.event::<ActiveSessionCost>("active-session-cost");
+    c.event::<Vec<String>>("sessions-updated");
+    c.event::<SessionSummary>("session-updated");
+    c.event::<Vec<String>>("metrics-updated");
+    c.event::<UsageWindowWarning>("usage-window-warning");
+
+    c
+}
+
+/// The generated TypeScript module
+pub fn typescript() -> String {
+    let contract = contract();
+    let mut out = String::from(
+        "// Generated by `ironhide-bindings` from the Rust types; do not edit.\n\
+         // Regenerate with: cargo run --no-default-features --features bindings --bin ironhide-bindings\n",
+    );
+
+    for (name, schema) in contract.generator.definitions() {
+        out.push('\n');
+        let schema = schema.clone().into_object();
+        push_doc(&mut out, &schema, "");
+        match object_body(&schema, "") {
+            Some(body) if schema.subschemas.is_none() => {
+                out.push_str(&format!("export interface {} {}\n", name, body));
+            }
+            _ => out.push_str(&format!("export type {} = {};\n", name, ts_type(&Schema::Object(schema)))),
+        }
+    }
+
+    let maps = [
+        ("Commands", "Response type of each command", &contract.commands),
+        ("Events", "Payload type of each event", &contract.events),
+    ];
+    for (map, doc, entries) in maps {
+        out.push_str(&format!("\n/** {} */\nexport interface {} {{\n", doc, map));
+        for entry in entries {
+            out.push_str(&format!("  {}: {};\n", property_name(entry.name), entry.ty));
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+/// TypeScript type of a schema
+fn ts_type(schema: &Schema) -> String {
+    let schema = match schema {
+        Schema::Bool(true) => return "unknown".to_string(),
+        Schema::Bool(false) => return "never".to_string(),
+        Schema::Object(schema) => schema,
+    };
+
+    if let Some(reference) = &schema.reference {
+        return reference.rsplit('/').next().unwrap_or(reference).to_string();
+    }
+    if let Some(values) = &schema.enum_values {
+        return union(values.iter().map(literal));
+    }
+    if let Some(value) = &schema.const_value {
+        return literal(value);
+    }
+    if let Some(subschemas) = &schema.subschemas {
+        if let Some(all_of) = &subschemas.all_of {
+            let mut parts: Vec<String> = all_of.iter().map(ts_type).collect();
+            if schema.instance_type.is_some() || schema.object.is_some() {
+                let mut own = schema.clone();
+                own.subschemas = None;
+                parts.push(ts_type(&Schema::Object(own)));
+            }
+            return parts.join(" & ");
+        }
+        if let Some(variants) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+            return union(variants.iter().map(ts_type));
+        }
+    }
+
+    match &schema.instance_type {
+        Some(SingleOrVec::Single(ty)) => instance_type(schema, ty),
+        Some(SingleOrVec::Vec(types)) => union(types.iter().map(|ty| instance_type(schema, ty))),
+        None => object_body(schema, "").unwrap_or_else(|| "unknown".to_string()),
+    }
+}
+
+fn instance_type(schema: &SchemaObject, ty: &InstanceType) -> String {
+    match ty {
+        InstanceType::Null => "null".to_string(),
+        InstanceType::Boolean => "boolean".to_string(),
+        InstanceType::Integer | InstanceType::Number => "number".to_string(),
+        InstanceType::String => "string".to_string(),
+        InstanceType::Array => match schema.array.as_ref().and_then(|a| a.items.as_ref()) {
+            Some(SingleOrVec::Single(item)) => {
+                let item = ts_type(item);
+                if item.contains(' ') {
+                    format!("({})[]", item)
+                } else {
+                    format!("{}[]", item)
+                }
+            }
+            Some(SingleOrVec::Vec(items)) => {
+                format!("[{}]", items.iter().map(ts_type).collect::<Vec<_>>().join(", "))
+            }
+            None => "unknown[]".to_string(),
+        },
+        InstanceType::Object => object_body(schema, "").unwrap_or_else(|| "Record<string, unknown>".to_string()),
+    }
+}
+
+/// `{ ... }` for an object schema with properties, or a `Record` for a map
+fn object_body(schema: &SchemaObject, indent: &str) -> Option<String> {
+    let object = schema.object.as_ref()?;
+    if object.properties.is_empty() {
+        return object
+            .additional_properties
+            .as_ref()
+            .map(|values| format!("Record<string, {}>", ts_type(values)));
+    }
+
+    let mut body = String::from("{\n");
+    let inner = format!("{}  ", indent);
+    for (name, property) in &object.properties {
+        if let Schema::Object(property) = property {
+            push_doc(&mut body, property, &inner);
+        }
+        let optional = if object.required.contains(name) { "" } else { "?" };
+        body.push_str(&format!("{}{}{}: {};\n", inner, property_name(name), optional, ts_type(property)));
+    }
+    body.push_str(indent);
+    body.push('}');
+    Some(body)
+}
+
+fn push_doc(out: &mut String, schema: &SchemaObject, indent: &str) {
+    let Some(description) = schema.metadata.as_ref().and_then(|m| m.description.as_deref()) else {
+        return;
+    };
+    let lines: Vec<&str> = description.lines().collect();
+    if let [line] = lines.as_slice() {
+        out.push_str(&format!("{}/** {} */\n", indent, line));
+    } else {
+        out.push_str(&format!("{}/**\n", indent));
+        for line in lines {
+            out.push_str(&format!("{} * {}\n", indent, line).replace(" * \n", " *\n"));
+        }
+        out.push_str(&format!("{} */\n", indent));
+    }
+}
+
+fn union(parts: impl Iterator<Item = String>) -> String {
+    let mut seen = BTreeSet::new();
+    let parts: Vec<String> = parts.filter(|p| seen.insert(p.clone())).collect();
+    match parts.len() {
+        0 => "never".to_string(),
+        _ => parts.join(" | "),
+    }
+}
+
+fn literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\'', "\\'")),
+        other => other.to_string(),
+    }
+}
+
+/// Quote names that aren't valid identifiers, e.g. event names
+fn property_name(name: &str) -> String {
+    let identifier = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    if identifier {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The checked-in definitions must match the Rust types
+    #[test]
+    fn test_bindings_up_to_date() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(OUTPUT_PATH);
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            current == typescript(),
+            "{} is out of date; regenerate it with ironhide-bindings",
+            path.display()
+        );
+    }
+}
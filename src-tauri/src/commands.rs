@@ -4,35 +4,84 @@
 //! These commands use the real parser and metrics calculators
 //! instead of relying solely on database queries.
 
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::RwLock;
 use std::time::{Duration, Instant, SystemTime};
 
 use serde::Serialize;
 
 use crate::db;
-use crate::metrics::cost::{calculate_turn_cost, CostBreakdown};
-use crate::metrics::session::{
-    calculate_session_metrics, estimate_deliverable_units, estimate_deliverable_units_v2,
-    SessionMetricsInput,
+use crate::db::crypto::{self, ContentEncryption, ContentEncryptionStatus, FieldCipher};
+use crate::db::jobs::{Job, JobKind, JobState};
+use crate::db::retention::{self, PruneStats, VacuumStats};
+use crate::db::snapshot::{self, BootstrapStats, SnapshotImportStats, SnapshotSyncReport};
+use crate::db::team::{self, TeamDailyTrend, TeamDashboard, TeamImportStats, TeamMember};
+use crate::metrics::cost::{
+    calculate_provider_turn_cost, find_pricing, get_active_pricing, set_pricing_overrides, unpriced_models,
+    CostBreakdown, CostProvider, ModelPricing,
 };
+use crate::metrics::session::{calculate_session_metrics, SessionMetricsInput};
 use crate::metrics::tokens::{SessionTokens, TurnTokens};
+use crate::metrics::cache::{cache_breakdown, CacheBreakdown};
+use crate::metrics::efficiency_curve::{efficiency_curve, SessionEfficiencyCurve, DEFAULT_WINDOW};
+use crate::metrics::benchmark::{benchmark_projects, BenchmarkSession, ProjectBenchmarks};
+use crate::metrics::comparison::{ComparedSession, MetricsComparisonDetailed};
+use crate::metrics::context::{ContextTimeline, ContextTimelineBuilder};
+use crate::metrics::health::{calculate_health, HealthInput, HealthScore};
+use crate::metrics::hourly::{HourlyUsage, HourlyUsageAccumulator};
+use crate::metrics::rework::{analyze_rework, extract_code_changes, CodeChange, FileChangeSummary, ReworkAnalysis};
+use crate::metrics::subagents::{build_subagent_tree, SubagentNode};
+use crate::metrics::tool_failures::{extract_tool_failures, ToolFailure};
+use crate::metrics::tool_usage::{summarize_tool_usage, ToolUsageStats};
+use crate::metrics::mcp::{calculate_mcp_stats, McpServerStats, McpStats};
+use crate::metrics::savings::{SavingsScenario, SavingsSimulation};
+use crate::metrics::deliverables::{estimate_deliverables, DeliverableEstimator};
+use crate::metrics::active_time::calculate_active_time;
+use crate::metrics::concurrency::{detect_concurrency, ConcurrencyReport, TurnInterval};
+use crate::metrics::api_errors::{summarize_api_errors, ApiErrorStats};
+use crate::metrics::stack::{detect_stack, ProjectStack};
+use crate::metrics::stats::{SessionDistributions, DEFAULT_BUCKETS};
+use crate::metrics::billing_blocks::{compute_billing_blocks, BillingBlocks, BLOCK_HOURS};
+use crate::metrics::burn_rate::{calculate_burn_rate, BurnRateReport, TurnActivity};
+use crate::metrics::thinking::{summarize_thinking, thinking_share, turn_thinking_cost, ThinkingTrend};
+use crate::metrics::usage_windows::{
+    compute_usage_windows, UsageThresholdTracker, UsageTurn, UsageWindow, UsageWindowWarning,
+};
+use crate::diagnostics::{BackendError, InitError, WatcherStatus};
+use crate::git::SessionGitContext;
+use crate::timezone::local_date;
+use crate::watcher::live::ACTIVE_WINDOW;
 use crate::export::{
     ExportFormat, ExportOptions, ExportableSession, ExportableTrend, ExportableTurn,
-    csv_export, json_export, get_export_directory, generate_export_filename,
+    bundle, csv_export, html_export, ics_export, json_export, md_export, parquet_export, sqlite_export, daily_trends,
+    get_export_directory, generate_export_filename,
 };
 use crate::parser::{
-    find_session_by_id, parse_session_by_id, scan_claude_sessions, CompletedTurn, SessionFileInfo,
+    extract_git_branch, extract_subagent_spawns, find_session_by_id, has_assistant_entry, project_path,
+    scan_claude_sessions,
+    CompletedTurn, ImportSource, IncrementalSessionParser, ParseDiagnostics, RawEntryPage, SessionFileInfo,
+    TurnAggregator,
 };
+use crate::models::turn::{AnnotationLabel, TurnAnnotation};
+use crate::notifications::NotificationEvent;
+use crate::parser::registry::{self, ProjectResolution, ResolutionSource};
+use crate::parser::transcript::{Transcript, TranscriptBuilder, TranscriptOptions};
+use crate::patterns::{detect_in_turns, types::{DetectionThresholds, Severity}, AntiPatternType};
 use crate::recommendations::{
-    engine::{generate_recommendations, generate_aggregate_recommendations},
-    types::{RecommendationInput, RecommendationSummary},
+    engine::{apply_feedback, generate_recommendations, generate_aggregate_recommendations, MCP_WINDOW_DAYS},
+    types::{parse_recommendation_id, RecommendationFeedback, RecommendationInput, RecommendationSummary},
+};
+use crate::settings::{
+    active_settings, apply_settings, cache_config, is_past_retention, is_real_user_project,
+    reporting_timezone, AppSettings, CacheConfig, IpcToken,
 };
-use crate::AppState;
+use crate::updates::{UpdateChannel, UpdateInfo};
+use crate::{AppState, State};
 use crate::CommandError;
 
 // ============================================================================
@@ -41,6 +90,7 @@ use crate::CommandError;
 
 /// Session summary for list views
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct SessionSummary {
     pub id: String,
     pub project_path: String,
@@ -49,6 +99,8 @@ pub struct SessionSummary {
     pub last_activity_at: Option<String>,
     pub model: Option<String>,
     pub total_cost: f64,
+    /// Own cost plus the cost of the subagent sessions it spawned
+    pub total_cost_including_subagents: f64,
     pub total_turns: u32,
     pub total_tokens: u64,
     pub duration_ms: u64,
@@ -59,6 +111,7 @@ pub struct SessionSummary {
 
 /// Full session detail with all metrics
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct SessionDetail {
     pub id: String,
     pub project_path: String,
@@ -69,24 +122,39 @@ pub struct SessionDetail {
     pub is_subagent: bool,
     pub file_path: String,
     pub metrics: SessionMetricsResponse,
+    /// Own cost plus the cost of the subagent sessions it spawned
+    pub total_cost_including_subagents: f64,
+    /// Cost of the turns annotated as wasted
+    pub wasted_cost: f64,
 }
 
 /// Session metrics response
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct SessionMetricsResponse {
     pub tokens: TokenSummaryResponse,
     pub cost: CostSummaryResponse,
     pub efficiency: EfficiencyResponse,
+    /// Wall-clock time from the first turn to the last
     pub duration_ms: u64,
+    /// Time spent in turns and in pauses of up to 15 minutes between them
+    pub active_duration_ms: u64,
+    /// Pauses between turns longer than 15 minutes
+    pub idle_duration_ms: u64,
     pub turn_count: u32,
     pub tool_count: u32,
     pub unique_tools: Vec<String>,
     pub models_used: Vec<String>,
     pub subagent_count: u32,
+    /// Times the conversation was compacted to free up context
+    pub compaction_count: u32,
+    /// Context tokens dropped by those compactions
+    pub tokens_lost_to_compaction: u64,
 }
 
 /// Token summary response
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct TokenSummaryResponse {
     pub input: u64,
     pub output: u64,
@@ -95,21 +163,32 @@ pub struct TokenSummaryResponse {
     pub cache_write_1h: u64,
     pub total: u64,
     pub context_used_pct: f64,
+    /// Output tokens spent on extended thinking (estimated)
+    pub thinking: u64,
+    /// Thinking tokens as a fraction (0-1) of output tokens
+    pub thinking_share: f64,
 }
 
 /// Cost summary response
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct CostSummaryResponse {
     pub input_cost: f64,
     pub output_cost: f64,
     pub cache_read_cost: f64,
+    /// 5-minute and 1-hour cache writes together
     pub cache_write_cost: f64,
+    pub cache_write_5m_cost: f64,
+    pub cache_write_1h_cost: f64,
     pub total_cost: f64,
     pub avg_cost_per_turn: f64,
+    /// Part of the output cost spent on extended thinking
+    pub thinking_cost: f64,
 }
 
 /// Efficiency metrics response
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct EfficiencyResponse {
     pub cer: f64,         // Cache Efficiency Ratio
     pub cgr: f64,         // Context Growth Rate
@@ -119,10 +198,16 @@ pub struct EfficiencyResponse {
     pub cpd: f64,         // Cycles per Deliverable
     pub oes_score: f64,   // Overall Efficiency Score
     pub oes_grade: String,
+    /// Deliverable units CPDU and CPD are based on
+    pub deliverable_units: f64,
+    /// Estimator that produced them; code changes fall back to tool usage
+    /// for sessions that changed no files
+    pub deliverable_estimator: DeliverableEstimator,
 }
 
 /// Turn summary for list views
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct TurnSummary {
     pub turn_number: u32,
     pub started_at: String,
@@ -141,16 +226,34 @@ pub struct TurnSummary {
 
 /// Turn tokens response
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct TurnTokensResponse {
     pub input: u64,
     pub output: u64,
     pub cache_read: u64,
     pub cache_write: u64,
     pub total: u64,
+    /// Output tokens spent on extended thinking (estimated)
+    pub thinking: u64,
+}
+
+/// One turn on a session's cost curve
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CostCurvePoint {
+    pub turn_number: u32,
+    pub started_at: String,
+    pub model: Option<String>,
+    pub cost: f64,
+    pub tokens: u64,
+    /// Session cost up to and including this turn
+    pub cumulative_cost: f64,
+    pub cumulative_tokens: u64,
 }
 
 /// Subagent summary for detailed subagent tracking
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct SubagentSummary {
     pub agent_id: String,
     pub slug: Option<String>,
@@ -162,13 +265,17 @@ pub struct SubagentSummary {
 
 /// Session comparison result
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct SessionComparison {
     pub sessions: Vec<SessionSummary>,
     pub metrics_comparison: MetricsComparison,
+    /// Structured diff of each session after the first against the first
+    pub detailed_comparisons: Vec<MetricsComparisonDetailed>,
 }
 
 /// Metrics comparison between sessions
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct MetricsComparison {
     pub cost_diff: f64,
     pub token_diff: i64,
@@ -176,18 +283,9 @@ pub struct MetricsComparison {
     pub duration_diff: i64,
 }
 
-/// Code change tracked during a session
-#[derive(Debug, Clone, Serialize)]
-pub struct CodeChange {
-    pub file_path: String,
-    pub change_type: String,  // "create", "edit", "delete"
-    pub tool_name: String,    // "Write", "Edit", "Bash"
-    pub turn_number: u32,
-    pub timestamp: String,
-}
-
 /// Cost trend data point for charts
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct CostTrendPoint {
     pub date: String,
     pub cost: f64,
@@ -196,6 +294,7 @@ pub struct CostTrendPoint {
 
 /// Efficiency trend data point for charts
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct EfficiencyTrendPoint {
     pub date: String,
     pub efficiency: f64,
@@ -204,6 +303,7 @@ pub struct EfficiencyTrendPoint {
 
 /// GitHub configuration auto-detected from environment
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct GitHubConfigResponse {
     pub has_token: bool,
     pub token_source: Option<String>,  // "gh_cli", "GITHUB_TOKEN", "GH_TOKEN"
@@ -212,6 +312,7 @@ pub struct GitHubConfigResponse {
 
 /// Per-sprint scores for bubble rendering
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct SprintScoreResponse {
     pub index: u32,
     pub start_date: String,
@@ -226,6 +327,7 @@ pub struct SprintScoreResponse {
 
 /// Developer performance metrics response (3-axis AI adoption metrics)
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct DeveloperPerformanceResponse {
     pub throughput_velocity: f64,
     pub parallelism_ratio: f64,
@@ -276,12 +378,109 @@ impl From<crate::metrics::developer::DeveloperPerformanceMetrics> for DeveloperP
 // Session Cache
 // ============================================================================
 
+/// A session's parsed turns, shared between the cache and the commands
+/// reading them
+///
+/// Commands borrow from it instead of copying the turns out of the cache.
+type SharedTurns = Arc<[CompletedTurn]>;
+
 /// Cached session data to avoid re-parsing unchanged files
 struct CachedSession {
     last_modified: SystemTime,
     file_size: u64,
-    turns: Arc<Vec<CompletedTurn>>,
-    last_accessed: Instant,
+    turns: SharedTurns,
+    /// Parser state at the end of the file, used to parse only appended lines
+    /// when the file grows. None when the turns were loaded from the database.
+    parser: Option<IncrementalSessionParser>,
+}
+
+/// Parsed sessions kept under a memory budget, evicted least recently used first
+///
+/// Each entry is charged the estimated size of its turns, so one huge session
+/// counts as much as many small ones. A session that alone exceeds the budget
+/// is not cached at all.
+struct SessionCache {
+    /// Session ID -> (entry, estimated bytes, last access tick)
+    entries: HashMap<String, (CachedSession, usize, u64)>,
+    /// Access tick -> session ID, oldest first
+    recency: BTreeMap<u64, String>,
+    next_tick: u64,
+    total_bytes: usize,
+    budget_bytes: usize,
+}
+
+impl SessionCache {
+    fn new(budget_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            next_tick: 0,
+            total_bytes: 0,
+            budget_bytes,
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+
+    /// Look up a session, marking it as most recently used
+    fn get_mut(&mut self, session_id: &str) -> Option<&mut CachedSession> {
+        let tick = self.tick();
+        let (entry, _, last_tick) = self.entries.get_mut(session_id)?;
+        self.recency.remove(last_tick);
+        self.recency.insert(tick, session_id.to_string());
+        *last_tick = tick;
+        Some(entry)
+    }
+
+    fn insert(&mut self, session_id: String, entry: CachedSession) {
+        self.remove(&session_id);
+        let bytes: usize = entry.turns.iter().map(CompletedTurn::estimated_size).sum();
+        if bytes > self.budget_bytes {
+            return;
+        }
+
+        let tick = self.tick();
+        self.recency.insert(tick, session_id.clone());
+        self.entries.insert(session_id, (entry, bytes, tick));
+        self.total_bytes += bytes;
+        self.evict_to_budget();
+    }
+
+    fn remove(&mut self, session_id: &str) {
+        if let Some((_, bytes, tick)) = self.entries.remove(session_id) {
+            self.recency.remove(&tick);
+            self.total_bytes -= bytes;
+        }
+    }
+
+    fn set_budget(&mut self, budget_bytes: usize) {
+        self.budget_bytes = budget_bytes;
+        self.evict_to_budget();
+    }
+
+    fn evict_to_budget(&mut self) {
+        while self.total_bytes > self.budget_bytes {
+            let oldest = match self.recency.first_key_value() {
+                Some((_, id)) => id.clone(),
+                None => break,
+            };
+            self.remove(&oldest);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.total_bytes = 0;
+    }
+}
+
+/// Session cache budget from settings, in bytes
+fn session_cache_budget(config: &CacheConfig) -> usize {
+    (config.session_cache_budget_mb as usize).saturating_mul(1024 * 1024)
 }
 
 /// Cached session list with pre-computed summaries
@@ -304,12 +503,10 @@ impl SessionListCache {
     }
 }
 
-// Cache validity duration (30 seconds - can be tuned)
-const SESSION_LIST_CACHE_TTL_SECS: u64 = 30;
-
 // Global session cache using lazy_static
 lazy_static::lazy_static! {
-    static ref SESSION_CACHE: RwLock<HashMap<String, CachedSession>> = RwLock::new(HashMap::new());
+    static ref SESSION_CACHE: Mutex<SessionCache> =
+        Mutex::new(SessionCache::new(session_cache_budget(&CacheConfig::default())));
     static ref SESSION_LIST_CACHE: RwLock<SessionListCache> = RwLock::new(SessionListCache::new());
 }
 
@@ -324,9 +521,10 @@ lazy_static::lazy_static! {
 /// keyed by the `days` parameter (represented as Option<u32>) so that
 /// switching between time ranges (7d, 30d, 90d, All) returns the correct
 /// data instead of a stale result from a different range. Reads return
-/// `None` once the TTL has elapsed, causing the next caller to recompute.
+/// `None` once the TTL has elapsed, causing the next caller to recompute,
+/// or once the value's aggregate generation has been superseded.
 struct AggregateCache<T: Clone> {
-    data: HashMap<Option<u32>, (Instant, T)>,
+    data: HashMap<Option<u32>, (Instant, u64, T)>,
     ttl: Duration,
 }
 
@@ -338,9 +536,13 @@ impl<T: Clone> AggregateCache<T> {
         }
     }
 
+    /// The cached value for `days`; while aggregates are pinned it's served
+    /// past its TTL
     fn get(&self, days: Option<u32>) -> Option<T> {
-        self.data.get(&days).and_then(|(time, data)| {
-            if time.elapsed() < self.ttl {
+        let pinned = aggregates_pinned();
+        let generation = AGGREGATE_GENERATION.load(Ordering::SeqCst);
+        self.data.get(&days).and_then(|(time, computed_in, data)| {
+            if *computed_in == generation && (pinned || time.elapsed() < self.ttl) {
                 Some(data.clone())
             } else {
                 None
@@ -349,27 +551,249 @@ impl<T: Clone> AggregateCache<T> {
     }
 
     fn set(&mut self, days: Option<u32>, data: T) {
-        self.data.insert(days, (Instant::now(), data));
+        let generation = AGGREGATE_GENERATION.load(Ordering::SeqCst);
+        self.data.insert(days, (Instant::now(), generation, data));
+    }
+
+    fn set_ttl(&mut self, ttl_secs: u64) {
+        self.ttl = Duration::from_secs(ttl_secs);
+    }
+
+    fn clear(&mut self) {
+        self.data.clear();
+    }
+}
+
+/// Generation of the cached aggregates; bumping it drops every value
+/// computed before
+static AGGREGATE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Number of preload phases currently writing sessions
+static AGGREGATE_PINS: AtomicUsize = AtomicUsize::new(0);
+
+fn aggregates_pinned() -> bool {
+    AGGREGATE_PINS.load(Ordering::SeqCst) > 0
+}
+
+/// Keeps dashboard aggregates on a consistent snapshot while a preload
+/// phase stores sessions
+///
+/// While any guard is alive, the first aggregate computed for a time range
+/// is served until the guard drops instead of being recomputed from a
+/// database that's still filling up, so totals don't creep up as sessions
+/// land; invalidations from synced sessions wait too. Dropping the last
+/// guard publishes a new generation, so the next read sees everything the
+/// phase stored.
+struct AggregateSnapshot;
+
+impl AggregateSnapshot {
+    fn pin() -> Self {
+        AGGREGATE_PINS.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for AggregateSnapshot {
+    fn drop(&mut self) {
+        if AGGREGATE_PINS.fetch_sub(1, Ordering::SeqCst) == 1 {
+            AGGREGATE_GENERATION.fetch_add(1, Ordering::SeqCst);
+        }
     }
 }
 
 lazy_static::lazy_static! {
-    /// Cache for `get_dashboard_summary` - TTL 30 seconds
+    /// Cache for `get_dashboard_summary` - TTL 30 seconds by default
     static ref DASHBOARD_CACHE: Mutex<AggregateCache<DashboardSummaryResponse>> =
-        Mutex::new(AggregateCache::new(30));
+        Mutex::new(AggregateCache::new(CacheConfig::default().dashboard_ttl_secs));
 
-    /// Cache for `get_daily_metrics` - TTL 60 seconds
+    /// Cache for `get_daily_metrics` - TTL 60 seconds by default
     static ref DAILY_CACHE: Mutex<AggregateCache<Vec<DailyMetricsResponse>>> =
-        Mutex::new(AggregateCache::new(60));
+        Mutex::new(AggregateCache::new(CacheConfig::default().daily_metrics_ttl_secs));
 
-    /// Cache for `get_project_metrics` - TTL 60 seconds
+    /// Cache for `get_project_metrics` - TTL 60 seconds by default
     static ref PROJECT_CACHE: Mutex<AggregateCache<Vec<ProjectMetricsResponse>>> =
-        Mutex::new(AggregateCache::new(60));
+        Mutex::new(AggregateCache::new(CacheConfig::default().project_metrics_ttl_secs));
+}
+
+/// Apply cache limits to the live caches
+///
+/// New TTLs take effect on the next lookup; the parsed session cache is
+/// trimmed right away if it's over the new budget.
+fn apply_cache_config(config: &CacheConfig) {
+    if let Ok(mut cache) = DASHBOARD_CACHE.lock() {
+        cache.set_ttl(config.dashboard_ttl_secs);
+    }
+    if let Ok(mut cache) = DAILY_CACHE.lock() {
+        cache.set_ttl(config.daily_metrics_ttl_secs);
+    }
+    if let Ok(mut cache) = PROJECT_CACHE.lock() {
+        cache.set_ttl(config.project_metrics_ttl_secs);
+    }
+    if let Ok(mut cache) = SESSION_CACHE.lock() {
+        cache.set_budget(session_cache_budget(config));
+    }
 }
 
 /// Flag to track if initial preload is complete
 static SESSIONS_PRELOADED: AtomicBool = AtomicBool::new(false);
 
+/// Minimum time between two progress events of the same preload phase or
+/// export job
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+type PreloadListener = Box<dyn Fn(&PreloadProgress) + Send + Sync>;
+
+/// Cancellation and progress reporting for `preload_all_sessions`
+///
+/// Held in `AppState`; clones share the same flags so the background phase
+/// sees a cancellation requested after the command returned.
+#[derive(Clone, Default)]
+pub struct PreloadControl {
+    running: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    listener: Arc<std::sync::OnceLock<PreloadListener>>,
+    /// When a preload last finished or stopped (RFC 3339)
+    last_finished: Arc<Mutex<Option<String>>>,
+}
+
+impl PreloadControl {
+    /// Register the receiver of progress updates (the desktop app emits them
+    /// as `preload-progress` events)
+    pub fn set_listener(&self, listener: impl Fn(&PreloadProgress) + Send + Sync + 'static) {
+        let _ = self.listener.set(Box::new(listener));
+    }
+
+    /// Ask a running preload to stop; returns whether one was running
+    pub fn cancel(&self) -> bool {
+        let running = self.running.load(Ordering::SeqCst);
+        if running {
+            self.cancelled.store(true, Ordering::SeqCst);
+        }
+        running
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn begin(&self) {
+        self.cancelled.store(false, Ordering::SeqCst);
+        self.running.store(true, Ordering::SeqCst);
+    }
+
+    /// Mark the queued second phase as running, keeping a cancellation
+    /// requested while it waited
+    fn resume(&self) {
+        self.running.store(true, Ordering::SeqCst);
+    }
+
+    fn end(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Ok(mut last_finished) = self.last_finished.lock() {
+            *last_finished = Some(chrono::Utc::now().to_rfc3339());
+        }
+    }
+
+    pub fn last_finished(&self) -> Option<String> {
+        self.last_finished.lock().ok().and_then(|t| t.clone())
+    }
+
+    fn report(&self, progress: &PreloadProgress) {
+        if let Some(listener) = self.listener.get() {
+            listener(progress);
+        }
+    }
+}
+
+/// Sessions parsed at the same time by a preload or project warm-up
+const PARSE_CONCURRENCY: usize = 8;
+
+/// Payload of the `preload-progress` event
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct PreloadProgress {
+    /// 1 for the sessions loaded before the command returns, 2 for the rest
+    /// parsed in the background
+    pub phase: u8,
+    pub processed: u32,
+    pub total: u32,
+    /// Sessions served from the database cache instead of being parsed
+    pub cache_hits: u32,
+    pub elapsed_ms: u64,
+    /// Estimated time left in this phase, once anything has been processed
+    pub eta_ms: Option<u64>,
+    pub done: bool,
+    pub cancelled: bool,
+}
+
+/// Tracks one preload phase and reports it, throttled to
+/// `PROGRESS_EVENT_INTERVAL`
+struct PreloadReporter {
+    control: PreloadControl,
+    phase: u8,
+    total: u32,
+    processed: u32,
+    cache_hits: u32,
+    started: Instant,
+    last_report: Option<Instant>,
+}
+
+impl PreloadReporter {
+    fn new(control: PreloadControl, phase: u8, total: usize) -> Self {
+        Self {
+            control,
+            phase,
+            total: total as u32,
+            processed: 0,
+            cache_hits: 0,
+            started: Instant::now(),
+            last_report: None,
+        }
+    }
+
+    fn cache_hits(&mut self, count: u32) {
+        self.cache_hits += count;
+        self.advance(count);
+    }
+
+    fn advance(&mut self, count: u32) {
+        self.processed = (self.processed + count).min(self.total);
+        let due = self
+            .last_report
+            .is_none_or(|at| at.elapsed() >= PROGRESS_EVENT_INTERVAL);
+        if due {
+            self.report(false);
+        }
+    }
+
+    fn finish(&mut self) {
+        self.report(true);
+    }
+
+    fn report(&mut self, done: bool) {
+        self.last_report = Some(Instant::now());
+        self.control.report(&self.progress(done));
+    }
+
+    fn progress(&self, done: bool) -> PreloadProgress {
+        let elapsed = self.started.elapsed();
+        let eta_ms = (self.processed > 0 && !done).then(|| {
+            let remaining = self.total.saturating_sub(self.processed) as f64;
+            (elapsed.as_millis() as f64 / self.processed as f64 * remaining) as u64
+        });
+        PreloadProgress {
+            phase: self.phase,
+            processed: self.processed,
+            total: self.total,
+            cache_hits: self.cache_hits,
+            elapsed_ms: elapsed.as_millis() as u64,
+            eta_ms,
+            done,
+            cancelled: self.control.is_cancelled(),
+        }
+    }
+}
+
 /// Truncate a string to at most `max_bytes` bytes, ensuring we don't split a multi-byte character.
 fn truncate_str(s: &str, max_bytes: usize) -> &str {
     if s.len() <= max_bytes {
@@ -383,9 +807,9 @@ fn truncate_str(s: &str, max_bytes: usize) -> &str {
 }
 
 /// Check if a session is cached and still valid
-fn get_cached_session(session_id: &str, file_info: &SessionFileInfo) -> Option<Arc<Vec<CompletedTurn>>> {
-    let cache = SESSION_CACHE.read().ok()?;
-    let cached = cache.get(session_id)?;
+fn get_cached_session(session_id: &str, file_info: &SessionFileInfo) -> Option<SharedTurns> {
+    let mut cache = SESSION_CACHE.lock().ok()?;
+    let cached = cache.get_mut(session_id)?;
 
     // Check if file has been modified
     if cached.last_modified == file_info.modified && cached.file_size == file_info.size {
@@ -396,28 +820,20 @@ fn get_cached_session(session_id: &str, file_info: &SessionFileInfo) -> Option<A
 }
 
 /// Store parsed session in cache
-fn cache_session(session_id: &str, file_info: &SessionFileInfo, turns: Vec<CompletedTurn>) {
-    if let Ok(mut cache) = SESSION_CACHE.write() {
-        // Limit cache size to avoid memory issues (500 sessions covers most preloaded data)
-        if cache.len() > 500 {
-            // Evict oldest 50 entries by last_accessed
-            let mut entries: Vec<(String, Instant)> = cache
-                .iter()
-                .map(|(k, v)| (k.clone(), v.last_accessed))
-                .collect();
-            entries.sort_by_key(|(_, t)| *t);
-            for (key, _) in entries.into_iter().take(50) {
-                cache.remove(&key);
-            }
-        }
-
+fn cache_session(
+    session_id: &str,
+    file_info: &SessionFileInfo,
+    turns: SharedTurns,
+    parser: Option<IncrementalSessionParser>,
+) {
+    if let Ok(mut cache) = SESSION_CACHE.lock() {
         cache.insert(
             session_id.to_string(),
             CachedSession {
                 last_modified: file_info.modified,
                 file_size: file_info.size,
-                turns: Arc::new(turns),
-                last_accessed: Instant::now(),
+                turns,
+                parser,
             },
         );
     }
@@ -425,11 +841,21 @@ fn cache_session(session_id: &str, file_info: &SessionFileInfo, turns: Vec<Compl
 
 /// Clear the session cache
 fn clear_cache() {
-    if let Ok(mut cache) = SESSION_CACHE.write() {
+    if let Ok(mut cache) = SESSION_CACHE.lock() {
         cache.clear();
     }
 }
 
+/// Drop one session's cached turns, parser state and summary
+fn forget_session(session_id: &str) {
+    if let Ok(mut cache) = SESSION_CACHE.lock() {
+        cache.remove(session_id);
+    }
+    if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
+        list_cache.summaries.remove(session_id);
+    }
+}
+
 /// Clear all caches (turn cache and session list cache)
 fn clear_all_caches() {
     clear_cache();
@@ -439,6 +865,40 @@ fn clear_all_caches() {
         list_cache.last_refresh = Instant::now() - std::time::Duration::from_secs(3600);
     }
     SESSIONS_PRELOADED.store(false, Ordering::SeqCst);
+    crate::parser::scan::clear_scan_cache();
+}
+
+/// Drop cached session summaries and aggregates so they are recomputed on
+/// next access, e.g. after pricing or project filters change
+fn clear_derived_caches() {
+    if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
+        list_cache.summaries.clear();
+    }
+    drop_aggregate_caches();
+}
+
+/// Drop the dashboard, daily and project aggregates after session data changed
+///
+/// Deferred while a preload phase has them pinned; the phase publishes a
+/// new generation when it ends.
+fn clear_aggregate_caches() {
+    if aggregates_pinned() {
+        return;
+    }
+    drop_aggregate_caches();
+}
+
+/// Drop the cached aggregates right away, pinned or not
+fn drop_aggregate_caches() {
+    if let Ok(mut cache) = DASHBOARD_CACHE.lock() {
+        cache.clear();
+    }
+    if let Ok(mut cache) = DAILY_CACHE.lock() {
+        cache.clear();
+    }
+    if let Ok(mut cache) = PROJECT_CACHE.lock() {
+        cache.clear();
+    }
 }
 
 /// Get file modification time as ISO-8601 timestamp string
@@ -451,11 +911,13 @@ fn clear_all_caches() {
 /// on subsequent reads (macOS `SystemTime` truncation issue).
 pub fn get_file_mtime(path: &Path) -> Option<String> {
     let metadata = std::fs::metadata(path).ok()?;
-    let modified = metadata.modified().ok()?;
+    Some(format_mtime(metadata.modified().ok()?))
+}
 
-    // Convert SystemTime to DateTime<Utc>
+/// Format a modification time the way [`get_file_mtime`] does
+fn format_mtime(modified: std::time::SystemTime) -> String {
     let datetime: chrono::DateTime<chrono::Utc> = modified.into();
-    Some(datetime.to_rfc3339_opts(chrono::SecondsFormat::Micros, true))
+    datetime.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
 }
 
 /// Compare two mtime strings for equality, tolerating format differences.
@@ -519,10 +981,10 @@ fn is_db_cache_valid(state: &AppState, session_id: &str, file_path: &Path) -> Op
     // Get current file mtime
     let current_mtime = get_file_mtime(file_path)?;
 
-    // Check database using with_connection pattern
+    // Check database on a pooled read connection
     let db = state.db.get()?;
 
-    match db.with_connection(|conn| {
+    match db.with_read_connection(|conn| {
         db::queries::is_session_cache_valid(conn, session_id, &current_mtime)
     }) {
         Ok(valid) => Some(valid),
@@ -540,7 +1002,7 @@ fn get_cached_session_list() -> Vec<SessionFileInfo> {
         let cache = SESSION_LIST_CACHE.read().ok();
         if let Some(c) = cache {
             let elapsed = c.last_refresh.elapsed().as_secs();
-            if elapsed < SESSION_LIST_CACHE_TTL_SECS && !c.sessions.is_empty() {
+            if elapsed < cache_config().session_list_ttl_secs && !c.sessions.is_empty() {
                 return c.sessions.clone();
             }
         }
@@ -632,6 +1094,7 @@ fn compute_session_summary(file_info: &SessionFileInfo) -> SessionSummary {
                 last_activity_at: last_activity,
                 model,
                 total_cost: total_breakdown.total_cost,
+                total_cost_including_subagents: total_breakdown.total_cost,
                 total_turns: turns.len() as u32,
                 total_tokens: session_tokens.total(),
                 duration_ms,
@@ -652,6 +1115,7 @@ fn compute_session_summary(file_info: &SessionFileInfo) -> SessionSummary {
                 last_activity_at: None,
                 model: None,
                 total_cost: 0.0,
+                total_cost_including_subagents: 0.0,
                 total_turns: 0,
                 total_tokens: 0,
                 duration_ms: 0,
@@ -672,90 +1136,236 @@ fn get_database(state: &AppState) -> Result<&db::Database, CommandError> {
     state.db.get().ok_or(CommandError::NotInitialized)
 }
 
+/// Log an error the command works around and count it for
+/// `get_backend_errors`; `source` reads after "Failed to"
+fn report_error(state: &AppState, source: &'static str, error: impl std::fmt::Display) {
+    tracing::warn!("Failed to {}: {}", source, error);
+    state.diagnostics.record_error(source, error);
+}
+
+/// The value of a database read the command can do without, reporting a
+/// failure instead of dropping it
+fn ok_or_report<T>(state: &AppState, source: &'static str, result: Result<T, db::DbError>) -> Option<T> {
+    result.map_err(|e| report_error(state, source, e)).ok()
+}
+
+/// Cost of the subagent sessions spawned by each of `parent_ids`
+///
+/// Uses the parent links stored in the database, or sums the subagent files
+/// in each session's `subagents/` directory before anything is stored.
+fn subagent_costs(state: &AppState, parent_ids: &HashSet<&str>) -> HashMap<String, f64> {
+    if let Some(db) = state.db.get() {
+        match db.with_read_connection(db::queries::get_subagent_costs) {
+            Ok(costs) if !costs.is_empty() => return costs,
+            Ok(_) => {}
+            Err(e) => report_error(state, "load subagent costs", e),
+        }
+    }
+
+    let mut costs: HashMap<String, f64> = HashMap::new();
+    for file_info in get_cached_session_list() {
+        if let Some(parent_id) = file_info.parent_session_id.as_deref() {
+            if parent_ids.contains(parent_id) {
+                *costs.entry(parent_id.to_string()).or_default() += get_cached_summary(&file_info).total_cost;
+            }
+        }
+    }
+    costs
+}
+
+/// Fill in `total_cost_including_subagents` on session summaries
+fn add_subagent_costs(state: &AppState, summaries: &mut [SessionSummary]) {
+    let costs = {
+        let ids: HashSet<&str> = summaries.iter().map(|s| s.id.as_str()).collect();
+        subagent_costs(state, &ids)
+    };
+    for summary in summaries.iter_mut() {
+        summary.total_cost_including_subagents =
+            summary.total_cost + costs.get(&summary.id).copied().unwrap_or(0.0);
+    }
+}
+
 /// Parse a session and get its turns, using cache when available
 ///
 /// Cache check priority:
 /// 1. In-memory cache (fast, per-process)
 /// 2. Database cache (persistent, checks file mtime)
-/// 3. Parse from JSONL file (slowest, but always accurate)
+/// 3. Parse from JSONL file (appended lines only if the file grew since the
+///    cached parse)
 ///
 /// Note: Database cache check requires AppState for DB access.
 /// Use `get_session_turns_with_db_cache` when AppState is available.
-fn get_session_turns(session_id: &str) -> Result<(Vec<CompletedTurn>, SessionFileInfo), CommandError> {
+fn get_session_turns(session_id: &str) -> Result<(SharedTurns, SessionFileInfo), CommandError> {
     let file_info = find_session_by_id(session_id)
         .ok_or_else(|| CommandError::SessionNotFound(session_id.to_string()))?;
 
     // Try in-memory cache first
     if let Some(cached_turns) = get_cached_session(session_id, &file_info) {
-        return Ok(((*cached_turns).to_vec(), file_info));
+        return Ok((cached_turns, file_info));
     }
 
-    // Parse the session
-    let (turns, _stats) = parse_session_by_id(session_id)
+    // Parse the session (only the appended lines if it was cached before)
+    let turns = parse_session_turns(session_id, &file_info)?;
+
+    Ok((turns, file_info))
+}
+
+/// Parse a session's turns and cache them along with the parser state
+///
+/// If the cached copy of the session came from a shorter version of the same
+/// file, parsing resumes where it left off instead of starting over.
+fn parse_session_turns(
+    session_id: &str,
+    file_info: &SessionFileInfo,
+) -> Result<SharedTurns, CommandError> {
+    let (mut parser, previous) = match take_resumable_parser(session_id, file_info) {
+        Some(resumable) => {
+            tracing::debug!("Session {} grew, parsing appended lines only", session_id);
+            resumable
+        }
+        None => (IncrementalSessionParser::new(&file_info.path), SharedTurns::from([])),
+    };
+
+    let turns = parser
+        .advance(&previous)
         .map_err(|e| CommandError::Internal(e.to_string()))?;
 
-    // Cache the result in memory
-    cache_session(session_id, &file_info, turns.clone());
+    let turns = SharedTurns::from(turns);
+    cache_session(session_id, file_info, turns.clone(), Some(parser));
 
-    Ok((turns, file_info))
+    Ok(turns)
+}
+
+/// Take the cached parser state for a session whose file has grown since it
+/// was parsed, together with the turns that parse produced
+fn take_resumable_parser(
+    session_id: &str,
+    file_info: &SessionFileInfo,
+) -> Option<(IncrementalSessionParser, SharedTurns)> {
+    let mut cache = SESSION_CACHE.lock().ok()?;
+    let cached = cache.get_mut(session_id)?;
+
+    // A file that didn't grow was rewritten, not appended to
+    if file_info.size <= cached.file_size {
+        return None;
+    }
+
+    let parser = cached.parser.take()?;
+    Some((parser, cached.turns.clone()))
 }
 
-/// Parse a session and get its turns, checking database cache first
+/// Get a session's turns, serving them from the database when possible
 ///
-/// This version checks the database cache before parsing:
+/// Lookup order:
 /// 1. In-memory cache (fast, per-process)
-/// 2. Database cache with file mtime check (persistent across restarts)
-/// 3. Parse from JSONL file (if cache miss or mtime changed)
+/// 2. Turns stored in the database, if they were parsed from the current file mtime
+/// 3. Parse from JSONL file (only the appended lines if the file grew since it
+///    was last parsed), then store the turns to the database for next time
 ///
-/// Returns:
-/// - `needs_db_store = true` if the session was parsed and should be stored to DB
-/// - `needs_db_store = false` if data came from cache
-#[allow(dead_code)]
+/// Remote sessions, with no file on this machine, only have their stored turns.
 fn get_session_turns_with_db_cache(
     session_id: &str,
     state: &AppState,
-) -> Result<(Vec<CompletedTurn>, SessionFileInfo, bool), CommandError> {
-    let file_info = find_session_by_id(session_id)
-        .ok_or_else(|| CommandError::SessionNotFound(session_id.to_string()))?;
+) -> Result<(SharedTurns, SessionFileInfo), CommandError> {
+    let Some(file_info) = find_session_by_id(session_id) else {
+        return get_remote_session_turns(session_id, state);
+    };
 
     // 1. Try in-memory cache first (fastest)
     if let Some(cached_turns) = get_cached_session(session_id, &file_info) {
         tracing::trace!("Session {} found in memory cache", session_id);
-        return Ok(((*cached_turns).to_vec(), file_info, false)); // No need to store, already cached
-    }
-
-    // 2. Check database cache with file mtime validation
-    if let Some(true) = is_db_cache_valid(state, session_id, &file_info.path) {
-        tracing::debug!("Session {} has valid DB cache (mtime match), skipping parse", session_id);
-        // DB cache is valid - the data is already in the database
-        // We still need to parse to get the turns for this request,
-        // but we know the DB data is up to date, so no need to re-store
-        //
-        // Note: This optimization helps when multiple processes/restarts occur.
-        // The in-memory cache gets this data after parsing once.
-        let (turns, _stats) = parse_session_by_id(session_id)
-            .map_err(|e| CommandError::Internal(e.to_string()))?;
+        return Ok((cached_turns, file_info));
+    }
+
+    let db = state.db.get();
+    let current_mtime = get_file_mtime(&file_info.path);
 
-        // Cache in memory for subsequent requests
-        cache_session(session_id, &file_info, turns.clone());
+    // 2. Load stored turns if they were parsed from the current file
+    if let (Some(db), Some(mtime)) = (db, current_mtime.as_deref()) {
+        let stored = db.with_read_connection(|conn| {
+            match db::queries::get_session_turns_mtime(conn, session_id)? {
+                Some(stored_mtime) if mtime_matches(&stored_mtime, mtime) => {
+                    db::queries::get_session_turns_from_db(conn, session_id).map(Some)
+                }
+                _ => Ok(None),
+            }
+        });
 
-        return Ok((turns, file_info, false)); // DB already has current data
+        match stored {
+            Ok(Some(turns)) if !turns.is_empty() => {
+                tracing::debug!("Session {} turns served from DB (mtime match)", session_id);
+                let turns = SharedTurns::from(turns);
+                cache_session(session_id, &file_info, turns.clone(), None);
+                return Ok((turns, file_info));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("Failed to load turns for {} from DB: {:?}", session_id, e);
+                state.diagnostics.record_error("load session turns", e);
+            }
+        }
     }
 
-    tracing::debug!(
-        "Session {} cache miss (not in memory, DB cache invalid/missing), parsing JSONL",
-        session_id
-    );
+    tracing::debug!("Session {} turns not cached, parsing JSONL", session_id);
 
     // 3. Parse the session (cache miss or mtime mismatch)
-    let (turns, _stats) = parse_session_by_id(session_id)
-        .map_err(|e| CommandError::Internal(e.to_string()))?;
+    let turns = parse_session_turns(session_id, &file_info)?;
 
-    // Cache the result in memory
-    cache_session(session_id, &file_info, turns.clone());
+    if let Some(mtime) = current_mtime.as_deref() {
+        store_turns_to_db(state, session_id, &turns, mtime);
+    }
+
+    Ok((turns, file_info))
+}
+
+/// Stored turns of a remote session, whose file is on another machine
+fn get_remote_session_turns(
+    session_id: &str,
+    state: &AppState,
+) -> Result<(SharedTurns, SessionFileInfo), CommandError> {
+    let not_found = || CommandError::SessionNotFound(session_id.to_string());
+    let db = state.db.get().ok_or_else(not_found)?;
+    let stored = db.with_read_connection(|conn| {
+        match db::queries::get_remote_session_info(conn, session_id)? {
+            Some(info) => Ok(Some((db::queries::get_session_turns_from_db(conn, session_id)?, info))),
+            None => Ok(None),
+        }
+    })?;
+    let (turns, file_info) = stored.ok_or_else(not_found)?;
+    Ok((SharedTurns::from(turns), file_info))
+}
+
+/// Store a session's parsed turns, tagged with the file mtime they came from
+///
+/// Does nothing if the session itself hasn't been stored yet. Subagent
+/// sessions the turns spawned are linked to this session, and the session's
+/// anti-patterns are detected again and stored for the history.
+fn store_turns_to_db(state: &AppState, session_id: &str, turns: &[CompletedTurn], mtime: &str) {
+    if let Some(db) = state.db.get() {
+        store_turns(db, session_id, turns, mtime);
+    }
+}
 
-    // Signal that this session needs to be stored to DB (Task 48 handles this)
-    Ok((turns, file_info, true))
+/// `store_turns_to_db` for a given database; also marks the session as
+/// backfilled, since every row derived from its turns is rebuilt
+fn store_turns(db: &db::Database, session_id: &str, turns: &[CompletedTurn], mtime: &str) {
+    let patterns = detect_in_turns(session_id, turns, &AntiPatternType::all(), &DetectionThresholds::default());
+    let detected_at = chrono::Utc::now().to_rfc3339();
+    if let Err(e) = db.with_connection(|conn| {
+        if !db::queries::replace_session_turns(conn, session_id, turns, mtime)? {
+            return Ok(());
+        }
+        let agent_ids: Vec<String> = turns
+            .iter()
+            .flat_map(|t| t.subagent_ids.iter().cloned())
+            .collect();
+        db::queries::link_spawned_subagents(conn, session_id, &agent_ids)?;
+        db::queries::replace_session_antipatterns(conn, session_id, &patterns, &detected_at)?;
+        db::backfill::mark_session_backfilled(conn, session_id)?;
+        Ok(())
+    }) {
+        tracing::warn!("Failed to store turns for {} to DB: {:?}", session_id, e);
+    }
 }
 
 /// Calculate metrics from parsed turns
@@ -803,8 +1413,8 @@ fn calculate_metrics_from_turns(
         session_tokens.add_turn(&turn_tokens);
 
         // Calculate cost for this turn
-        let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-        let turn_cost = calculate_turn_cost(&turn_tokens, model);
+        let model = turn.model.as_deref();
+        let turn_cost = calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at));
         total_breakdown.add(&turn_cost);
 
         // Track tools
@@ -835,8 +1445,8 @@ fn calculate_metrics_from_turns(
     )
 }
 
-/// Convert CompletedTurn to TurnSummary response
-fn turn_to_summary(turn: &CompletedTurn) -> TurnSummary {
+/// Cost of a parsed turn, priced by its model and provider
+fn turn_cost(turn: &CompletedTurn) -> f64 {
     let turn_tokens = TurnTokens::new(
         turn.input_tokens,
         turn.output_tokens,
@@ -845,9 +1455,13 @@ fn turn_to_summary(turn: &CompletedTurn) -> TurnSummary {
         turn.cache_write_1h_tokens,
     );
 
-    let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-    let cost = calculate_turn_cost(&turn_tokens, model);
+    let model = turn.model.as_deref();
+    calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at)).total_cost
+}
 
+/// Convert CompletedTurn to TurnSummary response
+fn turn_to_summary(turn: &CompletedTurn) -> TurnSummary {
+    let cost = turn_cost(turn);
     let tools_used: Vec<String> = turn.tool_uses.iter().map(|t| t.name.clone()).collect();
 
     TurnSummary {
@@ -864,8 +1478,9 @@ fn turn_to_summary(turn: &CompletedTurn) -> TurnSummary {
             cache_read: turn.cache_read_tokens,
             cache_write: turn.cache_write_5m_tokens + turn.cache_write_1h_tokens,
             total: turn.total_tokens,
+            thinking: turn.thinking_tokens,
         },
-        cost: cost.total_cost,
+        cost,
         tool_count: turn.tool_count,
         tools_used,
         has_subagents: turn.has_subagents,
@@ -873,26 +1488,9 @@ fn turn_to_summary(turn: &CompletedTurn) -> TurnSummary {
     }
 }
 
-/// Check if a project path represents a real user project.
-///
-/// Claude Code stores session files under `~/.claude/projects/` in directories
-/// whose names are encoded filesystem paths (e.g., `-Users-nadavbarkai-dev-ironhide`
-/// decodes to `/Users/nadavbarkai/dev/ironhide`).
-///
-/// Some sessions live under temporary/artifact paths like `/private/tmp/madrox-logs-...`
-/// which are not real user projects. This function filters those out by requiring
-/// the decoded path to start with `/Users/`.
-fn is_real_user_project(project_path: &str) -> bool {
-    project_path.starts_with("/Users/")
-}
-
 /// Extract project name from path
 fn extract_project_name(path: &str) -> String {
-    std::path::Path::new(path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("Unknown")
-        .to_string()
+    project_path::project_name(path).unwrap_or("Unknown").to_string()
 }
 
 // ============================================================================
@@ -901,9 +1499,9 @@ fn extract_project_name(path: &str) -> String {
 
 /// Scan and get all sessions with basic metrics
 /// Uses DB-first approach for fast response, falls back to JSONL parsing
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_sessions(
-    state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     limit: Option<i64>,
     offset: Option<i64>,
 ) -> Result<Vec<SessionSummary>, CommandError> {
@@ -912,11 +1510,11 @@ pub async fn get_sessions(
 
     // Try DB-first path for instant response (no JSONL parsing)
     if let Some(db) = state.db.get() {
-        if let Ok(db_sessions) = db.with_connection(|conn| {
+        if let Some(db_sessions) = ok_or_report(&state, "load session list", db.with_read_connection(|conn| {
             db::queries::get_sessions_for_frontend(conn, limit, offset)
-        }) {
+        })) {
             if !db_sessions.is_empty() {
-                let summaries: Vec<SessionSummary> = db_sessions
+                let mut summaries: Vec<SessionSummary> = db_sessions
                     .into_iter()
                     .filter(|s| s.project_path.is_empty() || is_real_user_project(&s.project_path))
                     .map(|s| SessionSummary {
@@ -931,6 +1529,7 @@ pub async fn get_sessions(
                         last_activity_at: s.last_activity_at,
                         model: s.model,
                         total_cost: s.total_cost,
+                        total_cost_including_subagents: s.total_cost,
                         total_turns: s.total_turns,
                         total_tokens: s.total_tokens,
                         duration_ms: s.duration_ms,
@@ -939,6 +1538,7 @@ pub async fn get_sessions(
                         summary: s.summary,
                     })
                     .collect();
+                add_subagent_costs(&state, &mut summaries);
                 return Ok(summaries);
             }
         }
@@ -949,7 +1549,7 @@ pub async fn get_sessions(
 
     // Get or compute summaries from cache, filtering out empty sessions
     // and sessions from temporary/artifact paths
-    let summaries: Vec<SessionSummary> = sessions
+    let mut summaries: Vec<SessionSummary> = sessions
         .iter()
         .map(|file_info| get_cached_summary(file_info))
         .filter(|s| s.total_turns > 0)
@@ -958,25 +1558,13 @@ pub async fn get_sessions(
         .take(limit)
         .collect();
 
+    add_subagent_costs(&state, &mut summaries);
     Ok(summaries)
 }
 
-/// Detect rework cycles by looking for user messages indicating corrections.
+/// Count turns that went back to files changed in an earlier turn
 fn detect_rework_cycles(turns: &[CompletedTurn]) -> u32 {
-    let rework_keywords = [
-        "fix", "wrong", "error", "try again", "doesn't work", "broken",
-        "bug", "incorrect", "failed", "redo", "revert", "not working",
-    ];
-    let mut count = 0u32;
-    for turn in turns {
-        if let Some(ref msg) = turn.user_message {
-            let lower = msg.to_lowercase();
-            if rework_keywords.iter().any(|kw| lower.contains(kw)) {
-                count += 1;
-            }
-        }
-    }
-    count
+    analyze_rework(&extract_code_changes(turns)).rework_cycles
 }
 
 /// Detect clarification cycles from short question-like user messages.
@@ -1001,7 +1589,7 @@ fn detect_clarification_cycles(turns: &[CompletedTurn]) -> u32 {
 
 /// Calculate cost attributable to subagent turns.
 fn calculate_subagent_cost_from_turns(turns: &[CompletedTurn]) -> f64 {
-    use crate::metrics::cost::calculate_turn_cost;
+    use crate::metrics::cost::calculate_provider_turn_cost;
     use crate::metrics::tokens::TurnTokens;
 
     let mut cost = 0.0;
@@ -1014,31 +1602,28 @@ fn calculate_subagent_cost_from_turns(turns: &[CompletedTurn]) -> f64 {
                 turn.cache_write_5m_tokens,
                 turn.cache_write_1h_tokens,
             );
-            let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-            let turn_cost = calculate_turn_cost(&turn_tokens, model);
+            let model = turn.model.as_deref();
+            let turn_cost = calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at));
             cost += turn_cost.total_cost;
         }
     }
     cost
 }
 
-/// Get a single session by ID with full details
-#[tauri::command]
-pub async fn get_session(
-    _state: tauri::State<'_, AppState>,
-    id: String,
-) -> Result<Option<SessionDetail>, CommandError> {
-    let file_info = match find_session_by_id(&id) {
-        Some(f) => f,
-        None => return Ok(None),
-    };
+/// Count tool calls by name across turns, most used first
+fn count_tool_usage(turns: &[CompletedTurn]) -> Vec<(String, u32)> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for tool in turns.iter().flat_map(|t| &t.tool_uses) {
+        *counts.entry(tool.name.clone()).or_insert(0) += 1;
+    }
 
-    let (turns, _) = get_session_turns(&id)?;
-
-    if turns.is_empty() {
-        return Ok(None);
-    }
+    let mut usage: Vec<(String, u32)> = counts.into_iter().collect();
+    usage.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    usage
+}
 
+/// Calculate the full metrics response for a session's turns
+fn build_session_metrics(turns: &[CompletedTurn]) -> SessionMetricsResponse {
     let (
         session_tokens,
         total_breakdown,
@@ -1047,17 +1632,15 @@ pub async fn get_session(
         tool_count,
         subagent_count,
         duration_ms,
-    ) = calculate_metrics_from_turns(&turns);
+    ) = calculate_metrics_from_turns(turns);
 
     // Calculate full metrics
-    let turn_data: Vec<(u64, u32)> = turns
-        .iter()
-        .map(|t| (t.output_tokens, t.tool_count))
-        .collect();
-    let deliverable_units = estimate_deliverable_units_v2(tool_count, &turn_data);
-    let rework_cycles = detect_rework_cycles(&turns);
-    let clarification_cycles = detect_clarification_cycles(&turns);
-    let subagent_cost = calculate_subagent_cost_from_turns(&turns);
+    let deliverables = estimate_deliverables(crate::settings::deliverable_estimator(), turns);
+    let deliverable_units = deliverables.units;
+    let active_time = calculate_active_time(turns);
+    let rework_cycles = detect_rework_cycles(turns);
+    let clarification_cycles = detect_clarification_cycles(turns);
+    let subagent_cost = calculate_subagent_cost_from_turns(turns);
     let turn_count = turns.len() as u32;
 
     let per_turn_tokens: Vec<TurnTokens> = turns.iter().map(|t| {
@@ -1082,12 +1665,9 @@ pub async fn get_session(
     };
 
     let full_metrics = calculate_session_metrics(metrics_input);
+    let thinking_tokens: u64 = turns.iter().map(|t| t.thinking_tokens).sum();
 
-    let started_at = turns.first().map(|t| t.started_at.clone());
-    let last_activity = turns.last().and_then(|t| t.ended_at.clone());
-    let model = models.into_iter().next();
-
-    let metrics = SessionMetricsResponse {
+    SessionMetricsResponse {
         tokens: TokenSummaryResponse {
             input: full_metrics.tokens.input,
             output: full_metrics.tokens.output,
@@ -1096,6 +1676,8 @@ pub async fn get_session(
             cache_write_1h: full_metrics.tokens.cache_write_1h,
             total: full_metrics.tokens.total,
             context_used_pct: full_metrics.tokens.context_used_pct,
+            thinking: thinking_tokens,
+            thinking_share: thinking_share(thinking_tokens, full_metrics.tokens.output),
         },
         cost: CostSummaryResponse {
             input_cost: full_metrics.cost.input_cost,
@@ -1103,8 +1685,11 @@ pub async fn get_session(
             cache_read_cost: full_metrics.cost.cache_read_cost,
             cache_write_cost: full_metrics.cost.cache_write_5m_cost
                 + full_metrics.cost.cache_write_1h_cost,
+            cache_write_5m_cost: full_metrics.cost.cache_write_5m_cost,
+            cache_write_1h_cost: full_metrics.cost.cache_write_1h_cost,
             total_cost: full_metrics.cost.total_cost,
             avg_cost_per_turn: full_metrics.cost.avg_cost_per_turn,
+            thinking_cost: turns.iter().map(turn_thinking_cost).sum(),
         },
         efficiency: EfficiencyResponse {
             cer: full_metrics.efficiency.cer,
@@ -1115,15 +1700,47 @@ pub async fn get_session(
             cpd: full_metrics.efficiency.cpd,
             oes_score: full_metrics.efficiency.oes.overall,
             oes_grade: full_metrics.efficiency.oes.rating.label().to_string(),
+            deliverable_units,
+            deliverable_estimator: deliverables.estimator,
         },
         duration_ms: full_metrics.duration_ms,
+        active_duration_ms: active_time.active_ms,
+        idle_duration_ms: active_time.idle_ms,
         turn_count: full_metrics.turn_count,
         tool_count: full_metrics.tool_count,
         unique_tools: full_metrics.unique_tools,
         models_used: full_metrics.models_used,
         subagent_count,
+        compaction_count: turns.iter().map(|t| t.compactions.len() as u32).sum(),
+        tokens_lost_to_compaction: turns.iter().map(|t| t.tokens_lost_to_compaction()).sum(),
+    }
+}
+
+/// Get a single session by ID with full details
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Option<SessionDetail>, CommandError> {
+    let (turns, file_info) = match get_session_turns_with_db_cache(&id, &state) {
+        Ok(result) => result,
+        Err(CommandError::SessionNotFound(_)) => return Ok(None),
+        Err(e) => return Err(e),
     };
 
+    if turns.is_empty() {
+        return Ok(None);
+    }
+
+    let metrics = build_session_metrics(&turns);
+    let started_at = turns.first().map(|t| t.started_at.clone());
+    let last_activity = turns.last().and_then(|t| t.ended_at.clone());
+    let model = metrics.models_used.first().cloned();
+    let subagent_cost = subagent_costs(&state, &HashSet::from([file_info.session_id.as_str()]))
+        .get(&file_info.session_id)
+        .copied()
+        .unwrap_or(0.0);
+
     Ok(Some(SessionDetail {
         id: file_info.session_id.clone(),
         project_path: file_info.project_path.clone().unwrap_or_default(),
@@ -1133,21 +1750,71 @@ pub async fn get_session(
         model,
         is_subagent: file_info.is_subagent,
         file_path: file_info.path.to_string_lossy().to_string(),
+        total_cost_including_subagents: metrics.cost.total_cost + subagent_cost,
+        wasted_cost: wasted_turn_cost(&state, &file_info.session_id, &turns),
         metrics,
     }))
 }
 
 /// Get the database path
-#[tauri::command]
-pub async fn get_db_path(state: tauri::State<'_, AppState>) -> Result<String, CommandError> {
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_db_path(state: State<'_, AppState>) -> Result<String, CommandError> {
     let db = get_database(&state)?;
     Ok(db.path().to_string_lossy().to_string())
 }
 
+/// Move the database to `path` and reopen it there
+///
+/// `path` is the new database file, or a directory to put `analytics.db`
+/// in; a leading `~` is the home directory. The new location is used from
+/// then on, and on later starts unless `IRONHIDE_DB_PATH` overrides it, in
+/// which case moving is refused. Fails while a background job runs, and can
+/// be retried once it finishes. Returns the new path.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn migrate_db_to(state: State<'_, AppState>, path: String) -> Result<String, CommandError> {
+    let db = get_database(&state)?;
+    if let Some(env_path) = db::env_db_path() {
+        return Err(CommandError::Internal(format!(
+            "The database location is set by {} ({})",
+            db::DB_PATH_ENV,
+            env_path.display()
+        )));
+    }
+
+    let mut target = PathBuf::from(crate::settings::expand_home(path.trim()));
+    if !target.is_absolute() {
+        return Err(CommandError::Internal(format!("Database path must be absolute: {}", path)));
+    }
+    if target.is_dir() {
+        target = target.join("analytics.db");
+    }
+    if target == db.path() {
+        return Ok(target.to_string_lossy().to_string());
+    }
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| CommandError::Internal(format!("Failed to create {}: {}", parent.display(), e)))?;
+    }
+
+    let mut moved_to = state.jobs.moved_to.try_write().map_err(|_| {
+        CommandError::Internal("A background job is running; move the database once it finishes".to_string())
+    })?;
+    let from = db.path();
+    db.move_to(&target)?;
+    *moved_to = Some(target.clone());
+    drop(moved_to);
+
+    if let Err(e) = db::save_db_location(&target) {
+        report_error(&state, "save the database location", &e);
+    }
+    tracing::info!("Moved database from {:?} to {:?}", from, target);
+    Ok(target.to_string_lossy().to_string())
+}
+
 /// Get session metrics by ID
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_session_metrics(
-    _state: tauri::State<'_, AppState>,
+    _state: State<'_, AppState>,
     id: String,
 ) -> Result<Option<SessionMetricsResponse>, CommandError> {
     // Get the session detail which includes metrics
@@ -1156,9 +1823,9 @@ pub async fn get_session_metrics(
 }
 
 /// Get turns for a session with pagination
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_turns(
-    _state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     session_id: String,
     limit: Option<i64>,
     offset: Option<i64>,
@@ -1166,40 +1833,408 @@ pub async fn get_turns(
     let limit = limit.unwrap_or(100) as usize;
     let offset = offset.unwrap_or(0) as usize;
 
-    let (turns, _) = get_session_turns(&session_id)?;
+    let (turns, _) = get_session_turns_with_db_cache(&session_id, &state)?;
 
     let paginated: Vec<TurnSummary> = turns
-        .into_iter()
+        .iter()
         .skip(offset)
         .take(limit)
-        .map(|t| turn_to_summary(&t))
+        .map(turn_to_summary)
         .collect();
 
     Ok(paginated)
 }
 
+/// Get cumulative cost and tokens per turn of a session
+///
+/// Served from the turn costs stored with the session's turns when they're
+/// current; otherwise the session is parsed (storing its turns for next time)
+/// and the curve computed from the parsed turns.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_cost_curve(
+    state: State<'_, AppState>,
+    session_id: String,
+) -> Result<Vec<CostCurvePoint>, CommandError> {
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+
+    if let (Some(db), Some(mtime)) = (state.db.get(), get_file_mtime(&file_info.path)) {
+        let stored = db.with_read_connection(|conn| {
+            match db::queries::get_session_turns_mtime(conn, &session_id)? {
+                Some(stored_mtime) if mtime_matches(&stored_mtime, &mtime) => {
+                    db::queries::get_session_cost_curve_from_db(conn, &session_id).map(Some)
+                }
+                _ => Ok(None),
+            }
+        });
+
+        match stored {
+            Ok(Some(rows)) if !rows.is_empty() => {
+                return Ok(rows
+                    .into_iter()
+                    .map(|r| CostCurvePoint {
+                        turn_number: r.turn_number,
+                        started_at: r.started_at,
+                        model: r.model,
+                        cost: r.cost,
+                        tokens: r.tokens,
+                        cumulative_cost: r.cumulative_cost,
+                        cumulative_tokens: r.cumulative_tokens,
+                    })
+                    .collect());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("Failed to load cost curve for {} from DB: {:?}", session_id, e);
+                state.diagnostics.record_error("load cost curve", e);
+            }
+        }
+    }
+
+    let (turns, _) = get_session_turns_with_db_cache(&session_id, &state)?;
+    Ok(cost_curve_from_turns(&turns))
+}
+
+// ============================================================================
+// Turn Annotations
+// ============================================================================
+
+/// Cost of the turns of a session annotated as wasted
+fn wasted_turn_cost(state: &AppState, session_id: &str, turns: &[CompletedTurn]) -> f64 {
+    let annotations = match state.db.get() {
+        Some(db) => ok_or_report(
+            state,
+            "load turn annotations",
+            db.with_read_connection(|conn| db::queries::get_turn_annotations(conn, Some(session_id))),
+        )
+        .unwrap_or_default(),
+        None => return 0.0,
+    };
+    let wasted: HashSet<u32> = annotations
+        .iter()
+        .filter(|a| a.label == AnnotationLabel::Wasted)
+        .map(|a| a.turn_number)
+        .collect();
+    turns.iter().filter(|t| wasted.contains(&t.turn_number)).map(turn_cost).sum()
+}
+
+/// Mark a turn as wasted or valuable, with an optional note
+///
+/// Replaces the turn's earlier annotation. The cost of wasted turns is
+/// totalled as `wasted_cost` in the session detail and project metrics.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn annotate_turn(
+    state: State<'_, AppState>,
+    session_id: String,
+    turn_number: u32,
+    label: AnnotationLabel,
+    note: Option<String>,
+) -> Result<TurnAnnotation, CommandError> {
+    let db = get_database(&state)?;
+    let (turns, _) = get_session_turns_with_db_cache(&session_id, &state)?;
+    let turn = turns
+        .iter()
+        .find(|t| t.turn_number == turn_number)
+        .ok_or_else(|| CommandError::Internal(format!("Session {} has no turn {}", session_id, turn_number)))?;
+
+    let annotation = TurnAnnotation {
+        session_id,
+        turn_number,
+        label,
+        note: note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+        annotated_at: chrono::Utc::now().to_rfc3339(),
+        cost: turn_cost(turn),
+    };
+    db.with_connection(|conn| db::queries::upsert_turn_annotation(conn, &annotation))?;
+    drop_aggregate_caches();
+
+    Ok(annotation)
+}
+
+/// Remove a turn's annotation. Returns whether it had one.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn remove_turn_annotation(
+    state: State<'_, AppState>,
+    session_id: String,
+    turn_number: u32,
+) -> Result<bool, CommandError> {
+    let db = get_database(&state)?;
+    let removed = db.with_connection(|conn| db::queries::delete_turn_annotation(conn, &session_id, turn_number))?;
+    drop_aggregate_caches();
+    Ok(removed)
+}
+
+/// Get turn annotations, of one session or of all sessions, optionally only
+/// those with `label`
+///
+/// Each carries the cost of its turn as stored in the database, which is 0
+/// for turns not stored yet.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_turn_annotations(
+    state: State<'_, AppState>,
+    session_id: Option<String>,
+    label: Option<AnnotationLabel>,
+) -> Result<Vec<TurnAnnotation>, CommandError> {
+    let db = get_database(&state)?;
+    let mut annotations =
+        db.with_read_connection(|conn| db::queries::get_turn_annotations(conn, session_id.as_deref()))?;
+    if let Some(label) = label {
+        annotations.retain(|a| a.label == label);
+    }
+    Ok(annotations)
+}
+
+/// List the entries of a session file the parser skipped or only partly
+/// understood
+///
+/// Meant for spotting JSONL format changes: unknown entry types and content
+/// blocks come back with their raw payload. Always reads the file, since
+/// nothing about skipped entries is cached.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_parse_diagnostics(
+    _state: State<'_, AppState>,
+    session_id: String,
+) -> Result<ParseDiagnostics, CommandError> {
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+
+    crate::parser::jsonl::parse_streaming(&file_info.path, |_| {})
+        .map_err(|e| CommandError::Parser(e.to_string()))
+}
+
+/// Most raw entries returned per page
+const MAX_RAW_ENTRIES: i64 = 1_000;
+
+/// Get a page of a session file's entries as written, with the type the
+/// parser gave each one
+///
+/// For checking the source data when ironhide's numbers look wrong. Pages
+/// are counted in non-empty lines; `limit` defaults to 100.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_raw_entries(
+    _state: State<'_, AppState>,
+    session_id: String,
+    offset: Option<i64>,
+    limit: Option<i64>,
+) -> Result<RawEntryPage, CommandError> {
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+    let offset = offset.unwrap_or(0).max(0) as usize;
+    let limit = limit.unwrap_or(100).clamp(0, MAX_RAW_ENTRIES) as usize;
+
+    crate::parser::jsonl::read_raw_entries(&file_info.path, offset, limit)
+        .map_err(|e| CommandError::Parser(e.to_string()))
+}
+
+/// Get a session's conversation as ordered user, assistant and tool messages
+///
+/// Secrets are redacted and long tool results truncated unless `options`
+/// say otherwise.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_transcript(
+    _state: State<'_, AppState>,
+    session_id: String,
+    options: Option<TranscriptOptions>,
+) -> Result<Transcript, CommandError> {
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+
+    let mut builder = TranscriptBuilder::new(options.unwrap_or_default());
+    crate::parser::jsonl::parse_streaming(&file_info.path, |entry| builder.process_entry(entry))
+        .map_err(|e| CommandError::Parser(e.to_string()))?;
+
+    Ok(builder.finish(session_id))
+}
+
+/// Get a session's context window usage per turn, with its compactions
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_context_timeline(
+    _state: State<'_, AppState>,
+    session_id: String,
+) -> Result<ContextTimeline, CommandError> {
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+
+    let mut builder = ContextTimelineBuilder::new();
+    crate::parser::jsonl::parse_streaming(&file_info.path, |entry| builder.process_entry(entry))
+        .map_err(|e| CommandError::Parser(e.to_string()))?;
+
+    Ok(builder.finish(session_id))
+}
+
+/// Price each turn and accumulate the session totals
+fn cost_curve_from_turns(turns: &[CompletedTurn]) -> Vec<CostCurvePoint> {
+    let mut cumulative_cost = 0.0;
+    let mut cumulative_tokens = 0u64;
+    turns
+        .iter()
+        .map(|turn| {
+            let tokens = TurnTokens::new(
+                turn.input_tokens,
+                turn.output_tokens,
+                turn.cache_read_tokens,
+                turn.cache_write_5m_tokens,
+                turn.cache_write_1h_tokens,
+            );
+            let model = turn.model.as_deref();
+            let cost = calculate_provider_turn_cost(&tokens, model, turn.provider, Some(&turn.started_at)).total_cost;
+            cumulative_cost += cost;
+            cumulative_tokens += turn.total_tokens;
+            CostCurvePoint {
+                turn_number: turn.turn_number,
+                started_at: turn.started_at.clone(),
+                model: turn.model.clone(),
+                cost,
+                tokens: turn.total_tokens,
+                cumulative_cost,
+                cumulative_tokens,
+            }
+        })
+        .collect()
+}
+
 /// Force refresh of session cache
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn refresh_sessions() -> Result<(), CommandError> {
     clear_all_caches();
     tracing::info!("All session caches cleared");
     Ok(())
 }
 
+/// Re-parse one session and rebuild everything derived from it
+///
+/// Drops the session's cached turns and summary, parses the whole file again
+/// rather than resuming from cached parser state, and stores the session's
+/// metrics, turns and anti-patterns at current pricing. Useful after a
+/// pricing change or parser upgrade, without refreshing every session.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn recompute_session(
+    state: State<'_, AppState>,
+    session_id: String,
+) -> Result<SessionDetail, CommandError> {
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+    forget_session(&session_id);
+
+    let turns = parse_session_turns(&session_id, &file_info)?;
+    let summary = compute_session_summary(&file_info);
+    if let Some(mtime) = get_file_mtime(&file_info.path).filter(|m| !is_past_retention(m)) {
+        let (session_tokens, _, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
+        store_session_to_db(&state, &file_info, &summary, &session_tokens, &mtime);
+        store_turns_to_db(&state, &session_id, &turns, &mtime);
+    }
+    if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
+        list_cache.summaries.insert(session_id.clone(), summary);
+    }
+    clear_aggregate_caches();
+    tracing::info!("Recomputed session {} ({} turns)", session_id, turns.len());
+
+    get_session(state, session_id.clone())
+        .await?
+        .ok_or(CommandError::SessionNotFound(session_id))
+}
+
+/// Fill a summary template with one session's numbers
+///
+/// `template` is the name of a built-in or saved template, or the template
+/// text itself, e.g. `"{project} — {turns} turns, ${cost}, OES {oes}"`.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn summarize_session(
+    state: State<'_, AppState>,
+    session_id: String,
+    template: String,
+) -> Result<String, CommandError> {
+    let template = crate::export::template::resolve(&template, &active_settings().summary_templates);
+    crate::export::template::validate(&template)
+        .map_err(|e| CommandError::Internal(format!("Invalid summary template: {}", e)))?;
+
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+    let detail = get_session(state, session_id.clone())
+        .await?
+        .ok_or(CommandError::SessionNotFound(session_id))?;
+    let metrics = &detail.metrics;
+    let fields = crate::export::template::SummaryFields {
+        project: detail.project_name.clone(),
+        session_id: detail.id.clone(),
+        started_at: detail.started_at.clone(),
+        model: detail.model.clone(),
+        branch: extract_git_branch(&file_info.path),
+        summary: get_cached_summary(&file_info).summary,
+        turns: metrics.turn_count,
+        tools: metrics.tool_count,
+        subagents: metrics.subagent_count,
+        cost: metrics.cost.total_cost,
+        cost_with_subagents: detail.total_cost_including_subagents,
+        tokens: metrics.tokens.total,
+        duration_ms: metrics.duration_ms,
+        active_duration_ms: metrics.active_duration_ms,
+        oes: metrics.efficiency.oes_score,
+        grade: metrics.efficiency.oes_grade.clone(),
+        cer: metrics.efficiency.cer,
+    };
+
+    crate::export::template::render(&template, &fields)
+        .map_err(|e| CommandError::Internal(format!("Invalid summary template: {}", e)))
+}
+
+/// List the built-in summary templates followed by the saved ones
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_summary_templates() -> Result<Vec<crate::export::template::SummaryTemplate>, CommandError> {
+    let mut templates = crate::export::template::builtin_templates();
+    templates.extend(active_settings().summary_templates);
+    Ok(templates)
+}
+
 /// Get a quick count of available sessions
-#[tauri::command]
-pub async fn get_session_count() -> Result<u32, CommandError> {
+///
+/// Counts the sessions with at least one turn without computing summaries:
+/// a cached summary or the turns stored for the file's current mtime answer
+/// for most sessions, and the rest are checked for an assistant entry.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_count(state: State<'_, AppState>) -> Result<u32, CommandError> {
+    use rayon::prelude::*;
+
     let sessions = get_cached_session_list();
+    let summarized: HashMap<String, bool> = SESSION_LIST_CACHE
+        .read()
+        .map(|cache| {
+            cache
+                .summaries
+                .iter()
+                .map(|(id, summary)| (id.clone(), summary.total_turns > 0))
+                .collect()
+        })
+        .unwrap_or_default();
+    let stored = state
+        .db
+        .get()
+        .and_then(|db| {
+            ok_or_report(
+                &state,
+                "load stored turn counts",
+                db.with_read_connection(db::queries::get_stored_turn_counts),
+            )
+        })
+        .unwrap_or_default();
+
     let count = sessions
-        .iter()
-        .map(|file_info| get_cached_summary(file_info))
-        .filter(|s| s.total_turns > 0)
+        .par_iter()
+        .filter(|file_info| {
+            if let Some(has_turns) = summarized.get(&file_info.session_id) {
+                return *has_turns;
+            }
+            match stored.get(&file_info.session_id) {
+                Some((mtime, turns)) if mtime_matches(mtime, &format_mtime(file_info.modified)) => *turns > 0,
+                _ => has_assistant_entry(&file_info.path),
+            }
+        })
         .count();
     Ok(count as u32)
 }
 
 /// Scan for new sessions and return any newly discovered ones
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn scan_new_sessions(
     known_ids: Vec<String>,
 ) -> Result<Vec<SessionSummary>, CommandError> {
@@ -1251,86 +2286,156 @@ fn load_cached_sessions_from_db(state: &AppState) -> HashMap<String, db::queries
         }
     };
 
-    match db.with_connection(|conn| db::queries::get_all_sessions_with_mtime(conn)) {
-        Ok(sessions) => sessions,
-        Err(e) => {
-            tracing::warn!("Failed to load sessions from DB: {:?}", e);
-            HashMap::new()
-        }
-    }
+    ok_or_report(
+        state,
+        "load cached sessions",
+        db.with_read_connection(db::queries::get_all_sessions_with_mtime),
+    )
+    .unwrap_or_default()
 }
 
 /// Store a session and its metrics to the database for persistent caching
 fn store_session_to_db(
     state: &AppState,
-    _file_info: &SessionFileInfo,  // Reserved for future use (e.g., git branch extraction)
+    file_info: &SessionFileInfo,
     summary: &SessionSummary,
     session_tokens: &SessionTokens,
     file_mtime: &str,
 ) {
-    let db = match state.db.get() {
-        Some(db) => db,
-        None => {
-            tracing::debug!("DB not initialized, skipping session store");
-            return;
+    match state.db.get() {
+        Some(db) => store_session(db, file_info, summary, session_tokens, file_mtime),
+        None => tracing::debug!("DB not initialized, skipping session store"),
+    }
+}
+
+/// `store_session_to_db` for a given database
+fn store_session(
+    db: &db::Database,
+    file_info: &SessionFileInfo,
+    summary: &SessionSummary,
+    session_tokens: &SessionTokens,
+    file_mtime: &str,
+) {
+    if let Err(e) = db.with_connection(|conn| {
+        store_session_rows(conn, file_info, summary, session_tokens, file_mtime)
+    }) {
+        tracing::warn!("Failed to store session {} to DB: {:?}", summary.id, e);
+    }
+}
+
+/// A parsed session waiting to be stored: its file, summary, tokens and mtime
+type PendingSession = (SessionFileInfo, SessionSummary, SessionTokens, String);
+
+/// Store a batch of parsed sessions in one transaction
+///
+/// Either the whole batch is committed or none of it, so a preload killed
+/// midway leaves only whole batches behind; sessions of the lost batch are
+/// parsed again on the next run since their mtime isn't stored. `commit`
+/// runs in the same transaction, for progress markers that must not get
+/// ahead of the data.
+fn store_session_batch(
+    db: &db::Database,
+    batch: &[PendingSession],
+    commit: impl FnOnce(&rusqlite::Connection) -> Result<(), db::DbError>,
+) -> Result<(), db::DbError> {
+    db.with_connection(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        for (file_info, summary, session_tokens, file_mtime) in batch {
+            store_session_rows(&tx, file_info, summary, session_tokens, file_mtime)?;
         }
-    };
+        commit(&tx)?;
+        tx.commit()?;
+        Ok(())
+    })
+}
+
+/// Write a session's row, metrics and summary
+fn store_session_rows(
+    conn: &rusqlite::Connection,
+    file_info: &SessionFileInfo,
+    summary: &SessionSummary,
+    session_tokens: &SessionTokens,
+    file_mtime: &str,
+) -> Result<(), db::DbError> {
+    let branch = extract_git_branch(&file_info.path);
 
     // Store session with mtime
-    if let Err(e) = db.with_connection(|conn| {
-        db::queries::upsert_session_with_mtime(
-            conn,
-            &summary.id,
-            &summary.project_path,
-            &summary.project_name,
-            None, // branch - could be extracted from git info
-            &summary.started_at,
-            summary.last_activity_at.as_deref().unwrap_or(&summary.started_at),
-            summary.model.as_deref().unwrap_or("unknown"),
-            false, // is_active
-            &summary.file_path,
-            file_mtime,
-        )?;
-
-        // Compute cache_hit_rate (CER) = cache_read / (cache_read + cache_write)
-        let total_cache_write = session_tokens.total_cache_write_5m + session_tokens.total_cache_write_1h;
-        let total_cache = session_tokens.total_cache_read + total_cache_write;
-        let cache_hit_rate = if total_cache > 0 {
-            session_tokens.total_cache_read as f64 / total_cache as f64
-        } else {
-            0.0
-        };
+    db::queries::upsert_session_with_mtime(
+        conn,
+        &summary.id,
+        &summary.project_path,
+        &summary.project_name,
+        branch.as_deref(),
+        &summary.started_at,
+        summary.last_activity_at.as_deref().unwrap_or(&summary.started_at),
+        summary.model.as_deref().unwrap_or("unknown"),
+        false, // is_active
+        &summary.file_path,
+        file_mtime,
+    )?;
+
+    // Compute cache_hit_rate (CER) = cache_read / (cache_read + cache_write)
+    let total_cache_write = session_tokens.total_cache_write_5m + session_tokens.total_cache_write_1h;
+    let total_cache = session_tokens.total_cache_read + total_cache_write;
+    let cache_hit_rate = if total_cache > 0 {
+        session_tokens.total_cache_read as f64 / total_cache as f64
+    } else {
+        0.0
+    };
 
-        // Peak context % heuristic (no per-turn data available in this function)
-        const MAX_CONTEXT: f64 = 200_000.0;
-        let peak_context_pct = (session_tokens.total_input.max(session_tokens.total_cache_read) as f64
-            / MAX_CONTEXT * 100.0).min(100.0);
-
-        db::queries::upsert_session_metrics(
-            conn,
-            &summary.id,
-            summary.total_turns,
-            summary.duration_ms,
-            summary.total_cost,
-            session_tokens.total_input,
-            session_tokens.total_output,
-            session_tokens.total_cache_read,
-            total_cache_write,
-            0.0, // TODO: efficiency_score (OES) requires deliverable_units, subagent data not available here
-            cache_hit_rate,
-            peak_context_pct,
-        )?;
-
-        // Persist the summary (first user message) so it can be served from DB cache
-        // on subsequent runs without re-parsing the JSONL file.
-        if let Some(ref s) = summary.summary {
+    // Peak context % heuristic (no per-turn data available in this function)
+    const MAX_CONTEXT: f64 = 200_000.0;
+    let peak_context_pct = (session_tokens.total_input.max(session_tokens.total_cache_read) as f64
+        / MAX_CONTEXT * 100.0).min(100.0);
+
+    db::queries::upsert_session_metrics(
+        conn,
+        &summary.id,
+        summary.total_turns,
+        summary.duration_ms,
+        summary.total_cost,
+        session_tokens.total_input,
+        session_tokens.total_output,
+        session_tokens.total_cache_read,
+        total_cache_write,
+        0.0, // TODO: efficiency_score (OES) requires deliverable_units, subagent data not available here
+        cache_hit_rate,
+        peak_context_pct,
+    )?;
+
+    // Persist the summary (first user message) so it can be served from DB cache
+    // on subsequent runs without re-parsing the JSONL file. Without the content
+    // key it's left out, and backfilled like a missing summary once it's back.
+    if let Some(ref s) = summary.summary {
+        if crypto::content_encryption_status() != ContentEncryptionStatus::Locked {
             db::queries::upsert_session_summary(conn, &summary.id, s)?;
         }
+    }
+    if let Some(ref source) = file_info.source {
+        db::queries::update_session_source(conn, &summary.id, source)?;
+    }
+    link_subagent_parent(conn, file_info)?;
+    db::rollups::refresh_session(conn, &summary.id)?;
 
-        Ok(())
-    }) {
-        tracing::warn!("Failed to store session {} to DB: {:?}", summary.id, e);
+    Ok(())
+}
+
+/// Record the parent of a stored subagent session
+///
+/// The `<session-id>/subagents/` directory names the parent directly; older
+/// `agent-*.jsonl` files next to the session files are matched against the
+/// Task calls in stored turns instead.
+fn link_subagent_parent(conn: &rusqlite::Connection, file_info: &SessionFileInfo) -> Result<(), db::DbError> {
+    match file_info.parent_session_id {
+        Some(ref parent_id) => {
+            db::queries::update_session_parent(conn, &file_info.session_id, parent_id)?;
+        }
+        None if file_info.is_subagent || file_info.session_id.starts_with("agent-") => {
+            db::queries::link_subagent_to_spawner(conn, &file_info.session_id)?;
+        }
+        None => {}
     }
+    Ok(())
 }
 
 /// Convert DB cached session data to the SessionSummary format used by commands
@@ -1346,6 +2451,7 @@ fn convert_db_cache_to_summary(
         last_activity_at: cached.last_activity_at.clone(),
         model: cached.model.clone(),
         total_cost: cached.total_cost,
+        total_cost_including_subagents: cached.total_cost,
         total_turns: cached.total_turns,
         total_tokens: cached.total_tokens,
         duration_ms: cached.total_duration_ms,
@@ -1361,10 +2467,14 @@ fn convert_db_cache_to_summary(
 /// On first run: Parses all sessions from JSONL files, stores to SQLite DB
 /// On subsequent runs: Loads from DB (fast), only re-parses if file mtime changed
 ///
+/// Progress of both phases is reported through `AppState::preload`, which
+/// can also cancel them. A preload cancelled before the first phase
+/// completes is resumed by the next call.
+///
 /// Returns the count of sessions loaded
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn preload_all_sessions(
-    state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
 ) -> Result<u32, CommandError> {
     if SESSIONS_PRELOADED.load(Ordering::SeqCst) {
         // Already preloaded, return current count
@@ -1374,6 +2484,9 @@ pub async fn preload_all_sessions(
 
     tracing::info!("Preloading all sessions with persistent DB caching...");
     let start = Instant::now();
+    let control = state.preload.clone();
+    control.begin();
+    let snapshot = AggregateSnapshot::pin();
 
     // Step 1: Load all cached sessions from DB (fast)
     let db_cached_sessions = load_cached_sessions_from_db(&state);
@@ -1383,6 +2496,11 @@ pub async fn preload_all_sessions(
     let file_sessions = scan_claude_sessions();
     let total_count = file_sessions.len();
     tracing::info!("Found {} session files on disk", total_count);
+    if let Ok(db) = get_database(&state) {
+        if let Err(e) = save_project_registry(db) {
+            tracing::warn!("Failed to store project path resolutions: {}", e);
+        }
+    }
 
     // Update the session list cache first
     if let Ok(mut cache) = SESSION_LIST_CACHE.write() {
@@ -1394,6 +2512,7 @@ pub async fn preload_all_sessions(
     let mut cache_hits = 0;
     let mut cache_misses_list: Vec<SessionFileInfo> = Vec::new();
     let preload_limit = 500;
+    let mut reporter = PreloadReporter::new(control.clone(), 1, total_count.min(preload_limit));
     // Collect sessions that have DB cache hits but are missing the summary field.
     // These need a lightweight backfill (extract first user message from JSONL).
     let mut summary_backfill_list: Vec<SessionFileInfo> = Vec::new();
@@ -1425,6 +2544,7 @@ pub async fn preload_all_sessions(
 
         cache_misses_list.push(session.clone());
     }
+    reporter.cache_hits(cache_hits);
 
     // Step 3b: Backfill missing summaries for DB-cached sessions.
     // Uses a lightweight extraction that reads only until the first user message.
@@ -1451,46 +2571,66 @@ pub async fn preload_all_sessions(
         tracing::info!("Backfilled {} summaries", backfilled);
     }
 
-    // Step 4: Parse cache misses in parallel (up to 8 concurrent)
+    // Step 4: Parse cache misses in parallel (up to PARSE_CONCURRENCY at a time)
     let cache_misses = cache_misses_list.len();
     if !cache_misses_list.is_empty() {
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(8));
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(PARSE_CONCURRENCY));
         let mut handles = Vec::new();
 
         for session in cache_misses_list {
             let sem = semaphore.clone();
+            let control = control.clone();
             let handle = tokio::spawn(async move {
                 let _permit = sem.acquire_owned().await.unwrap();
+                if control.is_cancelled() {
+                    return Ok(None);
+                }
                 tokio::task::spawn_blocking(move || {
                     let current_mtime = get_file_mtime(&session.path);
                     let summary = compute_session_summary(&session);
 
-                    let session_tokens = if let Ok((turns, _)) = get_session_turns(&session.session_id) {
-                        let (tokens, _, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
-                        tokens
-                    } else {
-                        SessionTokens::new()
-                    };
+                    let turns = get_session_turns(&session.session_id)
+                        .map(|(turns, _)| turns)
+                        .unwrap_or_default();
+                    let (session_tokens, _, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
 
-                    (session, summary, session_tokens, current_mtime)
+                    Some((session, summary, session_tokens, turns, current_mtime))
                 }).await
             });
             handles.push(handle);
         }
 
-        // Collect results and store to DB + memory cache
-        for handle in handles {
-            if let Ok(Ok((session, summary, session_tokens, current_mtime))) = handle.await {
-                if let Some(ref mtime) = current_mtime {
-                    store_session_to_db(&state, &session, &summary, &session_tokens, mtime);
-                }
+        // Collect results and store to DB + memory cache in batches; sessions
+        // past the retention period are kept out of the DB. A restart skips
+        // the committed batches, whose mtimes now match.
+        let mut batch: Vec<(PendingSession, Arc<[CompletedTurn]>)> = Vec::new();
+        let mut handles = handles.into_iter().peekable();
+        while let Some(handle) = handles.next() {
+            if let Ok(Ok(Some((session, summary, session_tokens, turns, current_mtime)))) = handle.await {
                 if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
-                    list_cache.summaries.insert(session.session_id.clone(), summary);
+                    list_cache.summaries.insert(session.session_id.clone(), summary.clone());
+                }
+                if let Some(mtime) = current_mtime.filter(|m| !is_past_retention(m)) {
+                    batch.push(((session, summary, session_tokens, mtime), turns));
                 }
+                reporter.advance(1);
+            }
+            if batch.len() >= PRELOAD_BATCH_SIZE || (handles.peek().is_none() && !batch.is_empty()) {
+                store_preload_batch(&state, std::mem::take(&mut batch));
             }
         }
     }
 
+    // Publish the new aggregates before announcing the phase is done
+    drop(snapshot);
+    reporter.finish();
+    if control.is_cancelled() {
+        control.end();
+        let loaded = reporter.processed;
+        tracing::info!("Preload cancelled after {} of {} sessions", loaded, reporter.total);
+        return Ok(loaded);
+    }
+
     SESSIONS_PRELOADED.store(true, Ordering::SeqCst);
     let elapsed = start.elapsed();
     tracing::info!(
@@ -1501,195 +2641,920 @@ pub async fn preload_all_sessions(
         elapsed
     );
 
-    // Phase 2: Process remaining sessions in background
+    // Phase 2: Process remaining sessions in a background job, unless
+    // aggressive preloading is turned off; they're then parsed when first
+    // requested
     let remaining_count = file_sessions.len().saturating_sub(preload_limit);
-    if remaining_count > 0 {
-        let remaining_sessions: Vec<SessionFileInfo> = file_sessions.into_iter().skip(preload_limit).collect();
-        // Move the DB cache into the background task for cache-hit checking
-        let db_cache_for_phase2 = db_cached_sessions;
-
-        tokio::spawn(async move {
-            tracing::info!("Phase 2: Processing {} remaining sessions in background...", remaining_sessions.len());
-            let phase2_start = Instant::now();
-            let mut phase2_processed = 0u32;
-            let mut phase2_cached = 0u32;
-            let mut phase2_skipped = 0u32;
-
-            // Create a dedicated DB connection for Phase 2
-            // (Database wraps Mutex<Connection> and doesn't impl Clone)
-            let phase2_db = match db::Database::new(db::default_db_path()) {
-                Ok(db) => {
-                    if let Err(e) = db.initialize() {
-                        tracing::warn!("Phase 2: Failed to initialize DB: {:?}", e);
-                        return;
-                    }
-                    Some(db)
-                }
-                Err(e) => {
-                    tracing::warn!("Phase 2: Failed to open DB connection: {:?}", e);
-                    None
-                }
-            };
+    if remaining_count > 0 && cache_config().aggressive_preload {
+        let payload = serde_json::to_string(&PreloadRemainingPayload { skip: preload_limit })
+            .map_err(|e| CommandError::Internal(e.to_string()))?;
+        if let Err(e) = enqueue_job(&state, JobKind::PreloadRemaining, Some(payload)) {
+            tracing::warn!("Phase 2: Failed to queue the remaining {} sessions: {}", remaining_count, e);
+            control.end();
+        }
+    } else {
+        control.end();
+    }
 
-            // Process in chunks of 50 to avoid overwhelming the system
-            for chunk in remaining_sessions.chunks(50) {
-                // First, separate DB cache hits from misses to avoid unnecessary JSONL parsing
-                let mut chunk_cache_hits: Vec<(&SessionFileInfo, SessionSummary)> = Vec::new();
-                let mut chunk_misses: Vec<SessionFileInfo> = Vec::new();
+    Ok(total_count as u32)
+}
 
-                for session in chunk {
-                    let current_mtime = get_file_mtime(&session.path);
-                    if let Some(cached) = db_cache_for_phase2.get(&session.session_id) {
-                        let is_mtime_match = match (&cached.file_mtime, &current_mtime) {
-                            (Some(stored), Some(current)) => mtime_matches(stored, current),
-                            _ => false,
-                        };
-                        if is_mtime_match {
-                            let summary = convert_db_cache_to_summary(cached, session);
-                            chunk_cache_hits.push((session, summary));
-                            continue;
-                        }
-                    }
-                    chunk_misses.push(session.clone());
-                }
+/// Store a batch of first-phase sessions, then their turns
+fn store_preload_batch(state: &AppState, batch: Vec<(PendingSession, Arc<[CompletedTurn]>)>) {
+    let Some(db) = state.db.get() else {
+        return;
+    };
+    let (sessions, turns): (Vec<PendingSession>, Vec<Arc<[CompletedTurn]>>) = batch.into_iter().unzip();
+    if let Err(e) = store_session_batch(db, &sessions, |_| Ok(())) {
+        tracing::warn!("Failed to store a batch of {} sessions to DB: {:?}", sessions.len(), e);
+        return;
+    }
+    for ((session, _, _, mtime), turns) in sessions.iter().zip(turns) {
+        store_turns(db, &session.session_id, &turns, mtime);
+    }
+}
 
-                // Store cache hits into the memory cache immediately (no JSONL parsing needed)
-                for (session, mut summary) in chunk_cache_hits {
-                    phase2_cached += 1;
-                    // Backfill summary if missing (sessions cached before summary feature)
-                    if summary.summary.is_none() {
-                        if let Some(text) = crate::parser::extract_first_user_message(&session.path) {
-                            if let Some(ref db) = phase2_db {
-                                let _ = db.with_connection(|conn| {
-                                    db::queries::upsert_session_summary(conn, &session.session_id, &text)
-                                });
-                            }
-                            summary.summary = Some(text);
-                        }
-                    }
-                    if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
-                        list_cache.summaries.insert(session.session_id.clone(), summary);
-                    }
+/// Body of a `PreloadRemaining` job: load the sessions after the first
+/// `skip`, from the database where it's current and by parsing otherwise
+///
+/// Each batch is committed together with the job's new `skip`, so an
+/// attempt requeued after the app was killed resumes after the last
+/// committed batch.
+fn run_preload_remaining(
+    db: &db::Database,
+    control: &PreloadControl,
+    job_id: i64,
+    skip: usize,
+) -> Result<JobState, CommandError> {
+    use rayon::prelude::*;
+
+    control.resume();
+    let snapshot = AggregateSnapshot::pin();
+    let db_cached_sessions = match db.with_read_connection(db::queries::get_all_sessions_with_mtime) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            control.end();
+            return Err(e.into());
+        }
+    };
+    let remaining_sessions: Vec<SessionFileInfo> = scan_claude_sessions().into_iter().skip(skip).collect();
+
+    tracing::info!("Phase 2: Processing {} remaining sessions in background...", remaining_sessions.len());
+    let mut reporter = PreloadReporter::new(control.clone(), 2, remaining_sessions.len());
+    let phase2_start = Instant::now();
+    let mut phase2_processed = 0u32;
+    let mut phase2_cached = 0u32;
+    let mut phase2_skipped = 0u32;
+
+    // Process in batches to avoid overwhelming the system
+    for (index, chunk) in remaining_sessions.chunks(PRELOAD_BATCH_SIZE).enumerate() {
+        if control.is_cancelled() {
+            break;
+        }
+        // First, separate DB cache hits from misses to avoid unnecessary JSONL parsing
+        let mut chunk_misses: Vec<&SessionFileInfo> = Vec::new();
+
+        for session in chunk {
+            let current_mtime = get_file_mtime(&session.path);
+            let cached = db_cached_sessions.get(&session.session_id).filter(|cached| {
+                match (&cached.file_mtime, &current_mtime) {
+                    (Some(stored), Some(current)) => mtime_matches(stored, current),
+                    _ => false,
                 }
-
-                // Parse cache misses in parallel
-                if !chunk_misses.is_empty() {
-                    let mut handles = Vec::new();
-                    let semaphore = Arc::new(tokio::sync::Semaphore::new(8));
-
-                    for session in chunk_misses {
-                        let sem = semaphore.clone();
-
-                        let handle = tokio::spawn(async move {
-                            let _permit = sem.acquire_owned().await;
-                            tokio::task::spawn_blocking(move || {
-                                let current_mtime = get_file_mtime(&session.path);
-                                let summary = compute_session_summary(&session);
-
-                                let session_tokens = if let Ok((turns, _)) = get_session_turns(&session.session_id) {
-                                    let (tokens, _, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
-                                    tokens
-                                } else {
-                                    SessionTokens::new()
-                                };
-
-                                (session, summary, session_tokens, current_mtime)
-                            }).await
-                        });
-                        handles.push(handle);
-                    }
-
-                    // Collect results and store to DB + memory cache
-                    for handle in handles {
-                        if let Ok(Ok((session, summary, session_tokens, current_mtime))) = handle.await {
-                            if let Some(ref mtime) = current_mtime {
-                                // Store to DB using the dedicated Phase 2 connection
-                                if let Some(ref db) = phase2_db {
-                                    let total_cache_write = session_tokens.total_cache_write_5m + session_tokens.total_cache_write_1h;
-                                    let total_cache = session_tokens.total_cache_read + total_cache_write;
-                                    let cache_hit_rate = if total_cache > 0 {
-                                        session_tokens.total_cache_read as f64 / total_cache as f64
-                                    } else {
-                                        0.0
-                                    };
-                                    const MAX_CONTEXT: f64 = 200_000.0;
-                                    let peak_context_pct = (session_tokens.total_input.max(session_tokens.total_cache_read) as f64
-                                        / MAX_CONTEXT * 100.0).min(100.0);
-
-                                    let _ = db.with_connection(|conn| {
-                                        db::queries::upsert_session_with_mtime(
-                                            conn,
-                                            &summary.id,
-                                            &summary.project_path,
-                                            &summary.project_name,
-                                            None,
-                                            &summary.started_at,
-                                            summary.last_activity_at.as_deref().unwrap_or(&summary.started_at),
-                                            summary.model.as_deref().unwrap_or("unknown"),
-                                            false,
-                                            &summary.file_path,
-                                            mtime,
-                                        )?;
-                                        db::queries::upsert_session_metrics(
-                                            conn,
-                                            &summary.id,
-                                            summary.total_turns,
-                                            summary.duration_ms,
-                                            summary.total_cost,
-                                            session_tokens.total_input,
-                                            session_tokens.total_output,
-                                            session_tokens.total_cache_read,
-                                            total_cache_write,
-                                            0.0,
-                                            cache_hit_rate,
-                                            peak_context_pct,
-                                        )?;
-                                        if let Some(ref s) = summary.summary {
-                                            db::queries::upsert_session_summary(conn, &summary.id, s)?;
-                                        }
-                                        Ok(())
-                                    });
-                                    phase2_processed += 1;
-                                }
-                            } else {
-                                phase2_skipped += 1;
-                            }
-
-                            // Also cache in memory
-                            if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
-                                list_cache.summaries.insert(session.session_id.clone(), summary);
-                            }
-                        }
-                    }
+            });
+            let cached = match cached {
+                Some(cached) => cached,
+                None => {
+                    chunk_misses.push(session);
+                    continue;
                 }
-            }
+            };
 
-            // Invalidate aggregate caches so next dashboard request picks up Phase 2 data
-            if let Ok(mut cache) = DASHBOARD_CACHE.lock() {
-                cache.data.clear();
+            // Store cache hits into the memory cache immediately (no JSONL parsing needed)
+            phase2_cached += 1;
+            let mut summary = convert_db_cache_to_summary(cached, session);
+            // Backfill summary if missing (sessions cached before summary feature)
+            if summary.summary.is_none() {
+                if let Some(text) = crate::parser::extract_first_user_message(&session.path) {
+                    let _ = db.with_connection(|conn| {
+                        db::queries::upsert_session_summary(conn, &session.session_id, &text)
+                    });
+                    summary.summary = Some(text);
+                }
             }
-            if let Ok(mut cache) = DAILY_CACHE.lock() {
-                cache.data.clear();
+            if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
+                list_cache.summaries.insert(session.session_id.clone(), summary);
             }
-            if let Ok(mut cache) = PROJECT_CACHE.lock() {
-                cache.data.clear();
+            reporter.cache_hits(1);
+        }
+
+        // Parse cache misses in parallel
+        let parsed: Vec<(SessionSummary, SessionTokens, Option<String>)> = chunk_misses
+            .par_iter()
+            .map(|session| {
+                let current_mtime = get_file_mtime(&session.path);
+                let summary = compute_session_summary(session);
+                let session_tokens = match get_session_turns(&session.session_id) {
+                    Ok((turns, _)) => calculate_metrics_from_turns(&turns).0,
+                    Err(_) => SessionTokens::new(),
+                };
+                (summary, session_tokens, current_mtime)
+            })
+            .collect();
+
+        // Store to DB + memory cache; sessions past the retention period are
+        // kept out of the DB
+        let mut batch: Vec<PendingSession> = Vec::new();
+        for (session, (summary, session_tokens, current_mtime)) in chunk_misses.into_iter().zip(parsed) {
+            if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
+                list_cache.summaries.insert(session.session_id.clone(), summary.clone());
             }
+            match current_mtime.filter(|m| !is_past_retention(m)) {
+                Some(mtime) => batch.push((session.clone(), summary, session_tokens, mtime)),
+                None => phase2_skipped += 1,
+            }
+            reporter.advance(1);
+        }
 
-            tracing::info!(
-                "Phase 2 complete: processed {} sessions, cached {}, skipped {} in {:?}",
-                phase2_processed, phase2_cached, phase2_skipped, phase2_start.elapsed()
-            );
-        });
+        let progress = serde_json::to_string(&PreloadRemainingPayload {
+            skip: skip + (index + 1) * PRELOAD_BATCH_SIZE,
+        })
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+        match store_session_batch(db, &batch, |conn| db::jobs::update_job_payload(conn, job_id, &progress)) {
+            Ok(()) => phase2_processed += batch.len() as u32,
+            Err(e) => tracing::warn!("Phase 2: Failed to store a batch of {} sessions: {:?}", batch.len(), e),
+        }
     }
 
-    Ok(total_count as u32)
+    // Aggregates pick up Phase 2 data once the snapshot is released
+    drop(snapshot);
+    reporter.finish();
+    control.end();
+
+    let cancelled = control.is_cancelled();
+    tracing::info!(
+        "Phase 2 {}: processed {} sessions, cached {}, skipped {} in {:?}",
+        if cancelled { "cancelled" } else { "complete" },
+        phase2_processed, phase2_cached, phase2_skipped, phase2_start.elapsed()
+    );
+    Ok(if cancelled { JobState::Cancelled } else { JobState::Succeeded })
+}
+
+/// Stop a running preload
+///
+/// Sessions already loaded stay cached. Returns whether a preload was
+/// running.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn cancel_preload(state: State<'_, AppState>) -> Result<bool, CommandError> {
+    let cancelled = state.preload.cancel();
+    if cancelled {
+        tracing::info!("Preload cancellation requested");
+    }
+    Ok(cancelled)
+}
+
+type WarmupListener = Box<dyn Fn(&ProjectWarmupProgress) + Send + Sync>;
+
+/// Progress reporting for `warm_project_cache`, held in `AppState`
+#[derive(Clone, Default)]
+pub struct ProjectWarmups {
+    /// Projects being warmed up
+    running: Arc<Mutex<HashSet<String>>>,
+    listener: Arc<std::sync::OnceLock<WarmupListener>>,
+}
+
+impl ProjectWarmups {
+    /// Register the receiver of progress updates (the desktop app emits them
+    /// as `project-warmup-progress` events)
+    pub fn set_listener(&self, listener: impl Fn(&ProjectWarmupProgress) + Send + Sync + 'static) {
+        let _ = self.listener.set(Box::new(listener));
+    }
+
+    /// Mark a project as warming up; false if it already is
+    fn begin(&self, project_path: &str) -> bool {
+        self.running
+            .lock()
+            .map(|mut running| running.insert(project_path.to_string()))
+            .unwrap_or(false)
+    }
+
+    fn end(&self, project_path: &str) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(project_path);
+        }
+    }
+
+    fn report(&self, progress: &ProjectWarmupProgress) {
+        if let Some(listener) = self.listener.get() {
+            listener(progress);
+        }
+    }
+}
+
+/// Payload of the `project-warmup-progress` event
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectWarmupProgress {
+    pub project_path: String,
+    pub processed: u32,
+    pub total: u32,
+    pub elapsed_ms: u64,
+    pub done: bool,
+}
+
+/// Outcome of `warm_project_cache`
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectWarmupStats {
+    pub project_path: String,
+    /// Session files of the project, subagents included
+    pub sessions: u32,
+    /// Sessions whose turns were already in memory
+    pub already_cached: u32,
+    /// Sessions loaded from the database or parsed
+    pub loaded: u32,
+    /// Sessions whose file couldn't be read
+    pub failed: u32,
+    pub elapsed_ms: u64,
+}
+
+/// Load every session of a project into the memory caches
+///
+/// Turns come from the database where it's current and are parsed
+/// otherwise (and stored for next time), at most `PARSE_CONCURRENCY` at a
+/// time; summaries are cached alongside. Session details, metrics and
+/// project views then skip the cold parse. Progress is reported as
+/// `project-warmup-progress` events, throttled like preload progress.
+/// Warming up a project that is already warming up fails.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn warm_project_cache(
+    state: State<'_, AppState>,
+    project_path: String,
+) -> Result<ProjectWarmupStats, CommandError> {
+    use rayon::prelude::*;
+
+    if !state.warmups.begin(&project_path) {
+        return Err(CommandError::Internal(format!("{} is already warming up", project_path)));
+    }
+    let start = Instant::now();
+    let sessions: Vec<SessionFileInfo> = get_cached_session_list()
+        .into_iter()
+        .filter(|s| s.project_path.as_deref() == Some(project_path.as_str()))
+        .collect();
+    let total = sessions.len() as u32;
+
+    let processed = AtomicUsize::new(0);
+    let last_report: Mutex<Option<Instant>> = Mutex::new(None);
+    let report = |done: bool| {
+        let progress = ProjectWarmupProgress {
+            project_path: project_path.clone(),
+            processed: processed.load(Ordering::SeqCst) as u32,
+            total,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+            done,
+        };
+        state.warmups.report(&progress);
+    };
+    let warm = |session: &SessionFileInfo| -> (bool, bool) {
+        let already_cached = get_cached_session(&session.session_id, session).is_some();
+        let loaded = already_cached || get_session_turns_with_db_cache(&session.session_id, &state).is_ok();
+        if loaded {
+            get_cached_summary(session);
+        }
+        processed.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut last) = last_report.lock() {
+            if last.is_none_or(|at| at.elapsed() >= PROGRESS_EVENT_INTERVAL) {
+                *last = Some(Instant::now());
+                report(false);
+            }
+        }
+        (already_cached, loaded)
+    };
+
+    let outcomes: Vec<(bool, bool)> = match rayon::ThreadPoolBuilder::new().num_threads(PARSE_CONCURRENCY).build() {
+        Ok(pool) => pool.install(|| sessions.par_iter().map(warm).collect()),
+        Err(e) => {
+            tracing::warn!("Failed to start warm-up threads, warming up sequentially: {}", e);
+            sessions.iter().map(warm).collect()
+        }
+    };
+    report(true);
+    state.warmups.end(&project_path);
+
+    let already_cached = outcomes.iter().filter(|(cached, _)| *cached).count() as u32;
+    let loaded = outcomes.iter().filter(|(cached, loaded)| !*cached && *loaded).count() as u32;
+    let stats = ProjectWarmupStats {
+        project_path,
+        sessions: total,
+        already_cached,
+        loaded,
+        failed: total - already_cached - loaded,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+    };
+    tracing::info!(
+        "Warmed up {}: {} sessions ({} already cached, {} failed) in {}ms",
+        stats.project_path, stats.sessions, stats.already_cached, stats.failed, stats.elapsed_ms
+    );
+    Ok(stats)
+}
+
+// ============================================================================
+// Background Jobs
+// ============================================================================
+
+/// Worker threads running queued jobs, so an export doesn't wait for a
+/// backfill to finish
+const JOB_WORKERS: usize = 2;
+
+/// How long an idle worker sleeps before checking for retries that came due
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Days finished jobs stay listed by `list_jobs`
+const JOB_HISTORY_DAYS: i64 = 7;
+
+type JobListener = Box<dyn Fn(&Job) + Send + Sync>;
+
+/// Runs the jobs in the `jobs` table on background threads, held in `AppState`
+///
+/// The workers start with the first job queued, or at startup when jobs were
+/// left over from the last run, and each have their own database connection.
+#[derive(Clone, Default)]
+pub struct JobQueue {
+    started: Arc<AtomicBool>,
+    /// Bumped whenever a job is queued, to wake idle workers
+    wake: Arc<(Mutex<u64>, std::sync::Condvar)>,
+    listener: Arc<std::sync::OnceLock<JobListener>>,
+    /// Where the database was moved to while the workers were running;
+    /// workers hold it shared while they claim and run a job, so the
+    /// database can't move under a running job
+    moved_to: Arc<std::sync::RwLock<Option<PathBuf>>>,
+}
+
+impl JobQueue {
+    /// Register the receiver of job state changes (the desktop app emits them
+    /// as `job-updated` events)
+    pub fn set_listener(&self, listener: impl Fn(&Job) + Send + Sync + 'static) {
+        let _ = self.listener.set(Box::new(listener));
+    }
+
+    fn report(&self, job: &Job) {
+        if let Some(listener) = self.listener.get() {
+            listener(job);
+        }
+    }
+
+    fn notify(&self) {
+        let (queued, wake) = &*self.wake;
+        *queued.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        wake.notify_all();
+    }
+
+    /// Sleep until a job is queued after `seen` or the poll interval passes
+    fn wait(&self, seen: &mut u64) {
+        let (queued, wake) = &*self.wake;
+        let guard = queued.lock().unwrap_or_else(|e| e.into_inner());
+        let (guard, _) = wake
+            .wait_timeout_while(guard, JOB_POLL_INTERVAL, |queued| *queued == *seen)
+            .unwrap_or_else(|e| e.into_inner());
+        *seen = *guard;
+    }
+}
+
+/// What the workers need from `AppState`; the controls report progress and
+/// carry cancellations like they do for work started directly
+#[derive(Clone)]
+struct JobContext {
+    queue: JobQueue,
+    preload: PreloadControl,
+    backfill: BackfillControl,
+    exports: ExportJobs,
+}
+
+/// Arguments of a `PreloadRemaining` job
+#[derive(Debug, Serialize, serde::Deserialize)]
+struct PreloadRemainingPayload {
+    /// Sessions already loaded, by the first phase and by the batches this
+    /// job has committed so far
+    skip: usize,
+}
+
+/// Sessions stored per transaction while preloading
+const PRELOAD_BATCH_SIZE: usize = 50;
+
+/// Arguments of an `Export` job
+#[derive(Debug, Serialize, serde::Deserialize)]
+struct ExportPayload {
+    session_ids: Option<Vec<String>>,
+    options: ExportOptions,
+}
+
+/// ID of the export status a queued export reports to
+fn export_job_id(job_id: i64) -> String {
+    format!("job-{}", job_id)
+}
+
+/// Queue a job and wake the workers, starting them if needed
+///
+/// Returns the job's ID and whether it was newly queued; see
+/// `db::jobs::enqueue_job`.
+fn enqueue_job(state: &AppState, kind: JobKind, payload: Option<String>) -> Result<(i64, bool), CommandError> {
+    let db = get_database(state)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let (job_id, added) = db.with_connection(|conn| db::jobs::enqueue_job(conn, kind, payload.as_deref(), &now))?;
+    if added {
+        if let Ok(Some(job)) = db.with_read_connection(|conn| db::jobs::get_job(conn, job_id)) {
+            state.jobs.report(&job);
+        }
+    }
+    start_job_workers(state);
+    state.jobs.notify();
+    Ok((job_id, added))
+}
+
+/// Start the job workers, once
+fn start_job_workers(state: &AppState) {
+    let db = match state.db.get() {
+        Some(db) => db,
+        None => return,
+    };
+    if state.jobs.started.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let context = JobContext {
+        queue: state.jobs.clone(),
+        preload: state.preload.clone(),
+        backfill: state.backfill.clone(),
+        exports: state.exports.clone(),
+    };
+    for worker in 0..JOB_WORKERS {
+        let worker_db = match db::Database::new(db.path()) {
+            Ok(worker_db) => worker_db,
+            Err(e) => {
+                tracing::warn!("Failed to open a database connection for job worker {}: {:?}", worker, e);
+                continue;
+            }
+        };
+        let context = context.clone();
+        let spawned = std::thread::Builder::new()
+            .name(format!("job-worker-{}", worker))
+            .spawn(move || run_job_worker(&worker_db, &context));
+        if let Err(e) = spawned {
+            tracing::warn!("Failed to start job worker {}: {}", worker, e);
+        }
+    }
+}
+
+/// Put the jobs the app was closed in the middle of back in the queue and
+/// start working on them; also forgets jobs finished long ago
+pub fn resume_jobs(state: &AppState) {
+    let db = match state.db.get() {
+        Some(db) => db,
+        None => return,
+    };
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(JOB_HISTORY_DAYS)).to_rfc3339();
+    let resumed = db.with_connection(|conn| {
+        db::jobs::prune_finished_jobs(conn, &cutoff)?;
+        db::jobs::requeue_interrupted_jobs(conn)
+    });
+    match resumed {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Requeued {} interrupted jobs", count),
+        Err(e) => tracing::warn!("Failed to requeue interrupted jobs: {:?}", e),
+    }
+
+    // Backfills from before the job queue only have their run recorded
+    resume_interrupted_backfill(state);
+    start_job_workers(state);
+}
+
+/// Claim and run jobs until the app exits
+fn run_job_worker(db: &db::Database, context: &JobContext) {
+    let mut seen = 0;
+    loop {
+        let moved_to = context.queue.moved_to.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(path) = moved_to.as_ref().filter(|path| **path != db.path()) {
+            if let Err(e) = db.reopen(path) {
+                tracing::warn!("Failed to reopen the database at {:?} for a job worker: {:?}", path, e);
+            }
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        let job = match db.with_connection(|conn| db::jobs::claim_next_job(conn, &now)) {
+            Ok(Some(job)) => job,
+            Ok(None) => {
+                drop(moved_to);
+                context.queue.wait(&mut seen);
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to claim a job: {:?}", e);
+                drop(moved_to);
+                context.queue.wait(&mut seen);
+                continue;
+            }
+        };
+        context.queue.report(&job);
+        tracing::info!("Running job {} ({}, attempt {})", job.id, job.kind.as_str(), job.attempts);
+
+        let result = run_job(db, context, &job);
+        let now = chrono::Utc::now().to_rfc3339();
+        let recorded = match result {
+            Ok(outcome) => db.with_connection(|conn| db::jobs::finish_job(conn, job.id, outcome, &now)),
+            Err(e) => {
+                tracing::warn!("Job {} ({}) failed: {}", job.id, job.kind.as_str(), e);
+                db.with_connection(|conn| db::jobs::fail_job(conn, job.id, &e.to_string(), &now).map(|_| ()))
+            }
+        };
+        if let Err(e) = recorded {
+            tracing::warn!("Failed to record the outcome of job {}: {:?}", job.id, e);
+        }
+        if let Ok(Some(job)) = db.with_read_connection(|conn| db::jobs::get_job(conn, job.id)) {
+            context.queue.report(&job);
+        }
+        drop(moved_to);
+    }
+}
+
+/// Run one job; returns how it ended unless it failed
+fn run_job(db: &db::Database, context: &JobContext, job: &Job) -> Result<JobState, CommandError> {
+    fn payload<T: serde::de::DeserializeOwned>(job: &Job) -> Result<T, CommandError> {
+        serde_json::from_str(job.payload.as_deref().unwrap_or("null"))
+            .map_err(|e| CommandError::Internal(format!("Invalid payload for job {}: {}", job.id, e)))
+    }
+
+    match job.kind {
+        JobKind::Backfill => run_backfill_job(db, &context.backfill),
+        JobKind::PreloadRemaining => {
+            let payload: PreloadRemainingPayload = payload(job)?;
+            run_preload_remaining(db, &context.preload, job.id, payload.skip)
+        }
+        JobKind::Export => {
+            let payload: ExportPayload = payload(job)?;
+            let format = payload.options.format.parse::<ExportFormat>()?;
+            let export = context.exports.get_or_start(&export_job_id(job.id));
+            let result = write_session_export(&export, payload.session_ids, &payload.options, format);
+            let error = result.as_ref().err().map(|e| e.to_string());
+            export.finish(result);
+            match error {
+                Some(error) => Err(CommandError::Internal(error)),
+                None if export.is_cancelled() => Ok(JobState::Cancelled),
+                None => Ok(JobState::Succeeded),
+            }
+        }
+        JobKind::DailyDigest => {
+            let payload: DailyDigestPayload = payload(job)?;
+            run_daily_digest_job(db, job, &payload.date)
+        }
+    }
+}
+
+/// List background jobs, newest first
+///
+/// Queued and running jobs are always included, plus up to `limit` (default
+/// 50) of the most recent ones.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn list_jobs(state: State<'_, AppState>, limit: Option<u32>) -> Result<Vec<Job>, CommandError> {
+    let db = get_database(&state)?;
+    Ok(db.with_read_connection(|conn| db::jobs::list_jobs(conn, limit.unwrap_or(50)))?)
+}
+
+/// Cancel a queued or running job
+///
+/// A queued job is dropped before it starts; a running one is stopped like
+/// `cancel_backfill`, `cancel_preload` or `cancel_export` would. Returns
+/// whether the job was still queued or running.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn cancel_job(state: State<'_, AppState>, job_id: i64) -> Result<bool, CommandError> {
+    let db = get_database(&state)?;
+    let job = match db.with_read_connection(|conn| db::jobs::get_job(conn, job_id))? {
+        Some(job) => job,
+        None => return Ok(false),
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let dropped = db.with_connection(|conn| db::jobs::cancel_queued_job(conn, job_id, &now))?;
+    if dropped {
+        // Queued backfills and preloads already hold their control
+        match job.kind {
+            JobKind::Backfill => {
+                state.backfill.cancel();
+                state.backfill.end();
+            }
+            JobKind::PreloadRemaining => {
+                state.preload.cancel();
+                state.preload.end();
+            }
+            JobKind::Export => state.exports.get_or_start(&export_job_id(job_id)).cancel_unstarted(),
+            JobKind::DailyDigest => {}
+        }
+        if let Ok(Some(job)) = db.with_read_connection(|conn| db::jobs::get_job(conn, job_id)) {
+            state.jobs.report(&job);
+        }
+        return Ok(true);
+    }
+
+    Ok(job.state == JobState::Running
+        && match job.kind {
+            JobKind::Backfill => state.backfill.cancel(),
+            JobKind::PreloadRemaining => state.preload.cancel(),
+            JobKind::Export => state.exports.cancel(&export_job_id(job_id)),
+            // Posting isn't interruptible
+            JobKind::DailyDigest => false,
+        })
+}
+
+// ============================================================================
+// Database Backfill
+// ============================================================================
+
+/// Sessions parsed in parallel per backfill batch; progress is stored after
+/// each one
+const BACKFILL_BATCH_SIZE: usize = 25;
+
+type BackfillListener = Box<dyn Fn(&BackfillProgress) + Send + Sync>;
+
+/// Cancellation and progress reporting for `backfill_database`, held in
+/// `AppState`
+#[derive(Clone, Default)]
+pub struct BackfillControl {
+    running: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    listener: Arc<std::sync::OnceLock<BackfillListener>>,
+}
+
+impl BackfillControl {
+    /// Register the receiver of progress updates (the desktop app emits them
+    /// as `backfill-progress` events)
+    pub fn set_listener(&self, listener: impl Fn(&BackfillProgress) + Send + Sync + 'static) {
+        let _ = self.listener.set(Box::new(listener));
+    }
+
+    /// Ask a running backfill to stop; returns whether one was running
+    pub fn cancel(&self) -> bool {
+        let running = self.running.load(Ordering::SeqCst);
+        if running {
+            self.cancelled.store(true, Ordering::SeqCst);
+        }
+        running
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Mark a backfill as running; false if one already is
+    fn begin(&self) -> bool {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        self.cancelled.store(false, Ordering::SeqCst);
+        true
+    }
+
+    /// Mark a queued backfill as running, keeping a cancellation requested
+    /// while it waited
+    fn resume(&self) {
+        self.running.store(true, Ordering::SeqCst);
+    }
+
+    fn end(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    fn report(&self, progress: &BackfillProgress) {
+        if let Some(listener) = self.listener.get() {
+            listener(progress);
+        }
+    }
+}
+
+/// Payload of the `backfill-progress` event
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BackfillProgress {
+    pub processed: u32,
+    pub total: u32,
+    /// Sessions whose file couldn't be parsed
+    pub failed: u32,
+    /// Sessions stored before the app was closed, when resuming a run
+    pub previously_processed: u32,
+    pub elapsed_ms: u64,
+    pub done: bool,
+    pub cancelled: bool,
+}
+
+/// Re-parse sessions that are missing rows added by later schema changes
+///
+/// Session files not in the database, and stored sessions whose turns,
+/// model breakdowns and anti-patterns predate the current backfill version,
+/// are parsed again by a background job, most recently modified first.
+/// Progress is reported as `backfill-progress` events. Each session is
+/// marked as it's stored, so a backfill cut short by closing the app resumes
+/// at the next start with the sessions still missing.
+///
+/// Returns the number of sessions queued: 0 if nothing is missing or a
+/// backfill is already queued or running.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn backfill_database(state: State<'_, AppState>) -> Result<u32, CommandError> {
+    queue_backfill(&state)
+}
+
+/// Stop a running backfill; sessions already stored keep their rows
+///
+/// A cancelled backfill isn't resumed at the next start. Returns whether one
+/// was running.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn cancel_backfill(state: State<'_, AppState>) -> Result<bool, CommandError> {
+    Ok(state.backfill.cancel())
+}
+
+/// Queue a backfill whose run was recorded as unfinished
+fn resume_interrupted_backfill(state: &AppState) {
+    let db = match state.db.get() {
+        Some(db) => db,
+        None => return,
+    };
+    match db.with_read_connection(db::backfill::get_interrupted_run) {
+        Ok(Some(run)) => {
+            tracing::info!("Resuming backfill started {} ({} sessions done)", run.started_at, run.processed);
+            if let Err(e) = queue_backfill(state) {
+                tracing::warn!("Failed to resume backfill: {:?}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to check for an interrupted backfill: {:?}", e),
+    }
+}
+
+/// Queue a `Backfill` job if any session is behind the backfill version;
+/// returns how many are
+fn queue_backfill(state: &AppState) -> Result<u32, CommandError> {
+    let db = get_database(state)?;
+    let control = &state.backfill;
+    if !control.begin() {
+        return Ok(0);
+    }
+
+    let queued = backfill_sessions(db).map_err(CommandError::from).and_then(|sessions| {
+        if sessions.is_empty() {
+            close_interrupted_backfill(db)?;
+            return Ok(0);
+        }
+        enqueue_job(state, JobKind::Backfill, None)?;
+        Ok(sessions.len() as u32)
+    });
+    if !matches!(queued, Ok(count) if count > 0) {
+        control.end();
+    }
+    queued
+}
+
+/// Body of a `Backfill` job, on the worker's database connection
+fn run_backfill_job(db: &db::Database, control: &BackfillControl) -> Result<JobState, CommandError> {
+    control.resume();
+    let started = backfill_sessions(db).and_then(|sessions| {
+        if sessions.is_empty() {
+            close_interrupted_backfill(db)?;
+            return Ok(None);
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        let run = db.with_connection(|conn| db::backfill::start_run(conn, &now))?;
+        Ok(Some((run, sessions)))
+    });
+    let (run, sessions) = match started {
+        Ok(Some(started)) => started,
+        Ok(None) => {
+            control.end();
+            return Ok(JobState::Succeeded);
+        }
+        Err(e) => {
+            control.end();
+            return Err(e.into());
+        }
+    };
+
+    tracing::info!("Backfilling {} sessions", sessions.len());
+    run_backfill(db, control, &run, &sessions);
+    Ok(if control.is_cancelled() { JobState::Cancelled } else { JobState::Succeeded })
+}
+
+/// Session files behind the backfill version, most recently modified first
+fn backfill_sessions(db: &db::Database) -> Result<Vec<SessionFileInfo>, db::DbError> {
+    let versions = db.with_read_connection(db::backfill::get_backfill_versions)?;
+    let mut sessions: Vec<SessionFileInfo> = scan_claude_sessions()
+        .into_iter()
+        .filter(|s| {
+            versions
+                .get(&s.session_id)
+                .is_none_or(|v| *v < db::backfill::BACKFILL_VERSION)
+        })
+        .filter(|s| get_file_mtime(&s.path).is_some_and(|m| !is_past_retention(&m)))
+        .collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.modified));
+    Ok(sessions)
+}
+
+/// Close a recorded run that has nothing left to do
+fn close_interrupted_backfill(db: &db::Database) -> Result<(), db::DbError> {
+    let now = chrono::Utc::now().to_rfc3339();
+    db.with_connection(|conn| match db::backfill::get_interrupted_run(conn)? {
+        Some(run) => db::backfill::finish_run(conn, run.id, &now, false),
+        None => Ok(()),
+    })
+}
+
+/// Body of a backfill; stops between batches once cancelled
+fn run_backfill(
+    db: &db::Database,
+    control: &BackfillControl,
+    run: &db::backfill::BackfillRun,
+    sessions: &[SessionFileInfo],
+) {
+    use rayon::prelude::*;
+
+    let started = Instant::now();
+    let mut last_report: Option<Instant> = None;
+    let mut progress = BackfillProgress {
+        processed: 0,
+        total: sessions.len() as u32,
+        failed: 0,
+        previously_processed: run.processed,
+        elapsed_ms: 0,
+        done: false,
+        cancelled: false,
+    };
+
+    for batch in sessions.chunks(BACKFILL_BATCH_SIZE) {
+        if control.is_cancelled() {
+            break;
+        }
+
+        let parsed: Vec<Option<(SessionSummary, SharedTurns, String)>> = batch
+            .par_iter()
+            .map(|file_info| {
+                let mtime = get_file_mtime(&file_info.path)?;
+                let summary = compute_session_summary(file_info);
+                let (turns, _) = get_session_turns(&file_info.session_id).ok()?;
+                Some((summary, turns, mtime))
+            })
+            .collect();
+
+        for (file_info, parsed) in batch.iter().zip(parsed) {
+            let (summary, turns, mtime) = match parsed {
+                Some(parsed) => parsed,
+                None => {
+                    progress.failed += 1;
+                    continue;
+                }
+            };
+            let (session_tokens, _, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
+            store_session(db, file_info, &summary, &session_tokens, &mtime);
+            store_turns(db, &file_info.session_id, &turns, &mtime);
+            if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
+                list_cache.summaries.insert(file_info.session_id.clone(), summary);
+            }
+            progress.processed += 1;
+        }
+
+        if let Err(e) = db.with_connection(|conn| {
+            db::backfill::record_run_progress(conn, run.id, run.processed + progress.processed)
+        }) {
+            tracing::warn!("Failed to record backfill progress: {:?}", e);
+        }
+        if last_report.is_none_or(|at| at.elapsed() >= PROGRESS_EVENT_INTERVAL) {
+            last_report = Some(Instant::now());
+            progress.elapsed_ms = started.elapsed().as_millis() as u64;
+            control.report(&progress);
+        }
+    }
+
+    progress.cancelled = control.is_cancelled();
+    let finished_at = chrono::Utc::now().to_rfc3339();
+    if let Err(e) = db.with_connection(|conn| {
+        db::backfill::finish_run(conn, run.id, &finished_at, progress.cancelled)
+    }) {
+        tracing::warn!("Failed to finish backfill run: {:?}", e);
+    }
+    clear_derived_caches();
+
+    progress.done = true;
+    progress.elapsed_ms = started.elapsed().as_millis() as u64;
+    control.report(&progress);
+    control.end();
+    tracing::info!(
+        "Backfill {}: {} sessions stored, {} failed in {:?}",
+        if progress.cancelled { "cancelled" } else { "complete" },
+        progress.processed,
+        progress.failed,
+        started.elapsed()
+    );
 }
 
 /// Get sessions filtered by date range efficiently
 /// Uses DB-first approach for fast response, falls back to JSONL parsing
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_sessions_filtered(
-    state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     start_date: Option<String>,
     end_date: Option<String>,
     limit: Option<i64>,
@@ -1700,7 +3565,7 @@ pub async fn get_sessions_filtered(
 
     // Try DB-first path for instant response (no JSONL parsing)
     if let Some(db) = state.db.get() {
-        if let Ok(db_sessions) = db.with_connection(|conn| {
+        if let Some(db_sessions) = ok_or_report(&state, "load session list", db.with_read_connection(|conn| {
             db::queries::get_sessions_for_frontend_filtered(
                 conn,
                 start_date.as_deref(),
@@ -1708,9 +3573,9 @@ pub async fn get_sessions_filtered(
                 limit,
                 offset,
             )
-        }) {
+        })) {
             if !db_sessions.is_empty() {
-                let summaries: Vec<SessionSummary> = db_sessions
+                let mut summaries: Vec<SessionSummary> = db_sessions
                     .into_iter()
                     .filter(|s| s.project_path.is_empty() || is_real_user_project(&s.project_path))
                     .map(|s| SessionSummary {
@@ -1725,6 +3590,7 @@ pub async fn get_sessions_filtered(
                         last_activity_at: s.last_activity_at,
                         model: s.model,
                         total_cost: s.total_cost,
+                        total_cost_including_subagents: s.total_cost,
                         total_turns: s.total_turns,
                         total_tokens: s.total_tokens,
                         duration_ms: s.duration_ms,
@@ -1733,6 +3599,7 @@ pub async fn get_sessions_filtered(
                         summary: s.summary,
                     })
                     .collect();
+                add_subagent_costs(&state, &mut summaries);
                 return Ok(summaries);
             }
         }
@@ -1742,7 +3609,7 @@ pub async fn get_sessions_filtered(
     let sessions = get_cached_session_list();
 
     // Get summaries, filter out empty sessions, temp paths, and filter by date
-    let summaries: Vec<SessionSummary> = sessions
+    let mut summaries: Vec<SessionSummary> = sessions
         .iter()
         .map(|file_info| get_cached_summary(file_info))
         .filter(|s| s.total_turns > 0)
@@ -1753,10 +3620,11 @@ pub async fn get_sessions_filtered(
                 return true; // Include sessions with unknown dates
             }
 
-            if summary.started_at.len() < 10 {
-                return true; // Include sessions with short date strings
-            }
-            let session_date = &summary.started_at[..10]; // Extract YYYY-MM-DD
+            let session_date = match local_date(&summary.started_at) {
+                Some(date) => date,
+                None => return true, // Include sessions without a readable date
+            };
+            let session_date = session_date.as_str();
 
             if let Some(ref start) = start_date {
                 if session_date < start.as_str() {
@@ -1774,6 +3642,7 @@ pub async fn get_sessions_filtered(
         .take(limit)
         .collect();
 
+    add_subagent_costs(&state, &mut summaries);
     Ok(summaries)
 }
 
@@ -1784,18 +3653,18 @@ pub async fn get_sessions_filtered(
 /// to ensure the session list matches the aggregate metrics shown on
 /// project cards.
 /// Uses DB-first approach for fast response, falls back to JSONL parsing.
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_sessions_by_project(
-    state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     project_path: String,
 ) -> Result<Vec<SessionSummary>, CommandError> {
     // Try DB-first path for instant response (no JSONL parsing)
     if let Some(db) = state.db.get() {
-        if let Ok(db_sessions) = db.with_connection(|conn| {
+        if let Some(db_sessions) = ok_or_report(&state, "load project sessions", db.with_read_connection(|conn| {
             db::queries::get_sessions_for_frontend_by_project(conn, &project_path)
-        }) {
+        })) {
             if !db_sessions.is_empty() {
-                let summaries: Vec<SessionSummary> = db_sessions
+                let mut summaries: Vec<SessionSummary> = db_sessions
                     .into_iter()
                     .map(|s| SessionSummary {
                         id: s.session_id,
@@ -1809,6 +3678,7 @@ pub async fn get_sessions_by_project(
                         last_activity_at: s.last_activity_at,
                         model: s.model,
                         total_cost: s.total_cost,
+                        total_cost_including_subagents: s.total_cost,
                         total_turns: s.total_turns,
                         total_tokens: s.total_tokens,
                         duration_ms: s.duration_ms,
@@ -1817,6 +3687,7 @@ pub async fn get_sessions_by_project(
                         summary: s.summary,
                     })
                     .collect();
+                add_subagent_costs(&state, &mut summaries);
                 return Ok(summaries);
             }
         }
@@ -1825,7 +3696,7 @@ pub async fn get_sessions_by_project(
     // Fallback: Use cached session list with JSONL parsing
     let sessions = get_cached_session_list();
 
-    let summaries: Vec<SessionSummary> = sessions
+    let mut summaries: Vec<SessionSummary> = sessions
         .iter()
         .filter(|file_info| {
             file_info.project_path.as_deref() == Some(project_path.as_str())
@@ -1834,6 +3705,7 @@ pub async fn get_sessions_by_project(
         .filter(|s| s.total_turns > 0)
         .collect();
 
+    add_subagent_costs(&state, &mut summaries);
     Ok(summaries)
 }
 
@@ -1841,9 +3713,9 @@ pub async fn get_sessions_by_project(
 ///
 /// Returns information about all subagents spawned during the session,
 /// including their costs, tokens, and tools used.
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_session_subagents(
-    _state: tauri::State<'_, AppState>,
+    _state: State<'_, AppState>,
     session_id: String,
 ) -> Result<Vec<SubagentSummary>, CommandError> {
     let (turns, _) = get_session_turns(&session_id)?;
@@ -1851,7 +3723,7 @@ pub async fn get_session_subagents(
     // Collect all subagent IDs from the session turns
     let mut subagent_info: HashMap<String, SubagentSummary> = HashMap::new();
 
-    for turn in &turns {
+    for turn in turns.iter() {
         for agent_id in &turn.subagent_ids {
             let entry = subagent_info.entry(agent_id.clone()).or_insert_with(|| {
                 SubagentSummary {
@@ -1905,13 +3777,74 @@ pub async fn get_session_subagents(
     Ok(subagent_info.into_values().collect())
 }
 
+/// Get the subagent hierarchy for a session
+///
+/// Returns the session as the root node, with the subagents it spawned as
+/// children and subagents spawned by those nested below them. Every node
+/// carries its own cost and tokens plus rollups including its descendants.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_subagent_tree(
+    _state: State<'_, AppState>,
+    session_id: String,
+) -> Result<SubagentNode, CommandError> {
+    let (turns, file_info) = get_session_turns(&session_id)?;
+    if file_info.is_subagent {
+        return Err(CommandError::Internal(format!(
+            "{} is a subagent, not a session",
+            session_id
+        )));
+    }
+
+    let mut spawns = HashMap::new();
+    spawns.insert(session_id.clone(), extract_subagent_spawns(&file_info.path));
+    let root = subagent_node(&session_id, false, &turns);
+
+    // Subagent logs live in <project>/<session-id>/subagents/, for archived
+    // `<session-id>.jsonl.gz` sessions too
+    let subagents_dir = file_info.path.with_file_name(&session_id).join("subagents");
+    let mut agents = Vec::new();
+    for agent_file in get_cached_session_list()
+        .iter()
+        .filter(|s| s.is_subagent && s.path.parent() == Some(subagents_dir.as_path()))
+    {
+        let agent_turns = match get_cached_session(&agent_file.session_id, agent_file) {
+            Some(cached) => cached,
+            None => parse_session_turns(&agent_file.session_id, agent_file)?,
+        };
+        spawns.insert(agent_file.session_id.clone(), extract_subagent_spawns(&agent_file.path));
+        agents.push(subagent_node(&agent_file.session_id, true, &agent_turns));
+    }
+
+    Ok(build_subagent_tree(root, agents, &spawns))
+}
+
+/// Build an unlinked tree node from a session's or subagent's turns
+fn subagent_node(id: &str, is_subagent: bool, turns: &[CompletedTurn]) -> SubagentNode {
+    let (tokens, breakdown, unique_tools, _models, _tool_count, _subagent_count, _duration_ms) =
+        calculate_metrics_from_turns(turns);
+    let mut tools_used: Vec<String> = unique_tools.into_iter().collect();
+    tools_used.sort();
+
+    SubagentNode {
+        id: id.to_string(),
+        is_subagent,
+        started_at: turns.first().map(|t| t.started_at.clone()),
+        turn_count: turns.len() as u32,
+        tools_used,
+        cost: breakdown.total_cost,
+        tokens: tokens.total(),
+        ..Default::default()
+    }
+}
+
 /// Compare multiple sessions
 ///
-/// Returns the sessions with their metrics and a comparison of key metrics
-/// between the first session and subsequent sessions.
-#[tauri::command]
+/// Returns the sessions with their metrics, a comparison of key metrics
+/// between the first and last session, and a detailed diff of every later
+/// session against the first.
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn compare_sessions(
-    _state: tauri::State<'_, AppState>,
+    _state: State<'_, AppState>,
     session_ids: Vec<String>,
 ) -> Result<SessionComparison, CommandError> {
     if session_ids.is_empty() {
@@ -1920,6 +3853,7 @@ pub async fn compare_sessions(
 
     let mut summaries = Vec::new();
     let mut metrics_data: Vec<(f64, u64, f64, u64)> = Vec::new(); // (cost, tokens, cer, duration)
+    let mut compared: Vec<ComparedSession> = Vec::new();
 
     for id in &session_ids {
         let file_info = find_session_by_id(id)
@@ -1955,6 +3889,22 @@ pub async fn compare_sessions(
 
         metrics_data.push((total_breakdown.total_cost, total_tokens_val, cer, duration_ms));
 
+        let full_metrics = build_session_metrics(&turns);
+        compared.push(ComparedSession {
+            id: file_info.session_id.clone(),
+            cost: total_breakdown.total_cost,
+            tokens: total_tokens_val,
+            duration_ms,
+            turn_count: turns.len() as u32,
+            tool_count: full_metrics.tool_count,
+            cer: full_metrics.efficiency.cer,
+            cgr: full_metrics.efficiency.cgr,
+            wfs: full_metrics.efficiency.wfs,
+            oes: full_metrics.efficiency.oes_score,
+            tool_usage: count_tool_usage(&turns),
+            models: full_metrics.models_used,
+        });
+
         let summary = turns.iter()
             .find_map(|t| {
                 t.user_message.as_ref()
@@ -1970,6 +3920,7 @@ pub async fn compare_sessions(
             last_activity_at: last_activity,
             model,
             total_cost: total_breakdown.total_cost,
+            total_cost_including_subagents: total_breakdown.total_cost,
             total_turns: turns.len() as u32,
             total_tokens: total_tokens_val,
             duration_ms,
@@ -1999,130 +3950,560 @@ pub async fn compare_sessions(
         }
     };
 
+    let detailed_comparisons = compared
+        .iter()
+        .skip(1)
+        .map(|session| MetricsComparisonDetailed::between(&compared[0], session))
+        .collect();
+
     Ok(SessionComparison {
         sessions: summaries,
         metrics_comparison: comparison,
+        detailed_comparisons,
     })
 }
 
 /// Get code changes made during a session
 ///
 /// Analyzes tool uses to identify file operations (Write, Edit, Bash with file-modifying commands)
-/// and returns a list of code changes with their metadata.
-#[tauri::command]
+/// and returns a list of code changes with their metadata. Changes recorded
+/// with the stored turns are used while those turns are current; otherwise
+/// the session is parsed and its turns stored.
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_session_code_changes(
-    _state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     session_id: String,
 ) -> Result<Vec<CodeChange>, CommandError> {
-    let (turns, _) = get_session_turns(&session_id)?;
-
-    let mut changes = Vec::new();
+    if let Some(changes) = stored_code_changes(&state, &session_id) {
+        return Ok(changes);
+    }
+    let (turns, _) = get_session_turns_with_db_cache(&session_id, &state)?;
+    Ok(extract_code_changes(&turns))
+}
 
-    for turn in &turns {
-        for tool in &turn.tool_uses {
-            match tool.name.as_str() {
-                "Write" | "write" => {
-                    // Write tool creates or overwrites files
-                    if let Some(input) = &tool.input {
-                        if let Some(file_path) = input.get("file_path").and_then(|v| v.as_str()) {
-                            changes.push(CodeChange {
-                                file_path: file_path.to_string(),
-                                change_type: "create".to_string(),
-                                tool_name: "Write".to_string(),
-                                turn_number: turn.turn_number,
-                                timestamp: turn.started_at.clone(),
-                            });
-                        }
-                    }
-                }
-                "Edit" | "edit" => {
-                    // Edit tool modifies existing files
-                    if let Some(input) = &tool.input {
-                        if let Some(file_path) = input.get("file_path").and_then(|v| v.as_str()) {
-                            changes.push(CodeChange {
-                                file_path: file_path.to_string(),
-                                change_type: "edit".to_string(),
-                                tool_name: "Edit".to_string(),
-                                turn_number: turn.turn_number,
-                                timestamp: turn.started_at.clone(),
-                            });
-                        }
-                    }
-                }
-                "Bash" | "bash" => {
-                    // Bash tool might contain file operations
-                    if let Some(input) = &tool.input {
-                        if let Some(command) = input.get("command").and_then(|v| v.as_str()) {
-                            // Check for common file operations
-                            let file_ops = [
-                                ("rm ", "delete"),
-                                ("rm -", "delete"),
-                                ("touch ", "create"),
-                                ("mkdir ", "create"),
-                                ("mv ", "edit"),
-                                ("cp ", "create"),
-                                ("echo ", "edit"),  // echo > file
-                                ("cat >", "create"),
-                            ];
-
-                            for (pattern, change_type) in &file_ops {
-                                if command.contains(pattern) {
-                                    // Extract file path (simplified - takes first path-like argument)
-                                    let parts: Vec<&str> = command.split_whitespace().collect();
-                                    if let Some(file_path) = parts.iter().skip(1).find(|p| {
-                                        p.starts_with('/') || p.starts_with('.') || p.contains('/')
-                                    }) {
-                                        changes.push(CodeChange {
-                                            file_path: file_path.to_string(),
-                                            change_type: change_type.to_string(),
-                                            tool_name: "Bash".to_string(),
-                                            turn_number: turn.turn_number,
-                                            timestamp: turn.started_at.clone(),
-                                        });
-                                    }
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-                "NotebookEdit" | "notebook_edit" => {
-                    // NotebookEdit modifies Jupyter notebooks
-                    if let Some(input) = &tool.input {
-                        if let Some(notebook_path) = input.get("notebook_path").and_then(|v| v.as_str()) {
-                            changes.push(CodeChange {
-                                file_path: notebook_path.to_string(),
-                                change_type: "edit".to_string(),
-                                tool_name: "NotebookEdit".to_string(),
-                                turn_number: turn.turn_number,
-                                timestamp: turn.started_at.clone(),
-                            });
-                        }
-                    }
-                }
-                _ => {}
-            }
+/// Code changes recorded with a session's stored turns, if those turns were
+/// parsed from the current file (or the session is remote)
+fn stored_code_changes(state: &AppState, session_id: &str) -> Option<Vec<CodeChange>> {
+    let db = state.db.get()?;
+    let current_mtime = match find_session_by_id(session_id) {
+        Some(file_info) => Some(get_file_mtime(&file_info.path)?),
+        None => None,
+    };
+    db.with_read_connection(|conn| {
+        let stored_mtime = db::queries::get_session_turns_mtime(conn, session_id)?;
+        let is_current = match (&current_mtime, &stored_mtime) {
+            (Some(current), Some(stored)) => mtime_matches(stored, current),
+            (None, _) => db::queries::get_remote_session_info(conn, session_id)?.is_some(),
+            _ => false,
+        };
+        match is_current {
+            true => db::queries::get_session_code_changes_from_db(conn, session_id).map(Some),
+            false => Ok(None),
         }
+    })
+    .map_err(|e| {
+        tracing::warn!("Failed to load code changes for {} from DB: {:?}", session_id, e);
+        state.diagnostics.record_error("load code changes", e);
+    })
+    .ok()
+    .flatten()
+}
+
+/// Get the files of a project its sessions changed, most changed first
+///
+/// Counts Write, Edit and NotebookEdit operations recorded with the stored
+/// turns of the project's sessions.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_project_file_changes(
+    state: State<'_, AppState>,
+    project_path: String,
+) -> Result<Vec<FileChangeSummary>, CommandError> {
+    let db = get_database(&state)?;
+    Ok(db.with_read_connection(|conn| db::queries::get_project_file_changes_from_db(conn, &project_path))?)
+}
+
+/// One change to a file, with where it was made
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct FileChangeEntry {
+    pub session_id: String,
+    pub project_path: String,
+    pub project_name: String,
+    pub branch: Option<String>,
+    /// Import source or team label of the session; None for this machine
+    pub source: Option<String>,
+    pub turn_number: u32,
+    pub timestamp: String,
+    /// "Write", "Edit", "NotebookEdit" or "Bash"
+    pub tool_name: Option<String>,
+    /// "create", "edit" or "delete"
+    pub change_type: String,
+}
+
+/// Every stored change to a file across sessions
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct FileHistory {
+    pub file_path: String,
+    pub total_changes: u32,
+    pub creates: u32,
+    pub edits: u32,
+    pub deletes: u32,
+    pub sessions: u32,
+    pub turns: u32,
+    pub first_changed_at: Option<String>,
+    pub last_changed_at: Option<String>,
+    /// Cost of the turns that changed the file, each split evenly between
+    /// the files the turn changed
+    pub estimated_cost: f64,
+    /// Newest first
+    pub changes: Vec<FileChangeEntry>,
+}
+
+/// Get every change made to a file across sessions
+///
+/// Reads the code changes recorded as turns are stored, so sessions whose
+/// turns aren't in the database yet are missing until they are parsed.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_file_history(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<FileHistory, CommandError> {
+    let db = get_database(&state)?;
+    let file_path = file_path.trim().to_string();
+    if file_path.is_empty() {
+        return Err(CommandError::Internal("File path is required".to_string()));
+    }
+    let rows = db.with_read_connection(|conn| db::queries::get_file_changes_from_db(conn, &file_path))?;
+
+    let mut turns: HashMap<(&str, u32), f64> = HashMap::new();
+    let mut sessions = HashSet::new();
+    let count = |change_type: &str| rows.iter().filter(|r| r.change_type == change_type).count() as u32;
+    for row in &rows {
+        turns.insert((&row.session_id, row.turn_number), row.cost_share);
+        sessions.insert(row.session_id.as_str());
     }
 
-    Ok(changes)
+    Ok(FileHistory {
+        total_changes: rows.len() as u32,
+        creates: count("create"),
+        edits: count("edit"),
+        deletes: count("delete"),
+        sessions: sessions.len() as u32,
+        turns: turns.len() as u32,
+        first_changed_at: rows.last().map(|r| r.started_at.clone()),
+        last_changed_at: rows.first().map(|r| r.started_at.clone()),
+        estimated_cost: turns.values().sum(),
+        changes: rows
+            .iter()
+            .map(|row| FileChangeEntry {
+                session_id: row.session_id.clone(),
+                project_path: row.project_path.clone(),
+                project_name: row.project_name.clone(),
+                branch: row.branch.clone(),
+                source: row.source.clone(),
+                turn_number: row.turn_number,
+                timestamp: row.started_at.clone(),
+                tool_name: row.tool_name.clone(),
+                change_type: row.change_type.clone(),
+            })
+            .collect(),
+        file_path,
+    })
+}
+
+/// Get the tool calls of a session that failed, with what they attempted
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_tool_failures(
+    _state: State<'_, AppState>,
+    session_id: String,
+) -> Result<Vec<ToolFailure>, CommandError> {
+    let (turns, _) = get_session_turns(&session_id)?;
+    Ok(extract_tool_failures(&turns))
+}
+
+/// Get a session's per-turn cache reads, writes and fresh input, and the
+/// turns where cache efficiency dropped with their likely cause
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_cache_breakdown(
+    _state: State<'_, AppState>,
+    session_id: String,
+) -> Result<CacheBreakdown, CommandError> {
+    let (turns, _) = get_session_turns(&session_id)?;
+    Ok(cache_breakdown(&session_id, &turns))
+}
+
+/// Get a session's cost per turn and CER averaged over a sliding window of
+/// `window` turns (5 by default), with the turn where the session degraded
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_efficiency_curve(
+    state: State<'_, AppState>,
+    session_id: String,
+    window: Option<u32>,
+) -> Result<SessionEfficiencyCurve, CommandError> {
+    let (turns, _) = get_session_turns_with_db_cache(&session_id, &state)?;
+    let window = window.map_or(DEFAULT_WINDOW, |w| w as usize);
+    Ok(efficiency_curve(&session_id, &turns, window))
+}
+
+/// Get the files a session changed repeatedly, with churn scores
+///
+/// Rework means changing a file again in a later turn; see
+/// [`crate::metrics::rework`].
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_rework(
+    _state: State<'_, AppState>,
+    session_id: String,
+) -> Result<ReworkAnalysis, CommandError> {
+    let (turns, _) = get_session_turns(&session_id)?;
+    Ok(analyze_rework(&extract_code_changes(&turns)))
+}
+
+/// Health score of a session and its components
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SessionHealth {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub health: HealthScore,
+}
+
+/// Get a session's 0-100 health score
+///
+/// Combines the Overall Efficiency Score, detected anti-patterns, the tool
+/// failure rate and the file rework ratio, weighted by the `health_weights`
+/// setting.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_health(
+    state: State<'_, AppState>,
+    session_id: String,
+) -> Result<SessionHealth, CommandError> {
+    let (turns, _) = get_session_turns_with_db_cache(&session_id, &state)?;
+    let metrics = build_session_metrics(&turns);
+    let patterns = detect_in_turns(&session_id, &turns, &AntiPatternType::all(), &DetectionThresholds::default());
+    let tool_uses = turns.iter().flat_map(|t| &t.tool_uses);
+
+    let input = HealthInput {
+        oes: metrics.efficiency.oes_score,
+        antipattern_count: patterns.len() as u32,
+        antipattern_severity: patterns.iter().map(|p| p.severity_score()).sum(),
+        tool_uses: tool_uses.clone().count() as u32,
+        tool_failures: tool_uses.filter(|t| t.is_error).count() as u32,
+        rework_ratio: analyze_rework(&extract_code_changes(&turns)).rework_ratio(),
+    };
+
+    Ok(SessionHealth {
+        session_id,
+        health: calculate_health(&input, &active_settings().health_weights),
+    })
+}
+
+/// The session currently being worked in
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ActiveSession {
+    pub session_id: String,
+    pub project_path: Option<String>,
+    pub last_modified: String,
+    pub turn_count: u32,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+}
+
+/// Get the session that is still being written to, if any
+///
+/// Picks the most recently modified main session file that changed within
+/// the last couple of minutes. Live updates for it arrive as
+/// `active-session-cost` events.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_active_session(
+    state: State<'_, AppState>,
+) -> Result<Option<ActiveSession>, CommandError> {
+    let now = SystemTime::now();
+    let candidate = scan_claude_sessions()
+        .into_iter()
+        .filter(|s| !s.is_subagent)
+        .filter(|s| {
+            now.duration_since(s.modified)
+                .map(|age| age <= ACTIVE_WINDOW)
+                .unwrap_or(true)
+        })
+        .max_by_key(|s| s.modified);
+
+    let file_info = match candidate {
+        Some(file_info) => file_info,
+        None => return Ok(None),
+    };
+
+    let (turns, _) = get_session_turns_with_db_cache(&file_info.session_id, &state)?;
+    let (session_tokens, total_breakdown, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
+
+    Ok(Some(ActiveSession {
+        session_id: file_info.session_id,
+        project_path: file_info.project_path,
+        last_modified: chrono::DateTime::<chrono::Utc>::from(file_info.modified).to_rfc3339(),
+        turn_count: turns.len() as u32,
+        total_tokens: session_tokens.total(),
+        total_cost: total_breakdown.total_cost,
+    }))
 }
 
 // ============================================================================
 // Export Commands
 // ============================================================================
 
-/// Export sessions to CSV or JSON format
+/// Finished export jobs kept around for `get_export_status`
+const MAX_FINISHED_EXPORT_JOBS: usize = 20;
+
+type ExportListener = Box<dyn Fn(&ExportJobStatus) + Send + Sync>;
+
+/// State of a background export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ExportJobState {
+    Running,
+    Completed,
+    Failed,
+    /// Stopped early; the sessions processed until then were still written
+    Cancelled,
+}
+
+/// Progress or outcome of an export job, also the `export-progress` payload
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ExportJobStatus {
+    pub job_id: String,
+    pub state: ExportJobState,
+    pub processed: u32,
+    /// Sessions selected for export, known once the date filter has run
+    pub total: u32,
+    /// Exported file (or Parquet directory), once written
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A running or finished export
+struct ExportJob {
+    id: String,
+    status: Mutex<ExportJobStatus>,
+    cancelled: AtomicBool,
+    last_report: Mutex<Option<Instant>>,
+    listener: Arc<std::sync::OnceLock<ExportListener>>,
+}
+
+impl ExportJob {
+    fn status(&self) -> ExportJobStatus {
+        self.status.lock().map(|s| s.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ExportJobStatus)) -> ExportJobStatus {
+        let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut status);
+        status.clone()
+    }
+
+    fn set_total(&self, total: usize) {
+        let status = self.update(|s| s.total = total as u32);
+        self.report(&status, true);
+    }
+
+    fn advance(&self) {
+        let status = self.update(|s| s.processed += 1);
+        self.report(&status, false);
+    }
+
+    fn finish(&self, result: Result<String, CommandError>) {
+        let cancelled = self.is_cancelled();
+        let status = self.update(|s| match result {
+            Ok(path) => {
+                s.state = if cancelled { ExportJobState::Cancelled } else { ExportJobState::Completed };
+                s.path = Some(path);
+            }
+            Err(e) => {
+                s.state = ExportJobState::Failed;
+                s.error = Some(e.to_string());
+            }
+        });
+        self.report(&status, true);
+    }
+
+    /// Mark a queued export that was dropped before it started
+    fn cancel_unstarted(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        let status = self.update(|s| s.state = ExportJobState::Cancelled);
+        self.report(&status, true);
+    }
+
+    /// Report to the listener, throttled to `PROGRESS_EVENT_INTERVAL` unless
+    /// `force`d
+    fn report(&self, status: &ExportJobStatus, force: bool) {
+        let listener = match self.listener.get() {
+            Some(listener) => listener,
+            None => return,
+        };
+        let mut last_report = self.last_report.lock().unwrap_or_else(|e| e.into_inner());
+        if force || last_report.is_none_or(|at| at.elapsed() >= PROGRESS_EVENT_INTERVAL) {
+            *last_report = Some(Instant::now());
+            listener(status);
+        }
+    }
+}
+
+/// Background session exports, held in `AppState`
+#[derive(Clone, Default)]
+pub struct ExportJobs {
+    next_id: Arc<std::sync::atomic::AtomicU64>,
+    jobs: Arc<Mutex<Vec<Arc<ExportJob>>>>,
+    listener: Arc<std::sync::OnceLock<ExportListener>>,
+}
+
+impl ExportJobs {
+    /// Register the receiver of job updates (the desktop app emits them as
+    /// `export-progress` events)
+    pub fn set_listener(&self, listener: impl Fn(&ExportJobStatus) + Send + Sync + 'static) {
+        let _ = self.listener.set(Box::new(listener));
+    }
+
+    /// Current status of a job
+    pub fn status(&self, job_id: &str) -> Option<ExportJobStatus> {
+        self.get(job_id).map(|job| job.status())
+    }
+
+    /// Ask a running job to stop; returns whether it was running
+    pub fn cancel(&self, job_id: &str) -> bool {
+        match self.get(job_id) {
+            Some(job) if job.status().state == ExportJobState::Running => {
+                job.cancelled.store(true, Ordering::SeqCst);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn get(&self, job_id: &str) -> Option<Arc<ExportJob>> {
+        let jobs = self.jobs.lock().ok()?;
+        jobs.iter().find(|job| job.id == job_id).cloned()
+    }
+
+    /// Register a new running job, forgetting the oldest finished ones
+    fn start(&self) -> Arc<ExportJob> {
+        let job_id = format!("export-{}", self.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+        self.get_or_start(&job_id)
+    }
+
+    /// The job with this ID, registered as a new running job if there's none
+    ///
+    /// Queued exports are registered both by `export_sessions` and by the
+    /// worker that runs them, whichever comes first.
+    fn get_or_start(&self, job_id: &str) -> Arc<ExportJob> {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(job) = jobs.iter().find(|job| job.id == job_id) {
+            return job.clone();
+        }
+
+        let job = Arc::new(ExportJob {
+            id: job_id.to_string(),
+            status: Mutex::new(ExportJobStatus {
+                job_id: job_id.to_string(),
+                state: ExportJobState::Running,
+                processed: 0,
+                total: 0,
+                path: None,
+                error: None,
+            }),
+            cancelled: AtomicBool::new(false),
+            last_report: Mutex::new(None),
+            listener: self.listener.clone(),
+        });
+
+        let finished = jobs
+            .iter()
+            .filter(|job| job.status().state != ExportJobState::Running)
+            .count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_EXPORT_JOBS);
+        jobs.retain(|job| {
+            if excess > 0 && job.status().state != ExportJobState::Running {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+        jobs.push(job.clone());
+        job
+    }
+}
+
+/// Export sessions to CSV, JSON, Markdown, SQLite, Parquet or iCalendar format
 ///
-/// Returns the file path of the exported file.
-#[tauri::command]
+/// Markdown exports are human-readable reports; with `include_metrics` they
+/// also carry efficiency grades and detected anti-patterns. SQLite and Parquet
+/// exports add a daily metrics table for analysis in other tools; Parquet
+/// writes one file per table into a directory. iCalendar exports have one
+/// event per session, for overlaying usage on a calendar.
+///
+/// The export is queued as a background job and reports its progress as
+/// `export-progress` events. Returns the job ID to pass to
+/// `get_export_status` for the path of the exported file (or directory, for
+/// Parquet) and to `cancel_export`.
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn export_sessions(
-    _state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     session_ids: Option<Vec<String>>,
     options: ExportOptions,
 ) -> Result<String, CommandError> {
     let format = options.format.parse::<ExportFormat>()?;
 
+    if state.db.get().is_some() {
+        let payload = serde_json::to_string(&ExportPayload { session_ids, options })
+            .map_err(|e| CommandError::Internal(e.to_string()))?;
+        let (job_id, _) = enqueue_job(&state, JobKind::Export, Some(payload))?;
+        return Ok(state.exports.get_or_start(&export_job_id(job_id)).id.clone());
+    }
+
+    // No database to queue the export in
+    let job = state.exports.start();
+    let job_id = job.id.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = write_session_export(&job, session_ids, &options, format);
+        job.finish(result);
+    });
+
+    Ok(job_id)
+}
+
+/// Status of an export started with `export_sessions`
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_export_status(
+    state: State<'_, AppState>,
+    job_id: String,
+) -> Result<ExportJobStatus, CommandError> {
+    state
+        .exports
+        .status(&job_id)
+        .ok_or_else(|| CommandError::Internal(format!("Unknown export job: {}", job_id)))
+}
+
+/// Stop a running export, keeping the sessions processed so far
+///
+/// Returns whether the job was still running.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn cancel_export(state: State<'_, AppState>, job_id: String) -> Result<bool, CommandError> {
+    Ok(state.exports.cancel(&job_id))
+}
+
+/// Body of an export job; stops taking on sessions once cancelled and writes
+/// those it already converted
+fn write_session_export(
+    job: &ExportJob,
+    session_ids: Option<Vec<String>>,
+    options: &ExportOptions,
+    format: ExportFormat,
+) -> Result<String, CommandError> {
     // Get sessions to export from cache
     let all_sessions = get_cached_session_list();
     let sessions_to_export: Vec<SessionFileInfo> = if let Some(ids) = session_ids {
@@ -2140,11 +4521,15 @@ pub async fn export_sessions(
         sessions_to_export
             .into_iter()
             .filter(|s| {
-                // Get session start date from parsing
+                if job.is_cancelled() {
+                    return false;
+                }
+                // Compare the session's local start date with the range's dates
+                let start = start.get(..10).unwrap_or(start);
+                let end = end.get(..10).unwrap_or(end);
                 if let Ok((turns, _)) = get_session_turns(&s.session_id) {
-                    if let Some(first_turn) = turns.first() {
-                        let session_date = &first_turn.started_at;
-                        return session_date >= start && session_date <= end;
+                    if let Some(date) = turns.first().and_then(|t| local_date(&t.started_at)) {
+                        return date.as_str() >= start && date.as_str() <= end;
                     }
                 }
                 true // Include if we can't determine date
@@ -2153,12 +4538,18 @@ pub async fn export_sessions(
     } else {
         sessions_to_export
     };
+    job.set_total(sessions_to_export.len());
 
     // Convert to exportable format
     let mut exportable_sessions: Vec<ExportableSession> = Vec::new();
     let mut turns_map: HashMap<String, Vec<ExportableTurn>> = HashMap::new();
+    let mut reports: Vec<md_export::SessionReport> = Vec::new();
 
     for file_info in &sessions_to_export {
+        if job.is_cancelled() {
+            break;
+        }
+        job.advance();
         match get_session_turns(&file_info.session_id) {
             Ok((turns, _)) => {
                 let (
@@ -2184,6 +4575,8 @@ pub async fn export_sessions(
                     turns: turns.len() as u32,
                     tokens: session_tokens.total(),
                     cost: total_breakdown.total_cost,
+                    cache_write_5m_cost: total_breakdown.cache_write_5m_cost,
+                    cache_write_1h_cost: total_breakdown.cache_write_1h_cost,
                     duration_ms,
                     efficiency_score: None,
                 };
@@ -2208,6 +4601,27 @@ pub async fn export_sessions(
                     turns_map.insert(file_info.session_id.clone(), exportable_turns);
                 }
 
+                if matches!(format, ExportFormat::Markdown) {
+                    let analyze = options.include_metrics && !turns.is_empty();
+                    reports.push(md_export::SessionReport {
+                        session: exportable.clone(),
+                        efficiency: analyze.then(|| build_session_metrics(&turns).efficiency),
+                        tool_usage: count_tool_usage(&turns),
+                        antipatterns: if analyze {
+                            detect_in_turns(
+                                &file_info.session_id,
+                                &turns,
+                                &AntiPatternType::all(),
+                                &DetectionThresholds::default(),
+                            )
+                        } else {
+                            Vec::new()
+                        },
+                        // Markdown reads turns from the reports only
+                        turns: turns_map.remove(&file_info.session_id).unwrap_or_default(),
+                    });
+                }
+
                 exportable_sessions.push(exportable);
             }
             Err(_) => continue,
@@ -2218,6 +4632,12 @@ pub async fn export_sessions(
     let export_dir = get_export_directory();
     let filename = generate_export_filename("claude_sessions", format.extension());
     let export_path = export_dir.join(&filename);
+    // The Parquet tables go into a directory named after the export
+    let export_path = if matches!(format, ExportFormat::Parquet) {
+        export_path.with_extension("")
+    } else {
+        export_path
+    };
 
     // Write to file
     match format {
@@ -2236,6 +4656,22 @@ pub async fn export_sessions(
             };
             json_export::write_sessions_json(&exportable_sessions, turns_ref, options.include_metrics, &export_path)?;
         }
+        ExportFormat::Markdown => {
+            md_export::write_sessions_markdown(&reports, &export_path)?;
+        }
+        ExportFormat::Sqlite => {
+            let turns_ref = options.include_turns.then_some(&turns_map);
+            let daily = daily_trends(&exportable_sessions);
+            sqlite_export::write_sessions_sqlite(&exportable_sessions, turns_ref, &daily, &export_path)?;
+        }
+        ExportFormat::Parquet => {
+            let turns_ref = options.include_turns.then_some(&turns_map);
+            let daily = daily_trends(&exportable_sessions);
+            parquet_export::write_sessions_parquet(&exportable_sessions, turns_ref, &daily, &export_path)?;
+        }
+        ExportFormat::Ical => {
+            ics_export::write_sessions_ics(&exportable_sessions, &export_path)?;
+        }
     }
 
     tracing::info!("Exported {} sessions to {}", exportable_sessions.len(), export_path.display());
@@ -2243,13 +4679,13 @@ pub async fn export_sessions(
     Ok(export_path.to_string_lossy().to_string())
 }
 
-/// Export usage trends to CSV or JSON format
+/// Export usage trends to CSV, JSON, Markdown, SQLite, Parquet or iCalendar format
 ///
 /// Aggregates session data by day for the specified number of days.
 /// Returns the file path of the exported file.
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn export_trends(
-    _state: tauri::State<'_, AppState>,
+    _state: State<'_, AppState>,
     days: u32,
     format: String,
 ) -> Result<String, CommandError> {
@@ -2281,7 +4717,7 @@ pub async fn export_trends(
                     continue;
                 }
 
-                let date_key = utc_date.format("%Y-%m-%d").to_string();
+                let date_key = reporting_timezone().to_local(utc_date).format("%Y-%m-%d").to_string();
 
                 let (
                     session_tokens,
@@ -2346,6 +4782,18 @@ pub async fn export_trends(
         ExportFormat::Json => {
             json_export::write_trends_json(&trends, days, &export_path)?;
         }
+        ExportFormat::Markdown => {
+            md_export::write_trends_markdown(&trends, days, &export_path)?;
+        }
+        ExportFormat::Sqlite => {
+            sqlite_export::write_trends_sqlite(&trends, &export_path)?;
+        }
+        ExportFormat::Parquet => {
+            parquet_export::write_trends_parquet(&trends, &export_path)?;
+        }
+        ExportFormat::Ical => {
+            ics_export::write_trends_ics(&trends, &export_path)?;
+        }
     }
 
     tracing::info!("Exported {} days of trends to {}", trends.len(), export_path.display());
@@ -2353,48 +4801,220 @@ pub async fn export_trends(
     Ok(export_path.to_string_lossy().to_string())
 }
 
-// ============================================================================
-// Trend Commands
-// ============================================================================
+/// Export a session as a standalone HTML replay page
+///
+/// The page shows the conversation turn by turn with each turn's cost, and
+/// tool calls fold open to their input and result. Secrets are redacted and
+/// long tool results truncated as in the transcript view. In privacy mode
+/// the page holds only the per-turn metrics, without message content.
+/// Writes to `path`, or a timestamped file in the export directory, and
+/// returns the path.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn export_session_replay(
+    _state: State<'_, AppState>,
+    session_id: String,
+    path: Option<String>,
+) -> Result<String, CommandError> {
+    let (turns, file_info) = get_session_turns(&session_id)?;
+    let metrics_only = crate::settings::privacy_mode();
 
-use crate::trends::DailyTrend;
-use crate::trends::daily::{SessionData, get_daily_trends};
+    let mut replay_turns: Vec<html_export::ReplayTurn> = turns
+        .iter()
+        .zip(cost_curve_from_turns(&turns))
+        .map(|(turn, point)| html_export::ReplayTurn::new(turn, point.cost))
+        .collect();
+    let mut redactions = 0;
+    if !metrics_only {
+        let mut builder = TranscriptBuilder::new(TranscriptOptions::default());
+        crate::parser::jsonl::parse_streaming(&file_info.path, |entry| builder.process_entry(entry))
+            .map_err(|e| CommandError::Parser(e.to_string()))?;
+        let transcript = builder.finish(session_id.clone());
+        redactions = transcript.redactions;
+        html_export::assign_messages(&mut replay_turns, transcript.messages);
+    }
 
-/// Helper to convert sessions to trend data using cached session list
-fn collect_session_trend_data() -> Vec<SessionData> {
-    let all_sessions = get_cached_session_list();
-    let mut session_data = Vec::new();
+    let replay = html_export::SessionReplay {
+        session: ExportableSession::from(&compute_session_summary(&file_info)),
+        turns: replay_turns,
+        redactions,
+        metrics_only,
+    };
 
-    for file_info in &all_sessions {
-        if let Ok((turns, _)) = get_session_turns(&file_info.session_id) {
-            if turns.is_empty() {
-                continue;
-            }
+    let export_path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let prefix = format!("session_replay_{}", session_id.chars().take(8).collect::<String>());
+            get_export_directory().join(generate_export_filename(&prefix, "html"))
+        }
+    };
+    html_export::write_session_replay(&replay, &export_path)?;
 
-            let (
-                session_tokens,
-                total_breakdown,
-                _unique_tools,
-                _models,
-                _tool_count,
-                _subagent_count,
-                _duration_ms,
-            ) = calculate_metrics_from_turns(&turns);
+    tracing::info!("Exported replay of session {} to {}", session_id, export_path.display());
 
-            // Calculate efficiency (CER) using correct formula:
-            // CER = cache_read / (cache_read + cache_write)
-            let total_tokens = session_tokens.total();
-            let total_cache = session_tokens.total_cache();
-            let efficiency = if total_cache > 0 {
-                session_tokens.total_cache_read as f64 / total_cache as f64
-            } else {
-                0.0
-            };
+    Ok(export_path.to_string_lossy().to_string())
+}
 
-            let started_at = turns.first().map(|t| t.started_at.clone()).unwrap_or_default();
+/// `metrics.json` of a session bundle
+#[derive(Debug, Clone, Serialize)]
+struct BundleMetrics {
+    session_id: String,
+    metrics: SessionMetricsResponse,
+    /// Without message text, which is in the bundled JSONL
+    turns: Vec<TurnSummary>,
+}
 
-            session_data.push(SessionData {
-                started_at,
+/// Export a session as a zip bundle for bug reports
+///
+/// The bundle holds the session JSONL, the metrics and anti-patterns
+/// computed from that JSONL, and a manifest. Anonymizing replaces message
+/// text, tool payloads and paths with text of the same shape (see
+/// [`bundle`]); otherwise secrets are redacted. Bundles are always
+/// anonymized in privacy mode. Writes to `path`, or a timestamped file in
+/// the export directory, and returns the path.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn export_session_bundle(
+    _state: State<'_, AppState>,
+    session_id: String,
+    anonymize: bool,
+    path: Option<String>,
+) -> Result<String, CommandError> {
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+    let anonymize = anonymize || crate::settings::privacy_mode();
+
+    let reader = crate::parser::archive::open_session_file(&file_info.path)
+        .map_err(|e| CommandError::Parser(e.to_string()))?;
+    let anonymizer = bundle::Anonymizer::new();
+    let mut jsonl = String::new();
+    let mut lines = 0u32;
+    let mut secrets_redacted = 0u32;
+    let mut aggregator = TurnAggregator::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| CommandError::Parser(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line = match anonymize {
+            true => anonymizer.anonymize_line(&line),
+            false => {
+                let (redacted, count) = crate::redact::redact_secrets(&line);
+                secrets_redacted += count;
+                redacted.into_owned()
+            }
+        };
+        if let Ok(entry) = crate::parser::jsonl::parse_line(&line) {
+            aggregator.process_entry(entry);
+        }
+        jsonl.push_str(&line);
+        jsonl.push('\n');
+        lines += 1;
+    }
+    aggregator.flush();
+    let turns = aggregator.turns();
+
+    let metrics = BundleMetrics {
+        session_id: session_id.clone(),
+        metrics: build_session_metrics(turns),
+        turns: turns
+            .iter()
+            .map(|turn| TurnSummary {
+                user_message: None,
+                assistant_message: None,
+                ..turn_to_summary(turn)
+            })
+            .collect(),
+    };
+    let patterns = detect_in_turns(&session_id, turns, &AntiPatternType::all(), &DetectionThresholds::default());
+    let manifest = bundle::BundleManifest {
+        format_version: bundle::BUNDLE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        session_id: session_id.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        anonymized: anonymize,
+        secrets_redacted,
+        lines,
+    };
+
+    let export_path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let prefix = format!("session_bundle_{}", session_id.chars().take(8).collect::<String>());
+            get_export_directory().join(generate_export_filename(&prefix, "zip"))
+        }
+    };
+    bundle::write_session_bundle(&export_path, &manifest, &jsonl, &metrics, &patterns)?;
+
+    tracing::info!(
+        "Exported {} bundle of session {} to {}",
+        if anonymize { "an anonymized" } else { "a" },
+        session_id,
+        export_path.display()
+    );
+
+    Ok(export_path.to_string_lossy().to_string())
+}
+
+// ============================================================================
+// Trend Commands
+// ============================================================================
+
+use crate::trends::{DailyTrend, Granularity, Segment, TrendSeries};
+use crate::trends::digest::DailyDigest;
+use crate::trends::daily::{
+    aggregate_by_day, daily_map_to_sorted_vec, days_ago, get_daily_trends, parse_date,
+    select_daily_trends, today, SessionData,
+};
+use crate::trends::forecast::{self, forecast_costs, CostForecast};
+use crate::trends::monthly::aggregate_to_monthly;
+use crate::trends::periods::{self, DateRange, DateRangeComparison, PeriodTotals};
+use crate::trends::report::{self, WeeklyReport};
+use crate::trends::statistics::{self, detect_cost_anomalies, CostAnomaly};
+use crate::trends::weekly::aggregate_to_weekly;
+
+/// Helper to convert sessions to trend data using cached session list,
+/// optionally only the sessions of some projects or of one segment
+fn collect_session_trend_data(projects: Option<&[String]>, segment: Segment) -> Vec<SessionData> {
+    let all_sessions = get_cached_session_list();
+    let mut session_data = Vec::new();
+
+    for file_info in &all_sessions {
+        if !segment.includes(file_info.is_subagent) {
+            continue;
+        }
+        if let Some(projects) = projects {
+            if !file_info.project_path.as_ref().is_some_and(|p| projects.contains(p)) {
+                continue;
+            }
+        }
+        if let Ok((turns, _)) = get_session_turns(&file_info.session_id) {
+            if turns.is_empty() {
+                continue;
+            }
+
+            let (
+                session_tokens,
+                total_breakdown,
+                _unique_tools,
+                _models,
+                _tool_count,
+                _subagent_count,
+                _duration_ms,
+            ) = calculate_metrics_from_turns(&turns);
+
+            // Calculate efficiency (CER) using correct formula:
+            // CER = cache_read / (cache_read + cache_write)
+            let total_tokens = session_tokens.total();
+            let total_cache = session_tokens.total_cache();
+            let efficiency = if total_cache > 0 {
+                session_tokens.total_cache_read as f64 / total_cache as f64
+            } else {
+                0.0
+            };
+
+            let started_at = turns.first().map(|t| t.started_at.clone()).unwrap_or_default();
+
+            session_data.push(SessionData {
+                started_at,
                 turns: turns.len() as u32,
                 tokens: total_tokens,
                 cost: total_breakdown.total_cost,
@@ -2406,17 +5026,58 @@ fn collect_session_trend_data() -> Vec<SessionData> {
     session_data
 }
 
+/// Load per-day trend data for all history, oldest first, optionally of only
+/// some projects or of one segment
+///
+/// Uses the daily aggregates in the database when available, falling back
+/// to aggregating parsed sessions.
+fn load_daily_trends(state: &AppState, projects: Option<&[String]>, segment: Segment) -> Vec<DailyTrend> {
+    if let Some(db) = state.db.get() {
+        if let Some(daily) = ok_or_report(state, "load daily trends", db.with_read_connection(|conn| {
+            db::queries::get_daily_metrics_from_db(conn, None, projects, segment)
+        })) {
+            if !daily.is_empty() {
+                let mut trends: Vec<DailyTrend> = daily
+                    .into_iter()
+                    .map(|d| DailyTrend {
+                        date: d.date,
+                        sessions: d.session_count,
+                        turns: d.total_turns,
+                        total_tokens: d.total_tokens,
+                        total_cost: d.total_cost,
+                        avg_efficiency: d.avg_efficiency.unwrap_or(0.0),
+                    })
+                    .collect();
+                trends.sort_by(|a, b| a.date.cmp(&b.date));
+                return trends;
+            }
+        }
+    }
+
+    daily_map_to_sorted_vec(aggregate_by_day(&collect_session_trend_data(projects, segment)))
+}
+
 /// Get historical trends with optional date range and granularity
 ///
-/// Returns daily trend data for chart visualization.
-#[tauri::command]
+/// Granularity is "daily" (default), "weekly" or "monthly". Weekly and
+/// monthly trends include their daily/weekly breakdown and a comparison with
+/// the previous period, and cover every week or month overlapping the range.
+/// Daily trends include empty days unless `backfill` is false. With a
+/// `stack` (a language or framework such as "Rust"), only the sessions of
+/// projects using it are counted, and a `segment` other than "all" counts
+/// only user ("user_only") or subagent ("subagent_only") sessions.
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_trends(
-    _state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     start_date: Option<String>,
     end_date: Option<String>,
-    _granularity: Option<String>,
-) -> Result<Vec<DailyTrend>, String> {
-    let session_data = collect_session_trend_data();
+    granularity: Option<String>,
+    backfill: Option<bool>,
+    stack: Option<String>,
+    segment: Option<Segment>,
+) -> Result<TrendSeries, String> {
+    let projects = stack_projects(&state, stack.as_deref());
+    let daily = load_daily_trends(&state, projects.as_deref(), segment.unwrap_or_default());
 
     // Calculate days from date range, default to 30
     let days = if let (Some(start), Some(end)) = (&start_date, &end_date) {
@@ -2432,23 +5093,289 @@ pub async fn get_trends(
         30
     };
 
-    let daily = get_daily_trends(&session_data, days, start_date.as_deref(), end_date.as_deref());
+    // Periods are aggregated over all history so the first period in range
+    // still gets compared with the one before it
+    let range_start = start_date.clone().unwrap_or_else(|| days_ago(days as i64));
+    let range_end = end_date.clone().unwrap_or_else(today);
+
+    let series = match Granularity::from(granularity) {
+        Granularity::Daily => TrendSeries::Daily(select_daily_trends(
+            daily,
+            days,
+            start_date.as_deref(),
+            end_date.as_deref(),
+            backfill.unwrap_or(true),
+        )),
+        Granularity::Weekly => TrendSeries::Weekly(
+            aggregate_to_weekly(daily)
+                .into_iter()
+                .filter(|w| w.week_end >= range_start && w.week_start <= range_end)
+                .collect(),
+        ),
+        Granularity::Monthly => {
+            let first_month = range_start.get(..7).unwrap_or(&range_start);
+            let last_month = range_end.get(..7).unwrap_or(&range_end);
+            TrendSeries::Monthly(
+                aggregate_to_monthly(daily)
+                    .into_iter()
+                    .filter(|m| m.month.as_str() >= first_month && m.month.as_str() <= last_month)
+                    .collect(),
+            )
+        }
+    };
+
+    Ok(series)
+}
+
+/// Most contributing sessions listed per anomalous day
+const MAX_ANOMALY_SESSIONS: usize = 10;
+
+/// A day of unusual spend with the sessions that drove it
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CostAnomalyResponse {
+    #[serde(flatten)]
+    pub anomaly: CostAnomaly,
+    /// Sessions started that day, most expensive first
+    pub top_sessions: Vec<SessionSummary>,
+}
+
+/// Get days in the last N days whose cost is anomalously high
+///
+/// A day is anomalous when its cost exceeds the mean of the preceding two
+/// weeks by more than `sigma` standard deviations (default 2).
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_cost_anomalies(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+    sigma: Option<f64>,
+) -> Result<Vec<CostAnomalyResponse>, CommandError> {
+    let days = days.unwrap_or(90);
+    let sigma = sigma.unwrap_or(statistics::DEFAULT_SIGMA);
+    let window = statistics::DEFAULT_WINDOW_DAYS;
+
+    // Include a window of history before the range so its first days have a baseline
+    let history_start = days_ago(days as i64 + window as i64);
+    let daily = select_daily_trends(load_daily_trends(&state, None, Segment::All), days, Some(&history_start), None, true);
+    let cutoff = days_ago(days as i64);
+
+    let mut result = Vec::new();
+    for anomaly in detect_cost_anomalies(&daily, window, sigma)
+        .into_iter()
+        .filter(|a| a.date >= cutoff)
+    {
+        let mut top_sessions = get_sessions_filtered(
+            state.clone(),
+            Some(anomaly.date.clone()),
+            Some(anomaly.date.clone()),
+            Some(i64::MAX),
+            Some(0),
+        )
+        .await?;
+        top_sessions.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost));
+        top_sessions.truncate(MAX_ANOMALY_SESSIONS);
+
+        result.push(CostAnomalyResponse {
+            anomaly,
+            top_sessions,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Project spend for the next `days_ahead` days (default: rest of the month)
+///
+/// Fits a linear trend to the daily cost of the last 30 days; the forecast
+/// also includes the projected total for the current month.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_cost_forecast(
+    state: State<'_, AppState>,
+    days_ahead: Option<u32>,
+) -> Result<CostForecast, CommandError> {
+    let today = today();
+    let as_of = parse_date(&today)
+        .ok_or_else(|| CommandError::Internal(format!("Invalid date: {}", today)))?;
+
+    // The history window always reaches back to the start of the month
+    let history = forecast::DEFAULT_HISTORY_DAYS;
+    let start = days_ago(history as i64);
+    let daily = select_daily_trends(
+        load_daily_trends(&state, None, Segment::All),
+        history as u32,
+        Some(&start),
+        Some(&today),
+        true,
+    );
+
+    Ok(forecast_costs(&daily, as_of, days_ahead, history))
+}
+
+/// Compare the sessions started in two date ranges
+///
+/// Range B is measured against range A: cost, tokens, sessions, turns,
+/// efficiency and tool calls, with deltas and percentage changes. Tool usage
+/// only covers sessions whose turns are stored in the database.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn compare_periods(
+    state: State<'_, AppState>,
+    range_a: DateRange,
+    range_b: DateRange,
+) -> Result<DateRangeComparison, CommandError> {
+    let days_a = range_a.days().map_err(CommandError::Internal)?;
+    let days_b = range_b.days().map_err(CommandError::Internal)?;
+
+    let tool_usage = |range: &DateRange| -> Result<Vec<(String, u32)>, CommandError> {
+        match state.db.get() {
+            Some(db) => Ok(db.with_read_connection(|conn| {
+                db::queries::get_tool_usage_in_range(conn, &range.start_date, &range.end_date)
+            })?),
+            None => Ok(Vec::new()),
+        }
+    };
+    let tools_a = tool_usage(&range_a)?;
+    let tools_b = tool_usage(&range_b)?;
+
+    let daily = load_daily_trends(&state, None, Segment::All);
+    Ok(periods::compare_periods(
+        PeriodTotals::new(range_a, days_a, &daily, &tools_a),
+        &tools_a,
+        PeriodTotals::new(range_b, days_b, &daily, &tools_b),
+        &tools_b,
+    ))
+}
+
+/// Build the report for the week starting at `week_start` (YYYY-MM-DD)
+fn build_weekly_report(state: &AppState, week_start: Option<&str>) -> Result<WeeklyReport, CommandError> {
+    let db = get_database(state)?;
+    let start = match week_start {
+        Some(date) => parse_date(date)
+            .ok_or_else(|| CommandError::Internal(format!("Invalid date {:?}, expected YYYY-MM-DD", date)))?,
+        None => report::last_full_week(reporting_timezone().today()),
+    };
+    let (previous, week) = report::week_ranges(start);
+
+    let (tools_a, tools_b, sessions, antipatterns) = db.with_read_connection(|conn| {
+        Ok((
+            db::queries::get_tool_usage_in_range(conn, &previous.start_date, &previous.end_date)?,
+            db::queries::get_tool_usage_in_range(conn, &week.start_date, &week.end_date)?,
+            db::queries::get_sessions_in_range_by_cost(conn, &week.start_date, &week.end_date)?,
+            db::queries::get_antipattern_summary_in_range(
+                conn,
+                &previous.start_date,
+                &week.start_date,
+                &week.end_date,
+            )?,
+        ))
+    })?;
+
+    // Recommendations only look at the week's own sessions
+    let session_ids: HashSet<String> = sessions.iter().map(|s| s.session_id.clone()).collect();
+    let recommendations = if session_ids.is_empty() {
+        Vec::new()
+    } else {
+        get_aggregate_recommendations(
+            None,
+            &load_recommendation_feedback(state),
+            &recent_mcp_servers(state),
+            Some(&session_ids),
+        )?
+        .recommendations
+    };
+
+    let daily = load_daily_trends(state, None, Segment::All);
+    let days = report::WEEK_DAYS as u32;
+    let comparison = periods::compare_periods(
+        PeriodTotals::new(previous, days, &daily, &tools_a),
+        &tools_a,
+        PeriodTotals::new(week, days, &daily, &tools_b),
+        &tools_b,
+    );
+    Ok(WeeklyReport::new(comparison, &daily, sessions, antipatterns, recommendations))
+}
+
+/// Report on a week compared to the week before it
+///
+/// Covers totals with their changes, each day's cost and efficiency, the five
+/// costliest sessions, anti-patterns in either week, and the top three
+/// recommendations for the week's sessions.
+///
+/// # Arguments
+/// * `week_start` - First day of the week (YYYY-MM-DD). Defaults to the
+///   Monday of the last full week.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn generate_weekly_report(
+    state: State<'_, AppState>,
+    week_start: Option<String>,
+) -> Result<WeeklyReport, CommandError> {
+    build_weekly_report(&state, week_start.as_deref())
+}
+
+/// Export a weekly report to Markdown, JSON, CSV, SQLite, Parquet or iCalendar
+///
+/// Markdown and JSON hold the whole report; the other formats hold the days of
+/// the week, like a trends export. Returns the file path of the exported file.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn export_weekly_report(
+    state: State<'_, AppState>,
+    week_start: Option<String>,
+    format: String,
+) -> Result<String, CommandError> {
+    let export_format = format.parse::<ExportFormat>()?;
+    let report = build_weekly_report(&state, week_start.as_deref())?;
+
+    let export_path = get_export_directory()
+        .join(generate_export_filename("claude_weekly_report", export_format.extension()));
+    let trends: Vec<ExportableTrend> = report
+        .daily
+        .iter()
+        .map(|d| ExportableTrend {
+            date: d.date.clone(),
+            session_count: d.sessions as i32,
+            total_turns: d.turns as i32,
+            total_cost: d.total_cost,
+            total_tokens: d.total_tokens as i64,
+            avg_efficiency_score: (d.sessions > 0).then_some(d.avg_efficiency),
+        })
+        .collect();
+
+    match export_format {
+        ExportFormat::Markdown => md_export::write_weekly_report_markdown(&report, &export_path)?,
+        ExportFormat::Json => json_export::write_weekly_report_json(&report, &export_path)?,
+        ExportFormat::Csv => csv_export::write_trends_csv(&trends, &export_path)?,
+        ExportFormat::Sqlite => sqlite_export::write_trends_sqlite(&trends, &export_path)?,
+        ExportFormat::Parquet => parquet_export::write_trends_parquet(&trends, &export_path)?,
+        ExportFormat::Ical => ics_export::write_trends_ics(&trends, &export_path)?,
+    }
+
+    tracing::info!("Exported the weekly report for {} to {}", report.week.start_date, export_path.display());
 
-    Ok(daily)
+    Ok(export_path.to_string_lossy().to_string())
 }
 
 /// Get cost trend for the last N days
 ///
-/// Returns daily cost data with cumulative totals for chart visualization.
-#[tauri::command]
+/// Returns daily cost data with cumulative totals for chart visualization. Days without
+/// sessions are included with zero values unless `backfill` is false. A
+/// `stack` limits the trend to projects using that language or framework,
+/// a `segment` to user or subagent sessions.
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_cost_trend(
-    _state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     days: Option<u32>,
+    backfill: Option<bool>,
+    stack: Option<String>,
+    segment: Option<Segment>,
 ) -> Result<Vec<CostTrendPoint>, String> {
     let days = days.unwrap_or(30);
-    let session_data = collect_session_trend_data();
-
-    let daily = get_daily_trends(&session_data, days, None, None);
+    let projects = stack_projects(&state, stack.as_deref());
+    let daily = select_daily_trends(
+        load_daily_trends(&state, projects.as_deref(), segment.unwrap_or_default()),
+        days,
+        None,
+        None,
+        backfill.unwrap_or(true),
+    );
 
     let mut cumulative = 0.0;
     Ok(daily.into_iter().map(|d| {
@@ -2463,16 +5390,23 @@ pub async fn get_cost_trend(
 
 /// Get efficiency trend for the last N days
 ///
-/// Returns daily efficiency data for chart visualization.
-#[tauri::command]
+/// Returns daily efficiency data for chart visualization. Days without
+/// sessions are included with zero values unless `backfill` is false. A
+/// `stack` limits the trend to projects using that language or framework,
+/// a `segment` to user or subagent sessions.
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_efficiency_trend(
-    _state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     days: Option<u32>,
+    backfill: Option<bool>,
+    stack: Option<String>,
+    segment: Option<Segment>,
 ) -> Result<Vec<EfficiencyTrendPoint>, String> {
     let days = days.unwrap_or(30);
-    let session_data = collect_session_trend_data();
+    let projects = stack_projects(&state, stack.as_deref());
+    let session_data = collect_session_trend_data(projects.as_deref(), segment.unwrap_or_default());
 
-    let daily = get_daily_trends(&session_data, days, None, None);
+    let daily = get_daily_trends(&session_data, days, None, None, backfill.unwrap_or(true));
 
     Ok(daily.into_iter().map(|d| {
         EfficiencyTrendPoint {
@@ -2491,25 +5425,50 @@ pub async fn get_efficiency_trend(
 ///
 /// Analyzes session metrics and generates actionable recommendations.
 /// If session_id is None, analyzes all sessions for aggregate recommendations.
-#[tauri::command]
+/// Dismissed and snoozed recommendations are left out.
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_recommendations(
-    _state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     session_id: Option<String>,
     limit: Option<u32>,
 ) -> Result<RecommendationSummary, CommandError> {
+    let feedback = load_recommendation_feedback(&state);
     if let Some(id) = session_id {
         // Analyze single session
-        get_session_recommendations(&id, limit)
+        get_session_recommendations(&id, limit, &feedback)
     } else {
         // Analyze all sessions for aggregate recommendations
-        get_aggregate_recommendations(limit)
+        get_aggregate_recommendations(limit, &feedback, &recent_mcp_servers(&state), None)
     }
 }
 
+/// Stored recommendation dismissals (none without a database)
+/// MCP servers used in the window aggregate recommendations look at
+fn recent_mcp_servers(state: &AppState) -> Vec<McpServerStats> {
+    load_mcp_stats(state, Some(MCP_WINDOW_DAYS))
+        .map_err(|e| report_error(state, "load MCP stats", e))
+        .map(|stats| stats.servers)
+        .unwrap_or_default()
+}
+
+fn load_recommendation_feedback(state: &AppState) -> Vec<RecommendationFeedback> {
+    let db = match state.db.get() {
+        Some(db) => db,
+        None => return Vec::new(),
+    };
+    ok_or_report(
+        state,
+        "load recommendation feedback",
+        db.with_read_connection(db::queries::get_recommendation_feedback),
+    )
+    .unwrap_or_default()
+}
+
 /// Get recommendations for a specific session
 fn get_session_recommendations(
     session_id: &str,
     limit: Option<u32>,
+    feedback: &[RecommendationFeedback],
 ) -> Result<RecommendationSummary, CommandError> {
     let file_info = find_session_by_id(session_id)
         .ok_or_else(|| CommandError::SessionNotFound(session_id.to_string()))?;
@@ -2535,7 +5494,7 @@ fn get_session_recommendations(
     ) = calculate_metrics_from_turns(&turns);
 
     // Calculate full metrics for efficiency scores
-    let deliverable_units = estimate_deliverable_units(session_tokens.total_output);
+    let deliverable_units = estimate_deliverables(crate::settings::deliverable_estimator(), &turns).units;
     let turn_count = turns.len() as u32;
 
     let per_turn_tokens: Vec<TurnTokens> = turns.iter().map(|t| {
@@ -2554,7 +5513,7 @@ fn get_session_recommendations(
         subagent_count,
         subagent_cost: 0.0, // TODO: Calculate from subagent sessions
         deliverable_units,
-        rework_cycles: 0, // TODO: Detect rework patterns
+        rework_cycles: detect_rework_cycles(&turns),
         clarification_cycles: 0,
         per_turn_tokens: Some(per_turn_tokens),
     };
@@ -2562,8 +5521,7 @@ fn get_session_recommendations(
     let full_metrics = calculate_session_metrics(metrics_input);
 
     // Build recommendation input
-    let primary_model = models.into_iter().next()
-        .unwrap_or_else(|| "claude-opus-4-5-20251101".to_string());
+    let primary_model = models.into_iter().next().unwrap_or_default();
 
     let rec_input = RecommendationInput {
         session_id: Some(session_id.to_string()),
@@ -2581,12 +5539,19 @@ fn get_session_recommendations(
         output_tokens: session_tokens.total_output,
         cache_read_tokens: session_tokens.total_cache_read,
         cache_write_tokens: session_tokens.total_cache_write_5m + session_tokens.total_cache_write_1h,
+        cache_write_1h_tokens: session_tokens.total_cache_write_1h,
+        duration_ms: calculate_active_time(&turns).active_ms,
         project_path: file_info.project_path.clone(),
-        branch: None, // TODO: Extract from session data if available
+        branch: extract_git_branch(&file_info.path),
         avg_cost_per_turn: full_metrics.cost.avg_cost_per_turn,
     };
 
-    let mut summary = generate_recommendations(&rec_input);
+    let mut summary = apply_feedback(
+        generate_recommendations(&rec_input),
+        feedback,
+        file_info.project_path.as_deref(),
+        chrono::Utc::now(),
+    );
 
     // Apply limit if specified
     if let Some(n) = limit {
@@ -2597,8 +5562,19 @@ fn get_session_recommendations(
 }
 
 /// Get aggregate recommendations across all sessions using cached data
-fn get_aggregate_recommendations(limit: Option<u32>) -> Result<RecommendationSummary, CommandError> {
-    let sessions = get_cached_session_list();
+///
+/// `session_ids` limits the input to those sessions, e.g. the ones of a
+/// report week.
+fn get_aggregate_recommendations(
+    limit: Option<u32>,
+    feedback: &[RecommendationFeedback],
+    mcp_servers: &[McpServerStats],
+    session_ids: Option<&HashSet<String>>,
+) -> Result<RecommendationSummary, CommandError> {
+    let sessions: Vec<SessionFileInfo> = get_cached_session_list()
+        .into_iter()
+        .filter(|f| session_ids.is_none_or(|ids| ids.contains(&f.session_id)))
+        .collect();
 
     if sessions.is_empty() {
         return Ok(RecommendationSummary::from_recommendations(Vec::new(), None, 0));
@@ -2623,7 +5599,8 @@ fn get_aggregate_recommendations(limit: Option<u32>) -> Result<RecommendationSum
                 duration_ms,
             ) = calculate_metrics_from_turns(&turns);
 
-            let deliverable_units = estimate_deliverable_units(session_tokens.total_output);
+            let deliverable_units =
+                estimate_deliverables(crate::settings::deliverable_estimator(), &turns).units;
             let turn_count = turns.len() as u32;
 
             let per_turn_tokens: Vec<TurnTokens> = turns.iter().map(|t| {
@@ -2642,15 +5619,14 @@ fn get_aggregate_recommendations(limit: Option<u32>) -> Result<RecommendationSum
                 subagent_count,
                 subagent_cost: 0.0,
                 deliverable_units,
-                rework_cycles: 0,
+                rework_cycles: detect_rework_cycles(&turns),
                 clarification_cycles: 0,
                 per_turn_tokens: Some(per_turn_tokens),
             };
 
             let full_metrics = calculate_session_metrics(metrics_input);
 
-            let primary_model = models.into_iter().next()
-                .unwrap_or_else(|| "claude-opus-4-5-20251101".to_string());
+            let primary_model = models.into_iter().next().unwrap_or_default();
 
             inputs.push(RecommendationInput {
                 session_id: Some(file_info.session_id.clone()),
@@ -2668,14 +5644,21 @@ fn get_aggregate_recommendations(limit: Option<u32>) -> Result<RecommendationSum
                 output_tokens: session_tokens.total_output,
                 cache_read_tokens: session_tokens.total_cache_read,
                 cache_write_tokens: session_tokens.total_cache_write_5m + session_tokens.total_cache_write_1h,
+                cache_write_1h_tokens: session_tokens.total_cache_write_1h,
+                duration_ms: calculate_active_time(&turns).active_ms,
                 project_path: file_info.project_path.clone(),
-                branch: None,
+                branch: extract_git_branch(&file_info.path),
                 avg_cost_per_turn: full_metrics.cost.avg_cost_per_turn,
             });
         }
     }
 
-    let mut summary = generate_aggregate_recommendations(&inputs);
+    let mut summary = apply_feedback(
+        generate_aggregate_recommendations(&inputs, mcp_servers),
+        feedback,
+        None,
+        chrono::Utc::now(),
+    );
 
     // Apply limit if specified
     if let Some(n) = limit {
@@ -2685,12 +5668,82 @@ fn get_aggregate_recommendations(limit: Option<u32>) -> Result<RecommendationSum
     Ok(summary)
 }
 
+/// Dismiss a recommendation, or snooze it for `snooze_days`
+///
+/// A session's recommendation stays hidden for every session of its project,
+/// an aggregate one for the aggregate view, until the snooze ends or the
+/// recommendation becomes materially more valuable than when dismissed.
+/// Dismissing it again replaces the reason and the baseline.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn dismiss_recommendation(
+    state: State<'_, AppState>,
+    id: String,
+    reason: Option<String>,
+    snooze_days: Option<u32>,
+) -> Result<RecommendationFeedback, CommandError> {
+    let db = get_database(&state)?;
+    let (rec_type, session_id) = parse_recommendation_id(&id)
+        .ok_or_else(|| CommandError::Internal(format!("Invalid recommendation id: {}", id)))?;
+
+    // Regenerate without feedback to record what was dismissed
+    let (summary, project_path) = match &session_id {
+        Some(sid) => {
+            let file_info = find_session_by_id(sid)
+                .ok_or_else(|| CommandError::SessionNotFound(sid.clone()))?;
+            (get_session_recommendations(sid, None, &[])?, file_info.project_path)
+        }
+        None => (get_aggregate_recommendations(None, &[], &recent_mcp_servers(&state), None)?, None),
+    };
+    let current = summary
+        .recommendations
+        .iter()
+        .filter(|r| r.rec_type == rec_type)
+        .max_by(|a, b| a.potential_savings.partial_cmp(&b.potential_savings).unwrap_or(std::cmp::Ordering::Equal))
+        .ok_or_else(|| CommandError::Internal(format!("No current recommendation {}", id)))?;
+
+    let now = chrono::Utc::now();
+    let feedback = RecommendationFeedback {
+        id,
+        rec_type,
+        session_id,
+        project_path,
+        reason: reason.filter(|r| !r.trim().is_empty()),
+        potential_savings: current.potential_savings,
+        confidence: current.confidence,
+        dismissed_at: now.to_rfc3339(),
+        snoozed_until: snooze_days.map(|d| (now + chrono::Duration::days(d as i64)).to_rfc3339()),
+    };
+    db.with_connection(|conn| db::queries::upsert_recommendation_feedback(conn, &feedback))?;
+
+    Ok(feedback)
+}
+
+/// List dismissed and snoozed recommendations, most recent first
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn list_dismissed(
+    state: State<'_, AppState>,
+) -> Result<Vec<RecommendationFeedback>, CommandError> {
+    let db = get_database(&state)?;
+    Ok(db.with_read_connection(db::queries::get_recommendation_feedback)?)
+}
+
+/// Show a dismissed recommendation again. Returns whether it was dismissed.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn restore_recommendation(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<bool, CommandError> {
+    let db = get_database(&state)?;
+    Ok(db.with_connection(|conn| db::queries::delete_recommendation_feedback(conn, &id))?)
+}
+
 // ============================================================================
 // Dashboard Summary Commands (Efficient aggregation)
 // ============================================================================
 
 /// Dashboard summary response
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct DashboardSummaryResponse {
     pub total_sessions: u32,
     pub user_sessions: u32,
@@ -2702,10 +5755,28 @@ pub struct DashboardSummaryResponse {
     pub avg_turns_per_session: f64,
     pub avg_efficiency_score: Option<f64>,
     pub active_projects: u32,
+    /// Anti-pattern found most often in the period, if any were found
+    pub most_frequent_antipattern: Option<AntiPatternFrequency>,
+    /// Most sessions (subagents included) generating at the same moment
+    pub peak_concurrent_sessions: u32,
+    /// Fraction of the period's turn cost spent while sessions overlapped (0-1)
+    pub concurrent_cost_share: f64,
+}
+
+/// How often an anti-pattern type occurred
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct AntiPatternFrequency {
+    pub pattern_type: AntiPatternType,
+    pub display_name: String,
+    pub occurrences: u32,
+    /// Sessions it was found in
+    pub sessions: u32,
 }
 
 /// Daily metrics response
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct DailyMetricsResponse {
     pub date: String,
     pub session_count: u32,
@@ -2719,6 +5790,7 @@ pub struct DailyMetricsResponse {
 
 /// Project metrics response
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct ProjectMetricsResponse {
     pub project_path: String,
     pub project_name: String,
@@ -2728,15 +5800,20 @@ pub struct ProjectMetricsResponse {
     pub total_tokens: u64,
     pub avg_cost_per_session: f64,
     pub last_activity: String,
+    /// Cost of the turns annotated as wasted
+    pub wasted_cost: f64,
+    /// Detected languages and frameworks
+    #[serde(flatten)]
+    pub stack: ProjectStack,
 }
 
 /// Get dashboard summary metrics efficiently
 ///
 /// This command uses cached session data for fast aggregates,
 /// without re-scanning the filesystem on every call.
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_dashboard_summary(
-    state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     days: Option<u32>,
 ) -> Result<DashboardSummaryResponse, CommandError> {
     // Return cached result if still valid (TTL 30s) and for the same time range
@@ -2746,13 +5823,17 @@ pub async fn get_dashboard_summary(
         }
     }
 
+    // Subagent sessions can be folded into the sessions that spawned them
+    let attribute_subagents = active_settings().attribute_subagent_costs;
+
     // Try DB aggregate query - the DB may have data from previous runs even before
     // preload completes. The total_sessions > 0 check handles the empty DB case.
     if let Some(db) = state.db.get() {
-        if let Ok(agg) = db.with_connection(|conn| {
-            db::queries::get_dashboard_summary_from_db(conn, days)
-        }) {
+        if let Some(agg) = ok_or_report(&state, "load dashboard summary", db.with_read_connection(|conn| {
+            db::queries::get_dashboard_summary_from_db(conn, days, attribute_subagents)
+        })) {
             if agg.total_sessions > 0 {
+                let concurrency = load_concurrency(db, days).unwrap_or_default();
                 let result = DashboardSummaryResponse {
                     total_sessions: agg.total_sessions,
                     user_sessions: agg.user_sessions,
@@ -2764,6 +5845,9 @@ pub async fn get_dashboard_summary(
                     avg_turns_per_session: if agg.total_sessions > 0 { agg.total_turns as f64 / agg.total_sessions as f64 } else { 0.0 },
                     avg_efficiency_score: agg.avg_efficiency,
                     active_projects: agg.active_projects,
+                    most_frequent_antipattern: most_frequent_antipattern(&state, days),
+                    peak_concurrent_sessions: concurrency.peak_concurrency,
+                    concurrent_cost_share: concurrency.concurrent_cost_share,
                 };
                 // Store in cache keyed by days
                 if let Ok(mut cache) = DASHBOARD_CACHE.lock() {
@@ -2827,7 +5911,9 @@ pub async fn get_dashboard_summary(
             total_cost += total_breakdown.total_cost;
             total_turns += turns.len() as u32;
             total_tokens += session_tokens.total();
-            processed_count += 1;
+            if !(attribute_subagents && file_info.is_subagent) {
+                processed_count += 1;
+            }
 
             // Track user vs subagent sessions
             if file_info.is_subagent {
@@ -2855,6 +5941,12 @@ pub async fn get_dashboard_summary(
         None
     };
 
+    let concurrency = state
+        .db
+        .get()
+        .and_then(|db| load_concurrency(db, days).ok())
+        .unwrap_or_default();
+
     let result = DashboardSummaryResponse {
         total_sessions: processed_count as u32,
         user_sessions: user_session_count,
@@ -2866,6 +5958,9 @@ pub async fn get_dashboard_summary(
         avg_turns_per_session: if processed_count > 0 { total_turns as f64 / processed_count as f64 } else { 0.0 },
         avg_efficiency_score: avg_efficiency,
         active_projects: unique_projects.len() as u32,
+        most_frequent_antipattern: most_frequent_antipattern(&state, days),
+        peak_concurrent_sessions: concurrency.peak_concurrency,
+        concurrent_cost_share: concurrency.concurrent_cost_share,
     };
 
     // Store in cache keyed by days for subsequent requests
@@ -2876,26 +5971,157 @@ pub async fn get_dashboard_summary(
     Ok(result)
 }
 
+/// Get the time windows where two or more sessions were generating at once
+///
+/// Subagent sessions count as sessions of their own, so a session waiting on
+/// its subagents overlaps with them. Computed from stored turns of sessions
+/// started in the last `days` days (all time if omitted); windows are
+/// returned costliest first.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_concurrent_sessions(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<ConcurrencyReport, CommandError> {
+    let db = get_database(&state)?;
+    Ok(load_concurrency(db, days)?)
+}
+
+fn load_concurrency(db: &db::Database, days: Option<u32>) -> Result<ConcurrencyReport, db::DbError> {
+    let rows = db.with_read_connection(|conn| db::queries::get_turn_intervals_from_db(conn, days))?;
+    let intervals: Vec<TurnInterval> = rows
+        .into_iter()
+        .filter_map(|row| {
+            TurnInterval::new(
+                row.session_id,
+                row.project_name,
+                row.is_subagent,
+                &row.started_at,
+                row.ended_at.as_deref(),
+                row.duration_ms,
+                row.cost,
+            )
+        })
+        .collect();
+    Ok(detect_concurrency(&intervals))
+}
+
+/// Get percentiles (p50/p90/p99) and histograms of session duration, cost,
+/// turn count and tokens
+///
+/// Covers main sessions of real user projects started in the last `days`
+/// days (all time if omitted), with `buckets` histogram buckets (20 by
+/// default, at most 200).
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_distributions(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+    buckets: Option<usize>,
+) -> Result<SessionDistributions, CommandError> {
+    let db = get_database(&state)?;
+    let sessions = db.with_read_connection(|conn| db::queries::get_session_totals_from_db(conn, days))?;
+    Ok(SessionDistributions::from_sessions(&sessions, buckets.unwrap_or(DEFAULT_BUCKETS)))
+}
+
+/// Get rate-limit, overload and other API errors by day and by session
+///
+/// Counts the retried and final API errors recorded in stored turns of
+/// sessions started in the last `days` days (all time if omitted), along
+/// with the time spent waiting to retry.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_error_stats(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<ApiErrorStats, CommandError> {
+    let db = get_database(&state)?;
+    let turns = db.with_read_connection(|conn| db::queries::get_turn_api_errors_from_db(conn, days))?;
+    Ok(summarize_api_errors(&turns))
+}
+
+/// Get extended thinking usage per day and per thinking mode
+///
+/// Sums the estimated thinking tokens of stored turns of sessions started in
+/// the last `days` days (all time if omitted), with their share of output
+/// tokens and their cost, so days and modes with heavy thinking stand out.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_thinking_trend(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<ThinkingTrend, CommandError> {
+    let db = get_database(&state)?;
+    let usage = db.with_read_connection(|conn| db::queries::get_thinking_usage_from_db(conn, days))?;
+    Ok(summarize_thinking(&usage))
+}
+
+/// Get cost per active working hour per day and per project
+///
+/// Uses the active time of stored turns of sessions started in the last
+/// `days` days (all time if omitted), so long pauses don't dilute the rate.
+/// Subagent cost counts toward the hours of the session that spawned it.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_burn_rate(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<BurnRateReport, CommandError> {
+    let db = get_database(&state)?;
+    let rows = db.with_read_connection(|conn| db::queries::get_turn_intervals_from_db(conn, days))?;
+    let turns: Vec<TurnActivity> = rows
+        .into_iter()
+        .map(|row| TurnActivity {
+            session_id: row.session_id,
+            project_path: row.project_path,
+            project_name: row.project_name,
+            is_subagent: row.is_subagent,
+            started_at: row.started_at,
+            duration_ms: row.duration_ms,
+            cost: row.cost,
+        })
+        .collect();
+    Ok(calculate_burn_rate(&turns, crate::timezone::local_date))
+}
+
+/// Most frequent stored anti-pattern of the last `days` days
+fn most_frequent_antipattern(state: &AppState, days: Option<u32>) -> Option<AntiPatternFrequency> {
+    let db = state.db.get()?;
+    let frequencies = ok_or_report(
+        state,
+        "load anti-pattern frequencies",
+        db.with_read_connection(|conn| db::queries::get_antipattern_frequencies_from_db(conn, days)),
+    )?;
+    frequencies.into_iter().next().map(|row| AntiPatternFrequency {
+        pattern_type: row.pattern_type,
+        display_name: row.pattern_type.display_name().to_string(),
+        occurrences: row.occurrences,
+        sessions: row.sessions,
+    })
+}
+
 /// Get daily metrics efficiently
 ///
-/// Returns aggregated metrics grouped by day using cached session data.
-#[tauri::command]
+/// Returns aggregated metrics grouped by day using cached session data. A
+/// `segment` other than "all" counts only user or only subagent sessions;
+/// those results aren't cached.
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_daily_metrics(
-    state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     days: Option<u32>,
+    segment: Option<Segment>,
 ) -> Result<Vec<DailyMetricsResponse>, CommandError> {
-    if let Ok(cache) = DAILY_CACHE.lock() {
-        if let Some(cached) = cache.get(days) {
-            return Ok(cached);
+    let segment = segment.unwrap_or_default();
+    let cached = segment == Segment::All;
+    if cached {
+        if let Ok(cache) = DAILY_CACHE.lock() {
+            if let Some(cached) = cache.get(days) {
+                return Ok(cached);
+            }
         }
     }
 
     // Try DB aggregate query - the DB may have data from previous runs even before
     // preload completes. The !daily.is_empty() check handles the empty DB case.
     if let Some(db) = state.db.get() {
-        if let Ok(daily) = db.with_connection(|conn| {
-            db::queries::get_daily_metrics_from_db(conn, days)
-        }) {
+        if let Some(daily) = ok_or_report(&state, "load daily metrics", db.with_read_connection(|conn| {
+            db::queries::get_daily_metrics_from_db(conn, days, None, segment)
+        })) {
             if !daily.is_empty() {
                 let mut result: Vec<DailyMetricsResponse> = daily.into_iter().map(|d| {
                     DailyMetricsResponse {
@@ -2910,8 +6136,10 @@ pub async fn get_daily_metrics(
                     }
                 }).collect();
                 result.sort_by(|a, b| b.date.cmp(&a.date));
-                if let Ok(mut cache) = DAILY_CACHE.lock() {
-                    cache.set(days, result.clone());
+                if cached {
+                    if let Ok(mut cache) = DAILY_CACHE.lock() {
+                        cache.set(days, result.clone());
+                    }
                 }
                 return Ok(result);
             }
@@ -2927,6 +6155,9 @@ pub async fn get_daily_metrics(
     let mut by_date: HashMap<String, (u32, u32, u32, u32, f64, u64, f64, u32)> = HashMap::new();
 
     for file_info in sessions.iter() { // Process ALL sessions within date range
+        if !segment.includes(file_info.is_subagent) {
+            continue;
+        }
         // Skip sessions from temporary/artifact paths
         let project_path = file_info.project_path.clone().unwrap_or_default();
         if !project_path.is_empty() && !is_real_user_project(&project_path) {
@@ -2949,7 +6180,7 @@ pub async fn get_daily_metrics(
                     }
                 }
 
-                let date_key = utc_date.format("%Y-%m-%d").to_string();
+                let date_key = reporting_timezone().to_local(utc_date).format("%Y-%m-%d").to_string();
 
                 let (
                     session_tokens,
@@ -3006,8 +6237,10 @@ pub async fn get_daily_metrics(
     // Sort by date descending
     result.sort_by(|a, b| b.date.cmp(&a.date));
 
-    if let Ok(mut cache) = DAILY_CACHE.lock() {
-        cache.set(days, result.clone());
+    if cached {
+        if let Ok(mut cache) = DAILY_CACHE.lock() {
+            cache.set(days, result.clone());
+        }
     }
 
     Ok(result)
@@ -3016,27 +6249,44 @@ pub async fn get_daily_metrics(
 /// Get project metrics efficiently
 ///
 /// Returns metrics grouped by project path using cached session data.
-/// Accepts an optional `days` parameter to filter to recent sessions.
-#[tauri::command]
+/// Accepts an optional `days` parameter to filter to recent sessions, and an
+/// optional `stack` to keep only projects with that language or framework
+/// (e.g. "Rust" or "React").
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn get_project_metrics(
-    state: tauri::State<'_, AppState>,
+    state: State<'_, AppState>,
     days: Option<u32>,
+    stack: Option<String>,
 ) -> Result<Vec<ProjectMetricsResponse>, CommandError> {
+    let mut projects = all_project_metrics(&state, days);
+    if let Some(stack) = stack {
+        projects.retain(|p| p.stack.matches(&stack));
+    }
+    Ok(projects)
+}
+
+fn all_project_metrics(state: &AppState, days: Option<u32>) -> Vec<ProjectMetricsResponse> {
     if let Ok(cache) = PROJECT_CACHE.lock() {
         if let Some(cached) = cache.get(days) {
-            return Ok(cached);
+            return cached;
         }
     }
 
+    let attribute_subagents = active_settings().attribute_subagent_costs;
+
     // Try DB aggregate query - the DB may have data from previous runs even before
     // preload completes. The !projects.is_empty() check handles the empty DB case.
     if let Some(db) = state.db.get() {
-        if let Ok(projects) = db.with_connection(|conn| {
-            db::queries::get_project_metrics_from_db(conn, days)
-        }) {
+        if let Some(projects) = ok_or_report(state, "load project metrics", db.with_read_connection(|conn| {
+            db::queries::get_project_metrics_from_db(conn, days, attribute_subagents)
+        })) {
             if !projects.is_empty() {
+                let mut stacks = project_stacks(state);
+                let wasted = project_wasted_costs(state, days, attribute_subagents);
                 let mut result: Vec<ProjectMetricsResponse> = projects.into_iter().map(|p| {
                     ProjectMetricsResponse {
+                        stack: stacks.remove(&p.project_path).unwrap_or_default(),
+                        wasted_cost: wasted.get(&p.project_path).copied().unwrap_or(0.0),
                         project_path: p.project_path,
                         project_name: p.project_name,
                         session_count: p.session_count,
@@ -3051,7 +6301,7 @@ pub async fn get_project_metrics(
                 if let Ok(mut cache) = PROJECT_CACHE.lock() {
                     cache.set(days, result.clone());
                 }
-                return Ok(result);
+                return result;
             }
         }
     }
@@ -3064,6 +6314,7 @@ pub async fn get_project_metrics(
 
     let mut by_project: HashMap<String, (String, u32, f64, u32, u64, String)> = HashMap::new();
     // (project_name, session_count, total_cost, total_turns, total_tokens, last_activity)
+    let mut changed_files: HashMap<String, Vec<String>> = HashMap::new();
 
     for file_info in sessions.iter() {
         let project_path = file_info.project_path.clone().unwrap_or_default();
@@ -3104,7 +6355,9 @@ pub async fn get_project_metrics(
                 .entry(project_path.clone())
                 .or_insert((project_name, 0, 0.0, 0, 0, String::new()));
 
-            entry.1 += 1; // session_count
+            if !(attribute_subagents && file_info.is_subagent) {
+                entry.1 += 1; // session_count
+            }
             entry.2 += total_breakdown.total_cost; // total_cost
             entry.3 += turns.len() as u32; // total_turns
             entry.4 += session_tokens.total(); // total_tokens
@@ -3113,13 +6366,24 @@ pub async fn get_project_metrics(
             if entry.5.is_empty() || started_at > entry.5 {
                 entry.5 = started_at;
             }
+
+            changed_files
+                .entry(project_path)
+                .or_default()
+                .extend(extract_code_changes(&turns).into_iter().filter(|c| c.tool_name != "Bash").map(|c| c.file_path));
         }
     }
 
+    let wasted = project_wasted_costs(state, days, attribute_subagents);
     let mut result: Vec<ProjectMetricsResponse> = by_project
         .into_iter()
         .map(|(project_path, (project_name, session_count, total_cost, total_turns, total_tokens, last_activity))| {
             ProjectMetricsResponse {
+                stack: detect_stack(
+                    changed_files.get(&project_path).map(Vec::as_slice).unwrap_or_default(),
+                    Path::new(&project_path),
+                ),
+                wasted_cost: wasted.get(&project_path).copied().unwrap_or(0.0),
                 project_path,
                 project_name,
                 session_count,
@@ -3139,27 +6403,478 @@ pub async fn get_project_metrics(
         cache.set(days, result.clone());
     }
 
-    Ok(result)
+    result
 }
 
-// ============================================================================
-// Anti-Pattern Detection Commands
-// ============================================================================
-
-/// Detect anti-patterns in sessions
+/// Languages and frameworks of every project with stored sessions
 ///
-/// Analyzes sessions for inefficient patterns:
-/// - SubagentSprawl: Too many subagents for output
-/// - ContextChurn: Poor cache utilization (CER < 0.4)
+/// Detections are stored and redone for projects with sessions active since,
+/// which picks up newly changed files and added dependencies.
+fn project_stacks(state: &AppState) -> HashMap<String, ProjectStack> {
+    let Some(db) = state.db.get() else {
+        return HashMap::new();
+    };
+    let stacks = db.with_connection(|conn| {
+        let mut stacks = db::queries::get_project_stacks(conn)?;
+        let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        for project in db::queries::get_project_metrics_from_db(conn, None, false)? {
+            let current = stacks
+                .get(&project.project_path)
+                .is_some_and(|(_, detected_at)| *detected_at >= project.last_activity);
+            if current {
+                continue;
+            }
+            let files = db::queries::get_changed_files_from_db(conn, &project.project_path)?;
+            let stack = detect_stack(&files, Path::new(&project.project_path));
+            db::queries::upsert_project_stack(conn, &project.project_path, &stack, &now)?;
+            stacks.insert(project.project_path, (stack, now.clone()));
+        }
+        Ok(stacks)
+    });
+
+    match stacks {
+        Ok(stacks) => stacks.into_iter().map(|(path, (stack, _))| (path, stack)).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to detect project stacks: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Cost of the turns annotated as wasted, by project
+fn project_wasted_costs(state: &AppState, days: Option<u32>, attribute_subagents: bool) -> HashMap<String, f64> {
+    let Some(db) = state.db.get() else {
+        return HashMap::new();
+    };
+    ok_or_report(
+        state,
+        "load wasted turn costs",
+        db.with_read_connection(|conn| db::queries::get_wasted_cost_by_project(conn, days, attribute_subagents)),
+    )
+    .unwrap_or_default()
+}
+
+/// Paths of the projects with `stack` among their languages or frameworks,
+/// or None to leave projects unfiltered
+fn stack_projects(state: &AppState, stack: Option<&str>) -> Option<Vec<String>> {
+    let stack = stack?;
+    Some(
+        all_project_metrics(state, None)
+            .into_iter()
+            .filter(|p| p.stack.matches(stack))
+            .map(|p| p.project_path)
+            .collect(),
+    )
+}
+
+/// Benchmark each project's efficiency against all sessions
+///
+/// Covers main sessions of real user projects, limited to the last `days`
+/// days when given. With `compare_projects`, each project is also compared
+/// with all other projects combined. Projects are listed worst cache
+/// efficiency first, and flagged when it is unusually poor.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_project_benchmarks(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+    compare_projects: Option<bool>,
+) -> Result<ProjectBenchmarks, CommandError> {
+    let cutoff = days.map(|d| chrono::Utc::now() - chrono::Duration::days(d as i64));
+    let mut sessions = Vec::new();
+
+    for file_info in get_cached_session_list().iter().filter(|s| !s.is_subagent) {
+        let project_path = file_info.project_path.clone().unwrap_or_default();
+        if project_path.is_empty() || !is_real_user_project(&project_path) {
+            continue;
+        }
+
+        let turns = match get_session_turns_with_db_cache(&file_info.session_id, &state) {
+            Ok((turns, _)) if !turns.is_empty() => turns,
+            _ => continue,
+        };
+        if let (Some(cutoff_date), Ok(started)) = (cutoff, chrono::DateTime::parse_from_rfc3339(&turns[0].started_at)) {
+            if started < cutoff_date {
+                continue;
+            }
+        }
+
+        let metrics = build_session_metrics(&turns);
+        sessions.push(BenchmarkSession {
+            project_name: extract_project_name(&project_path),
+            project_path,
+            cost: metrics.cost.total_cost,
+            turns: metrics.turn_count,
+            cache_read_tokens: metrics.tokens.cache_read,
+            cache_write_tokens: metrics.tokens.cache_write_5m + metrics.tokens.cache_write_1h,
+            oes: metrics.efficiency.oes_score,
+        });
+    }
+
+    Ok(benchmark_projects(&sessions, compare_projects.unwrap_or(false)))
+}
+
+/// One session on a project's activity timeline
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectTimelineSession {
+    pub session_id: String,
+    pub started_at: String,
+    pub last_activity_at: Option<String>,
+    pub model: Option<String>,
+    pub total_turns: u32,
+    pub duration_ms: u64,
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    /// Cache efficiency ratio (CER) of the session
+    pub efficiency_score: Option<f64>,
+    /// Files changed with Write, Edit or NotebookEdit
+    pub code_changes: u32,
+    /// Idle time since the previous session ended; None for the first
+    /// session, 0 when it overlaps the previous one
+    pub gap_ms: Option<u64>,
+}
+
+/// Chronological activity of a project
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectTimeline {
+    pub project_path: String,
+    pub project_name: String,
+    pub sessions: Vec<ProjectTimelineSession>,
+    pub total_cost: f64,
+    pub total_code_changes: u32,
+    pub longest_gap_ms: u64,
+}
+
+/// Get a project's sessions in chronological order with the gaps between them
+///
+/// Subagent sessions are left out; their work shows up in the sessions that
+/// spawned them. Code change counts come from stored turns, parsing the
+/// sessions that don't have them yet.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_project_timeline(
+    state: State<'_, AppState>,
+    project_path: String,
+) -> Result<ProjectTimeline, CommandError> {
+    let db = get_database(&state)?;
+    let (rows, stored_counts) = db.with_read_connection(|conn| {
+        Ok((
+            db::queries::get_project_timeline_from_db(conn, &project_path)?,
+            db::queries::get_code_change_counts_from_db(conn, &project_path)?,
+        ))
+    })?;
+
+    let mut sessions = Vec::with_capacity(rows.len());
+    let mut previous_end: Option<chrono::DateTime<chrono::FixedOffset>> = None;
+
+    for row in rows {
+        let code_changes = match stored_counts.get(&row.session_id) {
+            Some(count) => *count,
+            None => get_session_turns(&row.session_id)
+                .map(|(turns, _)| {
+                    extract_code_changes(&turns)
+                        .iter()
+                        .filter(|c| c.tool_name != "Bash")
+                        .count() as u32
+                })
+                .unwrap_or(0),
+        };
+
+        let started = chrono::DateTime::parse_from_rfc3339(&row.started_at).ok();
+        let gap_ms = match (previous_end, started) {
+            (Some(end), Some(start)) => Some((start - end).num_milliseconds().max(0) as u64),
+            _ => None,
+        };
+        let ended = row
+            .last_activity_at
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .or(started);
+        // Sessions can overlap, so keep the latest end seen so far
+        if let Some(end) = ended {
+            previous_end = Some(previous_end.map_or(end, |prev| prev.max(end)));
+        }
+
+        sessions.push(ProjectTimelineSession {
+            session_id: row.session_id,
+            started_at: row.started_at,
+            last_activity_at: row.last_activity_at,
+            model: row.model,
+            total_turns: row.total_turns,
+            duration_ms: row.duration_ms,
+            total_cost: row.total_cost,
+            total_tokens: row.total_tokens,
+            efficiency_score: row.efficiency,
+            code_changes,
+            gap_ms,
+        });
+    }
+
+    Ok(ProjectTimeline {
+        project_name: extract_project_name(&project_path),
+        total_cost: sessions.iter().map(|s| s.total_cost).sum(),
+        total_code_changes: sessions.iter().map(|s| s.code_changes).sum(),
+        longest_gap_ms: sessions.iter().filter_map(|s| s.gap_ms).max().unwrap_or(0),
+        project_path,
+        sessions,
+    })
+}
+
+/// Get usage by day of week and hour of day (local time) for a heatmap
+///
+/// Dates are inclusive YYYY-MM-DD bounds; omit both for all time. Served from
+/// stored turns when every session in the range has them, otherwise computed
+/// from the session files.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_hourly_usage(
+    state: State<'_, AppState>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<HourlyUsage, CommandError> {
+    let (start, end) = (start_date.as_deref(), end_date.as_deref());
+
+    if let Some(db) = state.db.get() {
+        let rows = db.with_read_connection(|conn| {
+            if db::queries::count_sessions_without_turns(conn, start, end)? > 0 {
+                return Ok(None);
+            }
+            db::queries::get_hourly_usage_from_db(conn, start, end).map(Some)
+        });
+
+        match rows {
+            Ok(Some(rows)) if !rows.is_empty() => {
+                let mut usage = HourlyUsage::default();
+                for row in rows {
+                    usage.add_slot(
+                        row.day_of_week as usize,
+                        row.hour as usize,
+                        row.turns,
+                        row.sessions,
+                        row.total_tokens,
+                        row.total_cost,
+                    );
+                }
+                return Ok(usage);
+            }
+            Ok(_) => {}
+            Err(e) => report_error(&state, "load hourly usage", e),
+        }
+    }
+
+    let mut usage = HourlyUsageAccumulator::new();
+    for file_info in get_cached_session_list().iter() {
+        let project_path = file_info.project_path.clone().unwrap_or_default();
+        if !project_path.is_empty() && !is_real_user_project(&project_path) {
+            continue;
+        }
+
+        // Nothing in a file last written before the range can fall inside it
+        let modified = reporting_timezone()
+            .to_local(file_info.modified.into())
+            .format("%Y-%m-%d")
+            .to_string();
+        if start.is_some_and(|s| modified.as_str() < s) {
+            continue;
+        }
+
+        let turns = match get_session_turns_with_db_cache(&file_info.session_id, &state) {
+            Ok((turns, _)) => turns,
+            Err(_) => continue,
+        };
+
+        for turn in turns.iter() {
+            let date = local_date(&turn.started_at).unwrap_or_default();
+            let date = date.as_str();
+            if start.is_some_and(|s| date < s) || end.is_some_and(|e| date > e) {
+                continue;
+            }
+
+            let tokens = TurnTokens::new(
+                turn.input_tokens,
+                turn.output_tokens,
+                turn.cache_read_tokens,
+                turn.cache_write_5m_tokens,
+                turn.cache_write_1h_tokens,
+            );
+            let model = turn.model.as_deref();
+            let cost = calculate_provider_turn_cost(&tokens, model, turn.provider, Some(&turn.started_at)).total_cost;
+            usage.add_turn(&file_info.session_id, &turn.started_at, tokens.total(), cost);
+        }
+    }
+
+    Ok(usage.finish())
+}
+
+/// Turns started since `since`, from the database when there is one,
+/// otherwise from session files written since then
+fn load_usage_turns(state: &AppState, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<UsageTurn>, CommandError> {
+    let parse = |started_at: &str| {
+        chrono::DateTime::parse_from_rfc3339(started_at)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Utc))
+    };
+
+    if let Some(db) = state.db.get() {
+        let cutoff = since.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let rows = db.with_read_connection(|conn| db::queries::get_turn_usage_since(conn, &cutoff))?;
+        return Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(UsageTurn {
+                    started_at: parse(&row.started_at)?,
+                    session_id: row.session_id,
+                    tokens: row.tokens.max(0) as u64,
+                    cost: row.cost,
+                })
+            })
+            .collect());
+    }
+
+    let mut usage = Vec::new();
+    for file_info in get_cached_session_list().iter() {
+        if chrono::DateTime::<chrono::Utc>::from(file_info.modified) < since {
+            continue;
+        }
+        let turns = match get_session_turns(&file_info.session_id) {
+            Ok((turns, _)) => turns,
+            Err(_) => continue,
+        };
+        for turn in turns.iter() {
+            let Some(started_at) = parse(&turn.started_at).filter(|t| *t >= since) else {
+                continue;
+            };
+            let tokens = TurnTokens::new(
+                turn.input_tokens,
+                turn.output_tokens,
+                turn.cache_read_tokens,
+                turn.cache_write_5m_tokens,
+                turn.cache_write_1h_tokens,
+            );
+            let model = turn.model.as_deref();
+            usage.push(UsageTurn {
+                session_id: file_info.session_id.clone(),
+                started_at,
+                tokens: tokens.total(),
+                cost: calculate_provider_turn_cost(&tokens, model, turn.provider, Some(&turn.started_at)).total_cost,
+            });
+        }
+    }
+    Ok(usage)
+}
+
+/// Current usage in each rolling window configured in settings
+pub fn current_usage_windows(state: &AppState) -> Result<Vec<UsageWindow>, CommandError> {
+    let configs = active_settings().usage_windows;
+    let now = chrono::Utc::now();
+    let longest = configs.iter().map(|c| c.hours).max().unwrap_or(0);
+    if longest == 0 {
+        return Ok(Vec::new());
+    }
+    let turns = load_usage_turns(state, now - chrono::Duration::hours(longest as i64))?;
+    Ok(compute_usage_windows(&turns, &configs, now))
+}
+
+/// Get tokens and cost used within each rolling usage window
+///
+/// Windows (5 hours, 24 hours and 7 days unless changed in settings) end
+/// now. Each reports how close it is to its thresholds and when its oldest
+/// turn rolls out. A `usage-window-warning` event is emitted as a window
+/// reaches a threshold.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_usage_windows(state: State<'_, AppState>) -> Result<Vec<UsageWindow>, CommandError> {
+    current_usage_windows(&state)
+}
+
+/// Get usage grouped into 5-hour billing blocks, as ccusage reports them
+///
+/// Covers turns of the last `days` days (all time if omitted). While a block
+/// is open, its headroom is measured against the thresholds of the 5-hour
+/// usage window in settings.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_billing_blocks(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<BillingBlocks, CommandError> {
+    let now = chrono::Utc::now();
+    let since = match days {
+        Some(days) => now - chrono::Duration::days(days as i64),
+        None => chrono::DateTime::<chrono::Utc>::from(std::time::UNIX_EPOCH),
+    };
+    let turns = load_usage_turns(&state, since)?;
+    let limits = active_settings().usage_windows.into_iter().find(|w| w.hours == BLOCK_HOURS);
+    Ok(compute_billing_blocks(&turns, limits.as_ref(), now))
+}
+
+/// MCP usage of sessions started in the last `days` days, with result tokens
+/// priced at the input rate of the model that read them
+fn load_mcp_stats(state: &AppState, days: Option<u32>) -> Result<McpStats, CommandError> {
+    let db = get_database(state)?;
+    let calls = db.with_read_connection(|conn| db::queries::get_mcp_calls_from_db(conn, days))?;
+    Ok(calculate_mcp_stats(&calls, |model| {
+        find_pricing(model).map_or(0.0, |pricing| pricing.input_price_per_million)
+    }))
+}
+
+/// Get MCP server usage of the last `days` days (all time if omitted):
+/// calls, failure rates and the estimated token cost of their results
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_mcp_stats(state: State<'_, AppState>, days: Option<u32>) -> Result<McpStats, CommandError> {
+    load_mcp_stats(&state, days)
+}
+
+/// Get tool calls of the last `days` days (all time if omitted) by tool,
+/// with MCP tools also grouped by server
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_tool_usage(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<ToolUsageStats, CommandError> {
+    let db = get_database(&state)?;
+    let counts = db.with_read_connection(|conn| db::queries::get_tool_calls_from_db(conn, days))?;
+    Ok(summarize_tool_usage(counts))
+}
+
+/// Simulate what the last `days` days (all time if omitted) would have cost
+/// under hypothetical changes, e.g. Sonnet instead of Opus or a better CER
+///
+/// Scenarios apply in the order given; see `metrics::savings`.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn simulate_savings(
+    state: State<'_, AppState>,
+    scenarios: Vec<SavingsScenario>,
+    days: Option<u32>,
+) -> Result<SavingsSimulation, CommandError> {
+    if scenarios.is_empty() {
+        return Err(CommandError::Internal("No scenarios to simulate".to_string()));
+    }
+    for scenario in &scenarios {
+        scenario.validate().map_err(CommandError::Internal)?;
+    }
+    let db = get_database(&state)?;
+    let turns = db.with_read_connection(|conn| db::queries::get_simulated_turns_from_db(conn, days))?;
+    Ok(crate::metrics::savings::simulate_savings(&turns, &scenarios, days))
+}
+
+// ============================================================================
+// Anti-Pattern Detection Commands
+// ============================================================================
+
+/// Detect anti-patterns in sessions
+///
+/// Analyzes sessions for inefficient patterns:
+/// - SubagentSprawl: Too many subagents for output
+/// - ContextChurn: Poor cache utilization (CER < 0.4)
 /// - CostSpike: Turn cost > 3x session average
 /// - LongTurn: Turn duration > 5 minutes
 /// - ToolFailureSpree: 3+ consecutive tool failures
 /// - HighReworkRatio: Many edits to same files
+/// - CompactionHeavy: More than 2 compactions in a session
+/// - RetryStorm: 5+ API errors within 5 minutes
 ///
 /// # Arguments
 /// * `session_id` - Optional specific session to analyze. If None, scans all sessions.
 /// * `pattern_types` - Optional filter for specific patterns. If None, checks all.
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 pub async fn detect_antipatterns(
     session_id: Option<String>,
     pattern_types: Option<Vec<String>>,
@@ -3172,156 +6887,1802 @@ pub async fn detect_antipatterns(
             .collect()
     });
 
-    crate::patterns::detect_antipatterns(session_id, patterns, None)
+    crate::patterns::detect_antipatterns(session_id, patterns, None)
+}
+
+/// Anti-pattern occurrences of one type on one day
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct AntiPatternHistoryPoint {
+    pub date: String,
+    pub pattern_type: AntiPatternType,
+    pub occurrences: u32,
+    /// Sessions the pattern was found in that day
+    pub sessions: u32,
+    /// Mean severity score, from 1 (info) to 9 (far past a critical threshold)
+    pub avg_severity_score: f64,
+    pub impact_cost: f64,
+}
+
+/// Get daily anti-pattern counts per type for trend charts
+///
+/// Uses the anti-patterns stored when sessions' turns are stored, dated by
+/// the turn they occurred in (or the session start for session-wide ones).
+///
+/// # Arguments
+/// * `days` - Number of days to include. If None, returns all history.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_antipattern_history(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<Vec<AntiPatternHistoryPoint>, CommandError> {
+    let db = get_database(&state)?;
+    let rows = db.with_read_connection(|conn| db::queries::get_antipattern_history_from_db(conn, days))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AntiPatternHistoryPoint {
+            date: row.date,
+            pattern_type: row.pattern_type,
+            occurrences: row.occurrences,
+            sessions: row.sessions,
+            avg_severity_score: row.avg_severity_score,
+            impact_cost: row.impact_cost,
+        })
+        .collect())
+}
+
+// ============================================================================
+// Daily Digest
+// ============================================================================
+
+/// How often the session watcher checks whether a digest is due
+pub const DIGEST_CHECK_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Arguments of a `DailyDigest` job
+#[derive(Debug, Serialize, serde::Deserialize)]
+struct DailyDigestPayload {
+    date: String,
+}
+
+/// Build the digest of `date` from the database
+fn build_daily_digest(db: &db::Database, date: &str) -> Result<DailyDigest, CommandError> {
+    if parse_date(date).is_none() {
+        return Err(CommandError::Internal(format!("Invalid date {:?}, expected YYYY-MM-DD", date)));
+    }
+    Ok(db.with_read_connection(|conn| {
+        let daily: Vec<DailyTrend> = db::queries::get_daily_metrics_from_db(conn, None, None, Segment::All)?
+            .into_iter()
+            .map(|d| DailyTrend {
+                date: d.date,
+                sessions: d.session_count,
+                turns: d.total_turns,
+                total_tokens: d.total_tokens,
+                total_cost: d.total_cost,
+                avg_efficiency: d.avg_efficiency.unwrap_or(0.0),
+            })
+            .collect();
+        Ok(DailyDigest::new(
+            date,
+            &daily,
+            db::queries::get_top_project_on_from_db(conn, date)?,
+            db::queries::get_most_expensive_session_on_from_db(conn, date)?,
+            db::queries::get_digest_antipatterns_from_db(conn, date)?,
+        ))
+    })?)
+}
+
+/// Summarize a day against the week before it
+///
+/// Covers sessions, turns and cost next to their 7-day averages, the
+/// costliest project and session, and the anti-patterns found that day.
+///
+/// # Arguments
+/// * `date` - Day to summarize (YYYY-MM-DD). Defaults to yesterday.
+/// * `post` - Also send the digest to the configured webhooks, whether or not
+///   the scheduled digest is enabled
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn generate_daily_digest(
+    state: State<'_, AppState>,
+    date: Option<String>,
+    post: Option<bool>,
+) -> Result<DailyDigest, CommandError> {
+    let db = get_database(&state)?;
+    let date = date.unwrap_or_else(|| days_ago(1));
+    let digest = build_daily_digest(db, &date)?;
+    if post.unwrap_or(false) {
+        crate::notifications::send(&NotificationEvent::DailyDigest(digest.clone()))
+            .await
+            .map_err(CommandError::Internal)?;
+    }
+    Ok(digest)
+}
+
+/// Queue a job posting yesterday's digest, unless one was queued already
+///
+/// Does nothing unless the daily digest is enabled and a webhook is
+/// configured. The session watcher calls this every `DIGEST_CHECK_INTERVAL`,
+/// so the digest goes out shortly after midnight, or at the next start.
+pub fn schedule_daily_digest(state: &AppState) {
+    let settings = active_settings().notifications;
+    if !settings.daily_digest || settings.webhook_urls.is_empty() {
+        return;
+    }
+    let db = match state.db.get() {
+        Some(db) => db,
+        None => return,
+    };
+
+    let payload = match serde_json::to_string(&DailyDigestPayload { date: days_ago(1) }) {
+        Ok(payload) => payload,
+        Err(_) => return,
+    };
+    match db.with_read_connection(|conn| db::jobs::has_job(conn, JobKind::DailyDigest, &payload)) {
+        Ok(true) => {}
+        Ok(false) => {
+            if let Err(e) = enqueue_job(state, JobKind::DailyDigest, Some(payload)) {
+                tracing::warn!("Failed to queue the daily digest: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to check for a queued daily digest: {:?}", e),
+    }
+}
+
+/// Post the digest of a `DailyDigest` job's day
+fn run_daily_digest_job(db: &db::Database, job: &Job, date: &str) -> Result<JobState, CommandError> {
+    let digest = build_daily_digest(db, date)?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| CommandError::Internal(format!("Failed to start job {}: {}", job.id, e)))?;
+    runtime
+        .block_on(crate::notifications::send(&NotificationEvent::DailyDigest(digest)))
+        .map_err(CommandError::Internal)?;
+    Ok(JobState::Succeeded)
+}
+
+// ============================================================================
+// Developer Performance Commands
+// ============================================================================
+
+/// Auto-detect GitHub configuration from environment.
+///
+/// Checks gh CLI auth, env vars, and git remote to determine available config.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn detect_github_config() -> Result<GitHubConfigResponse, CommandError> {
+    use crate::github::{detect_github_token, detect_github_username};
+
+    let token = detect_github_token();
+    let token_source = if token.is_some() {
+        // Determine source
+        if std::process::Command::new("gh").args(["auth", "token"]).output()
+            .map(|o| o.status.success()).unwrap_or(false) {
+            Some("gh_cli".to_string())
+        } else if std::env::var("GITHUB_TOKEN").is_ok() {
+            Some("GITHUB_TOKEN".to_string())
+        } else {
+            Some("GH_TOKEN".to_string())
+        }
+    } else {
+        None
+    };
+
+    let username = detect_github_username();
+
+    Ok(GitHubConfigResponse {
+        has_token: token.is_some(),
+        token_source,
+        username,
+    })
+}
+
+/// Get developer performance metrics (3-axis AI adoption metrics)
+///
+/// Requires GitHub integration: owner, repo, and username. Token is auto-detected.
+/// `sprint_days` configures the sprint length (default 14).
+/// Analyzes the last 4 sprints and uses the 4 sprints before that as baseline.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_developer_metrics(
+    _state: State<'_, AppState>,
+    github_username: String,
+    sprint_days: Option<u32>,
+    num_sprints: Option<u32>,
+) -> Result<DeveloperPerformanceResponse, CommandError> {
+    use crate::github::{detect_github_token, fetch_sprint_github_data};
+    use crate::metrics::developer::{calculate_developer_metrics, SprintInput};
+
+    let token = detect_github_token()
+        .ok_or_else(|| CommandError::Internal(
+            "No GitHub token found. Install gh CLI and run 'gh auth login', or set GITHUB_TOKEN env var.".to_string()
+        ))?;
+
+    let sprint_len = sprint_days.unwrap_or(14).clamp(1, 90);
+    let now = chrono::Utc::now();
+
+    // Analyze N recent sprints + N baseline sprints = 2N total
+    let analysis_sprints = num_sprints.unwrap_or(4).clamp(1, 26);
+    let total_sprints = analysis_sprints.saturating_mul(2);
+
+    // Bug 4 fix: create HTTP client once and reuse across all sprint fetches
+    let client = reqwest::Client::new();
+
+    let mut all_sprint_inputs: Vec<SprintInput> = Vec::new();
+
+    for i in 0..total_sprints {
+        let sprint_end = now - chrono::Duration::days((i * sprint_len) as i64);
+        let sprint_start = sprint_end - chrono::Duration::days(sprint_len as i64);
+
+        let since = sprint_start.format("%Y-%m-%d").to_string();
+        let until = sprint_end.format("%Y-%m-%d").to_string();
+
+        // Fetch GitHub data for this sprint (searches across all repos)
+        let github_data = fetch_sprint_github_data(
+            &client,
+            &token,
+            &github_username,
+            &since,
+            &until,
+            sprint_len,
+        )
+        .await
+        .map_err(|e| CommandError::Internal(e))?;
+
+        // Calculate CC spend for this sprint window from local sessions
+        let cc_spend = compute_cc_spend_for_range(&sprint_start, &sprint_end);
+
+        all_sprint_inputs.push(SprintInput {
+            prs_merged: github_data.prs_merged,
+            concurrent_commit_days: github_data.concurrent_commit_days,
+            sprint_days: sprint_len,
+            cc_spend_usd: cc_spend,
+            start_date: since.clone(),
+            end_date: until.clone(),
+        });
+    }
+
+    // First 4 are analysis (most recent), last 4 are baseline
+    // Note: they're in reverse chronological order (0=most recent)
+    let analysis: Vec<SprintInput> = all_sprint_inputs[..analysis_sprints as usize].to_vec();
+    let baseline: Vec<SprintInput> = all_sprint_inputs[analysis_sprints as usize..].to_vec();
+
+    let baseline_ref = if baseline.iter().any(|s| s.prs_merged > 0) {
+        Some(baseline.as_slice())
+    } else {
+        None
+    };
+
+    let metrics = calculate_developer_metrics(&analysis, baseline_ref);
+    Ok(DeveloperPerformanceResponse::from(metrics))
+}
+
+/// Compute total Claude Code spend for a date range from local session data.
+fn compute_cc_spend_for_range(
+    start: &chrono::DateTime<chrono::Utc>,
+    end: &chrono::DateTime<chrono::Utc>,
+) -> f64 {
+    let all_sessions = get_cached_session_list();
+    let mut total_cost = 0.0;
+
+    for file_info in &all_sessions {
+        if file_info.is_subagent {
+            continue;
+        }
+
+        let summary = get_cached_summary(&file_info);
+        if summary.total_turns == 0 {
+            continue;
+        }
+
+        if !is_real_user_project(&summary.project_path) {
+            continue;
+        }
+
+        let started = match chrono::DateTime::parse_from_rfc3339(&summary.started_at) {
+            Ok(dt) => dt.with_timezone(&chrono::Utc),
+            Err(_) => continue,
+        };
+
+        if started >= *start && started < *end {
+            total_cost += summary.total_cost;
+        }
+    }
+
+    total_cost
+}
+
+// ============================================================================
+// Git Context Commands
+// ============================================================================
+
+/// Get the git branch and commits associated with a session
+///
+/// The branch comes from the session log; commits are those authored in the
+/// project repository while the session was active. Returns an empty commit
+/// list when the project isn't a git repository.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_session_git_context(
+    state: State<'_, AppState>,
+    session_id: String,
+) -> Result<SessionGitContext, CommandError> {
+    let (turns, file_info) = get_session_turns_with_db_cache(&session_id, &state)?;
+
+    let branch = extract_git_branch(&file_info.path);
+    if let (Some(db), Some(branch)) = (state.db.get(), branch.as_deref()) {
+        if let Err(e) = db.with_connection(|conn| {
+            db::queries::update_session_branch(conn, &session_id, branch)
+        }) {
+            tracing::warn!("Failed to store branch for {}: {:?}", session_id, e);
+        }
+    }
+
+    let started_at = turns.first().map(|t| t.started_at.as_str());
+    let ended_at = turns
+        .iter()
+        .rev()
+        .find_map(|t| t.ended_at.as_deref())
+        .or_else(|| turns.last().map(|t| t.started_at.as_str()));
+    let project_path = file_info.project_path.clone().unwrap_or_default();
+
+    Ok(crate::git::session_git_context(
+        &session_id,
+        &project_path,
+        branch,
+        started_at,
+        ended_at,
+    ))
+}
+
+// ============================================================================
+// Pricing Commands
+// ============================================================================
+
+/// Model pricing entry as shown in settings
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ModelPricingResponse {
+    #[serde(flatten)]
+    pub pricing: ModelPricing,
+    /// Whether this entry is a user override rather than a built-in default
+    pub is_custom: bool,
+}
+
+/// Load user pricing overrides from the database into the active pricing table
+///
+/// Called once at startup and again whenever an override changes.
+pub fn load_pricing_overrides(db: &db::Database) -> Result<usize, CommandError> {
+    let overrides = db.with_connection(db::queries::get_custom_pricing)?;
+    let count = overrides.len();
+    set_pricing_overrides(overrides);
+    Ok(count)
+}
+
+/// Reject pricing entries that would produce nonsense costs
+fn validate_pricing(pricing: &ModelPricing) -> Result<(), CommandError> {
+    if pricing.model_id.trim().is_empty() {
+        return Err(CommandError::Internal("Model ID must not be empty".to_string()));
+    }
+
+    let rates = [
+        pricing.input_price_per_million,
+        pricing.output_price_per_million,
+        pricing.cache_write_5m_per_million,
+        pricing.cache_write_1h_per_million,
+        pricing.cache_read_per_million,
+    ];
+    if rates.iter().any(|rate| !rate.is_finite() || *rate < 0.0) {
+        return Err(CommandError::Internal(format!(
+            "Invalid pricing for {}: rates must be non-negative numbers",
+            pricing.model_id
+        )));
+    }
+    if let Some(date) = &pricing.effective_from {
+        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+            return Err(CommandError::Internal(format!(
+                "Invalid start date {:?} for {}: use YYYY-MM-DD",
+                date, pricing.model_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Reload overrides and bring stored session costs in line with them;
+/// returns the number of sessions repriced
+fn apply_pricing_change(db: &db::Database) -> Result<u32, CommandError> {
+    load_pricing_overrides(db)?;
+    let updated = db.with_connection(db::queries::recalculate_session_costs)?;
+    tracing::info!("Recalculated costs for {} sessions after pricing change", updated);
+    rebuild_rollups(db)?;
+    clear_derived_caches();
+    Ok(updated)
+}
+
+/// Reprice every stored session and turn with the rates in effect on its
+/// date, and rebuild the daily rollups from the new costs
+///
+/// Pricing changes already do this; run it to fix historical aggregates
+/// stored before a rate was added, e.g. by an older version. Returns the
+/// number of sessions repriced.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn recompute_costs(state: State<'_, AppState>) -> Result<u32, CommandError> {
+    let db = get_database(&state)?;
+    apply_pricing_change(db)
+}
+
+/// Get the pricing table currently used for cost calculations
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_model_pricing(
+    state: State<'_, AppState>,
+) -> Result<Vec<ModelPricingResponse>, CommandError> {
+    let db = get_database(&state)?;
+    let custom_ids: HashSet<(String, CostProvider, Option<String>)> = db
+        .with_connection(db::queries::get_custom_pricing)?
+        .into_iter()
+        .map(|p| (p.model_id, p.provider, p.effective_from))
+        .collect();
+
+    Ok(get_active_pricing()
+        .into_iter()
+        .map(|pricing| ModelPricingResponse {
+            is_custom: custom_ids.contains(&(
+                pricing.model_id.clone(),
+                pricing.provider,
+                pricing.effective_from.clone(),
+            )),
+            pricing,
+        })
+        .collect())
+}
+
+/// Get the models seen in sessions that have no pricing, sorted
+///
+/// Their turns count at zero cost; add a rate with `set_model_pricing` to
+/// price them.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_unpriced_models(_state: State<'_, AppState>) -> Result<Vec<String>, CommandError> {
+    Ok(unpriced_models())
+}
+
+/// Add or update a user pricing override
+///
+/// With `effective_from`, the rates are added for the days from that date
+/// on, and earlier days keep the rates they had. Takes effect immediately:
+/// stored session costs are recalculated and cached aggregates are dropped.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn set_model_pricing(
+    state: State<'_, AppState>,
+    pricing: ModelPricing,
+) -> Result<(), CommandError> {
+    validate_pricing(&pricing)?;
+    let db = get_database(&state)?;
+
+    db.with_connection(|conn| db::queries::upsert_custom_pricing(conn, &pricing))?;
+    apply_pricing_change(db)?;
+    Ok(())
+}
+
+/// Remove a user pricing override, reverting the model to its built-in price
+///
+/// `provider` defaults to the Anthropic API, and `effective_from` to the
+/// undated override. Returns false if the model had no such override.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn reset_model_pricing(
+    state: State<'_, AppState>,
+    model_id: String,
+    provider: Option<CostProvider>,
+    effective_from: Option<String>,
+) -> Result<bool, CommandError> {
+    let db = get_database(&state)?;
+    let provider = provider.unwrap_or_default();
+
+    let removed = db.with_connection(|conn| {
+        db::queries::delete_custom_pricing(conn, &model_id, provider, effective_from.as_deref())
+    })?;
+    if removed {
+        apply_pricing_change(db)?;
+    }
+    Ok(removed)
+}
+
+// ============================================================================
+// Settings Commands
+// ============================================================================
+
+/// Load stored settings from the database and make them active
+///
+/// Called once at startup.
+pub fn load_settings(db: &db::Database) -> Result<AppSettings, CommandError> {
+    let entries = db.with_connection(db::queries::get_settings_entries)?;
+    let mut settings = AppSettings::from_entries(&entries);
+    // Only the patterns fall back: privacy, encryption and the rest must survive
+    if let Some(e) = settings.reset_invalid_patterns() {
+        tracing::warn!("Stored project patterns are invalid, using the default patterns: {}", e);
+    }
+    apply_settings(&settings)
+        .map_err(|e| CommandError::Internal(format!("Invalid project pattern: {}", e)))?;
+    apply_cache_config(&settings.cache);
+    Ok(settings)
+}
+
+/// Get the current application settings
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_settings(
+    state: State<'_, AppState>,
+) -> Result<AppSettings, CommandError> {
+    let db = get_database(&state)?;
+    let entries = db.with_connection(db::queries::get_settings_entries)?;
+    Ok(AppSettings::from_entries(&entries))
+}
+
+/// Save application settings and apply them immediately
+///
+/// Project patterns, webhook URLs, health weights, the timezone and summary
+/// templates are validated before anything is stored.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn set_settings(
+    state: State<'_, AppState>,
+    settings: AppSettings,
+) -> Result<AppSettings, CommandError> {
+    let db = get_database(&state)?;
+
+    if let Some(url) = settings.notifications.invalid_webhook_url() {
+        return Err(CommandError::Internal(format!("Invalid webhook URL: {}", url)));
+    }
+    if !settings.health_weights.is_valid() {
+        return Err(CommandError::Internal(
+            "Health weights must not be negative and at least one must be above zero".to_string(),
+        ));
+    }
+    if settings.reporting_timezone().is_none() {
+        return Err(CommandError::Internal(format!(
+            "Invalid timezone {:?}: use \"UTC\" or an offset such as \"+02:00\"",
+            settings.timezone.as_deref().unwrap_or_default()
+        )));
+    }
+    if !settings.polling.is_valid() {
+        return Err(CommandError::Internal(
+            "Polling intervals must be at least 1 second, the active one no longer than the idle one".to_string(),
+        ));
+    }
+    if settings.claude_roots.iter().any(|root| root.path.trim().is_empty()) {
+        return Err(CommandError::Internal("Claude directory paths must not be empty".to_string()));
+    }
+    if settings.usage_windows.iter().any(|w| !w.is_valid()) {
+        return Err(CommandError::Internal(
+            "Usage windows must be 1 hour to 90 days long with non-negative thresholds".to_string(),
+        ));
+    }
+    if let Some(client) = settings.ipc_tokens.iter().find(|client| !client.is_valid()) {
+        return Err(CommandError::Internal(format!(
+            "IPC client {:?} needs a name and a token of at least {} characters",
+            client.name,
+            IpcToken::MIN_TOKEN_LEN
+        )));
+    }
+    crate::export::template::validate_saved(&settings.summary_templates)
+        .map_err(|e| CommandError::Internal(format!("Invalid summary template: {}", e)))?;
+    crate::settings::ProjectFilter::new(&settings)
+        .map_err(|e| CommandError::Internal(format!("Invalid project pattern: {}", e)))?;
+    if settings.encrypt_content && !crate::settings::encrypt_content() {
+        // Fail before anything is saved if there's no keychain to keep the key in
+        if !crate::keychain::SUPPORTED {
+            return Err(CommandError::Internal(
+                "Content encryption needs the macOS keychain or the Linux Secret Service".to_string(),
+            ));
+        }
+        load_content_cipher(true)?;
+    }
+    if !settings.encrypt_content
+        && crate::settings::encrypt_content()
+        && db.with_connection(crypto::has_encrypted_content)?
+        && load_content_cipher(false)?.is_none()
+    {
+        // Stays on until stored content can be decrypted
+        return Err(CommandError::Internal(
+            "Stored content is encrypted but the keychain has no content key".to_string(),
+        ));
+    }
+    let privacy_enabled = settings.privacy_mode && !crate::settings::privacy_mode();
+    let roots_changed = settings.claude_roots != crate::settings::claude_roots();
+    // Saved before they take effect, so a failed write leaves the running
+    // settings matching the stored ones
+    db.with_connection(|conn| db::queries::upsert_settings_entries(conn, &settings.to_entries()))?;
+    apply_settings(&settings)
+        .map_err(|e| CommandError::Internal(format!("Invalid project pattern: {}", e)))?;
+    // A new timezone moves sessions between days
+    db.with_connection(db::rollups::ensure_current)?;
+    apply_cache_config(&settings.cache);
+    apply_retention_policy(db, &settings)?;
+    apply_privacy_mode(db, &settings)?;
+    apply_content_encryption(db, &settings)?;
+    if roots_changed {
+        // The session list must be rescanned from the new set of directories
+        clear_all_caches();
+    } else if privacy_enabled {
+        // Parsed turns in memory still hold the content
+        clear_cache();
+    }
+    clear_derived_caches();
+
+    Ok(settings)
+}
+
+/// Re-read the stored cache limits and apply them without a restart
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn reload_cache_config(
+    state: State<'_, AppState>,
+) -> Result<CacheConfig, CommandError> {
+    let db = get_database(&state)?;
+    let settings = load_settings(db)?;
+    Ok(settings.cache)
+}
+
+// ============================================================================
+// Update Commands
+// ============================================================================
+
+/// Get the channel update checks look at
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_update_channel() -> Result<UpdateChannel, CommandError> {
+    Ok(active_settings().update_channel)
+}
+
+/// Switch the update channel and store it with the other settings
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn set_update_channel(
+    state: State<'_, AppState>,
+    channel: UpdateChannel,
+) -> Result<UpdateChannel, CommandError> {
+    let db = get_database(&state)?;
+    let value = serde_json::to_string(&channel)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize update channel: {}", e)))?;
+    db.with_connection(|conn| {
+        db::queries::upsert_settings_entries(conn, &[("update_channel".to_string(), value)])
+    })?;
+    let settings = AppSettings {
+        update_channel: channel,
+        ..active_settings()
+    };
+    apply_settings(&settings)
+        .map_err(|e| CommandError::Internal(format!("Invalid project pattern: {}", e)))?;
+    Ok(channel)
+}
+
+/// Look for a release newer than the running version on the configured
+/// channel, with its release notes
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn check_for_updates() -> Result<UpdateInfo, CommandError> {
+    let channel = active_settings().update_channel;
+    let releases = crate::updates::fetch_releases()
+        .await
+        .map_err(|e| CommandError::Internal(format!("Failed to check for updates: {}", e)))?;
+    let info = crate::updates::update_info(env!("CARGO_PKG_VERSION"), channel, &releases);
+    if info.update_available {
+        tracing::info!(
+            "Update available on the {:?} channel: {}",
+            channel,
+            info.latest_version.as_deref().unwrap_or_default()
+        );
+    }
+    Ok(info)
+}
+
+// ============================================================================
+// IPC Commands
+// ============================================================================
+
+/// What an editor extension needs to connect to the IPC socket
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct IpcConnectionInfo {
+    pub name: String,
+    pub token: String,
+    /// Socket path, or the pipe name on Windows
+    pub endpoint: String,
+}
+
+/// Store `ipc_tokens` with the other settings and make them active
+fn save_ipc_tokens(state: &State<'_, AppState>, ipc_tokens: Vec<IpcToken>) -> Result<(), CommandError> {
+    let db = get_database(state)?;
+    let value = serde_json::to_string(&ipc_tokens)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize IPC tokens: {}", e)))?;
+    db.with_connection(|conn| db::queries::upsert_settings_entries(conn, &[("ipc_tokens".to_string(), value)]))?;
+    let settings = AppSettings {
+        ipc_tokens,
+        ..active_settings()
+    };
+    apply_settings(&settings)
+        .map_err(|e| CommandError::Internal(format!("Invalid project pattern: {}", e)))
+}
+
+/// Allow a new client, e.g. an editor extension, to connect to the IPC
+/// socket, returning its token and where to connect
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn create_ipc_token(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<IpcConnectionInfo, CommandError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(CommandError::Internal("IPC client name must not be empty".to_string()));
+    }
+    let mut ipc_tokens = active_settings().ipc_tokens;
+    if ipc_tokens.iter().any(|client| client.name == name) {
+        return Err(CommandError::Internal(format!("An IPC client named {} already exists", name)));
+    }
+    let token = crate::ipc::generate_token().map_err(CommandError::Internal)?;
+    ipc_tokens.push(IpcToken { name: name.clone(), token: token.clone() });
+    save_ipc_tokens(&state, ipc_tokens)?;
+
+    tracing::info!("Added IPC client {}", name);
+    Ok(IpcConnectionInfo { name, token, endpoint: crate::ipc::endpoint() })
+}
+
+/// Remove an IPC client from the allowlist; connections it already has stay
+/// open. Returns true if it was allowlisted.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn revoke_ipc_token(state: State<'_, AppState>, name: String) -> Result<bool, CommandError> {
+    let mut ipc_tokens = active_settings().ipc_tokens;
+    let count = ipc_tokens.len();
+    ipc_tokens.retain(|client| client.name != name);
+    if ipc_tokens.len() == count {
+        return Ok(false);
+    }
+    save_ipc_tokens(&state, ipc_tokens)?;
+    Ok(true)
+}
+
+// ============================================================================
+// Retention Commands
+// ============================================================================
+
+/// Prune sessions outside the retention period configured in settings
+///
+/// Does nothing if no retention period is set. Called at startup and
+/// whenever settings are saved.
+pub fn apply_retention_policy(
+    db: &db::Database,
+    settings: &AppSettings,
+) -> Result<PruneStats, CommandError> {
+    let cutoff = match settings.retention_cutoff() {
+        Some(cutoff) => cutoff,
+        None => return Ok(PruneStats::default()),
+    };
+    let stats = db.with_connection(|conn| retention::prune_sessions_before(conn, &cutoff))?;
+    if stats.sessions_deleted > 0 {
+        rebuild_rollups(db)?;
+        tracing::info!(
+            "Retention: pruned {} sessions ({} turns) inactive since before {}",
+            stats.sessions_deleted,
+            stats.turns_deleted,
+            cutoff
+        );
+    }
+    Ok(stats)
+}
+
+/// Remove message content already stored in the database when privacy mode
+/// is on
+///
+/// Turns parsed from now on are scrubbed by the parser; this covers those
+/// stored before privacy mode was enabled. Called at startup and whenever
+/// settings are saved.
+pub fn apply_privacy_mode(db: &db::Database, settings: &AppSettings) -> Result<u32, CommandError> {
+    if !settings.privacy_mode {
+        return Ok(0);
+    }
+    let scrubbed = db.with_connection(db::queries::scrub_stored_content)?;
+    if scrubbed > 0 {
+        tracing::info!("Privacy mode: removed message content from {} stored turns", scrubbed);
+    }
+    Ok(scrubbed)
+}
+
+/// The content key from the OS keychain, created and stored first if
+/// `create` is set and there is none yet
+fn load_content_cipher(create: bool) -> Result<Option<FieldCipher>, CommandError> {
+    let key_error = |e: String| CommandError::Internal(format!("Content key unavailable: {}", e));
+    if let Some(key) = crate::keychain::load_content_key().map_err(key_error)? {
+        return Ok(Some(FieldCipher::new(&key)));
+    }
+    if !create {
+        return Ok(None);
+    }
+    let key = FieldCipher::generate_key()?;
+    crate::keychain::store_content_key(&key).map_err(key_error)?;
+    Ok(Some(FieldCipher::new(&key)))
+}
+
+/// Encrypt or decrypt the message content stored in the database to match
+/// `encrypt_content`
+///
+/// Enabling encrypts content stored in plaintext so far. Disabling decrypts
+/// it again, and only switches encryption off once that has succeeded: until
+/// then content stays encrypted, or locked without the key. Called at
+/// startup and whenever settings are saved.
+pub fn apply_content_encryption(db: &db::Database, settings: &AppSettings) -> Result<u32, CommandError> {
+    if settings.encrypt_content {
+        let cipher = match load_content_cipher(true) {
+            Ok(cipher) => Arc::new(cipher.expect("created when missing")),
+            Err(e) => {
+                crypto::set_content_encryption(ContentEncryption::Locked);
+                return Err(e);
+            }
+        };
+        crypto::set_content_encryption(ContentEncryption::On(cipher.clone()));
+        let encrypted = db.with_connection(|conn| crypto::encrypt_stored_content(conn, &cipher))?;
+        if encrypted > 0 {
+            tracing::info!("Encrypted {} stored message content values", encrypted);
+        }
+        return Ok(encrypted);
+    }
+
+    // Switched off while holding the write connection, so no content is
+    // stored encrypted between the check or decryption and the switch
+    let switch_off = |cipher: Option<&FieldCipher>| {
+        db.with_connection(|conn| {
+            let decrypted = match cipher {
+                Some(cipher) => crypto::decrypt_stored_content(conn, cipher)?,
+                None if crypto::has_encrypted_content(conn)? => return Ok(None),
+                None => 0,
+            };
+            crypto::set_content_encryption(ContentEncryption::Off);
+            Ok(Some(decrypted))
+        })
+    };
+    if let Some(decrypted) = switch_off(None)? {
+        return Ok(decrypted);
+    }
+
+    let cipher = match load_content_cipher(false) {
+        Ok(Some(cipher)) => cipher,
+        failed => {
+            // Content stays encrypted; without a key in use it's locked
+            if crypto::content_encryption_status() == ContentEncryptionStatus::Off {
+                crypto::set_content_encryption(ContentEncryption::Locked);
+            }
+            return Err(failed.err().unwrap_or_else(|| {
+                CommandError::Internal("Stored content is encrypted but the keychain has no content key".to_string())
+            }));
+        }
+    };
+    let decrypted = switch_off(Some(&cipher))?.unwrap_or_default();
+    tracing::info!("Decrypted {} stored message content values", decrypted);
+    Ok(decrypted)
+}
+
+/// Delete cached sessions (with their turns and metrics) inactive for more
+/// than `days` days
+///
+/// Only the database is touched, never the session files. Sessions whose
+/// files are still on disk are cached again on the next full scan unless
+/// they also fall outside the retention period set in settings.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn prune_sessions_older_than(
+    state: State<'_, AppState>,
+    days: u32,
+) -> Result<PruneStats, CommandError> {
+    if days == 0 {
+        return Err(CommandError::Internal("days must be at least 1".to_string()));
+    }
+    let db = get_database(&state)?;
+
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(days as i64))
+        .to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+    let stats = db.with_connection(|conn| retention::prune_sessions_before(conn, &cutoff))?;
+    if stats.sessions_deleted > 0 {
+        rebuild_rollups(db)?;
+        clear_derived_caches();
+    }
+
+    Ok(stats)
+}
+
+/// Compact the database file, releasing space left by deleted rows
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn vacuum_database(
+    state: State<'_, AppState>,
+) -> Result<VacuumStats, CommandError> {
+    let db = get_database(&state)?;
+    Ok(db.with_connection(retention::vacuum)?)
+}
+
+/// Rebuild the daily rollups after a change to many stored sessions
+fn rebuild_rollups(db: &db::Database) -> Result<u32, CommandError> {
+    let rows = db.with_connection(db::rollups::rebuild)?;
+    tracing::debug!("Rebuilt {} daily rollups", rows);
+    Ok(rows)
+}
+
+/// Recompute the daily rollups behind trends and daily metrics from the
+/// stored sessions
+///
+/// They're kept up to date as sessions are stored; this is for repairing
+/// them. Returns the number of day and project rows written.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn rebuild_daily_rollups(
+    state: State<'_, AppState>,
+) -> Result<u32, CommandError> {
+    let rows = rebuild_rollups(get_database(&state)?)?;
+    clear_derived_caches();
+    Ok(rows)
+}
+
+// ============================================================================
+// Snapshot Commands
+// ============================================================================
+
+/// Write a snapshot of the analytics database for another machine to import
+///
+/// Defaults to a timestamped file in the export directory. Returns the path
+/// written.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn export_snapshot(
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> Result<String, CommandError> {
+    let db = get_database(&state)?;
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => get_export_directory().join(generate_export_filename("ironhide_snapshot", "db")),
+    };
+    db.with_connection(|conn| snapshot::export_snapshot(conn, &path))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Merge a snapshot written on another machine
+///
+/// Sessions are matched by ID and the copy with the later activity wins.
+/// Imported sessions count towards the dashboard, trends and project
+/// totals; their session files stay on the other machine.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn import_snapshot(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<SnapshotImportStats, CommandError> {
+    let db = get_database(&state)?;
+    let stats = db.with_connection(|conn| snapshot::import_snapshot(conn, Path::new(&path)))?;
+    if stats.sessions_added + stats.sessions_updated > 0 {
+        settle_merged_content(db)?;
+        rebuild_rollups(db)?;
+        clear_derived_caches();
+    }
+    Ok(stats)
+}
+
+/// Bring content merged in from a snapshot, which arrives in plaintext, in
+/// line with the settings: encrypted while encryption is on
+fn settle_merged_content(db: &db::Database) -> Result<(), CommandError> {
+    db.with_connection(crypto::encrypt_plaintext_content)?;
+    Ok(())
+}
+
+/// Snapshot picked up on first launch when placed next to the database
+const BOOTSTRAP_SNAPSHOT_FILE: &str = "bootstrap.db";
+
+fn bootstrap_snapshot_path(db: &db::Database) -> PathBuf {
+    db.path().with_file_name(BOOTSTRAP_SNAPSHOT_FILE)
+}
+
+fn bootstrap(state: &AppState, path: &Path) -> Result<BootstrapStats, CommandError> {
+    let db = get_database(state)?;
+    let local_files: HashMap<String, PathBuf> = scan_claude_sessions()
+        .into_iter()
+        .map(|s| (s.session_id, s.path))
+        .collect();
+    let stats = db.with_connection(|conn| snapshot::bootstrap_snapshot(conn, path, &local_files))?;
+    settle_merged_content(db)?;
+    rebuild_rollups(db)?;
+    clear_all_caches();
+    tracing::info!(
+        "Bootstrapped {} sessions from {:?} ({} remote)",
+        stats.sessions_imported,
+        path,
+        stats.sessions_remote
+    );
+    Ok(stats)
+}
+
+/// Fill an empty database from a snapshot exported on another machine
+///
+/// Skips the full parse of a first launch: sessions come from the snapshot
+/// with their metrics and turns. Those whose files aren't on this machine
+/// are marked remote and shown from the stored data only. Defaults to
+/// `bootstrap.db` next to the database. Fails once the database has
+/// sessions; use `import_snapshot` to merge then.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn bootstrap_from_snapshot(
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> Result<BootstrapStats, CommandError> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => bootstrap_snapshot_path(get_database(&state)?),
+    };
+    bootstrap(&state, &path)
+}
+
+/// Bootstrap from `bootstrap.db` next to the database if one was shipped
+/// and nothing is stored yet
+///
+/// Called once at startup. The file is renamed afterwards so it's only
+/// imported once.
+pub fn bootstrap_on_first_launch(state: &AppState) {
+    let Some(db) = state.db.get() else { return };
+    let path = bootstrap_snapshot_path(db);
+    if !path.is_file() {
+        return;
+    }
+    let empty = db
+        .with_read_connection(db::queries::get_cached_session_count)
+        .is_ok_and(|count| count == 0);
+    if !empty {
+        return;
+    }
+    match bootstrap(state, &path) {
+        Ok(_) => {
+            if let Err(e) = std::fs::rename(&path, path.with_extension("db.imported")) {
+                tracing::warn!("Failed to rename {:?} after bootstrapping: {}", path, e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to bootstrap from {:?}: {:?}", path, e),
+    }
+}
+
+/// Sync through a shared folder: merge the other machines' snapshots found
+/// there, then write this machine's
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn sync_snapshot_folder(
+    state: State<'_, AppState>,
+    folder: String,
+) -> Result<SnapshotSyncReport, CommandError> {
+    let db = get_database(&state)?;
+    let report = db.with_connection(|conn| snapshot::sync_snapshot_folder(conn, Path::new(&folder)))?;
+    if report.stats.sessions_added + report.stats.sessions_updated > 0 {
+        settle_merged_content(db)?;
+        rebuild_rollups(db)?;
+        clear_derived_caches();
+    }
+    Ok(report)
+}
+
+// ============================================================================
+// Team Roll-up Commands
+// ============================================================================
+
+/// Default period of the team trends, in days
+const DEFAULT_TEAM_TREND_DAYS: u32 = 30;
+
+/// Import a teammate's JSON session export under their name
+///
+/// The file is what `export_sessions` writes with the json format. Team
+/// sessions are stored apart from this machine's and only appear in the
+/// team dashboard and trends; a newer export of the same user updates the
+/// sessions imported before.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn import_team_export(
+    state: State<'_, AppState>,
+    path: String,
+    user_name: String,
+) -> Result<TeamImportStats, CommandError> {
+    let user_name = user_name.trim();
+    if user_name.is_empty() {
+        return Err(CommandError::Internal("user_name must not be empty".to_string()));
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| CommandError::Internal(format!("Failed to read {}: {}", path, e)))?;
+    let export: team::TeamExportFile = serde_json::from_str(&content)
+        .map_err(|e| CommandError::Internal(format!("{} is not a JSON session export: {}", path, e)))?;
+
+    let db = get_database(&state)?;
+    let imported_at = chrono::Utc::now().to_rfc3339();
+    let stats = db.with_connection(|conn| team::import_team_export(conn, user_name, &path, &export, &imported_at))?;
+    tracing::info!(
+        "Imported {} new and {} updated team sessions for {}",
+        stats.sessions_added,
+        stats.sessions_updated,
+        user_name
+    );
+    Ok(stats)
+}
+
+/// Get the users with imported team sessions
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_team_members(state: State<'_, AppState>) -> Result<Vec<TeamMember>, CommandError> {
+    let db = get_database(&state)?;
+    Ok(db.with_read_connection(team::get_team_members)?)
+}
+
+/// Remove a user from the team roll-up; returns the sessions removed
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn remove_team_member(
+    state: State<'_, AppState>,
+    user_name: String,
+) -> Result<u32, CommandError> {
+    let db = get_database(&state)?;
+    Ok(db.with_connection(|conn| team::remove_team_member(conn, &user_name))?)
+}
+
+/// Get team totals for the last `days` days (all time when omitted), per user
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_team_dashboard(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<TeamDashboard, CommandError> {
+    let db = get_database(&state)?;
+    Ok(db.with_read_connection(|conn| team::get_team_dashboard(conn, days))?)
+}
+
+/// Get daily team usage split by user (default: last 30 days)
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_team_trends(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<Vec<TeamDailyTrend>, CommandError> {
+    let db = get_database(&state)?;
+    let days = days.unwrap_or(DEFAULT_TEAM_TREND_DAYS);
+    Ok(db.with_read_connection(|conn| team::get_team_trends(conn, days))?)
+}
+
+// ============================================================================
+// Archive Commands
+// ============================================================================
+
+/// Session files moved into the archive
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ArchiveReport {
+    pub sessions_archived: u32,
+    /// Size of the archived files before compression
+    pub bytes_before: u64,
+    /// Size of the archived files after compression
+    pub bytes_after: u64,
+    /// Files that could not be archived, with the reason
+    pub failed: Vec<String>,
+}
+
+/// Projects directory of the default Claude directory, the only one sessions
+/// are archived from
+fn claude_projects_dir() -> Result<PathBuf, CommandError> {
+    crate::parser::scan::default_claude_dir()
+        .map(|dir| dir.join("projects"))
+        .ok_or_else(|| CommandError::Internal("Could not determine home directory".to_string()))
+}
+
+/// Make sure a session and its turns are stored for the file's current mtime
+///
+/// Archived sessions are only ever parsed again to show their detail view,
+/// so their metrics must be in the database before the file is moved.
+fn ensure_session_stored(state: &AppState, file_info: &SessionFileInfo) -> Result<(), CommandError> {
+    let mtime = get_file_mtime(&file_info.path)
+        .ok_or_else(|| CommandError::Internal(format!("Cannot stat {}", file_info.path.display())))?;
+    let db = get_database(state)?;
+    let stored = db.with_connection(|conn| {
+        let session_valid = db::queries::get_session_file_mtime(conn, &file_info.session_id)?
+            .is_some_and(|stored| mtime_matches(&stored, &mtime));
+        let turns_valid = db::queries::get_session_turns_mtime(conn, &file_info.session_id)?
+            .is_some_and(|stored| mtime_matches(&stored, &mtime));
+        Ok(session_valid && turns_valid)
+    })?;
+    if stored {
+        return Ok(());
+    }
+
+    let turns = crate::parser::session::parse_session_to_turns(&file_info.path)
+        .map_err(|e| CommandError::Parser(e.to_string()))?;
+    let summary = compute_session_summary(file_info);
+    let (session_tokens, _, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
+    store_session_to_db(state, file_info, &summary, &session_tokens, &mtime);
+    store_turns_to_db(state, &file_info.session_id, &turns, &mtime);
+    Ok(())
+}
+
+/// Compress session files inactive for more than `days` days into the archive
+///
+/// Only files under ~/.claude/projects are archived; imported directories are
+/// left alone. Metrics are stored to the database first and stay there, and
+/// archived sessions remain listed and can still be opened.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn archive_sessions_older_than(
+    state: State<'_, AppState>,
+    days: u32,
+) -> Result<ArchiveReport, CommandError> {
+    if days == 0 {
+        return Err(CommandError::Internal("days must be at least 1".to_string()));
+    }
+    let db = get_database(&state)?;
+    let projects_dir = claude_projects_dir()?;
+    let archive_dir = crate::parser::archive::default_archive_dir();
+    let cutoff = SystemTime::now() - Duration::from_secs(days as u64 * 24 * 60 * 60);
+
+    let candidates: Vec<SessionFileInfo> = scan_claude_sessions()
+        .into_iter()
+        .filter(|s| {
+            s.modified < cutoff
+                && s.path.starts_with(&projects_dir)
+                && !crate::parser::archive::is_compressed(&s.path)
+        })
+        .collect();
+
+    let mut report = ArchiveReport::default();
+    for file_info in candidates {
+        let archived = ensure_session_stored(&state, &file_info).and_then(|_| {
+            crate::parser::archive::archive_file(&file_info.path, &projects_dir, &archive_dir)
+                .map_err(|e| CommandError::Internal(e.to_string()))
+        });
+        let archived = match archived {
+            Ok(path) => path,
+            Err(e) => {
+                report.failed.push(format!("{}: {}", file_info.path.display(), e));
+                continue;
+            }
+        };
+
+        db.with_connection(|conn| {
+            db::queries::update_session_file_path(conn, &file_info.session_id, &archived.to_string_lossy())
+        })?;
+        report.sessions_archived += 1;
+        report.bytes_before += file_info.size;
+        report.bytes_after += std::fs::metadata(&archived).map(|m| m.len()).unwrap_or(0);
+    }
+
+    if report.sessions_archived > 0 {
+        clear_all_caches();
+        tracing::info!(
+            "Archived {} sessions ({} -> {} bytes)",
+            report.sessions_archived,
+            report.bytes_before,
+            report.bytes_after
+        );
+    }
+
+    Ok(report)
+}
+
+/// Move an archived session back to ~/.claude/projects, uncompressed
+///
+/// Returns the restored file path.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn restore_session(
+    state: State<'_, AppState>,
+    session_id: String,
+) -> Result<String, CommandError> {
+    let db = get_database(&state)?;
+    let file_info = find_session_by_id(&session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.clone()))?;
+    if !crate::parser::archive::is_compressed(&file_info.path) {
+        return Err(CommandError::Internal(format!("Session {} is not archived", session_id)));
+    }
+
+    let restored = crate::parser::archive::restore_file(
+        &file_info.path,
+        &crate::parser::archive::default_archive_dir(),
+        &claude_projects_dir()?,
+    )
+    .map_err(|e| CommandError::Internal(format!("Failed to restore {}: {}", session_id, e)))?;
+    let restored = restored.to_string_lossy().into_owned();
+
+    db.with_connection(|conn| db::queries::update_session_file_path(conn, &session_id, &restored))?;
+    clear_all_caches();
+
+    Ok(restored)
 }
 
 // ============================================================================
-// Developer Performance Commands
+// Import Commands
 // ============================================================================
 
-/// Auto-detect GitHub configuration from environment.
+/// Result of importing sessions from a directory
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ImportResult {
+    /// Source label the sessions were stored under
+    pub source: String,
+    /// Canonical path of the imported directory
+    pub path: String,
+    /// JSONL files found under the directory
+    pub files_found: u32,
+    /// Sessions parsed and stored
+    pub sessions_imported: u32,
+    /// Sessions skipped because they are already tracked from ~/.claude or
+    /// another source
+    pub duplicates: u32,
+    /// Files that don't parse as Claude Code sessions
+    pub invalid_files: Vec<String>,
+}
+
+/// Register the import sources stored in the database for scanning
 ///
-/// Checks gh CLI auth, env vars, and git remote to determine available config.
-#[tauri::command]
-pub async fn detect_github_config() -> Result<GitHubConfigResponse, CommandError> {
-    use crate::github::{detect_github_token, detect_github_username};
+/// Called once at startup. Sources whose directory no longer exists are
+/// kept registered so their sessions come back if it is remounted.
+pub fn load_import_sources(db: &db::Database) -> Result<usize, CommandError> {
+    let sources: Vec<ImportSource> = db
+        .with_connection(db::queries::get_import_sources)?
+        .into_iter()
+        .map(|(path, label)| ImportSource {
+            root: PathBuf::from(path),
+            label,
+        })
+        .collect();
+    let count = sources.len();
+    crate::parser::scan::set_import_sources(sources);
+    Ok(count)
+}
 
-    let token = detect_github_token();
-    let token_source = if token.is_some() {
-        // Determine source
-        if std::process::Command::new("gh").args(["auth", "token"]).output()
-            .map(|o| o.status.success()).unwrap_or(false) {
-            Some("gh_cli".to_string())
-        } else if std::env::var("GITHUB_TOKEN").is_ok() {
-            Some("GITHUB_TOKEN".to_string())
-        } else {
-            Some("GH_TOKEN".to_string())
-        }
-    } else {
-        None
+/// Import Claude Code sessions from a directory outside ~/.claude
+///
+/// Scans the directory tree for JSONL files, keeps those that parse into at
+/// least one turn, and stores them in the database under `label` (default:
+/// the directory name). The directory is remembered, so its sessions are
+/// listed alongside those from ~/.claude from then on and re-importing it
+/// picks up new files.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn import_sessions_from_path(
+    state: State<'_, AppState>,
+    path: String,
+    label: Option<String>,
+) -> Result<ImportResult, CommandError> {
+    let db = get_database(&state)?;
+
+    let root = std::fs::canonicalize(&path)
+        .map_err(|e| CommandError::Internal(format!("Cannot read {}: {}", path, e)))?;
+    if !root.is_dir() {
+        return Err(CommandError::Internal(format!("Not a directory: {}", path)));
+    }
+    let label = label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .or_else(|| root.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "import".to_string());
+    let source = ImportSource {
+        root: root.clone(),
+        label: label.clone(),
     };
 
-    let username = detect_github_username();
+    let known: HashSet<String> = scan_claude_sessions()
+        .into_iter()
+        .filter(|s| s.source.is_none() || !s.path.starts_with(&root))
+        .map(|s| s.session_id)
+        .collect();
+    let files = crate::parser::scan::scan_import_dir(&source);
+
+    let mut result = ImportResult {
+        source: label.clone(),
+        path: root.to_string_lossy().into_owned(),
+        files_found: files.len() as u32,
+        sessions_imported: 0,
+        duplicates: 0,
+        invalid_files: Vec::new(),
+    };
 
-    Ok(GitHubConfigResponse {
-        has_token: token.is_some(),
-        token_source,
-        username,
-    })
+    let mut valid = Vec::new();
+    for file_info in files {
+        if known.contains(&file_info.session_id) {
+            result.duplicates += 1;
+            continue;
+        }
+        match crate::parser::session::parse_session_to_turns(&file_info.path) {
+            Ok(turns) if !turns.is_empty() => valid.push((file_info, turns)),
+            _ => result
+                .invalid_files
+                .push(file_info.path.to_string_lossy().into_owned()),
+        }
+    }
+
+    db.with_connection(|conn| {
+        db::queries::upsert_import_source(conn, &result.path, &label)
+    })?;
+    crate::parser::scan::add_import_source(source);
+    clear_all_caches();
+
+    for (file_info, turns) in valid {
+        let mtime = match get_file_mtime(&file_info.path) {
+            Some(mtime) => mtime,
+            None => continue,
+        };
+        let summary = compute_session_summary(&file_info);
+        let (session_tokens, _, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
+        store_session_to_db(&state, &file_info, &summary, &session_tokens, &mtime);
+        store_turns_to_db(&state, &file_info.session_id, &turns, &mtime);
+        result.sessions_imported += 1;
+    }
+    clear_derived_caches();
+
+    tracing::info!(
+        "Imported {} sessions from {} as '{}' ({} duplicates, {} invalid)",
+        result.sessions_imported,
+        result.path,
+        label,
+        result.duplicates,
+        result.invalid_files.len()
+    );
+
+    Ok(result)
 }
 
-/// Get developer performance metrics (3-axis AI adoption metrics)
+// ============================================================================
+// Project Registry
+// ============================================================================
+
+/// Register the project path resolutions stored in the database
 ///
-/// Requires GitHub integration: owner, repo, and username. Token is auto-detected.
-/// `sprint_days` configures the sprint length (default 14).
-/// Analyzes the last 4 sprints and uses the 4 sprints before that as baseline.
-#[tauri::command]
-pub async fn get_developer_metrics(
-    _state: tauri::State<'_, AppState>,
-    github_username: String,
-    sprint_days: Option<u32>,
-    num_sprints: Option<u32>,
-) -> Result<DeveloperPerformanceResponse, CommandError> {
-    use crate::github::{detect_github_token, fetch_sprint_github_data};
-    use crate::metrics::developer::{calculate_developer_metrics, SprintInput};
+/// Called once at startup, before the first scan, so directories resolved
+/// in an earlier run aren't resolved again.
+pub fn load_project_registry(db: &db::Database) -> Result<usize, CommandError> {
+    let resolutions = db.with_connection(db::queries::get_project_registry)?;
+    let count = resolutions.len();
+    registry::set_registry(resolutions);
+    Ok(count)
+}
 
-    let token = detect_github_token()
-        .ok_or_else(|| CommandError::Internal(
-            "No GitHub token found. Install gh CLI and run 'gh auth login', or set GITHUB_TOKEN env var.".to_string()
-        ))?;
+/// Store the resolutions made by scans since the last call
+fn save_project_registry(db: &db::Database) -> Result<(), CommandError> {
+    let resolutions = registry::take_unsaved();
+    if resolutions.is_empty() {
+        return Ok(());
+    }
+    db.with_connection(|conn| {
+        for resolution in &resolutions {
+            db::queries::upsert_project_resolution(conn, resolution)?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
 
-    let sprint_len = sprint_days.unwrap_or(14).clamp(1, 90);
-    let now = chrono::Utc::now();
+/// Correct the project path of an encoded project directory
+///
+/// `project_dir` is the directory's name under `projects/`, e.g.
+/// `-Users-me-my-app`. With a `project_path` the directory is pinned to it;
+/// without one the override is removed and the directory resolved again.
+/// Sessions already stored from the directory move to the new path.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn set_project_path_override(
+    state: State<'_, AppState>,
+    project_dir: String,
+    project_path: Option<String>,
+) -> Result<ProjectResolution, CommandError> {
+    let db = get_database(&state)?;
 
-    // Analyze N recent sprints + N baseline sprints = 2N total
-    let analysis_sprints = num_sprints.unwrap_or(4).clamp(1, 26);
-    let total_sprints = analysis_sprints.saturating_mul(2);
+    let project_dir = project_dir.trim().to_string();
+    if project_dir.is_empty() || project_dir.contains(['/', '\\']) {
+        return Err(CommandError::Internal(format!("Not a project directory name: {:?}", project_dir)));
+    }
 
-    // Bug 4 fix: create HTTP client once and reuse across all sprint fetches
-    let client = reqwest::Client::new();
+    let resolution = match project_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let resolution = ProjectResolution {
+                encoded_dir: project_dir.clone(),
+                project_path: project_path::normalize_project_path(path),
+                source: ResolutionSource::Override,
+                ambiguous: false,
+            };
+            db.with_connection(|conn| db::queries::upsert_project_resolution(conn, &resolution))?;
+            resolution
+        }
+        None => {
+            // Resolve from the sessions of the first root holding the directory
+            let files = crate::parser::scan::scan_roots()
+                .into_iter()
+                .map(|root| root.path.join("projects").join(&project_dir))
+                .find(|dir| dir.is_dir())
+                .and_then(|dir| std::fs::read_dir(dir).ok())
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| entry.path())
+                        .filter(|path| crate::parser::archive::is_session_file(path))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let resolution = registry::resolve(&project_dir, &files).ok_or_else(|| {
+                CommandError::Internal(format!("Cannot resolve project directory {:?}", project_dir))
+            })?;
+            db.with_connection(|conn| {
+                db::queries::delete_project_resolution(conn, &project_dir)?;
+                if resolution.source != ResolutionSource::Decoded {
+                    db::queries::upsert_project_resolution(conn, &resolution)?;
+                }
+                Ok(())
+            })?;
+            resolution
+        }
+    };
 
-    let mut all_sprint_inputs: Vec<SprintInput> = Vec::new();
+    registry::register(resolution.clone());
+    let project_name = extract_project_name(&resolution.project_path);
+    let moved = db.with_connection(|conn| {
+        db::queries::move_project_dir_sessions(conn, &project_dir, &resolution.project_path, &project_name)
+    })?;
+    if moved > 0 {
+        rebuild_rollups(db)?;
+    }
+    clear_all_caches();
+    drop_aggregate_caches();
 
-    for i in 0..total_sprints {
-        let sprint_end = now - chrono::Duration::days((i * sprint_len) as i64);
-        let sprint_start = sprint_end - chrono::Duration::days(sprint_len as i64);
+    tracing::info!(
+        "Project directory {} now resolves to {} ({}, {} sessions moved)",
+        project_dir,
+        resolution.project_path,
+        resolution.source.as_str(),
+        moved
+    );
 
-        let since = sprint_start.format("%Y-%m-%d").to_string();
-        let until = sprint_end.format("%Y-%m-%d").to_string();
+    Ok(resolution)
+}
 
-        // Fetch GitHub data for this sprint (searches across all repos)
-        let github_data = fetch_sprint_github_data(
-            &client,
-            &token,
-            &github_username,
-            &since,
-            &until,
-            sprint_len,
-        )
-        .await
-        .map_err(|e| CommandError::Internal(e))?;
+// ============================================================================
+// Watcher Sync
+// ============================================================================
 
-        // Calculate CC spend for this sprint window from local sessions
-        let cc_spend = compute_cc_spend_for_range(&sprint_start, &sprint_end);
+/// Sessions stored by a watcher-driven sync
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    /// Summaries of the sessions that were re-parsed and stored
+    pub sessions: Vec<SessionSummary>,
+    /// Changed files that aren't session files or have no turns yet
+    pub skipped: u32,
+}
 
-        all_sprint_inputs.push(SprintInput {
-            prs_merged: github_data.prs_merged,
-            concurrent_commit_days: github_data.concurrent_commit_days,
-            sprint_days: sprint_len,
-            cc_spend_usd: cc_spend,
-            start_date: since.clone(),
-            end_date: until.clone(),
-        });
+/// Re-parse changed session files and store them in the database
+///
+/// Called by the watcher's sync worker with the files changed since its last
+/// batch. Files are parsed in parallel on the rayon pool, then each session's
+/// row, metrics and turns are upserted and its cached summary replaced. The
+/// aggregate caches are dropped so the next dashboard request sees the change.
+pub fn sync_session_files(state: &AppState, paths: &[PathBuf]) -> SyncReport {
+    use rayon::prelude::*;
+
+    // Rescan so files created since the last listing are known
+    let sessions = scan_claude_sessions();
+    let wanted: HashSet<&PathBuf> = paths.iter().collect();
+    let changed: Vec<SessionFileInfo> = sessions
+        .iter()
+        .filter(|s| wanted.contains(&s.path))
+        .cloned()
+        .collect();
+    if let Ok(mut cache) = SESSION_LIST_CACHE.write() {
+        cache.sessions = sessions;
+        cache.last_refresh = Instant::now();
     }
 
-    // First 4 are analysis (most recent), last 4 are baseline
-    // Note: they're in reverse chronological order (0=most recent)
-    let analysis: Vec<SprintInput> = all_sprint_inputs[..analysis_sprints as usize].to_vec();
-    let baseline: Vec<SprintInput> = all_sprint_inputs[analysis_sprints as usize..].to_vec();
+    let parsed: Vec<(SessionFileInfo, SessionSummary, SharedTurns, Option<String>)> = changed
+        .into_par_iter()
+        .filter_map(|file_info| {
+            let (turns, _) = get_session_turns(&file_info.session_id).ok()?;
+            if turns.is_empty() {
+                return None;
+            }
+            let summary = compute_session_summary(&file_info);
+            let mtime = get_file_mtime(&file_info.path);
+            Some((file_info, summary, turns, mtime))
+        })
+        .collect();
 
-    let baseline_ref = if baseline.iter().any(|s| s.prs_merged > 0) {
-        Some(baseline.as_slice())
-    } else {
-        None
+    let mut report = SyncReport {
+        sessions: Vec::with_capacity(parsed.len()),
+        skipped: (paths.len() - parsed.len()) as u32,
     };
+    for (file_info, summary, turns, mtime) in parsed {
+        if let Some(mtime) = mtime.as_ref().filter(|m| !is_past_retention(m)) {
+            let (session_tokens, _, _, _, _, _, _) = calculate_metrics_from_turns(&turns);
+            store_session_to_db(state, &file_info, &summary, &session_tokens, mtime);
+            store_turns_to_db(state, &file_info.session_id, &turns, mtime);
+        }
+        if let Ok(mut list_cache) = SESSION_LIST_CACHE.write() {
+            list_cache.summaries.insert(file_info.session_id.clone(), summary.clone());
+        }
+        report.sessions.push(summary);
+    }
 
-    let metrics = calculate_developer_metrics(&analysis, baseline_ref);
-    Ok(DeveloperPerformanceResponse::from(metrics))
+    if !report.sessions.is_empty() {
+        clear_aggregate_caches();
+        add_subagent_costs(state, &mut report.sessions);
+    }
+    report
 }
 
-/// Compute total Claude Code spend for a date range from local session data.
-fn compute_cc_spend_for_range(
-    start: &chrono::DateTime<chrono::Utc>,
-    end: &chrono::DateTime<chrono::Utc>,
-) -> f64 {
-    let all_sessions = get_cached_session_list();
-    let mut total_cost = 0.0;
+/// Notification events raised by a sync batch
+///
+/// Anti-patterns are looked for in the synced sessions; the daily budget and
+/// cost anomalies are checked for today. Only the checks enabled in settings
+/// run, and none at all without a webhook to send to.
+pub fn sync_notification_events(state: &AppState, report: &SyncReport) -> Vec<NotificationEvent> {
+    let settings = active_settings().notifications;
+    let mut events = Vec::new();
+    if settings.webhook_urls.is_empty() || report.sessions.is_empty() {
+        return events;
+    }
 
-    for file_info in &all_sessions {
-        if file_info.is_subagent {
-            continue;
+    if settings.antipattern_found {
+        let thresholds = DetectionThresholds::default();
+        for summary in &report.sessions {
+            let turns = match get_session_turns(&summary.id) {
+                Ok((turns, _)) => turns,
+                Err(_) => continue,
+            };
+            events.extend(
+                detect_in_turns(&summary.id, &turns, &AntiPatternType::all(), &thresholds)
+                    .into_iter()
+                    .filter(|p| p.severity != Severity::Info)
+                    .map(|p| NotificationEvent::AntiPatternFound {
+                        session_id: p.session_id,
+                        pattern_type: p.pattern_type,
+                        severity: p.severity,
+                        turn_number: p.turn_number,
+                        description: p.description,
+                        impact_cost: p.impact_cost,
+                    }),
+            );
         }
+    }
 
-        let summary = get_cached_summary(&file_info);
-        if summary.total_turns == 0 {
-            continue;
+    let check_budget = settings.budget_exceeded && settings.daily_budget.is_some();
+    if check_budget || settings.anomaly_detected {
+        let today = days_ago(0);
+        let daily = load_daily_trends(state, None, Segment::All);
+
+        if let (true, Some(budget), Some(day)) = (
+            check_budget,
+            settings.daily_budget,
+            daily.iter().find(|d| d.date == today),
+        ) {
+            if day.total_cost > budget {
+                events.push(NotificationEvent::BudgetExceeded {
+                    date: today.clone(),
+                    cost: day.total_cost,
+                    budget,
+                });
+            }
         }
 
-        if !is_real_user_project(&summary.project_path) {
-            continue;
+        if settings.anomaly_detected {
+            let window = statistics::DEFAULT_WINDOW_DAYS;
+            events.extend(
+                detect_cost_anomalies(&daily, window, statistics::DEFAULT_SIGMA)
+                    .into_iter()
+                    .filter(|a| a.date == today)
+                    .map(|a| NotificationEvent::AnomalyDetected {
+                        date: a.date,
+                        cost: a.cost,
+                        baseline_mean: a.baseline_mean,
+                        z_score: a.z_score,
+                    }),
+            );
         }
+    }
 
-        let started = match chrono::DateTime::parse_from_rfc3339(&summary.started_at) {
-            Ok(dt) => dt.with_timezone(&chrono::Utc),
-            Err(_) => continue,
-        };
+    events
+}
 
-        if started >= *start && started < *end {
-            total_cost += summary.total_cost;
+/// Usage windows that reached a threshold since the tracker last looked
+///
+/// Checked after each sync batch; windows without thresholds never warn.
+pub fn usage_window_warnings(state: &AppState, tracker: &mut UsageThresholdTracker) -> Vec<UsageWindowWarning> {
+    let has_thresholds = active_settings()
+        .usage_windows
+        .iter()
+        .any(|w| w.token_threshold.is_some() || w.cost_threshold.is_some());
+    if !has_thresholds {
+        return Vec::new();
+    }
+    match current_usage_windows(state) {
+        Ok(windows) => tracker.check(&windows),
+        Err(e) => {
+            tracing::warn!("Failed to check usage windows: {}", e);
+            Vec::new()
         }
     }
+}
 
-    total_cost
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// The analytics database's file, schema and size
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DatabaseDiagnostics {
+    pub path: String,
+    pub size_bytes: u64,
+    pub schema_version: u32,
+    /// Latest schema version this build knows; higher than `schema_version`
+    /// when a migration failed
+    pub latest_schema_version: u32,
+}
+
+/// Fill of the in-memory caches
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CacheDiagnostics {
+    /// Session files in the cached scan
+    pub listed_sessions: u32,
+    pub cached_summaries: u32,
+    /// Sessions whose parsed turns are held in memory
+    pub cached_sessions: u32,
+    pub cached_bytes: u64,
+    pub budget_bytes: u64,
+}
+
+/// Environment health, for the diagnostics panel
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DiagnosticsReport {
+    /// Claude data directory ($CLAUDE_CONFIG_DIR or ~/.claude)
+    pub claude_dir: Option<String>,
+    /// Whether it has a `projects` directory
+    pub claude_dir_found: bool,
+    /// The Claude directory plus extra roots from settings
+    pub scan_roots: u32,
+    /// None when the database couldn't be opened
+    pub database: Option<DatabaseDiagnostics>,
+    pub cache: CacheDiagnostics,
+    /// When a preload last finished or stopped (RFC 3339)
+    pub last_preload_at: Option<String>,
+    pub watcher: WatcherStatus,
+    /// Locked when `encrypt_content` is on but the keychain has no usable
+    /// key; message content is then neither shown nor stored
+    pub content_encryption: ContentEncryptionStatus,
+    /// Startup steps that failed; the app runs without them
+    pub init_errors: Vec<InitError>,
+}
+
+/// Get the health of the backend: where sessions are read from, the
+/// database, caches, preload and watcher, and startup errors
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_diagnostics(state: State<'_, AppState>) -> Result<DiagnosticsReport, CommandError> {
+    let claude_dir = crate::parser::scan::default_claude_dir();
+    let database = match state.db.get() {
+        Some(db) => Some(db.with_read_connection(|conn| {
+            let size: i64 = conn.query_row(
+                "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                [],
+                |row| row.get(0),
+            )?;
+            Ok(DatabaseDiagnostics {
+                path: db.path().to_string_lossy().to_string(),
+                size_bytes: size as u64,
+                schema_version: db::migrations::current_version(conn)?,
+                latest_schema_version: db::migrations::MIGRATIONS.iter().map(|m| m.version).max().unwrap_or(0),
+            })
+        })?),
+        None => None,
+    };
+
+    let (listed_sessions, cached_summaries) = SESSION_LIST_CACHE
+        .read()
+        .map(|cache| (cache.sessions.len() as u32, cache.summaries.len() as u32))
+        .unwrap_or_default();
+    let (cached_sessions, cached_bytes, budget_bytes) = SESSION_CACHE
+        .lock()
+        .map(|cache| (cache.entries.len() as u32, cache.total_bytes as u64, cache.budget_bytes as u64))
+        .unwrap_or_default();
+
+    Ok(DiagnosticsReport {
+        claude_dir_found: claude_dir.as_ref().is_some_and(|dir| dir.join("projects").is_dir()),
+        claude_dir: claude_dir.map(|dir| dir.to_string_lossy().to_string()),
+        scan_roots: crate::parser::scan::scan_roots().len() as u32,
+        database,
+        cache: CacheDiagnostics {
+            listed_sessions,
+            cached_summaries,
+            cached_sessions,
+            cached_bytes,
+            budget_bytes,
+        },
+        last_preload_at: state.preload.last_finished(),
+        watcher: state.diagnostics.watcher_status(),
+        content_encryption: crypto::content_encryption_status(),
+        init_errors: state.diagnostics.init_errors(),
+    })
+}
+
+/// Get the non-fatal errors commands worked around since startup, most
+/// recent first, with how often each happened
+#[cfg_attr(feature = "gui", tauri::command)]
+pub async fn get_backend_errors(state: State<'_, AppState>) -> Result<Vec<BackendError>, CommandError> {
+    Ok(state.diagnostics.backend_errors())
 }
 
 // ============================================================================
@@ -3339,6 +8700,7 @@ mod tests {
             "myapp"
         );
         assert_eq!(extract_project_name("/some/path/project"), "project");
+        assert_eq!(extract_project_name("C:\\Users\\me\\app"), "app");
         assert_eq!(extract_project_name(""), "Unknown");
     }
 
@@ -3352,6 +8714,8 @@ mod tests {
             cache_write_1h: 50,
             total: 3650, // 1000+500+2000+100+50
             context_used_pct: 75.0, // 0-100 scale
+            thinking: 200,
+            thinking_share: 0.4,
         };
 
         let json = serde_json::to_string(&summary).unwrap();
@@ -3369,6 +8733,7 @@ mod tests {
             last_activity_at: Some("2026-01-14T08:00:00.000Z".to_string()),
             model: Some("claude-opus-4-5-20251101".to_string()),
             total_cost: 5.25,
+            total_cost_including_subagents: 5.25,
             total_turns: 10,
             total_tokens: 50000,
             duration_ms: 900000,
@@ -3398,6 +8763,7 @@ mod tests {
                 cache_read: 1000,
                 cache_write: 500,
                 total: 150,
+                thinking: 0,
             },
             cost: 0.15,
             tool_count: 2,
@@ -3490,6 +8856,7 @@ mod tests {
             last_activity_at: Some("2026-01-14T08:00:00.000Z".to_string()),
             model: Some("claude-opus-4-5-20251101".to_string()),
             total_cost: 3.00,
+            total_cost_including_subagents: 3.00,
             total_turns: 5,
             total_tokens: 30000,
             duration_ms: 600000,
@@ -3506,6 +8873,7 @@ mod tests {
             last_activity_at: Some("2026-01-14T10:00:00.000Z".to_string()),
             model: Some("claude-opus-4-5-20251101".to_string()),
             total_cost: 5.50,
+            total_cost_including_subagents: 5.50,
             total_turns: 8,
             total_tokens: 40000,
             duration_ms: 720000,
@@ -3522,6 +8890,7 @@ mod tests {
                 efficiency_diff: 0.05,
                 duration_diff: 120000,
             },
+            detailed_comparisons: Vec::new(),
         };
 
         let json = serde_json::to_string(&comparison).unwrap();
@@ -3658,4 +9027,212 @@ mod tests {
             "2026-02-19T09:57:33.000000Z"
         ));
     }
+
+    #[test]
+    fn test_validate_pricing() {
+        let mut pricing = crate::metrics::cost::get_default_pricing().remove(0);
+        assert!(validate_pricing(&pricing).is_ok());
+
+        pricing.output_price_per_million = -1.0;
+        assert!(validate_pricing(&pricing).is_err());
+
+        pricing.output_price_per_million = f64::NAN;
+        assert!(validate_pricing(&pricing).is_err());
+
+        pricing.output_price_per_million = 15.0;
+        pricing.effective_from = Some("2026-06-01".to_string());
+        assert!(validate_pricing(&pricing).is_ok());
+        pricing.effective_from = Some("June 2026".to_string());
+        assert!(validate_pricing(&pricing).is_err());
+
+        pricing.effective_from = None;
+        pricing.model_id = "  ".to_string();
+        assert!(validate_pricing(&pricing).is_err());
+    }
+
+    fn cached_session(message_len: usize) -> CachedSession {
+        let turn = CompletedTurn {
+            turn_number: 1,
+            started_at: "2026-01-14T07:44:28.531Z".to_string(),
+            ended_at: None,
+            duration_ms: None,
+            user_message: Some("x".repeat(message_len)),
+            assistant_message: None,
+            model: None,
+            stop_reason: None,
+            provider: Default::default(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_write_5m_tokens: 0,
+            cache_write_1h_tokens: 0,
+            total_tokens: 0,
+            total_context: 0,
+            tool_uses: Vec::new(),
+            tool_count: 0,
+            has_subagents: false,
+            subagent_ids: Vec::new(),
+            start_uuid: None,
+            end_uuid: None,
+            entry_count: 0,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_tokens: 0,
+            thinking_level: None,
+        };
+        CachedSession {
+            last_modified: SystemTime::UNIX_EPOCH,
+            file_size: 0,
+            turns: SharedTurns::from([turn]),
+            parser: None,
+        }
+    }
+
+    #[test]
+    fn test_export_jobs() {
+        let jobs = ExportJobs::default();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        {
+            let events = events.clone();
+            jobs.set_listener(move |status| events.lock().unwrap().push(status.clone()));
+        }
+
+        let job = jobs.start();
+        let job_id = job.id.clone();
+        job.set_total(3);
+        job.advance();
+        assert!(jobs.cancel(&job_id));
+        assert!(job.is_cancelled());
+        job.finish(Ok("/tmp/partial.csv".to_string()));
+
+        let status = jobs.status(&job_id).unwrap();
+        assert_eq!(status.state, ExportJobState::Cancelled);
+        assert_eq!((status.processed, status.total), (1, 3));
+        assert_eq!(status.path.as_deref(), Some("/tmp/partial.csv"));
+        // Finished jobs can't be cancelled again
+        assert!(!jobs.cancel(&job_id));
+        assert!(jobs.status("export-missing").is_none());
+
+        let failed = jobs.start();
+        failed.finish(Err(CommandError::Internal("disk full".to_string())));
+        let status = failed.status();
+        assert_eq!(status.state, ExportJobState::Failed);
+        assert_eq!(status.error.as_deref(), Some("Internal error: disk full"));
+
+        // The total and both outcomes were reported; the progress in between
+        // came too soon after the total
+        let states: Vec<ExportJobState> = events.lock().unwrap().iter().map(|e| e.state).collect();
+        assert_eq!(
+            states,
+            vec![ExportJobState::Running, ExportJobState::Cancelled, ExportJobState::Failed]
+        );
+
+        // Only the most recent finished jobs are kept
+        for _ in 0..MAX_FINISHED_EXPORT_JOBS {
+            jobs.start().finish(Ok(String::new()));
+        }
+        assert!(jobs.status(&job_id).is_none());
+        let running = jobs.start();
+        assert_eq!(running.status().state, ExportJobState::Running);
+        assert_eq!(jobs.jobs.lock().unwrap().len(), MAX_FINISHED_EXPORT_JOBS + 1);
+    }
+
+    #[test]
+    fn test_preload_control() {
+        let control = PreloadControl::default();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        {
+            let reports = reports.clone();
+            control.set_listener(move |p| reports.lock().unwrap().push(p.clone()));
+        }
+
+        // Nothing to cancel until a preload starts
+        assert!(!control.cancel());
+        control.begin();
+
+        let mut reporter = PreloadReporter::new(control.clone(), 1, 4);
+        reporter.cache_hits(2);
+        // Throttled: too soon after the first report
+        reporter.advance(1);
+        assert!(control.cancel());
+        assert!(control.clone().is_cancelled());
+        reporter.finish();
+        control.end();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!((reports[0].processed, reports[0].cache_hits, reports[0].total), (2, 2, 4));
+        assert!(reports[0].eta_ms.is_some() && !reports[0].cancelled);
+        let last = &reports[1];
+        assert_eq!(last.processed, 3);
+        assert!(last.done && last.cancelled && last.eta_ms.is_none());
+
+        // A new preload clears the cancellation
+        control.begin();
+        assert!(!control.is_cancelled());
+    }
+
+    #[test]
+    fn test_project_warmups() {
+        let warmups = ProjectWarmups::default();
+        assert!(warmups.begin("/work/app"));
+        // One warm-up per project at a time
+        assert!(!warmups.clone().begin("/work/app"));
+        assert!(warmups.begin("/work/api"));
+        warmups.end("/work/app");
+        assert!(warmups.begin("/work/app"));
+    }
+
+    #[test]
+    fn test_session_cache_memory_budget() {
+        let mut cache = SessionCache::new(10_000);
+        cache.insert("a".to_string(), cached_session(3_000));
+        cache.insert("b".to_string(), cached_session(3_000));
+        cache.insert("c".to_string(), cached_session(100));
+
+        // Touching "a" makes "b" the least recently used
+        assert!(cache.get_mut("a").is_some());
+        cache.insert("d".to_string(), cached_session(3_000));
+        assert!(cache.get_mut("b").is_none());
+        for id in ["a", "c", "d"] {
+            assert!(cache.get_mut(id).is_some(), "{} was evicted", id);
+        }
+        assert!(cache.total_bytes <= 10_000);
+
+        // A session larger than the whole budget is not cached
+        cache.insert("huge".to_string(), cached_session(20_000));
+        assert!(cache.get_mut("huge").is_none());
+        assert_eq!(cache.entries.len(), 3);
+
+        // Shrinking the budget evicts oldest first
+        cache.set_budget(3_500);
+        assert!(cache.get_mut("a").is_none());
+        assert!(cache.get_mut("c").is_none());
+        assert!(cache.get_mut("d").is_some());
+
+        cache.clear();
+        assert_eq!(cache.total_bytes, 0);
+    }
+
+    #[test]
+    fn test_aggregate_snapshot_pins_values() {
+        let mut cache = AggregateCache::new(0);
+        cache.set(Some(7), 1u32);
+        // Expired right away
+        assert_eq!(cache.get(Some(7)), None);
+
+        let snapshot = AggregateSnapshot::pin();
+        let nested = AggregateSnapshot::pin();
+        // Served past its TTL while a preload phase runs
+        assert_eq!(cache.get(Some(7)), Some(1));
+        drop(nested);
+        assert_eq!(cache.get(Some(7)), Some(1));
+
+        // Releasing the last pin publishes a new generation
+        drop(snapshot);
+        cache.set_ttl(60);
+        assert_eq!(cache.get(Some(7)), None);
+        cache.set(Some(7), 2);
+        assert_eq!(cache.get(Some(7)), Some(2));
+    }
 }
@@ -0,0 +1,133 @@
+//! Backfill bookkeeping
+//!
+//! Sessions stored before a table or column was added (stored turns, model
+//! breakdowns, anti-patterns) lack the rows derived from it until they are
+//! parsed again. Each session records the [`BACKFILL_VERSION`] its rows were
+//! last rebuilt for, so a backfill only re-parses the sessions behind it and
+//! a run stopped halfway loses nothing already stored.
+//!
+//! Runs are recorded in `backfill_runs`; one without `finished_at` was cut
+//! short by the app closing and is resumed on the next start.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::DbError;
+
+/// Bump when a schema change adds rows that only a re-parse fills in
+///
+/// 2: API errors of each turn
+/// 3: thinking tokens and thinking mode of each turn
+pub const BACKFILL_VERSION: u32 = 3;
+
+/// A backfill run, finished or not
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackfillRun {
+    pub id: i64,
+    pub started_at: String,
+    /// Sessions rebuilt so far, across resumptions
+    pub processed: u32,
+}
+
+/// Backfill version of every stored session
+pub fn get_backfill_versions(conn: &Connection) -> Result<HashMap<String, u32>, DbError> {
+    let mut stmt = conn.prepare("SELECT session_id, COALESCE(backfill_version, 0) FROM sessions")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+}
+
+/// Record that a session's rows are up to date with [`BACKFILL_VERSION`]
+pub fn mark_session_backfilled(conn: &Connection, session_id: &str) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE sessions SET backfill_version = ?1 WHERE session_id = ?2",
+        params![BACKFILL_VERSION, session_id],
+    )?;
+    Ok(())
+}
+
+/// The run the app was closed in the middle of, if any
+pub fn get_interrupted_run(conn: &Connection) -> Result<Option<BackfillRun>, DbError> {
+    Ok(conn
+        .query_row(
+            "SELECT id, started_at, processed FROM backfill_runs
+             WHERE finished_at IS NULL ORDER BY id DESC LIMIT 1",
+            [],
+            |row| {
+                Ok(BackfillRun {
+                    id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    processed: row.get(2)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Continue the interrupted run, or start a new one
+pub fn start_run(conn: &Connection, started_at: &str) -> Result<BackfillRun, DbError> {
+    if let Some(run) = get_interrupted_run(conn)? {
+        return Ok(run);
+    }
+    conn.execute("INSERT INTO backfill_runs (started_at) VALUES (?1)", params![started_at])?;
+    Ok(BackfillRun {
+        id: conn.last_insert_rowid(),
+        started_at: started_at.to_string(),
+        processed: 0,
+    })
+}
+
+/// Store how many sessions a run has rebuilt
+pub fn record_run_progress(conn: &Connection, run_id: i64, processed: u32) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE backfill_runs SET processed = ?1 WHERE id = ?2",
+        params![processed, run_id],
+    )?;
+    Ok(())
+}
+
+/// Close a run; cancelled runs aren't resumed either
+pub fn finish_run(conn: &Connection, run_id: i64, finished_at: &str, cancelled: bool) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE backfill_runs SET finished_at = ?1, cancelled = ?2 WHERE id = ?3",
+        params![finished_at, cancelled, run_id],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::upsert_session_with_mtime;
+    use crate::db::{migrations, schema};
+
+    #[test]
+    fn test_backfill_runs() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        migrations::run(&conn).unwrap();
+
+        for id in ["s1", "s2"] {
+            upsert_session_with_mtime(
+                &conn, id, "/Users/me/app", "app", None,
+                "2026-02-05T10:00:00Z", "2026-02-05T10:30:00Z",
+                "claude-opus-4-5-20251101", false, &format!("/tmp/{}.jsonl", id), "m1",
+            )
+            .unwrap();
+        }
+        mark_session_backfilled(&conn, "s1").unwrap();
+        let versions = get_backfill_versions(&conn).unwrap();
+        assert_eq!(versions["s1"], BACKFILL_VERSION);
+        assert_eq!(versions["s2"], 0);
+
+        // A run left open is picked up again with its progress
+        let run = start_run(&conn, "2026-02-06T09:00:00Z").unwrap();
+        record_run_progress(&conn, run.id, 1).unwrap();
+        let resumed = start_run(&conn, "2026-02-06T10:00:00Z").unwrap();
+        assert_eq!(resumed, BackfillRun { processed: 1, ..run.clone() });
+
+        finish_run(&conn, run.id, "2026-02-06T10:05:00Z", false).unwrap();
+        assert_eq!(get_interrupted_run(&conn).unwrap(), None);
+        assert_ne!(start_run(&conn, "2026-02-07T09:00:00Z").unwrap().id, run.id);
+    }
+}
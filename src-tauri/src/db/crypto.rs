@@ -0,0 +1,326 @@
+//! Encryption of stored message content
+//!
+//! With `encrypt_content` on, user and assistant message text, tool inputs
+//! and results, session summaries and turn annotation notes are stored
+//! AES-256-GCM encrypted, each value on its own with a random nonce, as
+//! `enc1:` followed by base64. Everything else stays plaintext, including
+//! project paths and the paths of changed files: queries group, filter and
+//! join on them, which values encrypted with a random nonce can't support.
+//! The key lives in the OS keychain (see [`crate::keychain`]), never in the
+//! database.
+//!
+//! Values without the prefix are read as they are, so a database can be
+//! encrypted or decrypted in place, and a half-converted one still reads.
+//! While encryption is on but the key can't be loaded, content can't be read
+//! and writes of it fail, rather than storing it in plaintext or dropping
+//! it: turns are stored from their session files once the key is back.
+
+use std::sync::{Arc, RwLock};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::DbError;
+
+/// Prefix of encrypted values; the digit is the format version
+const PREFIX: &str = "enc1:";
+
+/// Key length of AES-256
+pub const KEY_LEN: usize = 32;
+
+/// Columns holding message content, by table
+const CONTENT_COLUMNS: &[(&str, &[&str])] = &[
+    ("turns", &["user_message", "assistant_message"]),
+    ("tool_uses", &["input_json", "result"]),
+    ("sessions", &["summary"]),
+    ("turn_annotations", &["note"]),
+];
+
+/// An AES-256-GCM key for content columns
+pub struct FieldCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl FieldCipher {
+    pub fn new(key: &[u8; KEY_LEN]) -> Self {
+        // AES-256-GCM accepts any 32-byte key
+        let key = UnboundKey::new(&AES_256_GCM, key).expect("32-byte AES-256-GCM key");
+        Self { key: LessSafeKey::new(key), rng: SystemRandom::new() }
+    }
+
+    /// A new random key
+    pub fn generate_key() -> Result<[u8; KEY_LEN], DbError> {
+        let mut key = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| DbError::Encryption("No secure random source for a key".to_string()))?;
+        Ok(key)
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, DbError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| DbError::Encryption("No secure random source for a nonce".to_string()))?;
+        let mut sealed = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+            .map_err(|_| DbError::Encryption("Failed to encrypt a value".to_string()))?;
+
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&sealed);
+        Ok(format!("{}{}", PREFIX, STANDARD.encode(out)))
+    }
+
+    /// Decrypt a value written by `encrypt`; plaintext values pass through
+    pub fn decrypt(&self, value: &str) -> Result<String, DbError> {
+        let encoded = match value.strip_prefix(PREFIX) {
+            Some(encoded) => encoded,
+            None => return Ok(value.to_string()),
+        };
+        let invalid = || DbError::Encryption("Encrypted value is corrupt or uses another key".to_string());
+        let mut bytes = STANDARD.decode(encoded).map_err(|_| invalid())?;
+        if bytes.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let mut sealed = bytes.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&bytes).map_err(|_| invalid())?;
+        let plaintext = self.key.open_in_place(nonce, Aad::empty(), &mut sealed).map_err(|_| invalid())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| invalid())
+    }
+}
+
+/// Whether content is stored encrypted, and with which key
+#[derive(Clone, Default)]
+pub enum ContentEncryption {
+    /// Content is stored as it is
+    #[default]
+    Off,
+    On(Arc<FieldCipher>),
+    /// Encryption is on but the key couldn't be loaded
+    Locked,
+}
+
+lazy_static::lazy_static! {
+    static ref CONTENT_ENCRYPTION: RwLock<ContentEncryption> = RwLock::new(ContentEncryption::Off);
+}
+
+/// Make `encryption` apply to content read and written from now on
+pub fn set_content_encryption(encryption: ContentEncryption) {
+    if let Ok(mut active) = CONTENT_ENCRYPTION.write() {
+        *active = encryption;
+    }
+}
+
+fn content_encryption() -> ContentEncryption {
+    CONTENT_ENCRYPTION.read().map(|active| active.clone()).unwrap_or_default()
+}
+
+/// Whether stored content is encrypted, for reporting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ContentEncryptionStatus {
+    #[default]
+    Off,
+    On,
+    /// Encryption is on but the key couldn't be loaded: stored content can't
+    /// be read, and new content isn't stored until the key is available
+    Locked,
+}
+
+/// Whether content is currently stored encrypted
+pub fn content_encryption_status() -> ContentEncryptionStatus {
+    match content_encryption() {
+        ContentEncryption::Off => ContentEncryptionStatus::Off,
+        ContentEncryption::On(_) => ContentEncryptionStatus::On,
+        ContentEncryption::Locked => ContentEncryptionStatus::Locked,
+    }
+}
+
+/// Whether a stored value is encrypted
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+/// A content value as it's to be stored
+///
+/// Fails rather than storing nothing while encryption is on but the key is
+/// missing, so the write is refused and made again once the key is back.
+pub fn seal(value: Option<&str>) -> Result<Option<String>, DbError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    // Empty strings are kept, so SQL can still tell them from real content
+    if value.is_empty() {
+        return Ok(Some(String::new()));
+    }
+    match content_encryption() {
+        ContentEncryption::Off => Ok(Some(value.to_string())),
+        ContentEncryption::On(_) if is_encrypted(value) => Ok(Some(value.to_string())),
+        ContentEncryption::On(cipher) => cipher.encrypt(value).map(Some),
+        ContentEncryption::Locked => Err(DbError::Encryption(
+            "Content encryption is on but the content key is unavailable".to_string(),
+        )),
+    }
+}
+
+/// A stored content value as it's to be read; None if it can't be decrypted
+pub fn open(value: Option<String>) -> Option<String> {
+    let value = value?;
+    if !is_encrypted(&value) {
+        return Some(value);
+    }
+    match content_encryption() {
+        ContentEncryption::On(cipher) => cipher.decrypt(&value).ok(),
+        ContentEncryption::Off | ContentEncryption::Locked => None,
+    }
+}
+
+/// Rewrite every content value through `f`, skipping values that are
+/// already in the form `f` produces; returns the number of values rewritten
+fn rewrite_content(
+    conn: &Connection,
+    skip: impl Fn(&str) -> bool,
+    f: impl Fn(&str) -> Result<Option<String>, DbError>,
+) -> Result<u32, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let mut rewritten = 0;
+    for (table, columns) in CONTENT_COLUMNS {
+        for column in *columns {
+            let rows: Vec<(i64, String)> = {
+                let mut stmt = tx.prepare(&format!(
+                    "SELECT rowid, {column} FROM {table} WHERE {column} IS NOT NULL AND {column} != ''"
+                ))?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                rows.collect::<Result<Vec<_>, _>>()?
+            };
+            let mut update = tx.prepare(&format!("UPDATE {table} SET {column} = ?1 WHERE rowid = ?2"))?;
+            for (rowid, value) in rows {
+                if skip(&value) {
+                    continue;
+                }
+                update.execute(rusqlite::params![f(&value)?, rowid])?;
+                rewritten += 1;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(rewritten)
+}
+
+/// Whether any content is stored encrypted
+pub fn has_encrypted_content(conn: &Connection) -> Result<bool, DbError> {
+    for (table, columns) in CONTENT_COLUMNS {
+        for column in *columns {
+            let found: bool = conn.query_row(
+                &format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE {column} LIKE '{PREFIX}%')"),
+                [],
+                |row| row.get(0),
+            )?;
+            if found {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Encrypt the content already stored in plaintext
+///
+/// Runs in one transaction, so an interrupted run leaves everything as it
+/// was.
+pub fn encrypt_stored_content(conn: &Connection, cipher: &FieldCipher) -> Result<u32, DbError> {
+    rewrite_content(conn, is_encrypted, |value| cipher.encrypt(value).map(Some))
+}
+
+/// Decrypt the content stored encrypted, e.g. before encryption is turned off
+pub fn decrypt_stored_content(conn: &Connection, cipher: &FieldCipher) -> Result<u32, DbError> {
+    rewrite_content(conn, |value| !is_encrypted(value), |value| cipher.decrypt(value).map(Some))
+}
+
+/// Encrypt content stored in plaintext if encryption is on, e.g. after
+/// rows were merged in from elsewhere
+pub fn encrypt_plaintext_content(conn: &Connection) -> Result<u32, DbError> {
+    match content_encryption() {
+        ContentEncryption::On(cipher) => encrypt_stored_content(conn, &cipher),
+        ContentEncryption::Off | ContentEncryption::Locked => Ok(0),
+    }
+}
+
+/// Decrypt the content of a database copy leaving this machine, whose key
+/// stays behind; values the active key can't decrypt are cleared
+pub fn decrypt_for_export(conn: &Connection) -> Result<u32, DbError> {
+    let cipher = match content_encryption() {
+        ContentEncryption::On(cipher) => Some(cipher),
+        ContentEncryption::Off | ContentEncryption::Locked => None,
+    };
+    rewrite_content(
+        conn,
+        |value| !is_encrypted(value),
+        |value| Ok(cipher.as_ref().and_then(|cipher| cipher.decrypt(value).ok())),
+    )
+}
+
+/// SQL reading `column` of `table` from another machine's database: content
+/// encrypted with a key this machine doesn't have reads as NULL
+pub fn foreign_column_sql(table: &str, column: &str) -> String {
+    let is_content = CONTENT_COLUMNS
+        .iter()
+        .any(|(t, columns)| *t == table && columns.contains(&column));
+    match is_content {
+        true => format!("CASE WHEN {column} LIKE '{PREFIX}%' THEN NULL ELSE {column} END"),
+        false => column.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    #[test]
+    fn test_field_cipher() {
+        let cipher = FieldCipher::new(&FieldCipher::generate_key().unwrap());
+        let sealed = cipher.encrypt("fix the failing test").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_ne!(sealed, cipher.encrypt("fix the failing test").unwrap());
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "fix the failing test");
+        assert_eq!(cipher.decrypt("plaintext").unwrap(), "plaintext");
+
+        let other = FieldCipher::new(&FieldCipher::generate_key().unwrap());
+        assert!(other.decrypt(&sealed).is_err());
+        assert!(cipher.decrypt("enc1:not base64!").is_err());
+
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO sessions (session_id, project_path, project_name, started_at, last_activity_at, model, file_path, summary)
+             VALUES ('s1', '/work/app', 'app', '2026-03-10T09:00:00Z', '2026-03-10T09:00:00Z', 'm', '/p/s1.jsonl', 'Summary');
+             INSERT INTO turns (turn_id, session_id, turn_number, started_at, user_message, assistant_message)
+             VALUES ('t1', 's1', 1, '2026-03-10T09:00:00Z', 'Hello', NULL);
+             INSERT INTO turn_annotations (session_id, turn_number, label, note, annotated_at)
+             VALUES ('s1', 1, 'wasted', 'Went in circles', '2026-03-10T10:00:00Z');",
+        )
+        .unwrap();
+
+        assert!(!has_encrypted_content(&conn).unwrap());
+        assert_eq!(encrypt_stored_content(&conn, &cipher).unwrap(), 3);
+        assert!(has_encrypted_content(&conn).unwrap());
+        assert_eq!(encrypt_stored_content(&conn, &cipher).unwrap(), 0);
+        let stored: String = conn.query_row("SELECT user_message FROM turns", [], |row| row.get(0)).unwrap();
+        assert!(is_encrypted(&stored));
+        assert_eq!(cipher.decrypt(&stored).unwrap(), "Hello");
+
+        assert_eq!(decrypt_stored_content(&conn, &cipher).unwrap(), 3);
+        let summary: String = conn.query_row("SELECT summary FROM sessions", [], |row| row.get(0)).unwrap();
+        assert_eq!(summary, "Summary");
+        assert!(!has_encrypted_content(&conn).unwrap());
+    }
+}
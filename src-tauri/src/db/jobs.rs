@@ -0,0 +1,387 @@
+//! Background job queue
+//!
+//! Long-running and scheduled work (backfills, the second preload phase,
+//! exports, daily digests) is
+//! recorded in `jobs` before it runs, so it can be listed while it runs and
+//! isn't lost when the app closes. Workers claim the queued job with the
+//! highest priority, oldest first; a failed job is queued again after a
+//! backoff until it runs out of attempts. Jobs still marked running at the
+//! next start were cut short and go back in the queue.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::DbError;
+
+/// Delay before the first retry of a failed job; doubles with each attempt
+const RETRY_BASE_SECS: i64 = 30;
+
+/// What a job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Re-parse sessions missing rows added by later schema changes
+    Backfill,
+    /// Load the sessions the first preload phase left out
+    PreloadRemaining,
+    /// Write a session export
+    Export,
+    /// Post the daily digest of a day to the webhooks
+    DailyDigest,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::Backfill => "backfill",
+            JobKind::PreloadRemaining => "preload_remaining",
+            JobKind::Export => "export",
+            JobKind::DailyDigest => "daily_digest",
+        }
+    }
+
+    /// Higher runs first: exports have someone waiting on them, the
+    /// remaining preload fills the session list, backfills can wait
+    pub fn priority(&self) -> i32 {
+        match self {
+            JobKind::Export => 10,
+            JobKind::PreloadRemaining => 5,
+            JobKind::DailyDigest => 2,
+            JobKind::Backfill => 0,
+        }
+    }
+
+    /// Attempts before a failing job is given up on
+    pub fn max_attempts(&self) -> u32 {
+        match self {
+            JobKind::Backfill => 3,
+            JobKind::PreloadRemaining => 2,
+            // A failed export is usually a bad path; retrying won't help
+            JobKind::Export => 1,
+            // Webhooks are already retried, and a retry would repost to the
+            // ones that got it
+            JobKind::DailyDigest => 1,
+        }
+    }
+
+    /// Whether only one job of this kind may be queued or running at a time
+    fn is_singleton(&self) -> bool {
+        !matches!(self, JobKind::Export | JobKind::DailyDigest)
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "backfill" => Some(JobKind::Backfill),
+            "preload_remaining" => Some(JobKind::PreloadRemaining),
+            "export" => Some(JobKind::Export),
+            "daily_digest" => Some(JobKind::DailyDigest),
+            _ => None,
+        }
+    }
+}
+
+/// Where a job is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Succeeded => "succeeded",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(name: &str) -> Self {
+        match name {
+            "queued" => JobState::Queued,
+            "running" => JobState::Running,
+            "succeeded" => JobState::Succeeded,
+            "cancelled" => JobState::Cancelled,
+            _ => JobState::Failed,
+        }
+    }
+
+    /// Whether the job is done, one way or another
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobState::Succeeded | JobState::Failed | JobState::Cancelled)
+    }
+}
+
+/// A queued, running or finished job
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct Job {
+    pub id: i64,
+    pub kind: JobKind,
+    /// Kind-specific arguments, as JSON
+    pub payload: Option<String>,
+    pub state: JobState,
+    /// Higher runs first
+    pub priority: i32,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// Error of the last failed attempt
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Earliest time a retry may run
+    pub run_after: Option<String>,
+}
+
+const JOB_COLUMNS: &str = "id, kind, payload, state, priority, attempts, max_attempts, last_error, \
+                           created_at, started_at, finished_at, run_after";
+
+fn job_from_row(row: &Row) -> rusqlite::Result<Job> {
+    let kind: String = row.get(1)?;
+    Ok(Job {
+        id: row.get(0)?,
+        kind: JobKind::parse(&kind).ok_or_else(|| {
+            let error = format!("Unknown job kind: {}", kind);
+            rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, error.into())
+        })?,
+        payload: row.get(2)?,
+        state: JobState::parse(&row.get::<_, String>(3)?),
+        priority: row.get(4)?,
+        attempts: row.get(5)?,
+        max_attempts: row.get(6)?,
+        last_error: row.get(7)?,
+        created_at: row.get(8)?,
+        started_at: row.get(9)?,
+        finished_at: row.get(10)?,
+        run_after: row.get(11)?,
+    })
+}
+
+/// Queue a job with its kind's priority and attempt limit
+///
+/// Backfills and preloads are queued once: while one is queued or running,
+/// its ID is returned instead. Returns the job's ID and whether it was newly
+/// queued.
+pub fn enqueue_job(conn: &Connection, kind: JobKind, payload: Option<&str>, now: &str) -> Result<(i64, bool), DbError> {
+    if kind.is_singleton() {
+        let existing: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM jobs WHERE kind = ?1 AND state IN ('queued', 'running') ORDER BY id LIMIT 1",
+                params![kind.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(id) = existing {
+            return Ok((id, false));
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO jobs (kind, payload, state, priority, max_attempts, created_at)
+         VALUES (?1, ?2, 'queued', ?3, ?4, ?5)",
+        params![kind.as_str(), payload, kind.priority(), kind.max_attempts(), now],
+    )?;
+    Ok((conn.last_insert_rowid(), true))
+}
+
+/// Whether a job of `kind` with `payload` was ever queued, whatever its state
+pub fn has_job(conn: &Connection, kind: JobKind, payload: &str) -> Result<bool, DbError> {
+    Ok(conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM jobs WHERE kind = ?1 AND payload = ?2)",
+        params![kind.as_str(), payload],
+        |row| row.get(0),
+    )?)
+}
+
+/// Mark the next runnable job as running and return it
+///
+/// A single statement, so workers on separate connections never claim the
+/// same job.
+pub fn claim_next_job(conn: &Connection, now: &str) -> Result<Option<Job>, DbError> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "UPDATE jobs
+                 SET state = 'running', attempts = attempts + 1, started_at = ?1, finished_at = NULL
+                 WHERE id = (
+                     SELECT id FROM jobs
+                     WHERE state = 'queued' AND (run_after IS NULL OR run_after <= ?1)
+                     ORDER BY priority DESC, id ASC
+                     LIMIT 1
+                 )
+                 RETURNING {}",
+                JOB_COLUMNS
+            ),
+            params![now],
+            job_from_row,
+        )
+        .optional()?)
+}
+
+/// Record how a running job ended
+pub fn finish_job(conn: &Connection, job_id: i64, state: JobState, now: &str) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE jobs SET state = ?1, finished_at = ?2 WHERE id = ?3",
+        params![state.as_str(), now, job_id],
+    )?;
+    Ok(())
+}
+
+/// Record a failed attempt
+///
+/// The job is queued again after a backoff while it has attempts left, and
+/// fails for good otherwise. Returns its new state.
+pub fn fail_job(conn: &Connection, job_id: i64, error: &str, now: &str) -> Result<JobState, DbError> {
+    let (attempts, max_attempts): (u32, u32) = conn.query_row(
+        "SELECT attempts, max_attempts FROM jobs WHERE id = ?1",
+        params![job_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    if attempts < max_attempts {
+        let delay = chrono::Duration::seconds(RETRY_BASE_SECS << (attempts.saturating_sub(1)).min(10));
+        let run_after = chrono::DateTime::parse_from_rfc3339(now)
+            .map(|t| (t + delay).to_rfc3339())
+            .unwrap_or_else(|_| now.to_string());
+        conn.execute(
+            "UPDATE jobs SET state = 'queued', last_error = ?1, run_after = ?2 WHERE id = ?3",
+            params![error, run_after, job_id],
+        )?;
+        Ok(JobState::Queued)
+    } else {
+        conn.execute(
+            "UPDATE jobs SET state = 'failed', last_error = ?1, finished_at = ?2 WHERE id = ?3",
+            params![error, now, job_id],
+        )?;
+        Ok(JobState::Failed)
+    }
+}
+
+/// Replace a job's arguments, e.g. to record how far a running job got so a
+/// requeued attempt can pick up from there
+pub fn update_job_payload(conn: &Connection, job_id: i64, payload: &str) -> Result<(), DbError> {
+    conn.execute("UPDATE jobs SET payload = ?1 WHERE id = ?2", params![payload, job_id])?;
+    Ok(())
+}
+
+/// Cancel a job that hasn't started; returns whether it was queued
+pub fn cancel_queued_job(conn: &Connection, job_id: i64, now: &str) -> Result<bool, DbError> {
+    let cancelled = conn.execute(
+        "UPDATE jobs SET state = 'cancelled', finished_at = ?1 WHERE id = ?2 AND state = 'queued'",
+        params![now, job_id],
+    )?;
+    Ok(cancelled > 0)
+}
+
+/// Put jobs left running by a previous run of the app back in the queue
+///
+/// The interrupted attempt doesn't count against the job. Returns the number
+/// of jobs requeued.
+pub fn requeue_interrupted_jobs(conn: &Connection) -> Result<u32, DbError> {
+    let requeued = conn.execute(
+        "UPDATE jobs SET state = 'queued', attempts = MAX(attempts - 1, 0), started_at = NULL
+         WHERE state = 'running'",
+        [],
+    )?;
+    Ok(requeued as u32)
+}
+
+/// Get a job by ID
+pub fn get_job(conn: &Connection, job_id: i64) -> Result<Option<Job>, DbError> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {} FROM jobs WHERE id = ?1", JOB_COLUMNS),
+            params![job_id],
+            job_from_row,
+        )
+        .optional()?)
+}
+
+/// Most recent jobs, newest first; unfinished ones are always included
+pub fn list_jobs(conn: &Connection, limit: u32) -> Result<Vec<Job>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM jobs
+         WHERE state IN ('queued', 'running')
+            OR id IN (SELECT id FROM jobs ORDER BY id DESC LIMIT ?1)
+         ORDER BY id DESC",
+        JOB_COLUMNS
+    ))?;
+    let jobs = stmt
+        .query_map(params![limit], job_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(jobs)
+}
+
+/// Delete finished jobs that ended before `cutoff`; returns how many
+pub fn prune_finished_jobs(conn: &Connection, cutoff: &str) -> Result<u32, DbError> {
+    let deleted = conn.execute(
+        "DELETE FROM jobs
+         WHERE state IN ('succeeded', 'failed', 'cancelled') AND finished_at < ?1",
+        params![cutoff],
+    )?;
+    Ok(deleted as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrations, schema};
+
+    #[test]
+    fn test_job_lifecycle() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        migrations::run(&conn).unwrap();
+        let now = "2026-02-06T10:00:00+00:00";
+
+        let (backfill, added) = enqueue_job(&conn, JobKind::Backfill, None, now).unwrap();
+        assert!(added);
+        // Already queued
+        assert_eq!(enqueue_job(&conn, JobKind::Backfill, None, now).unwrap(), (backfill, false));
+        let (export, _) = enqueue_job(&conn, JobKind::Export, Some(r#"{"format":"csv"}"#), now).unwrap();
+        assert!(has_job(&conn, JobKind::Export, r#"{"format":"csv"}"#).unwrap());
+        assert!(!has_job(&conn, JobKind::DailyDigest, r#"{"format":"csv"}"#).unwrap());
+
+        // Highest priority first
+        let job = claim_next_job(&conn, now).unwrap().unwrap();
+        assert_eq!((job.id, job.state, job.attempts), (export, JobState::Running, 1));
+        finish_job(&conn, export, JobState::Succeeded, now).unwrap();
+
+        // A failure is retried after a backoff, then fails for good
+        assert_eq!(claim_next_job(&conn, now).unwrap().unwrap().id, backfill);
+        assert_eq!(fail_job(&conn, backfill, "disk full", now).unwrap(), JobState::Queued);
+        assert_eq!(claim_next_job(&conn, now).unwrap(), None);
+        let later = "2026-02-06T10:00:30+00:00";
+        assert_eq!(claim_next_job(&conn, later).unwrap().unwrap().attempts, 2);
+
+        // Left running when the app closed: back in the queue
+        assert_eq!(requeue_interrupted_jobs(&conn).unwrap(), 1);
+        let job = get_job(&conn, backfill).unwrap().unwrap();
+        assert_eq!((job.state, job.attempts), (JobState::Queued, 1));
+        update_job_payload(&conn, backfill, r#"{"skip":50}"#).unwrap();
+        assert_eq!(get_job(&conn, backfill).unwrap().unwrap().payload.as_deref(), Some(r#"{"skip":50}"#));
+
+        let retry = "2026-02-06T10:01:00+00:00";
+        claim_next_job(&conn, retry).unwrap();
+        fail_job(&conn, backfill, "disk full", retry).unwrap();
+        claim_next_job(&conn, "2026-02-06T10:02:00+00:00").unwrap();
+        assert_eq!(fail_job(&conn, backfill, "disk full", retry).unwrap(), JobState::Failed);
+
+        let (queued, _) = enqueue_job(&conn, JobKind::PreloadRemaining, None, now).unwrap();
+        assert!(cancel_queued_job(&conn, queued, now).unwrap());
+        assert!(!cancel_queued_job(&conn, export, now).unwrap());
+
+        let states: Vec<JobState> = list_jobs(&conn, 10).unwrap().iter().map(|j| j.state).collect();
+        assert_eq!(states, vec![JobState::Cancelled, JobState::Succeeded, JobState::Failed]);
+        assert_eq!(prune_finished_jobs(&conn, "2026-02-07T00:00:00+00:00").unwrap(), 3);
+    }
+}
@@ -0,0 +1,596 @@
+//! Versioned schema migrations
+//!
+//! `schema::create_tables` builds the tables of a fresh database. Changes made
+//! after a database was created are listed in [`MIGRATIONS`] and applied in
+//! version order, each in its own transaction. Applied versions are recorded
+//! in `schema_migrations`, so every migration runs once per database.
+//!
+//! Databases from before this table existed got some of these changes from
+//! ad-hoc statements at startup, and fresh databases already have the columns
+//! the current schema defines. Column additions therefore skip columns that
+//! are already there, which keeps every migration safe to run on any database.
+
+use rusqlite::{params, Connection};
+
+use super::DbError;
+
+/// One step of a migration
+pub enum Step {
+    /// Add a column to a table unless it already has one by that name
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+    /// Run a batch of SQL statements
+    Sql(&'static str),
+}
+
+/// A schema change, identified by its version
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub steps: &'static [Step],
+}
+
+/// Every migration, in version order. Append new ones at the end; never edit
+/// or renumber one that has shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "turn_rebuild_columns",
+        steps: &[
+            Step::AddColumn { table: "turns", column: "start_uuid", definition: "TEXT" },
+            Step::AddColumn { table: "turns", column: "end_uuid", definition: "TEXT" },
+            Step::AddColumn { table: "turns", column: "subagent_ids", definition: "TEXT" },
+            Step::AddColumn { table: "turns", column: "entry_count", definition: "INTEGER DEFAULT 0" },
+        ],
+    },
+    // File mtime the stored turns were parsed from (tracked separately from
+    // file_mtime, which preload refreshes without storing turns)
+    Migration {
+        version: 2,
+        name: "sessions_turns_mtime",
+        steps: &[Step::AddColumn { table: "sessions", column: "turns_mtime", definition: "TEXT" }],
+    },
+    // Marks user overrides so default pricing doesn't clobber them
+    Migration {
+        version: 3,
+        name: "pricing_is_custom",
+        steps: &[Step::AddColumn { table: "pricing", column: "is_custom", definition: "INTEGER DEFAULT 0" }],
+    },
+    // Import source label (NULL for sessions under ~/.claude)
+    Migration {
+        version: 4,
+        name: "sessions_source",
+        steps: &[Step::AddColumn { table: "sessions", column: "source", definition: "TEXT" }],
+    },
+    // Session that spawned a subagent session
+    Migration {
+        version: 5,
+        name: "sessions_parent_session_id",
+        steps: &[Step::AddColumn { table: "sessions", column: "parent_session_id", definition: "TEXT" }],
+    },
+    // Compaction boundaries of the turn, as JSON
+    Migration {
+        version: 6,
+        name: "turns_compactions",
+        steps: &[Step::AddColumn { table: "turns", column: "compactions", definition: "TEXT" }],
+    },
+    // First user message, so the session list doesn't have to query turns
+    Migration {
+        version: 7,
+        name: "sessions_summary",
+        steps: &[Step::AddColumn { table: "sessions", column: "summary", definition: "TEXT" }],
+    },
+    // Sessions stored with last_activity_at equal to started_at get
+    // started_at + total_duration_ms instead
+    Migration {
+        version: 8,
+        name: "fix_last_activity_at",
+        steps: &[Step::Sql(
+            r#"
+            UPDATE sessions
+            SET last_activity_at = strftime('%Y-%m-%dT%H:%M:%fZ',
+                julianday(sessions.started_at) + (
+                    SELECT CAST(m.total_duration_ms AS REAL) / 86400000.0
+                    FROM session_metrics m
+                    WHERE m.session_id = sessions.session_id
+                )
+            )
+            WHERE sessions.last_activity_at = sessions.started_at
+            AND EXISTS (
+                SELECT 1 FROM session_metrics m
+                WHERE m.session_id = sessions.session_id
+                AND m.total_duration_ms > 0
+            );
+            "#,
+        )],
+    },
+    // get_file_mtime() produces a "Z" suffix; older rows used "+00:00", which
+    // broke the string comparison in the cache-hit check
+    Migration {
+        version: 9,
+        name: "normalize_file_mtime",
+        steps: &[Step::Sql(
+            r#"
+            UPDATE sessions
+            SET file_mtime = replace(file_mtime, '+00:00', 'Z')
+            WHERE file_mtime IS NOT NULL
+              AND file_mtime LIKE '%+00:00';
+            "#,
+        )],
+    },
+    // Dismissed and snoozed recommendations
+    Migration {
+        version: 10,
+        name: "recommendations_feedback",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS recommendations_feedback (
+                recommendation_id TEXT PRIMARY KEY,
+                rec_type TEXT NOT NULL,
+                session_id TEXT,
+                project_path TEXT,
+                reason TEXT,
+                potential_savings REAL NOT NULL DEFAULT 0.0,
+                confidence REAL NOT NULL DEFAULT 0.0,
+                dismissed_at TEXT NOT NULL,
+                snoozed_until TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_recommendations_feedback_type
+                ON recommendations_feedback(rec_type);
+            "#,
+        )],
+    },
+    // Anti-patterns found in each session, kept for frequency trends
+    Migration {
+        version: 11,
+        name: "antipatterns",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS antipatterns (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL REFERENCES sessions(session_id) ON DELETE CASCADE,
+                pattern_type TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                severity_score REAL NOT NULL,
+                turn_number INTEGER,
+                description TEXT NOT NULL,
+                impact_cost REAL NOT NULL DEFAULT 0.0,
+                metric_value REAL NOT NULL,
+                threshold REAL NOT NULL,
+                occurred_at TEXT NOT NULL,
+                detected_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_antipatterns_session ON antipatterns(session_id);
+            CREATE INDEX IF NOT EXISTS idx_antipatterns_occurred ON antipatterns(occurred_at);
+            "#,
+        )],
+    },
+    // Which backfill a session's derived rows were last rebuilt for, and the
+    // backfill runs themselves so an interrupted one can be resumed
+    Migration {
+        version: 12,
+        name: "session_backfill",
+        steps: &[
+            Step::AddColumn { table: "sessions", column: "backfill_version", definition: "INTEGER DEFAULT 0" },
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS backfill_runs (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    started_at TEXT NOT NULL,
+                    finished_at TEXT,
+                    processed INTEGER NOT NULL DEFAULT 0,
+                    cancelled INTEGER NOT NULL DEFAULT 0
+                );
+                "#,
+            ),
+        ],
+    },
+    // Teammates' session exports for the team roll-up, kept apart from the
+    // local sessions
+    Migration {
+        version: 13,
+        name: "team_rollup",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS team_imports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL,
+                source_path TEXT,
+                export_date TEXT,
+                imported_at TEXT NOT NULL,
+                session_count INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS team_sessions (
+                user_name TEXT NOT NULL,
+                session_id TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                project_name TEXT NOT NULL,
+                model TEXT,
+                turns INTEGER NOT NULL DEFAULT 0,
+                tokens INTEGER NOT NULL DEFAULT 0,
+                cost REAL NOT NULL DEFAULT 0.0,
+                duration_ms INTEGER NOT NULL DEFAULT 0,
+                efficiency_score REAL,
+                import_id INTEGER REFERENCES team_imports(id) ON DELETE SET NULL,
+                PRIMARY KEY (user_name, session_id)
+            );
+            CREATE INDEX IF NOT EXISTS idx_team_sessions_started ON team_sessions(started_at);
+            "#,
+        )],
+    },
+    // Billing provider of each turn, and pricing keyed by model and provider
+    // so Bedrock and Vertex rates can differ from the Anthropic API
+    Migration {
+        version: 14,
+        name: "cost_providers",
+        steps: &[
+            Step::AddColumn { table: "turns", column: "provider", definition: "TEXT" },
+            Step::Sql(
+                r#"
+                CREATE TABLE pricing_by_provider (
+                    model_id TEXT NOT NULL,
+                    display_name TEXT NOT NULL,
+                    input_price_per_million REAL NOT NULL,
+                    output_price_per_million REAL NOT NULL,
+                    cache_write_5m_per_million REAL NOT NULL,
+                    cache_write_1h_per_million REAL NOT NULL,
+                    cache_read_per_million REAL NOT NULL,
+                    max_context_tokens INTEGER,
+                    updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                    is_custom INTEGER DEFAULT 0,
+                    provider TEXT NOT NULL DEFAULT 'anthropic',
+                    PRIMARY KEY (model_id, provider)
+                );
+                INSERT INTO pricing_by_provider (
+                    model_id, display_name, input_price_per_million, output_price_per_million,
+                    cache_write_5m_per_million, cache_write_1h_per_million, cache_read_per_million,
+                    max_context_tokens, updated_at, is_custom
+                )
+                SELECT
+                    model_id, display_name, input_price_per_million, output_price_per_million,
+                    cache_write_5m_per_million, cache_write_1h_per_million, cache_read_per_million,
+                    max_context_tokens, updated_at, is_custom
+                FROM pricing;
+                DROP TABLE pricing;
+                ALTER TABLE pricing_by_provider RENAME TO pricing;
+                "#,
+            ),
+        ],
+    },
+    // Background jobs, kept so they can be listed and survive restarts
+    Migration {
+        version: 15,
+        name: "jobs",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                payload TEXT,
+                state TEXT NOT NULL,
+                priority INTEGER NOT NULL DEFAULT 0,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                max_attempts INTEGER NOT NULL DEFAULT 1,
+                last_error TEXT,
+                created_at TEXT NOT NULL,
+                started_at TEXT,
+                finished_at TEXT,
+                run_after TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_jobs_state ON jobs(state, priority);
+            "#,
+        )],
+    },
+    // Rate limit and overload errors of the turn, as JSON
+    Migration {
+        version: 16,
+        name: "turns_api_errors",
+        steps: &[Step::AddColumn { table: "turns", column: "api_errors", definition: "TEXT" }],
+    },
+    // Estimated thinking tokens of the turn and the prompt's thinking mode
+    Migration {
+        version: 17,
+        name: "turns_thinking",
+        steps: &[
+            Step::AddColumn { table: "turn_metrics", column: "thinking_tokens", definition: "INTEGER DEFAULT 0" },
+            Step::AddColumn { table: "turns", column: "thinking_level", definition: "TEXT" },
+        ],
+    },
+    // Sessions taken from a snapshot whose files are on another machine
+    Migration {
+        version: 18,
+        name: "sessions_is_remote",
+        steps: &[Step::AddColumn { table: "sessions", column: "is_remote", definition: "INTEGER DEFAULT 0" }],
+    },
+    // Detected languages and frameworks of each project, as JSON arrays
+    Migration {
+        version: 19,
+        name: "project_stacks",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS project_stacks (
+                project_path TEXT PRIMARY KEY,
+                languages TEXT NOT NULL,
+                frameworks TEXT NOT NULL,
+                detected_at TEXT NOT NULL
+            );
+            "#,
+        )],
+    },
+    // Resolved project paths of encoded project directories
+    Migration {
+        version: 20,
+        name: "project_registry",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS project_registry (
+                encoded_dir TEXT PRIMARY KEY,
+                project_path TEXT NOT NULL,
+                source TEXT NOT NULL,
+                ambiguous INTEGER NOT NULL DEFAULT 0,
+                resolved_at TEXT NOT NULL
+            );
+            "#,
+        )],
+    },
+    // Code changes recorded from stored turns, backfilled from the file tool
+    // calls already stored; changes picked out of Bash commands come in as
+    // sessions are parsed again
+    Migration {
+        version: 21,
+        name: "code_changes_tool_name",
+        steps: &[
+            Step::AddColumn { table: "code_changes", column: "tool_name", definition: "TEXT" },
+            Step::Sql(
+                r#"
+                INSERT OR IGNORE INTO code_changes (change_id, turn_id, file_path, change_type, tool_name)
+                SELECT
+                    u.tool_use_id,
+                    u.turn_id,
+                    COALESCE(json_extract(u.input_json, '$.file_path'), json_extract(u.input_json, '$.notebook_path')),
+                    CASE WHEN u.tool_name IN ('Write', 'write') THEN 'create' ELSE 'edit' END,
+                    CASE
+                        WHEN u.tool_name IN ('Write', 'write') THEN 'Write'
+                        WHEN u.tool_name IN ('Edit', 'edit') THEN 'Edit'
+                        ELSE 'NotebookEdit'
+                    END
+                FROM tool_uses u
+                WHERE u.tool_name IN ('Write', 'write', 'Edit', 'edit', 'NotebookEdit', 'notebook_edit')
+                  AND json_valid(u.input_json)
+                  AND COALESCE(json_extract(u.input_json, '$.file_path'), json_extract(u.input_json, '$.notebook_path')) IS NOT NULL;
+                "#,
+            ),
+        ],
+    },
+    // Daily totals per project for trends and daily metrics, and the
+    // timezone they were built in; filled in by db::rollups on startup
+    Migration {
+        version: 22,
+        name: "daily_rollups",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS daily_rollups (
+                day TEXT NOT NULL,
+                project_path TEXT NOT NULL,
+                session_count INTEGER NOT NULL,
+                user_session_count INTEGER NOT NULL,
+                subagent_session_count INTEGER NOT NULL,
+                total_turns INTEGER NOT NULL,
+                total_cost REAL NOT NULL,
+                total_tokens INTEGER NOT NULL,
+                cache_read INTEGER NOT NULL,
+                cache_write INTEGER NOT NULL,
+                PRIMARY KEY (day, project_path)
+            );
+            CREATE TABLE IF NOT EXISTS daily_rollups_state (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                timezone TEXT NOT NULL,
+                rebuilt_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_sessions_started ON sessions(started_at);
+            "#,
+        )],
+    },
+    // Turns users marked as wasted or valuable
+    Migration {
+        version: 23,
+        name: "turn_annotations",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS turn_annotations (
+                session_id TEXT NOT NULL,
+                turn_number INTEGER NOT NULL,
+                label TEXT NOT NULL,
+                note TEXT,
+                annotated_at TEXT NOT NULL,
+                PRIMARY KEY (session_id, turn_number)
+            );
+            CREATE INDEX IF NOT EXISTS idx_turn_annotations_label ON turn_annotations(label);
+            "#,
+        )],
+    },
+    // Daily rollups split into user and subagent sessions, so trends can be
+    // segmented; clearing the state rebuilds them on the next startup
+    Migration {
+        version: 24,
+        name: "daily_rollups_segments",
+        steps: &[Step::Sql(
+            r#"
+            DROP TABLE IF EXISTS daily_rollups;
+            CREATE TABLE daily_rollups (
+                day TEXT NOT NULL,
+                project_path TEXT NOT NULL,
+                subagent INTEGER NOT NULL,
+                session_count INTEGER NOT NULL,
+                user_session_count INTEGER NOT NULL,
+                subagent_session_count INTEGER NOT NULL,
+                total_turns INTEGER NOT NULL,
+                total_cost REAL NOT NULL,
+                total_tokens INTEGER NOT NULL,
+                cache_read INTEGER NOT NULL,
+                cache_write INTEGER NOT NULL,
+                PRIMARY KEY (day, project_path, subagent)
+            );
+            DELETE FROM daily_rollups_state;
+            "#,
+        )],
+    },
+    // Pricing keyed by start date too, so a model can keep its old rates for
+    // the days before a price change; existing rows have no start date ('')
+    Migration {
+        version: 25,
+        name: "pricing_effective_dates",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE pricing_by_date (
+                model_id TEXT NOT NULL,
+                display_name TEXT NOT NULL,
+                input_price_per_million REAL NOT NULL,
+                output_price_per_million REAL NOT NULL,
+                cache_write_5m_per_million REAL NOT NULL,
+                cache_write_1h_per_million REAL NOT NULL,
+                cache_read_per_million REAL NOT NULL,
+                max_context_tokens INTEGER,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                is_custom INTEGER DEFAULT 0,
+                provider TEXT NOT NULL DEFAULT 'anthropic',
+                effective_from TEXT NOT NULL DEFAULT '',
+                PRIMARY KEY (model_id, provider, effective_from)
+            );
+            INSERT INTO pricing_by_date (
+                model_id, display_name, input_price_per_million, output_price_per_million,
+                cache_write_5m_per_million, cache_write_1h_per_million, cache_read_per_million,
+                max_context_tokens, updated_at, is_custom, provider
+            )
+            SELECT
+                model_id, display_name, input_price_per_million, output_price_per_million,
+                cache_write_5m_per_million, cache_write_1h_per_million, cache_read_per_million,
+                max_context_tokens, updated_at, is_custom, provider
+            FROM pricing;
+            DROP TABLE pricing;
+            ALTER TABLE pricing_by_date RENAME TO pricing;
+            "#,
+        )],
+    },
+];
+
+const MIGRATIONS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    applied_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+"#;
+
+/// Highest migration version applied to the database (0 if none)
+pub fn current_version(conn: &Connection) -> Result<u32, DbError> {
+    conn.execute_batch(MIGRATIONS_TABLE)?;
+    let version: u32 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(version)
+}
+
+/// Apply the migrations the database doesn't have yet
+///
+/// Returns the number of migrations applied. A failing migration is rolled
+/// back and stops the run; the ones before it stay applied.
+pub fn run(conn: &Connection) -> Result<u32, DbError> {
+    run_migrations(conn, MIGRATIONS)
+}
+
+fn run_migrations(conn: &Connection, migrations: &[Migration]) -> Result<u32, DbError> {
+    let current = current_version(conn)?;
+    let mut applied = 0;
+
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        apply(conn, migration).map_err(|e| {
+            DbError::Migration(format!("{} ({}): {}", migration.version, migration.name, e))
+        })?;
+        tracing::info!("Applied schema migration {} ({})", migration.version, migration.name);
+        applied += 1;
+    }
+
+    Ok(applied)
+}
+
+fn apply(conn: &Connection, migration: &Migration) -> Result<(), DbError> {
+    let tx = conn.unchecked_transaction()?;
+
+    for step in migration.steps {
+        match step {
+            Step::AddColumn { table, column, definition } => {
+                if !has_column(&tx, table, column)? {
+                    tx.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {};", table, column, definition))?;
+                }
+            }
+            Step::Sql(sql) => tx.execute_batch(sql)?,
+        }
+    }
+
+    tx.execute(
+        "INSERT INTO schema_migrations (version, name) VALUES (?1, ?2)",
+        params![migration.version, migration.name],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, DbError> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as u32 + 1, "{}", migration.name);
+        }
+    }
+
+    #[test]
+    fn test_run_migrations() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+
+        // Some columns already exist, from the schema or an older startup
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN source TEXT;").unwrap();
+        assert_eq!(run(&conn).unwrap(), MIGRATIONS.len() as u32);
+        assert_eq!(current_version(&conn).unwrap(), MIGRATIONS.len() as u32);
+        assert!(has_column(&conn, "sessions", "parent_session_id").unwrap());
+        assert!(has_column(&conn, "pricing", "is_custom").unwrap());
+        assert!(has_column(&conn, "pricing", "provider").unwrap());
+        assert!(has_column(&conn, "pricing", "effective_from").unwrap());
+
+        // Nothing left to apply
+        assert_eq!(run(&conn).unwrap(), 0);
+
+        // A failing migration rolls back and isn't recorded
+        let broken = [Migration {
+            version: 100,
+            name: "broken",
+            steps: &[
+                Step::AddColumn { table: "sessions", column: "tags", definition: "TEXT" },
+                Step::Sql("SELECT * FROM missing_table;"),
+            ],
+        }];
+        let err = run_migrations(&conn, &broken).unwrap_err();
+        assert!(err.to_string().contains("100 (broken)"));
+        assert!(!has_column(&conn, "sessions", "tags").unwrap());
+        assert_eq!(current_version(&conn).unwrap(), MIGRATIONS.len() as u32);
+    }
+}
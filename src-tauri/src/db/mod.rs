@@ -1,17 +1,34 @@
 //! Database module for SQLite operations
 //!
 //! This module handles all database interactions including:
-//! - Schema creation and migrations
+//! - Schema creation and versioned migrations
 //! - Session and turn queries
 //! - Metrics storage and retrieval
+//! - Snapshots for syncing between machines
+//! - Backfilling rows for sessions stored before their tables existed
+//! - Team roll-up of other users' session exports
+//! - The queue of background jobs
+//! - Daily rollups behind trends and daily metrics
+//! - Optional encryption of stored message content
 
 pub mod schema;
+pub mod migrations;
 pub mod queries;
+pub mod retention;
+pub mod snapshot;
+pub mod backfill;
+pub mod team;
+pub mod jobs;
+pub mod rollups;
+pub mod crypto;
 
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
 
-use rusqlite::Connection;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{Connection, OpenFlags};
 use thiserror::Error;
 
 /// Database errors
@@ -28,25 +45,83 @@ pub enum DbError {
 
     #[error("Lock poisoned")]
     LockPoisoned,
+
+    #[error("Snapshot failed: {0}")]
+    Snapshot(String),
+
+    #[error("Encryption failed: {0}")]
+    Encryption(String),
 }
 
+/// Register application-defined SQL functions on a connection
+///
+/// - `is_real_user_project(path)`: whether a project path passes the
+///   configured include/exclude patterns (see `settings::is_real_user_project`)
+/// - `local_date(timestamp)`: the YYYY-MM-DD date of an RFC 3339 timestamp in
+///   the reporting timezone, or today's date for 'now'
+/// - `local_datetime(timestamp)`: the same as 'YYYY-MM-DD HH:MM:SS' wall-clock
+///   time, for use with SQLite's date functions
+///
+/// Both return NULL for values that aren't timestamps.
+///
+/// - `decrypt_content(value)`: a stored content column as plaintext, or
+///   NULL if it can't be decrypted (see `crypto::open`)
+pub fn register_functions(conn: &Connection) -> Result<(), DbError> {
+    // Not deterministic: the result changes when the settings do
+    conn.create_scalar_function("is_real_user_project", 1, FunctionFlags::SQLITE_UTF8, |ctx| {
+        let path: Option<String> = ctx.get(0)?;
+        Ok(path.is_some_and(|p| crate::settings::is_real_user_project(&p)))
+    })?;
+    conn.create_scalar_function("local_date", 1, FunctionFlags::SQLITE_UTF8, |ctx| {
+        let timestamp: Option<String> = ctx.get(0)?;
+        let tz = crate::settings::reporting_timezone();
+        Ok(timestamp.and_then(|ts| match ts.as_str() {
+            "now" => Some(tz.today().format("%Y-%m-%d").to_string()),
+            _ => tz.local_date(&ts),
+        }))
+    })?;
+    conn.create_scalar_function("local_datetime", 1, FunctionFlags::SQLITE_UTF8, |ctx| {
+        let timestamp: Option<String> = ctx.get(0)?;
+        Ok(timestamp
+            .and_then(|ts| crate::timezone::local_datetime(&ts))
+            .map(|local| local.format("%Y-%m-%d %H:%M:%S").to_string()))
+    })?;
+    conn.create_scalar_function("decrypt_content", 1, FunctionFlags::SQLITE_UTF8, |ctx| {
+        Ok(crypto::open(ctx.get(0)?))
+    })?;
+    Ok(())
+}
+
+/// Read-only connections opened next to the write connection
+const READ_POOL_SIZE: usize = 4;
+
+/// How long a statement waits for another connection's lock before failing
+/// with SQLITE_BUSY (the preload writer and the background phase 2 writer
+/// each hold their own connection)
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Database connection wrapper
+///
+/// Writes go through a single connection. The database runs in WAL mode, so
+/// queries that only read can use one of a few read-only connections instead
+/// and don't wait for a write (or another read) in progress.
 pub struct Database {
     conn: Mutex<Connection>,
-    path: PathBuf,
+    /// Empty when the database can't run in WAL mode (e.g. in memory)
+    readers: Vec<Mutex<Connection>>,
+    next_reader: AtomicUsize,
+    path: RwLock<PathBuf>,
 }
 
 impl Database {
     /// Create a new database connection
     pub fn new(path: PathBuf) -> Result<Self, DbError> {
-        let conn = Connection::open(&path)?;
-
-        // Enable foreign keys
-        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
-
+        let (conn, readers) = open_connections(&path)?;
         Ok(Self {
             conn: Mutex::new(conn),
-            path,
+            readers: readers.into_iter().map(Mutex::new).collect(),
+            next_reader: AtomicUsize::new(0),
+            path: RwLock::new(path),
         })
     }
 
@@ -54,58 +129,64 @@ impl Database {
     pub fn initialize(&self) -> Result<(), DbError> {
         let conn = self.conn.lock().map_err(|_| DbError::LockPoisoned)?;
         schema::create_tables(&conn)?;
+
+        // Runs before the default pricing upsert, which depends on pricing.is_custom
+        // and the (model_id, provider) key
+        migrations::run(&conn)?;
         schema::insert_default_pricing(&conn)?;
 
-        // Migration: Fix last_activity_at for sessions where it was incorrectly set equal to started_at.
-        // Computes correct value from started_at + total_duration_ms from session_metrics.
-        conn.execute_batch(r#"
-            UPDATE sessions
-            SET last_activity_at = strftime('%Y-%m-%dT%H:%M:%fZ',
-                julianday(sessions.started_at) + (
-                    SELECT CAST(m.total_duration_ms AS REAL) / 86400000.0
-                    FROM session_metrics m
-                    WHERE m.session_id = sessions.session_id
-                )
-            )
-            WHERE sessions.last_activity_at = sessions.started_at
-            AND EXISTS (
-                SELECT 1 FROM session_metrics m
-                WHERE m.session_id = sessions.session_id
-                AND m.total_duration_ms > 0
-            );
-        "#)?;
-
-        // Migration: Add summary column to sessions table (stores the first user message
-        // so it can be shown in the session list without querying the turns table).
-        // ALTER TABLE ADD COLUMN is idempotent-safe: we ignore the error if the column
-        // already exists (SQLite returns "duplicate column name" in that case).
-        if let Err(e) = conn.execute_batch("ALTER TABLE sessions ADD COLUMN summary TEXT;") {
-            let msg = e.to_string();
-            if !msg.contains("duplicate column") {
-                return Err(DbError::Migration(format!("Failed to add summary column: {}", msg)));
-            }
+        Ok(())
+    }
+
+    /// Get the database file path
+    pub fn path(&self) -> PathBuf {
+        self.path.read().map(|p| p.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Copy the database to `target` and continue on the copy
+    ///
+    /// The write connection is held throughout, so no write is lost between
+    /// the copy and the switch. `VACUUM INTO` writes a consistent, compacted
+    /// copy; the old file and its WAL are removed once nothing uses them.
+    /// Other `Database` instances on the old file must [`reopen`](Self::reopen)
+    /// before they write again.
+    pub fn move_to(&self, target: &Path) -> Result<(), DbError> {
+        let mut conn = self.conn.lock().map_err(|_| DbError::LockPoisoned)?;
+        if target.exists() {
+            return Err(DbError::Migration(format!("{} already exists", target.display())));
         }
+        conn.execute("VACUUM INTO ?1", [target.to_string_lossy()])?;
 
-        // Migration: Normalize file_mtime format for consistent cache-hit comparison.
-        //
-        // get_file_mtime() now uses to_rfc3339_opts(Micros, true) which produces
-        // timestamps like "2026-02-20T11:44:48.663732Z" (6 fractional digits, Z suffix).
-        //
-        // Some older DB records use "+00:00" suffix instead of "Z". This migration
-        // normalizes those so the string comparison in the cache-hit check succeeds.
-        conn.execute_batch(r#"
-            UPDATE sessions
-            SET file_mtime = replace(file_mtime, '+00:00', 'Z')
-            WHERE file_mtime IS NOT NULL
-              AND file_mtime LIKE '%+00:00';
-        "#)?;
+        let old = self.path();
+        if let Err(e) = self.swap_connections(&mut conn, target) {
+            let _ = std::fs::remove_file(target);
+            return Err(e);
+        }
+        drop(conn);
 
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = old.clone().into_os_string();
+            file.push(suffix);
+            let _ = std::fs::remove_file(file);
+        }
         Ok(())
     }
 
-    /// Get the database file path
-    pub fn path(&self) -> &PathBuf {
-        &self.path
+    /// Close the connections and open `path` instead
+    pub fn reopen(&self, path: &Path) -> Result<(), DbError> {
+        let mut conn = self.conn.lock().map_err(|_| DbError::LockPoisoned)?;
+        self.swap_connections(&mut conn, path)
+    }
+
+    fn swap_connections(&self, conn: &mut Connection, path: &Path) -> Result<(), DbError> {
+        let (writer, readers) = open_connections(path)?;
+        // Without a pool before, reads keep going through the writer
+        for (slot, reader) in self.readers.iter().zip(readers) {
+            *slot.lock().map_err(|_| DbError::LockPoisoned)? = reader;
+        }
+        *conn = writer;
+        *self.path.write().map_err(|_| DbError::LockPoisoned)? = path.to_path_buf();
+        Ok(())
     }
 
     /// Execute a query with the database connection
@@ -116,12 +197,213 @@ impl Database {
         let conn = self.conn.lock().map_err(|_| DbError::LockPoisoned)?;
         f(&conn)
     }
+
+    /// Execute a read-only query on a pooled read connection
+    ///
+    /// Takes the first idle reader, or waits for one if all are in use. The
+    /// reader sees the last committed state, so writes from `with_connection`
+    /// are visible once that call has returned. Falls back to the write
+    /// connection when there is no pool.
+    pub fn with_read_connection<F, T>(&self, f: F) -> Result<T, DbError>
+    where
+        F: FnOnce(&Connection) -> Result<T, DbError>,
+    {
+        if self.readers.is_empty() {
+            return self.with_connection(f);
+        }
+
+        let start = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+        for i in 0..self.readers.len() {
+            if let Ok(conn) = self.readers[(start + i) % self.readers.len()].try_lock() {
+                return f(&conn);
+            }
+        }
+
+        let conn = self.readers[start].lock().map_err(|_| DbError::LockPoisoned)?;
+        f(&conn)
+    }
+}
+
+/// Open the write connection to the database at `path`, and the read pool
+/// if it runs in WAL mode
+fn open_connections(path: &Path) -> Result<(Connection, Vec<Connection>), DbError> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+
+    // WAL is a property of the database file; it stays on for later opens
+    let journal_mode: String =
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+
+    // Enable foreign keys
+    conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA synchronous = NORMAL;")?;
+    register_functions(&conn)?;
+
+    let readers = if journal_mode.eq_ignore_ascii_case("wal") {
+        (0..READ_POOL_SIZE)
+            .map(|_| open_reader(path))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        tracing::warn!("Database journal mode is {}, reads share the write connection", journal_mode);
+        Vec::new()
+    };
+
+    Ok((conn, readers))
 }
 
+/// Open a read-only connection to the database at `path`
+fn open_reader(path: &Path) -> Result<Connection, DbError> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI,
+    )?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    register_functions(&conn)?;
+    Ok(conn)
+}
+
+/// Environment variable overriding where the database is kept
+pub const DB_PATH_ENV: &str = "IRONHIDE_DB_PATH";
+
 /// Get the default database path
+///
+/// The local data directory follows `XDG_DATA_HOME` on Linux.
 pub fn default_db_path() -> PathBuf {
     let data_dir = dirs::data_local_dir()
         .unwrap_or_else(|| PathBuf::from("."));
 
     data_dir.join("ironhide").join("analytics.db")
 }
+
+/// File next to the default database recording where it was moved to
+fn location_file() -> PathBuf {
+    default_db_path().with_file_name("database_location")
+}
+
+/// Database path set by `IRONHIDE_DB_PATH`, if any
+pub fn env_db_path() -> Option<PathBuf> {
+    std::env::var_os(DB_PATH_ENV)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Where the database is opened from: `IRONHIDE_DB_PATH`, then the location
+/// it was last moved to, then the default path
+pub fn configured_db_path() -> PathBuf {
+    env_db_path()
+        .or_else(|| {
+            std::fs::read_to_string(location_file())
+                .ok()
+                .map(|location| location.trim().to_string())
+                .filter(|location| !location.is_empty())
+                .map(PathBuf::from)
+        })
+        .unwrap_or_else(default_db_path)
+}
+
+/// Record `path` as the database location for the next start; the default
+/// path clears the record
+pub fn save_db_location(path: &Path) -> std::io::Result<()> {
+    let file = location_file();
+    if path == default_db_path() {
+        return match std::fs::remove_file(&file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(file, path.to_string_lossy().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_pool_sees_committed_writes() {
+        let dir = std::env::temp_dir().join("ironhide_db_pool_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let db = Database::new(dir.join("analytics.db")).unwrap();
+        db.initialize().unwrap();
+        assert_eq!(db.readers.len(), READ_POOL_SIZE);
+
+        db.with_connection(|conn| {
+            conn.execute("INSERT INTO settings (key, value) VALUES ('a', '1')", [])?;
+            Ok(())
+        })
+        .unwrap();
+
+        // Every reader sees the write, and readers refuse to write
+        for _ in 0..READ_POOL_SIZE {
+            let value: String = db
+                .with_read_connection(|conn| {
+                    Ok(conn.query_row("SELECT value FROM settings WHERE key = 'a'", [], |row| row.get(0))?)
+                })
+                .unwrap();
+            assert_eq!(value, "1");
+        }
+        assert!(db
+            .with_read_connection(|conn| Ok(conn.execute("DELETE FROM settings", [])?))
+            .is_err());
+
+        // A reader held open doesn't block other reads
+        let held = db.readers[0].lock().unwrap();
+        assert!(db.with_read_connection(queries::get_settings_entries).is_ok());
+        drop(held);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_move_to() {
+        let dir = std::env::temp_dir().join("ironhide_db_move_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let old = dir.join("analytics.db");
+        let new = dir.join("moved").join("analytics.db");
+        std::fs::create_dir_all(new.parent().unwrap()).unwrap();
+
+        let db = Database::new(old.clone()).unwrap();
+        db.initialize().unwrap();
+        let worker = Database::new(old.clone()).unwrap();
+        db.with_connection(|conn| {
+            conn.execute("INSERT INTO settings (key, value) VALUES ('a', '1')", [])?;
+            Ok(())
+        })
+        .unwrap();
+
+        db.move_to(&new).unwrap();
+        assert_eq!(db.path(), new);
+        assert!(!old.exists());
+        for _ in 0..READ_POOL_SIZE {
+            let entries = db.with_read_connection(queries::get_settings_entries).unwrap();
+            assert_eq!(entries.get("a").map(String::as_str), Some("1"));
+        }
+
+        // Another connection sees writes once it follows
+        worker.reopen(&new).unwrap();
+        worker
+            .with_connection(|conn| {
+                conn.execute("INSERT INTO settings (key, value) VALUES ('b', '2')", [])?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(db.with_read_connection(queries::get_settings_entries).unwrap().len(), 2);
+
+        // Moving onto an existing file is refused
+        assert!(matches!(db.move_to(&new), Err(DbError::Migration(_))));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_in_memory_database_reads_through_writer() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        db.initialize().unwrap();
+        assert!(db.readers.is_empty());
+        assert!(db.with_read_connection(queries::get_settings_entries).unwrap().is_empty());
+    }
+}
@@ -3,11 +3,30 @@
 //! Contains functions for querying sessions, turns, and metrics
 
 use rusqlite::{params, Connection, OptionalExtension};
-use std::collections::HashMap;
-use super::DbError;
+use std::collections::{BTreeMap, HashMap};
+use super::{crypto, DbError};
+use crate::metrics::api_errors::TurnApiErrors;
+use crate::metrics::cost::{calculate_provider_turn_cost, CostProvider, ModelPricing};
+use crate::metrics::rework::{code_change, CodeChange, FileChangeSummary};
+use crate::metrics::stack::ProjectStack;
+use crate::metrics::stats::SessionTotals;
+use crate::metrics::thinking::ThinkingUsage;
+use crate::metrics::mcp::McpCall;
+use crate::metrics::savings::SimulatedTurn;
+use crate::metrics::tokens::TurnTokens;
+use crate::metrics::tool_usage::ToolCallCount;
 use crate::models::session::{Session, SessionSummary};
-use crate::models::turn::Turn;
+use crate::parser::registry::{ProjectResolution, ResolutionSource};
+use crate::parser::session::{CompletedTurn, ToolUse};
+use crate::parser::tool_names::canonical_tool_name;
+use crate::parser::SessionFileInfo;
+use crate::models::turn::{AnnotationLabel, Turn, TurnAnnotation};
 use crate::models::metrics::SessionMetrics;
+use crate::patterns::{AntiPatternType, DetectedPattern};
+use crate::recommendations::{RecommendationFeedback, RecommendationType};
+use crate::trends::digest::{DigestAntiPattern, DigestProject, DigestSession, BASELINE_DAYS};
+use crate::trends::report::{ReportAntiPattern, ReportSession};
+use crate::trends::Segment;
 
 /// Dashboard aggregate results from SQL query
 #[derive(Debug, Clone)]
@@ -35,6 +54,30 @@ pub struct DailyAggregates {
     pub avg_efficiency: Option<f64>,
 }
 
+/// Turn aggregates for one local day-of-week/hour slot
+#[derive(Debug, Clone)]
+pub struct HourlyAggregates {
+    /// Day of week, Monday = 0
+    pub day_of_week: u32,
+    pub hour: u32,
+    pub turns: u32,
+    pub sessions: u32,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+}
+
+/// Cost and tokens of one stored turn, with running totals for the session
+#[derive(Debug, Clone)]
+pub struct TurnCostAggregates {
+    pub turn_number: u32,
+    pub started_at: String,
+    pub model: Option<String>,
+    pub cost: f64,
+    pub tokens: u64,
+    pub cumulative_cost: f64,
+    pub cumulative_tokens: u64,
+}
+
 /// Project aggregate results from SQL query
 #[derive(Debug, Clone)]
 pub struct ProjectAggregates {
@@ -250,8 +293,8 @@ pub fn get_turns(
                 started_at: row.get(3)?,
                 ended_at: row.get(4)?,
                 duration_ms: row.get(5)?,
-                user_message: row.get(6)?,
-                assistant_message: row.get(7)?,
+                user_message: crypto::open(row.get(6)?),
+                assistant_message: crypto::open(row.get(7)?),
                 model: row.get(8)?,
                 stop_reason: row.get(9)?,
                 created_at: row.get(10)?,
@@ -336,6 +379,116 @@ pub fn upsert_session(
     Ok(())
 }
 
+/// Set the git branch recorded for a session
+/// Returns false if the session isn't in the database
+pub fn update_session_branch(conn: &Connection, session_id: &str, branch: &str) -> Result<bool, DbError> {
+    let updated = conn.execute(
+        "UPDATE sessions SET branch = ?1, updated_at = CURRENT_TIMESTAMP WHERE session_id = ?2",
+        params![branch, session_id],
+    )?;
+    Ok(updated > 0)
+}
+
+/// Set the import source label recorded for a session
+/// Returns false if the session isn't in the database
+pub fn update_session_source(conn: &Connection, session_id: &str, source: &str) -> Result<bool, DbError> {
+    let updated = conn.execute(
+        "UPDATE sessions SET source = ?1, updated_at = CURRENT_TIMESTAMP WHERE session_id = ?2",
+        params![source, session_id],
+    )?;
+    Ok(updated > 0)
+}
+
+/// Point a session at the file it's now stored in, e.g. after archiving
+/// Returns false if the session isn't in the database
+pub fn update_session_file_path(conn: &Connection, session_id: &str, file_path: &str) -> Result<bool, DbError> {
+    let updated = conn.execute(
+        "UPDATE sessions SET file_path = ?1, updated_at = CURRENT_TIMESTAMP WHERE session_id = ?2",
+        params![file_path, session_id],
+    )?;
+    Ok(updated > 0)
+}
+
+/// Record the session that spawned a subagent session
+/// Returns false if the session isn't in the database
+pub fn update_session_parent(conn: &Connection, session_id: &str, parent_id: &str) -> Result<bool, DbError> {
+    let updated = conn.execute(
+        "UPDATE sessions SET parent_session_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE session_id = ?2",
+        params![parent_id, session_id],
+    )?;
+    Ok(updated > 0)
+}
+
+/// Link stored subagent sessions to the session whose Task calls started them
+///
+/// Agent IDs may or may not carry the `agent-` prefix of the file name, so
+/// both forms are matched. Sessions that already have a parent are left alone.
+/// Returns the number of sessions linked.
+pub fn link_spawned_subagents(conn: &Connection, parent_id: &str, agent_ids: &[String]) -> Result<u32, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        UPDATE sessions SET parent_session_id = ?1, updated_at = CURRENT_TIMESTAMP
+        WHERE session_id IN (?2, 'agent-' || ?2)
+          AND session_id != ?1
+          AND parent_session_id IS NULL
+        "#,
+    )?;
+    let mut linked = 0;
+    for agent_id in agent_ids {
+        let id = agent_id.strip_prefix("agent-").unwrap_or(agent_id);
+        linked += stmt.execute(params![parent_id, id])? as u32;
+    }
+    Ok(linked)
+}
+
+/// Link a subagent session to a stored session whose turns spawned it
+///
+/// Covers subagents stored after their parent. Returns false if no spawning
+/// turn was found or the session already has a parent.
+pub fn link_subagent_to_spawner(conn: &Connection, session_id: &str) -> Result<bool, DbError> {
+    let agent_id = session_id.strip_prefix("agent-").unwrap_or(session_id);
+    let spawner: Option<String> = conn
+        .query_row(
+            r#"
+            SELECT session_id FROM turns
+            WHERE session_id != ?1
+              AND (subagent_ids LIKE '%"' || ?2 || '"%' OR subagent_ids LIKE '%"agent-' || ?2 || '"%')
+            LIMIT 1
+            "#,
+            params![session_id, agent_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let parent_id = match spawner {
+        Some(id) => id,
+        None => return Ok(false),
+    };
+    let updated = conn.execute(
+        "UPDATE sessions SET parent_session_id = ?1, updated_at = CURRENT_TIMESTAMP \
+         WHERE session_id = ?2 AND parent_session_id IS NULL",
+        params![parent_id, session_id],
+    )?;
+    Ok(updated > 0)
+}
+
+/// Total cost of the subagent sessions linked to each parent session
+pub fn get_subagent_costs(conn: &Connection) -> Result<HashMap<String, f64>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT s.parent_session_id, COALESCE(SUM(m.total_cost), 0.0)
+        FROM sessions s
+        JOIN session_metrics m ON m.session_id = s.session_id
+        WHERE s.parent_session_id IS NOT NULL
+        GROUP BY s.parent_session_id
+        "#,
+    )?;
+    let costs = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(costs)
+}
+
 /// Insert or update session metrics
 pub fn upsert_session_metrics(
     conn: &Connection,
@@ -489,8 +642,8 @@ pub fn get_sessions_for_frontend(
             COALESCE(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write, 0) as total_tokens,
             COALESCE(m.total_duration_ms, 0) as duration_ms,
             COALESCE(
-                s.summary,
-                (SELECT SUBSTR(t.user_message, 1, 200) FROM turns t WHERE t.session_id = s.session_id AND t.user_message IS NOT NULL AND t.user_message != '' ORDER BY t.turn_number ASC LIMIT 1)
+                decrypt_content(s.summary),
+                (SELECT SUBSTR(decrypt_content(t.user_message), 1, 200) FROM turns t WHERE t.session_id = s.session_id AND t.user_message IS NOT NULL AND t.user_message != '' ORDER BY t.turn_number ASC LIMIT 1)
             ) as summary
         FROM sessions s
         LEFT JOIN session_metrics m ON s.session_id = m.session_id
@@ -550,8 +703,8 @@ pub fn get_sessions_for_frontend_filtered(
             COALESCE(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write, 0) as total_tokens,
             COALESCE(m.total_duration_ms, 0) as duration_ms,
             COALESCE(
-                s.summary,
-                (SELECT SUBSTR(t.user_message, 1, 200) FROM turns t WHERE t.session_id = s.session_id AND t.user_message IS NOT NULL AND t.user_message != '' ORDER BY t.turn_number ASC LIMIT 1)
+                decrypt_content(s.summary),
+                (SELECT SUBSTR(decrypt_content(t.user_message), 1, 200) FROM turns t WHERE t.session_id = s.session_id AND t.user_message IS NOT NULL AND t.user_message != '' ORDER BY t.turn_number ASC LIMIT 1)
             ) as summary
         FROM sessions s
         LEFT JOIN session_metrics m ON s.session_id = m.session_id
@@ -560,13 +713,13 @@ pub fn get_sessions_for_frontend_filtered(
     );
 
     if start_date.is_some() {
-        sql.push_str(" AND substr(s.started_at, 1, 10) >= ?3");
+        sql.push_str(" AND local_date(s.started_at) >= ?3");
     }
     if end_date.is_some() {
         sql.push_str(if start_date.is_some() {
-            " AND substr(s.started_at, 1, 10) <= ?4"
+            " AND local_date(s.started_at) <= ?4"
         } else {
-            " AND substr(s.started_at, 1, 10) <= ?3"
+            " AND local_date(s.started_at) <= ?3"
         });
     }
 
@@ -686,8 +839,8 @@ pub fn get_sessions_for_frontend_by_project(
             COALESCE(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write, 0) as total_tokens,
             COALESCE(m.total_duration_ms, 0) as duration_ms,
             COALESCE(
-                s.summary,
-                (SELECT SUBSTR(t.user_message, 1, 200) FROM turns t WHERE t.session_id = s.session_id AND t.user_message IS NOT NULL AND t.user_message != '' ORDER BY t.turn_number ASC LIMIT 1)
+                decrypt_content(s.summary),
+                (SELECT SUBSTR(decrypt_content(t.user_message), 1, 200) FROM turns t WHERE t.session_id = s.session_id AND t.user_message IS NOT NULL AND t.user_message != '' ORDER BY t.turn_number ASC LIMIT 1)
             ) as summary
         FROM sessions s
         LEFT JOIN session_metrics m ON s.session_id = m.session_id
@@ -722,6 +875,311 @@ pub fn get_sessions_for_frontend_by_project(
     Ok(sessions)
 }
 
+/// One session of a project's activity timeline
+#[derive(Debug, Clone)]
+pub struct ProjectTimelineRow {
+    pub session_id: String,
+    pub started_at: String,
+    pub last_activity_at: Option<String>,
+    pub model: Option<String>,
+    pub total_turns: u32,
+    pub duration_ms: u64,
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    /// Cache efficiency ratio: cache reads over all cache traffic
+    pub efficiency: Option<f64>,
+}
+
+/// Get a project's main sessions (no subagents) in chronological order
+pub fn get_project_timeline_from_db(
+    conn: &Connection,
+    project_path: &str,
+) -> Result<Vec<ProjectTimelineRow>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            s.session_id,
+            s.started_at,
+            s.last_activity_at,
+            s.model,
+            m.total_turns,
+            COALESCE(m.total_duration_ms, 0) as duration_ms,
+            COALESCE(m.total_cost, 0.0) as total_cost,
+            COALESCE(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write, 0) as total_tokens,
+            CASE WHEN m.total_cache_read + m.total_cache_write > 0
+            THEN CAST(m.total_cache_read AS REAL) / (m.total_cache_read + m.total_cache_write)
+            ELSE NULL END as efficiency
+        FROM sessions s
+        JOIN session_metrics m ON s.session_id = m.session_id
+        WHERE s.project_path = ?1
+          AND m.total_turns > 0
+          AND s.file_path NOT LIKE '%subagent%'
+        ORDER BY s.started_at ASC
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![project_path], |row| {
+        Ok(ProjectTimelineRow {
+            session_id: row.get(0)?,
+            started_at: row.get(1)?,
+            last_activity_at: row.get(2)?,
+            model: row.get(3)?,
+            total_turns: row.get::<_, i32>(4)? as u32,
+            duration_ms: row.get::<_, i64>(5)? as u64,
+            total_cost: row.get(6)?,
+            total_tokens: row.get::<_, i64>(7)? as u64,
+            efficiency: row.get(8)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Count file changes made with Write, Edit and NotebookEdit per session of a
+/// project, from the stored turns
+///
+/// Sessions without stored turns are missing from the map; sessions with
+/// turns but no file changes map to 0.
+pub fn get_code_change_counts_from_db(
+    conn: &Connection,
+    project_path: &str,
+) -> Result<HashMap<String, u32>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            t.session_id,
+            COUNT(c.change_id) as changes
+        FROM turns t
+        JOIN sessions s ON s.session_id = t.session_id
+        LEFT JOIN code_changes c ON c.turn_id = t.turn_id
+            AND c.tool_name IN ('Write', 'Edit', 'NotebookEdit')
+        WHERE s.project_path = ?1
+        GROUP BY t.session_id
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![project_path], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u32))
+    })?;
+
+    Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+}
+
+/// Paths of the files changed with Write, Edit and NotebookEdit in the stored
+/// turns of a project's sessions, once per change
+pub fn get_changed_files_from_db(conn: &Connection, project_path: &str) -> Result<Vec<String>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT c.file_path
+        FROM code_changes c
+        JOIN turns t ON t.turn_id = c.turn_id
+        JOIN sessions s ON s.session_id = t.session_id
+        WHERE s.project_path = ?1
+          AND c.tool_name IN ('Write', 'Edit', 'NotebookEdit')
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![project_path], |row| row.get::<_, String>(0))?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Files changed with Write, Edit and NotebookEdit in a project's stored
+/// turns, most changed first
+pub fn get_project_file_changes_from_db(
+    conn: &Connection,
+    project_path: &str,
+) -> Result<Vec<FileChangeSummary>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            c.file_path,
+            COUNT(*) as changes,
+            COUNT(DISTINCT t.session_id),
+            MAX(t.started_at)
+        FROM code_changes c
+        JOIN turns t ON t.turn_id = c.turn_id
+        JOIN sessions s ON s.session_id = t.session_id
+        WHERE s.project_path = ?1
+          AND c.tool_name IN ('Write', 'Edit', 'NotebookEdit')
+        GROUP BY c.file_path
+        ORDER BY changes DESC, c.file_path
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![project_path], |row| {
+        Ok(FileChangeSummary {
+            file_path: row.get(0)?,
+            changes: row.get(1)?,
+            sessions: row.get(2)?,
+            last_changed_at: row.get(3)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Stored code changes of a session, in the order they were made
+pub fn get_session_code_changes_from_db(conn: &Connection, session_id: &str) -> Result<Vec<CodeChange>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT c.file_path, c.change_type, COALESCE(c.tool_name, ''), t.turn_number, t.started_at
+        FROM code_changes c
+        JOIN turns t ON t.turn_id = c.turn_id
+        WHERE t.session_id = ?1
+        ORDER BY t.turn_number, c.rowid
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![session_id], |row| {
+        Ok(CodeChange {
+            file_path: row.get(0)?,
+            change_type: row.get(1)?,
+            tool_name: row.get(2)?,
+            turn_number: row.get(3)?,
+            timestamp: row.get(4)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// One stored change to a file, with the turn and session it was made in
+#[derive(Debug, Clone)]
+pub struct FileChangeRow {
+    pub session_id: String,
+    pub project_path: String,
+    pub project_name: String,
+    pub branch: Option<String>,
+    pub source: Option<String>,
+    pub turn_number: u32,
+    pub started_at: String,
+    pub tool_name: Option<String>,
+    pub change_type: String,
+    /// The turn's cost divided by the number of files it changed
+    pub cost_share: f64,
+}
+
+/// Stored changes to a file across all sessions, newest first
+///
+/// Paths are compared with backslashes read as forward slashes.
+pub fn get_file_changes_from_db(conn: &Connection, file_path: &str) -> Result<Vec<FileChangeRow>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            t.session_id,
+            s.project_path,
+            s.project_name,
+            s.branch,
+            s.source,
+            t.turn_number,
+            t.started_at,
+            c.tool_name,
+            c.change_type,
+            COALESCE(m.total_cost, 0.0)
+                / (SELECT COUNT(DISTINCT c2.file_path) FROM code_changes c2 WHERE c2.turn_id = c.turn_id)
+        FROM code_changes c
+        JOIN turns t ON t.turn_id = c.turn_id
+        JOIN sessions s ON s.session_id = t.session_id
+        LEFT JOIN turn_metrics m ON m.turn_id = c.turn_id
+        WHERE replace(c.file_path, '\', '/') = replace(?1, '\', '/')
+        ORDER BY t.started_at DESC, t.session_id, c.change_id
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![file_path], |row| {
+        Ok(FileChangeRow {
+            session_id: row.get(0)?,
+            project_path: row.get(1)?,
+            project_name: row.get(2)?,
+            branch: row.get(3)?,
+            source: row.get(4)?,
+            turn_number: row.get(5)?,
+            started_at: row.get(6)?,
+            tool_name: row.get(7)?,
+            change_type: row.get(8)?,
+            cost_share: row.get(9)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Stored stacks of every project, with when each was detected
+pub fn get_project_stacks(conn: &Connection) -> Result<HashMap<String, (ProjectStack, String)>, DbError> {
+    let mut stmt = conn.prepare("SELECT project_path, languages, frameworks, detected_at FROM project_stacks")?;
+    let rows = stmt.query_map([], |row| {
+        let languages: String = row.get(1)?;
+        let frameworks: String = row.get(2)?;
+        let stack = ProjectStack {
+            languages: serde_json::from_str(&languages).unwrap_or_default(),
+            frameworks: serde_json::from_str(&frameworks).unwrap_or_default(),
+        };
+        Ok((row.get::<_, String>(0)?, (stack, row.get::<_, String>(3)?)))
+    })?;
+
+    Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+}
+
+/// Store the detected stack of a project, replacing the previous detection
+pub fn upsert_project_stack(
+    conn: &Connection,
+    project_path: &str,
+    stack: &ProjectStack,
+    detected_at: &str,
+) -> Result<(), DbError> {
+    conn.execute(
+        r#"
+        INSERT INTO project_stacks (project_path, languages, frameworks, detected_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(project_path) DO UPDATE SET
+            languages = excluded.languages,
+            frameworks = excluded.frameworks,
+            detected_at = excluded.detected_at
+        "#,
+        params![
+            project_path,
+            serde_json::to_string(&stack.languages).unwrap_or_default(),
+            serde_json::to_string(&stack.frameworks).unwrap_or_default(),
+            detected_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// File details of a remote session, one bootstrapped from a snapshot
+/// whose file is on another machine
+///
+/// None if the session isn't stored or its file is on this machine.
+pub fn get_remote_session_info(conn: &Connection, session_id: &str) -> Result<Option<SessionFileInfo>, DbError> {
+    let info = conn
+        .query_row(
+            r#"
+            SELECT file_path, project_path, last_activity_at, source, parent_session_id
+            FROM sessions
+            WHERE session_id = ?1 AND is_remote = 1
+            "#,
+            params![session_id],
+            |row| {
+                let file_path: String = row.get(0)?;
+                let last_activity_at: String = row.get(2)?;
+                let parent_session_id: Option<String> = row.get(4)?;
+                Ok(SessionFileInfo {
+                    session_id: session_id.to_string(),
+                    project_path: Some(row.get(1)?),
+                    modified: chrono::DateTime::parse_from_rfc3339(&last_activity_at)
+                        .map_or(std::time::UNIX_EPOCH, std::time::SystemTime::from),
+                    size: 0,
+                    is_subagent: parent_session_id.is_some() || file_path.contains("subagent"),
+                    source: row.get(3)?,
+                    parent_session_id,
+                    path: file_path.into(),
+                })
+            },
+        )
+        .optional()?;
+    Ok(info)
+}
+
 /// Check if a session exists in the database
 pub fn session_exists(conn: &Connection, session_id: &str) -> Result<bool, DbError> {
     let count: i32 = conn.query_row(
@@ -816,7 +1274,7 @@ pub fn get_all_sessions_with_mtime(
             total_cost: row.get(11)?,
             total_tokens: row.get::<_, i64>(12)? as u64,
             total_duration_ms: row.get::<_, i64>(13)? as u64,
-            summary: row.get(14)?,
+            summary: crypto::open(row.get(14)?),
         })
     })?;
 
@@ -855,6 +1313,10 @@ pub fn upsert_session_with_mtime(
             last_activity_at = excluded.last_activity_at,
             is_active = excluded.is_active,
             file_mtime = excluded.file_mtime,
+            branch = COALESCE(excluded.branch, sessions.branch),
+            -- A remote session's file turned up on this machine
+            file_path = CASE WHEN sessions.is_remote = 1 THEN excluded.file_path ELSE sessions.file_path END,
+            is_remote = 0,
             updated_at = CURRENT_TIMESTAMP
         "#,
         params![
@@ -883,201 +1345,989 @@ pub fn upsert_session_summary(
 ) -> Result<(), DbError> {
     conn.execute(
         "UPDATE sessions SET summary = ?1 WHERE session_id = ?2",
-        params![summary, session_id],
+        params![crypto::seal(Some(summary))?, session_id],
     )?;
     Ok(())
 }
 
-/// Dashboard aggregate: total_sessions, total_cost, total_turns, total_tokens, active_projects
-/// Filters to real user projects (project_path LIKE '/Users/%') and sessions with turns > 0.
-/// When `days` is Some, only includes sessions from the last N days.
-/// Uses substr() for date comparisons to handle RFC3339 timestamps safely,
-/// and guards against non-date values (e.g. 'unknown') with a LIKE '20%' check.
-pub fn get_dashboard_summary_from_db(conn: &Connection, days: Option<u32>) -> Result<DashboardAggregates, DbError> {
-    let date_filter = if days.is_some() {
-        "AND s.started_at LIKE '20%' AND substr(s.started_at, 1, 10) >= date('now', '-' || ?1 || ' days')"
-    } else {
-        ""
-    };
-
-    let sql = format!(
-        r#"
-        SELECT
-            COUNT(*) as total_sessions,
-            COUNT(CASE WHEN s.file_path NOT LIKE '%/subagents/%' AND s.file_path NOT LIKE '%/agent-%' THEN 1 END) as user_sessions,
-            COUNT(CASE WHEN s.file_path LIKE '%/subagents/%' OR s.file_path LIKE '%/agent-%' THEN 1 END) as subagent_sessions,
-            COALESCE(SUM(m.total_cost), 0.0) as total_cost,
-            COALESCE(SUM(m.total_turns), 0) as total_turns,
-            COALESCE(SUM(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write), 0) as total_tokens,
-            COUNT(DISTINCT s.project_path) as active_projects
-        FROM sessions s
-        LEFT JOIN session_metrics m ON s.session_id = m.session_id
-        WHERE s.project_path LIKE '/Users/%'
-          AND COALESCE(m.total_turns, 0) > 0
-          {date_filter}
-        "#,
-    );
+/// Clear stored message text, tool inputs and tool results, and session
+/// summaries, for privacy mode. Returns the number of turns scrubbed.
+pub fn scrub_stored_content(conn: &Connection) -> Result<u32, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let turns = tx.execute(
+        "UPDATE turns SET user_message = NULL, assistant_message = NULL \
+         WHERE user_message IS NOT NULL OR assistant_message IS NOT NULL",
+        [],
+    )? as u32;
+    tx.execute(
+        "UPDATE tool_uses SET input_json = NULL, result = NULL \
+         WHERE input_json IS NOT NULL OR result IS NOT NULL",
+        [],
+    )?;
+    tx.execute("UPDATE sessions SET summary = NULL WHERE summary IS NOT NULL", [])?;
+    tx.commit()?;
+    Ok(turns)
+}
 
-    let mut stmt = conn.prepare(&sql)?;
+/// Get the file modification time the stored turns for a session were parsed from
+pub fn get_session_turns_mtime(conn: &Connection, session_id: &str) -> Result<Option<String>, DbError> {
+    let mtime = conn
+        .query_row(
+            "SELECT turns_mtime FROM sessions WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()?
+        .flatten();
 
-    let (total_sessions, user_sessions, subagent_sessions, total_cost, total_turns, total_tokens, active_projects) = if let Some(d) = days {
-        stmt.query_row(params![d], |row| {
-            Ok((
-                row.get::<_, i32>(0)? as u32,
-                row.get::<_, i32>(1)? as u32,
-                row.get::<_, i32>(2)? as u32,
-                row.get::<_, f64>(3)?,
-                row.get::<_, i32>(4)? as u32,
-                row.get::<_, i64>(5)? as u64,
-                row.get::<_, i32>(6)? as u32,
-            ))
-        })?
-    } else {
-        stmt.query_row([], |row| {
-            Ok((
-                row.get::<_, i32>(0)? as u32,
-                row.get::<_, i32>(1)? as u32,
-                row.get::<_, i32>(2)? as u32,
-                row.get::<_, f64>(3)?,
-                row.get::<_, i32>(4)? as u32,
-                row.get::<_, i64>(5)? as u64,
-                row.get::<_, i32>(6)? as u32,
-            ))
-        })?
-    };
+    Ok(mtime)
+}
 
-    // Compute global CER = SUM(cache_read) / (SUM(cache_read) + SUM(cache_write))
-    // Using global totals instead of averaging per-session CERs avoids skew from
-    // many small sessions (e.g. subagents) with tiny or zero cache values.
-    let eff_sql = format!(
+/// Turn count of every session with stored turns, with the file mtime the
+/// turns were stored at
+pub fn get_stored_turn_counts(conn: &Connection) -> Result<HashMap<String, (String, u32)>, DbError> {
+    let mut stmt = conn.prepare(
         r#"
-        SELECT
-            CASE WHEN SUM(m.total_cache_read) + SUM(m.total_cache_write) > 0
-            THEN CAST(SUM(m.total_cache_read) AS REAL) / (SUM(m.total_cache_read) + SUM(m.total_cache_write))
-            ELSE NULL END
+        SELECT s.session_id, s.turns_mtime, COUNT(t.turn_id)
         FROM sessions s
-        JOIN session_metrics m ON s.session_id = m.session_id
-        WHERE s.project_path LIKE '/Users/%'
-          AND m.total_turns > 0
-          {date_filter}
+        LEFT JOIN turns t ON t.session_id = s.session_id
+        WHERE s.turns_mtime IS NOT NULL
+        GROUP BY s.session_id
         "#,
-    );
+    )?;
 
-    let avg_efficiency: Option<f64> = if let Some(d) = days {
-        conn.query_row(&eff_sql, params![d], |row| row.get::<_, Option<f64>>(0))?
-    } else {
-        conn.query_row(&eff_sql, [], |row| row.get::<_, Option<f64>>(0))?
-    };
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, (row.get::<_, String>(1)?, row.get::<_, i64>(2)? as u32)))
+    })?;
 
-    Ok(DashboardAggregates {
-        total_sessions,
-        user_sessions,
-        subagent_sessions,
-        total_cost,
-        total_turns,
-        total_tokens,
-        active_projects,
-        avg_efficiency,
-    })
+    Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
 }
 
-/// Daily metrics aggregate grouped by date.
+/// Replace all stored turns (with token metrics and tool uses) for a session
+///
+/// Records `file_mtime` as the session's turns_mtime so callers can tell whether
+/// the stored turns are current. Turns reference the sessions table, so nothing
+/// is stored when the session row doesn't exist yet; returns whether turns were stored.
+pub fn replace_session_turns(
+    conn: &Connection,
+    session_id: &str,
+    turns: &[CompletedTurn],
+    file_mtime: &str,
+) -> Result<bool, DbError> {
+    if !session_exists(conn, session_id)? {
+        return Ok(false);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+
+    // turn_metrics, tool_uses and code_changes cascade from turns
+    tx.execute("DELETE FROM turns WHERE session_id = ?1", params![session_id])?;
+
+    {
+        let mut turn_stmt = tx.prepare(
+            r#"
+            INSERT INTO turns (
+                turn_id, session_id, turn_number, started_at, ended_at, duration_ms,
+                user_message, assistant_message, model, stop_reason,
+                start_uuid, end_uuid, subagent_ids, entry_count, compactions, provider, api_errors,
+                thinking_level
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+            "#,
+        )?;
+        let mut metrics_stmt = tx.prepare(
+            r#"
+            INSERT INTO turn_metrics (
+                turn_id, input_tokens, output_tokens, cache_read_tokens,
+                cache_write_5m_tokens, cache_write_1h_tokens, total_cost,
+                context_usage_pct, tool_count, thinking_tokens
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+        )?;
+        let mut tool_stmt = tx.prepare(
+            r#"
+            INSERT OR REPLACE INTO tool_uses (tool_use_id, turn_id, tool_name, input_json, result, is_error)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )?;
+        let mut change_stmt = tx.prepare(
+            r#"
+            INSERT OR REPLACE INTO code_changes (change_id, turn_id, file_path, change_type, tool_name)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )?;
+
+        for turn in turns {
+            let turn_id = format!("{}:{}", session_id, turn.turn_number);
+            let subagent_ids = serde_json::to_string(&turn.subagent_ids).unwrap_or_default();
+            let compactions = if turn.compactions.is_empty() {
+                None
+            } else {
+                serde_json::to_string(&turn.compactions).ok()
+            };
+            let api_errors = if turn.api_errors.is_empty() {
+                None
+            } else {
+                serde_json::to_string(&turn.api_errors).ok()
+            };
+
+            turn_stmt.execute(params![
+                turn_id,
+                session_id,
+                turn.turn_number,
+                turn.started_at,
+                turn.ended_at,
+                turn.duration_ms,
+                crypto::seal(turn.user_message.as_deref())?,
+                crypto::seal(turn.assistant_message.as_deref())?,
+                turn.model,
+                turn.stop_reason,
+                turn.start_uuid,
+                turn.end_uuid,
+                subagent_ids,
+                turn.entry_count,
+                compactions,
+                turn.provider.as_str(),
+                api_errors,
+                turn.thinking_level,
+            ])?;
+
+            let tokens = TurnTokens::new(
+                turn.input_tokens,
+                turn.output_tokens,
+                turn.cache_read_tokens,
+                turn.cache_write_5m_tokens,
+                turn.cache_write_1h_tokens,
+            );
+            let model = turn.model.as_deref();
+            let cost = calculate_provider_turn_cost(&tokens, model, turn.provider, Some(&turn.started_at));
+            const MAX_CONTEXT: f64 = 200_000.0;
+            let context_usage_pct = (turn.total_context as f64 / MAX_CONTEXT * 100.0).min(100.0);
+
+            metrics_stmt.execute(params![
+                turn_id,
+                turn.input_tokens as i64,
+                turn.output_tokens as i64,
+                turn.cache_read_tokens as i64,
+                turn.cache_write_5m_tokens as i64,
+                turn.cache_write_1h_tokens as i64,
+                cost.total_cost,
+                context_usage_pct,
+                turn.tool_count,
+                turn.thinking_tokens as i64,
+            ])?;
+
+            for tool in &turn.tool_uses {
+                let input_json = tool.input.as_ref().map(|v| v.to_string());
+                tool_stmt.execute(params![
+                    tool.id,
+                    turn_id,
+                    tool.name,
+                    crypto::seal(input_json.as_deref())?,
+                    crypto::seal(tool.result.as_deref())?,
+                    if tool.is_error { 1 } else { 0 },
+                ])?;
+                if let Some(change) = code_change(turn, tool) {
+                    change_stmt.execute(params![
+                        tool.id,
+                        turn_id,
+                        change.file_path,
+                        change.change_type,
+                        change.tool_name,
+                    ])?;
+                }
+            }
+        }
+    }
+
+    tx.execute(
+        "UPDATE sessions SET turns_mtime = ?1 WHERE session_id = ?2",
+        params![file_mtime, session_id],
+    )?;
+    tx.commit()?;
+
+    Ok(true)
+}
+
+/// Load the stored turns for a session, in turn order
+pub fn get_session_turns_from_db(conn: &Connection, session_id: &str) -> Result<Vec<CompletedTurn>, DbError> {
+    let mut tool_stmt = conn.prepare(
+        r#"
+        SELECT u.turn_id, u.tool_use_id, u.tool_name, u.input_json, u.result, u.is_error
+        FROM tool_uses u
+        JOIN turns t ON u.turn_id = t.turn_id
+        WHERE t.session_id = ?1
+        ORDER BY u.rowid ASC
+        "#,
+    )?;
+
+    let mut tools_by_turn: HashMap<String, Vec<ToolUse>> = HashMap::new();
+    let rows = tool_stmt.query_map(params![session_id], |row| {
+        let input_json = crypto::open(row.get(3)?);
+        Ok((
+            row.get::<_, String>(0)?,
+            ToolUse {
+                id: row.get(1)?,
+                name: canonical_tool_name(&row.get::<_, String>(2)?),
+                input: input_json.and_then(|s| serde_json::from_str(&s).ok()),
+                result: crypto::open(row.get(4)?),
+                is_error: row.get::<_, i32>(5)? == 1,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (turn_id, tool) = row?;
+        tools_by_turn.entry(turn_id).or_default().push(tool);
+    }
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            t.turn_id,
+            t.turn_number,
+            t.started_at,
+            t.ended_at,
+            t.duration_ms,
+            t.user_message,
+            t.assistant_message,
+            t.model,
+            t.stop_reason,
+            t.start_uuid,
+            t.end_uuid,
+            t.subagent_ids,
+            COALESCE(t.entry_count, 0),
+            COALESCE(m.input_tokens, 0),
+            COALESCE(m.output_tokens, 0),
+            COALESCE(m.cache_read_tokens, 0),
+            COALESCE(m.cache_write_5m_tokens, 0),
+            COALESCE(m.cache_write_1h_tokens, 0),
+            COALESCE(m.tool_count, 0),
+            t.compactions,
+            t.provider,
+            t.api_errors,
+            COALESCE(m.thinking_tokens, 0),
+            t.thinking_level
+        FROM turns t
+        LEFT JOIN turn_metrics m ON t.turn_id = m.turn_id
+        WHERE t.session_id = ?1
+        ORDER BY t.turn_number ASC
+        "#,
+    )?;
+
+    let turns = stmt
+        .query_map(params![session_id], |row| {
+            let turn_id: String = row.get(0)?;
+            let subagent_ids: Vec<String> = row
+                .get::<_, Option<String>>(11)?
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default();
+            let input_tokens = row.get::<_, i64>(13)? as u64;
+            let output_tokens = row.get::<_, i64>(14)? as u64;
+            let cache_read_tokens = row.get::<_, i64>(15)? as u64;
+            let cache_write_5m_tokens = row.get::<_, i64>(16)? as u64;
+            let cache_write_1h_tokens = row.get::<_, i64>(17)? as u64;
+            let model: Option<String> = row.get(7)?;
+            let provider = stored_provider(row.get(20)?, model.as_deref());
+
+            Ok(CompletedTurn {
+                turn_number: row.get(1)?,
+                started_at: row.get(2)?,
+                ended_at: row.get(3)?,
+                duration_ms: row.get(4)?,
+                user_message: crypto::open(row.get(5)?),
+                assistant_message: crypto::open(row.get(6)?),
+                model,
+                stop_reason: row.get(8)?,
+                provider,
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_write_5m_tokens,
+                cache_write_1h_tokens,
+                total_tokens: input_tokens + output_tokens + cache_read_tokens
+                    + cache_write_5m_tokens + cache_write_1h_tokens,
+                total_context: input_tokens + cache_read_tokens
+                    + cache_write_5m_tokens + cache_write_1h_tokens,
+                tool_uses: tools_by_turn.remove(&turn_id).unwrap_or_default(),
+                tool_count: row.get(18)?,
+                has_subagents: !subagent_ids.is_empty(),
+                subagent_ids,
+                start_uuid: row.get(9)?,
+                end_uuid: row.get(10)?,
+                entry_count: row.get(12)?,
+                compactions: row
+                    .get::<_, Option<String>>(19)?
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default(),
+                api_errors: row
+                    .get::<_, Option<String>>(21)?
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default(),
+                thinking_tokens: row.get::<_, i64>(22)? as u64,
+                thinking_level: row.get(23)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(turns)
+}
+
+/// Provider stored with a turn; rows from before it was recorded fall back
+/// to detecting it from the model
+fn stored_provider(provider: Option<String>, model: Option<&str>) -> CostProvider {
+    provider
+        .as_deref()
+        .and_then(CostProvider::parse)
+        .or_else(|| model.map(CostProvider::from_model))
+        .unwrap_or_default()
+}
+
+/// Get user-defined pricing overrides from the pricing table
+pub fn get_custom_pricing(conn: &Connection) -> Result<Vec<ModelPricing>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            model_id,
+            display_name,
+            input_price_per_million,
+            output_price_per_million,
+            cache_write_5m_per_million,
+            cache_write_1h_per_million,
+            cache_read_per_million,
+            max_context_tokens,
+            provider,
+            effective_from
+        FROM pricing
+        WHERE is_custom = 1
+        ORDER BY model_id ASC, provider ASC, effective_from ASC
+        "#,
+    )?;
+
+    let pricing = stmt
+        .query_map([], |row| {
+            Ok(ModelPricing {
+                model_id: row.get(0)?,
+                display_name: row.get(1)?,
+                input_price_per_million: row.get(2)?,
+                output_price_per_million: row.get(3)?,
+                cache_write_5m_per_million: row.get(4)?,
+                cache_write_1h_per_million: row.get(5)?,
+                cache_read_per_million: row.get(6)?,
+                max_context_tokens: row.get(7)?,
+                provider: CostProvider::parse(&row.get::<_, String>(8)?).unwrap_or_default(),
+                effective_from: Some(row.get::<_, String>(9)?).filter(|date| !date.is_empty()),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(pricing)
+}
+
+/// Insert or replace a user-defined pricing override
+///
+/// Entries are keyed by model, provider and start date; an undated entry is
+/// stored with an empty `effective_from`.
+pub fn upsert_custom_pricing(conn: &Connection, pricing: &ModelPricing) -> Result<(), DbError> {
+    conn.execute(
+        r#"
+        INSERT INTO pricing (
+            model_id, display_name, input_price_per_million, output_price_per_million,
+            cache_write_5m_per_million, cache_write_1h_per_million, cache_read_per_million,
+            max_context_tokens, provider, effective_from, updated_at, is_custom
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, CURRENT_TIMESTAMP, 1)
+        ON CONFLICT(model_id, provider, effective_from) DO UPDATE SET
+            display_name = excluded.display_name,
+            input_price_per_million = excluded.input_price_per_million,
+            output_price_per_million = excluded.output_price_per_million,
+            cache_write_5m_per_million = excluded.cache_write_5m_per_million,
+            cache_write_1h_per_million = excluded.cache_write_1h_per_million,
+            cache_read_per_million = excluded.cache_read_per_million,
+            max_context_tokens = excluded.max_context_tokens,
+            updated_at = CURRENT_TIMESTAMP,
+            is_custom = 1
+        "#,
+        params![
+            pricing.model_id,
+            pricing.display_name,
+            pricing.input_price_per_million,
+            pricing.output_price_per_million,
+            pricing.cache_write_5m_per_million,
+            pricing.cache_write_1h_per_million,
+            pricing.cache_read_per_million,
+            pricing.max_context_tokens,
+            pricing.provider.as_str(),
+            pricing.effective_from.as_deref().unwrap_or_default(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Remove a user-defined pricing override, the undated one if
+/// `effective_from` is None
+/// Returns true if an override existed
+pub fn delete_custom_pricing(
+    conn: &Connection,
+    model_id: &str,
+    provider: CostProvider,
+    effective_from: Option<&str>,
+) -> Result<bool, DbError> {
+    let deleted = conn.execute(
+        "DELETE FROM pricing WHERE model_id = ?1 AND provider = ?2 AND effective_from = ?3 AND is_custom = 1",
+        params![model_id, provider.as_str(), effective_from.unwrap_or_default()],
+    )?;
+    Ok(deleted > 0)
+}
+
+/// Recalculate cached session and turn costs with the currently active pricing
+///
+/// Every stored turn is repriced with its own model, provider, cache write
+/// split and date, and a session's total becomes the sum of its turns, so
+/// the two always agree, also for sessions that switched models or cross
+/// the start of a new rate. Sessions without stored turns only have token
+/// totals to go by: they're priced with their primary model at the rates of
+/// the day they started, cache writes at the 5-minute rate, and the provider
+/// of their first turn.
+/// Returns the number of sessions updated.
+pub fn recalculate_session_costs(conn: &Connection) -> Result<u32, DbError> {
+    let tx = conn.unchecked_transaction()?;
+
+    let mut session_costs: HashMap<String, f64> = HashMap::new();
+    {
+        let mut turn_stmt = tx.prepare(
+            r#"
+            SELECT m.turn_id, t.session_id, t.model, m.input_tokens, m.output_tokens, m.cache_read_tokens,
+                   m.cache_write_5m_tokens, m.cache_write_1h_tokens, t.provider, t.started_at
+            FROM turn_metrics m
+            JOIN turns t ON t.turn_id = m.turn_id
+            "#,
+        )?;
+        let turn_rows = turn_stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    TurnTokens::new(
+                        row.get::<_, i64>(3)? as u64,
+                        row.get::<_, i64>(4)? as u64,
+                        row.get::<_, i64>(5)? as u64,
+                        row.get::<_, i64>(6)? as u64,
+                        row.get::<_, i64>(7)? as u64,
+                    ),
+                    row.get::<_, Option<String>>(8)?,
+                    row.get::<_, Option<String>>(9)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut turn_update_stmt = tx.prepare("UPDATE turn_metrics SET total_cost = ?1 WHERE turn_id = ?2")?;
+        for (turn_id, session_id, model, tokens, provider, started_at) in turn_rows {
+            let provider = stored_provider(provider, model.as_deref());
+            let model = model.as_deref();
+            let cost = calculate_provider_turn_cost(&tokens, model, provider, started_at.as_deref()).total_cost;
+            turn_update_stmt.execute(params![cost, turn_id])?;
+            *session_costs.entry(session_id).or_default() += cost;
+        }
+    }
+
+    let mut updated = 0u32;
+    {
+        let mut stmt = tx.prepare(
+            r#"
+            SELECT
+                m.session_id,
+                s.model,
+                m.total_turns,
+                m.total_input_tokens,
+                m.total_output_tokens,
+                m.total_cache_read,
+                m.total_cache_write,
+                (
+                    SELECT t.provider FROM turns t
+                    WHERE t.session_id = m.session_id AND t.provider IS NOT NULL
+                    ORDER BY t.turn_number ASC
+                    LIMIT 1
+                ),
+                s.started_at
+            FROM session_metrics m
+            JOIN sessions s ON s.session_id = m.session_id
+            "#,
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u32>(2)?,
+                    TurnTokens::new(
+                        row.get::<_, i64>(3)? as u64,
+                        row.get::<_, i64>(4)? as u64,
+                        row.get::<_, i64>(5)? as u64,
+                        row.get::<_, i64>(6)? as u64,
+                        0,
+                    ),
+                    row.get::<_, Option<String>>(7)?,
+                    row.get::<_, Option<String>>(8)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut update_stmt = tx.prepare(
+            r#"
+            UPDATE session_metrics
+            SET total_cost = ?1, avg_cost_per_turn = ?2, updated_at = CURRENT_TIMESTAMP
+            WHERE session_id = ?3
+            "#,
+        )?;
+        for (session_id, model, total_turns, tokens, provider, started_at) in rows {
+            let total_cost = match session_costs.get(&session_id) {
+                Some(cost) => *cost,
+                None => {
+                    let provider = stored_provider(provider, Some(&model));
+                    calculate_provider_turn_cost(&tokens, Some(&model), provider, started_at.as_deref()).total_cost
+                }
+            };
+            let avg_cost_per_turn = if total_turns > 0 {
+                total_cost / total_turns as f64
+            } else {
+                0.0
+            };
+            updated += update_stmt.execute(params![total_cost, avg_cost_per_turn, session_id])? as u32;
+        }
+    }
+    tx.commit()?;
+
+    Ok(updated)
+}
+
+/// Get all import sources as (path, label) pairs, oldest first
+pub fn get_import_sources(conn: &Connection) -> Result<Vec<(String, String)>, DbError> {
+    let mut stmt = conn.prepare("SELECT path, label FROM import_sources ORDER BY imported_at, path")?;
+    let sources = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(sources)
+}
+
+/// Insert an import source or relabel an existing one
+pub fn upsert_import_source(conn: &Connection, path: &str, label: &str) -> Result<(), DbError> {
+    conn.execute(
+        r#"
+        INSERT INTO import_sources (path, label, imported_at)
+        VALUES (?1, ?2, CURRENT_TIMESTAMP)
+        ON CONFLICT(path) DO UPDATE SET label = excluded.label
+        "#,
+        params![path, label],
+    )?;
+    Ok(())
+}
+
+/// Get the stored project path resolutions
+pub fn get_project_registry(conn: &Connection) -> Result<Vec<ProjectResolution>, DbError> {
+    let mut stmt = conn.prepare("SELECT encoded_dir, project_path, source, ambiguous FROM project_registry")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, bool>(3)?,
+        ))
+    })?;
+
+    let mut resolutions = Vec::new();
+    for row in rows {
+        let (encoded_dir, project_path, source, ambiguous) = row?;
+        // Skip sources written by a newer version
+        if let Some(source) = ResolutionSource::parse(&source) {
+            resolutions.push(ProjectResolution {
+                encoded_dir,
+                project_path,
+                source,
+                ambiguous,
+            });
+        }
+    }
+    Ok(resolutions)
+}
+
+/// Store a project path resolution, replacing the directory's previous one
+pub fn upsert_project_resolution(conn: &Connection, resolution: &ProjectResolution) -> Result<(), DbError> {
+    conn.execute(
+        r#"
+        INSERT INTO project_registry (encoded_dir, project_path, source, ambiguous, resolved_at)
+        VALUES (?1, ?2, ?3, ?4, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        ON CONFLICT(encoded_dir) DO UPDATE SET
+            project_path = excluded.project_path,
+            source = excluded.source,
+            ambiguous = excluded.ambiguous,
+            resolved_at = excluded.resolved_at
+        "#,
+        params![
+            resolution.encoded_dir,
+            resolution.project_path,
+            resolution.source.as_str(),
+            resolution.ambiguous,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Delete a directory's stored resolution
+pub fn delete_project_resolution(conn: &Connection, encoded_dir: &str) -> Result<(), DbError> {
+    conn.execute("DELETE FROM project_registry WHERE encoded_dir = ?1", params![encoded_dir])?;
+    Ok(())
+}
+
+/// Move the stored sessions of a project directory to another project path
+///
+/// Returns the number of sessions moved.
+pub fn move_project_dir_sessions(
+    conn: &Connection,
+    encoded_dir: &str,
+    project_path: &str,
+    project_name: &str,
+) -> Result<usize, DbError> {
+    let moved = conn.execute(
+        r#"
+        UPDATE sessions SET project_path = ?2, project_name = ?3, updated_at = CURRENT_TIMESTAMP
+        WHERE instr(replace(file_path, '\', '/'), '/' || ?1 || '/') > 0
+          AND project_path != ?2
+        "#,
+        params![encoded_dir, project_path, project_name],
+    )?;
+    Ok(moved)
+}
+
+/// Get all stored settings as key/value pairs
+pub fn get_settings_entries(conn: &Connection) -> Result<HashMap<String, String>, DbError> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+    let entries = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(entries)
+}
+
+/// Insert or replace settings in a single transaction
+pub fn upsert_settings_entries(conn: &Connection, entries: &[(String, String)]) -> Result<(), DbError> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            r#"
+            INSERT INTO settings (key, value, updated_at)
+            VALUES (?1, ?2, CURRENT_TIMESTAMP)
+            ON CONFLICT(key) DO UPDATE SET
+                value = excluded.value,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )?;
+        for (key, value) in entries {
+            stmt.execute(params![key, value])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Counts sessions as-is, or only top-level sessions when subagents are rolled
+/// up into their parents
+fn session_count_sql(attribute_subagents: bool) -> &'static str {
+    if attribute_subagents {
+        "COUNT(CASE WHEN s.parent_session_id IS NULL AND s.file_path NOT LIKE '%/subagents/%' AND s.file_path NOT LIKE '%/agent-%' THEN 1 END)"
+    } else {
+        "COUNT(*)"
+    }
+}
+
+/// Dashboard aggregate: total_sessions, total_cost, total_turns, total_tokens, active_projects
+/// Filters to real user projects (per the configured project patterns) and sessions with turns > 0.
 /// When `days` is Some, only includes sessions from the last N days.
-/// When `days` is None, includes all sessions (no date filter).
-/// Uses substr() for date comparisons to handle RFC3339 timestamps safely,
-/// and guards against non-date values (e.g. 'unknown') with a LIKE '20%' check.
-pub fn get_daily_metrics_from_db(conn: &Connection, days: Option<u32>) -> Result<Vec<DailyAggregates>, DbError> {
+/// When `attribute_subagents` is set, total_sessions leaves out subagent sessions,
+/// whose cost is then carried by the sessions that spawned them.
+/// Dates are compared in the reporting timezone via local_date(), and
+/// non-date values (e.g. 'unknown') are guarded against with a LIKE '20%' check.
+pub fn get_dashboard_summary_from_db(
+    conn: &Connection,
+    days: Option<u32>,
+    attribute_subagents: bool,
+) -> Result<DashboardAggregates, DbError> {
     let date_filter = if days.is_some() {
-        "AND substr(s.started_at, 1, 10) >= date('now', '-' || ?1 || ' days')"
+        "AND s.started_at LIKE '20%' AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')"
     } else {
         ""
     };
+    let session_count = session_count_sql(attribute_subagents);
 
     let sql = format!(
         r#"
         SELECT
-            substr(s.started_at, 1, 10) as day,
-            COUNT(*) as session_count,
-            COUNT(CASE WHEN s.file_path NOT LIKE '%/subagents/%' AND s.file_path NOT LIKE '%/agent-%' THEN 1 END) as user_session_count,
-            COUNT(CASE WHEN s.file_path LIKE '%/subagents/%' OR s.file_path LIKE '%/agent-%' THEN 1 END) as subagent_session_count,
-            COALESCE(SUM(m.total_turns), 0) as total_turns,
+            {session_count} as total_sessions,
+            COUNT(CASE WHEN s.file_path NOT LIKE '%/subagents/%' AND s.file_path NOT LIKE '%/agent-%' THEN 1 END) as user_sessions,
+            COUNT(CASE WHEN s.file_path LIKE '%/subagents/%' OR s.file_path LIKE '%/agent-%' THEN 1 END) as subagent_sessions,
             COALESCE(SUM(m.total_cost), 0.0) as total_cost,
+            COALESCE(SUM(m.total_turns), 0) as total_turns,
             COALESCE(SUM(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write), 0) as total_tokens,
-            CASE WHEN SUM(m.total_cache_read) + SUM(m.total_cache_write) > 0
-            THEN CAST(SUM(m.total_cache_read) AS REAL) / (SUM(m.total_cache_read) + SUM(m.total_cache_write))
-            ELSE NULL END as avg_efficiency
+            COUNT(DISTINCT s.project_path) as active_projects
         FROM sessions s
         LEFT JOIN session_metrics m ON s.session_id = m.session_id
-        WHERE s.project_path LIKE '/Users/%'
+        WHERE is_real_user_project(s.project_path)
           AND COALESCE(m.total_turns, 0) > 0
-          AND s.started_at LIKE '20%'
           {date_filter}
-        GROUP BY substr(s.started_at, 1, 10)
-        ORDER BY day DESC
         "#,
     );
 
     let mut stmt = conn.prepare(&sql)?;
 
-    let result = if let Some(d) = days {
-        let rows = stmt.query_map(params![d], |row| {
-            Ok(DailyAggregates {
-                date: row.get(0)?,
-                session_count: row.get::<_, i32>(1)? as u32,
-                user_session_count: row.get::<_, i32>(2)? as u32,
-                subagent_session_count: row.get::<_, i32>(3)? as u32,
-                total_turns: row.get::<_, i32>(4)? as u32,
-                total_cost: row.get::<_, f64>(5)?,
-                total_tokens: row.get::<_, i64>(6)? as u64,
-                avg_efficiency: row.get::<_, Option<f64>>(7)?,
-            })
-        })?;
-        rows.collect::<Result<Vec<_>, _>>()?
+    let (total_sessions, user_sessions, subagent_sessions, total_cost, total_turns, total_tokens, active_projects) = if let Some(d) = days {
+        stmt.query_row(params![d], |row| {
+            Ok((
+                row.get::<_, i32>(0)? as u32,
+                row.get::<_, i32>(1)? as u32,
+                row.get::<_, i32>(2)? as u32,
+                row.get::<_, f64>(3)?,
+                row.get::<_, i32>(4)? as u32,
+                row.get::<_, i64>(5)? as u64,
+                row.get::<_, i32>(6)? as u32,
+            ))
+        })?
     } else {
-        let rows = stmt.query_map([], |row| {
-            Ok(DailyAggregates {
-                date: row.get(0)?,
-                session_count: row.get::<_, i32>(1)? as u32,
-                user_session_count: row.get::<_, i32>(2)? as u32,
-                subagent_session_count: row.get::<_, i32>(3)? as u32,
-                total_turns: row.get::<_, i32>(4)? as u32,
-                total_cost: row.get::<_, f64>(5)?,
-                total_tokens: row.get::<_, i64>(6)? as u64,
-                avg_efficiency: row.get::<_, Option<f64>>(7)?,
-            })
-        })?;
-        rows.collect::<Result<Vec<_>, _>>()?
+        stmt.query_row([], |row| {
+            Ok((
+                row.get::<_, i32>(0)? as u32,
+                row.get::<_, i32>(1)? as u32,
+                row.get::<_, i32>(2)? as u32,
+                row.get::<_, f64>(3)?,
+                row.get::<_, i32>(4)? as u32,
+                row.get::<_, i64>(5)? as u64,
+                row.get::<_, i32>(6)? as u32,
+            ))
+        })?
     };
 
-    Ok(result)
+    // Compute global CER = SUM(cache_read) / (SUM(cache_read) + SUM(cache_write))
+    // Using global totals instead of averaging per-session CERs avoids skew from
+    // many small sessions (e.g. subagents) with tiny or zero cache values.
+    let eff_sql = format!(
+        r#"
+        SELECT
+            CASE WHEN SUM(m.total_cache_read) + SUM(m.total_cache_write) > 0
+            THEN CAST(SUM(m.total_cache_read) AS REAL) / (SUM(m.total_cache_read) + SUM(m.total_cache_write))
+            ELSE NULL END
+        FROM sessions s
+        JOIN session_metrics m ON s.session_id = m.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND m.total_turns > 0
+          {date_filter}
+        "#,
+    );
+
+    let avg_efficiency: Option<f64> = if let Some(d) = days {
+        conn.query_row(&eff_sql, params![d], |row| row.get::<_, Option<f64>>(0))?
+    } else {
+        conn.query_row(&eff_sql, [], |row| row.get::<_, Option<f64>>(0))?
+    };
+
+    Ok(DashboardAggregates {
+        total_sessions,
+        user_sessions,
+        subagent_sessions,
+        total_cost,
+        total_turns,
+        total_tokens,
+        active_projects,
+        avg_efficiency,
+    })
+}
+
+/// Daily metrics aggregate grouped by date.
+/// When `days` is Some, only includes sessions from the last N days.
+/// When `days` is None, includes all sessions (no date filter).
+/// When `projects` is Some, only includes sessions of those project paths.
+/// `segment` limits the sessions to user or subagent ones.
+/// Read from the daily rollups (see `db::rollups`), whose days are already
+/// reporting-timezone dates.
+pub fn get_daily_metrics_from_db(
+    conn: &Connection,
+    days: Option<u32>,
+    projects: Option<&[String]>,
+    segment: Segment,
+) -> Result<Vec<DailyAggregates>, DbError> {
+    let projects = projects.map(|p| serde_json::to_string(p).unwrap_or_default());
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            day,
+            SUM(session_count),
+            SUM(user_session_count),
+            SUM(subagent_session_count),
+            SUM(total_turns),
+            SUM(total_cost),
+            SUM(total_tokens),
+            CASE WHEN SUM(cache_read) + SUM(cache_write) > 0
+            THEN CAST(SUM(cache_read) AS REAL) / (SUM(cache_read) + SUM(cache_write))
+            ELSE NULL END as avg_efficiency
+        FROM daily_rollups
+        WHERE is_real_user_project(project_path)
+          AND (?1 IS NULL OR day >= date(local_date('now'), '-' || ?1 || ' days'))
+          AND (?2 IS NULL OR project_path IN (SELECT value FROM json_each(?2)))
+          AND (?3 IS NULL OR subagent = ?3)
+        GROUP BY day
+        ORDER BY day DESC
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![days, projects, segment.subagent()], |row| {
+        Ok(DailyAggregates {
+            date: row.get(0)?,
+            session_count: row.get::<_, i32>(1)? as u32,
+            user_session_count: row.get::<_, i32>(2)? as u32,
+            subagent_session_count: row.get::<_, i32>(3)? as u32,
+            total_turns: row.get::<_, i32>(4)? as u32,
+            total_cost: row.get::<_, f64>(5)?,
+            total_tokens: row.get::<_, i64>(6)? as u64,
+            avg_efficiency: row.get::<_, Option<f64>>(7)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Get the project with the highest cost on `day`, from the daily rollups
+pub fn get_top_project_on_from_db(conn: &Connection, day: &str) -> Result<Option<DigestProject>, DbError> {
+    Ok(conn
+        .query_row(
+            r#"
+            SELECT project_path, SUM(session_count), SUM(total_cost) as cost
+            FROM daily_rollups
+            WHERE day = ?1 AND is_real_user_project(project_path)
+            GROUP BY project_path
+            HAVING cost > 0
+            ORDER BY cost DESC, project_path ASC
+            LIMIT 1
+            "#,
+            params![day],
+            |row| {
+                let project_path: String = row.get(0)?;
+                Ok(DigestProject {
+                    project_name: project_path.rsplit('/').next().unwrap_or(&project_path).to_string(),
+                    project_path,
+                    sessions: row.get(1)?,
+                    cost: row.get(2)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Get the session with the highest cost among those started on `day`
+///
+/// Subagent sessions are left out; their cost shows up in the project totals.
+pub fn get_most_expensive_session_on_from_db(conn: &Connection, day: &str) -> Result<Option<DigestSession>, DbError> {
+    Ok(conn
+        .query_row(
+            r#"
+            SELECT s.session_id, s.project_path, m.total_turns, m.total_cost
+            FROM sessions s
+            JOIN session_metrics m ON m.session_id = s.session_id
+            WHERE is_real_user_project(s.project_path)
+              AND s.parent_session_id IS NULL
+              AND s.file_path NOT LIKE '%/subagents/%' AND s.file_path NOT LIKE '%/agent-%'
+              AND s.started_at LIKE '20%' AND local_date(s.started_at) = ?1
+              AND m.total_cost > 0
+            ORDER BY m.total_cost DESC, s.session_id ASC
+            LIMIT 1
+            "#,
+            params![day],
+            |row| {
+                Ok(DigestSession {
+                    session_id: row.get(0)?,
+                    project_path: row.get(1)?,
+                    turns: row.get(2)?,
+                    cost: row.get(3)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Get the sessions started within a date range (inclusive YYYY-MM-DD bounds,
+/// reporting timezone), costliest first
+///
+/// Subagent sessions are left out, as for the digest.
+pub fn get_sessions_in_range_by_cost(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<ReportSession>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT s.session_id, s.project_path, s.started_at, m.total_turns, m.total_cost
+        FROM sessions s
+        JOIN session_metrics m ON m.session_id = s.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND s.parent_session_id IS NULL
+          AND s.file_path NOT LIKE '%/subagents/%' AND s.file_path NOT LIKE '%/agent-%'
+          AND s.started_at LIKE '20%'
+          AND local_date(s.started_at) >= ?1
+          AND local_date(s.started_at) <= ?2
+        ORDER BY m.total_cost DESC, s.session_id ASC
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        let project_path: String = row.get(1)?;
+        Ok(ReportSession {
+            session_id: row.get(0)?,
+            project_name: project_path.rsplit('/').next().unwrap_or(&project_path).to_string(),
+            project_path,
+            started_at: row.get(2)?,
+            turns: row.get(3)?,
+            cost: row.get(4)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
 }
 
 /// Project metrics aggregate grouped by project_path.
 /// When `days` is Some, only includes sessions from the last N days.
-/// Uses substr() for date comparisons to handle RFC3339 timestamps safely,
-/// and guards against non-date values (e.g. 'unknown') with a LIKE '20%' check.
-pub fn get_project_metrics_from_db(conn: &Connection, days: Option<u32>) -> Result<Vec<ProjectAggregates>, DbError> {
+/// When `attribute_subagents` is set, subagent sessions count toward the project
+/// of the session that spawned them and aren't counted as sessions themselves.
+/// Dates are compared in the reporting timezone via local_date(), and
+/// non-date values (e.g. 'unknown') are guarded against with a LIKE '20%' check.
+pub fn get_project_metrics_from_db(
+    conn: &Connection,
+    days: Option<u32>,
+    attribute_subagents: bool,
+) -> Result<Vec<ProjectAggregates>, DbError> {
     let date_filter = if days.is_some() {
-        "AND s.started_at LIKE '20%' AND substr(s.started_at, 1, 10) >= date('now', '-' || ?1 || ' days')"
+        "AND s.started_at LIKE '20%' AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')"
     } else {
         ""
     };
+    let session_count = session_count_sql(attribute_subagents);
+    let project_path = if attribute_subagents {
+        "COALESCE(p.project_path, s.project_path)"
+    } else {
+        "s.project_path"
+    };
 
     let sql = format!(
         r#"
         SELECT
-            s.project_path,
-            COUNT(*) as session_count,
+            {project_path} as project,
+            {session_count} as session_count,
             COALESCE(SUM(m.total_cost), 0.0) as total_cost,
             COALESCE(SUM(m.total_turns), 0) as total_turns,
             COALESCE(SUM(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write), 0) as total_tokens,
             MAX(s.last_activity_at) as last_activity
         FROM sessions s
         LEFT JOIN session_metrics m ON s.session_id = m.session_id
-        WHERE s.project_path LIKE '/Users/%'
+        LEFT JOIN sessions p ON p.session_id = s.parent_session_id
+        WHERE is_real_user_project({project_path})
           AND COALESCE(m.total_turns, 0) > 0
           {date_filter}
-        GROUP BY s.project_path
+        GROUP BY project
         "#,
     );
 
@@ -1123,3 +2373,1657 @@ pub fn get_project_metrics_from_db(conn: &Connection, days: Option<u32>) -> Resu
 
     Ok(rows)
 }
+
+/// Aggregate stored turns by local day of week and hour of day
+///
+/// Dates are inclusive YYYY-MM-DD bounds on the turn start; dates, days and
+/// hours are all in the reporting timezone. Only covers sessions whose turns
+/// are stored; see `count_sessions_without_turns`.
+pub fn get_hourly_usage_from_db(
+    conn: &Connection,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<HourlyAggregates>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            (CAST(strftime('%w', local_datetime(t.started_at)) AS INTEGER) + 6) % 7 as day_of_week,
+            CAST(strftime('%H', local_datetime(t.started_at)) AS INTEGER) as hour,
+            COUNT(*) as turns,
+            COUNT(DISTINCT t.session_id) as sessions,
+            COALESCE(SUM(m.input_tokens + m.output_tokens + m.cache_read_tokens
+                + m.cache_write_5m_tokens + m.cache_write_1h_tokens), 0) as total_tokens,
+            COALESCE(SUM(m.total_cost), 0.0) as total_cost
+        FROM turns t
+        JOIN sessions s ON s.session_id = t.session_id
+        LEFT JOIN turn_metrics m ON m.turn_id = t.turn_id
+        WHERE is_real_user_project(s.project_path)
+          AND local_datetime(t.started_at) IS NOT NULL
+          AND (?1 IS NULL OR local_date(t.started_at) >= ?1)
+          AND (?2 IS NULL OR local_date(t.started_at) <= ?2)
+        GROUP BY day_of_week, hour
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok(HourlyAggregates {
+            day_of_week: row.get::<_, i64>(0)? as u32,
+            hour: row.get::<_, i64>(1)? as u32,
+            turns: row.get::<_, i64>(2)? as u32,
+            sessions: row.get::<_, i64>(3)? as u32,
+            total_tokens: row.get::<_, i64>(4)? as u64,
+            total_cost: row.get(5)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Start, end and cost of one stored turn
+#[derive(Debug, Clone)]
+pub struct TurnIntervalRow {
+    pub session_id: String,
+    pub project_path: String,
+    pub project_name: String,
+    pub is_subagent: bool,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub cost: f64,
+}
+
+/// Stored turns of user projects, oldest first, for concurrency detection
+/// and burn rates
+///
+/// Includes subagent sessions. With `days`, only turns of sessions started in
+/// the last N days (reporting timezone) are returned, as in the dashboard.
+pub fn get_turn_intervals_from_db(
+    conn: &Connection,
+    days: Option<u32>,
+) -> Result<Vec<TurnIntervalRow>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            t.session_id,
+            s.project_path,
+            s.project_name,
+            s.file_path LIKE '%/subagents/%' OR s.file_path LIKE '%/agent-%' as is_subagent,
+            t.started_at,
+            t.ended_at,
+            t.duration_ms,
+            COALESCE(m.total_cost, 0.0) as cost
+        FROM turns t
+        JOIN sessions s ON s.session_id = t.session_id
+        LEFT JOIN turn_metrics m ON m.turn_id = t.turn_id
+        WHERE is_real_user_project(s.project_path)
+          AND (?1 IS NULL OR (s.started_at LIKE '20%'
+               AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')))
+        ORDER BY t.started_at
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        Ok(TurnIntervalRow {
+            session_id: row.get(0)?,
+            project_path: row.get(1)?,
+            project_name: row.get(2)?,
+            is_subagent: row.get(3)?,
+            started_at: row.get(4)?,
+            ended_at: row.get(5)?,
+            duration_ms: row.get(6)?,
+            cost: row.get(7)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Duration, cost, turns and tokens of each main session of a real user
+/// project with at least one turn
+///
+/// Subagent sessions are left out so short helper runs don't skew the
+/// spread. With `days`, only sessions started in the last N days (reporting
+/// timezone) are returned, as in the dashboard.
+pub fn get_session_totals_from_db(
+    conn: &Connection,
+    days: Option<u32>,
+) -> Result<Vec<SessionTotals>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            COALESCE(m.total_duration_ms, 0),
+            COALESCE(m.total_cost, 0.0),
+            m.total_turns,
+            COALESCE(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write, 0)
+        FROM sessions s
+        JOIN session_metrics m ON s.session_id = m.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND m.total_turns > 0
+          AND s.file_path NOT LIKE '%/subagents/%' AND s.file_path NOT LIKE '%/agent-%'
+          AND (?1 IS NULL OR (s.started_at LIKE '20%'
+               AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')))
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        Ok(SessionTotals {
+            duration_ms: row.get(0)?,
+            cost: row.get(1)?,
+            turns: row.get(2)?,
+            tokens: row.get::<_, i64>(3)? as u64,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Session, start, token total and cost of one stored turn
+#[derive(Debug, Clone)]
+pub struct TurnUsageRow {
+    pub session_id: String,
+    pub started_at: String,
+    pub tokens: i64,
+    pub cost: f64,
+}
+
+/// Stored turns started at or after `since` (RFC 3339, UTC), for rolling
+/// usage windows
+///
+/// Every session counts, subagents and excluded projects included, since
+/// they all draw on the same plan limits.
+pub fn get_turn_usage_since(conn: &Connection, since: &str) -> Result<Vec<TurnUsageRow>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            t.session_id,
+            t.started_at,
+            COALESCE(m.input_tokens + m.output_tokens + m.cache_read_tokens
+                + m.cache_write_5m_tokens + m.cache_write_1h_tokens, 0) as tokens,
+            COALESCE(m.total_cost, 0.0) as cost
+        FROM turns t
+        LEFT JOIN turn_metrics m ON m.turn_id = t.turn_id
+        WHERE t.started_at >= ?1
+        ORDER BY t.started_at
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![since], |row| {
+        Ok(TurnUsageRow {
+            session_id: row.get(0)?,
+            started_at: row.get(1)?,
+            tokens: row.get(2)?,
+            cost: row.get(3)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Stored turns with API errors, oldest first
+///
+/// With `days`, only turns of sessions started in the last N days (reporting
+/// timezone) are returned. Every session counts, as for usage windows, since
+/// they share the same rate limits.
+pub fn get_turn_api_errors_from_db(
+    conn: &Connection,
+    days: Option<u32>,
+) -> Result<Vec<TurnApiErrors>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.session_id, s.project_name, t.started_at, t.api_errors
+        FROM turns t
+        JOIN sessions s ON s.session_id = t.session_id
+        WHERE t.api_errors IS NOT NULL
+          AND (?1 IS NULL OR (s.started_at LIKE '20%'
+               AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')))
+        ORDER BY t.started_at
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        Ok(TurnApiErrors {
+            session_id: row.get(0)?,
+            project_name: row.get(1)?,
+            started_at: row.get(2)?,
+            errors: row
+                .get::<_, Option<String>>(3)?
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Stored turns summed per day, model, provider and thinking mode
+///
+/// Limited to sessions started in the last `days` days when given.
+pub fn get_thinking_usage_from_db(conn: &Connection, days: Option<u32>) -> Result<Vec<ThinkingUsage>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            local_date(t.started_at) as day,
+            t.model,
+            t.provider,
+            t.thinking_level,
+            COUNT(*),
+            SUM(CASE WHEN COALESCE(m.thinking_tokens, 0) > 0 THEN 1 ELSE 0 END),
+            COALESCE(SUM(m.output_tokens), 0),
+            COALESCE(SUM(m.thinking_tokens), 0),
+            COALESCE(SUM(m.total_cost), 0.0)
+        FROM turns t
+        JOIN sessions s ON s.session_id = t.session_id
+        LEFT JOIN turn_metrics m ON m.turn_id = t.turn_id
+        WHERE t.started_at LIKE '20%'
+          AND (?1 IS NULL OR (s.started_at LIKE '20%'
+               AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')))
+        GROUP BY day, t.model, t.provider, t.thinking_level
+        ORDER BY day
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        let model: Option<String> = row.get(1)?;
+        let provider = stored_provider(row.get(2)?, model.as_deref());
+        Ok(ThinkingUsage {
+            date: row.get(0)?,
+            model,
+            provider,
+            thinking_level: row.get(3)?,
+            turns: row.get(4)?,
+            turns_with_thinking: row.get(5)?,
+            output_tokens: row.get::<_, i64>(6)? as u64,
+            thinking_tokens: row.get::<_, i64>(7)? as u64,
+            cost: row.get(8)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Per-turn cost and running totals for a session from its stored turns
+///
+/// Uses the turn costs persisted with the turns, so nothing is repriced.
+pub fn get_session_cost_curve_from_db(
+    conn: &Connection,
+    session_id: &str,
+) -> Result<Vec<TurnCostAggregates>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            t.turn_number,
+            t.started_at,
+            t.model,
+            COALESCE(m.total_cost, 0.0) as cost,
+            COALESCE(m.input_tokens + m.output_tokens + m.cache_read_tokens
+                + m.cache_write_5m_tokens + m.cache_write_1h_tokens, 0) as tokens,
+            SUM(COALESCE(m.total_cost, 0.0)) OVER (ORDER BY t.turn_number) as cumulative_cost,
+            SUM(COALESCE(m.input_tokens + m.output_tokens + m.cache_read_tokens
+                + m.cache_write_5m_tokens + m.cache_write_1h_tokens, 0)) OVER (ORDER BY t.turn_number) as cumulative_tokens
+        FROM turns t
+        LEFT JOIN turn_metrics m ON m.turn_id = t.turn_id
+        WHERE t.session_id = ?1
+        ORDER BY t.turn_number
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![session_id], |row| {
+        Ok(TurnCostAggregates {
+            turn_number: row.get(0)?,
+            started_at: row.get(1)?,
+            model: row.get(2)?,
+            cost: row.get(3)?,
+            tokens: row.get::<_, i64>(4)? as u64,
+            cumulative_cost: row.get(5)?,
+            cumulative_tokens: row.get::<_, i64>(6)? as u64,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Store a recommendation dismissal, replacing an earlier one for the same id
+pub fn upsert_recommendation_feedback(
+    conn: &Connection,
+    feedback: &RecommendationFeedback,
+) -> Result<(), DbError> {
+    conn.execute(
+        r#"
+        INSERT OR REPLACE INTO recommendations_feedback (
+            recommendation_id, rec_type, session_id, project_path, reason,
+            potential_savings, confidence, dismissed_at, snoozed_until
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        "#,
+        params![
+            feedback.id,
+            feedback.rec_type.key(),
+            feedback.session_id,
+            feedback.project_path,
+            feedback.reason,
+            feedback.potential_savings,
+            feedback.confidence,
+            feedback.dismissed_at,
+            feedback.snoozed_until,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Get all recommendation dismissals, most recent first
+///
+/// Rows with a recommendation type this version doesn't know are skipped.
+pub fn get_recommendation_feedback(conn: &Connection) -> Result<Vec<RecommendationFeedback>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT recommendation_id, rec_type, session_id, project_path, reason,
+               potential_savings, confidence, dismissed_at, snoozed_until
+        FROM recommendations_feedback
+        ORDER BY dismissed_at DESC
+        "#,
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        let rec_type = match RecommendationType::from_key(&row.get::<_, String>(1)?) {
+            Some(rec_type) => rec_type,
+            None => return Ok(None),
+        };
+        Ok(Some(RecommendationFeedback {
+            id: row.get(0)?,
+            rec_type,
+            session_id: row.get(2)?,
+            project_path: row.get(3)?,
+            reason: row.get(4)?,
+            potential_savings: row.get(5)?,
+            confidence: row.get(6)?,
+            dismissed_at: row.get(7)?,
+            snoozed_until: row.get(8)?,
+        }))
+    })?;
+
+    let mut feedback = Vec::new();
+    for row in rows {
+        feedback.extend(row?);
+    }
+    Ok(feedback)
+}
+
+/// Remove a recommendation dismissal. Returns whether there was one.
+pub fn delete_recommendation_feedback(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    let removed = conn.execute(
+        "DELETE FROM recommendations_feedback WHERE recommendation_id = ?1",
+        params![id],
+    )?;
+    Ok(removed > 0)
+}
+
+/// Store a turn annotation, replacing an earlier one for the same turn
+pub fn upsert_turn_annotation(conn: &Connection, annotation: &TurnAnnotation) -> Result<(), DbError> {
+    conn.execute(
+        r#"
+        INSERT OR REPLACE INTO turn_annotations (session_id, turn_number, label, note, annotated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+        params![
+            annotation.session_id,
+            annotation.turn_number,
+            annotation.label.as_str(),
+            crypto::seal(annotation.note.as_deref())?,
+            annotation.annotated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Get turn annotations, of one session or all of them, with the cost of
+/// each stored turn
+///
+/// Rows with a label this version doesn't know are skipped.
+pub fn get_turn_annotations(conn: &Connection, session_id: Option<&str>) -> Result<Vec<TurnAnnotation>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT a.session_id, a.turn_number, a.label, a.note, a.annotated_at, COALESCE(m.total_cost, 0.0)
+        FROM turn_annotations a
+        LEFT JOIN turns t ON t.session_id = a.session_id AND t.turn_number = a.turn_number
+        LEFT JOIN turn_metrics m ON m.turn_id = t.turn_id
+        WHERE ?1 IS NULL OR a.session_id = ?1
+        ORDER BY a.session_id, a.turn_number
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![session_id], |row| {
+        let label = match AnnotationLabel::parse(&row.get::<_, String>(2)?) {
+            Some(label) => label,
+            None => return Ok(None),
+        };
+        Ok(Some(TurnAnnotation {
+            session_id: row.get(0)?,
+            turn_number: row.get(1)?,
+            label,
+            note: crypto::open(row.get(3)?),
+            annotated_at: row.get(4)?,
+            cost: row.get(5)?,
+        }))
+    })?;
+
+    let mut annotations = Vec::new();
+    for row in rows {
+        annotations.extend(row?);
+    }
+    Ok(annotations)
+}
+
+/// Remove a turn annotation. Returns whether there was one.
+pub fn delete_turn_annotation(conn: &Connection, session_id: &str, turn_number: u32) -> Result<bool, DbError> {
+    let removed = conn.execute(
+        "DELETE FROM turn_annotations WHERE session_id = ?1 AND turn_number = ?2",
+        params![session_id, turn_number],
+    )?;
+    Ok(removed > 0)
+}
+
+/// Cost of the turns annotated as wasted, by project
+///
+/// Projects and dates follow `get_project_metrics_from_db`, so the totals
+/// line up with its rows.
+pub fn get_wasted_cost_by_project(
+    conn: &Connection,
+    days: Option<u32>,
+    attribute_subagents: bool,
+) -> Result<HashMap<String, f64>, DbError> {
+    let project_path = if attribute_subagents {
+        "COALESCE(p.project_path, s.project_path)"
+    } else {
+        "s.project_path"
+    };
+    let sql = format!(
+        r#"
+        SELECT {project_path} as project, SUM(m.total_cost)
+        FROM turn_annotations a
+        JOIN sessions s ON s.session_id = a.session_id
+        JOIN turns t ON t.session_id = a.session_id AND t.turn_number = a.turn_number
+        JOIN turn_metrics m ON m.turn_id = t.turn_id
+        LEFT JOIN sessions p ON p.session_id = s.parent_session_id
+        WHERE a.label = 'wasted'
+          AND is_real_user_project({project_path})
+          AND (?1 IS NULL OR (s.started_at LIKE '20%' AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')))
+        GROUP BY project
+        "#,
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![days], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+}
+
+/// Anti-pattern occurrences of one type on one day
+#[derive(Debug, Clone)]
+pub struct AntiPatternHistoryRow {
+    pub date: String,
+    pub pattern_type: AntiPatternType,
+    pub occurrences: u32,
+    pub sessions: u32,
+    pub avg_severity_score: f64,
+    pub impact_cost: f64,
+}
+
+/// How often an anti-pattern type occurred over a period
+#[derive(Debug, Clone, PartialEq)]
+pub struct AntiPatternFrequencyRow {
+    pub pattern_type: AntiPatternType,
+    pub occurrences: u32,
+    pub sessions: u32,
+}
+
+/// Replace the anti-patterns stored for a session
+///
+/// Each pattern is dated by the turn it was found in, or by the session's
+/// start when it applies to the whole session, so the session's turns should
+/// be stored first. Returns false without storing anything when the session
+/// row doesn't exist.
+pub fn replace_session_antipatterns(
+    conn: &Connection,
+    session_id: &str,
+    patterns: &[DetectedPattern],
+    detected_at: &str,
+) -> Result<bool, DbError> {
+    if !session_exists(conn, session_id)? {
+        return Ok(false);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM antipatterns WHERE session_id = ?1", params![session_id])?;
+    {
+        let mut stmt = tx.prepare(
+            r#"
+            INSERT INTO antipatterns (
+                session_id, pattern_type, severity, severity_score, turn_number,
+                description, impact_cost, metric_value, threshold, occurred_at, detected_at
+            )
+            VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9,
+                COALESCE(
+                    (SELECT started_at FROM turns WHERE session_id = ?1 AND turn_number = ?5),
+                    (SELECT started_at FROM sessions WHERE session_id = ?1)
+                ),
+                ?10
+            )
+            "#,
+        )?;
+        for pattern in patterns {
+            stmt.execute(params![
+                session_id,
+                pattern.pattern_type.as_str(),
+                pattern.severity.as_str(),
+                pattern.severity_score(),
+                pattern.turn_number,
+                pattern.description,
+                pattern.impact_cost,
+                pattern.metric_value,
+                pattern.threshold,
+                detected_at,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(true)
+}
+
+/// Filter on `a.occurred_at` for the last `?1` days (all time when NULL)
+const ANTIPATTERN_DAYS_FILTER: &str =
+    "(?1 IS NULL OR local_date(a.occurred_at) >= date(local_date('now'), '-' || ?1 || ' days'))";
+
+/// Get daily anti-pattern counts per type, oldest day first
+pub fn get_antipattern_history_from_db(
+    conn: &Connection,
+    days: Option<u32>,
+) -> Result<Vec<AntiPatternHistoryRow>, DbError> {
+    let sql = format!(
+        r#"
+        SELECT local_date(a.occurred_at) as day, a.pattern_type, COUNT(*),
+               COUNT(DISTINCT a.session_id), AVG(a.severity_score), SUM(a.impact_cost)
+        FROM antipatterns a
+        JOIN sessions s ON s.session_id = a.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND {ANTIPATTERN_DAYS_FILTER}
+        GROUP BY day, a.pattern_type
+        ORDER BY day ASC, COUNT(*) DESC, a.pattern_type ASC
+        "#,
+    );
+    let mut stmt = conn.prepare(&sql)?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        let pattern_type = match AntiPatternType::from_str(&row.get::<_, String>(1)?) {
+            Some(pattern_type) => pattern_type,
+            None => return Ok(None),
+        };
+        Ok(Some(AntiPatternHistoryRow {
+            date: row.get(0)?,
+            pattern_type,
+            occurrences: row.get(2)?,
+            sessions: row.get(3)?,
+            avg_severity_score: row.get(4)?,
+            impact_cost: row.get(5)?,
+        }))
+    })?;
+
+    let mut history = Vec::new();
+    for row in rows {
+        history.extend(row?);
+    }
+    Ok(history)
+}
+
+/// Get the anti-pattern types found on `day` with their daily average over
+/// the week before it
+pub fn get_digest_antipatterns_from_db(conn: &Connection, day: &str) -> Result<Vec<DigestAntiPattern>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT a.pattern_type,
+               COUNT(CASE WHEN local_date(a.occurred_at) = ?1 THEN 1 END),
+               COUNT(DISTINCT CASE WHEN local_date(a.occurred_at) = ?1 THEN a.session_id END),
+               COALESCE(SUM(CASE WHEN local_date(a.occurred_at) = ?1 THEN a.impact_cost END), 0.0),
+               COUNT(CASE WHEN local_date(a.occurred_at) < ?1 THEN 1 END)
+        FROM antipatterns a
+        JOIN sessions s ON s.session_id = a.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND local_date(a.occurred_at) BETWEEN date(?1, '-' || ?2 || ' days') AND ?1
+        GROUP BY a.pattern_type
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![day, BASELINE_DAYS], |row| {
+        let pattern_type = match AntiPatternType::from_str(&row.get::<_, String>(0)?) {
+            Some(pattern_type) => pattern_type,
+            None => return Ok(None),
+        };
+        Ok(Some(DigestAntiPattern {
+            pattern_type,
+            occurrences: row.get(1)?,
+            sessions: row.get(2)?,
+            impact_cost: row.get(3)?,
+            avg_occurrences_7d: row.get::<_, u32>(4)? as f64 / BASELINE_DAYS as f64,
+        }))
+    })?;
+
+    let mut patterns = Vec::new();
+    for row in rows {
+        patterns.extend(row?.filter(|p| p.occurrences > 0));
+    }
+    Ok(patterns)
+}
+
+/// Get the anti-patterns found from `start_date` to `end_date`, with the
+/// occurrences from `previous_start` up to `start_date` for comparison
+///
+/// Types found in neither range are left out.
+pub fn get_antipattern_summary_in_range(
+    conn: &Connection,
+    previous_start: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<ReportAntiPattern>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT a.pattern_type,
+               COUNT(CASE WHEN local_date(a.occurred_at) >= ?2 THEN 1 END),
+               COUNT(CASE WHEN local_date(a.occurred_at) < ?2 THEN 1 END),
+               COUNT(DISTINCT CASE WHEN local_date(a.occurred_at) >= ?2 THEN a.session_id END),
+               COALESCE(SUM(CASE WHEN local_date(a.occurred_at) >= ?2 THEN a.impact_cost END), 0.0)
+        FROM antipatterns a
+        JOIN sessions s ON s.session_id = a.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND local_date(a.occurred_at) BETWEEN ?1 AND ?3
+        GROUP BY a.pattern_type
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![previous_start, start_date, end_date], |row| {
+        let pattern_type = match AntiPatternType::from_str(&row.get::<_, String>(0)?) {
+            Some(pattern_type) => pattern_type,
+            None => return Ok(None),
+        };
+        Ok(Some(ReportAntiPattern {
+            pattern_type,
+            occurrences: row.get(1)?,
+            previous_occurrences: row.get(2)?,
+            sessions: row.get(3)?,
+            impact_cost: row.get(4)?,
+        }))
+    })?;
+
+    let mut patterns = Vec::new();
+    for row in rows {
+        patterns.extend(row?);
+    }
+    Ok(patterns)
+}
+
+/// Get anti-pattern types by number of occurrences, most frequent first
+pub fn get_antipattern_frequencies_from_db(
+    conn: &Connection,
+    days: Option<u32>,
+) -> Result<Vec<AntiPatternFrequencyRow>, DbError> {
+    let sql = format!(
+        r#"
+        SELECT a.pattern_type, COUNT(*), COUNT(DISTINCT a.session_id)
+        FROM antipatterns a
+        JOIN sessions s ON s.session_id = a.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND {ANTIPATTERN_DAYS_FILTER}
+        GROUP BY a.pattern_type
+        ORDER BY COUNT(*) DESC, COUNT(DISTINCT a.session_id) DESC, a.pattern_type ASC
+        "#,
+    );
+    let mut stmt = conn.prepare(&sql)?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        let pattern_type = match AntiPatternType::from_str(&row.get::<_, String>(0)?) {
+            Some(pattern_type) => pattern_type,
+            None => return Ok(None),
+        };
+        Ok(Some(AntiPatternFrequencyRow {
+            pattern_type,
+            occurrences: row.get(1)?,
+            sessions: row.get(2)?,
+        }))
+    })?;
+
+    let mut frequencies = Vec::new();
+    for row in rows {
+        frequencies.extend(row?);
+    }
+    Ok(frequencies)
+}
+
+/// Tool calls per tool name in the stored turns of sessions started within a
+/// date range (inclusive YYYY-MM-DD bounds, reporting timezone)
+pub fn get_tool_usage_in_range(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<(String, u32)>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT u.tool_name, COUNT(*)
+        FROM tool_uses u
+        JOIN turns t ON t.turn_id = u.turn_id
+        JOIN sessions s ON s.session_id = t.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND local_date(s.started_at) >= ?1
+          AND local_date(s.started_at) <= ?2
+        GROUP BY u.tool_name
+        ORDER BY u.tool_name
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u32))
+    })?;
+
+    // Stored names predate canonicalization, so spellings of one tool merge here
+    let mut usage: BTreeMap<String, u32> = BTreeMap::new();
+    for row in rows {
+        let (name, count) = row?;
+        *usage.entry(canonical_tool_name(&name)).or_default() += count;
+    }
+    Ok(usage.into_iter().collect())
+}
+
+/// Calls and failed calls per stored tool name over the last `days` days
+/// (all time if `None`)
+pub fn get_tool_calls_from_db(conn: &Connection, days: Option<u32>) -> Result<Vec<ToolCallCount>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT u.tool_name, COUNT(*), SUM(u.is_error)
+        FROM tool_uses u
+        JOIN turns t ON t.turn_id = u.turn_id
+        JOIN sessions s ON s.session_id = t.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND (?1 IS NULL OR (s.started_at LIKE '20%'
+               AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')))
+        GROUP BY u.tool_name
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        Ok(ToolCallCount {
+            tool: row.get(0)?,
+            calls: row.get::<_, i64>(1)? as u32,
+            errors: row.get::<_, Option<i64>>(2)?.unwrap_or(0) as u32,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Stored turns with their tokens and recorded cost, of sessions started in
+/// the last `days` days (all time if `None`), for the savings simulator
+pub fn get_simulated_turns_from_db(conn: &Connection, days: Option<u32>) -> Result<Vec<SimulatedTurn>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.session_id, s.project_path, COALESCE(t.model, s.model), t.provider,
+               m.input_tokens, m.output_tokens, m.cache_read_tokens,
+               m.cache_write_5m_tokens, m.cache_write_1h_tokens, m.total_cost
+        FROM turn_metrics m
+        JOIN turns t ON t.turn_id = m.turn_id
+        JOIN sessions s ON s.session_id = t.session_id
+        WHERE is_real_user_project(s.project_path)
+          AND (?1 IS NULL OR (s.started_at LIKE '20%'
+               AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')))
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        let model: Option<String> = row.get(2)?;
+        Ok(SimulatedTurn {
+            session_id: row.get(0)?,
+            project_path: row.get(1)?,
+            provider: stored_provider(row.get(3)?, model.as_deref()),
+            model: model.unwrap_or_default(),
+            tokens: TurnTokens::new(
+                row.get::<_, i64>(4)? as u64,
+                row.get::<_, i64>(5)? as u64,
+                row.get::<_, i64>(6)? as u64,
+                row.get::<_, i64>(7)? as u64,
+                row.get::<_, i64>(8)? as u64,
+            ),
+            cost: row.get(9)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Stored MCP tool calls of sessions started in the last `days` days (all
+/// time if `None`)
+pub fn get_mcp_calls_from_db(conn: &Connection, days: Option<u32>) -> Result<Vec<McpCall>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.session_id, u.tool_name, u.is_error, COALESCE(LENGTH(decrypt_content(u.result)), 0), t.model
+        FROM tool_uses u
+        JOIN turns t ON t.turn_id = u.turn_id
+        JOIN sessions s ON s.session_id = t.session_id
+        WHERE u.tool_name LIKE 'mcp\_\_%' ESCAPE '\'
+          AND is_real_user_project(s.project_path)
+          AND (?1 IS NULL OR (s.started_at LIKE '20%'
+               AND local_date(s.started_at) >= date(local_date('now'), '-' || ?1 || ' days')))
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![days], |row| {
+        Ok(McpCall {
+            session_id: row.get(0)?,
+            tool_name: row.get(1)?,
+            is_error: row.get::<_, i32>(2)? == 1,
+            result_chars: row.get::<_, i64>(3)? as u64,
+            model: row.get(4)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Count sessions active in a date range that have turns but none stored
+pub fn count_sessions_without_turns(
+    conn: &Connection,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<u32, DbError> {
+    let count: i64 = conn.query_row(
+        r#"
+        SELECT COUNT(*)
+        FROM sessions s
+        JOIN session_metrics m ON m.session_id = s.session_id
+        WHERE m.total_turns > 0
+          AND is_real_user_project(s.project_path)
+          AND (?1 IS NULL OR local_date(COALESCE(s.last_activity_at, s.started_at)) >= ?1)
+          AND (?2 IS NULL OR local_date(s.started_at) <= ?2)
+          AND NOT EXISTS (SELECT 1 FROM turns t WHERE t.session_id = s.session_id)
+        "#,
+        params![start_date, end_date],
+        |row| row.get(0),
+    )?;
+    Ok(count as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn sample_turn(turn_number: u32) -> CompletedTurn {
+        CompletedTurn {
+            turn_number,
+            started_at: "2026-01-14T07:44:28.531Z".to_string(),
+            ended_at: Some("2026-01-14T07:44:30.000Z".to_string()),
+            duration_ms: Some(1469),
+            user_message: Some("Hello".to_string()),
+            assistant_message: Some("Hi".to_string()),
+            model: Some("claude-opus-4-5-20251101".to_string()),
+            stop_reason: Some("end_turn".to_string()),
+            provider: Default::default(),
+            input_tokens: 100,
+            output_tokens: 50,
+            cache_read_tokens: 1000,
+            cache_write_5m_tokens: 500,
+            cache_write_1h_tokens: 0,
+            total_tokens: 1650,
+            total_context: 1600,
+            tool_uses: vec![ToolUse {
+                id: format!("toolu_{}", turn_number),
+                name: "Read".to_string(),
+                input: Some(serde_json::json!({"file_path": "/tmp/a.rs"})),
+                result: Some("ok".to_string()),
+                is_error: false,
+            }],
+            tool_count: 1,
+            has_subagents: true,
+            subagent_ids: vec!["agent-1".to_string()],
+            start_uuid: Some("u1".to_string()),
+            end_uuid: Some("a1".to_string()),
+            entry_count: 4,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_tokens: 0,
+            thinking_level: None,
+        }
+    }
+
+    #[test]
+    fn test_replace_and_load_session_turns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+        schema::create_tables(&conn).unwrap();
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN turns_mtime TEXT;").unwrap();
+
+        // No session row yet: nothing is stored
+        let turns = vec![sample_turn(1), sample_turn(2)];
+        assert!(!replace_session_turns(&conn, "s1", &turns, "m1").unwrap());
+
+        upsert_session_with_mtime(
+            &conn, "s1", "/Users/me/app", "app", None,
+            "2026-01-14T07:44:28.531Z", "2026-01-14T07:44:30.000Z",
+            "claude-opus-4-5-20251101", false, "/tmp/s1.jsonl", "m1",
+        )
+        .unwrap();
+        assert!(replace_session_turns(&conn, "s1", &turns, "m1").unwrap());
+        assert_eq!(get_session_turns_mtime(&conn, "s1").unwrap(), Some("m1".to_string()));
+
+        let loaded = get_session_turns_from_db(&conn, "s1").unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].turn_number, 1);
+        assert_eq!(loaded[1].total_tokens, 1650);
+        assert_eq!(loaded[1].total_context, 1600);
+        assert_eq!(loaded[0].tool_uses.len(), 1);
+        assert_eq!(loaded[0].tool_uses[0].name, "Read");
+        assert_eq!(loaded[0].subagent_ids, vec!["agent-1".to_string()]);
+        assert!(loaded[0].has_subagents);
+        assert_eq!(loaded[0].entry_count, 4);
+
+        // Replacing drops turns that no longer exist
+        assert!(replace_session_turns(&conn, "s1", &turns[..1], "m2").unwrap());
+        assert_eq!(get_session_turns_from_db(&conn, "s1").unwrap().len(), 1);
+        assert_eq!(get_session_turns_mtime(&conn, "s1").unwrap(), Some("m2".to_string()));
+        assert_eq!(get_stored_turn_counts(&conn).unwrap().get("s1"), Some(&("m2".to_string(), 1)));
+    }
+
+    #[test]
+    fn test_file_changes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+
+        let tool = |id: &str, name: &str, path: &str| ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: Some(serde_json::json!({"file_path": path})),
+            result: None,
+            is_error: false,
+        };
+        let mut first = sample_turn(1);
+        first.tool_uses = vec![tool("t1", "Write", "/app/src/lib.rs"), tool("t2", "Edit", "/app/src/main.rs")];
+        let mut second = sample_turn(2);
+        second.started_at = "2026-01-14T08:00:00.000Z".to_string();
+        second.tool_uses = vec![tool("t3", "Edit", "/app/src/lib.rs"), tool("t4", "Edit", "/app/src/lib.rs")];
+
+        upsert_session_with_mtime(
+            &conn, "s1", "/app", "app", Some("main"),
+            "2026-01-14T07:44:28.531Z", "2026-01-14T08:00:00.000Z",
+            "claude-opus-4-5-20251101", false, "/tmp/s1.jsonl", "m1",
+        )
+        .unwrap();
+        replace_session_turns(&conn, "s1", &[first, second], "m1").unwrap();
+
+        let changes = get_file_changes_from_db(&conn, "/app/src/lib.rs").unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!((changes[0].turn_number, changes[2].turn_number), (2, 1));
+        assert_eq!(changes[2].change_type, "create");
+        assert_eq!(changes[0].tool_name.as_deref(), Some("Edit"));
+        assert_eq!(changes[0].branch.as_deref(), Some("main"));
+        // The first turn's cost is split between the two files it changed
+        let turn_cost: f64 = conn
+            .query_row("SELECT total_cost FROM turn_metrics WHERE turn_id = 's1:1'", [], |r| r.get(0))
+            .unwrap();
+        assert!((changes[2].cost_share - turn_cost / 2.0).abs() < 1e-9);
+        assert!((changes[0].cost_share - turn_cost).abs() < 1e-9);
+
+        let session_changes = get_session_code_changes_from_db(&conn, "s1").unwrap();
+        assert_eq!(
+            session_changes.iter().map(|c| (c.turn_number, c.file_path.as_str())).collect::<Vec<_>>(),
+            vec![(1, "/app/src/lib.rs"), (1, "/app/src/main.rs"), (2, "/app/src/lib.rs"), (2, "/app/src/lib.rs")]
+        );
+        let files = get_project_file_changes_from_db(&conn, "/app").unwrap();
+        assert_eq!(
+            files.iter().map(|f| (f.file_path.as_str(), f.changes, f.sessions)).collect::<Vec<_>>(),
+            vec![("/app/src/lib.rs", 3, 1), ("/app/src/main.rs", 1, 1)]
+        );
+        assert_eq!(files[0].last_changed_at, "2026-01-14T08:00:00.000Z");
+
+        // Re-storing the turns replaces their changes
+        replace_session_turns(&conn, "s1", &[sample_turn(1)], "m2").unwrap();
+        assert!(get_file_changes_from_db(&conn, "/app/src/lib.rs").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mcp_calls() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+        crate::db::register_functions(&conn).unwrap();
+
+        let tool = |id: &str, name: &str, result: &str, is_error: bool| ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: None,
+            result: Some(result.to_string()),
+            is_error,
+        };
+        let mut turn = sample_turn(1);
+        turn.tool_uses = vec![
+            tool("t1", "mcp__github__list_pulls", "[1, 2, 3]", false),
+            tool("t2", "MCP__linear__get_issue", "not found", true),
+            tool("t3", "Bash", "ok", false),
+            tool("t4", "mcpXXgithub", "ok", false),
+        ];
+        upsert_session_with_mtime(
+            &conn, "s1", "/home/me/app", "app", None,
+            "2026-01-14T07:44:28.531Z", "2026-01-14T08:00:00.000Z",
+            "claude-opus-4-5-20251101", false, "/tmp/s1.jsonl", "m1",
+        )
+        .unwrap();
+        replace_session_turns(&conn, "s1", &[turn], "m1").unwrap();
+
+        let mut calls = get_mcp_calls_from_db(&conn, None).unwrap();
+        calls.sort_by(|a, b| a.tool_name.cmp(&b.tool_name));
+        assert_eq!(
+            calls.iter().map(|c| (c.tool_name.as_str(), c.is_error, c.result_chars)).collect::<Vec<_>>(),
+            vec![("MCP__linear__get_issue", true, 9), ("mcp__github__list_pulls", false, 9)]
+        );
+        assert_eq!(calls[0].session_id, "s1");
+    }
+
+    #[test]
+    fn test_settings_entries_and_project_function() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::register_functions(&conn).unwrap();
+
+        upsert_settings_entries(&conn, &[("a".to_string(), "1".to_string())]).unwrap();
+        upsert_settings_entries(&conn, &[("a".to_string(), "2".to_string())]).unwrap();
+        let entries = get_settings_entries(&conn).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries["a"], "2");
+
+        let (real, temp, empty): (bool, bool, bool) = conn
+            .query_row(
+                "SELECT is_real_user_project('/home/me/app'), is_real_user_project('/tmp/x'), is_real_user_project('')",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert!(real);
+        assert!(!temp);
+        assert!(!empty);
+    }
+
+    #[test]
+    fn test_hourly_usage_from_db() {
+        use crate::metrics::hourly::local_slot;
+
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::register_functions(&conn).unwrap();
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN turns_mtime TEXT;").unwrap();
+
+        upsert_session_with_mtime(
+            &conn, "s1", "/Users/me/app", "app", None,
+            "2026-01-14T07:44:28.531Z", "2026-01-14T07:44:30.000Z",
+            "claude-opus-4-5-20251101", false, "/tmp/s1.jsonl", "m1",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO session_metrics (session_id, total_turns) VALUES ('s1', 2)",
+            [],
+        )
+        .unwrap();
+        assert_eq!(count_sessions_without_turns(&conn, None, None).unwrap(), 1);
+
+        replace_session_turns(&conn, "s1", &[sample_turn(1), sample_turn(2)], "m1").unwrap();
+        assert_eq!(count_sessions_without_turns(&conn, None, None).unwrap(), 0);
+
+        // SQL and Rust-side bucketing agree on the slot
+        let (day, hour) = local_slot("2026-01-14T07:44:28.531Z").unwrap();
+        let rows = get_hourly_usage_from_db(&conn, Some("2026-01-14"), None).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].day_of_week as usize, rows[0].hour as usize), (day, hour));
+        assert_eq!(rows[0].turns, 2);
+        assert_eq!(rows[0].sessions, 1);
+        assert_eq!(rows[0].total_tokens, 3300);
+        assert!(rows[0].total_cost > 0.0);
+
+        assert!(get_hourly_usage_from_db(&conn, Some("2026-01-15"), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_daily_metrics_filters() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+        crate::db::register_functions(&conn).unwrap();
+
+        let sessions = [
+            ("s1", "/Users/me/app", "/tmp/s1.jsonl"),
+            ("s2", "/Users/me/api", "/tmp/s1/subagents/agent-a.jsonl"),
+        ];
+        for (id, project, file_path) in sessions {
+            upsert_session_with_mtime(
+                &conn, id, project, "app", None,
+                "2026-01-14T07:44:28.531Z", "2026-01-14T07:44:30.000Z",
+                "claude-opus-4-5-20251101", false, file_path, "m1",
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_metrics (session_id, total_turns, total_cost) VALUES (?1, 2, 1.5)",
+                params![id],
+            )
+            .unwrap();
+        }
+        crate::db::rollups::rebuild(&conn).unwrap();
+
+        let all = get_daily_metrics_from_db(&conn, None, None, Segment::All).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].session_count, 2);
+        assert_eq!(all[0].subagent_session_count, 1);
+
+        let projects = vec!["/Users/me/api".to_string()];
+        let filtered = get_daily_metrics_from_db(&conn, None, Some(&projects), Segment::All).unwrap();
+        assert_eq!(filtered[0].session_count, 1);
+        assert_eq!(filtered[0].total_cost, 1.5);
+        assert!(get_daily_metrics_from_db(&conn, None, Some(&[]), Segment::All).unwrap().is_empty());
+
+        let user = get_daily_metrics_from_db(&conn, None, None, Segment::UserOnly).unwrap();
+        assert_eq!((user[0].session_count, user[0].subagent_session_count), (1, 0));
+        let subagents = get_daily_metrics_from_db(&conn, None, None, Segment::SubagentOnly).unwrap();
+        assert_eq!((subagents[0].session_count, subagents[0].user_session_count), (1, 0));
+        assert!(get_daily_metrics_from_db(&conn, None, Some(&["/Users/me/app".to_string()]), Segment::SubagentOnly)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_session_cost_curve_from_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN turns_mtime TEXT;").unwrap();
+
+        upsert_session_with_mtime(
+            &conn, "s1", "/Users/me/app", "app", None,
+            "2026-01-14T07:44:28.531Z", "2026-01-14T07:44:30.000Z",
+            "claude-opus-4-5-20251101", false, "/tmp/s1.jsonl", "m1",
+        )
+        .unwrap();
+        let mut turns = vec![sample_turn(1), sample_turn(2), sample_turn(3)];
+        turns[1].output_tokens = 1050;
+        turns[1].total_tokens = 2650;
+        replace_session_turns(&conn, "s1", &turns, "m1").unwrap();
+
+        let curve = get_session_cost_curve_from_db(&conn, "s1").unwrap();
+        assert_eq!(curve.iter().map(|p| p.turn_number).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(curve.iter().map(|p| p.cumulative_tokens).collect::<Vec<_>>(), vec![1650, 4300, 5950]);
+        assert!(curve[1].cost > curve[0].cost);
+        let total: f64 = curve.iter().map(|p| p.cost).sum();
+        assert!((curve[2].cumulative_cost - total).abs() < 1e-9);
+
+        assert!(get_session_cost_curve_from_db(&conn, "missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_project_timeline_from_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN turns_mtime TEXT;").unwrap();
+
+        for (id, started_at, file_path) in [
+            ("s2", "2026-01-15T09:00:00Z", "/p/s2.jsonl"),
+            ("s1", "2026-01-14T07:44:28.531Z", "/p/s1.jsonl"),
+            ("a1", "2026-01-14T08:00:00Z", "/p/s1/subagents/agent-a1.jsonl"),
+        ] {
+            upsert_session_with_mtime(
+                &conn, id, "/Users/me/app", "app", None,
+                started_at, started_at,
+                "claude-opus-4-5-20251101", false, file_path, "m1",
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_metrics (session_id, total_turns, total_cost, total_cache_read, total_cache_write) VALUES (?1, 1, 0.5, 300, 100)",
+                params![id],
+            )
+            .unwrap();
+        }
+
+        let rows = get_project_timeline_from_db(&conn, "/Users/me/app").unwrap();
+        assert_eq!(rows.iter().map(|r| r.session_id.as_str()).collect::<Vec<_>>(), vec!["s1", "s2"]);
+        assert_eq!(rows[0].efficiency, Some(0.75));
+        assert!(get_project_timeline_from_db(&conn, "/Users/me/other").unwrap().is_empty());
+
+        // Only sessions with stored turns get a count
+        let mut turn = sample_turn(1);
+        turn.tool_uses.push(ToolUse {
+            id: "toolu_edit".to_string(),
+            name: "Edit".to_string(),
+            input: Some(serde_json::json!({"file_path": "/tmp/a.rs"})),
+            result: None,
+            is_error: false,
+        });
+        replace_session_turns(&conn, "s1", &[turn, sample_turn(2)], "m1").unwrap();
+        replace_session_turns(&conn, "a1", &[sample_turn(1)], "m1").unwrap();
+        let counts = get_code_change_counts_from_db(&conn, "/Users/me/app").unwrap();
+        assert_eq!(counts.get("s1"), Some(&1));
+        assert_eq!(counts.get("a1"), Some(&0));
+        assert_eq!(counts.get("s2"), None);
+        assert_eq!(get_changed_files_from_db(&conn, "/Users/me/app").unwrap(), vec!["/tmp/a.rs"]);
+    }
+
+    #[test]
+    fn test_project_stacks() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+
+        let stack = ProjectStack {
+            languages: vec!["Rust".to_string(), "TypeScript".to_string()],
+            frameworks: vec!["Tauri".to_string()],
+        };
+        upsert_project_stack(&conn, "/Users/me/app", &ProjectStack::default(), "2026-01-01T00:00:00.000Z").unwrap();
+        upsert_project_stack(&conn, "/Users/me/app", &stack, "2026-01-02T00:00:00.000Z").unwrap();
+
+        let stacks = get_project_stacks(&conn).unwrap();
+        assert_eq!(stacks.len(), 1);
+        assert_eq!(stacks["/Users/me/app"], (stack, "2026-01-02T00:00:00.000Z".to_string()));
+    }
+
+    #[test]
+    fn test_project_registry() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+
+        let mut resolution = ProjectResolution {
+            encoded_dir: "-Users-me-my-app".to_string(),
+            project_path: "/Users/me/my/app".to_string(),
+            source: ResolutionSource::Decoded,
+            ambiguous: false,
+        };
+        upsert_project_resolution(&conn, &resolution).unwrap();
+        resolution.project_path = "/Users/me/my-app".to_string();
+        resolution.source = ResolutionSource::Override;
+        upsert_project_resolution(&conn, &resolution).unwrap();
+        assert_eq!(get_project_registry(&conn).unwrap(), vec![resolution.clone()]);
+
+        for (id, file_path) in [
+            ("s1", "/home/me/.claude/projects/-Users-me-my-app/s1.jsonl"),
+            ("s2", "C:\\Users\\me\\.claude\\projects\\-Users-me-my-app\\s2.jsonl"),
+            ("s3", "/home/me/.claude/projects/-Users-me-my-app-v2/s3.jsonl"),
+        ] {
+            conn.execute(
+                "INSERT INTO sessions (session_id, project_path, project_name, started_at, last_activity_at, model, file_path)
+                 VALUES (?1, '/Users/me/my/app', 'app', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z', 'm', ?2)",
+                params![id, file_path],
+            )
+            .unwrap();
+        }
+        let moved = move_project_dir_sessions(&conn, "-Users-me-my-app", "/Users/me/my-app", "my-app").unwrap();
+        assert_eq!(moved, 2);
+        let path: String = conn
+            .query_row("SELECT project_path FROM sessions WHERE session_id = 's3'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(path, "/Users/me/my/app");
+
+        delete_project_resolution(&conn, "-Users-me-my-app").unwrap();
+        assert!(get_project_registry(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recommendation_feedback() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+
+        let mut feedback = RecommendationFeedback {
+            id: "cost_saving:s1".to_string(),
+            rec_type: RecommendationType::CostSaving,
+            session_id: Some("s1".to_string()),
+            project_path: Some("/Users/me/app".to_string()),
+            reason: None,
+            potential_savings: 2.5,
+            confidence: 0.9,
+            dismissed_at: "2026-01-14T07:44:28Z".to_string(),
+            snoozed_until: None,
+        };
+        upsert_recommendation_feedback(&conn, &feedback).unwrap();
+        feedback.reason = Some("Expected for this project".to_string());
+        upsert_recommendation_feedback(&conn, &feedback).unwrap();
+        conn.execute(
+            "INSERT INTO recommendations_feedback (recommendation_id, rec_type, dismissed_at) VALUES ('x:all', 'retired_type', '2026-01-01')",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(get_recommendation_feedback(&conn).unwrap(), vec![feedback]);
+        assert!(delete_recommendation_feedback(&conn, "cost_saving:s1").unwrap());
+        assert!(!delete_recommendation_feedback(&conn, "cost_saving:s1").unwrap());
+        assert!(get_recommendation_feedback(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_turn_annotations() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+        crate::db::register_functions(&conn).unwrap();
+
+        upsert_session_with_mtime(
+            &conn, "s1", "/Users/me/app", "app", None,
+            "2026-01-14T07:44:28.531Z", "2026-01-14T07:44:30.000Z",
+            "claude-opus-4-5-20251101", false, "/tmp/s1.jsonl", "m1",
+        )
+        .unwrap();
+        replace_session_turns(&conn, "s1", &[sample_turn(1), sample_turn(2)], "m1").unwrap();
+        let turn_cost = get_session_cost_curve_from_db(&conn, "s1").unwrap()[0].cost;
+
+        let mut wasted = TurnAnnotation {
+            session_id: "s1".to_string(),
+            turn_number: 1,
+            label: AnnotationLabel::Valuable,
+            note: None,
+            annotated_at: "2026-01-15T09:00:00Z".to_string(),
+            cost: 0.0,
+        };
+        upsert_turn_annotation(&conn, &wasted).unwrap();
+        wasted.label = AnnotationLabel::Wasted;
+        wasted.note = Some("Went down the wrong API".to_string());
+        upsert_turn_annotation(&conn, &wasted).unwrap();
+        // Annotated before the turn was stored
+        let pending = TurnAnnotation { session_id: "s2".to_string(), ..wasted.clone() };
+        upsert_turn_annotation(&conn, &pending).unwrap();
+
+        let annotations = get_turn_annotations(&conn, None).unwrap();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].label, AnnotationLabel::Wasted);
+        assert_eq!(annotations[0].note.as_deref(), Some("Went down the wrong API"));
+        assert!((annotations[0].cost - turn_cost).abs() < 1e-9);
+        assert_eq!(annotations[1].cost, 0.0);
+        assert_eq!(get_turn_annotations(&conn, Some("s2")).unwrap(), vec![pending]);
+
+        let wasted_cost = get_wasted_cost_by_project(&conn, None, false).unwrap();
+        assert_eq!(wasted_cost.len(), 1);
+        assert!((wasted_cost["/Users/me/app"] - turn_cost).abs() < 1e-9);
+
+        assert!(delete_turn_annotation(&conn, "s1", 1).unwrap());
+        assert!(!delete_turn_annotation(&conn, "s1", 1).unwrap());
+        assert!(get_wasted_cost_by_project(&conn, None, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recalculate_session_costs() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+
+        for id in ["s1", "s2"] {
+            upsert_session_with_mtime(
+                &conn, id, "/Users/me/app", "app", None,
+                "2026-01-14T07:44:28.531Z", "2026-01-14T07:44:30.000Z",
+                "claude-opus-4-5-20251101", false, "/tmp/s.jsonl", "m1",
+            )
+            .unwrap();
+            upsert_session_metrics(&conn, id, 2, 0, 0.0, 200, 100, 2_000, 40_000, 0.0, 0.0, 0.0).unwrap();
+        }
+        // s1 switches to Sonnet and writes to the 1-hour cache
+        let mut second = sample_turn(2);
+        second.model = Some("claude-sonnet-4-5-20250929".to_string());
+        second.cache_write_5m_tokens = 0;
+        second.cache_write_1h_tokens = 39_500;
+        let turns = [sample_turn(1), second];
+        replace_session_turns(&conn, "s1", &turns, "m1").unwrap();
+        conn.execute("UPDATE turn_metrics SET total_cost = 0", []).unwrap();
+
+        assert_eq!(recalculate_session_costs(&conn).unwrap(), 2);
+
+        let expected: Vec<f64> = turns
+            .iter()
+            .map(|t| {
+                let tokens = TurnTokens::new(
+                    t.input_tokens,
+                    t.output_tokens,
+                    t.cache_read_tokens,
+                    t.cache_write_5m_tokens,
+                    t.cache_write_1h_tokens,
+                );
+                calculate_provider_turn_cost(&tokens, t.model.as_deref(), t.provider, Some(&t.started_at)).total_cost
+            })
+            .collect();
+        let curve = get_session_cost_curve_from_db(&conn, "s1").unwrap();
+        assert!((curve[0].cost - expected[0]).abs() < 1e-12);
+        assert!((curve[1].cost - expected[1]).abs() < 1e-12);
+
+        let session_cost = |id: &str| -> f64 {
+            conn.query_row("SELECT total_cost FROM session_metrics WHERE session_id = ?1", [id], |row| row.get(0))
+                .unwrap()
+        };
+        assert!((session_cost("s1") - (expected[0] + expected[1])).abs() < 1e-12);
+
+        // Without stored turns the session totals are all there is to price
+        let totals = TurnTokens::new(200, 100, 2_000, 40_000, 0);
+        let fallback = calculate_provider_turn_cost(
+            &totals,
+            Some("claude-opus-4-5-20251101"),
+            CostProvider::Anthropic,
+            Some("2026-01-14T07:44:28.531Z"),
+        );
+        assert!((session_cost("s2") - fallback.total_cost).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_recalculate_costs_across_effective_date() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+
+        // A model only this test prices, so the overrides don't leak into others
+        let model = "ironhide-straddle-test-model";
+        let rate = |input: f64, effective_from: Option<&str>| ModelPricing {
+            model_id: model.to_string(),
+            display_name: "Straddle".to_string(),
+            input_price_per_million: input,
+            output_price_per_million: 0.0,
+            cache_write_5m_per_million: 0.0,
+            cache_write_1h_per_million: 0.0,
+            cache_read_per_million: 0.0,
+            max_context_tokens: None,
+            provider: CostProvider::Anthropic,
+            effective_from: effective_from.map(String::from),
+        };
+        crate::metrics::cost::set_pricing_overrides(vec![rate(1.0, None), rate(2.0, Some("2026-03-01"))]);
+
+        upsert_session_with_mtime(
+            &conn, "s1", "/Users/me/app", "app", None,
+            "2026-02-28T23:50:00Z", "2026-03-01T00:10:00Z",
+            model, false, "/tmp/s1.jsonl", "m1",
+        )
+        .unwrap();
+        upsert_session_metrics(&conn, "s1", 2, 0, 0.0, 2_000_000, 0, 0, 0, 0.0, 0.0, 0.0).unwrap();
+        let turn = |n: u32, started_at: &str| CompletedTurn {
+            started_at: started_at.to_string(),
+            model: Some(model.to_string()),
+            provider: CostProvider::Anthropic,
+            input_tokens: 1_000_000,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_write_5m_tokens: 0,
+            ..sample_turn(n)
+        };
+        let turns = [turn(1, "2026-02-28T23:50:00Z"), turn(2, "2026-03-01T00:10:00Z")];
+        replace_session_turns(&conn, "s1", &turns, "m1").unwrap();
+
+        recalculate_session_costs(&conn).unwrap();
+        crate::metrics::cost::set_pricing_overrides(Vec::new());
+
+        let curve = get_session_cost_curve_from_db(&conn, "s1").unwrap();
+        assert!((curve[0].cost - 1.0).abs() < 1e-9);
+        assert!((curve[1].cost - 2.0).abs() < 1e-9);
+        let (total, average): (f64, f64) = conn
+            .query_row(
+                "SELECT total_cost, avg_cost_per_turn FROM session_metrics WHERE session_id = 's1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!((total - 3.0).abs() < 1e-9);
+        assert!((average - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dated_custom_pricing() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+
+        let mut pricing = ModelPricing {
+            model_id: "my-model".to_string(),
+            display_name: "My Model".to_string(),
+            input_price_per_million: 2.0,
+            output_price_per_million: 10.0,
+            cache_write_5m_per_million: 2.5,
+            cache_write_1h_per_million: 4.0,
+            cache_read_per_million: 0.2,
+            max_context_tokens: None,
+            provider: CostProvider::Anthropic,
+            effective_from: None,
+        };
+        upsert_custom_pricing(&conn, &pricing).unwrap();
+        pricing.effective_from = Some("2026-06-01".to_string());
+        pricing.input_price_per_million = 1.0;
+        upsert_custom_pricing(&conn, &pricing).unwrap();
+        // Same start date again replaces rather than adds
+        pricing.output_price_per_million = 5.0;
+        upsert_custom_pricing(&conn, &pricing).unwrap();
+
+        let custom = get_custom_pricing(&conn).unwrap();
+        assert_eq!(custom.len(), 2);
+        assert_eq!(custom[0].effective_from, None);
+        assert_eq!(custom[0].input_price_per_million, 2.0);
+        assert_eq!(custom[1].effective_from.as_deref(), Some("2026-06-01"));
+        assert_eq!(custom[1].output_price_per_million, 5.0);
+
+        assert!(delete_custom_pricing(&conn, "my-model", CostProvider::Anthropic, Some("2026-06-01")).unwrap());
+        assert!(!delete_custom_pricing(&conn, "my-model", CostProvider::Anthropic, Some("2026-06-01")).unwrap());
+        let custom = get_custom_pricing(&conn).unwrap();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].effective_from, None);
+    }
+
+    #[test]
+    fn test_antipattern_history() {
+        use crate::patterns::types::Severity;
+
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::register_functions(&conn).unwrap();
+        crate::db::migrations::run(&conn).unwrap();
+
+        let pattern = |pattern_type, turn_number| {
+            DetectedPattern::new(
+                pattern_type,
+                Severity::Warning,
+                "s1".to_string(),
+                turn_number,
+                String::new(),
+                0.5,
+                String::new(),
+                4.0,
+                3.0,
+            )
+        };
+        let patterns = vec![
+            pattern(AntiPatternType::CostSpike, Some(2)),
+            pattern(AntiPatternType::CostSpike, Some(3)),
+            pattern(AntiPatternType::ContextChurn, None),
+        ];
+        assert!(!replace_session_antipatterns(&conn, "s1", &patterns, "2026-01-16T00:00:00Z").unwrap());
+
+        upsert_session_with_mtime(
+            &conn, "s1", "/Users/me/app", "app", None,
+            "2026-01-14T23:50:00.000Z", "2026-01-15T00:30:00.000Z",
+            "claude-opus-4-5-20251101", false, "/p/s1.jsonl", "m1",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO turns (turn_id, session_id, turn_number, started_at) VALUES ('s1:3', 's1', 3, '2026-01-15T00:10:00.000Z')",
+            [],
+        )
+        .unwrap();
+        // Storing again replaces the earlier detection
+        for _ in 0..2 {
+            assert!(replace_session_antipatterns(&conn, "s1", &patterns, "2026-01-16T00:00:00Z").unwrap());
+        }
+
+        // Turn 2 isn't stored, so it falls back to the session start
+        let history = get_antipattern_history_from_db(&conn, None).unwrap();
+        let days: Vec<(&str, AntiPatternType, u32)> = history
+            .iter()
+            .map(|h| (h.date.as_str(), h.pattern_type, h.occurrences))
+            .collect();
+        assert_eq!(
+            days,
+            vec![
+                ("2026-01-14", AntiPatternType::ContextChurn, 1),
+                ("2026-01-14", AntiPatternType::CostSpike, 1),
+                ("2026-01-15", AntiPatternType::CostSpike, 1),
+            ]
+        );
+        assert!((history[1].avg_severity_score - 2.0 * 4.0 / 3.0).abs() < 1e-9);
+
+        let frequencies = get_antipattern_frequencies_from_db(&conn, None).unwrap();
+        assert_eq!(
+            frequencies[0],
+            AntiPatternFrequencyRow { pattern_type: AntiPatternType::CostSpike, occurrences: 2, sessions: 1 }
+        );
+        assert!(get_antipattern_frequencies_from_db(&conn, Some(7)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_subagent_links_and_aggregates() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        crate::db::register_functions(&conn).unwrap();
+        conn.execute_batch(
+            "ALTER TABLE sessions ADD COLUMN turns_mtime TEXT;
+             ALTER TABLE sessions ADD COLUMN parent_session_id TEXT;",
+        )
+        .unwrap();
+
+        for (id, project, file_path, cost) in [
+            ("s1", "/Users/me/app", "/p/s1.jsonl", 1.0),
+            ("a1", "/Users/me/app", "/p/s1/subagents/agent-a1.jsonl", 0.5),
+            ("agent-1", "/Users/me/other", "/p/agent-1.jsonl", 0.25),
+        ] {
+            upsert_session_with_mtime(
+                &conn, id, project, "app", None,
+                "2026-01-14T07:44:28.531Z", "2026-01-14T07:44:30.000Z",
+                "claude-opus-4-5-20251101", false, file_path, "m1",
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_metrics (session_id, total_turns, total_cost) VALUES (?1, 1, ?2)",
+                params![id, cost],
+            )
+            .unwrap();
+        }
+
+        assert!(update_session_parent(&conn, "a1", "s1").unwrap());
+        // sample_turn spawns "agent-1"
+        replace_session_turns(&conn, "s1", &[sample_turn(1)], "m1").unwrap();
+        assert!(link_subagent_to_spawner(&conn, "agent-1").unwrap());
+        assert_eq!(link_spawned_subagents(&conn, "s1", &["agent-1".to_string()]).unwrap(), 0);
+
+        let costs = get_subagent_costs(&conn).unwrap();
+        assert_eq!(costs.len(), 1);
+        assert!((costs["s1"] - 0.75).abs() < 1e-9);
+
+        let agg = get_dashboard_summary_from_db(&conn, None, false).unwrap();
+        assert_eq!(agg.total_sessions, 3);
+        let agg = get_dashboard_summary_from_db(&conn, None, true).unwrap();
+        assert_eq!(agg.total_sessions, 1);
+        assert!((agg.total_cost - 1.75).abs() < 1e-9);
+
+        // Attributed, the other project's subagent moves to its parent's project
+        assert_eq!(get_project_metrics_from_db(&conn, None, false).unwrap().len(), 2);
+        let projects = get_project_metrics_from_db(&conn, None, true).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].project_path, "/Users/me/app");
+        assert_eq!(projects[0].session_count, 1);
+        assert!((projects[0].total_cost - 1.75).abs() < 1e-9);
+    }
+}
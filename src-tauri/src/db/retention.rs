@@ -0,0 +1,125 @@
+//! Data retention
+//!
+//! Deletes cached sessions that have been inactive for longer than a cutoff
+//! and compacts the database file. Turns, metrics, tool uses and the other
+//! per-session rows go with their session through `ON DELETE CASCADE`.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::DbError;
+
+/// Rows removed by a prune
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct PruneStats {
+    pub sessions_deleted: u32,
+    pub turns_deleted: u32,
+}
+
+/// Database size before and after a VACUUM
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct VacuumStats {
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// Delete sessions whose last activity is before `cutoff` (RFC 3339)
+pub fn prune_sessions_before(conn: &Connection, cutoff: &str) -> Result<PruneStats, DbError> {
+    const STALE: &str = "SELECT session_id FROM sessions WHERE last_activity_at < ?1";
+
+    let tx = conn.unchecked_transaction()?;
+
+    let turns_deleted: u32 = tx.query_row(
+        &format!("SELECT COUNT(*) FROM turns WHERE session_id IN ({})", STALE),
+        params![cutoff],
+        |row| row.get(0),
+    )?;
+
+    // Read positions of the deleted files would otherwise linger forever
+    tx.execute(
+        "DELETE FROM file_positions WHERE file_path IN \
+         (SELECT file_path FROM sessions WHERE last_activity_at < ?1)",
+        params![cutoff],
+    )?;
+    let sessions_deleted = tx.execute(
+        &format!("DELETE FROM sessions WHERE session_id IN ({})", STALE),
+        params![cutoff],
+    )? as u32;
+
+    tx.commit()?;
+
+    Ok(PruneStats {
+        sessions_deleted,
+        turns_deleted,
+    })
+}
+
+/// Current size of the database in bytes
+pub fn database_size(conn: &Connection) -> Result<u64, DbError> {
+    let page_count: u64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let page_size: u64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    Ok(page_count * page_size)
+}
+
+/// Rebuild the database file, returning the space freed by deletes to the OS
+pub fn vacuum(conn: &Connection) -> Result<VacuumStats, DbError> {
+    let size_before = database_size(conn)?;
+    conn.execute_batch("VACUUM;")?;
+    Ok(VacuumStats {
+        size_before,
+        size_after: database_size(conn)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::{session_exists, update_file_position, upsert_session_with_mtime};
+    use crate::db::schema;
+
+    fn add_session(conn: &Connection, id: &str, last_activity_at: &str) {
+        let file_path = format!("/tmp/{}.jsonl", id);
+        upsert_session_with_mtime(
+            conn, id, "/Users/me/app", "app", None,
+            last_activity_at, last_activity_at,
+            "claude-opus-4-5-20251101", false, &file_path, "m1",
+        )
+        .unwrap();
+        update_file_position(conn, &file_path, 100).unwrap();
+
+        conn.execute(
+            "INSERT INTO turns (turn_id, session_id, turn_number, started_at) VALUES (?1, ?2, 1, ?3)",
+            params![format!("{}-1", id), id, last_activity_at],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_prune_sessions_before() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+        schema::create_tables(&conn).unwrap();
+
+        add_session(&conn, "old", "2026-01-01T10:00:00.000Z");
+        add_session(&conn, "new", "2026-03-01T10:00:00.000Z");
+
+        let stats = prune_sessions_before(&conn, "2026-02-01T00:00:00Z").unwrap();
+        assert_eq!(stats, PruneStats { sessions_deleted: 1, turns_deleted: 1 });
+        assert!(!session_exists(&conn, "old").unwrap());
+        assert!(session_exists(&conn, "new").unwrap());
+
+        let remaining: u32 = conn
+            .query_row("SELECT COUNT(*) FROM turns", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 1);
+        let positions: u32 = conn
+            .query_row("SELECT COUNT(*) FROM file_positions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(positions, 1);
+
+        let vacuumed = vacuum(&conn).unwrap();
+        assert!(vacuumed.size_after <= vacuumed.size_before);
+    }
+}
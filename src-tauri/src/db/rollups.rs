@@ -0,0 +1,198 @@
+//! Daily rollups
+//!
+//! Daily metrics and trends read `daily_rollups`, one row per day, project and
+//! kind of session (user or subagent) with the totals of the sessions that
+//! started that day, so they cost the same however many sessions are stored.
+//! Days are reporting-timezone dates (see [`crate::timezone`]).
+//!
+//! The ingestion pipeline refreshes a session's day whenever it stores the
+//! session. Changes to many sessions at once (pruning, snapshot imports,
+//! recalculated costs) rebuild every row instead, and so does a change of
+//! timezone, which moves sessions between days.
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::DbError;
+use crate::timezone::ReportingTimezone;
+
+/// Totals of stored sessions with turns, grouped by day, project and whether
+/// they're subagent sessions; a filter on `s` and the GROUP BY follow
+const INSERT_ROLLUPS: &str = r#"
+    INSERT INTO daily_rollups (
+        day, project_path, subagent, session_count, user_session_count, subagent_session_count,
+        total_turns, total_cost, total_tokens, cache_read, cache_write
+    )
+    SELECT
+        local_date(s.started_at) AS day,
+        s.project_path,
+        CASE WHEN s.file_path LIKE '%/subagents/%' OR s.file_path LIKE '%/agent-%' THEN 1 ELSE 0 END AS subagent,
+        COUNT(*),
+        COUNT(CASE WHEN s.file_path NOT LIKE '%/subagents/%' AND s.file_path NOT LIKE '%/agent-%' THEN 1 END),
+        COUNT(CASE WHEN s.file_path LIKE '%/subagents/%' OR s.file_path LIKE '%/agent-%' THEN 1 END),
+        SUM(m.total_turns),
+        COALESCE(SUM(m.total_cost), 0.0),
+        COALESCE(SUM(m.total_input_tokens + m.total_output_tokens + m.total_cache_read + m.total_cache_write), 0),
+        COALESCE(SUM(m.total_cache_read), 0),
+        COALESCE(SUM(m.total_cache_write), 0)
+    FROM sessions s
+    JOIN session_metrics m ON m.session_id = s.session_id
+    WHERE m.total_turns > 0
+      AND s.started_at LIKE '20%'
+"#;
+
+const GROUP_ROLLUPS: &str = "GROUP BY day, s.project_path, subagent HAVING day IS NOT NULL";
+
+/// The timezone rollups are built in
+///
+/// The OS timezone is keyed by its current offset, so rollups are rebuilt
+/// when daylight saving time starts or ends or the machine moves timezone.
+fn timezone_key() -> String {
+    let tz = crate::settings::reporting_timezone();
+    match tz {
+        ReportingTimezone::Local => format!("local {}", tz.current_offset()),
+        ReportingTimezone::Fixed(_) => tz.current_offset(),
+    }
+}
+
+/// Reporting-timezone date a stored session is rolled up under
+pub fn session_day(conn: &Connection, session_id: &str) -> Result<Option<String>, DbError> {
+    let day = conn
+        .query_row(
+            "SELECT local_date(started_at) FROM sessions WHERE session_id = ?1 AND started_at LIKE '20%'",
+            params![session_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()?;
+    Ok(day.flatten())
+}
+
+/// Recompute the rollups of one day from the sessions that started on it
+///
+/// Runs in the caller's transaction, if any.
+pub fn refresh_day(conn: &Connection, day: &str) -> Result<(), DbError> {
+    conn.execute("DELETE FROM daily_rollups WHERE day = ?1", params![day])?;
+    // Timezones are at most 14 hours from UTC, so the UTC range narrows the
+    // scan to sessions the day can contain
+    conn.execute(
+        &format!(
+            "{INSERT_ROLLUPS}
+              AND s.started_at >= date(?1, '-1 day')
+              AND s.started_at < date(?1, '+2 days')
+              AND local_date(s.started_at) = ?1
+            {GROUP_ROLLUPS}"
+        ),
+        params![day],
+    )?;
+    Ok(())
+}
+
+/// Refresh the day of a session just stored
+///
+/// Storing a session again never changes its start, so it stays on the day
+/// it was first rolled up under.
+pub fn refresh_session(conn: &Connection, session_id: &str) -> Result<(), DbError> {
+    match session_day(conn, session_id)? {
+        Some(day) => refresh_day(conn, &day),
+        None => Ok(()),
+    }
+}
+
+/// Recompute every rollup in the current reporting timezone
+///
+/// Returns the number of rows written.
+pub fn rebuild(conn: &Connection) -> Result<u32, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM daily_rollups", [])?;
+    let rows = tx.execute(&format!("{INSERT_ROLLUPS} {GROUP_ROLLUPS}"), [])? as u32;
+    tx.execute(
+        r#"
+        INSERT INTO daily_rollups_state (id, timezone, rebuilt_at) VALUES (1, ?1, ?2)
+        ON CONFLICT(id) DO UPDATE SET timezone = excluded.timezone, rebuilt_at = excluded.rebuilt_at
+        "#,
+        params![timezone_key(), chrono::Utc::now().to_rfc3339()],
+    )?;
+    tx.commit()?;
+    Ok(rows)
+}
+
+/// Rebuild the rollups unless they were built in the current reporting
+/// timezone; returns whether they were rebuilt
+pub fn ensure_current(conn: &Connection) -> Result<bool, DbError> {
+    let built_in: Option<String> = conn
+        .query_row("SELECT timezone FROM daily_rollups_state WHERE id = 1", [], |row| row.get(0))
+        .optional()?;
+    if built_in.as_deref() == Some(timezone_key().as_str()) {
+        return Ok(false);
+    }
+    rebuild(conn)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::upsert_session_with_mtime;
+    use crate::db::{migrations, register_functions, schema};
+
+    fn store(conn: &Connection, id: &str, started_at: &str, file_path: &str, cost: f64) {
+        upsert_session_with_mtime(
+            conn, id, "/Users/me/app", "app", None,
+            started_at, started_at,
+            "claude-opus-4-5-20251101", false, file_path, "m1",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO session_metrics (session_id, total_turns, total_cost, total_cache_read, total_cache_write)
+             VALUES (?1, 2, ?2, 300, 100)",
+            params![id, cost],
+        )
+        .unwrap();
+    }
+
+    fn rollup(conn: &Connection, day: &str) -> Option<(u32, u32, f64)> {
+        conn.query_row(
+            "SELECT SUM(session_count), SUM(subagent_session_count), SUM(total_cost)
+             FROM daily_rollups WHERE day = ?1 HAVING COUNT(*) > 0",
+            params![day],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_daily_rollups() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::create_tables(&conn).unwrap();
+        migrations::run(&conn).unwrap();
+        register_functions(&conn).unwrap();
+
+        store(&conn, "s1", "2026-01-14T12:00:00Z", "/p/s1.jsonl", 1.0);
+        store(&conn, "s2", "2026-01-14T13:00:00Z", "/p/s1/subagents/agent-a.jsonl", 0.5);
+        assert!(ensure_current(&conn).unwrap());
+        assert!(!ensure_current(&conn).unwrap());
+        assert_eq!(rollup(&conn, "2026-01-14"), Some((2, 1, 1.5)));
+        let subagent_cost: f64 = conn
+            .query_row("SELECT total_cost FROM daily_rollups WHERE day = '2026-01-14' AND subagent = 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(subagent_cost, 0.5);
+
+        // A new session only shows up once its day is refreshed
+        store(&conn, "s3", "2026-01-14T14:00:00Z", "/p/s3.jsonl", 2.0);
+        assert_eq!(rollup(&conn, "2026-01-14"), Some((2, 1, 1.5)));
+        refresh_session(&conn, "s3").unwrap();
+        assert_eq!(rollup(&conn, "2026-01-14"), Some((3, 1, 3.5)));
+        assert_eq!(session_day(&conn, "s3").unwrap().as_deref(), Some("2026-01-14"));
+
+        store(&conn, "s4", "2026-01-15T14:00:00Z", "/p/s4.jsonl", 2.0);
+        refresh_session(&conn, "s4").unwrap();
+        assert_eq!(rollup(&conn, "2026-01-15"), Some((1, 0, 2.0)));
+        assert_eq!(rollup(&conn, "2026-01-14"), Some((3, 1, 3.5)));
+
+        conn.execute("DELETE FROM sessions WHERE session_id = 's4'", []).unwrap();
+        assert_eq!(rebuild(&conn).unwrap(), 2);
+        assert_eq!(rollup(&conn, "2026-01-15"), None);
+    }
+}
@@ -19,6 +19,7 @@ CREATE TABLE IF NOT EXISTS sessions (
     is_active INTEGER DEFAULT 1,
     file_path TEXT NOT NULL,
     file_mtime TEXT,
+    is_remote INTEGER DEFAULT 0,
     created_at TEXT DEFAULT CURRENT_TIMESTAMP,
     updated_at TEXT DEFAULT CURRENT_TIMESTAMP
 );
@@ -39,6 +40,14 @@ CREATE TABLE IF NOT EXISTS turns (
     assistant_message TEXT,
     model TEXT,
     stop_reason TEXT,
+    start_uuid TEXT,
+    end_uuid TEXT,
+    subagent_ids TEXT,
+    entry_count INTEGER DEFAULT 0,
+    compactions TEXT,
+    provider TEXT,
+    api_errors TEXT,
+    thinking_level TEXT,
     created_at TEXT DEFAULT CURRENT_TIMESTAMP,
     UNIQUE(session_id, turn_number)
 );
@@ -56,7 +65,8 @@ CREATE TABLE IF NOT EXISTS turn_metrics (
     cache_write_1h_tokens INTEGER DEFAULT 0,
     total_cost REAL DEFAULT 0.0,
     context_usage_pct REAL DEFAULT 0.0,
-    tool_count INTEGER DEFAULT 0
+    tool_count INTEGER DEFAULT 0,
+    thinking_tokens INTEGER DEFAULT 0
 );
 
 -- Session aggregated metrics
@@ -101,6 +111,7 @@ CREATE TABLE IF NOT EXISTS code_changes (
     lines_added INTEGER DEFAULT 0,
     lines_removed INTEGER DEFAULT 0,
     extension TEXT,
+    tool_name TEXT,
     created_at TEXT DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -144,7 +155,7 @@ CREATE TABLE IF NOT EXISTS file_positions (
 
 -- Model pricing configuration
 CREATE TABLE IF NOT EXISTS pricing (
-    model_id TEXT PRIMARY KEY,
+    model_id TEXT NOT NULL,
     display_name TEXT NOT NULL,
     input_price_per_million REAL NOT NULL,
     output_price_per_million REAL NOT NULL,
@@ -152,16 +163,50 @@ CREATE TABLE IF NOT EXISTS pricing (
     cache_write_1h_per_million REAL NOT NULL,
     cache_read_per_million REAL NOT NULL,
     max_context_tokens INTEGER,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    is_custom INTEGER DEFAULT 0,
+    provider TEXT NOT NULL DEFAULT 'anthropic',
+    effective_from TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY (model_id, provider, effective_from)
+);
+
+-- Directories sessions were imported from, besides ~/.claude
+CREATE TABLE IF NOT EXISTS import_sources (
+    path TEXT PRIMARY KEY,
+    label TEXT NOT NULL,
+    imported_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+-- User settings (one JSON value per key)
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
     updated_at TEXT DEFAULT CURRENT_TIMESTAMP
 );
 "#;
 
 /// Default pricing data for Claude models
+///
+/// Rows the user has overridden (is_custom = 1) are left untouched.
 const DEFAULT_PRICING: &str = r#"
-INSERT OR REPLACE INTO pricing VALUES
+INSERT INTO pricing (
+    model_id, display_name, input_price_per_million, output_price_per_million,
+    cache_write_5m_per_million, cache_write_1h_per_million, cache_read_per_million,
+    max_context_tokens, updated_at
+) VALUES
     ('claude-opus-4-5-20251101', 'Claude Opus 4.5', 5.00, 25.00, 6.25, 10.00, 0.50, 200000, CURRENT_TIMESTAMP),
     ('claude-sonnet-4-5-20251101', 'Claude Sonnet 4.5', 3.00, 15.00, 3.75, 6.00, 0.30, 200000, CURRENT_TIMESTAMP),
-    ('claude-haiku-4-5-20251101', 'Claude Haiku 4.5', 1.00, 5.00, 1.25, 2.00, 0.10, 200000, CURRENT_TIMESTAMP);
+    ('claude-haiku-4-5-20251101', 'Claude Haiku 4.5', 1.00, 5.00, 1.25, 2.00, 0.10, 200000, CURRENT_TIMESTAMP)
+ON CONFLICT(model_id, provider, effective_from) DO UPDATE SET
+    display_name = excluded.display_name,
+    input_price_per_million = excluded.input_price_per_million,
+    output_price_per_million = excluded.output_price_per_million,
+    cache_write_5m_per_million = excluded.cache_write_5m_per_million,
+    cache_write_1h_per_million = excluded.cache_write_1h_per_million,
+    cache_read_per_million = excluded.cache_read_per_million,
+    max_context_tokens = excluded.max_context_tokens,
+    updated_at = excluded.updated_at
+WHERE pricing.is_custom = 0;
 "#;
 
 /// Create all database tables
@@ -0,0 +1,622 @@
+//! Database snapshots for moving analytics between machines
+//!
+//! A snapshot is a compacted copy of the whole database written with
+//! `VACUUM INTO`. Importing one merges its sessions into the local database:
+//! a session is taken from the snapshot when it is missing locally or its
+//! last activity is later than the local copy, and then replaces the local
+//! session with all its turns, metrics and tool uses. Ties keep the local
+//! copy. A replaced session keeps pointing at its local file; sessions whose
+//! file isn't on this machine are marked remote. Machine-local tables (read positions, pricing, settings, import
+//! sources) are never imported.
+//!
+//! The key of encrypted message content stays in this machine's keychain,
+//! so snapshots are written with their content decrypted. Content still
+//! encrypted in a snapshot (written by an older version) can't be read here
+//! and is imported as NULL.
+//!
+//! A shared folder (Dropbox, a network drive) works as a sync point: each
+//! machine writes its own snapshot there and merges everyone else's.
+//!
+//! A new machine can also start from a snapshot instead of parsing every
+//! session file: bootstrapping imports it into the empty database and marks
+//! the sessions whose files aren't on this machine as remote. Remote
+//! sessions are served from their stored metrics and turns only.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::{crypto, DbError};
+
+/// Tables holding a session's data, parents before children. Each entry is
+/// the table and the condition selecting the rows of the sessions in
+/// `temp.snapshot_winners`.
+const SESSION_TABLES: &[(&str, &str)] = &[
+    ("sessions", "session_id IN (SELECT session_id FROM temp.snapshot_winners)"),
+    ("turns", "session_id IN (SELECT session_id FROM temp.snapshot_winners)"),
+    ("turn_metrics", WINNER_TURNS),
+    ("tool_uses", WINNER_TURNS),
+    ("code_changes", WINNER_TURNS),
+    ("session_metrics", "session_id IN (SELECT session_id FROM temp.snapshot_winners)"),
+    ("subagents", "session_id IN (SELECT session_id FROM temp.snapshot_winners)"),
+    ("git_info", "session_id IN (SELECT session_id FROM temp.snapshot_winners)"),
+    ("antipatterns", "session_id IN (SELECT session_id FROM temp.snapshot_winners)"),
+];
+
+const WINNER_TURNS: &str = "turn_id IN (SELECT turn_id FROM snap.turns \
+     WHERE session_id IN (SELECT session_id FROM temp.snapshot_winners))";
+
+/// Outcome of merging a snapshot
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SnapshotImportStats {
+    /// Sessions that weren't in the local database
+    pub sessions_added: u32,
+    /// Local sessions replaced by a more recent copy
+    pub sessions_updated: u32,
+    /// Sessions whose local copy is as recent or more
+    pub sessions_skipped: u32,
+    /// Dismissed or snoozed recommendations taken from the snapshot
+    pub dismissals_merged: u32,
+}
+
+/// Outcome of bootstrapping from a snapshot
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BootstrapStats {
+    pub sessions_imported: u32,
+    /// Sessions whose files are also on this machine
+    pub sessions_local: u32,
+    /// Sessions whose files are only on the machine the snapshot came from
+    pub sessions_remote: u32,
+    pub dismissals_merged: u32,
+}
+
+/// Fill the empty database from the snapshot at `path`
+///
+/// `local_files` maps the IDs of the session files found on this machine to
+/// their paths. Imported sessions among them point at the local file;
+/// the others are marked remote. Fails if the database already has
+/// sessions, which [`import_snapshot`] merges into instead.
+pub fn bootstrap_snapshot(
+    conn: &Connection,
+    path: &Path,
+    local_files: &HashMap<String, PathBuf>,
+) -> Result<BootstrapStats, DbError> {
+    let existing: u32 = conn.query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))?;
+    if existing > 0 {
+        return Err(DbError::Snapshot(format!(
+            "the database already has {} sessions; import the snapshot to merge it instead",
+            existing
+        )));
+    }
+
+    let imported = import_snapshot(conn, path)?;
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("UPDATE sessions SET is_remote = 1", [])?;
+    let mut sessions_local = 0;
+    {
+        let mut stmt = tx.prepare("UPDATE sessions SET is_remote = 0, file_path = ?2 WHERE session_id = ?1")?;
+        for (session_id, file) in local_files {
+            sessions_local += stmt.execute(params![session_id, file.to_string_lossy()])? as u32;
+        }
+    }
+    tx.commit()?;
+
+    Ok(BootstrapStats {
+        sessions_imported: imported.sessions_added,
+        sessions_local,
+        sessions_remote: imported.sessions_added - sessions_local,
+        dismissals_merged: imported.dismissals_merged,
+    })
+}
+
+/// Write a snapshot of the database to `path`, replacing any file there
+pub fn export_snapshot(conn: &Connection, path: &Path) -> Result<(), DbError> {
+    // VACUUM INTO refuses to overwrite, and a half-written file must never
+    // be picked up by another machine syncing the same folder
+    let tmp = path.with_extension("db.partial");
+    let _ = std::fs::remove_file(&tmp);
+    conn.execute("VACUUM INTO ?1", params![tmp.to_string_lossy()])?;
+    if crypto::has_encrypted_content(conn)? {
+        if let Err(e) = decrypt_copy(&tmp) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+    }
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        DbError::Snapshot(format!("{}: {}", path.display(), e))
+    })
+}
+
+/// Decrypt the content of the database copy at `path`
+fn decrypt_copy(path: &Path) -> Result<(), DbError> {
+    let copy = Connection::open(path)?;
+    crypto::decrypt_for_export(&copy)?;
+    copy.close().map_err(|(_, e)| e)?;
+    Ok(())
+}
+
+/// Merge the sessions of the snapshot at `path` into the database
+pub fn import_snapshot(conn: &Connection, path: &Path) -> Result<SnapshotImportStats, DbError> {
+    if !path.is_file() {
+        return Err(DbError::Snapshot(format!("{} does not exist", path.display())));
+    }
+    conn.execute("ATTACH DATABASE ?1 AS snap", params![read_only_uri(path)])?;
+    let result = merge_attached(conn);
+    let detached = conn.execute_batch("DETACH DATABASE snap");
+    let stats = result?;
+    detached?;
+    Ok(stats)
+}
+
+fn merge_attached(conn: &Connection) -> Result<SnapshotImportStats, DbError> {
+    if table_columns(conn, "snap", "sessions")?.is_empty() {
+        return Err(DbError::Snapshot("not an ironhide database".to_string()));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let mut stats = SnapshotImportStats::default();
+
+    tx.execute_batch(
+        "DROP TABLE IF EXISTS temp.snapshot_winners;
+         CREATE TEMP TABLE snapshot_winners (
+             session_id TEXT PRIMARY KEY, is_new INTEGER NOT NULL, local_path TEXT
+         );
+         INSERT INTO temp.snapshot_winners
+         SELECT s.session_id, l.session_id IS NULL, l.file_path
+         FROM snap.sessions s
+         LEFT JOIN main.sessions l ON l.session_id = s.session_id
+         WHERE l.session_id IS NULL OR s.last_activity_at > l.last_activity_at;",
+    )?;
+    let (added, updated): (u32, u32) = tx.query_row(
+        "SELECT COALESCE(SUM(is_new), 0), COALESCE(SUM(1 - is_new), 0) FROM temp.snapshot_winners",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let total: u32 = tx.query_row("SELECT COUNT(*) FROM snap.sessions", [], |row| row.get(0))?;
+    stats.sessions_added = added;
+    stats.sessions_updated = updated;
+    stats.sessions_skipped = total - added - updated;
+
+    // Turns, metrics and the other rows follow through ON DELETE CASCADE
+    tx.execute(
+        "DELETE FROM main.sessions WHERE session_id IN (SELECT session_id FROM temp.snapshot_winners)",
+        [],
+    )?;
+
+    for (table, condition) in SESSION_TABLES {
+        let columns = shared_columns(&tx, table)?;
+        if columns.is_empty() {
+            continue;
+        }
+        let values: Vec<String> = columns.iter().map(|c| crypto::foreign_column_sql(table, c)).collect();
+        let (columns, values) = (columns.join(", "), values.join(", "));
+        tx.execute(
+            &format!(
+                "INSERT INTO main.{table} ({columns}) SELECT {values} FROM snap.{table} WHERE {condition}"
+            ),
+            [],
+        )?;
+    }
+
+    mark_remote_winners(&tx)?;
+
+    if !table_columns(&tx, "snap", "recommendations_feedback")?.is_empty() {
+        let columns = shared_columns(&tx, "recommendations_feedback")?;
+        let updates: Vec<String> = columns
+            .iter()
+            .filter(|c| *c != "recommendation_id")
+            .map(|c| format!("{c} = excluded.{c}"))
+            .collect();
+        let columns = columns.join(", ");
+        // WHERE true keeps the parser from reading ON CONFLICT as a join
+        stats.dismissals_merged = tx.execute(
+            &format!(
+                "INSERT INTO main.recommendations_feedback ({columns}) \
+                 SELECT {columns} FROM snap.recommendations_feedback WHERE true \
+                 ON CONFLICT(recommendation_id) DO UPDATE SET {} \
+                 WHERE excluded.dismissed_at > recommendations_feedback.dismissed_at",
+                updates.join(", ")
+            ),
+            [],
+        )? as u32;
+    }
+
+    tx.execute_batch("DROP TABLE temp.snapshot_winners;")?;
+    tx.commit()?;
+    Ok(stats)
+}
+
+/// Point the merged sessions at their file on this machine, or mark them
+/// remote when there is none so they are served from their stored turns
+fn mark_remote_winners(conn: &Connection) -> Result<(), DbError> {
+    let winners: Vec<(String, Option<String>, Option<String>)> = conn
+        .prepare(
+            "SELECT w.session_id, w.local_path, s.file_path FROM temp.snapshot_winners w \
+             JOIN main.sessions s ON s.session_id = w.session_id",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare("UPDATE main.sessions SET is_remote = ?2, file_path = ?3 WHERE session_id = ?1")?;
+    let on_this_machine = |p: &Option<String>| p.as_ref().filter(|p| Path::new(p).is_file()).cloned();
+    for (session_id, local_path, file_path) in winners {
+        let (is_remote, file_path) = match on_this_machine(&local_path).or_else(|| on_this_machine(&file_path)) {
+            Some(path) => (false, Some(path)),
+            None => (true, file_path),
+        };
+        stmt.execute(params![session_id, is_remote, file_path])?;
+    }
+    Ok(())
+}
+
+/// Columns of `schema.table`, empty when the table doesn't exist
+fn table_columns(conn: &Connection, schema: &str, table: &str) -> Result<Vec<String>, DbError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {schema}.table_info({table})"))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
+/// Columns a table has both locally and in the snapshot, so snapshots from
+/// older or newer versions still import. Generated row IDs are left to the
+/// local database.
+fn shared_columns(conn: &Connection, table: &str) -> Result<Vec<String>, DbError> {
+    let theirs = table_columns(conn, "snap", table)?;
+    Ok(table_columns(conn, "main", table)?
+        .into_iter()
+        .filter(|c| theirs.contains(c) && !(table == "antipatterns" && c == "id"))
+        .collect())
+}
+
+/// SQLite URI opening `path` read-only
+fn read_only_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let escaped = path.replace('%', "%25").replace('?', "%3F").replace('#', "%23");
+    match escaped.starts_with('/') {
+        true => format!("file:{}?mode=ro", escaped),
+        false => format!("file:/{}?mode=ro", escaped),
+    }
+}
+
+/// Snapshots exchanged through a sync folder
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SnapshotSyncReport {
+    /// The snapshot written for this machine
+    pub exported_path: String,
+    /// Other machines' snapshots that were merged
+    pub imported: Vec<String>,
+    /// Snapshots that could not be merged, with the reason
+    pub failed: Vec<String>,
+    pub stats: SnapshotImportStats,
+}
+
+/// Merge every other machine's snapshot in `folder`, then write this
+/// machine's own as `ironhide-<machine>.db`
+///
+/// Importing first means the snapshot written afterwards already carries
+/// the merged sessions, so machines converge even if they rarely overlap.
+pub fn sync_snapshot_folder(conn: &Connection, folder: &Path) -> Result<SnapshotSyncReport, DbError> {
+    std::fs::create_dir_all(folder)
+        .map_err(|e| DbError::Snapshot(format!("{}: {}", folder.display(), e)))?;
+    let own = folder.join(format!("ironhide-{}.db", machine_name()));
+
+    let mut others: Vec<PathBuf> = std::fs::read_dir(folder)
+        .map_err(|e| DbError::Snapshot(format!("{}: {}", folder.display(), e)))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "db") && *p != own)
+        .collect();
+    others.sort();
+
+    let mut report = SnapshotSyncReport::default();
+    for path in others {
+        match import_snapshot(conn, &path) {
+            Ok(stats) => {
+                report.stats.sessions_added += stats.sessions_added;
+                report.stats.sessions_updated += stats.sessions_updated;
+                report.stats.sessions_skipped += stats.sessions_skipped;
+                report.stats.dismissals_merged += stats.dismissals_merged;
+                report.imported.push(path.to_string_lossy().to_string());
+            }
+            Err(e) => report.failed.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    export_snapshot(conn, &own)?;
+    report.exported_path = own.to_string_lossy().to_string();
+    Ok(report)
+}
+
+/// This machine's host name, made safe for a file name
+fn machine_name() -> String {
+    let name = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| {
+            std::process::Command::new("hostname")
+                .output()
+                .ok()
+                .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        })
+        .unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    match name.is_empty() {
+        true => "local".to_string(),
+        false => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::{session_exists, upsert_session_with_mtime};
+    use crate::db::Database;
+
+    fn add_session(conn: &Connection, id: &str, last_activity_at: &str, turns: u32) {
+        upsert_session_with_mtime(
+            conn, id, "/Users/me/app", "app", None,
+            "2026-01-01T10:00:00.000Z", last_activity_at,
+            "claude-opus-4-5-20251101", false, &format!("/tmp/{}.jsonl", id), "m1",
+        )
+        .unwrap();
+        for n in 1..=turns {
+            let turn_id = format!("{}-{}", id, n);
+            conn.execute(
+                "INSERT INTO turns (turn_id, session_id, turn_number, started_at) VALUES (?1, ?2, ?3, ?4)",
+                params![turn_id, id, n, last_activity_at],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO turn_metrics (turn_id, input_tokens) VALUES (?1, 100)",
+                params![turn_id],
+            )
+            .unwrap();
+        }
+    }
+
+    fn turn_count(conn: &Connection, id: &str) -> u32 {
+        conn.query_row("SELECT COUNT(*) FROM turns WHERE session_id = ?1", params![id], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let dir = std::env::temp_dir().join("ironhide_snapshot_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let laptop = Database::new(dir.join("laptop.db")).unwrap();
+        laptop.initialize().unwrap();
+        let desktop = Database::new(dir.join("desktop.db")).unwrap();
+        desktop.initialize().unwrap();
+
+        laptop
+            .with_connection(|conn| {
+                add_session(conn, "only-laptop", "2026-02-01T10:00:00.000Z", 2);
+                add_session(conn, "shared-newer", "2026-02-03T10:00:00.000Z", 3);
+                add_session(conn, "shared-older", "2026-02-01T10:00:00.000Z", 1);
+                conn.execute(
+                    "INSERT INTO recommendations_feedback (recommendation_id, rec_type, dismissed_at) \
+                     VALUES ('rec-1', 'model', '2026-02-02T00:00:00Z')",
+                    [],
+                )?;
+                Ok(())
+            })
+            .unwrap();
+        desktop
+            .with_connection(|conn| {
+                add_session(conn, "shared-newer", "2026-02-02T10:00:00.000Z", 1);
+                add_session(conn, "shared-older", "2026-02-05T10:00:00.000Z", 4);
+                Ok(())
+            })
+            .unwrap();
+
+        let snapshot = dir.join("laptop-snapshot.db");
+        laptop.with_connection(|conn| export_snapshot(conn, &snapshot)).unwrap();
+
+        let stats = desktop.with_connection(|conn| import_snapshot(conn, &snapshot)).unwrap();
+        assert_eq!(
+            stats,
+            SnapshotImportStats {
+                sessions_added: 1,
+                sessions_updated: 1,
+                sessions_skipped: 1,
+                dismissals_merged: 1,
+            }
+        );
+        desktop
+            .with_connection(|conn| {
+                assert!(session_exists(conn, "only-laptop")?);
+                assert_eq!(turn_count(conn, "only-laptop"), 2);
+                // The laptop's copy replaced the desktop's turns entirely
+                assert_eq!(turn_count(conn, "shared-newer"), 3);
+                let metrics: u32 = conn.query_row("SELECT COUNT(*) FROM turn_metrics", [], |row| row.get(0))?;
+                assert_eq!(metrics, 2 + 3 + 4);
+                // The desktop's more recent copy is kept
+                assert_eq!(turn_count(conn, "shared-older"), 4);
+                Ok(())
+            })
+            .unwrap();
+
+        // Importing the same snapshot again changes nothing
+        let again = desktop.with_connection(|conn| import_snapshot(conn, &snapshot)).unwrap();
+        assert_eq!(again.sessions_skipped, 3);
+        assert_eq!(again.sessions_added + again.sessions_updated, 0);
+
+        let not_a_snapshot = dir.join("notes.db");
+        Connection::open(&not_a_snapshot).unwrap().execute_batch("CREATE TABLE notes (x);").unwrap();
+        assert!(matches!(
+            desktop.with_connection(|conn| import_snapshot(conn, &not_a_snapshot)),
+            Err(DbError::Snapshot(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_import_marks_remote_sessions() {
+        use crate::db::queries::{get_remote_session_info, get_session_turns_from_db};
+
+        let dir = std::env::temp_dir().join("ironhide_snapshot_remote_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // One session's file was synced to this machine, the other's wasn't
+        let synced = dir.join("synced.jsonl");
+        std::fs::write(&synced, "").unwrap();
+        let laptop = Database::new(dir.join("laptop.db")).unwrap();
+        laptop.initialize().unwrap();
+        laptop
+            .with_connection(|conn| {
+                add_session(conn, "synced", "2026-02-01T10:00:00.000Z", 1);
+                add_session(conn, "laptop-only", "2026-02-01T10:00:00.000Z", 2);
+                conn.execute(
+                    "UPDATE sessions SET file_path = ?1 WHERE session_id = 'synced'",
+                    params![synced.to_string_lossy()],
+                )?;
+                conn.execute(
+                    "UPDATE sessions SET file_path = '/nonexistent/laptop-only.jsonl' WHERE session_id = 'laptop-only'",
+                    [],
+                )?;
+                Ok(())
+            })
+            .unwrap();
+        let snapshot = dir.join("laptop-snapshot.db");
+        laptop.with_connection(|conn| export_snapshot(conn, &snapshot)).unwrap();
+
+        let desktop = Database::new(dir.join("desktop.db")).unwrap();
+        desktop.initialize().unwrap();
+        desktop.with_connection(|conn| import_snapshot(conn, &snapshot)).unwrap();
+        desktop
+            .with_connection(|conn| {
+                assert!(get_remote_session_info(conn, "synced")?.is_none());
+                let info = get_remote_session_info(conn, "laptop-only")?.expect("remote session");
+                assert_eq!(info.path, PathBuf::from("/nonexistent/laptop-only.jsonl"));
+                assert_eq!(get_session_turns_from_db(conn, "laptop-only")?.len(), 2);
+                Ok(())
+            })
+            .unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_import_encrypted_snapshot() {
+        let dir = std::env::temp_dir().join("ironhide_encrypted_snapshot_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Content encrypted with the laptop's key, which this machine lacks
+        let laptop_key = crypto::FieldCipher::new(&crypto::FieldCipher::generate_key().unwrap());
+        let laptop = Database::new(dir.join("laptop.db")).unwrap();
+        laptop.initialize().unwrap();
+        laptop
+            .with_connection(|conn| {
+                add_session(conn, "s1", "2026-02-01T10:00:00.000Z", 2);
+                conn.execute(
+                    "UPDATE turns SET user_message = ?1 WHERE turn_id = 's1-1'",
+                    params![laptop_key.encrypt("secret prompt")?],
+                )?;
+                conn.execute("UPDATE turns SET user_message = 'plain prompt' WHERE turn_id = 's1-2'", [])?;
+                Ok(())
+            })
+            .unwrap();
+
+        // Written decrypted; without the key here the value is cleared
+        let snapshot = dir.join("snapshot.db");
+        laptop.with_connection(|conn| export_snapshot(conn, &snapshot)).unwrap();
+        let copy = Connection::open(&snapshot).unwrap();
+        assert!(!crypto::has_encrypted_content(&copy).unwrap());
+        drop(copy);
+
+        // An old snapshot holding encrypted values imports them as NULL
+        let desktop = Database::new(dir.join("desktop.db")).unwrap();
+        desktop.initialize().unwrap();
+        let stats = desktop.with_connection(|conn| import_snapshot(conn, &dir.join("laptop.db"))).unwrap();
+        assert_eq!(stats.sessions_added, 1);
+        desktop
+            .with_connection(|conn| {
+                assert!(!crypto::has_encrypted_content(conn)?);
+                let messages: Vec<Option<String>> = conn
+                    .prepare("SELECT user_message FROM turns ORDER BY turn_number")?
+                    .query_map([], |row| row.get(0))?
+                    .collect::<Result<_, _>>()?;
+                assert_eq!(messages, vec![None, Some("plain prompt".to_string())]);
+                assert_eq!(turn_count(conn, "s1"), 2);
+                Ok(())
+            })
+            .unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_bootstrap_snapshot() {
+        let dir = std::env::temp_dir().join("ironhide_bootstrap_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let old = Database::new(dir.join("old.db")).unwrap();
+        old.initialize().unwrap();
+        old.with_connection(|conn| {
+            add_session(conn, "here", "2026-02-01T10:00:00.000Z", 2);
+            add_session(conn, "elsewhere", "2026-02-02T10:00:00.000Z", 1);
+            Ok(())
+        })
+        .unwrap();
+        let snapshot = dir.join("snapshot.db");
+        old.with_connection(|conn| export_snapshot(conn, &snapshot)).unwrap();
+
+        let new = Database::new(dir.join("new.db")).unwrap();
+        new.initialize().unwrap();
+        let local_path = dir.join("here.jsonl");
+        let local_files = HashMap::from([
+            ("here".to_string(), local_path.clone()),
+            ("not-in-snapshot".to_string(), dir.join("other.jsonl")),
+        ]);
+        let stats = new
+            .with_connection(|conn| bootstrap_snapshot(conn, &snapshot, &local_files))
+            .unwrap();
+        assert_eq!(
+            stats,
+            BootstrapStats {
+                sessions_imported: 2,
+                sessions_local: 1,
+                sessions_remote: 1,
+                dismissals_merged: 0,
+            }
+        );
+        new.with_connection(|conn| {
+            let (remote, file_path): (bool, String) = conn.query_row(
+                "SELECT is_remote, file_path FROM sessions WHERE session_id = 'here'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            assert!(!remote);
+            assert_eq!(file_path, local_path.to_string_lossy());
+            let remote: bool =
+                conn.query_row("SELECT is_remote FROM sessions WHERE session_id = 'elsewhere'", [], |row| row.get(0))?;
+            assert!(remote);
+            assert_eq!(turn_count(conn, "elsewhere"), 1);
+            Ok(())
+        })
+        .unwrap();
+
+        // Only an empty database can be bootstrapped
+        assert!(matches!(
+            new.with_connection(|conn| bootstrap_snapshot(conn, &snapshot, &local_files)),
+            Err(DbError::Snapshot(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
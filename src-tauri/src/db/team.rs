@@ -0,0 +1,374 @@
+//! Team roll-up of other users' session exports
+//!
+//! Teammates who run Ironhide themselves export their sessions as JSON
+//! (`export_sessions` with the json format), and each file is imported here
+//! under the teammate's name. Imported sessions go to their own `team_*`
+//! tables rather than `sessions`, so the personal dashboard never counts
+//! them; the queries below aggregate them with per-user attribution.
+//! Importing a newer export of the same user updates their sessions in place.
+
+use std::collections::BTreeMap;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::DbError;
+use crate::export::ExportableSession;
+
+/// The parts of an `export_sessions` JSON file the roll-up reads
+#[derive(Debug, Clone, Deserialize)]
+pub struct TeamExportFile {
+    #[serde(default)]
+    pub export_date: Option<String>,
+    pub sessions: Vec<TeamExportEntry>,
+}
+
+/// One session of an export; turn details aren't imported
+#[derive(Debug, Clone, Deserialize)]
+pub struct TeamExportEntry {
+    pub session: ExportableSession,
+}
+
+/// Outcome of importing one user's export
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TeamImportStats {
+    pub user_name: String,
+    pub sessions_added: u32,
+    /// Sessions already imported for this user, replaced by the new export
+    pub sessions_updated: u32,
+}
+
+/// A user with imported sessions
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TeamMember {
+    pub user_name: String,
+    pub session_count: u32,
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    pub first_session_at: Option<String>,
+    pub last_session_at: Option<String>,
+    pub last_imported_at: Option<String>,
+}
+
+/// One user's share of the team totals
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TeamMemberStats {
+    pub user_name: String,
+    pub session_count: u32,
+    pub total_turns: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    /// Fraction of the team's cost (0-1)
+    pub cost_share: f64,
+    pub avg_cost_per_session: f64,
+    pub avg_efficiency: Option<f64>,
+}
+
+/// Team totals over a period, broken down by user
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TeamDashboard {
+    pub total_sessions: u32,
+    pub total_turns: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub avg_efficiency: Option<f64>,
+    /// Costliest users first
+    pub members: Vec<TeamMemberStats>,
+}
+
+/// One user's usage on one day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TeamUserDay {
+    pub user_name: String,
+    pub session_count: u32,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+}
+
+/// Team usage on one day, with each user's part
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TeamDailyTrend {
+    pub date: String,
+    pub session_count: u32,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub by_user: Vec<TeamUserDay>,
+}
+
+/// Sessions inside the period; `?1` is the number of days, or NULL for all
+const PERIOD_FILTER: &str = "(?1 IS NULL OR (started_at LIKE '20%' \
+     AND local_date(started_at) >= date(local_date('now'), '-' || ?1 || ' days')))";
+
+/// Store a user's exported sessions, replacing earlier imports of the same sessions
+pub fn import_team_export(
+    conn: &Connection,
+    user_name: &str,
+    source_path: &str,
+    export: &TeamExportFile,
+    imported_at: &str,
+) -> Result<TeamImportStats, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO team_imports (user_name, source_path, export_date, imported_at, session_count)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![user_name, source_path, export.export_date, imported_at, export.sessions.len() as u32],
+    )?;
+    let import_id = tx.last_insert_rowid();
+
+    let mut stats = TeamImportStats {
+        user_name: user_name.to_string(),
+        ..Default::default()
+    };
+    {
+        let mut exists = tx.prepare("SELECT 1 FROM team_sessions WHERE user_name = ?1 AND session_id = ?2")?;
+        let mut upsert = tx.prepare(
+            "INSERT INTO team_sessions
+                (user_name, session_id, started_at, project_name, model, turns, tokens, cost,
+                 duration_ms, efficiency_score, import_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT(user_name, session_id) DO UPDATE SET
+                started_at = excluded.started_at,
+                project_name = excluded.project_name,
+                model = excluded.model,
+                turns = excluded.turns,
+                tokens = excluded.tokens,
+                cost = excluded.cost,
+                duration_ms = excluded.duration_ms,
+                efficiency_score = excluded.efficiency_score,
+                import_id = excluded.import_id",
+        )?;
+        for entry in &export.sessions {
+            let s = &entry.session;
+            if exists.exists(params![user_name, s.session_id])? {
+                stats.sessions_updated += 1;
+            } else {
+                stats.sessions_added += 1;
+            }
+            upsert.execute(params![
+                user_name,
+                s.session_id,
+                s.date,
+                s.project_name,
+                s.model,
+                s.turns,
+                s.tokens as i64,
+                s.cost,
+                s.duration_ms as i64,
+                s.efficiency_score,
+                import_id,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(stats)
+}
+
+/// Every user with imported sessions, by name
+pub fn get_team_members(conn: &Connection) -> Result<Vec<TeamMember>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT s.user_name, COUNT(*), COALESCE(SUM(s.cost), 0.0), COALESCE(SUM(s.tokens), 0),
+                MIN(s.started_at), MAX(s.started_at),
+                (SELECT MAX(i.imported_at) FROM team_imports i WHERE i.user_name = s.user_name)
+         FROM team_sessions s
+         GROUP BY s.user_name
+         ORDER BY s.user_name",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(TeamMember {
+            user_name: row.get(0)?,
+            session_count: row.get(1)?,
+            total_cost: row.get(2)?,
+            total_tokens: row.get::<_, i64>(3)? as u64,
+            first_session_at: row.get(4)?,
+            last_session_at: row.get(5)?,
+            last_imported_at: row.get(6)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Drop a user's imported sessions and import history; returns the sessions removed
+pub fn remove_team_member(conn: &Connection, user_name: &str) -> Result<u32, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let removed = tx.execute("DELETE FROM team_sessions WHERE user_name = ?1", params![user_name])?;
+    tx.execute("DELETE FROM team_imports WHERE user_name = ?1", params![user_name])?;
+    tx.commit()?;
+    Ok(removed as u32)
+}
+
+/// Team totals for the last `days` days (all time when None), per user
+pub fn get_team_dashboard(conn: &Connection, days: Option<u32>) -> Result<TeamDashboard, DbError> {
+    let sql = format!(
+        "SELECT user_name, COUNT(*), COALESCE(SUM(turns), 0), COALESCE(SUM(tokens), 0),
+                COALESCE(SUM(cost), 0.0), AVG(efficiency_score)
+         FROM team_sessions
+         WHERE {PERIOD_FILTER}
+         GROUP BY user_name
+         ORDER BY SUM(cost) DESC, user_name"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![days], |row| {
+        Ok(TeamMemberStats {
+            user_name: row.get(0)?,
+            session_count: row.get(1)?,
+            total_turns: row.get::<_, i64>(2)? as u64,
+            total_tokens: row.get::<_, i64>(3)? as u64,
+            total_cost: row.get(4)?,
+            cost_share: 0.0,
+            avg_cost_per_session: 0.0,
+            avg_efficiency: row.get(5)?,
+        })
+    })?;
+    let mut members = rows.collect::<Result<Vec<_>, _>>()?;
+
+    let mut dashboard = TeamDashboard {
+        total_sessions: members.iter().map(|m| m.session_count).sum(),
+        total_turns: members.iter().map(|m| m.total_turns).sum(),
+        total_tokens: members.iter().map(|m| m.total_tokens).sum(),
+        total_cost: members.iter().map(|m| m.total_cost).sum(),
+        ..Default::default()
+    };
+    for member in &mut members {
+        if dashboard.total_cost > 0.0 {
+            member.cost_share = member.total_cost / dashboard.total_cost;
+        }
+        if member.session_count > 0 {
+            member.avg_cost_per_session = member.total_cost / member.session_count as f64;
+        }
+    }
+    dashboard.avg_efficiency = conn.query_row(
+        &format!("SELECT AVG(efficiency_score) FROM team_sessions WHERE {PERIOD_FILTER}"),
+        params![days],
+        |row| row.get(0),
+    )?;
+    dashboard.members = members;
+    Ok(dashboard)
+}
+
+/// Daily team usage for the last `days` days, each day split by user
+///
+/// Days are in the reporting timezone; days without sessions are left out.
+pub fn get_team_trends(conn: &Connection, days: u32) -> Result<Vec<TeamDailyTrend>, DbError> {
+    let sql = format!(
+        "SELECT local_date(started_at) AS day, user_name, COUNT(*),
+                COALESCE(SUM(tokens), 0), COALESCE(SUM(cost), 0.0)
+         FROM team_sessions
+         WHERE {PERIOD_FILTER}
+         GROUP BY day, user_name
+         HAVING day IS NOT NULL
+         ORDER BY day, user_name"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![days], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            TeamUserDay {
+                user_name: row.get(1)?,
+                session_count: row.get(2)?,
+                total_tokens: row.get::<_, i64>(3)? as u64,
+                total_cost: row.get(4)?,
+            },
+        ))
+    })?;
+
+    let mut by_day: BTreeMap<String, TeamDailyTrend> = BTreeMap::new();
+    for row in rows {
+        let (date, user) = row?;
+        let day = by_day.entry(date.clone()).or_insert_with(|| TeamDailyTrend {
+            date,
+            session_count: 0,
+            total_tokens: 0,
+            total_cost: 0.0,
+            by_user: Vec::new(),
+        });
+        day.session_count += user.session_count;
+        day.total_tokens += user.total_tokens;
+        day.total_cost += user.total_cost;
+        day.by_user.push(user);
+    }
+    Ok(by_day.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrations, register_functions, schema};
+
+    fn export(sessions: &[(&str, &str, f64)]) -> TeamExportFile {
+        let sessions = sessions
+            .iter()
+            .map(|(id, date, cost)| {
+                serde_json::json!({
+                    "session": {
+                        "session_id": id,
+                        "date": date,
+                        "project_name": "api",
+                        "model": "claude-opus-4-5-20251101",
+                        "turns": 10,
+                        "tokens": 1000,
+                        "cost": cost,
+                        "duration_ms": 60000,
+                        "efficiency_score": 0.8
+                    },
+                    "turns": []
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({
+            "export_date": "2026-02-06T09:00:00Z",
+            "export_version": "1.0.0",
+            "total_sessions": sessions.len(),
+            "total_turns": 0,
+            "sessions": sessions
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_team_rollup() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_functions(&conn).unwrap();
+        schema::create_tables(&conn).unwrap();
+        migrations::run(&conn).unwrap();
+
+        let alice = export(&[("a1", "2026-02-04T10:00:00Z", 3.0), ("a2", "2026-02-05T10:00:00Z", 1.0)]);
+        let stats = import_team_export(&conn, "alice", "/tmp/alice.json", &alice, "2026-02-06T10:00:00Z").unwrap();
+        assert_eq!(stats.sessions_added, 2);
+        let bob = export(&[("b1", "2026-02-05T12:00:00Z", 4.0)]);
+        import_team_export(&conn, "bob", "/tmp/bob.json", &bob, "2026-02-06T10:00:00Z").unwrap();
+
+        // A newer export of alice's updates the session she already shared
+        let alice = export(&[("a2", "2026-02-05T10:00:00Z", 2.0)]);
+        let stats = import_team_export(&conn, "alice", "/tmp/alice.json", &alice, "2026-02-07T10:00:00Z").unwrap();
+        assert_eq!((stats.sessions_added, stats.sessions_updated), (0, 1));
+
+        let members = get_team_members(&conn).unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].user_name, "alice");
+        assert_eq!(members[0].session_count, 2);
+        assert_eq!(members[0].last_imported_at.as_deref(), Some("2026-02-07T10:00:00Z"));
+
+        let dashboard = get_team_dashboard(&conn, None).unwrap();
+        assert_eq!(dashboard.total_sessions, 3);
+        assert!((dashboard.total_cost - 9.0).abs() < 1e-9);
+        assert_eq!(dashboard.members[0].user_name, "alice");
+        assert!((dashboard.members[1].cost_share - 4.0 / 9.0).abs() < 1e-9);
+
+        let trends = get_team_trends(&conn, 3650).unwrap();
+        assert_eq!(trends.len(), 2);
+        assert_eq!(trends[1].session_count, 2);
+        assert!((trends[1].total_cost - 6.0).abs() < 1e-9);
+        let users: Vec<&str> = trends[1].by_user.iter().map(|u| u.user_name.as_str()).collect();
+        assert_eq!(users, vec!["alice", "bob"]);
+        assert!(get_team_trends(&conn, 0).unwrap().is_empty());
+
+        assert_eq!(remove_team_member(&conn, "bob").unwrap(), 1);
+        assert_eq!(get_team_members(&conn).unwrap().len(), 1);
+    }
+}
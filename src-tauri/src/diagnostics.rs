@@ -0,0 +1,146 @@
+//! Backend health for the diagnostics panel
+//!
+//! Startup problems such as a database that won't open or settings that
+//! won't load are logged and then worked around, so the app keeps running in
+//! a degraded mode. They are also recorded here, along with whether the
+//! session watcher is watching files or fell back to polling, for
+//! `get_diagnostics` to report.
+//!
+//! Commands degrade the same way at runtime: a database read that fails
+//! falls back to parsing session files, or to an empty result. Those errors
+//! are tallied by where they happened, so a database that fails on every
+//! query shows up in `get_backend_errors` rather than only in the log.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// How the background task follows session files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherStatus {
+    /// Not started yet, or not run at all (the CLI)
+    #[default]
+    NotStarted,
+    /// File system notifications
+    Watching,
+    /// The watcher couldn't start; session files are polled
+    Polling,
+}
+
+/// A startup step that failed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct InitError {
+    /// What was being set up, e.g. "database schema"
+    pub stage: String,
+    pub message: String,
+    /// When it failed (RFC 3339)
+    pub at: String,
+}
+
+/// A non-fatal error the backend worked around, with how often it happened
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BackendError {
+    /// What failed, e.g. "load dashboard summary"
+    pub source: String,
+    pub count: u64,
+    /// The most recent error message
+    pub last_message: String,
+    /// When it first and last happened (RFC 3339)
+    pub first_at: String,
+    pub last_at: String,
+}
+
+/// Health recorded while the backend starts and runs
+#[derive(Default)]
+pub struct Diagnostics {
+    init_errors: Mutex<Vec<InitError>>,
+    watcher: Mutex<WatcherStatus>,
+    backend_errors: Mutex<HashMap<&'static str, BackendError>>,
+}
+
+impl Diagnostics {
+    /// Record a failed startup step; the caller still logs it
+    pub fn record_init_error(&self, stage: &str, error: impl Display) {
+        if let Ok(mut errors) = self.init_errors.lock() {
+            errors.push(InitError {
+                stage: stage.to_string(),
+                message: error.to_string(),
+                at: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+    }
+
+    pub fn init_errors(&self) -> Vec<InitError> {
+        self.init_errors.lock().map(|errors| errors.clone()).unwrap_or_default()
+    }
+
+    pub fn set_watcher_status(&self, status: WatcherStatus) {
+        if let Ok(mut watcher) = self.watcher.lock() {
+            *watcher = status;
+        }
+    }
+
+    pub fn watcher_status(&self) -> WatcherStatus {
+        self.watcher.lock().map(|watcher| *watcher).unwrap_or_default()
+    }
+
+    /// Count a non-fatal error; the caller still logs it
+    ///
+    /// Sources are fixed strings so the tally stays bounded however many
+    /// sessions or queries fail.
+    pub fn record_error(&self, source: &'static str, error: impl Display) {
+        let Ok(mut errors) = self.backend_errors.lock() else {
+            return;
+        };
+        let now = chrono::Utc::now().to_rfc3339();
+        let entry = errors.entry(source).or_insert_with(|| BackendError {
+            source: source.to_string(),
+            count: 0,
+            last_message: String::new(),
+            first_at: now.clone(),
+            last_at: String::new(),
+        });
+        entry.count += 1;
+        entry.last_message = error.to_string();
+        entry.last_at = now;
+    }
+
+    /// Recorded errors, most recent first
+    pub fn backend_errors(&self) -> Vec<BackendError> {
+        let mut errors: Vec<BackendError> = self
+            .backend_errors
+            .lock()
+            .map(|errors| errors.values().cloned().collect())
+            .unwrap_or_default();
+        errors.sort_by(|a, b| b.last_at.cmp(&a.last_at).then_with(|| a.source.cmp(&b.source)));
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_error() {
+        let diagnostics = Diagnostics::default();
+        assert!(diagnostics.backend_errors().is_empty());
+
+        diagnostics.record_error("load dashboard summary", "database is locked");
+        diagnostics.record_error("load dashboard summary", "disk I/O error");
+        diagnostics.record_error("load daily metrics", "database is locked");
+
+        let errors = diagnostics.backend_errors();
+        assert_eq!(errors.len(), 2);
+        let dashboard = errors.iter().find(|e| e.source == "load dashboard summary").unwrap();
+        assert_eq!(dashboard.count, 2);
+        assert_eq!(dashboard.last_message, "disk I/O error");
+        assert!(dashboard.first_at <= dashboard.last_at);
+    }
+}
@@ -0,0 +1,248 @@
+//! Session bundles for bug reports
+//!
+//! A bundle is a zip of one session's JSONL together with the metrics and
+//! anti-patterns computed from it, so a report about a number that looks
+//! wrong carries what's needed to reproduce it. The metrics are computed
+//! from the JSONL as bundled, not from the original file.
+//!
+//! Anonymizing rewrites every string of an entry except structural fields
+//! (entry types, IDs, timestamps, model and tool names). Letters and digits
+//! are replaced while whitespace and punctuation stay, so text keeps its
+//! length, line breaks and path shape, and the estimates based on them come
+//! out the same. A word is replaced the same way everywhere in a bundle, so
+//! paths under the project directory stay under it and a file edited over
+//! several turns is still one file. Bundles that aren't anonymized have
+//! their secrets redacted.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+use zip::write::SimpleFileOptions;
+
+use crate::CommandError;
+
+/// Version of the bundle layout, bumped when files change meaning
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Fields kept as they are when anonymizing
+const STRUCTURAL_FIELDS: &[&str] = &[
+    "type",
+    "subtype",
+    "uuid",
+    "parentUuid",
+    "logicalParentUuid",
+    "leafUuid",
+    "sessionId",
+    "agentId",
+    "requestId",
+    "id",
+    "tool_use_id",
+    "timestamp",
+    "model",
+    "name",
+    "role",
+    "stop_reason",
+    "stop_sequence",
+    "service_tier",
+    "version",
+    "userType",
+    "level",
+];
+
+/// Fields holding tool input or output, rewritten whole
+const PAYLOAD_FIELDS: &[&str] = &["input", "toolUseResult"];
+
+/// `manifest.json` of a bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub session_id: String,
+    pub created_at: String,
+    pub anonymized: bool,
+    /// Secrets redacted from the JSONL (none are looked for when anonymized)
+    pub secrets_redacted: u32,
+    /// Lines of `session.jsonl`
+    pub lines: u32,
+}
+
+/// Rewrites session entries for an anonymized bundle
+///
+/// The hash keys are random, so replacements differ between bundles and
+/// can't be matched against guesses.
+#[derive(Default)]
+pub struct Anonymizer {
+    keys: RandomState,
+}
+
+impl Anonymizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replacement for `text` of the same length and shape
+    pub fn replace(&self, text: &str) -> String {
+        let mut replaced = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(is_word_char) {
+            replaced.push_str(&rest[..start]);
+            let word = &rest[start..];
+            let end = word.find(|c: char| !is_word_char(c)).unwrap_or(word.len());
+            self.replace_word(&word[..end], &mut replaced);
+            rest = &word[end..];
+        }
+        replaced.push_str(rest);
+        replaced
+    }
+
+    fn replace_word(&self, word: &str, out: &mut String) {
+        let mut state = self.keys.hash_one(word) | 1;
+        for c in word.chars() {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            out.push(match c.is_ascii_digit() {
+                true => (b'0' + (state % 10) as u8) as char,
+                false => (b'a' + (state % 26) as u8) as char,
+            });
+        }
+    }
+
+    /// Anonymize one JSONL line; lines that aren't JSON are replaced whole
+    pub fn anonymize_line(&self, line: &str) -> String {
+        match serde_json::from_str::<Value>(line) {
+            Ok(mut entry) => {
+                self.anonymize_value(&mut entry, false);
+                entry.to_string()
+            }
+            Err(_) => self.replace(line),
+        }
+    }
+
+    fn anonymize_value(&self, value: &mut Value, in_payload: bool) {
+        match value {
+            Value::String(text) => *text = self.replace(text),
+            Value::Array(items) => {
+                for item in items {
+                    self.anonymize_value(item, in_payload);
+                }
+            }
+            Value::Object(fields) => {
+                for (key, field) in fields.iter_mut() {
+                    let key = key.as_str();
+                    if in_payload || PAYLOAD_FIELDS.contains(&key) {
+                        self.anonymize_value(field, true);
+                    } else if !STRUCTURAL_FIELDS.contains(&key) {
+                        self.anonymize_value(field, false);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write a bundle: the manifest, the session JSONL and the computed metrics
+/// and anti-patterns, each as its own file in the zip
+pub fn write_session_bundle(
+    path: &Path,
+    manifest: &BundleManifest,
+    jsonl: &str,
+    metrics: &impl Serialize,
+    patterns: &impl Serialize,
+) -> Result<(), CommandError> {
+    let files = [
+        ("manifest.json", to_pretty_json(manifest)?),
+        ("session.jsonl", jsonl.to_string()),
+        ("metrics.json", to_pretty_json(metrics)?),
+        ("patterns.json", to_pretty_json(patterns)?),
+    ];
+
+    let file = std::fs::File::create(path)
+        .map_err(|e| CommandError::Internal(format!("Failed to create bundle file: {}", e)))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, contents) in files {
+        zip.start_file(name, options)
+            .and_then(|_| zip.write_all(contents.as_bytes()).map_err(Into::into))
+            .map_err(|e| CommandError::Internal(format!("Failed to write {} to bundle: {}", name, e)))?;
+    }
+    zip.finish()
+        .map_err(|e| CommandError::Internal(format!("Failed to write bundle file: {}", e)))?;
+    Ok(())
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !c.is_ascii_punctuation()
+}
+
+fn to_pretty_json(value: &impl Serialize) -> Result<String, CommandError> {
+    serde_json::to_string_pretty(value)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize bundle file: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_anonymize_line() {
+        let anonymizer = Anonymizer::new();
+        let line = r#"{"type":"assistant","uuid":"u1","timestamp":"2026-01-14T07:44:28.531Z","cwd":"/Users/alice/app","message":{"model":"claude-opus-4-5-20251101","content":[{"type":"text","text":"Fixed it.\nDone"},{"type":"tool_use","id":"t1","name":"Edit","input":{"file_path":"/Users/alice/app/main.rs","type":"secret-value"}}],"usage":{"input_tokens":12}}}"#;
+        let entry: Value = serde_json::from_str(&anonymizer.anonymize_line(line)).unwrap();
+
+        assert_eq!(entry["type"], "assistant");
+        assert_eq!(entry["timestamp"], "2026-01-14T07:44:28.531Z");
+        assert_eq!(entry["message"]["model"], "claude-opus-4-5-20251101");
+        assert_eq!(entry["message"]["usage"]["input_tokens"], 12);
+        let content = &entry["message"]["content"];
+        assert_eq!(content[1]["name"], "Edit");
+        assert_eq!(content[1]["id"], "t1");
+        // Tool input is payload, even under a structural name
+        assert_ne!(content[1]["input"]["type"], "secret-value");
+
+        let text = content[0]["text"].as_str().unwrap();
+        assert_ne!(text, "Fixed it.\nDone");
+        assert_eq!(text.len(), "Fixed it.\nDone".len());
+        assert_eq!(text.find('\n'), Some(9));
+
+        let cwd = entry["cwd"].as_str().unwrap();
+        assert!(!cwd.contains("alice"));
+        assert_eq!(cwd.matches('/').count(), 3);
+        assert_eq!(anonymizer.replace("/Users/alice/app"), cwd);
+        let file = content[1]["input"]["file_path"].as_str().unwrap();
+        assert!(file.starts_with(cwd));
+        assert_eq!(anonymizer.replace("not json"), anonymizer.anonymize_line("not json"));
+    }
+
+    #[test]
+    fn test_write_session_bundle() {
+        let path = std::env::temp_dir().join(format!("ironhide_bundle_{}.zip", std::process::id()));
+        let manifest = BundleManifest {
+            format_version: BUNDLE_FORMAT_VERSION,
+            app_version: "0.0.0".to_string(),
+            session_id: "s1".to_string(),
+            created_at: "2026-01-14T08:00:00Z".to_string(),
+            anonymized: true,
+            secrets_redacted: 0,
+            lines: 1,
+        };
+        write_session_bundle(&path, &manifest, "{}\n", &serde_json::json!({"cost": 1.5}), &Vec::<u32>::new())
+            .unwrap();
+
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut names: Vec<&str> = zip.file_names().collect();
+        names.sort();
+        assert_eq!(names, ["manifest.json", "metrics.json", "patterns.json", "session.jsonl"]);
+        let mut jsonl = String::new();
+        zip.by_name("session.jsonl").unwrap().read_to_string(&mut jsonl).unwrap();
+        assert_eq!(jsonl, "{}\n");
+        let _ = std::fs::remove_file(&path);
+    }
+}
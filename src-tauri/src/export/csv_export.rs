@@ -103,6 +103,8 @@ pub fn write_combined_csv(
         session_turns: u32,
         session_tokens: u64,
         session_cost: f64,
+        session_cache_write_5m_cost: f64,
+        session_cache_write_1h_cost: f64,
         session_efficiency: Option<f64>,
         turn_number: u32,
         turn_started_at: String,
@@ -126,6 +128,8 @@ pub fn write_combined_csv(
                     session_turns: session.turns,
                     session_tokens: session.tokens,
                     session_cost: session.cost,
+                    session_cache_write_5m_cost: session.cache_write_5m_cost,
+                    session_cache_write_1h_cost: session.cache_write_1h_cost,
                     session_efficiency: session.efficiency_score,
                     turn_number: turn.turn_number,
                     turn_started_at: turn.started_at.clone(),
@@ -152,6 +156,8 @@ pub fn write_combined_csv(
                 session_turns: session.turns,
                 session_tokens: session.tokens,
                 session_cost: session.cost,
+                session_cache_write_5m_cost: session.cache_write_5m_cost,
+                session_cache_write_1h_cost: session.cache_write_1h_cost,
                 session_efficiency: session.efficiency_score,
                 turn_number: 0,
                 turn_started_at: String::new(),
@@ -191,6 +197,8 @@ mod tests {
             turns: 5,
             tokens: 10000,
             cost: 1.50,
+            cache_write_5m_cost: 0.0,
+            cache_write_1h_cost: 0.0,
             duration_ms: 60000,
             efficiency_score: Some(0.85),
         }
@@ -288,6 +296,8 @@ mod tests {
                 turns: 5,
                 tokens: 10000,
                 cost: 1.50,
+                cache_write_5m_cost: 0.0,
+                cache_write_1h_cost: 0.0,
                 duration_ms: 60000,
                 efficiency_score: Some(0.85),
             },
@@ -299,6 +309,8 @@ mod tests {
                 turns: 10,
                 tokens: 20000,
                 cost: 2.00,
+                cache_write_5m_cost: 0.0,
+                cache_write_1h_cost: 0.0,
                 duration_ms: 120000,
                 efficiency_score: Some(0.90),
             },
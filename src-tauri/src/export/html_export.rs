@@ -0,0 +1,463 @@
+//! HTML session replay export
+//!
+//! Renders one session as a standalone page: the conversation turn by turn,
+//! each turn annotated with its tokens and cost, and tool calls folded into
+//! `<details>` blocks together with their results. The page carries its own
+//! styles and no scripts, so it can be shared with people who don't run
+//! Ironhide.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+
+use super::md_export::{format_cost, format_count, format_date, format_duration};
+use super::ExportableSession;
+use crate::metrics::tool_failures::tool_target;
+use crate::parser::transcript::{TranscriptMessage, TranscriptRole};
+use crate::parser::CompletedTurn;
+use crate::CommandError;
+
+/// Longest tool target shown in a collapsed tool call
+const TARGET_PREVIEW_CHARS: usize = 120;
+
+const STYLE: &str = "
+body { margin: 0; background: #0f1115; color: #d7dae0; font: 15px/1.55 -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif; }
+header, main, footer { max-width: 960px; margin: 0 auto; padding: 0 24px; }
+header { padding-top: 32px; }
+h1 { margin: 0 0 4px; font-size: 24px; color: #fff; }
+code, pre { font: 13px/1.45 ui-monospace, SFMono-Regular, Menlo, monospace; }
+.meta, .note, footer { color: #8b919c; font-size: 13px; }
+.stats { display: flex; flex-wrap: wrap; gap: 24px; margin: 20px 0 8px; }
+.stats div { min-width: 110px; }
+.stats dt { color: #8b919c; font-size: 12px; text-transform: uppercase; letter-spacing: .04em; }
+.stats dd { margin: 0; color: #fff; font-size: 20px; font-weight: 600; }
+.turn { margin: 28px 0; border: 1px solid #262a33; border-radius: 10px; background: #151820; overflow: hidden; }
+.turn-head { display: flex; flex-wrap: wrap; gap: 14px; align-items: baseline; padding: 10px 16px; background: #1b1f29; font-size: 13px; color: #8b919c; }
+.turn-head a { color: #fff; font-weight: 600; text-decoration: none; }
+.turn-head .cost { margin-left: auto; color: #f0b429; font-weight: 600; }
+.cost-bar { height: 3px; background: #1b1f29; }
+.cost-bar span { display: block; height: 100%; background: #f0b429; }
+.msg { margin: 12px 16px; }
+.role { font-size: 12px; font-weight: 600; text-transform: uppercase; letter-spacing: .04em; color: #8b919c; }
+.user .role { color: #6cb6ff; }
+.assistant .role { color: #c297ff; }
+.text { white-space: pre-wrap; overflow-wrap: anywhere; }
+.user .text { padding: 8px 12px; border-left: 3px solid #6cb6ff; background: #18202c; border-radius: 4px; }
+details { border: 1px solid #262a33; border-radius: 6px; background: #11141a; }
+summary { cursor: pointer; padding: 6px 10px; font-size: 13px; }
+summary code { color: #8b919c; }
+.tool-name { font-weight: 600; color: #7ee0a1; }
+.error .tool-name { color: #ff7b72; }
+details pre { margin: 0; padding: 8px 10px; border-top: 1px solid #262a33; white-space: pre-wrap; overflow-wrap: anywhere; max-height: 480px; overflow: auto; }
+details .text { padding: 8px 10px; border-top: 1px solid #262a33; color: #a3a9b4; }
+.truncated { margin: 0; padding: 4px 10px; color: #8b919c; font-size: 12px; font-style: italic; }
+footer { padding: 8px 24px 40px; }
+";
+
+/// One turn of a replay with its cost and the messages it contains
+#[derive(Debug, Clone)]
+pub struct ReplayTurn {
+    pub turn_number: u32,
+    pub started_at: String,
+    pub model: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub total_tokens: u64,
+    pub cost: f64,
+    pub messages: Vec<TranscriptMessage>,
+}
+
+impl ReplayTurn {
+    pub fn new(turn: &CompletedTurn, cost: f64) -> Self {
+        Self {
+            turn_number: turn.turn_number,
+            started_at: turn.started_at.clone(),
+            model: turn.model.clone(),
+            duration_ms: turn.duration_ms,
+            total_tokens: turn.total_tokens,
+            cost,
+            messages: Vec::new(),
+        }
+    }
+}
+
+/// Everything rendered on a replay page
+#[derive(Debug, Clone)]
+pub struct SessionReplay {
+    pub session: ExportableSession,
+    pub turns: Vec<ReplayTurn>,
+    /// Secrets replaced in the transcript
+    pub redactions: u32,
+    /// Leave message content out, showing only the turn metrics
+    pub metrics_only: bool,
+}
+
+/// Attach transcript messages to the turns they belong to
+///
+/// A message goes to the last turn started at or before its timestamp;
+/// messages without a usable timestamp stay with the turn before them.
+pub fn assign_messages(turns: &mut [ReplayTurn], messages: Vec<TranscriptMessage>) {
+    if turns.is_empty() {
+        return;
+    }
+    let starts: Vec<Option<DateTime<Utc>>> = turns.iter().map(|t| parse_timestamp(&t.started_at)).collect();
+    let mut current = 0;
+    for message in messages {
+        if let Some(at) = message.timestamp.as_deref().and_then(parse_timestamp) {
+            while current + 1 < turns.len() && starts[current + 1].is_some_and(|start| start <= at) {
+                current += 1;
+            }
+        }
+        turns[current].messages.push(message);
+    }
+}
+
+/// Render a session replay as a standalone HTML page
+pub fn render_session_replay(replay: &SessionReplay) -> String {
+    let s = &replay.session;
+    let mut html = String::new();
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{} · {}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape_html(&s.project_name),
+        escape_html(&format_date(&s.date)),
+        STYLE
+    );
+
+    let _ = writeln!(html, "<header>\n<h1>{}</h1>", escape_html(&s.project_name));
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">Session <code>{}</code> · {} · {}</p>",
+        escape_html(&s.session_id),
+        escape_html(&format_date(&s.date)),
+        escape_html(s.model.as_deref().unwrap_or("unknown model")),
+    );
+    let _ = writeln!(html, "<dl class=\"stats\">");
+    for (label, value) in [
+        ("Turns", s.turns.to_string()),
+        ("Tokens", format_count(s.tokens)),
+        ("Cost", format_cost(s.cost)),
+        ("Duration", format_duration(s.duration_ms)),
+    ] {
+        let _ = writeln!(html, "<div><dt>{}</dt><dd>{}</dd></div>", label, value);
+    }
+    let _ = writeln!(html, "</dl>");
+    if replay.redactions > 0 {
+        let _ = writeln!(
+            html,
+            "<p class=\"note\">{} secret{} redacted from this transcript.</p>",
+            replay.redactions,
+            if replay.redactions == 1 { "" } else { "s" }
+        );
+    }
+    if replay.metrics_only {
+        let _ = writeln!(html, "<p class=\"note\">Message content left out: privacy mode is on.</p>");
+    }
+    let _ = writeln!(html, "</header>\n<main>");
+
+    let max_cost = replay.turns.iter().map(|t| t.cost).fold(0.0, f64::max);
+    for turn in &replay.turns {
+        render_turn(&mut html, turn, max_cost, replay.metrics_only);
+    }
+
+    let _ = writeln!(
+        html,
+        "</main>\n<footer>Exported from Ironhide on {}.</footer>\n</body>\n</html>",
+        Utc::now().format("%Y-%m-%d %H:%M UTC")
+    );
+    html
+}
+
+/// Write a session replay page
+pub fn write_session_replay(replay: &SessionReplay, path: &PathBuf) -> Result<(), CommandError> {
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| CommandError::Internal(format!("Failed to create HTML file: {}", e)))?;
+
+    file.write_all(render_session_replay(replay).as_bytes())
+        .map_err(|e| CommandError::Internal(format!("Failed to write HTML file: {}", e)))?;
+
+    Ok(())
+}
+
+fn render_turn(html: &mut String, turn: &ReplayTurn, max_cost: f64, metrics_only: bool) {
+    let _ = writeln!(html, "<section class=\"turn\" id=\"turn-{}\">", turn.turn_number);
+    let _ = write!(
+        html,
+        "<div class=\"turn-head\"><a href=\"#turn-{0}\">Turn {0}</a><span>{1}</span>",
+        turn.turn_number,
+        escape_html(&format_date(&turn.started_at)),
+    );
+    if let Some(model) = &turn.model {
+        let _ = write!(html, "<span>{}</span>", escape_html(model));
+    }
+    if let Some(ms) = turn.duration_ms {
+        let _ = write!(html, "<span>{}</span>", format_duration(ms.max(0) as u64));
+    }
+    let _ = writeln!(
+        html,
+        "<span>{} tokens</span><span class=\"cost\">{}</span></div>",
+        format_count(turn.total_tokens),
+        format_cost(turn.cost),
+    );
+    let share = if max_cost > 0.0 { turn.cost / max_cost * 100.0 } else { 0.0 };
+    let _ = writeln!(html, "<div class=\"cost-bar\"><span style=\"width:{:.1}%\"></span></div>", share);
+    if metrics_only {
+        let _ = writeln!(html, "</section>");
+        return;
+    }
+
+    // Results are shown inside the tool call they answer
+    let results: HashMap<&str, &TranscriptMessage> = turn
+        .messages
+        .iter()
+        .filter(|m| m.role == TranscriptRole::ToolResult)
+        .filter_map(|m| Some((m.tool_use_id.as_deref()?, m)))
+        .collect();
+    let calls: Vec<&str> = turn
+        .messages
+        .iter()
+        .filter(|m| m.role == TranscriptRole::ToolUse)
+        .filter_map(|m| m.tool_use_id.as_deref())
+        .collect();
+
+    for message in &turn.messages {
+        match message.role {
+            TranscriptRole::User => render_text(html, "user", "You", message),
+            TranscriptRole::Assistant => render_text(html, "assistant", "Claude", message),
+            TranscriptRole::Thinking => {
+                let _ = writeln!(html, "<details class=\"msg thinking\"><summary>Thinking</summary>");
+                render_body(html, message);
+                let _ = writeln!(html, "</details>");
+            }
+            TranscriptRole::ToolUse => {
+                let result = message.tool_use_id.as_deref().and_then(|id| results.get(id));
+                render_tool_call(html, message, result.copied());
+            }
+            TranscriptRole::ToolResult => {
+                let paired = message.tool_use_id.as_deref().is_some_and(|id| calls.contains(&id));
+                if !paired {
+                    let _ = writeln!(
+                        html,
+                        "<details class=\"msg tool{}\"><summary>Result of <span class=\"tool-name\">{}</span></summary>",
+                        if message.is_error { " error" } else { "" },
+                        escape_html(message.tool_name.as_deref().unwrap_or("tool")),
+                    );
+                    render_pre(html, message);
+                    let _ = writeln!(html, "</details>");
+                }
+            }
+        }
+    }
+
+    let _ = writeln!(html, "</section>");
+}
+
+fn render_text(html: &mut String, class: &str, label: &str, message: &TranscriptMessage) {
+    let _ = writeln!(html, "<div class=\"msg {}\"><div class=\"role\">{}</div>", class, label);
+    render_body(html, message);
+    let _ = writeln!(html, "</div>");
+}
+
+fn render_body(html: &mut String, message: &TranscriptMessage) {
+    let _ = writeln!(
+        html,
+        "<div class=\"text\">{}</div>",
+        escape_html(message.text.as_deref().unwrap_or(""))
+    );
+    if message.truncated {
+        let _ = writeln!(html, "<p class=\"truncated\">Truncated</p>");
+    }
+}
+
+fn render_tool_call(html: &mut String, call: &TranscriptMessage, result: Option<&TranscriptMessage>) {
+    let failed = result.is_some_and(|r| r.is_error);
+    let _ = write!(
+        html,
+        "<details class=\"msg tool{}\"><summary><span class=\"tool-name\">{}</span>",
+        if failed { " error" } else { "" },
+        escape_html(call.tool_name.as_deref().unwrap_or("tool")),
+    );
+    if let Some(target) = call.tool_input.as_ref().and_then(tool_target) {
+        let preview: String = target.chars().take(TARGET_PREVIEW_CHARS).collect();
+        let ellipsis = if preview.len() < target.len() { "…" } else { "" };
+        let _ = write!(html, " <code>{}{}</code>", escape_html(&preview), ellipsis);
+    }
+    if failed {
+        html.push_str(" — failed");
+    }
+    let _ = writeln!(html, "</summary>");
+
+    if let Some(input) = &call.tool_input {
+        let input = serde_json::to_string_pretty(input).unwrap_or_default();
+        let _ = writeln!(html, "<pre>{}</pre>", escape_html(&input));
+    }
+    if let Some(result) = result {
+        render_pre(html, result);
+    }
+    let _ = writeln!(html, "</details>");
+}
+
+fn render_pre(html: &mut String, message: &TranscriptMessage) {
+    let _ = writeln!(html, "<pre>{}</pre>", escape_html(message.text.as_deref().unwrap_or("")));
+    if message.truncated {
+        let _ = writeln!(html, "<p class=\"truncated\">Truncated</p>");
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(role: TranscriptRole, timestamp: &str, text: &str) -> TranscriptMessage {
+        TranscriptMessage {
+            index: 0,
+            role,
+            uuid: None,
+            timestamp: Some(timestamp.to_string()),
+            text: Some(text.to_string()),
+            tool_use_id: None,
+            tool_name: None,
+            tool_input: None,
+            is_error: false,
+            truncated: false,
+            model: None,
+            is_sidechain: false,
+        }
+    }
+
+    fn replay_turn(n: u32, started_at: &str, cost: f64) -> ReplayTurn {
+        ReplayTurn {
+            turn_number: n,
+            started_at: started_at.to_string(),
+            model: Some("claude-opus-4-5-20251101".to_string()),
+            duration_ms: Some(65_000),
+            total_tokens: 12_345,
+            cost,
+            messages: Vec::new(),
+        }
+    }
+
+    fn session() -> ExportableSession {
+        ExportableSession {
+            session_id: "abcdef12-3456".to_string(),
+            date: "2026-01-14T07:00:00Z".to_string(),
+            project_name: "my-app".to_string(),
+            model: Some("claude-opus-4-5-20251101".to_string()),
+            turns: 2,
+            tokens: 24_690,
+            cost: 1.5,
+            cache_write_5m_cost: 0.0,
+            cache_write_1h_cost: 0.0,
+            duration_ms: 300_000,
+            efficiency_score: None,
+        }
+    }
+
+    fn tool_messages(id: &str, is_error: bool) -> [TranscriptMessage; 2] {
+        let mut call = message(TranscriptRole::ToolUse, "2026-01-14T07:00:05Z", "");
+        call.text = None;
+        call.tool_use_id = Some(id.to_string());
+        call.tool_name = Some("Bash".to_string());
+        call.tool_input = Some(json!({"command": "cargo test && echo <done>"}));
+        let mut result = message(TranscriptRole::ToolResult, "2026-01-14T07:00:09Z", "test result: FAILED");
+        result.tool_use_id = Some(id.to_string());
+        result.tool_name = Some("Bash".to_string());
+        result.is_error = is_error;
+        [call, result]
+    }
+
+    #[test]
+    fn test_assign_messages() {
+        let mut turns = vec![
+            replay_turn(1, "2026-01-14T07:00:00Z", 0.1),
+            replay_turn(2, "2026-01-14T07:05:00Z", 0.2),
+        ];
+        let mut untimed = message(TranscriptRole::Assistant, "", "no timestamp");
+        untimed.timestamp = None;
+        assign_messages(
+            &mut turns,
+            vec![
+                message(TranscriptRole::User, "2026-01-14T07:00:00Z", "first"),
+                message(TranscriptRole::Assistant, "2026-01-14T07:01:00Z", "reply"),
+                message(TranscriptRole::User, "2026-01-14T07:05:00Z", "second"),
+                untimed,
+            ],
+        );
+        assert_eq!(turns[0].messages.len(), 2);
+        let second: Vec<_> = turns[1].messages.iter().map(|m| m.text.as_deref().unwrap()).collect();
+        assert_eq!(second, vec!["second", "no timestamp"]);
+    }
+
+    #[test]
+    fn test_render_session_replay() {
+        let mut first = replay_turn(1, "2026-01-14T07:00:00Z", 0.5);
+        first.messages.push(message(TranscriptRole::User, "2026-01-14T07:00:00Z", "Fix <script> & tests"));
+        first.messages.extend(tool_messages("toolu_1", true));
+        first.messages.push(message(TranscriptRole::Assistant, "2026-01-14T07:00:10Z", "Done."));
+        let second = replay_turn(2, "2026-01-14T07:05:00Z", 1.0);
+
+        let replay = SessionReplay {
+            session: session(),
+            turns: vec![first, second],
+            redactions: 1,
+            metrics_only: false,
+        };
+        let html = render_session_replay(&replay);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<script"));
+        assert!(html.contains("Fix &lt;script&gt; &amp; tests"));
+        assert!(html.contains("<dd>$1.50</dd>"));
+        assert!(html.contains("1 secret redacted"));
+        assert!(html.contains("<a href=\"#turn-2\">Turn 2</a>"));
+        // The costliest turn gets the full bar
+        assert!(html.contains("style=\"width:50.0%\""));
+        assert!(html.contains("style=\"width:100.0%\""));
+        // The result is folded into its call, not repeated after it
+        assert!(html.contains("<details class=\"msg tool error\"><summary><span class=\"tool-name\">Bash</span> <code>cargo test &amp;&amp; echo &lt;done&gt;</code> — failed</summary>"));
+        assert_eq!(html.matches("test result: FAILED").count(), 1);
+        assert!(!html.contains("Result of"));
+    }
+
+    #[test]
+    fn test_render_metrics_only_replay() {
+        let mut turn = replay_turn(1, "2026-01-14T07:00:00Z", 0.5);
+        turn.messages.push(message(TranscriptRole::User, "2026-01-14T07:00:00Z", "my secret plan"));
+        turn.messages.extend(tool_messages("toolu_1", false));
+
+        let replay = SessionReplay { session: session(), turns: vec![turn], redactions: 0, metrics_only: true };
+        let html = render_session_replay(&replay);
+
+        assert!(html.contains("Message content left out: privacy mode is on."));
+        assert!(html.contains("<a href=\"#turn-1\">Turn 1</a>"));
+        assert!(html.contains("12,345 tokens"));
+        assert!(!html.contains("my secret plan"));
+        assert!(!html.contains("cargo test"));
+        assert!(!html.contains("test result"));
+    }
+}
@@ -0,0 +1,226 @@
+//! iCalendar export functionality
+//!
+//! Writes sessions as calendar events (start to last activity, with cost,
+//! tokens and project in the description) and daily trends as all-day
+//! events, so Claude usage can be overlaid on a calendar next to meetings
+//! and focus blocks.
+
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use super::{ExportableSession, ExportableTrend};
+use crate::CommandError;
+
+/// Longest line allowed by RFC 5545, in octets, before folding
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Shortest event written, so sessions without a duration still show up
+const MIN_EVENT_MS: i64 = 60_000;
+
+/// Render sessions as an iCalendar with one event per session
+///
+/// Sessions whose start isn't a timestamp are left out.
+pub fn render_sessions_ics(sessions: &[ExportableSession]) -> String {
+    let stamp = format_datetime(Utc::now());
+    let mut ics = begin_calendar("Claude Code Sessions");
+
+    for session in sessions {
+        let start = match DateTime::parse_from_rfc3339(&session.date) {
+            Ok(start) => start.with_timezone(&Utc),
+            Err(_) => continue,
+        };
+        let end = start + Duration::milliseconds((session.duration_ms as i64).max(MIN_EVENT_MS));
+
+        let mut description = format!(
+            "Project: {}\nCost: ${:.2}\nTokens: {}\nTurns: {}",
+            session.project_name, session.cost, session.tokens, session.turns
+        );
+        if let Some(ref model) = session.model {
+            let _ = write!(description, "\nModel: {}", model);
+        }
+        if let Some(efficiency) = session.efficiency_score {
+            let _ = write!(description, "\nCache efficiency: {:.1}%", efficiency * 100.0);
+        }
+        let _ = write!(description, "\nSession: {}", session.session_id);
+
+        push_line(&mut ics, "BEGIN:VEVENT");
+        push_line(&mut ics, &format!("UID:{}@ironhide", session.session_id));
+        push_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut ics, &format!("DTSTART:{}", format_datetime(start)));
+        push_line(&mut ics, &format!("DTEND:{}", format_datetime(end)));
+        push_line(
+            &mut ics,
+            &format!("SUMMARY:{}", escape_text(&format!("Claude: {} (${:.2})", session.project_name, session.cost))),
+        );
+        push_line(&mut ics, &format!("DESCRIPTION:{}", escape_text(&description)));
+        push_line(&mut ics, "TRANSP:TRANSPARENT");
+        push_line(&mut ics, "END:VEVENT");
+    }
+
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// Render daily trends as an iCalendar with one all-day event per day
+pub fn render_trends_ics(trends: &[ExportableTrend]) -> String {
+    let stamp = format_datetime(Utc::now());
+    let mut ics = begin_calendar("Claude Code Usage");
+
+    for trend in trends {
+        let date = match NaiveDate::parse_from_str(&trend.date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => continue,
+        };
+        let description = format!(
+            "Sessions: {}\nTurns: {}\nTokens: {}\nCost: ${:.2}",
+            trend.session_count, trend.total_turns, trend.total_tokens, trend.total_cost
+        );
+
+        push_line(&mut ics, "BEGIN:VEVENT");
+        push_line(&mut ics, &format!("UID:usage-{}@ironhide", trend.date));
+        push_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut ics, &format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+        push_line(&mut ics, &format!("DTEND;VALUE=DATE:{}", (date + Duration::days(1)).format("%Y%m%d")));
+        push_line(
+            &mut ics,
+            &format!(
+                "SUMMARY:{}",
+                escape_text(&format!("Claude: ${:.2}, {} sessions", trend.total_cost, trend.session_count))
+            ),
+        );
+        push_line(&mut ics, &format!("DESCRIPTION:{}", escape_text(&description)));
+        push_line(&mut ics, "TRANSP:TRANSPARENT");
+        push_line(&mut ics, "END:VEVENT");
+    }
+
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// Write sessions to an iCalendar file
+pub fn write_sessions_ics(sessions: &[ExportableSession], path: &PathBuf) -> Result<(), CommandError> {
+    write_ics(&render_sessions_ics(sessions), path)
+}
+
+/// Write daily trends to an iCalendar file
+pub fn write_trends_ics(trends: &[ExportableTrend], path: &PathBuf) -> Result<(), CommandError> {
+    write_ics(&render_trends_ics(trends), path)
+}
+
+fn write_ics(content: &str, path: &PathBuf) -> Result<(), CommandError> {
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| CommandError::Internal(format!("Failed to create iCalendar file: {}", e)))?;
+
+    file.write_all(content.as_bytes())
+        .map_err(|e| CommandError::Internal(format!("Failed to write iCalendar file: {}", e)))?;
+
+    Ok(())
+}
+
+fn begin_calendar(name: &str) -> String {
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, "PRODID:-//Ironhide//Claude Code Usage//EN");
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_line(&mut ics, &format!("X-WR-CALNAME:{}", escape_text(name)));
+    ics
+}
+
+/// Append a content line, folded to 75 octets and ended with CRLF
+fn push_line(ics: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            ics.push_str("\r\n ");
+            // The leading space of a continuation line counts toward its length
+            octets = 1;
+        }
+        ics.push(c);
+        octets += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// Escape a TEXT value: backslashes, semicolons, commas and line breaks
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+fn format_datetime(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(date: &str, duration_ms: u64) -> ExportableSession {
+        ExportableSession {
+            session_id: "test-123".to_string(),
+            date: date.to_string(),
+            project_name: "my-app, backend".to_string(),
+            model: Some("claude-opus-4-5-20251101".to_string()),
+            turns: 5,
+            tokens: 10000,
+            cost: 1.5,
+            cache_write_5m_cost: 0.0,
+            cache_write_1h_cost: 0.0,
+            duration_ms,
+            efficiency_score: Some(0.85),
+        }
+    }
+
+    #[test]
+    fn test_render_sessions_ics() {
+        let ics = render_sessions_ics(&[
+            session("2026-01-14T09:00:00+02:00", 90 * 60_000),
+            session("unknown", 0),
+        ]);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("UID:test-123@ironhide\r\n"));
+        assert!(ics.contains("DTSTART:20260114T070000Z\r\n"));
+        assert!(ics.contains("DTEND:20260114T083000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Claude: my-app\\, backend ($1.50)\r\n"));
+        // Unfolded, the description keeps its escaped line breaks
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains("DESCRIPTION:Project: my-app\\, backend\\nCost: $1.50\\nTokens: 10000\\nTurns: 5"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+    }
+
+    #[test]
+    fn test_render_trends_ics() {
+        let trend = ExportableTrend {
+            date: "2026-01-14".to_string(),
+            session_count: 3,
+            total_turns: 15,
+            total_cost: 4.5,
+            total_tokens: 30000,
+            avg_efficiency_score: None,
+        };
+        let ics = render_trends_ics(&[trend]);
+        assert!(ics.contains("DTSTART;VALUE=DATE:20260114\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20260115\r\n"));
+        assert!(ics.contains("SUMMARY:Claude: $4.50\\, 3 sessions\r\n"));
+    }
+
+    #[test]
+    fn test_push_line_folds_multibyte_text() {
+        let mut ics = String::new();
+        push_line(&mut ics, &"é".repeat(60));
+        let lines: Vec<&str> = ics.trim_end().split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert_eq!(ics.replace("\r\n ", "").trim_end(), "é".repeat(60));
+    }
+}
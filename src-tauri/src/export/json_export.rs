@@ -10,6 +10,7 @@ use std::path::PathBuf;
 use serde::Serialize;
 
 use super::{ExportableSession, ExportableTurn, ExportableTrend};
+use crate::trends::report::WeeklyReport;
 use crate::CommandError;
 
 /// Full session export with optional turns
@@ -52,6 +53,14 @@ pub struct TrendsExportJson {
     pub summary: TrendsSummary,
 }
 
+/// Weekly report export structure
+#[derive(Debug, Clone, Serialize)]
+pub struct WeeklyReportExportJson<'a> {
+    pub export_date: String,
+    pub export_version: &'static str,
+    pub report: &'a WeeklyReport,
+}
+
 /// Summary of trend data
 #[derive(Debug, Clone, Serialize)]
 pub struct TrendsSummary {
@@ -175,6 +184,26 @@ pub fn write_trends_json(
     Ok(())
 }
 
+/// Write a weekly report to JSON format
+pub fn write_weekly_report_json(report: &WeeklyReport, path: &PathBuf) -> Result<(), CommandError> {
+    let export = WeeklyReportExportJson {
+        export_date: chrono::Utc::now().to_rfc3339(),
+        export_version: EXPORT_VERSION,
+        report,
+    };
+
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize JSON: {}", e)))?;
+
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| CommandError::Internal(format!("Failed to create JSON file: {}", e)))?;
+
+    file.write_all(json.as_bytes())
+        .map_err(|e| CommandError::Internal(format!("Failed to write JSON file: {}", e)))?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,6 +218,8 @@ mod tests {
             turns: 5,
             tokens: 10000,
             cost: 1.50,
+            cache_write_5m_cost: 0.0,
+            cache_write_1h_cost: 0.0,
             duration_ms: 60000,
             efficiency_score: Some(0.85),
         }
@@ -330,6 +361,8 @@ mod tests {
                 turns: 5,
                 tokens: 10000,
                 cost: 1.0,
+                cache_write_5m_cost: 0.0,
+                cache_write_1h_cost: 0.0,
                 duration_ms: 60000,
                 efficiency_score: None,
             },
@@ -341,6 +374,8 @@ mod tests {
                 turns: 5,
                 tokens: 10000,
                 cost: 1.0,
+                cache_write_5m_cost: 0.0,
+                cache_write_1h_cost: 0.0,
                 duration_ms: 60000,
                 efficiency_score: None,
             },
@@ -373,6 +408,8 @@ mod tests {
                 turns: 10,
                 tokens: 20000,
                 cost: 2.0,
+                cache_write_5m_cost: 0.0,
+                cache_write_1h_cost: 0.0,
                 duration_ms: 60000,
                 efficiency_score: None,
             },
@@ -384,6 +421,8 @@ mod tests {
                 turns: 20,
                 tokens: 40000,
                 cost: 4.0,
+                cache_write_5m_cost: 0.0,
+                cache_write_1h_cost: 0.0,
                 duration_ms: 120000,
                 efficiency_score: None,
             },
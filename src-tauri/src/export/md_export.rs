@@ -0,0 +1,568 @@
+//! Markdown export functionality
+//!
+//! Renders human-readable session reports (summary table, efficiency
+//! grades, tool usage, detected anti-patterns and per-turn breakdown)
+//! that can be pasted into a PR description or wiki page.
+
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::{truncate_str, ExportableSession, ExportableTrend, ExportableTurn};
+use crate::commands::EfficiencyResponse;
+use crate::patterns::DetectedPattern;
+use crate::trends::report::WeeklyReport;
+use crate::CommandError;
+
+/// Maximum length of a user prompt preview in the turn table
+const PROMPT_PREVIEW_BYTES: usize = 80;
+
+/// Everything rendered for one session in a Markdown report
+#[derive(Debug, Clone)]
+pub struct SessionReport {
+    pub session: ExportableSession,
+    /// Efficiency metrics and grade (omitted when metrics aren't requested)
+    pub efficiency: Option<EfficiencyResponse>,
+    /// Tool call counts, most used first
+    pub tool_usage: Vec<(String, u32)>,
+    /// Anti-patterns detected in this session
+    pub antipatterns: Vec<DetectedPattern>,
+    /// Per-turn breakdown (empty when turns aren't requested)
+    pub turns: Vec<ExportableTurn>,
+}
+
+/// Render a Markdown report for the given sessions
+pub fn render_sessions_markdown(reports: &[SessionReport]) -> String {
+    let mut md = String::new();
+
+    let total_cost: f64 = reports.iter().map(|r| r.session.cost).sum();
+    let total_tokens: u64 = reports.iter().map(|r| r.session.tokens).sum();
+    let total_turns: u32 = reports.iter().map(|r| r.session.turns).sum();
+
+    let _ = writeln!(md, "# Claude Code Session Report\n");
+    let _ = writeln!(
+        md,
+        "_Exported {} · {} sessions · {} turns · {} tokens · {}_\n",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        reports.len(),
+        total_turns,
+        format_count(total_tokens),
+        format_cost(total_cost),
+    );
+
+    if reports.is_empty() {
+        let _ = writeln!(md, "No sessions matched the export filters.");
+        return md;
+    }
+
+    // Summary table
+    let _ = writeln!(md, "## Summary\n");
+    let _ = writeln!(
+        md,
+        "| Session | Date | Project | Model | Turns | Tokens | Cost | Duration | Grade |"
+    );
+    let _ = writeln!(md, "|---|---|---|---|--:|--:|--:|--:|---|");
+    for report in reports {
+        let s = &report.session;
+        let _ = writeln!(
+            md,
+            "| `{}` | {} | {} | {} | {} | {} | {} | {} | {} |",
+            short_id(&s.session_id),
+            format_date(&s.date),
+            escape_cell(&s.project_name),
+            escape_cell(s.model.as_deref().unwrap_or("-")),
+            s.turns,
+            format_count(s.tokens),
+            format_cost(s.cost),
+            format_duration(s.duration_ms),
+            report
+                .efficiency
+                .as_ref()
+                .map(|e| e.oes_grade.as_str())
+                .unwrap_or("-"),
+        );
+    }
+
+    for report in reports {
+        md.push('\n');
+        render_session_section(&mut md, report);
+    }
+
+    md
+}
+
+/// Render the detail section for a single session
+fn render_session_section(md: &mut String, report: &SessionReport) {
+    let s = &report.session;
+
+    let _ = writeln!(md, "## {} · `{}`\n", escape_cell(&s.project_name), short_id(&s.session_id));
+    let _ = writeln!(md, "| | |");
+    let _ = writeln!(md, "|---|---|");
+    let _ = writeln!(md, "| Session ID | `{}` |", s.session_id);
+    let _ = writeln!(md, "| Started | {} |", format_date(&s.date));
+    let _ = writeln!(md, "| Model | {} |", escape_cell(s.model.as_deref().unwrap_or("-")));
+    let _ = writeln!(md, "| Turns | {} |", s.turns);
+    let _ = writeln!(md, "| Tokens | {} |", format_count(s.tokens));
+    let _ = writeln!(md, "| Cost | {} |", format_cost(s.cost));
+    if s.cache_write_5m_cost > 0.0 || s.cache_write_1h_cost > 0.0 {
+        let _ = writeln!(
+            md,
+            "| Cache Writes | {} (5m) · {} (1h) |",
+            format_cost(s.cache_write_5m_cost),
+            format_cost(s.cache_write_1h_cost)
+        );
+    }
+    let _ = writeln!(md, "| Duration | {} |", format_duration(s.duration_ms));
+
+    if let Some(eff) = &report.efficiency {
+        let _ = writeln!(md, "\n### Efficiency\n");
+        let _ = writeln!(
+            md,
+            "Overall: **{}** ({:.0}/100)\n",
+            eff.oes_grade,
+            eff.oes_score * 100.0
+        );
+        let _ = writeln!(md, "| Metric | Value |");
+        let _ = writeln!(md, "|---|--:|");
+        let _ = writeln!(md, "| Cache Efficiency Ratio (CER) | {:.1}% |", eff.cer * 100.0);
+        let _ = writeln!(md, "| Context Growth Rate (CGR) | {:.2} |", eff.cgr);
+        if let Some(sei) = eff.sei {
+            let _ = writeln!(md, "| Subagent Efficiency Index (SEI) | {:.2} |", sei);
+        }
+        let _ = writeln!(md, "| Workflow Friction Score (WFS) | {:.2} |", eff.wfs);
+        let _ = writeln!(md, "| Cost per Deliverable Unit | {} |", format_cost(eff.cpdu));
+        let _ = writeln!(md, "| Cycles per Deliverable | {:.2} |", eff.cpd);
+    }
+
+    if !report.tool_usage.is_empty() {
+        let _ = writeln!(md, "\n### Tool Usage\n");
+        let _ = writeln!(md, "| Tool | Calls |");
+        let _ = writeln!(md, "|---|--:|");
+        for (tool, count) in &report.tool_usage {
+            let _ = writeln!(md, "| {} | {} |", escape_cell(tool), count);
+        }
+    }
+
+    if report.efficiency.is_some() {
+        let _ = writeln!(md, "\n### Anti-Patterns\n");
+        if report.antipatterns.is_empty() {
+            let _ = writeln!(md, "None detected.");
+        }
+        for pattern in &report.antipatterns {
+            let location = pattern
+                .turn_number
+                .map(|n| format!(", turn {}", n))
+                .unwrap_or_default();
+            let _ = writeln!(
+                md,
+                "- **{}** ({}{}): {} _Suggestion: {}_",
+                pattern.pattern_type.display_name(),
+                pattern.severity.as_str(),
+                location,
+                pattern.description,
+                pattern.suggestion,
+            );
+        }
+    }
+
+    if !report.turns.is_empty() {
+        let _ = writeln!(md, "\n### Turns\n");
+        let _ = writeln!(md, "| # | Started | Tokens | Cost | Tools | Prompt |");
+        let _ = writeln!(md, "|--:|---|--:|--:|---|---|");
+        for turn in &report.turns {
+            let prompt = turn
+                .user_message_preview
+                .as_deref()
+                .map(|m| {
+                    if m.len() > PROMPT_PREVIEW_BYTES {
+                        format!("{}...", truncate_str(m, PROMPT_PREVIEW_BYTES))
+                    } else {
+                        m.to_string()
+                    }
+                })
+                .unwrap_or_default();
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} | {} |",
+                turn.turn_number,
+                format_date(&turn.started_at),
+                format_count(turn.total_tokens),
+                format_cost(turn.cost),
+                escape_cell(&turn.tools_used),
+                escape_cell(&prompt),
+            );
+        }
+    }
+}
+
+/// Render a Markdown table of daily trends
+pub fn render_trends_markdown(trends: &[ExportableTrend], days: u32) -> String {
+    let mut md = String::new();
+
+    let total_sessions: i32 = trends.iter().map(|t| t.session_count).sum();
+    let total_cost: f64 = trends.iter().map(|t| t.total_cost).sum();
+
+    let _ = writeln!(md, "# Claude Code Usage Trends\n");
+    let _ = writeln!(
+        md,
+        "_Last {} days · {} sessions · {}_\n",
+        days,
+        total_sessions,
+        format_cost(total_cost),
+    );
+    let _ = writeln!(md, "| Date | Sessions | Turns | Tokens | Cost | Avg CER |");
+    let _ = writeln!(md, "|---|--:|--:|--:|--:|--:|");
+    for trend in trends {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {} | {} |",
+            trend.date,
+            trend.session_count,
+            trend.total_turns,
+            format_count(trend.total_tokens.max(0) as u64),
+            format_cost(trend.total_cost),
+            trend
+                .avg_efficiency_score
+                .map(|e| format!("{:.1}%", e * 100.0))
+                .unwrap_or_else(|| "-".to_string()),
+        );
+    }
+
+    md
+}
+
+/// Render a weekly report: totals against the previous week, the days of the
+/// week, top sessions, anti-patterns and recommendations
+pub fn render_weekly_report_markdown(report: &WeeklyReport) -> String {
+    let mut md = String::new();
+    let (previous, week) = (&report.comparison.range_a, &report.comparison.range_b);
+    let deltas = &report.comparison.deltas;
+
+    let _ = writeln!(md, "# Weekly Report: {} to {}
+", report.week.start_date, report.week.end_date);
+    let _ = writeln!(md, "| Metric | Previous week | This week | Change |");
+    let _ = writeln!(md, "|---|--:|--:|--:|");
+    let rows = [
+        ("Cost", format_cost(previous.total_cost), format_cost(week.total_cost), &deltas.cost),
+        ("Sessions", previous.sessions.to_string(), week.sessions.to_string(), &deltas.sessions),
+        ("Turns", previous.turns.to_string(), week.turns.to_string(), &deltas.turns),
+        ("Tokens", format_count(previous.total_tokens), format_count(week.total_tokens), &deltas.tokens),
+        (
+            "Avg CER",
+            format_percent(previous.avg_efficiency),
+            format_percent(week.avg_efficiency),
+            &deltas.efficiency,
+        ),
+    ];
+    for (metric, before, after, delta) in rows {
+        let _ = writeln!(md, "| {} | {} | {} | {:+.1}% |", metric, before, after, delta.percent_change);
+    }
+
+    let _ = writeln!(md, "\n## Daily\n");
+    let _ = writeln!(md, "| Date | Sessions | Turns | Cost | Avg CER |");
+    let _ = writeln!(md, "|---|--:|--:|--:|--:|");
+    for day in &report.daily {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {} |",
+            day.date,
+            day.sessions,
+            day.turns,
+            format_cost(day.total_cost),
+            if day.sessions > 0 { format_percent(day.avg_efficiency) } else { "-".to_string() },
+        );
+    }
+
+    if !report.top_sessions.is_empty() {
+        let _ = writeln!(md, "\n## Top Sessions\n");
+        let _ = writeln!(md, "| Session | Project | Started | Turns | Cost |");
+        let _ = writeln!(md, "|---|---|---|--:|--:|");
+        for session in &report.top_sessions {
+            let _ = writeln!(
+                md,
+                "| `{}` | {} | {} | {} | {} |",
+                short_id(&session.session_id),
+                escape_cell(&session.project_name),
+                format_date(&session.started_at),
+                session.turns,
+                format_cost(session.cost),
+            );
+        }
+    }
+
+    if !report.antipatterns.is_empty() {
+        let _ = writeln!(md, "\n## Anti-patterns\n");
+        let _ = writeln!(md, "| Pattern | This week | Previous week | Sessions | Impact |");
+        let _ = writeln!(md, "|---|--:|--:|--:|--:|");
+        for pattern in &report.antipatterns {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} |",
+                pattern.pattern_type.display_name(),
+                pattern.occurrences,
+                pattern.previous_occurrences,
+                pattern.sessions,
+                format_cost(pattern.impact_cost),
+            );
+        }
+    }
+
+    if !report.recommendations.is_empty() {
+        let _ = writeln!(md, "\n## Recommendations\n");
+        for (i, rec) in report.recommendations.iter().enumerate() {
+            let _ = writeln!(md, "{}. **{}**: {}", i + 1, rec.title, rec.description.replace('\n', " "));
+            for item in &rec.action_items {
+                let _ = writeln!(md, "   - {}", item);
+            }
+        }
+    }
+
+    md
+}
+
+/// Write a Markdown session report
+pub fn write_sessions_markdown(
+    reports: &[SessionReport],
+    path: &PathBuf,
+) -> Result<(), CommandError> {
+    write_markdown(&render_sessions_markdown(reports), path)
+}
+
+/// Write a Markdown trends report
+pub fn write_trends_markdown(
+    trends: &[ExportableTrend],
+    days: u32,
+    path: &PathBuf,
+) -> Result<(), CommandError> {
+    write_markdown(&render_trends_markdown(trends, days), path)
+}
+
+/// Write a Markdown weekly report
+pub fn write_weekly_report_markdown(report: &WeeklyReport, path: &PathBuf) -> Result<(), CommandError> {
+    write_markdown(&render_weekly_report_markdown(report), path)
+}
+
+fn write_markdown(content: &str, path: &PathBuf) -> Result<(), CommandError> {
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| CommandError::Internal(format!("Failed to create Markdown file: {}", e)))?;
+
+    file.write_all(content.as_bytes())
+        .map_err(|e| CommandError::Internal(format!("Failed to write Markdown file: {}", e)))?;
+
+    Ok(())
+}
+
+/// Make text safe for a table cell: no pipes, no line breaks
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// First 8 characters of a session ID
+fn short_id(session_id: &str) -> &str {
+    truncate_str(session_id, 8)
+}
+
+/// Format an RFC 3339 timestamp as "YYYY-MM-DD HH:MM", passing through anything else
+pub(super) fn format_date(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.with_timezone(&chrono::Utc).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+pub(super) fn format_cost(cost: f64) -> String {
+    format!("${:.2}", cost)
+}
+
+/// Format a 0-1 ratio as a percentage
+fn format_percent(ratio: f64) -> String {
+    format!("{:.1}%", ratio * 100.0)
+}
+
+/// Format a count with thousands separators
+pub(super) fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Format a duration as e.g. "1h 5m", "12m 3s" or "45s"
+pub(super) fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if h > 0 {
+        format!("{}h {}m", h, m)
+    } else if m > 0 {
+        format!("{}m {}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::types::{AntiPatternType, Severity};
+    use crate::metrics::deliverables::DeliverableEstimator;
+
+    fn create_test_report() -> SessionReport {
+        SessionReport {
+            session: ExportableSession {
+                session_id: "abcdef12-3456".to_string(),
+                date: "2026-01-14T07:00:00Z".to_string(),
+                project_name: "test-project".to_string(),
+                model: Some("claude-opus-4-5-20251101".to_string()),
+                turns: 2,
+                tokens: 1234567,
+                cost: 1.5,
+                cache_write_5m_cost: 0.0,
+                cache_write_1h_cost: 0.0,
+                duration_ms: 3_900_000,
+                efficiency_score: Some(0.85),
+            },
+            efficiency: Some(EfficiencyResponse {
+                cer: 0.85,
+                cgr: 0.1,
+                sei: None,
+                wfs: 0.2,
+                cpdu: 0.5,
+                cpd: 1.0,
+                oes_score: 0.7,
+                oes_grade: "Good".to_string(),
+                deliverable_units: 4.0,
+                deliverable_estimator: DeliverableEstimator::CodeChanges,
+            }),
+            tool_usage: vec![("Read".to_string(), 3), ("Edit".to_string(), 1)],
+            antipatterns: vec![DetectedPattern {
+                pattern_type: AntiPatternType::CostSpike,
+                severity: Severity::Warning,
+                session_id: "abcdef12-3456".to_string(),
+                turn_number: Some(2),
+                description: "Turn cost 4x average.".to_string(),
+                impact_cost: 0.5,
+                suggestion: "Split the task".to_string(),
+                metric_value: 4.0,
+                threshold: 3.0,
+            }],
+            turns: vec![ExportableTurn {
+                session_id: "abcdef12-3456".to_string(),
+                turn_number: 1,
+                started_at: "2026-01-14T07:00:00Z".to_string(),
+                ended_at: None,
+                model: None,
+                input_tokens: 100,
+                output_tokens: 50,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                total_tokens: 150,
+                cost: 0.01,
+                tool_count: 1,
+                tools_used: "Read".to_string(),
+                user_message_preview: Some("fix the | pipe\nplease".to_string()),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_render_sessions_markdown() {
+        let md = render_sessions_markdown(&[create_test_report()]);
+
+        assert!(md.starts_with("# Claude Code Session Report"));
+        assert!(md.contains("| `abcdef12` | 2026-01-14 07:00 | test-project |"));
+        assert!(md.contains("1,234,567"));
+        assert!(md.contains("$1.50"));
+        assert!(md.contains("1h 5m"));
+        assert!(md.contains("Overall: **Good** (70/100)"));
+        assert!(md.contains("| Read | 3 |"));
+        assert!(md.contains("- **Cost Spike** (warning, turn 2)"));
+        // Cell text is escaped so the table stays intact
+        assert!(md.contains("fix the \\| pipe please"));
+    }
+
+    #[test]
+    fn test_render_sessions_markdown_without_metrics() {
+        let mut report = create_test_report();
+        report.efficiency = None;
+        report.turns.clear();
+
+        let md = render_sessions_markdown(&[report]);
+        assert!(!md.contains("### Efficiency"));
+        assert!(!md.contains("### Anti-Patterns"));
+        assert!(!md.contains("### Turns"));
+        assert!(md.contains("### Tool Usage"));
+
+        let empty = render_sessions_markdown(&[]);
+        assert!(empty.contains("No sessions matched"));
+    }
+
+    #[test]
+    fn test_write_trends_markdown() {
+        let path = std::env::temp_dir().join("test_trends.md");
+        let trends = vec![ExportableTrend {
+            date: "2026-01-14".to_string(),
+            session_count: 3,
+            total_turns: 15,
+            total_cost: 4.5,
+            total_tokens: 30000,
+            avg_efficiency_score: Some(0.82),
+        }];
+
+        write_trends_markdown(&trends, 7, &path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("| 2026-01-14 | 3 | 15 | 30,000 | $4.50 | 82.0% |"));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_render_weekly_report_markdown() {
+        use crate::trends::periods::{compare_periods, DateRange, PeriodTotals};
+        use crate::trends::report::{ReportSession, WeeklyReport};
+        use crate::trends::DailyTrend;
+
+        let range = |start: &str, end: &str| DateRange { start_date: start.to_string(), end_date: end.to_string() };
+        let daily = vec![
+            DailyTrend { sessions: 1, total_cost: 2.0, avg_efficiency: 0.5, ..DailyTrend::new("2026-03-03".to_string()) },
+            DailyTrend { sessions: 2, total_cost: 3.0, avg_efficiency: 0.8, ..DailyTrend::new("2026-03-10".to_string()) },
+        ];
+        let comparison = compare_periods(
+            PeriodTotals::new(range("2026-03-02", "2026-03-08"), 7, &daily, &[]),
+            &[],
+            PeriodTotals::new(range("2026-03-09", "2026-03-15"), 7, &daily, &[]),
+            &[],
+        );
+        let session = ReportSession {
+            session_id: "abcdef12-3456".to_string(),
+            project_path: "/work/app".to_string(),
+            project_name: "app".to_string(),
+            started_at: "2026-03-10T09:00:00Z".to_string(),
+            turns: 4,
+            cost: 3.0,
+        };
+        let report = WeeklyReport::new(comparison, &daily, vec![session], Vec::new(), Vec::new());
+
+        let md = render_weekly_report_markdown(&report);
+        assert!(md.starts_with("# Weekly Report: 2026-03-09 to 2026-03-15"));
+        assert!(md.contains("| Cost | $2.00 | $3.00 | +50.0% |"));
+        assert!(md.contains("| 2026-03-09 | 0 | 0 | $0.00 | - |"));
+        assert!(md.contains("| 2026-03-10 | 2 | 0 | $3.00 | 80.0% |"));
+        assert!(md.contains("| `abcdef12` | app | 2026-03-10 09:00 | 4 | $3.00 |"));
+        assert!(!md.contains("## Recommendations"));
+    }
+
+    #[test]
+    fn test_format_helpers() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_duration(45_000), "45s");
+        assert_eq!(format_duration(723_000), "12m 3s");
+        assert_eq!(format_date("not a date"), "not a date");
+    }
+}
@@ -1,11 +1,22 @@
-//! Export module for CSV and JSON export functionality
+//! Export module for CSV, JSON, Markdown, SQLite, Parquet, iCalendar and HTML export functionality
 //!
-//! Provides Tauri commands for exporting session data and trends
-//! in CSV and JSON formats.
+//! Provides Tauri commands for exporting session data and trends in CSV, JSON
+//! and Markdown formats, as SQLite databases or Parquet files for analysis in
+//! external tools, and as iCalendar files for calendar apps. Single sessions
+//! can also be exported as an HTML replay page, or as a bundle to attach to
+//! bug reports.
 
+pub mod bundle;
 pub mod csv_export;
+pub mod html_export;
+pub mod ics_export;
 pub mod json_export;
+pub mod md_export;
+pub mod parquet_export;
+pub mod sqlite_export;
+pub mod template;
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
@@ -20,6 +31,10 @@ use crate::models::metrics::DailyMetrics;
 pub enum ExportFormat {
     Csv,
     Json,
+    Markdown,
+    Sqlite,
+    Parquet,
+    Ical,
 }
 
 impl std::str::FromStr for ExportFormat {
@@ -29,8 +44,12 @@ impl std::str::FromStr for ExportFormat {
         match s.to_lowercase().as_str() {
             "csv" => Ok(ExportFormat::Csv),
             "json" => Ok(ExportFormat::Json),
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "sqlite" | "db" => Ok(ExportFormat::Sqlite),
+            "parquet" => Ok(ExportFormat::Parquet),
+            "ical" | "ics" => Ok(ExportFormat::Ical),
             _ => Err(CommandError::Internal(format!(
-                "Invalid export format: {}. Use 'csv' or 'json'",
+                "Invalid export format: {}. Use 'csv', 'json', 'markdown', 'sqlite', 'parquet' or 'ical'",
                 s
             ))),
         }
@@ -43,6 +62,10 @@ impl ExportFormat {
         match self {
             ExportFormat::Csv => "csv",
             ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Sqlite => "sqlite",
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Ical => "ics",
         }
     }
 }
@@ -50,7 +73,7 @@ impl ExportFormat {
 /// Options for export operations
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExportOptions {
-    /// Export format (csv or json)
+    /// Export format (csv, json, markdown, sqlite, parquet or ical)
     pub format: String,
     /// Include turn-level details
     #[serde(default)]
@@ -88,6 +111,12 @@ pub struct ExportableSession {
     pub turns: u32,
     pub tokens: u64,
     pub cost: f64,
+    /// Part of `cost` spent writing the 5-minute prompt cache
+    #[serde(default)]
+    pub cache_write_5m_cost: f64,
+    /// Part of `cost` spent writing the 1-hour prompt cache
+    #[serde(default)]
+    pub cache_write_1h_cost: f64,
     pub duration_ms: u64,
     pub efficiency_score: Option<f64>,
 }
@@ -102,6 +131,8 @@ impl From<&SessionSummary> for ExportableSession {
             turns: summary.total_turns,
             tokens: summary.total_tokens,
             cost: summary.total_cost,
+            cache_write_5m_cost: 0.0, // Summaries don't split cost; set from turns on export
+            cache_write_1h_cost: 0.0,
             duration_ms: summary.duration_ms,
             efficiency_score: None, // Will be populated separately if metrics included
         }
@@ -178,6 +209,54 @@ impl From<&DailyMetrics> for ExportableTrend {
     }
 }
 
+/// Aggregate exported sessions by the UTC date they started
+///
+/// Sessions without a start date are left out. The efficiency average covers
+/// the sessions that have a score.
+pub fn daily_trends(sessions: &[ExportableSession]) -> Vec<ExportableTrend> {
+    // date -> (trend, efficiency sum, sessions with efficiency)
+    let mut by_date: BTreeMap<&str, (ExportableTrend, f64, u32)> = BTreeMap::new();
+
+    for session in sessions {
+        let date = match session.date.get(..10) {
+            Some(date) if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() => date,
+            _ => continue,
+        };
+        let (trend, efficiency_sum, efficiency_count) = by_date.entry(date).or_insert_with(|| {
+            (
+                ExportableTrend {
+                    date: date.to_string(),
+                    session_count: 0,
+                    total_turns: 0,
+                    total_cost: 0.0,
+                    total_tokens: 0,
+                    avg_efficiency_score: None,
+                },
+                0.0,
+                0,
+            )
+        });
+        trend.session_count += 1;
+        trend.total_turns += session.turns as i32;
+        trend.total_cost += session.cost;
+        trend.total_tokens += session.tokens as i64;
+        if let Some(efficiency) = session.efficiency_score {
+            *efficiency_sum += efficiency;
+            *efficiency_count += 1;
+        }
+    }
+
+    by_date
+        .into_values()
+        .map(|(mut trend, efficiency_sum, efficiency_count)| {
+            if efficiency_count > 0 {
+                trend.avg_efficiency_score = Some(efficiency_sum / efficiency_count as f64);
+            }
+            trend
+        })
+        .collect()
+}
+
 /// Get the default export directory (Downloads folder or temp dir)
 pub fn get_export_directory() -> PathBuf {
     dirs::download_dir()
@@ -193,7 +272,12 @@ pub fn generate_export_filename(prefix: &str, extension: &str) -> String {
 
 // Re-export commands
 pub use csv_export::*;
+pub use html_export::*;
+pub use ics_export::*;
 pub use json_export::*;
+pub use md_export::*;
+pub use parquet_export::*;
+pub use sqlite_export::*;
 
 #[cfg(test)]
 mod tests {
@@ -205,6 +289,11 @@ mod tests {
         assert!(matches!("CSV".parse::<ExportFormat>().unwrap(), ExportFormat::Csv));
         assert!(matches!("json".parse::<ExportFormat>().unwrap(), ExportFormat::Json));
         assert!(matches!("JSON".parse::<ExportFormat>().unwrap(), ExportFormat::Json));
+        assert!(matches!("markdown".parse::<ExportFormat>().unwrap(), ExportFormat::Markdown));
+        assert!(matches!("md".parse::<ExportFormat>().unwrap(), ExportFormat::Markdown));
+        assert!(matches!("sqlite".parse::<ExportFormat>().unwrap(), ExportFormat::Sqlite));
+        assert!(matches!("Parquet".parse::<ExportFormat>().unwrap(), ExportFormat::Parquet));
+        assert!(matches!("ics".parse::<ExportFormat>().unwrap(), ExportFormat::Ical));
         assert!("xml".parse::<ExportFormat>().is_err());
     }
 
@@ -212,6 +301,42 @@ mod tests {
     fn test_export_format_extension() {
         assert_eq!(ExportFormat::Csv.extension(), "csv");
         assert_eq!(ExportFormat::Json.extension(), "json");
+        assert_eq!(ExportFormat::Markdown.extension(), "md");
+        assert_eq!(ExportFormat::Sqlite.extension(), "sqlite");
+        assert_eq!(ExportFormat::Parquet.extension(), "parquet");
+        assert_eq!(ExportFormat::Ical.extension(), "ics");
+    }
+
+    #[test]
+    fn test_daily_trends() {
+        let session = |date: &str, cost: f64, efficiency: Option<f64>| ExportableSession {
+            session_id: format!("s-{}-{}", date, cost),
+            date: date.to_string(),
+            project_name: "app".to_string(),
+            model: None,
+            turns: 2,
+            tokens: 100,
+            cost,
+            cache_write_5m_cost: 0.0,
+            cache_write_1h_cost: 0.0,
+            duration_ms: 0,
+            efficiency_score: efficiency,
+        };
+        let trends = daily_trends(&[
+            session("2026-02-06T09:00:00Z", 2.0, None),
+            session("2026-02-05T10:00:00Z", 1.0, Some(0.8)),
+            session("2026-02-05T18:00:00Z", 0.5, Some(0.6)),
+            session("unknown", 9.0, Some(0.1)),
+        ]);
+
+        assert_eq!(trends.len(), 2);
+        assert_eq!(trends[0].date, "2026-02-05");
+        assert_eq!(trends[0].session_count, 2);
+        assert_eq!(trends[0].total_turns, 4);
+        assert_eq!(trends[0].total_cost, 1.5);
+        assert!((trends[0].avg_efficiency_score.unwrap() - 0.7).abs() < 1e-9);
+        assert_eq!(trends[1].date, "2026-02-06");
+        assert_eq!(trends[1].avg_efficiency_score, None);
     }
 
     #[test]
@@ -289,6 +414,7 @@ mod tests {
             total_turns: 10,
             total_tokens: 50000,
             total_cost: 5.0,
+            total_cost_including_subagents: 5.0,
             duration_ms: 3600000,
             is_subagent: false,
             file_path: "/path/to/session.jsonl".to_string(),
@@ -323,6 +449,7 @@ mod tests {
                 cache_read: 2000,
                 cache_write: 100,
                 total: 3600,
+                thinking: 0,
             },
             cost: 0.30,
             tool_count: 3,
@@ -361,6 +488,7 @@ mod tests {
                 cache_read: 0,
                 cache_write: 0,
                 total: 0,
+                thinking: 0,
             },
             cost: 0.0,
             tool_count: 0,
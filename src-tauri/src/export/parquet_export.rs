@@ -0,0 +1,226 @@
+//! Parquet export functionality
+//!
+//! A Parquet file holds a single table, so a sessions export is a directory
+//! with `sessions.parquet`, `daily_metrics.parquet` and, when turns are
+//! included, `turns.parquet`. A trends export is one `daily_metrics` file.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+
+use super::{ExportableSession, ExportableTrend, ExportableTurn};
+use crate::CommandError;
+
+/// Values of one column, all rows of the table
+enum Values {
+    Text(Vec<String>),
+    OptionalText(Vec<Option<String>>),
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+    OptionalFloat(Vec<Option<f64>>),
+}
+
+impl Values {
+    /// Field declaration in Parquet's message type syntax
+    fn field(&self, name: &str) -> String {
+        match self {
+            Values::Text(_) => format!("REQUIRED BYTE_ARRAY {} (UTF8);", name),
+            Values::OptionalText(_) => format!("OPTIONAL BYTE_ARRAY {} (UTF8);", name),
+            Values::Int(_) => format!("REQUIRED INT64 {};", name),
+            Values::Float(_) => format!("REQUIRED DOUBLE {};", name),
+            Values::OptionalFloat(_) => format!("OPTIONAL DOUBLE {};", name),
+        }
+    }
+}
+
+/// Definition levels of an optional column (1 = value present), and the
+/// present values
+fn split_optional<T: Clone>(values: &[Option<T>]) -> (Vec<i16>, Vec<T>) {
+    let levels = values.iter().map(|v| v.is_some() as i16).collect();
+    let present = values.iter().flatten().cloned().collect();
+    (levels, present)
+}
+
+fn parquet_err(e: ParquetError) -> CommandError {
+    CommandError::Internal(format!("Failed to write Parquet file: {}", e))
+}
+
+/// Write columns as a single row group Parquet file
+fn write_table(name: &str, columns: Vec<(&str, Values)>, path: &Path) -> Result<(), CommandError> {
+    let fields: String = columns.iter().map(|(column, values)| values.field(column)).collect();
+    let schema = Arc::new(parse_message_type(&format!("message {} {{ {} }}", name, fields)).map_err(parquet_err)?);
+    let file = File::create(path)
+        .map_err(|e| CommandError::Internal(format!("Failed to create Parquet file: {}", e)))?;
+    let mut writer = SerializedFileWriter::new(file, schema, Arc::new(WriterProperties::builder().build()))
+        .map_err(parquet_err)?;
+
+    let mut row_group = writer.next_row_group().map_err(parquet_err)?;
+    for (_, values) in &columns {
+        let mut column = match row_group.next_column().map_err(parquet_err)? {
+            Some(column) => column,
+            None => break,
+        };
+        match values {
+            Values::Text(v) => {
+                let v: Vec<ByteArray> = v.iter().map(|s| ByteArray::from(s.as_str())).collect();
+                column.typed::<ByteArrayType>().write_batch(&v, None, None)
+            }
+            Values::OptionalText(v) => {
+                let (levels, v) = split_optional(v);
+                let v: Vec<ByteArray> = v.iter().map(|s| ByteArray::from(s.as_str())).collect();
+                column.typed::<ByteArrayType>().write_batch(&v, Some(&levels), None)
+            }
+            Values::Int(v) => column.typed::<Int64Type>().write_batch(v, None, None),
+            Values::Float(v) => column.typed::<DoubleType>().write_batch(v, None, None),
+            Values::OptionalFloat(v) => {
+                let (levels, v) = split_optional(v);
+                column.typed::<DoubleType>().write_batch(&v, Some(&levels), None)
+            }
+        }
+        .map_err(parquet_err)?;
+        column.close().map_err(parquet_err)?;
+    }
+    row_group.close().map_err(parquet_err)?;
+    writer.close().map_err(parquet_err)?;
+    Ok(())
+}
+
+fn write_daily_metrics(trends: &[ExportableTrend], path: &Path) -> Result<(), CommandError> {
+    write_table(
+        "daily_metrics",
+        vec![
+            ("date", Values::Text(trends.iter().map(|t| t.date.clone()).collect())),
+            ("session_count", Values::Int(trends.iter().map(|t| t.session_count as i64).collect())),
+            ("total_turns", Values::Int(trends.iter().map(|t| t.total_turns as i64).collect())),
+            ("total_cost", Values::Float(trends.iter().map(|t| t.total_cost).collect())),
+            ("total_tokens", Values::Int(trends.iter().map(|t| t.total_tokens).collect())),
+            ("avg_efficiency_score", Values::OptionalFloat(trends.iter().map(|t| t.avg_efficiency_score).collect())),
+        ],
+        path,
+    )
+}
+
+/// Write sessions, their turns (when given) and daily metrics as Parquet
+/// files in the directory `dir`
+pub fn write_sessions_parquet(
+    sessions: &[ExportableSession],
+    turns_map: Option<&HashMap<String, Vec<ExportableTurn>>>,
+    daily: &[ExportableTrend],
+    dir: &Path,
+) -> Result<(), CommandError> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| CommandError::Internal(format!("Failed to create export directory: {}", e)))?;
+
+    write_table(
+        "sessions",
+        vec![
+            ("session_id", Values::Text(sessions.iter().map(|s| s.session_id.clone()).collect())),
+            ("date", Values::Text(sessions.iter().map(|s| s.date.clone()).collect())),
+            ("project_name", Values::Text(sessions.iter().map(|s| s.project_name.clone()).collect())),
+            ("model", Values::OptionalText(sessions.iter().map(|s| s.model.clone()).collect())),
+            ("turns", Values::Int(sessions.iter().map(|s| s.turns as i64).collect())),
+            ("tokens", Values::Int(sessions.iter().map(|s| s.tokens as i64).collect())),
+            ("cost", Values::Float(sessions.iter().map(|s| s.cost).collect())),
+            ("cache_write_5m_cost", Values::Float(sessions.iter().map(|s| s.cache_write_5m_cost).collect())),
+            ("cache_write_1h_cost", Values::Float(sessions.iter().map(|s| s.cache_write_1h_cost).collect())),
+            ("duration_ms", Values::Int(sessions.iter().map(|s| s.duration_ms as i64).collect())),
+            ("efficiency_score", Values::OptionalFloat(sessions.iter().map(|s| s.efficiency_score).collect())),
+        ],
+        &dir.join("sessions.parquet"),
+    )?;
+
+    if let Some(turns_map) = turns_map {
+        let turns: Vec<&ExportableTurn> = sessions
+            .iter()
+            .filter_map(|s| turns_map.get(&s.session_id))
+            .flatten()
+            .collect();
+        write_table(
+            "turns",
+            vec![
+                ("session_id", Values::Text(turns.iter().map(|t| t.session_id.clone()).collect())),
+                ("turn_number", Values::Int(turns.iter().map(|t| t.turn_number as i64).collect())),
+                ("started_at", Values::Text(turns.iter().map(|t| t.started_at.clone()).collect())),
+                ("ended_at", Values::OptionalText(turns.iter().map(|t| t.ended_at.clone()).collect())),
+                ("model", Values::OptionalText(turns.iter().map(|t| t.model.clone()).collect())),
+                ("input_tokens", Values::Int(turns.iter().map(|t| t.input_tokens as i64).collect())),
+                ("output_tokens", Values::Int(turns.iter().map(|t| t.output_tokens as i64).collect())),
+                ("cache_read_tokens", Values::Int(turns.iter().map(|t| t.cache_read_tokens as i64).collect())),
+                ("cache_write_tokens", Values::Int(turns.iter().map(|t| t.cache_write_tokens as i64).collect())),
+                ("total_tokens", Values::Int(turns.iter().map(|t| t.total_tokens as i64).collect())),
+                ("cost", Values::Float(turns.iter().map(|t| t.cost).collect())),
+                ("tool_count", Values::Int(turns.iter().map(|t| t.tool_count as i64).collect())),
+                ("tools_used", Values::Text(turns.iter().map(|t| t.tools_used.clone()).collect())),
+                ("user_message_preview", Values::OptionalText(turns.iter().map(|t| t.user_message_preview.clone()).collect())),
+            ],
+            &dir.join("turns.parquet"),
+        )?;
+    }
+
+    write_daily_metrics(daily, &dir.join("daily_metrics.parquet"))
+}
+
+/// Write daily trends to a Parquet file
+pub fn write_trends_parquet(trends: &[ExportableTrend], path: &Path) -> Result<(), CommandError> {
+    write_daily_metrics(trends, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    fn create_test_session(session_id: &str, model: Option<&str>) -> ExportableSession {
+        ExportableSession {
+            session_id: session_id.to_string(),
+            date: "2026-01-14T07:00:00Z".to_string(),
+            project_name: "test-project".to_string(),
+            model: model.map(str::to_string),
+            turns: 3,
+            tokens: 10000,
+            cost: 1.50,
+            cache_write_5m_cost: 0.0,
+            cache_write_1h_cost: 0.0,
+            duration_ms: 60000,
+            efficiency_score: None,
+        }
+    }
+
+    #[test]
+    fn test_write_sessions_parquet() {
+        let dir = std::env::temp_dir().join("ironhide_parquet_export_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let sessions = vec![
+            create_test_session("a", Some("claude-opus-4-5-20251101")),
+            create_test_session("b", None),
+        ];
+        let daily = crate::export::daily_trends(&sessions);
+
+        write_sessions_parquet(&sessions, None, &daily, &dir).unwrap();
+        assert!(!dir.join("turns.parquet").exists());
+
+        let reader = SerializedFileReader::new(File::open(dir.join("sessions.parquet")).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().map(|r| r.unwrap()).collect();
+        assert_eq!(rows[0].get_string(0).unwrap(), "a");
+        assert_eq!(rows[0].get_string(3).unwrap(), "claude-opus-4-5-20251101");
+        assert!(rows[1].get_string(3).is_err()); // null model
+        assert_eq!(rows[1].get_long(4).unwrap(), 3);
+        assert_eq!(rows[1].get_double(6).unwrap(), 1.5);
+
+        let reader = SerializedFileReader::new(File::open(dir.join("daily_metrics.parquet")).unwrap()).unwrap();
+        let row = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        assert_eq!(row.get_string(0).unwrap(), "2026-01-14");
+        assert_eq!(row.get_long(1).unwrap(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
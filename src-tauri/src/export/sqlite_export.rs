@@ -0,0 +1,241 @@
+//! SQLite export functionality
+//!
+//! Writes sessions, turns and daily metrics as tables of a standalone SQLite
+//! database that DuckDB, pandas or the sqlite3 shell can open directly.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use super::{ExportableSession, ExportableTrend, ExportableTurn};
+use crate::CommandError;
+
+const SESSIONS_TABLE: &str = r#"
+CREATE TABLE sessions (
+    session_id TEXT PRIMARY KEY,
+    date TEXT NOT NULL,
+    project_name TEXT NOT NULL,
+    model TEXT,
+    turns INTEGER NOT NULL,
+    tokens INTEGER NOT NULL,
+    cost REAL NOT NULL,
+    cache_write_5m_cost REAL NOT NULL,
+    cache_write_1h_cost REAL NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    efficiency_score REAL
+);
+"#;
+
+const TURNS_TABLE: &str = r#"
+CREATE TABLE turns (
+    session_id TEXT NOT NULL REFERENCES sessions(session_id),
+    turn_number INTEGER NOT NULL,
+    started_at TEXT NOT NULL,
+    ended_at TEXT,
+    model TEXT,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    cache_read_tokens INTEGER NOT NULL,
+    cache_write_tokens INTEGER NOT NULL,
+    total_tokens INTEGER NOT NULL,
+    cost REAL NOT NULL,
+    tool_count INTEGER NOT NULL,
+    tools_used TEXT NOT NULL,
+    user_message_preview TEXT,
+    PRIMARY KEY (session_id, turn_number)
+);
+"#;
+
+const DAILY_METRICS_TABLE: &str = r#"
+CREATE TABLE daily_metrics (
+    date TEXT PRIMARY KEY,
+    session_count INTEGER NOT NULL,
+    total_turns INTEGER NOT NULL,
+    total_cost REAL NOT NULL,
+    total_tokens INTEGER NOT NULL,
+    avg_efficiency_score REAL
+);
+"#;
+
+fn sqlite_err(e: rusqlite::Error) -> CommandError {
+    CommandError::Internal(format!("Failed to write SQLite export: {}", e))
+}
+
+/// Create an empty export database, replacing any file already at `path`
+fn create_export_db(path: &Path) -> Result<Connection, CommandError> {
+    if path.exists() {
+        std::fs::remove_file(path)
+            .map_err(|e| CommandError::Internal(format!("Failed to replace SQLite file: {}", e)))?;
+    }
+    Connection::open(path).map_err(sqlite_err)
+}
+
+/// Write sessions, their turns (when given) and daily metrics to a SQLite database
+pub fn write_sessions_sqlite(
+    sessions: &[ExportableSession],
+    turns_map: Option<&HashMap<String, Vec<ExportableTurn>>>,
+    daily: &[ExportableTrend],
+    path: &Path,
+) -> Result<(), CommandError> {
+    let mut conn = create_export_db(path)?;
+    let tx = conn.transaction().map_err(sqlite_err)?;
+
+    tx.execute_batch(SESSIONS_TABLE).map_err(sqlite_err)?;
+    {
+        let mut stmt = tx
+            .prepare("INSERT INTO sessions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)")
+            .map_err(sqlite_err)?;
+        for s in sessions {
+            stmt.execute(params![
+                s.session_id,
+                s.date,
+                s.project_name,
+                s.model,
+                s.turns,
+                s.tokens as i64,
+                s.cost,
+                s.cache_write_5m_cost,
+                s.cache_write_1h_cost,
+                s.duration_ms as i64,
+                s.efficiency_score,
+            ])
+            .map_err(sqlite_err)?;
+        }
+    }
+
+    if let Some(turns_map) = turns_map {
+        tx.execute_batch(TURNS_TABLE).map_err(sqlite_err)?;
+        let mut stmt = tx
+            .prepare("INSERT INTO turns VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)")
+            .map_err(sqlite_err)?;
+        // Session order, so rows come out the same on every export
+        for turn in sessions.iter().filter_map(|s| turns_map.get(&s.session_id)).flatten() {
+            stmt.execute(params![
+                turn.session_id,
+                turn.turn_number,
+                turn.started_at,
+                turn.ended_at,
+                turn.model,
+                turn.input_tokens as i64,
+                turn.output_tokens as i64,
+                turn.cache_read_tokens as i64,
+                turn.cache_write_tokens as i64,
+                turn.total_tokens as i64,
+                turn.cost,
+                turn.tool_count,
+                turn.tools_used,
+                turn.user_message_preview,
+            ])
+            .map_err(sqlite_err)?;
+        }
+    }
+
+    insert_daily_metrics(&tx, daily)?;
+    tx.commit().map_err(sqlite_err)
+}
+
+/// Write daily trends to a SQLite database with a single daily_metrics table
+pub fn write_trends_sqlite(trends: &[ExportableTrend], path: &Path) -> Result<(), CommandError> {
+    let mut conn = create_export_db(path)?;
+    let tx = conn.transaction().map_err(sqlite_err)?;
+    insert_daily_metrics(&tx, trends)?;
+    tx.commit().map_err(sqlite_err)
+}
+
+fn insert_daily_metrics(conn: &Connection, trends: &[ExportableTrend]) -> Result<(), CommandError> {
+    conn.execute_batch(DAILY_METRICS_TABLE).map_err(sqlite_err)?;
+    let mut stmt = conn
+        .prepare("INSERT INTO daily_metrics VALUES (?1, ?2, ?3, ?4, ?5, ?6)")
+        .map_err(sqlite_err)?;
+    for t in trends {
+        stmt.execute(params![
+            t.date,
+            t.session_count,
+            t.total_turns,
+            t.total_cost,
+            t.total_tokens,
+            t.avg_efficiency_score,
+        ])
+        .map_err(sqlite_err)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::daily_trends;
+
+    fn create_test_session(session_id: &str) -> ExportableSession {
+        ExportableSession {
+            session_id: session_id.to_string(),
+            date: "2026-01-14T07:00:00Z".to_string(),
+            project_name: "test-project".to_string(),
+            model: Some("claude-opus-4-5-20251101".to_string()),
+            turns: 1,
+            tokens: 10000,
+            cost: 1.50,
+            cache_write_5m_cost: 0.0,
+            cache_write_1h_cost: 0.0,
+            duration_ms: 60000,
+            efficiency_score: Some(0.85),
+        }
+    }
+
+    fn create_test_turn(session_id: &str) -> ExportableTurn {
+        ExportableTurn {
+            session_id: session_id.to_string(),
+            turn_number: 1,
+            started_at: "2026-01-14T07:00:00Z".to_string(),
+            ended_at: None,
+            model: None,
+            input_tokens: 100,
+            output_tokens: 50,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            total_tokens: 150,
+            cost: 0.01,
+            tool_count: 2,
+            tools_used: "Read, Edit".to_string(),
+            user_message_preview: Some("Fix the bug".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_write_sessions_sqlite() {
+        let path = std::env::temp_dir().join("ironhide_export_test.sqlite");
+        let sessions = vec![create_test_session("a"), create_test_session("b")];
+        let turns_map: HashMap<String, Vec<ExportableTurn>> =
+            [("a".to_string(), vec![create_test_turn("a")])].into_iter().collect();
+
+        // Writing twice replaces the earlier export
+        for _ in 0..2 {
+            write_sessions_sqlite(&sessions, Some(&turns_map), &daily_trends(&sessions), &path).unwrap();
+        }
+
+        let conn = Connection::open(&path).unwrap();
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(count("sessions"), 2);
+        assert_eq!(count("turns"), 1);
+        let (date, session_count, total_cost): (String, i64, f64) = conn
+            .query_row("SELECT date, session_count, total_cost FROM daily_metrics", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!((date.as_str(), session_count, total_cost), ("2026-01-14", 2, 3.0));
+
+        // Without turns there is no turns table
+        write_sessions_sqlite(&sessions, None, &[], &path).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        let tables: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'turns'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables, 0);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,278 @@
+//! Session summary templates
+//!
+//! Fills the `{placeholder}` fields of a template with a session's numbers,
+//! for one-line status updates to paste into chat or notes, e.g.
+//! `{project} — {turns} turns, ${cost}, OES {oes}`. `{{` and `}}` stand for
+//! literal braces. An unknown placeholder is an error rather than being left
+//! in place, so a typo shows up when the template is saved.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::md_export::{format_count, format_duration};
+
+/// A named template, built in or saved in settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SummaryTemplate {
+    pub name: String,
+    pub template: String,
+}
+
+impl SummaryTemplate {
+    fn new(name: &str, template: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            template: template.to_string(),
+        }
+    }
+}
+
+/// Templates available without saving any
+pub fn builtin_templates() -> Vec<SummaryTemplate> {
+    vec![
+        SummaryTemplate::new("status", "{project} — {turns} turns, ${cost}, OES {oes}"),
+        SummaryTemplate::new(
+            "standup",
+            "{date}: worked on {project} ({branch}) for {active_duration}: {summary}",
+        ),
+        SummaryTemplate::new(
+            "detailed",
+            "{project} · {date} · {model}\n{turns} turns, {tools} tool calls, {subagents} subagents over {duration}\n\
+             ${cost} for {tokens} tokens · OES {oes} ({grade}) · cache efficiency {cer}%",
+        ),
+    ]
+}
+
+/// Placeholders a template can use, with what they stand for
+pub const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("project", "Project name"),
+    ("session_id", "First 8 characters of the session ID"),
+    ("date", "Start date (YYYY-MM-DD)"),
+    ("started_at", "Start date and time (YYYY-MM-DD HH:MM)"),
+    ("model", "Main model"),
+    ("branch", "Git branch"),
+    ("summary", "First prompt, shortened"),
+    ("turns", "Turn count"),
+    ("tools", "Tool call count"),
+    ("subagents", "Subagent count"),
+    ("cost", "Cost in USD, two decimals"),
+    ("cost_with_subagents", "Cost including spawned subagents"),
+    ("tokens", "Total tokens"),
+    ("duration", "Time from first to last turn"),
+    ("active_duration", "Time spent working, without long pauses"),
+    ("oes", "Overall Efficiency Score (0-100)"),
+    ("grade", "OES grade"),
+    ("cer", "Cache efficiency ratio (%)"),
+];
+
+/// Session values a template is filled with
+#[derive(Debug, Clone, Default)]
+pub struct SummaryFields {
+    pub project: String,
+    pub session_id: String,
+    /// Start of the session (RFC 3339)
+    pub started_at: String,
+    pub model: Option<String>,
+    pub branch: Option<String>,
+    pub summary: Option<String>,
+    pub turns: u32,
+    pub tools: u32,
+    pub subagents: u32,
+    pub cost: f64,
+    pub cost_with_subagents: f64,
+    pub tokens: u64,
+    pub duration_ms: u64,
+    pub active_duration_ms: u64,
+    /// Overall Efficiency Score (0.0 - 1.0)
+    pub oes: f64,
+    pub grade: String,
+    /// Cache Efficiency Ratio (0.0 - 1.0)
+    pub cer: f64,
+}
+
+impl SummaryFields {
+    fn value(&self, placeholder: &str) -> Option<String> {
+        let local = crate::timezone::local_datetime(&self.started_at);
+        let value = match placeholder {
+            "project" => self.project.clone(),
+            "session_id" => self.session_id.chars().take(8).collect(),
+            "date" => local.map_or_else(|| self.started_at.clone(), |dt| dt.format("%Y-%m-%d").to_string()),
+            "started_at" => local.map_or_else(|| self.started_at.clone(), |dt| dt.format("%Y-%m-%d %H:%M").to_string()),
+            "model" => self.model.clone().unwrap_or_else(|| "unknown".to_string()),
+            "branch" => self.branch.clone().unwrap_or_else(|| "no branch".to_string()),
+            "summary" => self.summary.clone().unwrap_or_default(),
+            "turns" => self.turns.to_string(),
+            "tools" => self.tools.to_string(),
+            "subagents" => self.subagents.to_string(),
+            "cost" => format!("{:.2}", self.cost),
+            "cost_with_subagents" => format!("{:.2}", self.cost_with_subagents),
+            "tokens" => format_count(self.tokens),
+            "duration" => format_duration(self.duration_ms),
+            "active_duration" => format_duration(self.active_duration_ms),
+            "oes" => format!("{:.0}", self.oes * 100.0),
+            "grade" => self.grade.clone(),
+            "cer" => format!("{:.0}", self.cer * 100.0),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Why a template can't be filled in
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TemplateError {
+    #[error("Unknown placeholder {{{0}}}")]
+    UnknownPlaceholder(String),
+    #[error("Unclosed {{ at position {0}; write {{{{ for a literal brace")]
+    Unclosed(usize),
+    #[error("Unmatched }} at position {0}; write }}}} for a literal brace")]
+    Unmatched(usize),
+    #[error("Template names must not be empty")]
+    EmptyName,
+    #[error("Template name {0:?} is already taken")]
+    DuplicateName(String),
+}
+
+/// Fill in a template
+pub fn render(template: &str, fields: &SummaryFields) -> Result<String, TemplateError> {
+    let mut out = String::with_capacity(template.len() * 2);
+    let mut chars = template.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|(_, c)| *c == '{').is_some() => out.push('{'),
+            '}' if chars.next_if(|(_, c)| *c == '}').is_some() => out.push('}'),
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) => name.push(c),
+                        None => return Err(TemplateError::Unclosed(pos)),
+                    }
+                }
+                let name = name.trim();
+                let value = fields
+                    .value(name)
+                    .ok_or_else(|| TemplateError::UnknownPlaceholder(name.to_string()))?;
+                out.push_str(&value);
+            }
+            '}' => return Err(TemplateError::Unmatched(pos)),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// Check a template without filling it in
+pub fn validate(template: &str) -> Result<(), TemplateError> {
+    render(template, &SummaryFields::default()).map(|_| ())
+}
+
+/// Check templates to be saved: names must be set and unique, including
+/// against the built-in ones, and every template must be valid
+pub fn validate_saved(templates: &[SummaryTemplate]) -> Result<(), TemplateError> {
+    let mut names: Vec<String> = builtin_templates().into_iter().map(|t| t.name).collect();
+    for template in templates {
+        let name = template.name.trim();
+        if name.is_empty() {
+            return Err(TemplateError::EmptyName);
+        }
+        if names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            return Err(TemplateError::DuplicateName(name.to_string()));
+        }
+        validate(&template.template)?;
+        names.push(name.to_string());
+    }
+    Ok(())
+}
+
+/// The template text for a built-in or saved template name, or `name_or_template`
+/// itself if no template has that name
+pub fn resolve(name_or_template: &str, saved: &[SummaryTemplate]) -> String {
+    let name = name_or_template.trim();
+    builtin_templates()
+        .into_iter()
+        .chain(saved.iter().cloned())
+        .find(|t| t.name.trim().eq_ignore_ascii_case(name))
+        .map_or_else(|| name_or_template.to_string(), |t| t.template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> SummaryFields {
+        SummaryFields {
+            project: "ironhide".to_string(),
+            session_id: "abcdef12-3456".to_string(),
+            started_at: "2026-03-01T10:00:00Z".to_string(),
+            model: Some("claude-opus-4-5-20251101".to_string()),
+            branch: Some("main".to_string()),
+            summary: Some("Fix the parser".to_string()),
+            turns: 12,
+            tools: 40,
+            subagents: 2,
+            cost: 3.456,
+            cost_with_subagents: 4.0,
+            tokens: 1_234_567,
+            duration_ms: 3_900_000,
+            active_duration_ms: 1_500_000,
+            oes: 0.824,
+            grade: "Good".to_string(),
+            cer: 0.91,
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(
+            render("{project} — {turns} turns, ${cost}, OES {oes}", &fields()).unwrap(),
+            "ironhide — 12 turns, $3.46, OES 82"
+        );
+        assert_eq!(
+            render("{{ {session_id} }} {tokens} tokens in { duration }", &fields()).unwrap(),
+            "{ abcdef12 } 1,234,567 tokens in 1h 5m"
+        );
+        assert_eq!(
+            render("{grade}", &SummaryFields::default()).unwrap(),
+            "",
+            "empty values render as empty text"
+        );
+    }
+
+    #[test]
+    fn test_template_errors() {
+        assert_eq!(
+            render("{projcet}", &fields()),
+            Err(TemplateError::UnknownPlaceholder("projcet".to_string()))
+        );
+        assert_eq!(validate("cost {cost"), Err(TemplateError::Unclosed(5)));
+        assert_eq!(validate("a } b"), Err(TemplateError::Unmatched(2)));
+        for template in builtin_templates() {
+            assert_eq!(validate(&template.template), Ok(()), "{}", template.name);
+        }
+        for (name, _) in PLACEHOLDERS {
+            assert!(fields().value(name).is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_saved_templates() {
+        let saved = vec![SummaryTemplate::new("Short", "{project}: ${cost}")];
+        assert_eq!(validate_saved(&saved), Ok(()));
+        assert_eq!(resolve("short", &saved), "{project}: ${cost}");
+        assert_eq!(resolve("status", &saved), builtin_templates()[0].template);
+        assert_eq!(resolve("{turns} turns", &saved), "{turns} turns");
+
+        assert_eq!(
+            validate_saved(&[SummaryTemplate::new("Status", "{cost}")]),
+            Err(TemplateError::DuplicateName("Status".to_string()))
+        );
+        assert_eq!(validate_saved(&[SummaryTemplate::new(" ", "{cost}")]), Err(TemplateError::EmptyName));
+        assert_eq!(
+            validate_saved(&[SummaryTemplate::new("x", "{nope}")]),
+            Err(TemplateError::UnknownPlaceholder("nope".to_string()))
+        );
+    }
+}
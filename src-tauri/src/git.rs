@@ -0,0 +1,205 @@
+//! Git integration
+//!
+//! Correlates sessions with the project repository by shelling out to `git`:
+//! which branch a session ran on and which commits were authored while it
+//! was active.
+
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// Field separator used in `git log` output (ASCII unit separator)
+const FIELD_SEP: char = '\u{1f}';
+
+/// Commits authored this long after the last turn still count towards the
+/// session (committing is often the final step after Claude finishes)
+const COMMIT_GRACE_MINUTES: i64 = 10;
+
+/// A commit made in the project repository
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct GitCommit {
+    pub sha: String,
+    pub short_sha: String,
+    pub author: String,
+    /// Author date (RFC 3339)
+    pub authored_at: String,
+    pub subject: String,
+}
+
+/// Git context for a session
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SessionGitContext {
+    pub session_id: String,
+    pub project_path: String,
+    /// Whether the project path is inside a git work tree
+    pub is_git_repo: bool,
+    /// Branch recorded for the session, if any
+    pub branch: Option<String>,
+    /// Start of the window commits were matched against (RFC 3339)
+    pub window_start: Option<String>,
+    /// End of the window commits were matched against (RFC 3339)
+    pub window_end: Option<String>,
+    /// Commits authored during the session, oldest first
+    pub commits: Vec<GitCommit>,
+}
+
+/// Run git in `repo` and return stdout if it succeeded
+fn run_git(repo: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        None
+    }
+}
+
+/// Check whether a path is inside a git work tree
+pub fn is_git_repo(path: &Path) -> bool {
+    path.is_dir()
+        && run_git(path, &["rev-parse", "--is-inside-work-tree"])
+            .is_some_and(|out| out.trim() == "true")
+}
+
+/// Get commits authored between `start` and `end` (inclusive), oldest first
+///
+/// Looks at `branch` if it still exists locally, otherwise at all refs (the
+/// branch may have been merged and deleted since the session).
+pub fn commits_in_window(
+    repo: &Path,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    branch: Option<&str>,
+) -> Vec<GitCommit> {
+    // git filters on committer date; widen the range by a day and filter
+    // precisely on author date below
+    let since = format!("--since={}", (start - Duration::days(1)).format("%Y-%m-%d %H:%M:%S +0000"));
+    let until = format!("--until={}", (end + Duration::days(1)).format("%Y-%m-%d %H:%M:%S +0000"));
+    let format = format!("--format=%H{0}%h{0}%an{0}%aI{0}%s", FIELD_SEP);
+
+    let branch_ref = branch
+        .map(|b| format!("refs/heads/{}", b))
+        .filter(|r| run_git(repo, &["rev-parse", "--verify", "--quiet", r]).is_some());
+
+    let mut args = vec!["log", "--reverse", since.as_str(), until.as_str(), format.as_str()];
+    match branch_ref.as_deref() {
+        Some(r) => args.push(r),
+        None => args.push("--all"),
+    }
+
+    let output = match run_git(repo, &args) {
+        Some(out) => out,
+        None => return Vec::new(),
+    };
+
+    parse_log_output(&output)
+        .into_iter()
+        .filter(|commit| {
+            DateTime::parse_from_rfc3339(&commit.authored_at)
+                .map(|dt| {
+                    let dt = dt.with_timezone(&Utc);
+                    dt >= start && dt <= end
+                })
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Build the git context for a session active from `started_at` to `ended_at`
+pub fn session_git_context(
+    session_id: &str,
+    project_path: &str,
+    branch: Option<String>,
+    started_at: Option<&str>,
+    ended_at: Option<&str>,
+) -> SessionGitContext {
+    let repo = Path::new(project_path);
+    let is_repo = !project_path.is_empty() && is_git_repo(repo);
+
+    let parse = |ts: Option<&str>| {
+        ts.and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+    };
+    let start = parse(started_at);
+    let end = parse(ended_at)
+        .or(start)
+        .map(|end| end + Duration::minutes(COMMIT_GRACE_MINUTES));
+
+    let commits = match (is_repo, start, end) {
+        (true, Some(start), Some(end)) => commits_in_window(repo, start, end, branch.as_deref()),
+        _ => Vec::new(),
+    };
+
+    SessionGitContext {
+        session_id: session_id.to_string(),
+        project_path: project_path.to_string(),
+        is_git_repo: is_repo,
+        branch,
+        window_start: start.map(|dt| dt.to_rfc3339()),
+        window_end: end.map(|dt| dt.to_rfc3339()),
+        commits,
+    }
+}
+
+/// Parse `git log` output produced with the `FIELD_SEP`-separated format
+fn parse_log_output(output: &str) -> Vec<GitCommit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, FIELD_SEP);
+            Some(GitCommit {
+                sha: fields.next()?.to_string(),
+                short_sha: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                authored_at: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_output() {
+        let output = "abc123\u{1f}abc\u{1f}Dev\u{1f}2026-01-14T08:00:00+00:00\u{1f}Fix parser\n\
+                      def456\u{1f}def\u{1f}Dev\u{1f}2026-01-14T09:00:00+00:00\u{1f}Add tests: a \u{1f} b\n\
+                      malformed line\n";
+
+        let commits = parse_log_output(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].short_sha, "abc");
+        assert_eq!(commits[0].subject, "Fix parser");
+        assert_eq!(commits[1].subject, "Add tests: a \u{1f} b");
+    }
+
+    #[test]
+    fn test_session_git_context_outside_repo() {
+        let dir = std::env::temp_dir().join("ironhide_git_context_test");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let ctx = session_git_context(
+            "s1",
+            dir.to_str().unwrap(),
+            Some("main".to_string()),
+            Some("2026-01-14T08:00:00Z"),
+            None,
+        );
+
+        assert!(!ctx.is_git_repo);
+        assert!(ctx.commits.is_empty());
+        assert_eq!(ctx.branch.as_deref(), Some("main"));
+        assert_eq!(ctx.window_end.as_deref(), Some("2026-01-14T08:10:00+00:00"));
+    }
+}
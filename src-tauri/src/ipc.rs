@@ -0,0 +1,526 @@
+//! Local IPC API for editor extensions
+//!
+//! A few commands served over a Unix domain socket (a named pipe on
+//! Windows), so an editor extension can show what the session being worked
+//! in costs without the HTTP API. The socket sits in an `ipc` directory
+//! next to the database, wherever it was moved to, and only the current
+//! user can open either; `IRONHIDE_IPC_PATH` puts it (or names the pipe)
+//! elsewhere, in a directory other users can't enter.
+//!
+//! Messages are JSON, one per line. A client first sends
+//! `{"token": "..."}` with a token from the `ipc_tokens` allowlist in the
+//! settings; any other token gets `{"error": "Unauthorized"}` and the
+//! connection is closed. Requests then look like
+//! `{"id": 1, "method": "get_session_metrics", "params": {"session_id": "..."}}`
+//! and are answered with `{"id": 1, "result": ...}` or
+//! `{"id": 1, "error": "..."}`. Methods:
+//! - `get_active_session`
+//! - `get_session_metrics`, with `session_id`
+//! - `subscribe`: `active-session-cost` updates follow from then on as
+//!   `{"event": "active-session-cost", "payload": {...}}`
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::broadcast;
+
+use crate::settings::IpcToken;
+use crate::watcher::live::ActiveSessionCost;
+use crate::{commands, AppState, CommandError};
+
+/// Environment variable overriding the socket path (pipe name on Windows)
+pub const IPC_PATH_ENV: &str = "IRONHIDE_IPC_PATH";
+
+/// Updates buffered per subscriber before the oldest are dropped
+const EVENT_BUFFER: usize = 64;
+
+/// Random bytes in a generated token
+const TOKEN_BYTES: usize = 32;
+
+/// Longest hello line read from a client not yet authorized
+const MAX_HELLO_LEN: u64 = 1024;
+
+/// How long a new client has to send its hello
+const HELLO_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Live updates for subscribed IPC clients, held in `AppState`
+#[derive(Clone)]
+pub struct IpcEvents {
+    sender: broadcast::Sender<ActiveSessionCost>,
+}
+
+impl Default for IpcEvents {
+    fn default() -> Self {
+        Self { sender: broadcast::channel(EVENT_BUFFER).0 }
+    }
+}
+
+impl IpcEvents {
+    /// Pass an `active-session-cost` update on to subscribed clients
+    pub fn publish(&self, update: &ActiveSessionCost) {
+        // Fails only when no client is subscribed
+        let _ = self.sender.send(update.clone());
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<ActiveSessionCost> {
+        self.sender.subscribe()
+    }
+}
+
+/// Where clients connect: the socket path, or the pipe name on Windows
+pub fn endpoint() -> String {
+    if let Some(path) = std::env::var(IPC_PATH_ENV).ok().filter(|p| !p.trim().is_empty()) {
+        return path.trim().to_string();
+    }
+    if cfg!(windows) {
+        r"\\.\pipe\ironhide".to_string()
+    } else {
+        crate::db::configured_db_path()
+            .with_file_name("ipc")
+            .join("ironhide.sock")
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// A new random client token
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "No secure random source for a token".to_string())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Name of the allowlisted client `token` belongs to, if any
+pub fn authorize<'a>(allowlist: &'a [IpcToken], token: &str) -> Option<&'a str> {
+    allowlist
+        .iter()
+        .find(|client| !client.token.is_empty() && constant_time_eq(client.token.as_bytes(), token.as_bytes()))
+        .map(|client| client.name.as_str())
+}
+
+/// Compare without returning early, so timing doesn't reveal how much of a
+/// guessed token was right
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[derive(Deserialize)]
+struct Hello {
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct SessionParams {
+    session_id: String,
+}
+
+/// Talk to one client until it disconnects
+///
+/// `allowlist` is read when the client connects, so a revoked token keeps
+/// working only for connections already open.
+pub async fn serve_connection<S>(stream: S, state: &AppState, allowlist: &[IpcToken]) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+
+    // Bounded in length and time, so a client can't hold on to memory or a
+    // task without a token
+    let mut line = String::new();
+    let mut limited = (&mut reader).take(MAX_HELLO_LEN);
+    let hello = match tokio::time::timeout(HELLO_TIMEOUT, limited.read_line(&mut line)).await {
+        Ok(Ok(0)) => return Ok(()),
+        Ok(Ok(_)) => serde_json::from_str::<Hello>(&line).ok(),
+        Ok(Err(e)) => return Err(e),
+        Err(_) => None,
+    };
+    let client = match hello.as_ref().and_then(|hello| authorize(allowlist, &hello.token)) {
+        Some(client) => client,
+        None => {
+            tracing::warn!("Refused an IPC client with an unknown token");
+            return write_message(&mut writer, &json!({"error": "Unauthorized"})).await;
+        }
+    };
+    tracing::info!("IPC client {} connected", client);
+    write_message(&mut writer, &json!({"ok": true})).await?;
+
+    let mut lines = reader.lines();
+    let mut events: Option<broadcast::Receiver<ActiveSessionCost>> = None;
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = match line? {
+                    Some(line) => line,
+                    None => break,
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let reply = match serde_json::from_str::<Request>(&line) {
+                    Ok(request) if request.method == "subscribe" => {
+                        events.get_or_insert_with(|| state.ipc.subscribe());
+                        json!({"id": request.id, "result": null})
+                    }
+                    Ok(request) => match call(state, &request.method, request.params).await {
+                        Ok(result) => json!({"id": request.id, "result": result}),
+                        Err(e) => json!({"id": request.id, "error": e}),
+                    },
+                    Err(e) => json!({"id": null, "error": format!("Invalid request: {}", e)}),
+                };
+                write_message(&mut writer, &reply).await?;
+            }
+            update = next_event(&mut events) => match update {
+                Ok(update) => {
+                    write_message(&mut writer, &json!({"event": "active-session-cost", "payload": update})).await?;
+                }
+                // A slow client misses the updates it fell behind on
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => events = None,
+            },
+        }
+    }
+
+    tracing::info!("IPC client {} disconnected", client);
+    Ok(())
+}
+
+async fn next_event(
+    events: &mut Option<broadcast::Receiver<ActiveSessionCost>>,
+) -> Result<ActiveSessionCost, broadcast::error::RecvError> {
+    match events {
+        Some(events) => events.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Run a request method
+async fn call(state: &AppState, method: &str, params: Value) -> Result<Value, String> {
+    match method {
+        "get_active_session" => to_json(commands::get_active_session(state.into()).await),
+        "get_session_metrics" => {
+            let params: SessionParams =
+                serde_json::from_value(params).map_err(|e| format!("Invalid params: {}", e))?;
+            to_json(commands::get_session_metrics(state.into(), params.session_id).await)
+        }
+        _ => Err(format!("Unknown method: {}", method)),
+    }
+}
+
+fn to_json<T: Serialize>(result: Result<T, CommandError>) -> Result<Value, String> {
+    let value = result.map_err(|e| e.to_string())?;
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &Value) -> std::io::Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await
+}
+
+/// Accept clients at [`endpoint`] until the process exits
+#[cfg(feature = "gui")]
+pub async fn serve(app: tauri::AppHandle) {
+    let endpoint = endpoint();
+    if let Err(e) = listen(app, &endpoint).await {
+        tracing::error!("IPC API on {} stopped: {}", endpoint, e);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn spawn_connection<S>(app: tauri::AppHandle, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    use tauri::Manager;
+
+    tokio::spawn(async move {
+        let state = app.state::<AppState>();
+        if let Err(e) = serve_connection(stream, state.inner(), &crate::settings::ipc_tokens()).await {
+            tracing::debug!("IPC connection closed: {}", e);
+        }
+    });
+}
+
+#[cfg(all(feature = "gui", unix))]
+async fn listen(app: tauri::AppHandle, endpoint: &str) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+
+    let path = std::path::Path::new(endpoint);
+    // The socket is bound with the process umask before its mode is set, so
+    // its directory must keep other users out from the start
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        match std::fs::metadata(dir) {
+            Ok(meta) if meta.mode() & 0o077 != 0 => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("{} is open to other users", dir.display()),
+                ));
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    // A socket left behind by an earlier run would make binding fail; anything
+    // else at the path is left alone
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and isn't a socket", endpoint),
+            ));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    tracing::info!("IPC API listening on {}", endpoint);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        spawn_connection(app.clone(), stream);
+    }
+}
+
+#[cfg(all(feature = "gui", windows))]
+async fn listen(app: tauri::AppHandle, endpoint: &str) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+
+    let security = pipe_security::CurrentUserOnly::new()?;
+    let create = |first: bool| -> std::io::Result<NamedPipeServer> {
+        let mut options = ServerOptions::new();
+        // Failing if the pipe exists keeps another process from serving it first
+        options.first_pipe_instance(first).reject_remote_clients(true);
+        // SAFETY: `security` holds valid security attributes and outlives the call
+        unsafe { options.create_with_security_attributes_raw(endpoint, security.as_ptr()) }
+    };
+    let mut server = create(true)?;
+    tracing::info!("IPC API listening on {}", endpoint);
+
+    loop {
+        server.connect().await?;
+        // The next client connects to a new instance of the pipe
+        let next = create(false)?;
+        spawn_connection(app.clone(), std::mem::replace(&mut server, next));
+    }
+}
+
+/// A named pipe DACL that lets in the current user and nobody else
+#[cfg(all(feature = "gui", windows))]
+mod pipe_security {
+    use std::ffi::c_void;
+    use std::io;
+    use std::ptr;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, LocalFree, HANDLE};
+    use windows_sys::Win32::Security::Authorization::{
+        ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+    };
+    use windows_sys::Win32::Security::{
+        GetTokenInformation, TokenUser, PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES, TOKEN_QUERY, TOKEN_USER,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    pub struct CurrentUserOnly {
+        attributes: SECURITY_ATTRIBUTES,
+    }
+
+    // The descriptor is owned by the struct and only read once built
+    unsafe impl Send for CurrentUserOnly {}
+    unsafe impl Sync for CurrentUserOnly {}
+
+    impl CurrentUserOnly {
+        pub fn new() -> io::Result<Self> {
+            // Protected, so nothing is inherited: full access for the user only
+            let sddl: Vec<u16> = format!("D:P(A;;GA;;;{})", current_user_sid()?)
+                .encode_utf16()
+                .chain(Some(0))
+                .collect();
+            let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+            // SAFETY: `sddl` is NUL-terminated; the descriptor is freed on drop
+            let converted = unsafe {
+                ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                    sddl.as_ptr(),
+                    SDDL_REVISION_1,
+                    &mut descriptor,
+                    ptr::null_mut(),
+                )
+            };
+            if converted == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self {
+                attributes: SECURITY_ATTRIBUTES {
+                    nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+                    lpSecurityDescriptor: descriptor,
+                    bInheritHandle: 0,
+                },
+            })
+        }
+
+        /// The `SECURITY_ATTRIBUTES` to create the pipe with
+        pub fn as_ptr(&self) -> *mut c_void {
+            &self.attributes as *const SECURITY_ATTRIBUTES as *mut c_void
+        }
+    }
+
+    impl Drop for CurrentUserOnly {
+        fn drop(&mut self) {
+            // SAFETY: allocated with LocalAlloc by the SDDL conversion
+            unsafe { LocalFree(self.attributes.lpSecurityDescriptor) };
+        }
+    }
+
+    /// The SID of the user the process runs as, e.g. "S-1-5-21-...-1001"
+    fn current_user_sid() -> io::Result<String> {
+        // SAFETY: every pointer passed points into a live local or a buffer of
+        // the size given, and the token handle and SID string are released
+        unsafe {
+            let mut token: HANDLE = ptr::null_mut();
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut len = 0u32;
+            GetTokenInformation(token, TokenUser, ptr::null_mut(), 0, &mut len);
+            // u64s keep the TOKEN_USER aligned
+            let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+            let queried = GetTokenInformation(token, TokenUser, buffer.as_mut_ptr().cast(), len, &mut len);
+            let error = io::Error::last_os_error();
+            CloseHandle(token);
+            if queried == 0 {
+                return Err(error);
+            }
+
+            let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+            let mut sid: *mut u16 = ptr::null_mut();
+            if ConvertSidToStringSidW(user.User.Sid, &mut sid) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let len = (0..).take_while(|&i| *sid.add(i) != 0).count();
+            let string = String::from_utf16_lossy(std::slice::from_raw_parts(sid, len));
+            LocalFree(sid.cast());
+            Ok(string)
+        }
+    }
+}
+
+#[cfg(all(feature = "gui", not(any(unix, windows))))]
+async fn listen(_app: tauri::AppHandle, _endpoint: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "No local sockets on this platform"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist() -> Vec<IpcToken> {
+        vec![IpcToken { name: "vscode".to_string(), token: "s3cret-token".to_string() }]
+    }
+
+    /// Send `lines` over a fresh connection and collect what comes back
+    /// before the server stops answering
+    fn exchange(lines: &[&str], publish: Option<ActiveSessionCost>) -> Vec<Value> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let state = AppState::default();
+            let allowlist = allowlist();
+            let (client, server) = tokio::io::duplex(4096);
+            let (client_read, mut client_write) = tokio::io::split(client);
+
+            let talk = async {
+                for line in lines {
+                    client_write.write_all(format!("{}\n", line).as_bytes()).await.unwrap();
+                }
+                let mut replies = Vec::new();
+                let mut responses = BufReader::new(client_read).lines();
+                loop {
+                    let wait = std::time::Duration::from_millis(200);
+                    match tokio::time::timeout(wait, responses.next_line()).await {
+                        Ok(Ok(Some(line))) => replies.push(serde_json::from_str(&line).unwrap()),
+                        _ => break,
+                    }
+                    if replies.len() == lines.len() {
+                        if let Some(update) = &publish {
+                            state.ipc.publish(update);
+                        }
+                    }
+                }
+                drop(client_write);
+                replies
+            };
+            let (replies, _) = tokio::join!(talk, serve_connection(server, &state, &allowlist));
+            replies
+        })
+    }
+
+    #[test]
+    fn test_authorize() {
+        let allowlist = allowlist();
+        assert_eq!(authorize(&allowlist, "s3cret-token"), Some("vscode"));
+        assert_eq!(authorize(&allowlist, "s3cret-tokeN"), None);
+        assert_eq!(authorize(&allowlist, ""), None);
+        assert_eq!(authorize(&[], "s3cret-token"), None);
+
+        let token = generate_token().unwrap();
+        assert_eq!(token.len(), 43);
+        assert_ne!(token, generate_token().unwrap());
+    }
+
+    #[test]
+    fn test_serve_connection() {
+        let refused = exchange(&[r#"{"token": "guess"}"#, r#"{"id": 1, "method": "get_active_session"}"#], None);
+        assert_eq!(refused, vec![json!({"error": "Unauthorized"})]);
+        // Hello lines are cut off at MAX_HELLO_LEN, valid token or not
+        let padded = format!(r#"{{"token": "s3cret-token", "pad": "{}"}}"#, "x".repeat(MAX_HELLO_LEN as usize));
+        assert_eq!(exchange(&[&padded], None), vec![json!({"error": "Unauthorized"})]);
+
+        let update = ActiveSessionCost {
+            session_id: "s1".to_string(),
+            total_tokens: 1_200,
+            total_cost: 0.42,
+            tokens_delta: 200,
+            cost_delta: 0.05,
+            turn_in_progress: true,
+        };
+        let replies = exchange(
+            &[
+                r#"{"token": "s3cret-token"}"#,
+                r#"{"id": 1, "method": "get_session_metrics", "params": {}}"#,
+                r#"{"id": 2, "method": "delete_everything"}"#,
+                "not json",
+                r#"{"id": "sub", "method": "subscribe"}"#,
+            ],
+            Some(update),
+        );
+        assert_eq!(replies.len(), 6);
+        assert_eq!(replies[0], json!({"ok": true}));
+        assert_eq!(replies[1]["id"], 1);
+        assert!(replies[1]["error"].as_str().unwrap().starts_with("Invalid params"));
+        assert_eq!(replies[2]["error"], "Unknown method: delete_everything");
+        assert!(replies[3]["error"].as_str().unwrap().starts_with("Invalid request"));
+        assert_eq!(replies[4], json!({"id": "sub", "result": null}));
+        assert_eq!(replies[5]["event"], "active-session-cost");
+        assert_eq!(replies[5]["payload"]["session_id"], "s1");
+        assert_eq!(replies[5]["payload"]["total_cost"], 0.42);
+    }
+}
@@ -0,0 +1,128 @@
+//! Key storage in the OS keychain
+//!
+//! Holds the key for encrypted message content (see [`crate::db::crypto`]).
+//! Shells out to the platform's own tool, as `github` does with `gh`: the
+//! `security` command for the macOS login keychain, and `secret-tool` for
+//! the Secret Service on Linux. The key never appears in a command line, so
+//! other users can't read it from the process list.
+
+use std::process::Command;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+
+use crate::db::crypto::KEY_LEN;
+
+/// Whether this platform has a keychain to keep the key in; content
+/// encryption can't be turned on elsewhere
+pub const SUPPORTED: bool = cfg!(any(target_os = "macos", target_os = "linux"));
+
+const SERVICE: &str = "ironhide";
+const ACCOUNT: &str = "content-key";
+
+/// Load the content key; None if none has been stored yet
+pub fn load_content_key() -> Result<Option<[u8; KEY_LEN]>, String> {
+    let encoded = match lookup()? {
+        Some(encoded) => encoded,
+        None => return Ok(None),
+    };
+    let key = STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| "The keychain entry isn't a valid key".to_string())?;
+    key.try_into()
+        .map(Some)
+        .map_err(|_| "The keychain entry isn't a valid key".to_string())
+}
+
+/// Store `key` as the content key, replacing any stored before
+pub fn store_content_key(key: &[u8; KEY_LEN]) -> Result<(), String> {
+    store(&STANDARD.encode(key))
+}
+
+/// Run `program` with `input` on stdin
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn run_with_input(program: &str, args: &[&str], input: &str) -> Result<std::process::Output, String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("Failed to write to {}: {}", program, e))?;
+    }
+    child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+#[cfg(target_os = "macos")]
+fn lookup() -> Result<Option<String>, String> {
+    let output = Command::new("security")
+        .args(["find-generic-password", "-s", SERVICE, "-a", ACCOUNT, "-w"])
+        .output()
+        .map_err(|e| format!("Failed to run security: {}", e))?;
+    // Exit code 44: no such item
+    match output.status.code() {
+        Some(0) => Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string())),
+        Some(44) => Ok(None),
+        _ => Err(format!("Keychain lookup failed: {}", String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn store(secret: &str) -> Result<(), String> {
+    // Interactive mode reads the command from stdin rather than argv
+    let command = format!("add-generic-password -U -s {} -a {} -w {}\n", SERVICE, ACCOUNT, secret);
+    let output = run_with_input("security", &["-i"], &command)?;
+    if !output.status.success() {
+        return Err(format!("Keychain store failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn lookup() -> Result<Option<String>, String> {
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", SERVICE, "account", ACCOUNT])
+        .output()
+        .map_err(|e| format!("Failed to run secret-tool: {}", e))?;
+    // secret-tool exits with 1 and prints nothing when there's no such item
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !secret.is_empty() {
+        Ok(Some(secret))
+    } else if output.stderr.is_empty() {
+        Ok(None)
+    } else {
+        Err(format!("Keychain lookup failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn store(secret: &str) -> Result<(), String> {
+    let output = run_with_input(
+        "secret-tool",
+        &["store", "--label=ironhide content key", "service", SERVICE, "account", ACCOUNT],
+        secret,
+    )?;
+    if !output.status.success() {
+        return Err(format!("Keychain store failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn lookup() -> Result<Option<String>, String> {
+    Err("No supported keychain on this platform".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn store(_secret: &str) -> Result<(), String> {
+    Err("No supported keychain on this platform".to_string())
+}
@@ -7,36 +7,126 @@
 //! - Metrics calculation
 //! - File system watching for live updates
 //! - Session caching for performance
+//!
+//! The Tauri app is behind the default `gui` feature. Without it the same
+//! commands can be driven headless, which is what the `ironhide-cli` binary
+//! does. The `bindings` feature derives JSON schemas of the command
+//! responses and event payloads, from which `ironhide-bindings` generates the
+//! frontend's TypeScript definitions.
 
+#[cfg(feature = "http-api")]
+pub mod api;
+#[cfg(feature = "bindings")]
+pub mod bindings;
 pub mod commands;
 pub mod db;
+pub mod diagnostics;
 pub mod export;
+pub mod git;
 pub mod github;
+pub mod ipc;
+pub mod keychain;
 pub mod metrics;
 pub mod models;
+pub mod notifications;
 pub mod parser;
 pub mod patterns;
 pub mod recommendations;
+pub mod redact;
+pub mod settings;
+pub mod timezone;
 pub mod trends;
+pub mod updates;
 pub mod watcher;
 
 use std::sync::OnceLock;
 
 use db::Database;
 
-/// Application state managed by Tauri
+/// Application state shared by the commands
+///
+/// Managed by Tauri in the desktop app; the CLI builds its own with
+/// [`init_app_state`].
 pub struct AppState {
     pub db: OnceLock<Database>,
+    /// Cancels and reports progress of `preload_all_sessions`
+    pub preload: commands::PreloadControl,
+    /// Background session exports
+    pub exports: commands::ExportJobs,
+    /// Cancels and reports progress of `backfill_database`
+    pub backfill: commands::BackfillControl,
+    /// Workers for the queue of background jobs
+    pub jobs: commands::JobQueue,
+    /// Reports progress of `warm_project_cache`
+    pub warmups: commands::ProjectWarmups,
+    /// Live updates for clients of the IPC socket
+    pub ipc: ipc::IpcEvents,
+    /// Startup errors, watcher status and errors commands worked around, for
+    /// `get_diagnostics` and `get_backend_errors`
+    pub diagnostics: diagnostics::Diagnostics,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
             db: OnceLock::new(),
+            preload: commands::PreloadControl::default(),
+            exports: commands::ExportJobs::default(),
+            backfill: commands::BackfillControl::default(),
+            jobs: commands::JobQueue::default(),
+            warmups: commands::ProjectWarmups::default(),
+            ipc: ipc::IpcEvents::default(),
+            diagnostics: diagnostics::Diagnostics::default(),
         }
     }
 }
 
+/// Borrowed managed state passed to commands
+///
+/// Tauri resolves it from the app's managed state like `tauri::State`;
+/// headless callers wrap a reference with `State::from`.
+pub struct State<'r, T: Send + Sync + 'static>(&'r T);
+
+impl<'r, T: Send + Sync + 'static> State<'r, T> {
+    pub fn inner(&self) -> &'r T {
+        self.0
+    }
+}
+
+impl<T: Send + Sync + 'static> std::ops::Deref for State<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<T: Send + Sync + 'static> Clone for State<'_, T> {
+    fn clone(&self) -> Self {
+        State(self.0)
+    }
+}
+
+impl<'r, T: Send + Sync + 'static> From<&'r T> for State<'r, T> {
+    fn from(inner: &'r T) -> Self {
+        State(inner)
+    }
+}
+
+#[cfg(feature = "gui")]
+impl<'r, T: Send + Sync + 'static> From<tauri::State<'r, T>> for State<'r, T> {
+    fn from(state: tauri::State<'r, T>) -> Self {
+        State(state.inner())
+    }
+}
+
+#[cfg(feature = "gui")]
+impl<'de, T: Send + Sync + 'static, R: tauri::Runtime> tauri::ipc::CommandArg<'de, R> for State<'de, T> {
+    fn from_command(command: tauri::ipc::CommandItem<'de, R>) -> Result<Self, tauri::ipc::InvokeError> {
+        <tauri::State<'de, T> as tauri::ipc::CommandArg<'de, R>>::from_command(command).map(State::from)
+    }
+}
+
 /// Error type for Tauri commands
 #[derive(Debug, thiserror::Error)]
 pub enum CommandError {
@@ -67,47 +157,104 @@ impl serde::Serialize for CommandError {
 }
 
 // ============================================================================
-// Tauri Application Setup
+// Application Setup
 // ============================================================================
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
-
-    tracing::info!("Starting Ironhide backend");
-
-    // Initialize database before session scan
-    let db_path = db::default_db_path();
-    tracing::info!("Database path: {:?}", db_path);
+/// Open the analytics database and apply the stored settings, import
+/// sources and pricing overrides
+///
+/// Falls back to a state without a database if it can't be opened; commands
+/// then parse the session files directly. Failed steps are recorded in the
+/// state's diagnostics.
+pub fn init_app_state(db_path: &std::path::Path) -> AppState {
+    let state = AppState::default();
+    let failed = |stage: &str, e: &dyn std::fmt::Display| {
+        tracing::warn!("Failed to load {}: {}", stage, e);
+        state.diagnostics.record_init_error(stage, e);
+    };
 
     // Create database directory if it doesn't exist
     if let Some(parent) = db_path.parent() {
         if let Err(e) = std::fs::create_dir_all(parent) {
             tracing::error!("Failed to create database directory: {}", e);
+            state.diagnostics.record_init_error("database directory", e);
         }
     }
 
     // Create and initialize the database
-    let app_state = match db::Database::new(db_path.clone()) {
-        Ok(database) => {
-            if let Err(e) = database.initialize() {
-                tracing::error!("Failed to initialize database schema: {}", e);
-                AppState::default()
-            } else {
-                tracing::info!("Database initialized successfully at {:?}", db_path);
-                let state = AppState::default();
-                let _ = state.db.set(database);
-                state
-            }
-        }
+    let database = match db::Database::new(db_path.to_path_buf()) {
+        Ok(database) => database,
         Err(e) => {
             tracing::error!("Failed to create database connection: {}", e);
-            AppState::default()
+            state.diagnostics.record_init_error("database connection", e);
+            return state;
         }
     };
+    if let Err(e) = database.initialize() {
+        tracing::error!("Failed to initialize database schema: {}", e);
+        state.diagnostics.record_init_error("database schema", e);
+        return state;
+    }
+    tracing::info!("Database initialized successfully at {:?}", db_path);
+
+    match commands::load_settings(&database) {
+        Ok(settings) => {
+            if let Err(e) = commands::apply_retention_policy(&database, &settings) {
+                tracing::warn!("Failed to apply retention policy: {}", e);
+                state.diagnostics.record_init_error("retention policy", &e);
+            }
+            if let Err(e) = commands::apply_privacy_mode(&database, &settings) {
+                tracing::warn!("Failed to apply privacy mode: {}", e);
+                state.diagnostics.record_init_error("privacy mode", &e);
+            }
+            if let Err(e) = commands::apply_content_encryption(&database, &settings) {
+                tracing::warn!("Failed to apply content encryption: {}", e);
+                state.diagnostics.record_init_error("content encryption", &e);
+            }
+        }
+        Err(e) => failed("settings", &e),
+    }
+    // Built in the timezone the settings just made active
+    match database.with_connection(db::rollups::ensure_current) {
+        Ok(true) => tracing::info!("Rebuilt daily rollups"),
+        Ok(false) => {}
+        Err(e) => failed("daily rollups", &e),
+    }
+    match commands::load_import_sources(&database) {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Registered {} import sources", count),
+        Err(e) => failed("import sources", &e),
+    }
+    match commands::load_project_registry(&database) {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Loaded {} project path resolutions", count),
+        Err(e) => failed("project registry", &e),
+    }
+    match commands::load_pricing_overrides(&database) {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Loaded {} custom pricing overrides", count),
+        Err(e) => failed("custom pricing", &e),
+    }
+
+    let _ = state.db.set(database);
+    state
+}
+
+#[cfg(feature = "gui")]
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // Initialize logging
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    tracing::info!("Starting Ironhide backend");
+
+    // Initialize database before session scan
+    let db_path = db::configured_db_path();
+    tracing::info!("Database path: {:?}", db_path);
+
+    let app_state = init_app_state(&db_path);
 
     tauri::Builder::default()
         .manage(app_state)
@@ -121,42 +268,180 @@ pub fn run() {
             commands::get_sessions_filtered,
             commands::get_sessions_by_project,
             commands::preload_all_sessions,
+            commands::cancel_preload,
+            commands::warm_project_cache,
+            commands::backfill_database,
+            commands::cancel_backfill,
+            // Background job commands
+            commands::list_jobs,
+            commands::cancel_job,
             // Turn commands
             commands::get_turns,
+            commands::get_session_cost_curve,
+            commands::annotate_turn,
+            commands::remove_turn_annotation,
+            commands::get_turn_annotations,
+            commands::get_parse_diagnostics,
+            commands::get_session_raw_entries,
+            commands::get_context_timeline,
+            commands::get_session_transcript,
             // Subagent commands
             commands::get_session_subagents,
+            commands::get_subagent_tree,
             // Comparison commands
             commands::compare_sessions,
             // Code changes commands
             commands::get_session_code_changes,
+            commands::get_file_history,
+            commands::get_project_file_changes,
+            commands::get_session_rework,
+            commands::get_tool_failures,
+            commands::get_cache_breakdown,
+            commands::get_session_efficiency_curve,
+            commands::get_session_health,
+            commands::get_active_session,
             // Dashboard summary commands (efficient aggregation)
             commands::get_dashboard_summary,
             commands::get_daily_metrics,
             commands::get_project_metrics,
+            commands::get_project_benchmarks,
+            commands::get_project_timeline,
+            commands::get_hourly_usage,
+            commands::get_usage_windows,
+            commands::get_billing_blocks,
+            commands::get_tool_usage,
+            commands::simulate_savings,
+            commands::get_mcp_stats,
+            commands::get_concurrent_sessions,
+            commands::get_error_stats,
+            commands::get_thinking_trend,
+            commands::get_burn_rate,
+            commands::get_session_distributions,
             // Trend commands
             commands::get_trends,
+            commands::get_cost_anomalies,
+            commands::get_cost_forecast,
+            commands::compare_periods,
+            commands::generate_weekly_report,
             commands::get_cost_trend,
             commands::get_efficiency_trend,
             // Utility commands
             commands::get_db_path,
+            commands::migrate_db_to,
             commands::refresh_sessions,
+            commands::recompute_session,
+            commands::summarize_session,
+            commands::get_summary_templates,
             commands::scan_new_sessions,
             // Export commands
             commands::export_sessions,
+            commands::get_export_status,
+            commands::cancel_export,
             commands::export_trends,
+            commands::export_weekly_report,
+            commands::export_session_replay,
+            commands::export_session_bundle,
             // Recommendations commands
             commands::get_recommendations,
+            commands::dismiss_recommendation,
+            commands::list_dismissed,
+            commands::restore_recommendation,
             // Anti-pattern detection commands
             commands::detect_antipatterns,
+            commands::get_antipattern_history,
+            commands::generate_daily_digest,
             // Developer performance commands
             commands::detect_github_config,
             commands::get_developer_metrics,
+            // Git commands
+            commands::get_session_git_context,
+            // Pricing commands
+            commands::get_model_pricing,
+            commands::set_model_pricing,
+            commands::reset_model_pricing,
+            commands::get_unpriced_models,
+            commands::recompute_costs,
+            // Settings commands
+            commands::get_settings,
+            commands::set_settings,
+            commands::reload_cache_config,
+            commands::get_update_channel,
+            commands::set_update_channel,
+            commands::check_for_updates,
+            // IPC commands
+            commands::create_ipc_token,
+            commands::revoke_ipc_token,
+            // Retention commands
+            commands::prune_sessions_older_than,
+            commands::vacuum_database,
+            commands::rebuild_daily_rollups,
+            // Snapshot commands
+            commands::export_snapshot,
+            commands::import_snapshot,
+            commands::sync_snapshot_folder,
+            commands::bootstrap_from_snapshot,
+            // Team roll-up commands
+            commands::import_team_export,
+            commands::get_team_members,
+            commands::remove_team_member,
+            commands::get_team_dashboard,
+            commands::get_team_trends,
+            // Archive commands
+            commands::archive_sessions_older_than,
+            commands::restore_session,
+            // Import commands
+            commands::import_sessions_from_path,
+            commands::set_project_path_override,
+            // Diagnostics
+            commands::get_diagnostics,
+            commands::get_backend_errors,
         ])
         .setup(|app| {
+            // Optional HTTP API, enabled with the `http-api` feature and IRONHIDE_HTTP_ADDR
+            #[cfg(feature = "http-api")]
+            if let Some(addr) = api::configured_addr() {
+                tauri::async_runtime::spawn(api::serve(api::ApiState::App(app.handle().clone()), addr));
+            }
+
+            // Local socket for editor extensions; clients need an allowlisted token
+            tauri::async_runtime::spawn(ipc::serve(app.handle().clone()));
+
+            // Preload, export, backfill, job and warm-up progress is streamed to the frontend as it happens
+            {
+                use tauri::{Emitter, Manager};
+                let state = app.state::<AppState>();
+                let app_handle = app.handle().clone();
+                state.preload.set_listener(move |progress| {
+                    let _ = app_handle.emit("preload-progress", progress);
+                });
+                let app_handle = app.handle().clone();
+                state.exports.set_listener(move |status| {
+                    let _ = app_handle.emit("export-progress", status);
+                });
+                let app_handle = app.handle().clone();
+                state.backfill.set_listener(move |progress| {
+                    let _ = app_handle.emit("backfill-progress", progress);
+                });
+                let app_handle = app.handle().clone();
+                state.jobs.set_listener(move |job| {
+                    let _ = app_handle.emit("job-updated", job);
+                });
+                let app_handle = app.handle().clone();
+                state.warmups.set_listener(move |progress| {
+                    let _ = app_handle.emit("project-warmup-progress", progress);
+                });
+
+                // A snapshot shipped with a fresh install stands in for the first preload
+                commands::bootstrap_on_first_launch(&state);
+
+                // Jobs cut short by closing the app pick up where they stopped
+                commands::resume_jobs(&state);
+            }
+
             let app_handle = app.handle().clone();
             std::thread::spawn(move || {
                 // Do initial session scan in background (non-blocking)
-                tracing::info!("Scanning for sessions in ~/.claude/projects/");
+                tracing::info!("Scanning for Claude Code sessions");
                 let session_count = crate::parser::scan_claude_sessions().len();
                 tracing::info!("Found {} session files", session_count);
 
@@ -169,24 +454,147 @@ pub fn run() {
         .expect("error while running tauri application");
 }
 
-/// Background task that periodically checks for new/updated sessions
+/// Background task that streams session changes to the frontend
+///
+/// Appended JSONL lines are picked up by the notify-based `SessionWatcher`
+/// and emitted per completed turn as `turn-appended` events. Every few
+/// seconds the running spend of the session being worked in is emitted as
+/// `active-session-cost` and passed on to subscribed IPC clients. Changed
+/// files are also queued for the sync worker, which stores them in the
+/// database. A slower full rescan still reports newly discovered sessions as
+/// `sessions-updated`. If the watcher can't be started, session files are
+/// polled instead, quickly while any of them changed recently and slowly
+/// when idle (see `PollingConfig`). The loop also queues the scheduled daily
+/// digest once it's due.
+#[cfg(feature = "gui")]
 fn session_watcher_task(app_handle: tauri::AppHandle) {
     use std::collections::HashSet;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
     use tauri::Emitter;
+    use watcher::live::LiveSessionTracker;
+    use watcher::poll::SessionPoller;
+    use watcher::sync::SyncQueue;
+    use watcher::{SessionWatcher, WatchEvent};
+
+    const RESCAN_INTERVAL: Duration = Duration::from_secs(30);
+    const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+    const ACTIVE_COST_INTERVAL: Duration = Duration::from_secs(3);
 
     let mut known_sessions: HashSet<String> = HashSet::new();
+    let mut tracker = LiveSessionTracker::new();
 
-    // Initial population
-    for session in parser::scan_claude_sessions() {
+    // Initial population; current sizes mark where live tailing begins
+    let initial_sessions = parser::scan_claude_sessions();
+    let mut poller = SessionPoller::new(&initial_sessions);
+    for session in initial_sessions {
+        tracker.set_baseline(session.path.clone(), session.size);
         known_sessions.insert(session.session_id);
     }
 
+    let session_watcher = watcher::default_sessions_path().and_then(|path| {
+        let mut session_watcher = match SessionWatcher::new(path) {
+            Ok(w) => w,
+            Err(e) => {
+                tracing::warn!("Failed to create session watcher: {}", e);
+                return None;
+            }
+        };
+        match session_watcher.start() {
+            Ok(()) => {
+                // Extra Claude directories configured later are picked up by
+                // the periodic rescan
+                for root in parser::scan::scan_roots().into_iter().filter(|r| r.label.is_some()) {
+                    if let Err(e) = session_watcher.watch_also(root.path.join("projects")) {
+                        tracing::info!("Not watching {:?}: {}", root.path, e);
+                    }
+                }
+                Some(session_watcher)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to start session watcher: {}", e);
+                None
+            }
+        }
+    });
+
+    {
+        use tauri::Manager;
+        let status = if session_watcher.is_some() {
+            diagnostics::WatcherStatus::Watching
+        } else {
+            tracing::warn!("File watching unavailable, falling back to adaptive polling");
+            diagnostics::WatcherStatus::Polling
+        };
+        app_handle.state::<AppState>().diagnostics.set_watcher_status(status);
+    }
+
+    let mut last_rescan = Instant::now();
+    let mut last_cost_tick = Instant::now();
+    let mut last_digest_check: Option<Instant> = None;
+    let mut sync_queue = SyncQueue::default();
+    let (sync_tx, sync_rx) = std::sync::mpsc::channel();
+    {
+        let app_handle = app_handle.clone();
+        std::thread::spawn(move || session_sync_task(app_handle, sync_rx));
+    }
+
     loop {
-        // Check every 30 seconds
-        std::thread::sleep(Duration::from_secs(30));
+        let (events, scanned) = match &session_watcher {
+            Some(session_watcher) => {
+                std::thread::sleep(WATCH_POLL_INTERVAL);
+                (session_watcher.poll(), None)
+            }
+            None => {
+                // Scan often while sessions are being written, rarely when idle
+                std::thread::sleep(poller.interval(&settings::polling_config(), Instant::now()));
+                let sessions = parser::scan_claude_sessions();
+                (poller.poll(&sessions, Instant::now()), Some(sessions))
+            }
+        };
+
+        for event in events {
+            sync_queue.push(&event, Instant::now());
+            match event {
+                WatchEvent::NewSession { path, .. }
+                | WatchEvent::SessionUpdated { path, .. }
+                | WatchEvent::SubagentCreated { path, .. } => {
+                    for update in tracker.process_file(&path) {
+                        let _ = app_handle.emit("turn-appended", &update);
+                    }
+                }
+                WatchEvent::FileDeleted { path } => tracker.forget(&path),
+            }
+        }
+
+        let ready = sync_queue.take_ready(Instant::now());
+        if !ready.is_empty() {
+            let _ = sync_tx.send(ready);
+        }
+
+        if last_cost_tick.elapsed() >= ACTIVE_COST_INTERVAL {
+            last_cost_tick = Instant::now();
+            if let Some(update) = tracker.active_session_cost(last_cost_tick) {
+                use tauri::Manager;
+                let _ = app_handle.emit("active-session-cost", &update);
+                app_handle.state::<AppState>().ipc.publish(&update);
+            }
+        }
+
+        if !matches!(last_digest_check, Some(t) if t.elapsed() < commands::DIGEST_CHECK_INTERVAL) {
+            use tauri::Manager;
+            last_digest_check = Some(Instant::now());
+            commands::schedule_daily_digest(&app_handle.state::<AppState>());
+        }
 
-        let current_sessions = parser::scan_claude_sessions();
+        // Polling scans double as the rescan for new sessions
+        let current_sessions = match scanned {
+            Some(sessions) => sessions,
+            None if last_rescan.elapsed() < RESCAN_INTERVAL => continue,
+            None => {
+                last_rescan = Instant::now();
+                parser::scan_claude_sessions()
+            }
+        };
         let mut new_sessions = Vec::new();
 
         for session in &current_sessions {
@@ -200,6 +608,137 @@ fn session_watcher_task(app_handle: tauri::AppHandle) {
         // Emit event if new sessions found
         if !new_sessions.is_empty() {
             let _ = app_handle.emit("sessions-updated", &new_sessions);
+            notifications::notify(
+                current_sessions
+                    .iter()
+                    .filter(|s| !s.is_subagent && new_sessions.contains(&s.session_id))
+                    .map(|s| notifications::NotificationEvent::NewSession {
+                        session_id: s.session_id.clone(),
+                        project_path: s.project_path.clone(),
+                    })
+                    .collect(),
+            );
+        }
+    }
+}
+
+/// Worker that stores batches of changed session files in the database
+///
+/// Emits `session-updated` with the new summary of each stored session and
+/// one `metrics-updated` per batch with the IDs of the sessions it changed,
+/// then sends any webhook notifications the batch raised and emits
+/// `usage-window-warning` for usage windows that reached a threshold.
+#[cfg(feature = "gui")]
+fn session_sync_task(
+    app_handle: tauri::AppHandle,
+    batches: std::sync::mpsc::Receiver<Vec<std::path::PathBuf>>,
+) {
+    use tauri::{Emitter, Manager};
+
+    let mut usage_thresholds = metrics::usage_windows::UsageThresholdTracker::default();
+    for paths in batches {
+        let state = app_handle.state::<AppState>();
+        let report = commands::sync_session_files(&state, &paths);
+        tracing::debug!(
+            "Synced {} sessions ({} files skipped)",
+            report.sessions.len(),
+            report.skipped
+        );
+        if report.sessions.is_empty() {
+            continue;
+        }
+
+        for summary in &report.sessions {
+            let _ = app_handle.emit("session-updated", summary);
+        }
+        let session_ids: Vec<&str> = report.sessions.iter().map(|s| s.id.as_str()).collect();
+        let _ = app_handle.emit("metrics-updated", &session_ids);
+
+        notifications::notify(commands::sync_notification_events(&state, &report));
+
+        for warning in commands::usage_window_warnings(&state, &mut usage_thresholds) {
+            let _ = app_handle.emit("usage-window-warning", &warning);
+        }
+    }
+}
+
+/// Background task that keeps the database in sync with the session files
+/// when running headless
+///
+/// The `ironhide-cli serve` counterpart of `session_watcher_task`: changed
+/// files are stored by the same sync path, found through the notify-based
+/// `SessionWatcher` or, if it can't be started, by adaptive polling. Webhook
+/// notifications for the stored sessions are sent and the scheduled daily
+/// digest is queued, but nothing is streamed since there's no frontend.
+pub fn headless_sync_task(state: std::sync::Arc<AppState>) {
+    use std::time::{Duration, Instant};
+    use watcher::poll::SessionPoller;
+    use watcher::sync::SyncQueue;
+    use watcher::SessionWatcher;
+
+    const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    let mut poller = SessionPoller::new(&parser::scan_claude_sessions());
+    let session_watcher = watcher::default_sessions_path().and_then(|path| {
+        let mut session_watcher = SessionWatcher::new(path)
+            .inspect_err(|e| tracing::warn!("Failed to create session watcher: {}", e))
+            .ok()?;
+        match session_watcher.start() {
+            Ok(()) => {
+                for root in parser::scan::scan_roots().into_iter().filter(|r| r.label.is_some()) {
+                    if let Err(e) = session_watcher.watch_also(root.path.join("projects")) {
+                        tracing::info!("Not watching {:?}: {}", root.path, e);
+                    }
+                }
+                Some(session_watcher)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to start session watcher: {}", e);
+                None
+            }
+        }
+    });
+    let status = if session_watcher.is_some() {
+        diagnostics::WatcherStatus::Watching
+    } else {
+        tracing::warn!("File watching unavailable, falling back to adaptive polling");
+        diagnostics::WatcherStatus::Polling
+    };
+    state.diagnostics.set_watcher_status(status);
+
+    let mut last_digest_check: Option<Instant> = None;
+    let mut sync_queue = SyncQueue::default();
+    loop {
+        let events = match &session_watcher {
+            Some(session_watcher) => {
+                std::thread::sleep(WATCH_POLL_INTERVAL);
+                session_watcher.poll()
+            }
+            None => {
+                std::thread::sleep(poller.interval(&settings::polling_config(), Instant::now()));
+                poller.poll(&parser::scan_claude_sessions(), Instant::now())
+            }
+        };
+        for event in &events {
+            sync_queue.push(event, Instant::now());
+        }
+
+        let ready = sync_queue.take_ready(Instant::now());
+        if !ready.is_empty() {
+            let report = commands::sync_session_files(&state, &ready);
+            tracing::debug!(
+                "Synced {} sessions ({} files skipped)",
+                report.sessions.len(),
+                report.skipped
+            );
+            if !report.sessions.is_empty() {
+                notifications::notify(commands::sync_notification_events(&state, &report));
+            }
+        }
+
+        if !matches!(last_digest_check, Some(t) if t.elapsed() < commands::DIGEST_CHECK_INTERVAL) {
+            last_digest_check = Some(Instant::now());
+            commands::schedule_daily_digest(&state);
         }
     }
 }
@@ -0,0 +1,119 @@
+//! Active and idle time of a session
+//!
+//! Wall-clock duration runs from the first turn to the last, so a session
+//! left open overnight looks like a twelve-hour effort. Active time instead
+//! adds up the turns themselves plus the pauses between them, except that a
+//! pause longer than [`IDLE_GAP_MS`] counts as idle rather than thinking
+//! time.
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+use crate::parser::CompletedTurn;
+
+/// Longest pause between turns still counted as active
+pub const IDLE_GAP_MS: i64 = 15 * 60 * 1000;
+
+/// A session's duration split into active and idle time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ActiveTime {
+    pub active_ms: u64,
+    pub idle_ms: u64,
+}
+
+/// Split the time from the first turn's start to the last turn's end into
+/// active and idle time
+pub fn calculate_active_time(turns: &[CompletedTurn]) -> ActiveTime {
+    let spans = turns.iter().map(|t| (t.started_at.as_str(), t.duration_ms));
+    time_by_turn(spans).into_iter().fold(ActiveTime::default(), |mut total, time| {
+        total.active_ms += time.active_ms;
+        total.idle_ms += time.idle_ms;
+        total
+    })
+}
+
+/// Active and idle time of each turn, given as start and duration in order
+///
+/// A turn's time is its own duration plus the pause since the previous turn
+/// ended, so the totals add up to [`calculate_active_time`]. Turns whose
+/// start doesn't parse get none.
+pub fn time_by_turn<'a>(turns: impl IntoIterator<Item = (&'a str, Option<i64>)>) -> Vec<ActiveTime> {
+    let mut previous_end: Option<DateTime<FixedOffset>> = None;
+
+    turns
+        .into_iter()
+        .map(|(started_at, duration_ms)| {
+            let mut time = ActiveTime::default();
+            let start = match DateTime::parse_from_rfc3339(started_at) {
+                Ok(start) => start,
+                Err(_) => return time,
+            };
+            if let Some(end) = previous_end {
+                let gap = (start - end).num_milliseconds().max(0);
+                match gap > IDLE_GAP_MS {
+                    true => time.idle_ms += gap as u64,
+                    false => time.active_ms += gap as u64,
+                }
+            }
+
+            let duration = duration_ms.unwrap_or(0).max(0);
+            time.active_ms += duration as u64;
+            previous_end = Some(start + chrono::Duration::milliseconds(duration));
+            time
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(started_at: &str, duration_ms: Option<i64>) -> CompletedTurn {
+        CompletedTurn {
+            turn_number: 1,
+            started_at: started_at.to_string(),
+            ended_at: None,
+            duration_ms,
+            user_message: None,
+            assistant_message: None,
+            model: None,
+            stop_reason: None,
+            provider: Default::default(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_write_5m_tokens: 0,
+            cache_write_1h_tokens: 0,
+            total_tokens: 0,
+            total_context: 0,
+            tool_uses: Vec::new(),
+            tool_count: 0,
+            has_subagents: false,
+            subagent_ids: Vec::new(),
+            start_uuid: None,
+            end_uuid: None,
+            entry_count: 0,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_tokens: 0,
+            thinking_level: None,
+        }
+    }
+
+    #[test]
+    fn test_calculate_active_time() {
+        let minute = 60_000;
+        let turns = [
+            // 2 minutes of work, a 5 minute pause, 3 more minutes
+            turn("2026-02-05T22:00:00Z", Some(2 * minute)),
+            turn("2026-02-05T22:07:00Z", Some(3 * minute)),
+            // Left open overnight, then one last turn without an end
+            turn("2026-02-06T08:10:00Z", None),
+        ];
+
+        let time = calculate_active_time(&turns);
+        assert_eq!(time.active_ms, (2 + 5 + 3) as u64 * minute as u64);
+        assert_eq!(time.idle_ms, 10 * 60 * minute as u64);
+        assert_eq!(calculate_active_time(&[]), ActiveTime::default());
+    }
+}
@@ -0,0 +1,233 @@
+//! API errors and retries
+//!
+//! Claude Code retries requests the API turns away with a rate limit (429)
+//! or because it is overloaded (529), writing an `api_error` entry for each
+//! retry, and shows the error once it gives up. These are counted by kind,
+//! per day and per session, and bursts of retries close together are picked
+//! out as retry storms.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::parser::{ApiErrorEvent, ApiErrorKind, CompletedTurn};
+
+/// API errors counted by kind
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ApiErrorCounts {
+    pub total: u32,
+    pub rate_limit: u32,
+    pub overloaded: u32,
+    pub other: u32,
+    /// Errors Claude Code gave up on and showed
+    pub retries_exhausted: u32,
+    /// Time spent waiting to retry
+    pub retry_wait_ms: u64,
+}
+
+impl ApiErrorCounts {
+    fn add(&mut self, error: &ApiErrorEvent) {
+        self.total += 1;
+        match error.kind {
+            ApiErrorKind::RateLimit => self.rate_limit += 1,
+            ApiErrorKind::Overloaded => self.overloaded += 1,
+            ApiErrorKind::Other => self.other += 1,
+        }
+        if error.retries_exhausted {
+            self.retries_exhausted += 1;
+        }
+        self.retry_wait_ms += error.retry_in_ms.unwrap_or(0);
+    }
+}
+
+/// API errors of one calendar day (reporting timezone)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DailyApiErrors {
+    pub date: String,
+    #[serde(flatten)]
+    pub counts: ApiErrorCounts,
+}
+
+/// API errors of one session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SessionApiErrors {
+    pub session_id: String,
+    pub project_name: String,
+    #[serde(flatten)]
+    pub counts: ApiErrorCounts,
+    pub first_error_at: Option<String>,
+    pub last_error_at: Option<String>,
+}
+
+/// API error totals, per day (oldest first) and per session (most errors
+/// first)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ApiErrorStats {
+    pub totals: ApiErrorCounts,
+    pub by_day: Vec<DailyApiErrors>,
+    pub by_session: Vec<SessionApiErrors>,
+}
+
+/// The API errors recorded with one stored turn
+#[derive(Debug, Clone)]
+pub struct TurnApiErrors {
+    pub session_id: String,
+    pub project_name: String,
+    /// Start of the turn, for errors written without a timestamp
+    pub started_at: String,
+    pub errors: Vec<ApiErrorEvent>,
+}
+
+/// Count API errors by kind, day and session
+pub fn summarize_api_errors(turns: &[TurnApiErrors]) -> ApiErrorStats {
+    let mut totals = ApiErrorCounts::default();
+    let mut by_day: BTreeMap<String, ApiErrorCounts> = BTreeMap::new();
+    let mut by_session: BTreeMap<&str, SessionApiErrors> = BTreeMap::new();
+
+    for turn in turns {
+        for error in &turn.errors {
+            let timestamp = error.timestamp.as_deref().unwrap_or(&turn.started_at);
+            totals.add(error);
+            if let Some(date) = crate::timezone::local_date(timestamp) {
+                by_day.entry(date).or_default().add(error);
+            }
+
+            let session = by_session.entry(&turn.session_id).or_insert_with(|| SessionApiErrors {
+                session_id: turn.session_id.clone(),
+                project_name: turn.project_name.clone(),
+                counts: ApiErrorCounts::default(),
+                first_error_at: None,
+                last_error_at: None,
+            });
+            session.counts.add(error);
+            if session.first_error_at.as_deref().is_none_or(|first| timestamp < first) {
+                session.first_error_at = Some(timestamp.to_string());
+            }
+            if session.last_error_at.as_deref().is_none_or(|last| timestamp > last) {
+                session.last_error_at = Some(timestamp.to_string());
+            }
+        }
+    }
+
+    let mut by_session: Vec<SessionApiErrors> = by_session.into_values().collect();
+    by_session.sort_by_key(|s| std::cmp::Reverse(s.counts.total));
+
+    ApiErrorStats {
+        totals,
+        by_day: by_day
+            .into_iter()
+            .map(|(date, counts)| DailyApiErrors { date, counts })
+            .collect(),
+        by_session,
+    }
+}
+
+/// A burst of API errors within a short window
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryStorm {
+    /// Turn the first error of the burst belongs to
+    pub turn_number: u32,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub counts: ApiErrorCounts,
+}
+
+/// Find bursts of at least `min_errors` API errors each within `window_secs`
+/// of the burst's first error
+///
+/// Bursts don't overlap; errors without a timestamp are skipped.
+pub fn find_retry_storms(turns: &[CompletedTurn], window_secs: i64, min_errors: u32) -> Vec<RetryStorm> {
+    let mut errors: Vec<(u32, DateTime<Utc>, &ApiErrorEvent)> = turns
+        .iter()
+        .flat_map(|turn| turn.api_errors.iter().map(move |e| (turn.turn_number, e)))
+        .filter_map(|(turn_number, error)| {
+            let at = DateTime::parse_from_rfc3339(error.timestamp.as_deref()?).ok()?;
+            Some((turn_number, at.with_timezone(&Utc), error))
+        })
+        .collect();
+    errors.sort_by_key(|(_, at, _)| *at);
+
+    let mut storms = Vec::new();
+    let mut start = 0;
+    while start < errors.len() {
+        let (turn_number, started_at, _) = errors[start];
+        let end = errors[start..]
+            .iter()
+            .position(|(_, at, _)| (*at - started_at).num_seconds() > window_secs)
+            .map_or(errors.len(), |offset| start + offset);
+
+        if (end - start) as u32 >= min_errors.max(1) {
+            let mut counts = ApiErrorCounts::default();
+            for (_, _, error) in &errors[start..end] {
+                counts.add(error);
+            }
+            storms.push(RetryStorm {
+                turn_number,
+                started_at,
+                ended_at: errors[end - 1].1,
+                counts,
+            });
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+    storms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(timestamp: &str, kind: ApiErrorKind, retry_in_ms: u64) -> ApiErrorEvent {
+        ApiErrorEvent {
+            timestamp: Some(timestamp.to_string()),
+            kind,
+            status: None,
+            retry_attempt: Some(1),
+            retry_in_ms: Some(retry_in_ms),
+            retries_exhausted: false,
+        }
+    }
+
+    #[test]
+    fn test_summarize_api_errors() {
+        let turns = [
+            TurnApiErrors {
+                session_id: "a".to_string(),
+                project_name: "app".to_string(),
+                started_at: "2026-03-01T10:00:00Z".to_string(),
+                errors: vec![
+                    error("2026-03-01T10:00:05Z", ApiErrorKind::Overloaded, 1_000),
+                    error("2026-03-01T10:00:07Z", ApiErrorKind::Overloaded, 2_000),
+                ],
+            },
+            TurnApiErrors {
+                session_id: "b".to_string(),
+                project_name: "api".to_string(),
+                started_at: "2026-03-02T09:00:00Z".to_string(),
+                errors: vec![ApiErrorEvent {
+                    timestamp: None,
+                    retry_in_ms: None,
+                    retries_exhausted: true,
+                    ..error("", ApiErrorKind::RateLimit, 0)
+                }],
+            },
+        ];
+
+        let stats = summarize_api_errors(&turns);
+        assert_eq!((stats.totals.total, stats.totals.overloaded, stats.totals.rate_limit), (3, 2, 1));
+        assert_eq!(stats.totals.retries_exhausted, 1);
+        assert_eq!(stats.totals.retry_wait_ms, 3_000);
+        assert_eq!(stats.by_day.len(), 2);
+        assert_eq!(stats.by_session[0].session_id, "a");
+        assert_eq!(stats.by_session[0].last_error_at.as_deref(), Some("2026-03-01T10:00:07Z"));
+        // Falls back to the turn start
+        assert_eq!(stats.by_session[1].first_error_at.as_deref(), Some("2026-03-02T09:00:00Z"));
+    }
+}
@@ -0,0 +1,216 @@
+//! Per-project efficiency benchmarks
+//!
+//! Averages each project's sessions (cost per turn, cache efficiency, OES,
+//! turns per session) and compares them with the baseline of all sessions,
+//! and optionally with all other projects combined. Projects whose cache
+//! efficiency falls well short of the baseline are flagged.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::comparison::MetricDelta;
+
+/// Sessions a project needs before its cache efficiency is judged
+pub const MIN_SESSIONS_FOR_FLAG: u32 = 3;
+/// How far below the baseline CER a project's CER has to be to get flagged
+pub const POOR_CER_MARGIN: f64 = 0.15;
+
+/// One session as input to the benchmarks
+#[derive(Debug, Clone, Default)]
+pub struct BenchmarkSession {
+    pub project_path: String,
+    pub project_name: String,
+    pub cost: f64,
+    pub turns: u32,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// Overall Efficiency Score (0.0 - 1.0)
+    pub oes: f64,
+}
+
+/// Averages over a group of sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BenchmarkStats {
+    pub session_count: u32,
+    pub cost_per_turn: f64,
+    /// Cache Efficiency Ratio over all of the sessions' cache tokens
+    pub cer: f64,
+    /// Mean Overall Efficiency Score
+    pub oes: f64,
+    pub turns_per_session: f64,
+}
+
+impl BenchmarkStats {
+    fn from_sessions<'a>(sessions: impl IntoIterator<Item = &'a BenchmarkSession>) -> Self {
+        let (mut count, mut cost, mut turns, mut cache_read, mut cache_write, mut oes) =
+            (0u32, 0.0, 0u64, 0u64, 0u64, 0.0);
+        for session in sessions {
+            count += 1;
+            cost += session.cost;
+            turns += session.turns as u64;
+            cache_read += session.cache_read_tokens;
+            cache_write += session.cache_write_tokens;
+            oes += session.oes;
+        }
+
+        if count == 0 {
+            return Self::default();
+        }
+        let cache_total = cache_read + cache_write;
+        Self {
+            session_count: count,
+            cost_per_turn: if turns > 0 { cost / turns as f64 } else { 0.0 },
+            cer: if cache_total > 0 { cache_read as f64 / cache_total as f64 } else { 0.0 },
+            oes: oes / count as f64,
+            turns_per_session: turns as f64 / count as f64,
+        }
+    }
+}
+
+/// A project's averages next to those of a reference group
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BenchmarkDeltas {
+    pub cost_per_turn: MetricDelta,
+    pub cer: MetricDelta,
+    pub oes: MetricDelta,
+    pub turns_per_session: MetricDelta,
+}
+
+impl BenchmarkDeltas {
+    fn between(reference: &BenchmarkStats, stats: &BenchmarkStats) -> Self {
+        Self {
+            cost_per_turn: MetricDelta::new(reference.cost_per_turn, stats.cost_per_turn),
+            cer: MetricDelta::new(reference.cer, stats.cer),
+            oes: MetricDelta::new(reference.oes, stats.oes),
+            turns_per_session: MetricDelta::new(reference.turns_per_session, stats.turns_per_session),
+        }
+    }
+}
+
+/// Benchmark of one project
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectBenchmark {
+    pub project_path: String,
+    pub project_name: String,
+    pub stats: BenchmarkStats,
+    /// Compared with all sessions
+    pub vs_baseline: BenchmarkDeltas,
+    /// Compared with all other projects combined, when requested and there
+    /// are other projects
+    pub vs_peers: Option<BenchmarkDeltas>,
+    /// CER at least `POOR_CER_MARGIN` below the baseline, over at least
+    /// `MIN_SESSIONS_FOR_FLAG` sessions
+    pub poor_cache_efficiency: bool,
+}
+
+/// Benchmarks of every project against the global baseline
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectBenchmarks {
+    pub baseline: BenchmarkStats,
+    /// Worst cache efficiency relative to the baseline first
+    pub projects: Vec<ProjectBenchmark>,
+}
+
+/// Benchmark each project's sessions against all sessions
+///
+/// With `compare_peers`, each project is also compared with the sessions of
+/// all other projects.
+pub fn benchmark_projects(sessions: &[BenchmarkSession], compare_peers: bool) -> ProjectBenchmarks {
+    let baseline = BenchmarkStats::from_sessions(sessions);
+
+    let mut by_project: BTreeMap<&str, Vec<&BenchmarkSession>> = BTreeMap::new();
+    for session in sessions {
+        by_project.entry(&session.project_path).or_default().push(session);
+    }
+
+    let mut projects: Vec<ProjectBenchmark> = by_project
+        .into_iter()
+        .map(|(path, project_sessions)| {
+            let stats = BenchmarkStats::from_sessions(project_sessions.iter().copied());
+            let vs_peers = if compare_peers && stats.session_count < baseline.session_count {
+                let peers = BenchmarkStats::from_sessions(sessions.iter().filter(|s| s.project_path != path));
+                Some(BenchmarkDeltas::between(&peers, &stats))
+            } else {
+                None
+            };
+            let poor_cache_efficiency = stats.session_count >= MIN_SESSIONS_FOR_FLAG
+                && stats.cer <= baseline.cer - POOR_CER_MARGIN;
+
+            ProjectBenchmark {
+                project_path: path.to_string(),
+                project_name: project_sessions[0].project_name.clone(),
+                vs_baseline: BenchmarkDeltas::between(&baseline, &stats),
+                stats,
+                vs_peers,
+                poor_cache_efficiency,
+            }
+        })
+        .collect();
+
+    projects.sort_by(|a, b| {
+        a.vs_baseline
+            .cer
+            .diff
+            .total_cmp(&b.vs_baseline.cer.diff)
+            .then_with(|| a.project_path.cmp(&b.project_path))
+    });
+
+    ProjectBenchmarks { baseline, projects }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(project: &str, cost: f64, turns: u32, cache_read: u64, cache_write: u64, oes: f64) -> BenchmarkSession {
+        BenchmarkSession {
+            project_path: format!("/dev/{}", project),
+            project_name: project.to_string(),
+            cost,
+            turns,
+            cache_read_tokens: cache_read,
+            cache_write_tokens: cache_write,
+            oes,
+        }
+    }
+
+    #[test]
+    fn test_benchmark_projects() {
+        let mut sessions: Vec<BenchmarkSession> =
+            (0..3).map(|_| session("api", 2.0, 10, 900, 100, 0.8)).collect();
+        sessions.extend((0..3).map(|_| session("web", 4.0, 20, 300, 700, 0.5)));
+        sessions.push(session("docs", 1.0, 5, 900, 100, 0.9));
+
+        let result = benchmark_projects(&sessions, true);
+        assert_eq!(result.baseline.session_count, 7);
+        // (900 * 4 + 300 * 3) / 7000
+        assert!((result.baseline.cer - 4500.0 / 7000.0).abs() < 1e-9);
+
+        // Worst cache efficiency first, and only it is flagged
+        let names: Vec<&str> = result.projects.iter().map(|p| p.project_name.as_str()).collect();
+        assert_eq!(names, vec!["web", "api", "docs"]);
+        let web = &result.projects[0];
+        assert!(web.poor_cache_efficiency);
+        assert!((web.stats.cost_per_turn - 0.2).abs() < 1e-9);
+        assert_eq!(web.stats.turns_per_session, 20.0);
+        assert!((web.stats.oes - 0.5).abs() < 1e-9);
+        assert!(web.vs_baseline.cer.diff < 0.0);
+        // Against the other projects only: api and docs both have CER 0.9
+        let peers = web.vs_peers.as_ref().unwrap();
+        assert!((peers.cer.baseline - 0.9).abs() < 1e-9);
+
+        // Too few sessions to judge, even with equally poor caching
+        assert!(!result.projects.iter().any(|p| p.project_name != "web" && p.poor_cache_efficiency));
+        let sparse = benchmark_projects(&[session("a", 1.0, 1, 900, 100, 0.5), session("b", 1.0, 1, 0, 1000, 0.5)], false);
+        assert!(sparse.projects.iter().all(|p| !p.poor_cache_efficiency && p.vs_peers.is_none()));
+
+        // A lone project has no peers
+        let single = benchmark_projects(&sessions[..3], true);
+        assert!(single.projects[0].vs_peers.is_none());
+    }
+}
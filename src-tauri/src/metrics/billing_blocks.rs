@@ -0,0 +1,209 @@
+//! 5-hour billing blocks
+//!
+//! Rolling usage windows (see [`super::usage_windows`]) always end now.
+//! Billing blocks instead follow how plan usage resets: a block opens with
+//! the first turn after a quiet spell, at the start of that hour, and closes
+//! five hours later. The next turn after that opens a new block. This is the
+//! grouping ccusage reports as "blocks".
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, DurationRound, SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::metrics::usage_windows::{UsageTurn, UsageWindowConfig};
+
+/// Length of a billing block
+pub const BLOCK_HOURS: u32 = 5;
+
+/// Usage within one billing block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BillingBlock {
+    /// Start of the block (RFC 3339), on the hour
+    pub start: String,
+    /// When the block closes (RFC 3339)
+    pub end: String,
+    /// Start of the block's last turn (RFC 3339)
+    pub last_activity: String,
+    /// Whether the block is still open
+    pub is_active: bool,
+    pub tokens: u64,
+    pub cost: f64,
+    pub turns: u32,
+    pub sessions: u32,
+}
+
+/// How much of the plan's 5-hour limits the open block has left
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BlockHeadroom {
+    pub token_threshold: Option<u64>,
+    pub cost_threshold: Option<f64>,
+    /// Tokens used as a percentage of the token threshold (0-100+)
+    pub token_pct: Option<f64>,
+    /// Cost as a percentage of the cost threshold (0-100+)
+    pub cost_pct: Option<f64>,
+    /// Tokens left before the threshold, 0 once reached
+    pub tokens_remaining: Option<u64>,
+    /// USD left before the threshold, 0 once reached
+    pub cost_remaining: Option<f64>,
+    pub minutes_remaining: u32,
+    /// Tokens by the end of the block if usage keeps its pace so far
+    pub projected_tokens: u64,
+    /// Cost by the end of the block if usage keeps its pace so far
+    pub projected_cost: f64,
+}
+
+/// Billing blocks over a period, oldest first, and the open block's headroom
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BillingBlocks {
+    pub blocks: Vec<BillingBlock>,
+    /// Set while the last block is open
+    pub current: Option<BlockHeadroom>,
+}
+
+/// Group turns into billing blocks as of `now`
+///
+/// `limits` is the 5-hour window configured in settings, if any; its
+/// thresholds stand in for the plan's limits.
+pub fn compute_billing_blocks(
+    turns: &[UsageTurn],
+    limits: Option<&UsageWindowConfig>,
+    now: DateTime<Utc>,
+) -> BillingBlocks {
+    let block_length = Duration::hours(BLOCK_HOURS as i64);
+    let mut sorted: Vec<&UsageTurn> = turns.iter().filter(|t| t.started_at <= now).collect();
+    sorted.sort_by_key(|t| t.started_at);
+
+    let mut groups: Vec<(DateTime<Utc>, Vec<&UsageTurn>)> = Vec::new();
+    for turn in sorted {
+        match groups.last_mut() {
+            Some((start, group)) if turn.started_at < *start + block_length => group.push(turn),
+            _ => {
+                let start = turn
+                    .started_at
+                    .duration_trunc(Duration::hours(1))
+                    .unwrap_or(turn.started_at);
+                groups.push((start, vec![turn]));
+            }
+        }
+    }
+
+    let format = |t: DateTime<Utc>| t.to_rfc3339_opts(SecondsFormat::Secs, true);
+    let blocks: Vec<BillingBlock> = groups
+        .iter()
+        .map(|(start, group)| {
+            let end = *start + block_length;
+            let sessions: HashSet<&str> = group.iter().map(|t| t.session_id.as_str()).collect();
+            BillingBlock {
+                start: format(*start),
+                end: format(end),
+                last_activity: group.last().map(|t| format(t.started_at)).unwrap_or_default(),
+                is_active: now < end,
+                tokens: group.iter().map(|t| t.tokens).sum(),
+                cost: group.iter().map(|t| t.cost).sum(),
+                turns: group.len() as u32,
+                sessions: sessions.len() as u32,
+            }
+        })
+        .collect();
+
+    let current = groups
+        .last()
+        .zip(blocks.last())
+        .filter(|(_, block)| block.is_active)
+        .map(|((start, _), block)| headroom(block, *start, limits, now));
+
+    BillingBlocks { blocks, current }
+}
+
+fn headroom(
+    block: &BillingBlock,
+    start: DateTime<Utc>,
+    limits: Option<&UsageWindowConfig>,
+    now: DateTime<Utc>,
+) -> BlockHeadroom {
+    let token_threshold = limits.and_then(|l| l.token_threshold).filter(|t| *t > 0);
+    let cost_threshold = limits.and_then(|l| l.cost_threshold).filter(|c| *c > 0.0);
+
+    let block_minutes = (BLOCK_HOURS * 60) as f64;
+    // At least a minute in, so a block that just opened doesn't project wildly
+    let elapsed = (now - start).num_minutes().clamp(1, BLOCK_HOURS as i64 * 60) as f64;
+    let pace = block_minutes / elapsed;
+
+    BlockHeadroom {
+        token_threshold,
+        cost_threshold,
+        token_pct: token_threshold.map(|t| block.tokens as f64 / t as f64 * 100.0),
+        cost_pct: cost_threshold.map(|c| block.cost / c * 100.0),
+        tokens_remaining: token_threshold.map(|t| t.saturating_sub(block.tokens)),
+        cost_remaining: cost_threshold.map(|c| (c - block.cost).max(0.0)),
+        minutes_remaining: (block_minutes - elapsed).max(0.0) as u32,
+        projected_tokens: (block.tokens as f64 * pace).round() as u64,
+        projected_cost: block.cost * pace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    fn turn(session: &str, started_at: &str, tokens: u64, cost: f64) -> UsageTurn {
+        UsageTurn {
+            session_id: session.to_string(),
+            started_at: at(started_at),
+            tokens,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_billing_blocks() {
+        let turns = [
+            turn("b", "2026-03-10T17:10:00Z", 20_000, 1.0),
+            turn("a", "2026-03-10T09:25:00Z", 10_000, 0.5),
+            turn("a", "2026-03-10T13:59:00Z", 30_000, 1.5),
+            // Past 14:00, so it opens the next block
+            turn("a", "2026-03-10T14:05:00Z", 40_000, 2.0),
+            turn("c", "2026-03-10T16:00:00Z", 20_000, 1.0),
+        ];
+        let limits = UsageWindowConfig {
+            hours: 5,
+            token_threshold: Some(100_000),
+            cost_threshold: None,
+        };
+
+        let report = compute_billing_blocks(&turns, Some(&limits), at("2026-03-10T18:00:00Z"));
+        assert_eq!(report.blocks.len(), 2);
+        let first = &report.blocks[0];
+        assert_eq!((first.start.as_str(), first.end.as_str()), ("2026-03-10T09:00:00Z", "2026-03-10T14:00:00Z"));
+        assert_eq!((first.tokens, first.turns, first.sessions), (40_000, 2, 1));
+        assert!(!first.is_active);
+
+        let second = &report.blocks[1];
+        assert_eq!(second.start, "2026-03-10T14:00:00Z");
+        assert_eq!(second.last_activity, "2026-03-10T17:10:00Z");
+        assert_eq!((second.tokens, second.sessions), (80_000, 3));
+        assert!(second.is_active);
+
+        // 4 of 5 hours gone
+        let current = report.current.unwrap();
+        assert_eq!(current.token_pct, Some(80.0));
+        assert_eq!(current.tokens_remaining, Some(20_000));
+        assert_eq!(current.cost_remaining, None);
+        assert_eq!(current.minutes_remaining, 60);
+        assert_eq!(current.projected_tokens, 100_000);
+        assert!((current.projected_cost - 5.0).abs() < 1e-9);
+
+        // Once the last block closes nothing is open
+        let later = compute_billing_blocks(&turns, Some(&limits), at("2026-03-10T19:00:00Z"));
+        assert!(later.current.is_none());
+        assert_eq!(compute_billing_blocks(&[], None, at("2026-03-10T19:00:00Z")), BillingBlocks::default());
+    }
+}
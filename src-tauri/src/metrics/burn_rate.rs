@@ -0,0 +1,216 @@
+//! Burn rate: cost per active working hour
+//!
+//! Spend per day says little on its own, since a long day of work costs more
+//! than a short one. Dividing by active time (see [`super::active_time`])
+//! gives what an hour of agent assistance costs, per day and per project.
+//! Subagent turns add their cost but no time of their own, as they run while
+//! the session that spawned them waits.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+use crate::metrics::active_time::time_by_turn;
+
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+/// One stored turn, in session order
+#[derive(Debug, Clone)]
+pub struct TurnActivity {
+    pub session_id: String,
+    pub project_path: String,
+    pub project_name: String,
+    pub is_subagent: bool,
+    pub started_at: String,
+    pub duration_ms: Option<i64>,
+    pub cost: f64,
+}
+
+/// Cost and active time of a set of turns
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BurnRate {
+    pub active_hours: f64,
+    pub cost: f64,
+    /// Cost divided by active hours; 0 without active time
+    pub cost_per_hour: f64,
+}
+
+impl BurnRate {
+    fn add(&mut self, active_ms: u64, cost: f64) {
+        self.active_hours += active_ms as f64 / MS_PER_HOUR;
+        self.cost += cost;
+        self.cost_per_hour = if self.active_hours > 0.0 {
+            self.cost / self.active_hours
+        } else {
+            0.0
+        };
+    }
+}
+
+/// Burn rate of one calendar day (reporting timezone)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DailyBurnRate {
+    pub date: String,
+    #[serde(flatten)]
+    pub rate: BurnRate,
+}
+
+/// Burn rate of one project
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectBurnRate {
+    pub project_path: String,
+    pub project_name: String,
+    #[serde(flatten)]
+    pub rate: BurnRate,
+}
+
+/// Burn rate overall, per day (oldest first) and per project (costliest
+/// first)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct BurnRateReport {
+    pub totals: BurnRate,
+    pub by_day: Vec<DailyBurnRate>,
+    pub by_project: Vec<ProjectBurnRate>,
+    /// Change in cost per hour per day, fitted over the days with active
+    /// time; positive when an hour of work is getting more expensive
+    pub trend_per_day: f64,
+}
+
+/// Total cost and active time by day and project
+///
+/// `local_date` maps a turn's start to its calendar day; turns it can't
+/// place are left out.
+pub fn calculate_burn_rate(
+    turns: &[TurnActivity],
+    local_date: impl Fn(&str) -> Option<String>,
+) -> BurnRateReport {
+    let mut sessions: HashMap<&str, Vec<&TurnActivity>> = HashMap::new();
+    for turn in turns {
+        sessions.entry(&turn.session_id).or_default().push(turn);
+    }
+
+    let mut totals = BurnRate::default();
+    let mut by_day: BTreeMap<String, BurnRate> = BTreeMap::new();
+    let mut by_project: HashMap<&str, (&str, BurnRate)> = HashMap::new();
+
+    for session_turns in sessions.values() {
+        let times = time_by_turn(session_turns.iter().map(|t| (t.started_at.as_str(), t.duration_ms)));
+        for (turn, time) in session_turns.iter().zip(times) {
+            let Some(date) = local_date(&turn.started_at) else {
+                continue;
+            };
+            let active_ms = if turn.is_subagent { 0 } else { time.active_ms };
+            totals.add(active_ms, turn.cost);
+            by_day.entry(date).or_default().add(active_ms, turn.cost);
+            by_project
+                .entry(&turn.project_path)
+                .or_insert_with(|| (&turn.project_name, BurnRate::default()))
+                .1
+                .add(active_ms, turn.cost);
+        }
+    }
+
+    let by_day: Vec<DailyBurnRate> = by_day
+        .into_iter()
+        .map(|(date, rate)| DailyBurnRate { date, rate })
+        .collect();
+    let mut by_project: Vec<ProjectBurnRate> = by_project
+        .into_iter()
+        .map(|(path, (name, rate))| ProjectBurnRate {
+            project_path: path.to_string(),
+            project_name: name.to_string(),
+            rate,
+        })
+        .collect();
+    by_project.sort_by(|a, b| b.rate.cost.total_cmp(&a.rate.cost).then(a.project_path.cmp(&b.project_path)));
+
+    BurnRateReport {
+        totals,
+        trend_per_day: daily_trend(&by_day),
+        by_day,
+        by_project,
+    }
+}
+
+/// Least-squares slope of cost per hour against the day, over the days with
+/// active time
+fn daily_trend(days: &[DailyBurnRate]) -> f64 {
+    let points: Vec<(f64, f64)> = days
+        .iter()
+        .filter(|day| day.rate.active_hours > 0.0)
+        .filter_map(|day| {
+            let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()?;
+            Some((date.num_days_from_ce() as f64, day.rate.cost_per_hour))
+        })
+        .collect();
+    if points.len() < 2 {
+        return 0.0;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(session_id: &str, project: &str, is_subagent: bool, started_at: &str, minutes: i64, cost: f64) -> TurnActivity {
+        TurnActivity {
+            session_id: session_id.to_string(),
+            project_path: format!("/work/{}", project),
+            project_name: project.to_string(),
+            is_subagent,
+            started_at: started_at.to_string(),
+            duration_ms: Some(minutes * 60_000),
+            cost,
+        }
+    }
+
+    fn utc_date(timestamp: &str) -> Option<String> {
+        timestamp.get(..10).map(String::from)
+    }
+
+    #[test]
+    fn test_calculate_burn_rate() {
+        let turns = [
+            // A 30 minute turn, a 10 minute pause and a 20 minute turn
+            turn("s1", "app", false, "2026-03-01T10:00:00Z", 30, 4.0),
+            turn("s1", "app", false, "2026-03-01T10:40:00Z", 20, 2.0),
+            // Subagent cost without time of its own
+            turn("a1", "app", true, "2026-03-01T10:05:00Z", 10, 3.0),
+            // Next day: an hour on another project, after a long idle gap
+            turn("s1", "app", false, "2026-03-02T09:00:00Z", 30, 3.0),
+            turn("s2", "cli", false, "2026-03-02T12:00:00Z", 60, 6.0),
+        ];
+
+        let report = calculate_burn_rate(&turns, utc_date);
+        assert_eq!(report.by_day.len(), 2);
+        assert_eq!(report.by_day[0].date, "2026-03-01");
+        assert!((report.by_day[0].rate.active_hours - 1.0).abs() < 1e-9);
+        assert!((report.by_day[0].rate.cost_per_hour - 9.0).abs() < 1e-9);
+        assert!((report.by_day[1].rate.active_hours - 1.5).abs() < 1e-9);
+        assert!((report.by_day[1].rate.cost_per_hour - 6.0).abs() < 1e-9);
+
+        assert_eq!(report.by_project[0].project_name, "app");
+        assert!((report.by_project[0].rate.cost - 12.0).abs() < 1e-9);
+        assert!((report.by_project[0].rate.active_hours - 1.5).abs() < 1e-9);
+        assert!((report.totals.cost_per_hour - 18.0 / 2.5).abs() < 1e-9);
+        assert!((report.trend_per_day + 3.0).abs() < 1e-9, "got {}", report.trend_per_day);
+
+        assert_eq!(calculate_burn_rate(&[], utc_date), BurnRateReport::default());
+    }
+}
@@ -0,0 +1,292 @@
+//! Per-turn prompt cache breakdown
+//!
+//! Splits each turn's input side into cache reads, cache writes and fresh
+//! (uncached) input, and finds the turns where the cache efficiency ratio
+//! (CER = cache read / (cache read + cache write)) fell sharply, with the
+//! most likely reason: a compaction rewrote the context, the model changed
+//! (each model has its own cache), the cache expired during a pause, or large
+//! file reads added a lot of new context.
+
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::metrics::tokens::CHARS_PER_TOKEN;
+use crate::parser::CompletedTurn;
+
+/// Fall in CER, in points of the 0-1 ratio, that counts as a drop
+const DROP_THRESHOLD: f64 = 0.25;
+
+/// Lifetime of a 5-minute and a 1-hour cache entry
+const CACHE_TTL_5M_MS: i64 = 5 * 60 * 1000;
+const CACHE_TTL_1H_MS: i64 = 60 * 60 * 1000;
+
+/// Estimated tokens of file content read in one turn that count as large
+const LARGE_READ_TOKENS: u64 = 20_000;
+
+/// Cache usage of one turn
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CacheTurn {
+    pub turn_number: u32,
+    pub started_at: String,
+    pub fresh_input_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// Shares of the turn's input side (0-1); zero when it had no input
+    pub fresh_ratio: f64,
+    pub cache_read_ratio: f64,
+    pub cache_write_ratio: f64,
+    /// None when the turn neither read nor wrote the cache
+    pub cer: Option<f64>,
+}
+
+/// Why cache efficiency dropped at a turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CacheDropCause {
+    Compaction,
+    ModelSwitch,
+    CacheExpired,
+    LargeFileRead,
+    Unknown,
+}
+
+/// A turn whose CER fell sharply from the turn before
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CacheDrop {
+    pub turn_number: u32,
+    pub previous_cer: f64,
+    pub cer: f64,
+    pub cause: CacheDropCause,
+    /// e.g. the models switched between, the pause length or the files read
+    pub detail: Option<String>,
+}
+
+/// Per-turn cache usage of a session and where its efficiency dropped
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CacheBreakdown {
+    pub session_id: String,
+    pub turns: Vec<CacheTurn>,
+    pub drops: Vec<CacheDrop>,
+    /// CER of the whole session
+    pub session_cer: Option<f64>,
+}
+
+/// Break down a session's cache usage per turn
+pub fn cache_breakdown(session_id: &str, turns: &[CompletedTurn]) -> CacheBreakdown {
+    let mut breakdown = CacheBreakdown {
+        session_id: session_id.to_string(),
+        ..Default::default()
+    };
+    let (mut total_read, mut total_write) = (0u64, 0u64);
+    let mut previous: Option<(&CompletedTurn, f64)> = None;
+
+    for turn in turns {
+        let cache_write = turn.cache_write_5m_tokens + turn.cache_write_1h_tokens;
+        let input_side = turn.input_tokens + turn.cache_read_tokens + cache_write;
+        let share = |tokens: u64| match input_side {
+            0 => 0.0,
+            total => tokens as f64 / total as f64,
+        };
+        let cer = cer(turn.cache_read_tokens, cache_write);
+        total_read += turn.cache_read_tokens;
+        total_write += cache_write;
+
+        breakdown.turns.push(CacheTurn {
+            turn_number: turn.turn_number,
+            started_at: turn.started_at.clone(),
+            fresh_input_tokens: turn.input_tokens,
+            cache_read_tokens: turn.cache_read_tokens,
+            cache_write_tokens: cache_write,
+            fresh_ratio: share(turn.input_tokens),
+            cache_read_ratio: share(turn.cache_read_tokens),
+            cache_write_ratio: share(cache_write),
+            cer,
+        });
+
+        let cer = match cer {
+            Some(cer) => cer,
+            None => continue,
+        };
+        if let Some((prev_turn, previous_cer)) = previous {
+            if previous_cer - cer >= DROP_THRESHOLD {
+                let (cause, detail) = drop_cause(prev_turn, turn);
+                breakdown.drops.push(CacheDrop {
+                    turn_number: turn.turn_number,
+                    previous_cer,
+                    cer,
+                    cause,
+                    detail,
+                });
+            }
+        }
+        previous = Some((turn, cer));
+    }
+
+    breakdown.session_cer = cer(total_read, total_write);
+    breakdown
+}
+
+fn cer(read: u64, write: u64) -> Option<f64> {
+    match read + write {
+        0 => None,
+        total => Some(read as f64 / total as f64),
+    }
+}
+
+/// Most likely reason the cache stopped paying off at `turn`
+fn drop_cause(previous: &CompletedTurn, turn: &CompletedTurn) -> (CacheDropCause, Option<String>) {
+    if !turn.compactions.is_empty() {
+        let trigger = turn.compactions.iter().find_map(|c| c.trigger.clone());
+        return (CacheDropCause::Compaction, trigger.map(|t| format!("{} compaction", t)));
+    }
+
+    if let (Some(before), Some(after)) = (&previous.model, &turn.model) {
+        if before != after {
+            return (CacheDropCause::ModelSwitch, Some(format!("{} → {}", before, after)));
+        }
+    }
+
+    let ttl = match previous.cache_write_1h_tokens {
+        0 => CACHE_TTL_5M_MS,
+        _ => CACHE_TTL_1H_MS,
+    };
+    let pause = previous_end(previous).zip(DateTime::parse_from_rfc3339(&turn.started_at).ok());
+    if let Some((end, start)) = pause {
+        let pause_ms = (start - end).num_milliseconds();
+        if pause_ms > ttl {
+            return (CacheDropCause::CacheExpired, Some(format!("{} min pause", pause_ms / 60_000)));
+        }
+    }
+
+    let reads: Vec<(&str, u64)> = turn
+        .tool_uses
+        .iter()
+        .filter(|tool| tool.name == "Read")
+        .filter_map(|tool| {
+            let path = tool.input.as_ref()?.get("file_path")?.as_str()?;
+            let tokens = tool.result.as_ref().map_or(0, |r| r.len() as u64 / CHARS_PER_TOKEN);
+            Some((path, tokens))
+        })
+        .collect();
+    if reads.iter().map(|(_, tokens)| tokens).sum::<u64>() >= LARGE_READ_TOKENS {
+        let files: Vec<&str> = reads.iter().map(|(path, _)| *path).collect();
+        return (CacheDropCause::LargeFileRead, Some(files.join(", ")));
+    }
+
+    (CacheDropCause::Unknown, None)
+}
+
+fn previous_end(turn: &CompletedTurn) -> Option<DateTime<chrono::FixedOffset>> {
+    match &turn.ended_at {
+        Some(end) => DateTime::parse_from_rfc3339(end).ok(),
+        None => {
+            let start = DateTime::parse_from_rfc3339(&turn.started_at).ok()?;
+            Some(start + chrono::Duration::milliseconds(turn.duration_ms.unwrap_or(0)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{CompactBoundary, ToolUse};
+    use serde_json::json;
+
+    fn turn(n: u32, started_at: &str, model: &str, read: u64, write: u64) -> CompletedTurn {
+        CompletedTurn {
+            turn_number: n,
+            started_at: started_at.to_string(),
+            ended_at: None,
+            duration_ms: Some(60_000),
+            user_message: None,
+            assistant_message: None,
+            model: Some(model.to_string()),
+            stop_reason: None,
+            provider: Default::default(),
+            input_tokens: 100,
+            output_tokens: 500,
+            cache_read_tokens: read,
+            cache_write_5m_tokens: write,
+            cache_write_1h_tokens: 0,
+            total_tokens: 0,
+            total_context: 0,
+            tool_uses: Vec::new(),
+            tool_count: 0,
+            has_subagents: false,
+            subagent_ids: Vec::new(),
+            start_uuid: None,
+            end_uuid: None,
+            entry_count: 0,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_tokens: 0,
+            thinking_level: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_breakdown() {
+        let opus = "claude-opus-4-5-20251101";
+        let mut compacted = turn(3, "2026-02-05T10:04:00Z", opus, 1_000, 50_000);
+        compacted.compactions.push(CompactBoundary {
+            timestamp: None,
+            trigger: Some("auto".to_string()),
+            pre_tokens: Some(150_000),
+            post_tokens: Some(30_000),
+        });
+        let mut reading = turn(6, "2026-02-05T10:20:00Z", opus, 10_000, 90_000);
+        reading.tool_uses.push(ToolUse {
+            id: "toolu_1".to_string(),
+            name: "Read".to_string(),
+            input: Some(json!({"file_path": "/app/schema.sql"})),
+            result: Some("x".repeat(100_000)),
+            is_error: false,
+        });
+
+        let turns = [
+            turn(1, "2026-02-05T10:00:00Z", opus, 0, 20_000),
+            turn(2, "2026-02-05T10:02:00Z", opus, 90_000, 10_000),
+            compacted,
+            // Already cold after the compaction, so no second drop
+            turn(4, "2026-02-05T10:06:00Z", opus, 0, 40_000),
+            turn(5, "2026-02-05T10:17:00Z", opus, 90_000, 10_000),
+            reading,
+        ];
+
+        let breakdown = cache_breakdown("s1", &turns);
+        assert_eq!(breakdown.turns.len(), 6);
+        let second = &breakdown.turns[1];
+        assert!((second.cache_read_ratio - 90_000.0 / 100_100.0).abs() < 1e-9);
+        assert_eq!(second.cer, Some(0.9));
+
+        let causes: Vec<(u32, CacheDropCause)> = breakdown.drops.iter().map(|d| (d.turn_number, d.cause)).collect();
+        assert_eq!(
+            causes,
+            vec![
+                (3, CacheDropCause::Compaction),
+                (6, CacheDropCause::LargeFileRead),
+            ]
+        );
+        assert_eq!(breakdown.drops[1].detail.as_deref(), Some("/app/schema.sql"));
+    }
+
+    #[test]
+    fn test_drop_causes() {
+        let opus = "claude-opus-4-5-20251101";
+        let sonnet = "claude-sonnet-4-5-20251101";
+        let turns = [
+            turn(1, "2026-02-05T10:00:00Z", opus, 90_000, 10_000),
+            turn(2, "2026-02-05T10:02:00Z", sonnet, 0, 100_000),
+            turn(3, "2026-02-05T10:04:00Z", sonnet, 95_000, 5_000),
+            turn(4, "2026-02-05T10:30:00Z", sonnet, 0, 100_000),
+        ];
+        let drops = cache_breakdown("s1", &turns).drops;
+        assert_eq!(drops[0].cause, CacheDropCause::ModelSwitch);
+        assert_eq!(drops[1].cause, CacheDropCause::CacheExpired);
+        assert_eq!(drops[1].detail.as_deref(), Some("25 min pause"));
+    }
+}
@@ -0,0 +1,242 @@
+//! Session comparison
+//!
+//! Structured diff between two sessions: efficiency scores, totals, per-turn
+//! averages (so a long and a short session can be compared fairly), tool
+//! usage and models.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::trends::percent_change;
+
+/// Metrics of one session as input to a comparison
+#[derive(Debug, Clone, Default)]
+pub struct ComparedSession {
+    pub id: String,
+    pub cost: f64,
+    pub tokens: u64,
+    pub duration_ms: u64,
+    pub turn_count: u32,
+    pub tool_count: u32,
+    pub cer: f64,
+    pub cgr: f64,
+    pub wfs: f64,
+    pub oes: f64,
+    /// Calls per tool name
+    pub tool_usage: Vec<(String, u32)>,
+    pub models: Vec<String>,
+}
+
+/// A metric in the baseline and the compared session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct MetricDelta {
+    pub baseline: f64,
+    pub value: f64,
+    /// `value - baseline`
+    pub diff: f64,
+    /// Change relative to the baseline, in percent
+    pub percent_change: f64,
+}
+
+impl MetricDelta {
+    pub fn new(baseline: f64, value: f64) -> Self {
+        Self {
+            baseline,
+            value,
+            diff: value - baseline,
+            percent_change: percent_change(value, baseline),
+        }
+    }
+}
+
+/// Efficiency score deltas
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct EfficiencyDeltas {
+    pub cer: MetricDelta,
+    pub cgr: MetricDelta,
+    pub wfs: MetricDelta,
+    pub oes: MetricDelta,
+}
+
+/// Total deltas
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TotalDeltas {
+    pub cost: MetricDelta,
+    pub tokens: MetricDelta,
+    pub duration_ms: MetricDelta,
+    pub turns: MetricDelta,
+    pub tool_calls: MetricDelta,
+}
+
+/// Per-turn average deltas
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct PerTurnDeltas {
+    pub cost: MetricDelta,
+    pub tokens: MetricDelta,
+    pub duration_ms: MetricDelta,
+    pub tool_calls: MetricDelta,
+}
+
+/// Calls of one tool in each session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ToolUsageDelta {
+    pub tool: String,
+    pub baseline_count: u32,
+    pub count: u32,
+    pub diff: i64,
+}
+
+/// Models used by each session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ModelDiff {
+    pub common: Vec<String>,
+    pub only_baseline: Vec<String>,
+    pub only_compared: Vec<String>,
+}
+
+/// Structured diff of a session against a baseline session
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct MetricsComparisonDetailed {
+    pub baseline_id: String,
+    pub session_id: String,
+    pub efficiency: EfficiencyDeltas,
+    pub totals: TotalDeltas,
+    pub per_turn: PerTurnDeltas,
+    /// Every tool used by either session, largest absolute change first
+    pub tool_usage: Vec<ToolUsageDelta>,
+    pub models: ModelDiff,
+}
+
+impl MetricsComparisonDetailed {
+    /// Compare `session` against `baseline`
+    pub fn between(baseline: &ComparedSession, session: &ComparedSession) -> Self {
+        let per_turn = |total: f64, turns: u32| {
+            if turns > 0 {
+                total / turns as f64
+            } else {
+                0.0
+            }
+        };
+        let per_turn_delta = |f: fn(&ComparedSession) -> f64| {
+            MetricDelta::new(
+                per_turn(f(baseline), baseline.turn_count),
+                per_turn(f(session), session.turn_count),
+            )
+        };
+
+        Self {
+            baseline_id: baseline.id.clone(),
+            session_id: session.id.clone(),
+            efficiency: EfficiencyDeltas {
+                cer: MetricDelta::new(baseline.cer, session.cer),
+                cgr: MetricDelta::new(baseline.cgr, session.cgr),
+                wfs: MetricDelta::new(baseline.wfs, session.wfs),
+                oes: MetricDelta::new(baseline.oes, session.oes),
+            },
+            totals: TotalDeltas {
+                cost: MetricDelta::new(baseline.cost, session.cost),
+                tokens: MetricDelta::new(baseline.tokens as f64, session.tokens as f64),
+                duration_ms: MetricDelta::new(baseline.duration_ms as f64, session.duration_ms as f64),
+                turns: MetricDelta::new(baseline.turn_count as f64, session.turn_count as f64),
+                tool_calls: MetricDelta::new(baseline.tool_count as f64, session.tool_count as f64),
+            },
+            per_turn: PerTurnDeltas {
+                cost: per_turn_delta(|s| s.cost),
+                tokens: per_turn_delta(|s| s.tokens as f64),
+                duration_ms: per_turn_delta(|s| s.duration_ms as f64),
+                tool_calls: per_turn_delta(|s| s.tool_count as f64),
+            },
+            tool_usage: diff_tool_usage(&baseline.tool_usage, &session.tool_usage),
+            models: diff_models(&baseline.models, &session.models),
+        }
+    }
+}
+
+/// Per-tool call counts of `session` against `baseline`
+pub fn diff_tool_usage(baseline: &[(String, u32)], session: &[(String, u32)]) -> Vec<ToolUsageDelta> {
+    let mut counts: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    for (tool, count) in baseline {
+        counts.entry(tool).or_default().0 += count;
+    }
+    for (tool, count) in session {
+        counts.entry(tool).or_default().1 += count;
+    }
+
+    let mut deltas: Vec<ToolUsageDelta> = counts
+        .into_iter()
+        .map(|(tool, (baseline_count, count))| ToolUsageDelta {
+            tool: tool.to_string(),
+            baseline_count,
+            count,
+            diff: count as i64 - baseline_count as i64,
+        })
+        .collect();
+    // Stable sort keeps ties in name order
+    deltas.sort_by_key(|d| std::cmp::Reverse(d.diff.abs()));
+    deltas
+}
+
+fn diff_models(baseline: &[String], session: &[String]) -> ModelDiff {
+    let baseline: BTreeSet<&String> = baseline.iter().collect();
+    let session: BTreeSet<&String> = session.iter().collect();
+
+    ModelDiff {
+        common: baseline.intersection(&session).map(|m| m.to_string()).collect(),
+        only_baseline: baseline.difference(&session).map(|m| m.to_string()).collect(),
+        only_compared: session.difference(&baseline).map(|m| m.to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, cost: f64, turns: u32, tools: &[(&str, u32)], models: &[&str]) -> ComparedSession {
+        ComparedSession {
+            id: id.to_string(),
+            cost,
+            tokens: 1000 * turns as u64,
+            turn_count: turns,
+            tool_count: tools.iter().map(|(_, c)| c).sum(),
+            cer: 0.5,
+            oes: 60.0,
+            tool_usage: tools.iter().map(|(t, c)| (t.to_string(), *c)).collect(),
+            models: models.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compare_sessions_detailed() {
+        let baseline = session("a", 2.0, 10, &[("Read", 5), ("Edit", 2)], &["opus", "haiku"]);
+        let mut other = session("b", 2.0, 20, &[("Read", 4), ("Bash", 6)], &["opus", "sonnet"]);
+        other.cer = 0.8;
+
+        let diff = MetricsComparisonDetailed::between(&baseline, &other);
+        assert_eq!(diff.baseline_id, "a");
+        assert!((diff.efficiency.cer.diff - 0.3).abs() < 1e-9);
+        assert_eq!(diff.efficiency.oes.diff, 0.0);
+
+        // Same total cost over twice the turns: half the cost per turn
+        assert_eq!(diff.totals.cost.diff, 0.0);
+        assert_eq!(diff.per_turn.cost.baseline, 0.2);
+        assert_eq!(diff.per_turn.cost.value, 0.1);
+        assert!((diff.per_turn.cost.percent_change + 50.0).abs() < 1e-9);
+        assert_eq!(diff.per_turn.tokens.diff, 0.0);
+
+        let tools: Vec<(&str, i64)> = diff.tool_usage.iter().map(|t| (t.tool.as_str(), t.diff)).collect();
+        assert_eq!(tools, vec![("Bash", 6), ("Edit", -2), ("Read", -1)]);
+
+        assert_eq!(diff.models.common, vec!["opus"]);
+        assert_eq!(diff.models.only_baseline, vec!["haiku"]);
+        assert_eq!(diff.models.only_compared, vec!["sonnet"]);
+    }
+}
@@ -0,0 +1,257 @@
+//! Concurrent session detection
+//!
+//! Finds the time windows in which turns from two or more sessions were
+//! running at once, e.g. parallel agents in separate terminals or subagents
+//! working while the session that spawned them waits. Each window carries
+//! the share of turn cost spent inside it, so parallel workloads can be
+//! weighed against the rest of the spend.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Most windows returned in a report, costliest first
+pub const MAX_WINDOWS: usize = 100;
+
+/// One turn's span of wall-clock time
+#[derive(Debug, Clone)]
+pub struct TurnInterval {
+    pub session_id: String,
+    pub project_name: String,
+    pub is_subagent: bool,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub cost: f64,
+}
+
+impl TurnInterval {
+    /// Build an interval from stored turn times
+    ///
+    /// The end falls back to start + duration. Returns None if the start
+    /// can't be parsed; turns without an end or duration get an empty span.
+    pub fn new(
+        session_id: String,
+        project_name: String,
+        is_subagent: bool,
+        started_at: &str,
+        ended_at: Option<&str>,
+        duration_ms: Option<i64>,
+        cost: f64,
+    ) -> Option<Self> {
+        let start = DateTime::parse_from_rfc3339(started_at).ok()?.with_timezone(&Utc);
+        let end = ended_at
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .or_else(|| duration_ms.map(|ms| start + chrono::Duration::milliseconds(ms.max(0))))
+            .unwrap_or(start)
+            .max(start);
+        Some(Self { session_id, project_name, is_subagent, start, end, cost })
+    }
+
+    fn duration_ms(&self) -> i64 {
+        (self.end - self.start).num_milliseconds()
+    }
+}
+
+/// A stretch of time with turns from two or more sessions running at once
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ConcurrencyWindow {
+    pub started_at: String,
+    pub ended_at: String,
+    pub duration_ms: u64,
+    /// Most sessions running at the same moment
+    pub peak_sessions: u32,
+    /// Every session with a turn running during the window
+    pub session_ids: Vec<String>,
+    /// How many of those sessions are subagents
+    pub subagent_sessions: u32,
+    pub projects: Vec<String>,
+    /// Turn cost prorated to the part of each turn inside the window
+    pub cost: f64,
+}
+
+/// Concurrent windows over a period and how much of its cost they hold
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ConcurrencyReport {
+    /// Costliest windows first, at most [`MAX_WINDOWS`]
+    pub windows: Vec<ConcurrencyWindow>,
+    pub window_count: u32,
+    pub peak_concurrency: u32,
+    /// Time with two or more sessions running
+    pub concurrent_ms: u64,
+    pub concurrent_cost: f64,
+    pub total_cost: f64,
+    /// Fraction of total cost spent in concurrent windows (0-1)
+    pub concurrent_cost_share: f64,
+}
+
+/// Find the windows where turns from different sessions overlap
+///
+/// Turns that only touch (one ends as the next starts) don't overlap. A turn
+/// partly inside a window contributes the matching fraction of its cost.
+pub fn detect_concurrency(intervals: &[TurnInterval]) -> ConcurrencyReport {
+    // (time, is_start, interval index); ends sort before starts at the same time
+    let mut events: Vec<(DateTime<Utc>, bool, usize)> = Vec::with_capacity(intervals.len() * 2);
+    for (i, interval) in intervals.iter().enumerate() {
+        if interval.end > interval.start {
+            events.push((interval.start, true, i));
+            events.push((interval.end, false, i));
+        }
+    }
+    events.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    // Running turns per session, and every running turn
+    let mut running: HashMap<&str, u32> = HashMap::new();
+    let mut running_turns: BTreeSet<usize> = BTreeSet::new();
+    let mut windows: Vec<ConcurrencyWindow> = Vec::new();
+    let mut open: Option<(DateTime<Utc>, u32, BTreeSet<usize>)> = None;
+
+    for (time, is_start, i) in events {
+        let session = intervals[i].session_id.as_str();
+        if is_start {
+            *running.entry(session).or_insert(0) += 1;
+            running_turns.insert(i);
+        } else {
+            running_turns.remove(&i);
+            if let Some(count) = running.get_mut(session) {
+                *count -= 1;
+                if *count == 0 {
+                    running.remove(session);
+                }
+            }
+        }
+
+        let sessions = running.len() as u32;
+        match (&mut open, sessions >= 2) {
+            (Some((_, peak, members)), true) => {
+                *peak = (*peak).max(sessions);
+                if is_start {
+                    members.insert(i);
+                }
+            }
+            (None, true) => open = Some((time, sessions, running_turns.clone())),
+            (Some(_), false) => {
+                if let Some((start, peak, members)) = open.take() {
+                    windows.push(close_window(intervals, start, time, peak, &members));
+                }
+            }
+            (None, false) => {}
+        }
+    }
+
+    // An empty f64 sum is -0.0, so no turns or no windows cost plain zero
+    let total_cost: f64 = match intervals.is_empty() {
+        true => 0.0,
+        false => intervals.iter().map(|t| t.cost).sum(),
+    };
+    let mut report = ConcurrencyReport {
+        window_count: windows.len() as u32,
+        peak_concurrency: windows.iter().map(|w| w.peak_sessions).max().unwrap_or(0),
+        concurrent_ms: windows.iter().map(|w| w.duration_ms).sum(),
+        concurrent_cost: match windows.is_empty() {
+            true => 0.0,
+            false => windows.iter().map(|w| w.cost).sum(),
+        },
+        total_cost,
+        ..Default::default()
+    };
+    if total_cost > 0.0 {
+        report.concurrent_cost_share = (report.concurrent_cost / total_cost).min(1.0);
+    }
+
+    windows.sort_by(|a, b| b.cost.partial_cmp(&a.cost).unwrap_or(std::cmp::Ordering::Equal));
+    windows.truncate(MAX_WINDOWS);
+    report.windows = windows;
+    report
+}
+
+fn close_window(
+    intervals: &[TurnInterval],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    peak: u32,
+    members: &BTreeSet<usize>,
+) -> ConcurrencyWindow {
+    let mut sessions = BTreeSet::new();
+    let mut subagents = BTreeSet::new();
+    let mut projects = BTreeSet::new();
+    let mut cost = 0.0;
+
+    for &i in members {
+        let turn = &intervals[i];
+        sessions.insert(turn.session_id.clone());
+        if turn.is_subagent {
+            subagents.insert(turn.session_id.as_str());
+        }
+        projects.insert(turn.project_name.clone());
+
+        let overlap = (turn.end.min(end) - turn.start.max(start)).num_milliseconds().max(0);
+        cost += turn.cost * overlap as f64 / turn.duration_ms() as f64;
+    }
+
+    ConcurrencyWindow {
+        started_at: start.to_rfc3339(),
+        ended_at: end.to_rfc3339(),
+        duration_ms: (end - start).num_milliseconds().max(0) as u64,
+        peak_sessions: peak,
+        subagent_sessions: subagents.len() as u32,
+        session_ids: sessions.into_iter().collect(),
+        projects: projects.into_iter().collect(),
+        cost,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(session: &str, subagent: bool, start: &str, end: &str, cost: f64) -> TurnInterval {
+        TurnInterval::new(session.into(), "app".into(), subagent, start, Some(end), None, cost).unwrap()
+    }
+
+    #[test]
+    fn test_detect_concurrency() {
+        let intervals = [
+            // Main session turn, 10:00-10:10, with a subagent running 10:02-10:06
+            interval("main", false, "2026-02-05T10:00:00Z", "2026-02-05T10:10:00Z", 1.0),
+            interval("agent-1", true, "2026-02-05T10:02:00Z", "2026-02-05T10:06:00Z", 0.4),
+            // A second terminal joins 10:04-10:08, peaking at three sessions
+            interval("other", false, "2026-02-05T10:04:00Z", "2026-02-05T10:08:00Z", 0.8),
+            // Next main turn only touches the previous one; nothing overlaps
+            interval("main", false, "2026-02-05T10:10:00Z", "2026-02-05T10:20:00Z", 2.0),
+        ];
+
+        let report = detect_concurrency(&intervals);
+        assert_eq!(report.window_count, 1);
+        assert_eq!(report.peak_concurrency, 3);
+        assert_eq!(report.concurrent_ms, 6 * 60_000);
+
+        let window = &report.windows[0];
+        assert_eq!(window.started_at, "2026-02-05T10:02:00+00:00");
+        assert_eq!(window.ended_at, "2026-02-05T10:08:00+00:00");
+        assert_eq!(window.session_ids, vec!["agent-1", "main", "other"]);
+        assert_eq!(window.subagent_sessions, 1);
+        // 6 of main's 10 minutes, plus both other turns in full
+        assert!((window.cost - (0.6 + 0.4 + 0.8)).abs() < 1e-9);
+        assert!((report.concurrent_cost_share - 1.8 / 4.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_single_session_has_no_windows() {
+        let intervals = [
+            interval("main", false, "2026-02-05T10:00:00Z", "2026-02-05T10:10:00Z", 1.0),
+            interval("main", false, "2026-02-05T10:05:00Z", "2026-02-05T10:15:00Z", 1.0),
+        ];
+        let report = detect_concurrency(&intervals);
+        assert!(report.windows.is_empty());
+        assert_eq!(report.concurrent_cost_share, 0.0);
+        assert!(report.concurrent_cost.is_sign_positive());
+        assert!((report.total_cost - 2.0).abs() < 1e-9);
+
+        let empty = detect_concurrency(&[]);
+        assert!(empty.total_cost == 0.0 && empty.total_cost.is_sign_positive());
+    }
+}
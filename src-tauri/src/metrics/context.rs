@@ -0,0 +1,267 @@
+//! Context window utilization
+//!
+//! Follows how full the model's context window was over a session. Turn-level
+//! token counts add up every API request in the turn, so the timeline is built
+//! from the session entries instead: each turn's context is the largest
+//! single request (input + cache read tokens) made during it.
+//!
+//! Compactions are the `compact_boundary` entries the turn aggregator attaches
+//! to turns. Older sessions don't have those, so a turn whose context falls to
+//! half of a previous one that was near the ceiling counts as an inferred
+//! compaction.
+
+use serde::Serialize;
+
+use crate::metrics::cost::find_pricing;
+use crate::parser::{CompletedTurn, Entry, EntryType, TurnAggregator};
+
+/// Context window assumed for models without a known limit
+pub const DEFAULT_CONTEXT_LIMIT: u64 = 200_000;
+
+/// Window of the long-context beta, assumed when a request outgrows the model's limit
+pub const EXTENDED_CONTEXT_LIMIT: u64 = 1_000_000;
+
+/// Utilization (percent) a turn must reach before a context drop after it
+/// counts as an inferred compaction
+const COMPACTION_MIN_UTILIZATION_PCT: f64 = 60.0;
+
+/// Context usage of one turn
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ContextPoint {
+    pub turn_number: u32,
+    pub started_at: String,
+    pub model: Option<String>,
+    /// Largest input + cache read tokens of a single request in the turn
+    pub context_tokens: u64,
+    /// Context window of the turn's model
+    pub context_limit: u64,
+    pub utilization_pct: f64,
+}
+
+/// A point where the conversation was summarized to free up context
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CompactionEvent {
+    /// First turn to run on the compacted context
+    pub turn_number: u32,
+    pub timestamp: Option<String>,
+    /// "auto" or "manual" as recorded by Claude Code; None when inferred
+    pub trigger: Option<String>,
+    /// Context tokens just before compacting
+    pub pre_tokens: Option<u64>,
+    /// Detected from a drop in context usage rather than a boundary entry
+    pub inferred: bool,
+}
+
+/// Per-turn context usage of a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ContextTimeline {
+    pub session_id: String,
+    pub points: Vec<ContextPoint>,
+    pub compactions: Vec<CompactionEvent>,
+    pub peak_utilization_pct: f64,
+}
+
+/// Builds a [`ContextTimeline`] from a session's entries in file order
+pub struct ContextTimelineBuilder {
+    aggregator: TurnAggregator,
+    /// Largest request context seen since the last completed turn
+    turn_peak: u64,
+    points: Vec<ContextPoint>,
+    compactions: Vec<CompactionEvent>,
+}
+
+impl ContextTimelineBuilder {
+    pub fn new() -> Self {
+        Self {
+            aggregator: TurnAggregator::new(),
+            turn_peak: 0,
+            points: Vec::new(),
+            compactions: Vec::new(),
+        }
+    }
+
+    /// Feed the next entry of the session
+    pub fn process_entry(&mut self, entry: Entry) {
+        // Sidechain requests run in the subagent's own context
+        if entry.entry_type == EntryType::Assistant && !entry.is_subagent() {
+            if let Some(usage) = &entry.usage {
+                self.turn_peak = self.turn_peak.max(usage.input_tokens + usage.cache_read_input_tokens);
+            }
+        }
+
+        if let Some(turn) = self.aggregator.process_entry(entry) {
+            self.push_turn(turn);
+        }
+    }
+
+    fn push_turn(&mut self, turn: CompletedTurn) {
+        self.compactions.extend(turn.compactions.into_iter().map(|c| CompactionEvent {
+            turn_number: turn.turn_number,
+            timestamp: c.timestamp,
+            trigger: c.trigger,
+            pre_tokens: c.pre_tokens,
+            inferred: false,
+        }));
+
+        let context_tokens = std::mem::take(&mut self.turn_peak);
+        let context_limit = context_limit(turn.model.as_deref(), context_tokens);
+        self.points.push(ContextPoint {
+            turn_number: turn.turn_number,
+            started_at: turn.started_at,
+            model: turn.model,
+            context_tokens,
+            context_limit,
+            utilization_pct: context_tokens as f64 / context_limit as f64 * 100.0,
+        });
+    }
+
+    /// Complete the last turn and build the timeline
+    pub fn finish(mut self, session_id: String) -> ContextTimeline {
+        if let Some(turn) = self.aggregator.flush() {
+            self.push_turn(turn);
+        }
+
+        let inferred = infer_compactions(&self.points, &self.compactions);
+        self.compactions.extend(inferred);
+        self.compactions.sort_by_key(|c| c.turn_number);
+
+        let peak_utilization_pct = self
+            .points
+            .iter()
+            .map(|p| p.utilization_pct)
+            .fold(0.0_f64, f64::max);
+
+        ContextTimeline {
+            session_id,
+            points: self.points,
+            compactions: self.compactions,
+            peak_utilization_pct,
+        }
+    }
+}
+
+impl Default for ContextTimelineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Context window of a model, widened to the extended window if a request
+/// already used more than the model's usual limit
+pub fn context_limit(model: Option<&str>, observed_tokens: u64) -> u64 {
+    let limit = model
+        .and_then(find_pricing)
+        .and_then(|p| p.max_context_tokens)
+        .map(u64::from)
+        .unwrap_or(DEFAULT_CONTEXT_LIMIT);
+    if observed_tokens > limit {
+        EXTENDED_CONTEXT_LIMIT.max(limit)
+    } else {
+        limit
+    }
+}
+
+/// Compactions implied by context usage halving after a turn near the
+/// ceiling, for turns no boundary entry already explains
+fn infer_compactions(points: &[ContextPoint], recorded: &[CompactionEvent]) -> Vec<CompactionEvent> {
+    let mut inferred = Vec::new();
+    // Last turn with usage; turns without requests don't break the comparison
+    let mut previous: Option<&ContextPoint> = None;
+
+    for point in points.iter().filter(|p| p.context_tokens > 0) {
+        if let Some(prev) = previous {
+            let dropped = point.context_tokens * 2 <= prev.context_tokens;
+            let recorded_here = recorded
+                .iter()
+                .any(|c| c.turn_number > prev.turn_number && c.turn_number <= point.turn_number);
+            if dropped && prev.utilization_pct >= COMPACTION_MIN_UTILIZATION_PCT && !recorded_here {
+                inferred.push(CompactionEvent {
+                    turn_number: point.turn_number,
+                    timestamp: Some(point.started_at.clone()),
+                    trigger: None,
+                    pre_tokens: Some(prev.context_tokens),
+                    inferred: true,
+                });
+            }
+        }
+        previous = Some(point);
+    }
+
+    inferred
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::jsonl::parse_line;
+
+    fn user(uuid: &str, timestamp: &str) -> Entry {
+        parse_line(&format!(
+            r#"{{"type":"user","uuid":"{}","timestamp":"{}","message":{{"role":"user","content":"go"}}}}"#,
+            uuid, timestamp
+        ))
+        .unwrap()
+    }
+
+    fn assistant(uuid: &str, input: u64, cache_read: u64, stop_reason: &str) -> Entry {
+        parse_line(&format!(
+            r#"{{"type":"assistant","uuid":"{}","timestamp":"2026-03-02T10:00:00Z","message":{{"role":"assistant","model":"claude-sonnet-4-5-20251101","content":[{{"type":"text","text":"ok"}}],"stop_reason":"{}","usage":{{"input_tokens":{},"cache_read_input_tokens":{},"output_tokens":10}}}}}}"#,
+            uuid, stop_reason, input, cache_read
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_context_timeline() {
+        let mut builder = ContextTimelineBuilder::new();
+
+        // Turn 1: the peak request counts, not the sum of the turn's requests
+        builder.process_entry(user("u1", "2026-03-02T10:00:00Z"));
+        builder.process_entry(assistant("a1", 1_000, 40_000, "tool_use"));
+        builder.process_entry(assistant("a2", 2_000, 48_000, "end_turn"));
+        // Turn 2 nears the ceiling, then Claude Code compacts
+        builder.process_entry(user("u2", "2026-03-02T10:05:00Z"));
+        builder.process_entry(assistant("a3", 10_000, 170_000, "end_turn"));
+        builder.process_entry(
+            parse_line(r#"{"type":"system","subtype":"compact_boundary","timestamp":"2026-03-02T10:06:00Z","compactMetadata":{"trigger":"auto","preTokens":181000}}"#)
+                .unwrap(),
+        );
+        builder.process_entry(user("u3", "2026-03-02T10:07:00Z"));
+        builder.process_entry(assistant("a4", 5_000, 15_000, "end_turn"));
+        // Turn 4 climbs again, turn 5 falls sharply without a boundary entry
+        builder.process_entry(user("u4", "2026-03-02T10:10:00Z"));
+        builder.process_entry(assistant("a5", 20_000, 140_000, "end_turn"));
+        builder.process_entry(user("u5", "2026-03-02T10:15:00Z"));
+        builder.process_entry(assistant("a6", 3_000, 20_000, "end_turn"));
+
+        let timeline = builder.finish("s1".to_string());
+
+        let context: Vec<u64> = timeline.points.iter().map(|p| p.context_tokens).collect();
+        assert_eq!(context, vec![50_000, 180_000, 20_000, 160_000, 23_000]);
+        assert_eq!(timeline.points[0].context_limit, 200_000);
+        assert!((timeline.points[1].utilization_pct - 90.0).abs() < 1e-9);
+        assert!((timeline.peak_utilization_pct - 90.0).abs() < 1e-9);
+
+        assert_eq!(timeline.compactions.len(), 2);
+        let recorded = &timeline.compactions[0];
+        assert_eq!(recorded.turn_number, 3);
+        assert_eq!(recorded.trigger.as_deref(), Some("auto"));
+        assert_eq!(recorded.pre_tokens, Some(181_000));
+        assert!(!recorded.inferred);
+        let inferred = &timeline.compactions[1];
+        assert_eq!(inferred.turn_number, 5);
+        assert_eq!(inferred.pre_tokens, Some(160_000));
+        assert!(inferred.inferred);
+    }
+
+    #[test]
+    fn test_context_limit() {
+        assert_eq!(context_limit(Some("claude-opus-4-5-20251101"), 10_000), 200_000);
+        assert_eq!(context_limit(None, 10_000), DEFAULT_CONTEXT_LIMIT);
+        // A request past the usual window means the session ran on the extended one
+        assert_eq!(context_limit(Some("claude-sonnet-4-5-20251101"), 350_000), EXTENDED_CONTEXT_LIMIT);
+    }
+}
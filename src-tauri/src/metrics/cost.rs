@@ -1,13 +1,37 @@
 //! Cost calculation utilities
 //!
-//! Handles computing costs based on token usage and pricing
+//! Handles computing costs based on token usage and pricing. Pricing entries
+//! belong to a [`CostProvider`], the backend that bills the requests: the
+//! Anthropic API, Amazon Bedrock or Google Vertex AI. A turn's provider is
+//! detected from its response metadata, and its model is looked up in that
+//! provider's entries first, then in the Anthropic ones.
+//!
+//! A model can have several entries with different `effective_from` dates
+//! when its price changed. Costs use the entry in effect on the turn's date,
+//! so past sessions keep the rate they were billed at.
+//!
+//! Models with no pricing entry, and turns that record no model, cost
+//! nothing rather than a guessed rate; they are collected in
+//! [`unpriced_models`] so users can add the missing rates.
+
+use std::collections::BTreeSet;
+use std::sync::{Mutex, RwLock};
 
 use serde::{Deserialize, Serialize};
 
 use super::tokens::TurnTokens;
 
+lazy_static::lazy_static! {
+    /// Pricing used for cost calculations: the built-in defaults with any
+    /// user overrides from the pricing table layered on top
+    static ref ACTIVE_PRICING: RwLock<Vec<ModelPricing>> = RwLock::new(get_default_pricing());
+    /// Models turns were priced for without a pricing entry
+    static ref UNPRICED_MODELS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+}
+
 /// Pricing for a Claude model
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct ModelPricing {
     pub model_id: String,
     pub display_name: String,
@@ -17,6 +41,92 @@ pub struct ModelPricing {
     pub cache_write_1h_per_million: f64,
     pub cache_read_per_million: f64,
     pub max_context_tokens: Option<u32>,
+    /// Backend these rates apply to
+    #[serde(default)]
+    pub provider: CostProvider,
+    /// First day (YYYY-MM-DD, UTC) these rates apply; they last until the
+    /// next entry for the same model and provider starts. None for rates
+    /// with no known start.
+    #[serde(default)]
+    pub effective_from: Option<String>,
+}
+
+/// Backend that bills a request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CostProvider {
+    #[default]
+    Anthropic,
+    Bedrock,
+    Vertex,
+}
+
+impl CostProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostProvider::Anthropic => "anthropic",
+            CostProvider::Bedrock => "bedrock",
+            CostProvider::Vertex => "vertex",
+        }
+    }
+
+    /// Parse a stored provider name
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "anthropic" => Some(CostProvider::Anthropic),
+            "bedrock" => Some(CostProvider::Bedrock),
+            "vertex" => Some(CostProvider::Vertex),
+            _ => None,
+        }
+    }
+
+    /// Detect the provider of a response from its message ID and model
+    ///
+    /// Bedrock and Vertex tag their message IDs (`msg_bdrk_`, `msg_vrtx_`);
+    /// without one, the shape of the model ID decides.
+    pub fn detect(model: Option<&str>, message_id: Option<&str>) -> Self {
+        match message_id {
+            Some(id) if id.starts_with("msg_bdrk_") => CostProvider::Bedrock,
+            Some(id) if id.starts_with("msg_vrtx_") => CostProvider::Vertex,
+            _ => model.map(Self::from_model).unwrap_or_default(),
+        }
+    }
+
+    /// Detect the provider from a model ID alone
+    ///
+    /// Bedrock IDs look like `us.anthropic.claude-sonnet-4-5-20250929-v1:0`
+    /// (or an inference profile ARN), Vertex IDs like `claude-opus-4-5@20251101`.
+    pub fn from_model(model: &str) -> Self {
+        if model.starts_with("arn:aws:bedrock") || model.contains("anthropic.claude") {
+            CostProvider::Bedrock
+        } else if model.contains('@') {
+            CostProvider::Vertex
+        } else {
+            CostProvider::Anthropic
+        }
+    }
+}
+
+/// Rewrite a Bedrock or Vertex model ID in the Anthropic API form
+///
+/// `us.anthropic.claude-opus-4-5-20251101-v1:0` and `claude-opus-4-5@20251101`
+/// both become `claude-opus-4-5-20251101`.
+pub fn normalize_model_id(model: &str) -> String {
+    match model.rfind("anthropic.") {
+        Some(pos) => {
+            let model = &model[pos + "anthropic.".len()..];
+            let is_version = |suffix: &str| {
+                suffix.split(':').all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+            };
+            // Drop the Bedrock version suffix, e.g. "-v1:0"
+            match model.rfind("-v") {
+                Some(end) if is_version(&model[end + 2..]) => model[..end].to_string(),
+                _ => model.to_string(),
+            }
+        }
+        None => model.replace('@', "-"),
+    }
 }
 
 impl ModelPricing {
@@ -98,6 +208,8 @@ pub fn get_default_pricing() -> Vec<ModelPricing> {
             cache_write_1h_per_million: 10.00,
             cache_read_per_million: 0.50,
             max_context_tokens: Some(200000),
+            provider: CostProvider::Anthropic,
+            effective_from: None,
         },
         ModelPricing {
             model_id: "claude-sonnet-4-5-20251101".to_string(),
@@ -108,6 +220,8 @@ pub fn get_default_pricing() -> Vec<ModelPricing> {
             cache_write_1h_per_million: 6.00,
             cache_read_per_million: 0.30,
             max_context_tokens: Some(200000),
+            provider: CostProvider::Anthropic,
+            effective_from: None,
         },
         ModelPricing {
             model_id: "claude-haiku-4-5-20251101".to_string(),
@@ -118,6 +232,8 @@ pub fn get_default_pricing() -> Vec<ModelPricing> {
             cache_write_1h_per_million: 2.00,
             cache_read_per_million: 0.10,
             max_context_tokens: Some(200000),
+            provider: CostProvider::Anthropic,
+            effective_from: None,
         },
         ModelPricing {
             model_id: "claude-opus-4-6-20260219".to_string(),
@@ -128,6 +244,8 @@ pub fn get_default_pricing() -> Vec<ModelPricing> {
             cache_write_1h_per_million: 10.00,
             cache_read_per_million: 0.50,
             max_context_tokens: Some(200000),
+            provider: CostProvider::Anthropic,
+            effective_from: None,
         },
         ModelPricing {
             model_id: "claude-sonnet-4-6-20260219".to_string(),
@@ -138,6 +256,8 @@ pub fn get_default_pricing() -> Vec<ModelPricing> {
             cache_write_1h_per_million: 6.00,
             cache_read_per_million: 0.30,
             max_context_tokens: Some(200000),
+            provider: CostProvider::Anthropic,
+            effective_from: None,
         },
         ModelPricing {
             model_id: "claude-sonnet-4-20250514".to_string(),
@@ -148,53 +268,210 @@ pub fn get_default_pricing() -> Vec<ModelPricing> {
             cache_write_1h_per_million: 6.00,
             cache_read_per_million: 0.30,
             max_context_tokens: Some(200000),
+            provider: CostProvider::Anthropic,
+            effective_from: None,
         },
     ]
 }
 
+/// Layer pricing overrides on top of a base pricing list
+///
+/// Overrides replace entries with the same model ID, provider and start
+/// date; other entries are appended.
+pub fn merge_pricing(base: Vec<ModelPricing>, overrides: Vec<ModelPricing>) -> Vec<ModelPricing> {
+    let mut pricing = base;
+    for entry in overrides {
+        match pricing
+            .iter_mut()
+            .find(|p| {
+                p.model_id == entry.model_id && p.provider == entry.provider && p.effective_from == entry.effective_from
+            })
+        {
+            Some(existing) => *existing = entry,
+            None => pricing.push(entry),
+        }
+    }
+    pricing
+}
+
+/// Replace the active pricing with the defaults plus the given user overrides
+///
+/// Forgets the unpriced models, since the new entries may cover them; the
+/// ones still without rates are found again as costs are recalculated.
+pub fn set_pricing_overrides(overrides: Vec<ModelPricing>) {
+    let pricing = merge_pricing(get_default_pricing(), overrides);
+    if let Ok(mut active) = ACTIVE_PRICING.write() {
+        *active = pricing;
+    }
+    if let Ok(mut unpriced) = UNPRICED_MODELS.lock() {
+        unpriced.clear();
+    }
+}
+
+/// Models seen in priced turns that have no pricing entry, sorted
+///
+/// Their turns are counted at zero cost until a rate is added with
+/// `set_model_pricing`.
+pub fn unpriced_models() -> Vec<String> {
+    UNPRICED_MODELS
+        .lock()
+        .map(|unpriced| unpriced.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Reported in [`unpriced_models`] for turns that don't record a model
+pub const UNKNOWN_MODEL: &str = "unknown";
+
+/// Remember a model that had no pricing entry
+fn note_unpriced(model: &str) {
+    if let Ok(mut unpriced) = UNPRICED_MODELS.lock() {
+        if !unpriced.contains(model) {
+            tracing::warn!("No pricing for model {}, counting its turns at zero cost", model);
+            unpriced.insert(model.to_string());
+        }
+    }
+}
+
+/// Get the pricing currently used for cost calculations
+pub fn get_active_pricing() -> Vec<ModelPricing> {
+    ACTIVE_PRICING
+        .read()
+        .map(|pricing| pricing.clone())
+        .unwrap_or_else(|_| get_default_pricing())
+}
+
+/// The UTC date (YYYY-MM-DD) of a timestamp or date, for picking rates
+fn pricing_date(at: &str) -> Option<&str> {
+    at.get(..10)
+        .filter(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+}
+
+/// The entries of `pricing` in effect on `date` (YYYY-MM-DD), one per model
+/// and provider
+///
+/// Before the first start date of a model, its earliest rates apply.
+pub fn pricing_in_effect(pricing: &[ModelPricing], date: &str) -> Vec<ModelPricing> {
+    (0..pricing.len())
+        .filter(|&index| is_in_effect(pricing, index, date))
+        .map(|index| pricing[index].clone())
+        .collect()
+}
+
+/// Whether `pricing[index]` is the entry of its model and provider in
+/// effect on `date`: the latest start on or before the date, or else the
+/// earliest start. Of two entries with the same start, the first counts.
+fn is_in_effect(pricing: &[ModelPricing], index: usize, date: &str) -> bool {
+    let entry = &pricing[index];
+    let start = entry.effective_from.as_deref().unwrap_or_default();
+    !pricing.iter().enumerate().any(|(other_index, other)| {
+        if other_index == index || other.model_id != entry.model_id || other.provider != entry.provider {
+            return false;
+        }
+        let other_start = other.effective_from.as_deref().unwrap_or_default();
+        match (other_start <= date, start <= date) {
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => other_start > start || (other_start == start && other_index < index),
+            (false, false) => other_start < start || (other_start == start && other_index < index),
+        }
+    })
+}
+
+/// Today's UTC date, for rates in effect now
+fn today() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
+
 /// Find pricing for a model by ID
 /// Supports exact matches, partial matches, and common aliases
 pub fn find_pricing(model_id: &str) -> Option<ModelPricing> {
+    find_provider_pricing(model_id, CostProvider::from_model(model_id))
+}
+
+/// Find pricing for a model billed through a provider
+///
+/// Looks in the provider's own entries first; Bedrock and Vertex list the
+/// same rates as the Anthropic API, so models without an entry there fall
+/// back to the Anthropic price.
+pub fn find_provider_pricing(model_id: &str, provider: CostProvider) -> Option<ModelPricing> {
+    find_provider_pricing_at(model_id, provider, None)
+}
+
+/// Find the pricing of a model in effect at `at` (an RFC 3339 timestamp or
+/// a date); today's if None or not a date
+///
+/// Runs for every turn priced, so the entry is picked from the active
+/// pricing under the read lock and only that entry is cloned.
+pub fn find_provider_pricing_at(model_id: &str, provider: CostProvider, at: Option<&str>) -> Option<ModelPricing> {
+    let today_date;
+    let date = match at.and_then(pricing_date) {
+        Some(date) => date,
+        None => {
+            today_date = today();
+            &today_date
+        }
+    };
+    match ACTIVE_PRICING.read() {
+        Ok(pricing) => find_provider_pricing_in(&pricing, model_id, provider, date).cloned(),
+        Err(_) => find_provider_pricing_in(&get_default_pricing(), model_id, provider, date).cloned(),
+    }
+}
+
+/// Find the entry of `pricing` in effect on `date` for a model billed
+/// through a provider
+fn find_provider_pricing_in<'a>(
+    pricing: &'a [ModelPricing],
+    model_id: &str,
+    provider: CostProvider,
+    date: &str,
+) -> Option<&'a ModelPricing> {
+    let model_id = normalize_model_id(model_id);
+    let find_for = |provider: CostProvider| {
+        find_pricing_matching(pricing, &model_id, |index, p| {
+            p.provider == provider && is_in_effect(pricing, index, date)
+        })
+    };
+
+    if provider != CostProvider::Anthropic {
+        if let Some(p) = find_for(provider) {
+            return Some(p);
+        }
+    }
+    find_for(CostProvider::Anthropic)
+}
+
+/// Find pricing for a model among the entries of `pricing` that `include`
+/// accepts (given their index)
+///
+/// Supports exact matches, partial matches, and common aliases.
+fn find_pricing_matching<'a>(
+    pricing: &'a [ModelPricing],
+    model_id: &str,
+    include: impl Fn(usize, &ModelPricing) -> bool,
+) -> Option<&'a ModelPricing> {
+    let candidates = || pricing.iter().enumerate().filter(|(index, p)| include(*index, p)).map(|(_, p)| p);
     let model_lower = model_id.to_lowercase();
 
     // First try exact match
-    if let Some(pricing) = get_default_pricing()
-        .into_iter()
-        .find(|p| p.model_id == model_id)
-    {
-        return Some(pricing);
+    if let Some(p) = candidates().find(|p| p.model_id == model_id) {
+        return Some(p);
     }
 
     // Try partial match (model_id contains the pricing model_id)
-    if let Some(pricing) = get_default_pricing()
-        .into_iter()
-        .find(|p| model_id.contains(&p.model_id))
-    {
-        return Some(pricing);
+    if let Some(p) = candidates().find(|p| model_id.contains(&p.model_id)) {
+        return Some(p);
     }
 
     // Try alias matching
-    if model_lower.contains("opus") {
-        return get_default_pricing().into_iter().find(|p| p.model_id.contains("opus"));
-    }
-    if model_lower.contains("sonnet") {
-        return get_default_pricing().into_iter().find(|p| p.model_id.contains("sonnet"));
-    }
-    if model_lower.contains("haiku") {
-        return get_default_pricing().into_iter().find(|p| p.model_id.contains("haiku"));
+    for alias in ["opus", "sonnet", "haiku"] {
+        if model_lower.contains(alias) {
+            return candidates().find(|p| p.model_id.contains(alias));
+        }
     }
 
     None
 }
 
-/// Get default pricing (Opus) as fallback
-pub fn get_default_pricing_fallback() -> ModelPricing {
-    get_default_pricing()
-        .into_iter()
-        .find(|p| p.model_id.contains("opus"))
-        .unwrap()
-}
-
 /// Session-level cost aggregation
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SessionCost {
@@ -230,20 +507,41 @@ impl SessionCost {
     }
 }
 
-/// Calculate cost for a single turn
-pub fn calculate_turn_cost(tokens: &TurnTokens, model: &str) -> CostBreakdown {
-    let pricing = find_pricing(model).unwrap_or_else(get_default_pricing_fallback);
-    CostBreakdown::from_tokens(tokens, &pricing)
+/// Calculate cost for a single turn started at `at` (see
+/// [`find_provider_pricing_at`])
+pub fn calculate_turn_cost(tokens: &TurnTokens, model: &str, at: Option<&str>) -> CostBreakdown {
+    calculate_provider_turn_cost(tokens, Some(model), CostProvider::from_model(model), at)
+}
+
+/// Calculate cost for a single turn billed through a provider
+///
+/// Zero for a turn without a model or a model without pricing, which is
+/// noted in [`unpriced_models`] ([`UNKNOWN_MODEL`] when there's no model).
+pub fn calculate_provider_turn_cost(
+    tokens: &TurnTokens,
+    model: Option<&str>,
+    provider: CostProvider,
+    at: Option<&str>,
+) -> CostBreakdown {
+    match model.and_then(|model| find_provider_pricing_at(model, provider, at)) {
+        Some(pricing) => CostBreakdown::from_tokens(tokens, &pricing),
+        None => {
+            note_unpriced(model.unwrap_or(UNKNOWN_MODEL));
+            CostBreakdown::default()
+        }
+    }
 }
 
 /// Calculate session cost from multiple turns
 pub fn calculate_session_cost(turns: &[TurnTokens], model: &str) -> SessionCost {
-    let pricing = find_pricing(model).unwrap_or_else(get_default_pricing_fallback);
-
     let mut total_breakdown = CostBreakdown::default();
-    for turn in turns {
-        let turn_breakdown = CostBreakdown::from_tokens(turn, &pricing);
-        total_breakdown.add(&turn_breakdown);
+    match find_pricing(model) {
+        Some(pricing) => {
+            for turn in turns {
+                total_breakdown.add(&CostBreakdown::from_tokens(turn, &pricing));
+            }
+        }
+        None => note_unpriced(model),
     }
 
     SessionCost::from_breakdown(total_breakdown, turns.len() as u32)
@@ -253,6 +551,10 @@ pub fn calculate_session_cost(turns: &[TurnTokens], model: &str) -> SessionCost
 mod tests {
     use super::*;
 
+    fn find_pricing_in(pricing: &[ModelPricing], model_id: &str) -> Option<ModelPricing> {
+        find_pricing_matching(pricing, model_id, |_, _| true).cloned()
+    }
+
     #[test]
     fn test_calculate_cost() {
         let pricing = ModelPricing {
@@ -264,6 +566,8 @@ mod tests {
             cache_write_1h_per_million: 10.00,
             cache_read_per_million: 0.50,
             max_context_tokens: Some(200000),
+            provider: CostProvider::Anthropic,
+            effective_from: None,
         };
 
         let tokens = TurnTokens::new(1_000_000, 100_000, 0, 0, 0);
@@ -309,7 +613,7 @@ mod tests {
     #[test]
     fn test_calculate_turn_cost() {
         let tokens = TurnTokens::new(1_000_000, 100_000, 500_000, 100_000, 0);
-        let cost = calculate_turn_cost(&tokens, "claude-opus-4-5-20251101");
+        let cost = calculate_turn_cost(&tokens, "claude-opus-4-5-20251101", None);
 
         // Input: 1M * $5/M = $5.00
         // Output: 100K * $25/M = $2.50
@@ -324,15 +628,15 @@ mod tests {
         let tokens = TurnTokens::new(1_000_000, 100_000, 0, 0, 0);
 
         // Opus: 1M * $5 + 100K * $25 = $5 + $2.50 = $7.50
-        let opus_cost = calculate_turn_cost(&tokens, "claude-opus-4-5-20251101");
+        let opus_cost = calculate_turn_cost(&tokens, "claude-opus-4-5-20251101", None);
         assert!((opus_cost.total_cost - 7.5).abs() < 0.01);
 
         // Sonnet: 1M * $3 + 100K * $15 = $3 + $1.50 = $4.50
-        let sonnet_cost = calculate_turn_cost(&tokens, "claude-sonnet-4-5-20251101");
+        let sonnet_cost = calculate_turn_cost(&tokens, "claude-sonnet-4-5-20251101", None);
         assert!((sonnet_cost.total_cost - 4.5).abs() < 0.01);
 
         // Haiku: 1M * $1 + 100K * $5 = $1 + $0.50 = $1.50
-        let haiku_cost = calculate_turn_cost(&tokens, "claude-haiku-4-5-20251101");
+        let haiku_cost = calculate_turn_cost(&tokens, "claude-haiku-4-5-20251101", None);
         assert!((haiku_cost.total_cost - 1.5).abs() < 0.01);
     }
 
@@ -366,12 +670,144 @@ mod tests {
     fn test_cache_write_pricing() {
         // Test 5-minute cache write pricing
         let tokens_5m = TurnTokens::new(0, 0, 0, 1_000_000, 0);
-        let cost_5m = calculate_turn_cost(&tokens_5m, "claude-opus-4-5-20251101");
+        let cost_5m = calculate_turn_cost(&tokens_5m, "claude-opus-4-5-20251101", None);
         assert!((cost_5m.cache_write_5m_cost - 6.25).abs() < 0.01);
 
         // Test 1-hour cache write pricing
         let tokens_1h = TurnTokens::new(0, 0, 0, 0, 1_000_000);
-        let cost_1h = calculate_turn_cost(&tokens_1h, "claude-opus-4-5-20251101");
+        let cost_1h = calculate_turn_cost(&tokens_1h, "claude-opus-4-5-20251101", None);
         assert!((cost_1h.cache_write_1h_cost - 10.0).abs() < 0.01);
     }
+
+    #[test]
+    fn test_unpriced_model_costs_nothing() {
+        let tokens = TurnTokens::new(1_000_000, 100_000, 0, 0, 0);
+        let cost = calculate_turn_cost(&tokens, "llama-3-70b-unpriced-test", None);
+        assert_eq!(cost.total_cost, 0.0);
+        assert!(unpriced_models().contains(&"llama-3-70b-unpriced-test".to_string()));
+
+        let session = calculate_session_cost(std::slice::from_ref(&tokens), "llama-3-70b-unpriced-test");
+        assert_eq!(session.breakdown.total_cost, 0.0);
+        assert!(!unpriced_models().contains(&"claude-opus-4-5-20251101".to_string()));
+
+        let cost = calculate_provider_turn_cost(&tokens, None, CostProvider::Anthropic, None);
+        assert_eq!(cost.total_cost, 0.0);
+        assert!(unpriced_models().contains(&UNKNOWN_MODEL.to_string()));
+    }
+
+    #[test]
+    fn test_merge_pricing_overrides() {
+        let custom = ModelPricing {
+            model_id: "my-local-model".to_string(),
+            display_name: "Local".to_string(),
+            input_price_per_million: 0.0,
+            output_price_per_million: 0.0,
+            cache_write_5m_per_million: 0.0,
+            cache_write_1h_per_million: 0.0,
+            cache_read_per_million: 0.0,
+            max_context_tokens: None,
+            provider: CostProvider::Anthropic,
+            effective_from: None,
+        };
+        let mut cheaper_opus = find_pricing_in(&get_default_pricing(), "claude-opus-4-5-20251101").unwrap();
+        cheaper_opus.input_price_per_million = 1.0;
+
+        let defaults_len = get_default_pricing().len();
+        let merged = merge_pricing(get_default_pricing(), vec![custom, cheaper_opus]);
+        assert_eq!(merged.len(), defaults_len + 1);
+
+        let opus = find_pricing_in(&merged, "claude-opus-4-5-20251101").unwrap();
+        assert_eq!(opus.input_price_per_million, 1.0);
+
+        let local = find_pricing_in(&merged, "my-local-model").unwrap();
+        assert_eq!(local.display_name, "Local");
+    }
+
+    #[test]
+    fn test_detect_provider() {
+        let bedrock_model = "us.anthropic.claude-sonnet-4-5-20250929-v1:0";
+        assert_eq!(CostProvider::detect(Some(bedrock_model), None), CostProvider::Bedrock);
+        assert_eq!(CostProvider::from_model("claude-opus-4-5@20251101"), CostProvider::Vertex);
+        assert_eq!(CostProvider::from_model("claude-opus-4-5-20251101"), CostProvider::Anthropic);
+        // The message ID wins over the model ID
+        assert_eq!(
+            CostProvider::detect(Some("claude-opus-4-5-20251101"), Some("msg_vrtx_01AbC")),
+            CostProvider::Vertex
+        );
+        assert_eq!(CostProvider::detect(None, Some("msg_bdrk_01AbC")), CostProvider::Bedrock);
+
+        assert_eq!(normalize_model_id(bedrock_model), "claude-sonnet-4-5-20250929");
+        assert_eq!(normalize_model_id("anthropic.claude-opus-4-5-20251101-v1:0"), "claude-opus-4-5-20251101");
+        assert_eq!(normalize_model_id("claude-opus-4-5@20251101"), "claude-opus-4-5-20251101");
+        assert_eq!(normalize_model_id("my-model-v2"), "my-model-v2");
+    }
+
+    #[test]
+    fn test_provider_pricing() {
+        let mut bedrock_opus = find_pricing_in(&get_default_pricing(), "claude-opus-4-5-20251101").unwrap();
+        bedrock_opus.provider = CostProvider::Bedrock;
+        bedrock_opus.input_price_per_million = 5.50;
+        let pricing = merge_pricing(get_default_pricing(), vec![bedrock_opus]);
+        assert_eq!(pricing.len(), get_default_pricing().len() + 1);
+
+        let bedrock = "global.anthropic.claude-opus-4-5-20251101-v1:0";
+        let opus = find_provider_pricing_in(&pricing, bedrock, CostProvider::Bedrock, "2026-03-01").unwrap();
+        assert_eq!(opus.input_price_per_million, 5.50);
+
+        // The Anthropic API price is untouched
+        let direct =
+            find_provider_pricing_in(&pricing, "claude-opus-4-5-20251101", CostProvider::Anthropic, "2026-03-01")
+                .unwrap();
+        assert_eq!(direct.input_price_per_million, 5.00);
+
+        // No Bedrock entry for Sonnet: the Anthropic price applies
+        let sonnet = "anthropic.claude-sonnet-4-5-20251101-v1:0";
+        let sonnet = find_provider_pricing_in(&pricing, sonnet, CostProvider::Bedrock, "2026-03-01").unwrap();
+        assert_eq!(sonnet.provider, CostProvider::Anthropic);
+        assert_eq!(sonnet.input_price_per_million, 3.00);
+
+        let vertex =
+            find_provider_pricing_in(&pricing, "claude-haiku-4-5@20251101", CostProvider::Vertex, "2026-03-01")
+                .unwrap();
+        assert_eq!(vertex.model_id, "claude-haiku-4-5-20251101");
+    }
+
+    #[test]
+    fn test_pricing_in_effect() {
+        let opus = find_pricing_in(&get_default_pricing(), "claude-opus-4-5-20251101").unwrap();
+        let dated = |from: &str, input: f64| ModelPricing {
+            effective_from: Some(from.to_string()),
+            input_price_per_million: input,
+            ..opus.clone()
+        };
+        let pricing = merge_pricing(
+            get_default_pricing(),
+            vec![dated("2026-06-01", 4.0), dated("2026-09-01", 3.0)],
+        );
+        assert_eq!(pricing.len(), get_default_pricing().len() + 2);
+
+        let input_on = |date: &str| {
+            let in_effect = pricing_in_effect(&pricing, date);
+            assert_eq!(in_effect.len(), get_default_pricing().len());
+            find_pricing_in(&in_effect, "claude-opus-4-5-20251101").unwrap().input_price_per_million
+        };
+        assert_eq!(input_on("2026-05-31"), 5.0);
+        assert_eq!(input_on("2026-06-01"), 4.0);
+        assert_eq!(input_on("2026-12-24"), 3.0);
+        let input_at = |date: &str| {
+            find_provider_pricing_in(&pricing, "claude-opus-4-5-20251101", CostProvider::Anthropic, date)
+                .unwrap()
+                .input_price_per_million
+        };
+        assert_eq!(input_at("2026-05-31"), 5.0);
+        assert_eq!(input_at("2026-12-24"), 3.0);
+
+        // Without an undated entry, the earliest rates cover the time before
+        let later_only: Vec<ModelPricing> = pricing.into_iter().filter(|p| p.effective_from.is_some()).collect();
+        let in_effect = pricing_in_effect(&later_only, "2025-01-01");
+        assert_eq!(in_effect[0].input_price_per_million, 4.0);
+
+        assert_eq!(pricing_date("2026-06-01T09:30:00Z"), Some("2026-06-01"));
+        assert_eq!(pricing_date(""), None);
+    }
 }
@@ -0,0 +1,300 @@
+//! Deliverable unit estimation
+//!
+//! Cost per Deliverable Unit (CPDU) and Cycles per Deliverable (CPD) divide
+//! by an estimate of how much a session produced. Three estimators are
+//! available:
+//! - output tokens: one unit per 5,000 output tokens
+//! - tool usage: tool calls plus turns with meaningful output
+//! - code changes: files created and edited, lines changed and test files
+//!   written, read from the Write, Edit, MultiEdit and NotebookEdit inputs
+//!
+//! The code change estimator falls back to tool usage for sessions that
+//! changed no files (research, reviews) or whose tool inputs weren't kept
+//! (privacy mode).
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::session::{estimate_deliverable_units, estimate_deliverable_units_v2};
+use crate::parser::CompletedTurn;
+
+/// Units per file created, per file edited, per line changed and per test
+/// file written
+const CREATED_FILE_UNITS: f64 = 1.0;
+const EDITED_FILE_UNITS: f64 = 0.5;
+const LINE_UNITS: f64 = 0.01;
+const TEST_FILE_UNITS: f64 = 0.5;
+
+/// How deliverable units are estimated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DeliverableEstimator {
+    /// v1: output tokens only
+    OutputTokens,
+    /// v2: tool calls and turns with meaningful output
+    ToolUsage,
+    /// v3: files and lines changed
+    #[default]
+    CodeChanges,
+}
+
+/// Code written in a session, from its file tool inputs
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CodeChangeStats {
+    /// Files first written in the session
+    pub files_created: u32,
+    /// Existing files changed in the session
+    pub files_edited: u32,
+    pub lines_added: u32,
+    pub lines_removed: u32,
+    /// Created or edited files that look like tests
+    pub test_files: u32,
+}
+
+impl CodeChangeStats {
+    pub fn is_empty(&self) -> bool {
+        self.files_created == 0 && self.files_edited == 0
+    }
+}
+
+/// A session's deliverable units and the estimator that produced them
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DeliverableEstimate {
+    pub units: f64,
+    pub estimator: DeliverableEstimator,
+}
+
+/// Tally the files and lines changed by a session's tool calls
+pub fn code_change_stats(turns: &[CompletedTurn]) -> CodeChangeStats {
+    let mut stats = CodeChangeStats::default();
+    // File path -> created in this session
+    let mut files: HashMap<&str, bool> = HashMap::new();
+
+    for tool in turns.iter().flat_map(|t| &t.tool_uses) {
+        let input = match &tool.input {
+            Some(input) => input,
+            None => continue,
+        };
+        let path_key = match tool.name.as_str() {
+            "NotebookEdit" => "notebook_path",
+            _ => "file_path",
+        };
+        let path = match input.get(path_key).and_then(Value::as_str) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        let (added, removed) = match tool.name.as_str() {
+            "Write" => (line_count(str_field(input, "content")), 0),
+            "Edit" => line_diff(str_field(input, "old_string"), str_field(input, "new_string")),
+            "MultiEdit" => input
+                .get("edits")
+                .and_then(Value::as_array)
+                .map(|edits| {
+                    edits.iter().fold((0, 0), |(added, removed), edit| {
+                        let (a, r) = line_diff(str_field(edit, "old_string"), str_field(edit, "new_string"));
+                        (added + a, removed + r)
+                    })
+                })
+                .unwrap_or_default(),
+            "NotebookEdit" => (line_count(str_field(input, "new_source")), 0),
+            _ => continue,
+        };
+        stats.lines_added += added;
+        stats.lines_removed += removed;
+        files.entry(path).or_insert(tool.name == "Write");
+    }
+
+    for (path, created) in files {
+        match created {
+            true => stats.files_created += 1,
+            false => stats.files_edited += 1,
+        }
+        if is_test_file(path) {
+            stats.test_files += 1;
+        }
+    }
+    stats
+}
+
+/// Deliverable units from code changes, or None if no files were changed
+pub fn estimate_deliverable_units_v3(stats: &CodeChangeStats) -> Option<f64> {
+    if stats.is_empty() {
+        return None;
+    }
+    let units = stats.files_created as f64 * CREATED_FILE_UNITS
+        + stats.files_edited as f64 * EDITED_FILE_UNITS
+        + (stats.lines_added + stats.lines_removed) as f64 * LINE_UNITS
+        + stats.test_files as f64 * TEST_FILE_UNITS;
+    Some(units.max(1.0))
+}
+
+/// Estimate a session's deliverable units with the given estimator
+pub fn estimate_deliverables(
+    estimator: DeliverableEstimator,
+    turns: &[CompletedTurn],
+) -> DeliverableEstimate {
+    let tool_usage = || DeliverableEstimate {
+        units: {
+            let turn_data: Vec<(u64, u32)> = turns.iter().map(|t| (t.output_tokens, t.tool_count)).collect();
+            let tool_count = turns.iter().map(|t| t.tool_count).sum();
+            estimate_deliverable_units_v2(tool_count, &turn_data)
+        },
+        estimator: DeliverableEstimator::ToolUsage,
+    };
+
+    match estimator {
+        DeliverableEstimator::OutputTokens => DeliverableEstimate {
+            units: estimate_deliverable_units(turns.iter().map(|t| t.output_tokens).sum()),
+            estimator,
+        },
+        DeliverableEstimator::ToolUsage => tool_usage(),
+        DeliverableEstimator::CodeChanges => match estimate_deliverable_units_v3(&code_change_stats(turns)) {
+            Some(units) => DeliverableEstimate { units, estimator },
+            None => tool_usage(),
+        },
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+fn line_count(text: &str) -> u32 {
+    text.lines().count() as u32
+}
+
+/// Lines added and removed by replacing `old` with `new`, ignoring order
+fn line_diff(old: &str, new: &str) -> (u32, u32) {
+    let mut remaining: HashMap<&str, u32> = HashMap::new();
+    for line in old.lines() {
+        *remaining.entry(line).or_insert(0) += 1;
+    }
+    let mut added = 0;
+    for line in new.lines() {
+        match remaining.get_mut(line) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => added += 1,
+        }
+    }
+    (added, remaining.values().sum())
+}
+
+/// Whether a path looks like a test file in the common layouts
+fn is_test_file(path: &str) -> bool {
+    let path = path.replace('\\', "/").to_lowercase();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let dirs: HashSet<&str> = path.split('/').collect();
+    dirs.contains("tests")
+        || dirs.contains("test")
+        || dirs.contains("__tests__")
+        || name.starts_with("test_")
+        || name.contains("_test.")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ToolUse;
+    use serde_json::json;
+
+    fn turn(tools: Vec<(&str, Value)>) -> CompletedTurn {
+        let tool_uses: Vec<ToolUse> = tools
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, input))| ToolUse {
+                id: format!("toolu_{}", i),
+                name: name.to_string(),
+                input: Some(input),
+                result: None,
+                is_error: false,
+            })
+            .collect();
+        CompletedTurn {
+            turn_number: 1,
+            started_at: "2026-02-05T10:00:00Z".to_string(),
+            ended_at: None,
+            duration_ms: None,
+            user_message: None,
+            assistant_message: None,
+            model: None,
+            stop_reason: None,
+            provider: Default::default(),
+            input_tokens: 0,
+            output_tokens: 2_000,
+            cache_read_tokens: 0,
+            cache_write_5m_tokens: 0,
+            cache_write_1h_tokens: 0,
+            total_tokens: 0,
+            total_context: 0,
+            tool_count: tool_uses.len() as u32,
+            tool_uses,
+            has_subagents: false,
+            subagent_ids: Vec::new(),
+            start_uuid: None,
+            end_uuid: None,
+            entry_count: 0,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_tokens: 0,
+            thinking_level: None,
+        }
+    }
+
+    #[test]
+    fn test_code_change_stats() {
+        let turns = [turn(vec![
+            ("Write", json!({"file_path": "/app/src/parser.rs", "content": "a\nb\nc\n"})),
+            ("Edit", json!({"file_path": "/app/src/parser.rs", "old_string": "b", "new_string": "b\nd"})),
+            ("Edit", json!({"file_path": "/app/src/lib.rs", "old_string": "x\ny", "new_string": "z"})),
+            (
+                "MultiEdit",
+                json!({"file_path": "/app/tests/parser_test.rs", "edits": [{"old_string": "", "new_string": "t1\nt2"}]}),
+            ),
+            ("Read", json!({"file_path": "/app/README.md"})),
+        ])];
+
+        let stats = code_change_stats(&turns);
+        assert_eq!(
+            stats,
+            CodeChangeStats {
+                files_created: 1,
+                files_edited: 2,
+                lines_added: 3 + 1 + 1 + 2,
+                lines_removed: 2,
+                test_files: 1,
+            }
+        );
+        let units = estimate_deliverable_units_v3(&stats).unwrap();
+        assert!((units - (1.0 + 1.0 + 0.09 + 0.5)).abs() < 1e-9);
+
+        let estimate = estimate_deliverables(DeliverableEstimator::CodeChanges, &turns);
+        assert_eq!(estimate.estimator, DeliverableEstimator::CodeChanges);
+    }
+
+    #[test]
+    fn test_estimator_fallback() {
+        // No files changed: the code change estimator falls back to tool usage
+        let turns = [turn(vec![("Grep", json!({"pattern": "fn main"})), ("Read", json!({"file_path": "/a.rs"}))])];
+        let estimate = estimate_deliverables(DeliverableEstimator::CodeChanges, &turns);
+        assert_eq!(estimate.estimator, DeliverableEstimator::ToolUsage);
+        assert!((estimate.units - 1.3).abs() < 1e-9);
+
+        let estimate = estimate_deliverables(DeliverableEstimator::OutputTokens, &turns);
+        assert!((estimate.units - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_is_test_file() {
+        assert!(is_test_file("/app/tests/integration.rs"));
+        assert!(is_test_file("src/__tests__/App.tsx"));
+        assert!(is_test_file("src/app.spec.ts"));
+        assert!(is_test_file("pkg/test_utils.py"));
+        assert!(!is_test_file("/app/src/contest.rs"));
+    }
+}
@@ -0,0 +1,200 @@
+//! Rolling efficiency across a long session
+//!
+//! A session that runs long keeps resending an ever larger context, so each
+//! turn costs more than the one before even when the work done per turn
+//! doesn't change. Averaging cost per turn and CER over a sliding window of
+//! turns smooths out single expensive turns, and comparing the window with
+//! the session's first full window shows where it degraded: the turn from
+//! which starting a fresh session would have been cheaper.
+
+use serde::Serialize;
+
+use crate::metrics::cost::calculate_provider_turn_cost;
+use crate::metrics::tokens::TurnTokens;
+use crate::parser::CompletedTurn;
+
+/// Turns averaged over when no window is given
+pub const DEFAULT_WINDOW: usize = 5;
+
+/// Rise in rolling cost per turn over the first window that counts as degraded
+const DEGRADATION_FACTOR: f64 = 2.0;
+
+/// One turn with the averages of the window ending at it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct EfficiencyCurvePoint {
+    pub turn_number: u32,
+    pub started_at: String,
+    /// Context sent with the turn, in tokens
+    pub context_tokens: u64,
+    pub cost: f64,
+    /// Average cost of the turns in the window
+    pub rolling_cost_per_turn: f64,
+    /// CER of the turns in the window; None when none of them used the cache
+    pub rolling_cer: Option<f64>,
+    /// Turns in the window; fewer than the window size at the session start
+    pub window_turns: u32,
+}
+
+/// Rolling cost per turn and CER of a session, and where it degraded
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SessionEfficiencyCurve {
+    pub session_id: String,
+    pub window: u32,
+    pub points: Vec<EfficiencyCurvePoint>,
+    /// Rolling cost per turn of the first full window; None for sessions
+    /// shorter than the window
+    pub baseline_cost_per_turn: Option<f64>,
+    /// First turn whose rolling cost per turn reached twice the baseline
+    pub degraded_at_turn: Option<u32>,
+    /// Cost of the turns from `degraded_at_turn` on
+    pub cost_since_degraded: f64,
+}
+
+/// Compute a session's efficiency curve over windows of `window` turns
+pub fn efficiency_curve(session_id: &str, turns: &[CompletedTurn], window: usize) -> SessionEfficiencyCurve {
+    let window = window.max(1);
+    let mut curve = SessionEfficiencyCurve {
+        session_id: session_id.to_string(),
+        window: window as u32,
+        ..Default::default()
+    };
+    let costs: Vec<f64> = turns.iter().map(turn_cost).collect();
+
+    for (i, turn) in turns.iter().enumerate() {
+        let start = (i + 1).saturating_sub(window);
+        let in_window = &turns[start..=i];
+        let window_cost: f64 = costs[start..=i].iter().sum();
+        let read: u64 = in_window.iter().map(|t| t.cache_read_tokens).sum();
+        let write: u64 = in_window
+            .iter()
+            .map(|t| t.cache_write_5m_tokens + t.cache_write_1h_tokens)
+            .sum();
+
+        let point = EfficiencyCurvePoint {
+            turn_number: turn.turn_number,
+            started_at: turn.started_at.clone(),
+            context_tokens: turn.total_context,
+            cost: costs[i],
+            rolling_cost_per_turn: window_cost / in_window.len() as f64,
+            rolling_cer: match read + write {
+                0 => None,
+                total => Some(read as f64 / total as f64),
+            },
+            window_turns: in_window.len() as u32,
+        };
+
+        if i + 1 == window {
+            curve.baseline_cost_per_turn = Some(point.rolling_cost_per_turn);
+        } else if let Some(baseline) = curve.baseline_cost_per_turn {
+            if curve.degraded_at_turn.is_none()
+                && baseline > 0.0
+                && point.rolling_cost_per_turn >= baseline * DEGRADATION_FACTOR
+            {
+                curve.degraded_at_turn = Some(turn.turn_number);
+            }
+        }
+        if curve.degraded_at_turn.is_some() {
+            curve.cost_since_degraded += costs[i];
+        }
+        curve.points.push(point);
+    }
+
+    curve
+}
+
+fn turn_cost(turn: &CompletedTurn) -> f64 {
+    let tokens = TurnTokens::new(
+        turn.input_tokens,
+        turn.output_tokens,
+        turn.cache_read_tokens,
+        turn.cache_write_5m_tokens,
+        turn.cache_write_1h_tokens,
+    );
+    let model = turn.model.as_deref();
+    calculate_provider_turn_cost(&tokens, model, turn.provider, Some(&turn.started_at)).total_cost
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(n: u32, read: u64, write: u64) -> CompletedTurn {
+        CompletedTurn {
+            turn_number: n,
+            started_at: format!("2026-02-05T10:{:02}:00Z", n),
+            ended_at: None,
+            duration_ms: None,
+            user_message: None,
+            assistant_message: None,
+            model: Some("claude-opus-4-5-20251101".to_string()),
+            stop_reason: None,
+            provider: Default::default(),
+            input_tokens: 100,
+            output_tokens: 500,
+            cache_read_tokens: read,
+            cache_write_5m_tokens: write,
+            cache_write_1h_tokens: 0,
+            total_tokens: 0,
+            total_context: read + write + 100,
+            tool_uses: Vec::new(),
+            tool_count: 0,
+            has_subagents: false,
+            subagent_ids: Vec::new(),
+            start_uuid: None,
+            end_uuid: None,
+            entry_count: 0,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_tokens: 0,
+            thinking_level: None,
+        }
+    }
+
+    #[test]
+    fn test_efficiency_curve() {
+        // Context grows slowly, then balloons from turn 7
+        let turns: Vec<CompletedTurn> = (1..=10)
+            .map(|n| match n {
+                1..=6 => turn(n, 20_000 + n as u64 * 1_000, 2_000),
+                _ => turn(n, 150_000 + n as u64 * 20_000, 10_000),
+            })
+            .collect();
+
+        let curve = efficiency_curve("s1", &turns, 3);
+        assert_eq!(curve.window, 3);
+        assert_eq!(curve.points.len(), 10);
+        assert_eq!(curve.points[0].window_turns, 1);
+        assert_eq!(curve.points[5].window_turns, 3);
+        assert_eq!(curve.points[9].context_tokens, 360_100);
+
+        let first = &curve.points[0];
+        assert!((first.rolling_cost_per_turn - first.cost).abs() < 1e-12);
+        assert_eq!(first.rolling_cer, Some(21_000.0 / 23_000.0));
+        let third = &curve.points[2];
+        let average = curve.points[..3].iter().map(|p| p.cost).sum::<f64>() / 3.0;
+        assert!((third.rolling_cost_per_turn - average).abs() < 1e-12);
+        assert_eq!(curve.baseline_cost_per_turn, Some(third.rolling_cost_per_turn));
+
+        assert_eq!(curve.degraded_at_turn, Some(7));
+        let since: f64 = curve.points[6..].iter().map(|p| p.cost).sum();
+        assert!((curve.cost_since_degraded - since).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_efficiency_curve_short_or_steady() {
+        let steady: Vec<CompletedTurn> = (1..=8).map(|n| turn(n, 50_000, 1_000)).collect();
+        let curve = efficiency_curve("s1", &steady, DEFAULT_WINDOW);
+        assert!(curve.baseline_cost_per_turn.is_some());
+        assert_eq!(curve.degraded_at_turn, None);
+        assert_eq!(curve.cost_since_degraded, 0.0);
+
+        let short = efficiency_curve("s1", &steady[..2], DEFAULT_WINDOW);
+        assert_eq!(short.points.len(), 2);
+        assert_eq!(short.baseline_cost_per_turn, None);
+
+        // A zero window is treated as one turn
+        assert_eq!(efficiency_curve("s1", &steady, 0).window, 1);
+    }
+}
@@ -0,0 +1,181 @@
+//! Session health score
+//!
+//! Folds the efficiency score, detected anti-patterns, tool failures and
+//! file rework into one 0-100 number. Each component is scored 0-100 on its
+//! own (higher is healthier) and the health score is their weighted mean.
+//! The weights come from settings so users can decide what matters to them.
+
+use serde::{Deserialize, Serialize};
+
+/// Points taken off the anti-pattern component per point of pattern severity
+/// score (a warning just past its threshold scores 2, so costs 10 points)
+const ANTIPATTERN_PENALTY_PER_SEVERITY_POINT: f64 = 5.0;
+
+/// Relative weights of the health score components
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct HealthWeights {
+    pub efficiency: f64,
+    pub antipatterns: f64,
+    pub tool_failures: f64,
+    pub rework: f64,
+}
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        Self {
+            efficiency: 0.4,
+            antipatterns: 0.2,
+            tool_failures: 0.2,
+            rework: 0.2,
+        }
+    }
+}
+
+impl HealthWeights {
+    /// Whether the weights can be used: none negative or non-finite, and at
+    /// least one above zero
+    pub fn is_valid(&self) -> bool {
+        let weights = [self.efficiency, self.antipatterns, self.tool_failures, self.rework];
+        weights.iter().all(|w| w.is_finite() && *w >= 0.0) && weights.iter().any(|w| *w > 0.0)
+    }
+}
+
+/// Session measurements the health score is built from
+#[derive(Debug, Clone, Default)]
+pub struct HealthInput {
+    /// Overall Efficiency Score (0.0 - 1.0)
+    pub oes: f64,
+    pub antipattern_count: u32,
+    /// Sum of the detected patterns' severity scores
+    pub antipattern_severity: f64,
+    pub tool_uses: u32,
+    pub tool_failures: u32,
+    /// Share of file edits that revisited a file changed in an earlier turn
+    pub rework_ratio: f64,
+}
+
+/// One component of the health score
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct HealthComponent {
+    /// "efficiency", "antipatterns", "tool_failures" or "rework"
+    pub name: String,
+    pub label: String,
+    /// The measurement: OES, pattern count, failure rate or rework ratio
+    pub value: f64,
+    /// Component score, 0-100
+    pub score: f64,
+    /// Share of the health score, after normalizing the weights
+    pub weight: f64,
+}
+
+/// Composite health of a session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct HealthScore {
+    /// Weighted mean of the component scores, 0-100
+    pub score: f64,
+    pub grade: String,
+    pub components: Vec<HealthComponent>,
+}
+
+/// Score a session's health
+///
+/// Invalid weights fall back to the defaults.
+pub fn calculate_health(input: &HealthInput, weights: &HealthWeights) -> HealthScore {
+    let weights = if weights.is_valid() { *weights } else { HealthWeights::default() };
+    let total_weight = weights.efficiency + weights.antipatterns + weights.tool_failures + weights.rework;
+
+    let failure_rate = if input.tool_uses > 0 {
+        input.tool_failures as f64 / input.tool_uses as f64
+    } else {
+        0.0
+    };
+
+    let parts = [
+        ("efficiency", "Efficiency", input.oes, input.oes * 100.0, weights.efficiency),
+        (
+            "antipatterns",
+            "Anti-patterns",
+            input.antipattern_count as f64,
+            100.0 - input.antipattern_severity * ANTIPATTERN_PENALTY_PER_SEVERITY_POINT,
+            weights.antipatterns,
+        ),
+        ("tool_failures", "Tool failures", failure_rate, (1.0 - failure_rate) * 100.0, weights.tool_failures),
+        ("rework", "Rework", input.rework_ratio, (1.0 - input.rework_ratio) * 100.0, weights.rework),
+    ];
+
+    let components: Vec<HealthComponent> = parts
+        .into_iter()
+        .map(|(name, label, value, score, weight)| HealthComponent {
+            name: name.to_string(),
+            label: label.to_string(),
+            value,
+            score: score.clamp(0.0, 100.0),
+            weight: weight / total_weight,
+        })
+        .collect();
+
+    let score = components.iter().map(|c| c.score * c.weight).sum::<f64>();
+
+    HealthScore {
+        score,
+        grade: grade(score).to_string(),
+        components,
+    }
+}
+
+/// Letter grade of a health score
+fn grade(score: f64) -> &'static str {
+    match score {
+        s if s >= 90.0 => "A",
+        s if s >= 80.0 => "B",
+        s if s >= 70.0 => "C",
+        s if s >= 60.0 => "D",
+        _ => "F",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_health() {
+        let input = HealthInput {
+            oes: 0.8,
+            antipattern_count: 2,
+            antipattern_severity: 6.0,
+            tool_uses: 20,
+            tool_failures: 5,
+            rework_ratio: 0.1,
+        };
+
+        let health = calculate_health(&input, &HealthWeights::default());
+        let scores: Vec<(&str, f64)> = health.components.iter().map(|c| (c.name.as_str(), c.score)).collect();
+        assert_eq!(
+            scores,
+            vec![("efficiency", 80.0), ("antipatterns", 70.0), ("tool_failures", 75.0), ("rework", 90.0)]
+        );
+        // 0.4 * 80 + 0.2 * (70 + 75 + 90)
+        assert!((health.score - 79.0).abs() < 1e-9);
+        assert_eq!(health.grade, "C");
+
+        // Weights are relative
+        let efficiency_only = HealthWeights { efficiency: 2.0, antipatterns: 0.0, tool_failures: 0.0, rework: 0.0 };
+        let health = calculate_health(&input, &efficiency_only);
+        assert!((health.score - 80.0).abs() < 1e-9);
+        assert!((health.components[0].weight - 1.0).abs() < 1e-9);
+
+        // Unusable weights fall back to the defaults
+        let zero = HealthWeights { efficiency: 0.0, antipatterns: 0.0, tool_failures: 0.0, rework: 0.0 };
+        assert!(!zero.is_valid());
+        assert!((calculate_health(&input, &zero).score - 79.0).abs() < 1e-9);
+
+        // Heavy anti-patterns bottom out at zero
+        let input = HealthInput { antipattern_severity: 40.0, ..input };
+        assert_eq!(calculate_health(&input, &HealthWeights::default()).components[1].score, 0.0);
+    }
+}
@@ -0,0 +1,147 @@
+//! Hour-of-day usage aggregation
+//!
+//! Buckets turns into a day-of-week × hour-of-day grid (in the reporting
+//! timezone) for the usage heatmap.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Timelike};
+use serde::Serialize;
+
+/// Days per week (rows of the grid)
+pub const DAYS: usize = 7;
+/// Hours per day (columns of the grid)
+pub const HOURS: usize = 24;
+
+/// Usage grid indexed as `[day_of_week][hour]`
+///
+/// Days run Monday (0) to Sunday (6); hours are local time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct HourlyUsage {
+    pub tokens: Vec<Vec<u64>>,
+    pub cost: Vec<Vec<f64>>,
+    /// Distinct sessions with at least one turn in the slot
+    pub sessions: Vec<Vec<u32>>,
+    pub turns: Vec<Vec<u32>>,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub total_turns: u32,
+}
+
+impl Default for HourlyUsage {
+    fn default() -> Self {
+        Self {
+            tokens: vec![vec![0; HOURS]; DAYS],
+            cost: vec![vec![0.0; HOURS]; DAYS],
+            sessions: vec![vec![0; HOURS]; DAYS],
+            turns: vec![vec![0; HOURS]; DAYS],
+            total_tokens: 0,
+            total_cost: 0.0,
+            total_turns: 0,
+        }
+    }
+}
+
+impl HourlyUsage {
+    /// Add pre-aggregated values for one slot
+    ///
+    /// Out-of-range slots are ignored.
+    pub fn add_slot(&mut self, day: usize, hour: usize, turns: u32, sessions: u32, tokens: u64, cost: f64) {
+        if day >= DAYS || hour >= HOURS {
+            return;
+        }
+        self.turns[day][hour] += turns;
+        self.sessions[day][hour] += sessions;
+        self.tokens[day][hour] += tokens;
+        self.cost[day][hour] += cost;
+        self.total_turns += turns;
+        self.total_tokens += tokens;
+        self.total_cost += cost;
+    }
+}
+
+/// Builds an `HourlyUsage` grid one turn at a time
+#[derive(Debug, Default)]
+pub struct HourlyUsageAccumulator {
+    usage: HourlyUsage,
+    sessions: HashMap<(usize, usize), HashSet<String>>,
+}
+
+impl HourlyUsageAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a turn; returns false if its timestamp can't be parsed
+    pub fn add_turn(&mut self, session_id: &str, started_at: &str, tokens: u64, cost: f64) -> bool {
+        let (day, hour) = match local_slot(started_at) {
+            Some(slot) => slot,
+            None => return false,
+        };
+
+        let is_new_session = self
+            .sessions
+            .entry((day, hour))
+            .or_default()
+            .insert(session_id.to_string());
+        self.usage
+            .add_slot(day, hour, 1, u32::from(is_new_session), tokens, cost);
+        true
+    }
+
+    pub fn finish(self) -> HourlyUsage {
+        self.usage
+    }
+}
+
+/// Local (day of week from Monday = 0, hour) of an RFC 3339 timestamp
+pub fn local_slot(timestamp: &str) -> Option<(usize, usize)> {
+    let local = crate::timezone::local_datetime(timestamp)?;
+    Some((
+        local.weekday().num_days_from_monday() as usize,
+        local.hour() as usize,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulator_buckets_turns() {
+        let mut acc = HourlyUsageAccumulator::new();
+        assert!(acc.add_turn("s1", "2026-02-02T09:00:00Z", 100, 0.10));
+        assert!(acc.add_turn("s1", "2026-02-02T09:05:00Z", 200, 0.20));
+        assert!(acc.add_turn("s2", "2026-02-02T09:10:00Z", 300, 0.30));
+        assert!(acc.add_turn("s2", "2026-02-03T14:00:00Z", 400, 0.40));
+        assert!(!acc.add_turn("s3", "not a timestamp", 500, 0.50));
+
+        let usage = acc.finish();
+        let (day, hour) = local_slot("2026-02-02T09:00:00Z").unwrap();
+        assert_eq!(usage.turns[day][hour], 3);
+        assert_eq!(usage.sessions[day][hour], 2);
+        assert_eq!(usage.tokens[day][hour], 600);
+        assert!((usage.cost[day][hour] - 0.60).abs() < 1e-9);
+
+        assert_eq!(usage.total_turns, 4);
+        assert_eq!(usage.total_tokens, 1000);
+        assert_eq!(usage.tokens.len(), DAYS);
+        assert!(usage.tokens.iter().all(|row| row.len() == HOURS));
+    }
+
+    #[test]
+    fn test_local_slot() {
+        let expected = crate::settings::reporting_timezone()
+            .local_datetime("2026-02-04T12:00:00+00:00")
+            .unwrap();
+        assert_eq!(
+            local_slot("2026-02-04T12:00:00.000Z"),
+            Some((
+                expected.weekday().num_days_from_monday() as usize,
+                expected.hour() as usize
+            ))
+        );
+        assert_eq!(local_slot(""), None);
+    }
+}
@@ -0,0 +1,222 @@
+//! MCP server analytics
+//!
+//! Every MCP tool call is attributed to its server (see
+//! [`crate::parser::tool_names::split_mcp_tool`]). Besides calls and
+//! failures, a server is charged for the context its results take up: the
+//! API reports no usage per tool result, so result tokens are estimated from
+//! their size and priced as input tokens of the model that read them.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::metrics::tokens::CHARS_PER_TOKEN;
+use crate::parser::tool_names::split_mcp_tool;
+
+/// One stored MCP tool call
+#[derive(Debug, Clone)]
+pub struct McpCall {
+    pub session_id: String,
+    pub tool_name: String,
+    pub is_error: bool,
+    /// Characters of the tool result
+    pub result_chars: u64,
+    /// Model of the turn that made the call
+    pub model: Option<String>,
+}
+
+/// Calls of one tool of an MCP server
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct McpToolStats {
+    /// Tool name without the server prefix
+    pub tool: String,
+    pub calls: u32,
+    pub errors: u32,
+    /// Errors as a share of calls (0-1)
+    pub failure_rate: f64,
+    /// Estimated tokens of the tool's results
+    pub result_tokens: u64,
+    /// Result tokens priced as input
+    pub estimated_cost: f64,
+}
+
+/// Usage of one MCP server
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct McpServerStats {
+    pub server: String,
+    pub calls: u32,
+    pub errors: u32,
+    /// Errors as a share of calls (0-1)
+    pub failure_rate: f64,
+    /// Sessions that called the server
+    pub sessions: u32,
+    pub result_tokens: u64,
+    pub estimated_cost: f64,
+    /// Estimated cost of the results of failed calls
+    pub failed_cost: f64,
+    /// Most called first
+    pub tools: Vec<McpToolStats>,
+}
+
+/// MCP usage over a period, busiest server first
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct McpStats {
+    pub total_calls: u32,
+    pub total_errors: u32,
+    pub result_tokens: u64,
+    pub estimated_cost: f64,
+    pub servers: Vec<McpServerStats>,
+}
+
+#[derive(Default)]
+struct Tally {
+    calls: u32,
+    errors: u32,
+    result_tokens: u64,
+    cost: f64,
+    failed_cost: f64,
+}
+
+impl Tally {
+    fn add(&mut self, is_error: bool, tokens: u64, cost: f64) {
+        self.calls += 1;
+        self.result_tokens += tokens;
+        self.cost += cost;
+        if is_error {
+            self.errors += 1;
+            self.failed_cost += cost;
+        }
+    }
+
+    fn failure_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+}
+
+#[derive(Default)]
+struct ServerTally<'a> {
+    tally: Tally,
+    sessions: HashSet<&'a str>,
+    tools: HashMap<&'a str, Tally>,
+}
+
+/// Aggregate MCP calls per server and tool
+///
+/// `input_price` gives the input price per million tokens of a model (an
+/// empty name when the turn's model is unknown). Calls to tools that aren't
+/// MCP tools are ignored.
+pub fn calculate_mcp_stats(calls: &[McpCall], input_price: impl Fn(&str) -> f64) -> McpStats {
+    let mut prices: HashMap<&str, f64> = HashMap::new();
+    let mut servers: HashMap<&str, ServerTally> = HashMap::new();
+
+    for call in calls {
+        let Some((server, tool)) = split_mcp_tool(&call.tool_name) else {
+            continue;
+        };
+        let model = call.model.as_deref().unwrap_or("");
+        let price = *prices.entry(model).or_insert_with(|| input_price(model));
+        let tokens = call.result_chars / CHARS_PER_TOKEN;
+        let cost = tokens as f64 / 1_000_000.0 * price;
+
+        let entry = servers.entry(server).or_default();
+        entry.tally.add(call.is_error, tokens, cost);
+        entry.sessions.insert(&call.session_id);
+        entry.tools.entry(tool).or_default().add(call.is_error, tokens, cost);
+    }
+
+    let mut servers: Vec<McpServerStats> = servers
+        .into_iter()
+        .map(|(server, ServerTally { tally, sessions, tools })| {
+            let mut tools: Vec<McpToolStats> = tools
+                .into_iter()
+                .map(|(tool, t)| McpToolStats {
+                    tool: tool.to_string(),
+                    calls: t.calls,
+                    errors: t.errors,
+                    failure_rate: t.failure_rate(),
+                    result_tokens: t.result_tokens,
+                    estimated_cost: t.cost,
+                })
+                .collect();
+            tools.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.tool.cmp(&b.tool)));
+            McpServerStats {
+                server: server.to_string(),
+                calls: tally.calls,
+                errors: tally.errors,
+                failure_rate: tally.failure_rate(),
+                sessions: sessions.len() as u32,
+                result_tokens: tally.result_tokens,
+                estimated_cost: tally.cost,
+                failed_cost: tally.failed_cost,
+                tools,
+            }
+        })
+        .collect();
+    servers.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.server.cmp(&b.server)));
+
+    McpStats {
+        total_calls: servers.iter().map(|s| s.calls).sum(),
+        total_errors: servers.iter().map(|s| s.errors).sum(),
+        result_tokens: servers.iter().map(|s| s.result_tokens).sum(),
+        // An empty f64 sum is -0.0; no MCP calls cost plain zero
+        estimated_cost: match servers.is_empty() {
+            true => 0.0,
+            false => servers.iter().map(|s| s.estimated_cost).sum(),
+        },
+        servers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(session_id: &str, tool_name: &str, is_error: bool, result_chars: u64) -> McpCall {
+        McpCall {
+            session_id: session_id.to_string(),
+            tool_name: tool_name.to_string(),
+            is_error,
+            result_chars,
+            model: Some("claude-sonnet-4-5".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_calculate_mcp_stats() {
+        let calls = [
+            call("s1", "mcp__github__list_pulls", false, 400_000),
+            call("s1", "mcp__github__list_pulls", true, 400),
+            call("s2", "mcp__github__create_issue", false, 4_000),
+            call("s2", "MCP__linear__get_issue", true, 40),
+            call("s2", "Bash", true, 4_000),
+        ];
+        let stats = calculate_mcp_stats(&calls, |_| 3.0);
+
+        assert_eq!((stats.total_calls, stats.total_errors), (4, 2));
+        assert_eq!(stats.servers.len(), 2);
+
+        let github = &stats.servers[0];
+        assert_eq!(github.server, "github");
+        assert_eq!((github.calls, github.errors, github.sessions), (3, 1, 2));
+        assert!((github.failure_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(github.result_tokens, 101_100);
+        assert!((github.estimated_cost - 0.3033).abs() < 1e-9);
+        assert!((github.failed_cost - 0.0003).abs() < 1e-9);
+        assert_eq!(github.tools[0].tool, "list_pulls");
+        assert_eq!(github.tools[0].calls, 2);
+
+        let linear = &stats.servers[1];
+        assert_eq!((linear.server.as_str(), linear.failure_rate), ("linear", 1.0));
+
+        let empty = calculate_mcp_stats(&[], |_| 3.0);
+        assert_eq!(empty, McpStats::default());
+        assert!(empty.estimated_cost.is_sign_positive());
+    }
+}
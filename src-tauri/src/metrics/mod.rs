@@ -5,12 +5,58 @@
 //! - Cost calculations
 //! - Efficiency scoring (OES, CER, SEI)
 //! - Session-level metrics aggregation
+//! - Session-to-session comparison
+//! - Hour-of-day usage heatmap
+//! - Subagent hierarchy rollups
+//! - Context window utilization over a session
+//! - File-level rework detection
+//! - Composite session health score
+//! - Per-project efficiency benchmarks
+//! - Failed tool calls
+//! - Deliverable unit estimation from code changes
+//! - Active and idle time within a session
+//! - Concurrent sessions across projects
+//! - Per-turn cache usage and efficiency drops
+//! - Rolling usage windows against plan limits
+//! - API errors, retries and retry storms
+//! - Percentiles and histograms of session totals
+//! - Extended thinking tokens, share and cost
+//! - Languages and frameworks of a project
+//! - Cost per active hour by day and project
+//! - 5-hour billing blocks and the open block's headroom
+//! - Tool calls by tool and by MCP server
+//! - MCP server calls, failure rates and result cost
+//! - Savings simulated under model and cache changes
+//! - Rolling cost per turn and CER within a session
 
 pub mod tokens;
 pub mod cost;
 pub mod efficiency;
 pub mod session;
 pub mod developer;
+pub mod comparison;
+pub mod hourly;
+pub mod subagents;
+pub mod context;
+pub mod rework;
+pub mod health;
+pub mod benchmark;
+pub mod tool_failures;
+pub mod deliverables;
+pub mod active_time;
+pub mod concurrency;
+pub mod cache;
+pub mod usage_windows;
+pub mod api_errors;
+pub mod stats;
+pub mod thinking;
+pub mod stack;
+pub mod burn_rate;
+pub mod billing_blocks;
+pub mod tool_usage;
+pub mod mcp;
+pub mod savings;
+pub mod efficiency_curve;
 
 use thiserror::Error;
 
@@ -0,0 +1,246 @@
+//! Rework detection from file edits
+//!
+//! Builds each file's change history from the session's file tool calls. A
+//! turn that changes a file already changed in an earlier turn is a rework
+//! cycle: the earlier result had to be revisited. Repeated edits to the same
+//! file within one turn are ordinary multi-step edits and don't count.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::parser::{CompletedTurn, ToolUse};
+
+/// Weight of an extra edit within a turn, relative to revisiting the file in
+/// a later turn, in the churn score
+const IN_TURN_EDIT_WEIGHT: f64 = 0.25;
+
+/// Code change tracked during a session
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CodeChange {
+    pub file_path: String,
+    pub change_type: String,  // "create", "edit", "delete"
+    pub tool_name: String,    // "Write", "Edit", "Bash"
+    pub turn_number: u32,
+    pub timestamp: String,
+}
+
+/// How often one file of a project was changed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct FileChangeSummary {
+    pub file_path: String,
+    /// Write, Edit and NotebookEdit operations on the file
+    pub changes: u32,
+    pub sessions: u32,
+    pub last_changed_at: String,
+}
+
+/// Change history of one file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct FileChurn {
+    pub file_path: String,
+    /// Write, Edit and NotebookEdit operations on the file
+    pub edits: u32,
+    /// Turns that changed the file, in order
+    pub turns: Vec<u32>,
+    /// Edits made after the turn that first changed the file
+    pub rework_edits: u32,
+    /// Later turns that changed the file again, plus a quarter point for each
+    /// extra edit within a turn
+    pub churn_score: f64,
+}
+
+/// File-level rework in a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ReworkAnalysis {
+    pub total_edits: u32,
+    pub rework_edits: u32,
+    /// Turns that changed a file already changed in an earlier turn
+    pub rework_cycles: u32,
+    /// Files changed more than once, highest churn first
+    pub files: Vec<FileChurn>,
+}
+
+impl ReworkAnalysis {
+    /// Share of edits that went back to a file changed in an earlier turn
+    pub fn rework_ratio(&self) -> f64 {
+        if self.total_edits == 0 {
+            0.0
+        } else {
+            self.rework_edits as f64 / self.total_edits as f64
+        }
+    }
+}
+
+/// Collect file operations (Write, Edit, NotebookEdit, and Bash commands that
+/// look like they touch files) from the session's tool uses
+pub fn extract_code_changes(turns: &[CompletedTurn]) -> Vec<CodeChange> {
+    turns
+        .iter()
+        .flat_map(|turn| turn.tool_uses.iter().filter_map(move |tool| code_change(turn, tool)))
+        .collect()
+}
+
+/// File operation of one tool use, if it makes one
+pub fn code_change(turn: &CompletedTurn, tool: &ToolUse) -> Option<CodeChange> {
+    let input = tool.input.as_ref()?;
+    let change = |file_path: &str, change_type: &str, tool_name: &str| CodeChange {
+        file_path: file_path.to_string(),
+        change_type: change_type.to_string(),
+        tool_name: tool_name.to_string(),
+        turn_number: turn.turn_number,
+        timestamp: turn.started_at.clone(),
+    };
+
+    match tool.name.as_str() {
+        // Write tool creates or overwrites files
+        "Write" | "write" => {
+            let file_path = input.get("file_path")?.as_str()?;
+            Some(change(file_path, "create", "Write"))
+        }
+        // Edit tool modifies existing files
+        "Edit" | "edit" => {
+            let file_path = input.get("file_path")?.as_str()?;
+            Some(change(file_path, "edit", "Edit"))
+        }
+        // Bash tool might contain file operations
+        "Bash" | "bash" => {
+            let command = input.get("command")?.as_str()?;
+            // Check for common file operations
+            let file_ops = [
+                ("rm ", "delete"),
+                ("rm -", "delete"),
+                ("touch ", "create"),
+                ("mkdir ", "create"),
+                ("mv ", "edit"),
+                ("cp ", "create"),
+                ("echo ", "edit"),  // echo > file
+                ("cat >", "create"),
+            ];
+
+            let (_, change_type) = file_ops.iter().find(|(pattern, _)| command.contains(pattern))?;
+            // Extract file path (simplified - takes first path-like argument)
+            let file_path = command
+                .split_whitespace()
+                .skip(1)
+                .find(|p| p.starts_with('/') || p.starts_with('.') || p.contains('/'))?;
+            Some(change(file_path, change_type, "Bash"))
+        }
+        // NotebookEdit modifies Jupyter notebooks
+        "NotebookEdit" | "notebook_edit" => {
+            let notebook_path = input.get("notebook_path")?.as_str()?;
+            Some(change(notebook_path, "edit", "NotebookEdit"))
+        }
+        _ => None,
+    }
+}
+
+/// Build per-file change histories and count rework
+///
+/// Only changes made with the file editing tools are considered; paths
+/// picked out of Bash commands are too unreliable to link edits by.
+pub fn analyze_rework(changes: &[CodeChange]) -> ReworkAnalysis {
+    // file -> (turn -> edits in that turn)
+    let mut by_file: BTreeMap<&str, BTreeMap<u32, u32>> = BTreeMap::new();
+    for change in changes.iter().filter(|c| c.tool_name != "Bash") {
+        *by_file
+            .entry(change.file_path.as_str())
+            .or_default()
+            .entry(change.turn_number)
+            .or_insert(0) += 1;
+    }
+
+    let mut analysis = ReworkAnalysis::default();
+    let mut rework_turns = HashSet::new();
+
+    for (file_path, turns) in by_file {
+        let edits: u32 = turns.values().sum();
+        let first_turn_edits = turns.values().next().copied().unwrap_or(0);
+        let rework_edits = edits - first_turn_edits;
+        rework_turns.extend(turns.keys().skip(1).copied());
+
+        analysis.total_edits += edits;
+        analysis.rework_edits += rework_edits;
+
+        if edits > 1 {
+            let revisits = turns.len() as u32 - 1;
+            let in_turn_repeats = edits - turns.len() as u32;
+            analysis.files.push(FileChurn {
+                file_path: file_path.to_string(),
+                edits,
+                turns: turns.keys().copied().collect(),
+                rework_edits,
+                churn_score: revisits as f64 + in_turn_repeats as f64 * IN_TURN_EDIT_WEIGHT,
+            });
+        }
+    }
+
+    analysis.rework_cycles = rework_turns.len() as u32;
+    analysis.files.sort_by(|a, b| {
+        b.churn_score
+            .partial_cmp(&a.churn_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    analysis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(file_path: &str, tool_name: &str, turn_number: u32) -> CodeChange {
+        CodeChange {
+            file_path: file_path.to_string(),
+            change_type: "edit".to_string(),
+            tool_name: tool_name.to_string(),
+            turn_number,
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_analyze_rework() {
+        let changes = vec![
+            // Written, then edited twice in the same turn: no rework
+            change("/src/new.rs", "Write", 1),
+            change("/src/new.rs", "Edit", 1),
+            change("/src/new.rs", "Edit", 1),
+            // Revisited in turns 3 and 4
+            change("/src/lib.rs", "Edit", 2),
+            change("/src/lib.rs", "Edit", 3),
+            change("/src/lib.rs", "Edit", 4),
+            change("/src/lib.rs", "Edit", 4),
+            // Revisited in turn 4 as well, which counts once as a cycle
+            change("/src/main.rs", "Edit", 1),
+            change("/src/main.rs", "Edit", 4),
+            // Bash paths are ignored
+            change("/tmp/out.txt", "Bash", 2),
+            change("/tmp/out.txt", "Bash", 5),
+        ];
+
+        let analysis = analyze_rework(&changes);
+        assert_eq!(analysis.total_edits, 9);
+        assert_eq!(analysis.rework_edits, 4);
+        assert_eq!(analysis.rework_cycles, 2);
+        assert!((analysis.rework_ratio() - 4.0 / 9.0).abs() < 1e-9);
+
+        let files: Vec<(&str, f64)> = analysis
+            .files
+            .iter()
+            .map(|f| (f.file_path.as_str(), f.churn_score))
+            .collect();
+        assert_eq!(
+            files,
+            vec![("/src/lib.rs", 2.25), ("/src/main.rs", 1.0), ("/src/new.rs", 0.5)]
+        );
+        assert_eq!(analysis.files[0].turns, vec![2, 3, 4]);
+        assert_eq!(analysis.files[0].rework_edits, 3);
+
+        assert_eq!(analyze_rework(&[]), ReworkAnalysis::default());
+    }
+}
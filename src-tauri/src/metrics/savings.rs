@@ -0,0 +1,239 @@
+//! Cost savings simulator
+//!
+//! Reprices stored turns under hypothetical changes to show what a workflow
+//! change would have saved. Each turn's saving is the difference between its
+//! price as it happened and its price with the scenarios applied, both from
+//! the active pricing table; the projected cost is the recorded cost minus
+//! that saving, so pricing the recorded cost was computed with doesn't skew
+//! the result.
+//!
+//! Scenarios apply in order, so "switch Opus to Sonnet, then reach a CER of
+//! 0.8" prices the cache change at Sonnet rates. Cache efficiency is judged
+//! per session rather than per turn, since a session's first turns always
+//! write the cache.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::cost::{find_provider_pricing, normalize_model_id, CostProvider};
+use super::tokens::TurnTokens;
+
+/// Projects listed in a simulation, by savings
+const TOP_PROJECTS: usize = 10;
+
+/// A hypothetical change to past usage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SavingsScenario {
+    /// Turns on models matching `from` (a model ID or a family such as
+    /// "opus") billed at the rates of `to` instead
+    SwitchModel { from: String, to: String },
+    /// Sessions whose Cache Efficiency Ratio is below `target` (0-1] reach
+    /// it, with the same number of cache tokens read and written in total
+    ImproveCer { target: f64 },
+}
+
+impl SavingsScenario {
+    /// Why the scenario can't be simulated, if it can't
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            SavingsScenario::SwitchModel { from, to } => {
+                if from.trim().is_empty() {
+                    return Err("The model to switch from must not be empty".to_string());
+                }
+                if find_provider_pricing(to, CostProvider::Anthropic).is_none() {
+                    return Err(format!("No pricing for model {:?}", to));
+                }
+                Ok(())
+            }
+            SavingsScenario::ImproveCer { target } => match *target > 0.0 && *target <= 1.0 {
+                true => Ok(()),
+                false => Err(format!("CER target must be above 0 and at most 1, got {}", target)),
+            },
+        }
+    }
+}
+
+/// A stored turn as input to the simulation
+#[derive(Debug, Clone)]
+pub struct SimulatedTurn {
+    pub session_id: String,
+    pub project_path: String,
+    pub model: String,
+    pub provider: CostProvider,
+    pub tokens: TurnTokens,
+    /// Recorded cost
+    pub cost: f64,
+}
+
+/// Savings within one project
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectSavings {
+    pub project_path: String,
+    pub actual_cost: f64,
+    pub projected_cost: f64,
+    pub savings: f64,
+}
+
+/// Result of a savings simulation
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SavingsSimulation {
+    pub scenarios: Vec<SavingsScenario>,
+    /// Days of history simulated; None for all of it
+    pub days: Option<u32>,
+    pub session_count: u32,
+    /// Sessions with at least one turn a scenario changed
+    pub affected_sessions: u32,
+    pub actual_cost: f64,
+    pub projected_cost: f64,
+    /// Negative when the scenarios would have cost more
+    pub savings: f64,
+    pub savings_percent: f64,
+    /// Savings per 30 days over the simulated period
+    pub monthly_savings: Option<f64>,
+    /// Projects with the largest savings
+    pub top_projects: Vec<ProjectSavings>,
+}
+
+/// Reprice `turns` under `scenarios`
+pub fn simulate_savings(turns: &[SimulatedTurn], scenarios: &[SavingsScenario], days: Option<u32>) -> SavingsSimulation {
+    let mut sessions: HashMap<&str, Vec<&SimulatedTurn>> = HashMap::new();
+    for turn in turns {
+        sessions.entry(turn.session_id.as_str()).or_default().push(turn);
+    }
+
+    let mut projects: HashMap<&str, ProjectSavings> = HashMap::new();
+    let mut affected_sessions = 0;
+    for session in sessions.values() {
+        let mut changed: Vec<(String, TurnTokens)> =
+            session.iter().map(|t| (t.model.clone(), t.tokens.clone())).collect();
+        for scenario in scenarios {
+            apply_scenario(scenario, &mut changed);
+        }
+
+        let mut affected = false;
+        for (turn, (model, tokens)) in session.iter().zip(&changed) {
+            let savings = price(&turn.model, turn.provider, &turn.tokens) - price(model, turn.provider, tokens);
+            affected |= *model != turn.model || *tokens != turn.tokens;
+
+            let project = projects.entry(turn.project_path.as_str()).or_insert_with(|| ProjectSavings {
+                project_path: turn.project_path.clone(),
+                ..ProjectSavings::default()
+            });
+            project.actual_cost += turn.cost;
+            project.projected_cost += turn.cost - savings;
+            project.savings += savings;
+        }
+        if affected {
+            affected_sessions += 1;
+        }
+    }
+
+    let actual_cost: f64 = projects.values().map(|p| p.actual_cost).sum();
+    let savings: f64 = projects.values().map(|p| p.savings).sum();
+    let mut top_projects: Vec<ProjectSavings> = projects.into_values().collect();
+    top_projects.sort_by(|a, b| b.savings.total_cmp(&a.savings).then_with(|| a.project_path.cmp(&b.project_path)));
+    top_projects.truncate(TOP_PROJECTS);
+
+    SavingsSimulation {
+        scenarios: scenarios.to_vec(),
+        days,
+        session_count: sessions.len() as u32,
+        affected_sessions,
+        actual_cost,
+        projected_cost: actual_cost - savings,
+        savings,
+        savings_percent: if actual_cost > 0.0 { savings / actual_cost * 100.0 } else { 0.0 },
+        monthly_savings: days.filter(|d| *d > 0).map(|d| savings / d as f64 * 30.0),
+        top_projects,
+    }
+}
+
+/// Apply a scenario to the (model, tokens) of a session's turns
+fn apply_scenario(scenario: &SavingsScenario, turns: &mut [(String, TurnTokens)]) {
+    match scenario {
+        SavingsScenario::SwitchModel { from, to } => {
+            let from = from.trim().to_lowercase();
+            for (model, _) in turns.iter_mut() {
+                if normalize_model_id(model).to_lowercase().contains(&from) {
+                    *model = to.clone();
+                }
+            }
+        }
+        SavingsScenario::ImproveCer { target } => {
+            let read: u64 = turns.iter().map(|(_, t)| t.cache_read_tokens).sum();
+            let written: u64 = turns.iter().map(|(_, t)| t.cache_write_5m_tokens + t.cache_write_1h_tokens).sum();
+            let total = read + written;
+            if total == 0 || read as f64 / total as f64 >= *target {
+                return;
+            }
+            // Every turn keeps this share of its cache writes; the rest become reads
+            let kept = (1.0 - target) * total as f64 / written as f64;
+            for (_, tokens) in turns.iter_mut() {
+                let write_5m = (tokens.cache_write_5m_tokens as f64 * kept).round() as u64;
+                let write_1h = (tokens.cache_write_1h_tokens as f64 * kept).round() as u64;
+                tokens.cache_read_tokens +=
+                    tokens.cache_write_5m_tokens - write_5m + tokens.cache_write_1h_tokens - write_1h;
+                tokens.cache_write_5m_tokens = write_5m;
+                tokens.cache_write_1h_tokens = write_1h;
+            }
+        }
+    }
+}
+
+fn price(model: &str, provider: CostProvider, tokens: &TurnTokens) -> f64 {
+    find_provider_pricing(model, provider).map_or(0.0, |pricing| pricing.calculate_cost(tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(session_id: &str, project: &str, model: &str, tokens: TurnTokens) -> SimulatedTurn {
+        let cost = price(model, CostProvider::Anthropic, &tokens);
+        SimulatedTurn {
+            session_id: session_id.to_string(),
+            project_path: project.to_string(),
+            model: model.to_string(),
+            provider: CostProvider::Anthropic,
+            tokens,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_simulate_savings() {
+        let turns = vec![
+            // $5 input + $25 output at Opus rates, $3 + $15 at Sonnet rates
+            turn("s1", "/app", "claude-opus-4-5-20251101", TurnTokens::new(1_000_000, 1_000_000, 0, 0, 0)),
+            turn("s2", "/api", "claude-sonnet-4-5-20251101", TurnTokens::new(1_000_000, 0, 0, 0, 0)),
+        ];
+
+        let switch = SavingsScenario::SwitchModel { from: "opus".to_string(), to: "claude-sonnet-4-5".to_string() };
+        let result = simulate_savings(&turns, std::slice::from_ref(&switch), Some(15));
+        assert_eq!(result.session_count, 2);
+        assert_eq!(result.affected_sessions, 1);
+        assert!((result.actual_cost - 33.0).abs() < 1e-9);
+        assert!((result.savings - 12.0).abs() < 1e-9);
+        assert!((result.projected_cost - 21.0).abs() < 1e-9);
+        assert!((result.monthly_savings.unwrap() - 24.0).abs() < 1e-9);
+        assert_eq!(result.top_projects[0].project_path, "/app");
+
+        // 1M written and none read reaches CER 0.5: half the writes are reads
+        let cached = vec![turn("s3", "/app", "claude-opus-4-5-20251101", TurnTokens::new(0, 0, 0, 1_000_000, 0))];
+        let result = simulate_savings(&cached, &[SavingsScenario::ImproveCer { target: 0.5 }], None);
+        assert!((result.savings - (6.25 - 3.125 - 0.25)).abs() < 1e-9);
+        assert_eq!(result.monthly_savings, None);
+
+        // Scenarios apply in order: the cache change is priced at Sonnet rates
+        let result = simulate_savings(&cached, &[switch, SavingsScenario::ImproveCer { target: 0.5 }], None);
+        assert!((result.projected_cost - (1.875 + 0.15)).abs() < 1e-9);
+
+        assert!(SavingsScenario::ImproveCer { target: 1.5 }.validate().is_err());
+        assert!(SavingsScenario::SwitchModel { from: "opus".to_string(), to: "gpt".to_string() }.validate().is_err());
+    }
+}
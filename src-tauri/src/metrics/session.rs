@@ -6,7 +6,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-use super::cost::{find_pricing, CostBreakdown};
+use super::cost::CostBreakdown;
 use super::efficiency::{calculate_cer, calculate_oes, calculate_sei_f64, normalize_cpd, normalize_cpdu, normalize_sei, EfficiencyScore};
 use super::tokens::{SessionTokens, TurnTokens};
 
@@ -288,22 +288,15 @@ pub fn aggregate_session_tokens(turns: &[TurnTokens]) -> SessionTokens {
 
 /// Calculate turn cost using model pricing
 pub fn calculate_turn_cost(tokens: &TurnTokens, model: &str) -> CostBreakdown {
-    let pricing = find_pricing(model)
-        .unwrap_or_else(|| find_pricing("claude-opus-4-5-20251101").unwrap());
-
-    CostBreakdown::from_tokens(tokens, &pricing)
+    super::cost::calculate_turn_cost(tokens, model, None)
 }
 
 /// Calculate session cost from multiple turns
 pub fn calculate_session_cost(turns: &[TurnTokens], model: &str) -> SessionCost {
-    let pricing = find_pricing(model)
-        .unwrap_or_else(|| find_pricing("claude-opus-4-5-20251101").unwrap());
-
     let mut total_breakdown = CostBreakdown::default();
 
     for turn in turns {
-        let turn_breakdown = CostBreakdown::from_tokens(turn, &pricing);
-        total_breakdown.add(&turn_breakdown);
+        total_breakdown.add(&calculate_turn_cost(turn, model));
     }
 
     SessionCost::from_breakdown(&total_breakdown, turns.len() as u32)
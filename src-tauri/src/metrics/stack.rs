@@ -0,0 +1,279 @@
+//! Language and framework detection per project
+//!
+//! A project's languages come from the extensions of the files its sessions
+//! changed, keeping those that make up a meaningful share of the changes, and
+//! from the manifests at the project root (Cargo.toml, package.json, ...).
+//! Frameworks come from the dependencies those manifests declare. Projects
+//! whose directory is gone, or is on another machine, are detected from
+//! their changes alone.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Share of a project's code changes a language needs to count as one of its
+/// languages
+const MIN_LANGUAGE_SHARE: f64 = 0.1;
+
+/// Languages by file extension
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("mts", "TypeScript"),
+    ("cts", "TypeScript"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("py", "Python"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("swift", "Swift"),
+    ("rb", "Ruby"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cc", "C++"),
+    ("cpp", "C++"),
+    ("cxx", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("php", "PHP"),
+    ("scala", "Scala"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("hs", "Haskell"),
+    ("lua", "Lua"),
+    ("dart", "Dart"),
+    ("zig", "Zig"),
+    ("sh", "Shell"),
+    ("bash", "Shell"),
+    ("zsh", "Shell"),
+];
+
+/// Root files that mark a project's language
+const MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust"),
+    ("go.mod", "Go"),
+    ("pyproject.toml", "Python"),
+    ("requirements.txt", "Python"),
+    ("setup.py", "Python"),
+    ("Gemfile", "Ruby"),
+    ("pom.xml", "Java"),
+    ("build.gradle", "Java"),
+    ("build.gradle.kts", "Kotlin"),
+    ("Package.swift", "Swift"),
+    ("composer.json", "PHP"),
+    ("mix.exs", "Elixir"),
+    ("pubspec.yaml", "Dart"),
+];
+
+/// Frameworks by manifest and the dependency that implies them
+const FRAMEWORKS: &[(&str, &str, &str)] = &[
+    ("package.json", "react", "React"),
+    ("package.json", "react-native", "React Native"),
+    ("package.json", "next", "Next.js"),
+    ("package.json", "vue", "Vue"),
+    ("package.json", "svelte", "Svelte"),
+    ("package.json", "@angular/core", "Angular"),
+    ("package.json", "express", "Express"),
+    ("package.json", "@nestjs/core", "NestJS"),
+    ("package.json", "electron", "Electron"),
+    ("package.json", "@tauri-apps/api", "Tauri"),
+    ("Cargo.toml", "tauri", "Tauri"),
+    ("Cargo.toml", "axum", "Axum"),
+    ("Cargo.toml", "actix-web", "Actix Web"),
+    ("Cargo.toml", "rocket", "Rocket"),
+    ("Cargo.toml", "bevy", "Bevy"),
+    ("Cargo.toml", "leptos", "Leptos"),
+    ("pyproject.toml", "django", "Django"),
+    ("pyproject.toml", "flask", "Flask"),
+    ("pyproject.toml", "fastapi", "FastAPI"),
+    ("requirements.txt", "django", "Django"),
+    ("requirements.txt", "flask", "Flask"),
+    ("requirements.txt", "fastapi", "FastAPI"),
+    ("Gemfile", "rails", "Rails"),
+    ("go.mod", "github.com/gin-gonic/gin", "Gin"),
+];
+
+/// Primary languages and frameworks of a project
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectStack {
+    /// Most changed first, then those only known from root files
+    pub languages: Vec<String>,
+    /// Sorted by name
+    pub frameworks: Vec<String>,
+}
+
+impl ProjectStack {
+    /// Whether `name` is one of the languages or frameworks, ignoring case
+    pub fn matches(&self, name: &str) -> bool {
+        self.languages
+            .iter()
+            .chain(&self.frameworks)
+            .any(|s| s.eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Language of a source file, from its extension
+pub fn language_for_path(path: &str) -> Option<&'static str> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, language)| *language)
+}
+
+/// Detect a project's stack from the files its sessions changed and the
+/// manifests in its root directory
+pub fn detect_stack(changed_files: &[String], root: &Path) -> ProjectStack {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for language in changed_files.iter().filter_map(|f| language_for_path(f)) {
+        *counts.entry(language).or_default() += 1;
+    }
+    let total: usize = counts.values().sum();
+    let mut changed: Vec<(&str, usize)> = counts
+        .into_iter()
+        .filter(|(_, count)| *count as f64 >= total as f64 * MIN_LANGUAGE_SHARE)
+        .collect();
+    changed.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let mut languages: Vec<String> = changed.into_iter().map(|(l, _)| l.to_string()).collect();
+
+    let mut frameworks = BTreeSet::new();
+    for (file, language) in root_languages(root) {
+        if !languages.iter().any(|l| l == language) {
+            languages.push(language.to_string());
+        }
+        let Ok(manifest) = std::fs::read_to_string(root.join(file)) else {
+            continue;
+        };
+        for (_, dependency, framework) in FRAMEWORKS.iter().filter(|(f, _, _)| *f == file) {
+            if declares_dependency(file, &manifest, dependency) {
+                frameworks.insert(framework.to_string());
+            }
+        }
+    }
+
+    ProjectStack {
+        languages,
+        frameworks: frameworks.into_iter().collect(),
+    }
+}
+
+/// Manifests found in `root`, with the language each one implies
+fn root_languages(root: &Path) -> Vec<(&'static str, &'static str)> {
+    let mut found: Vec<(&str, &str)> = MARKERS
+        .iter()
+        .copied()
+        .filter(|(file, _)| root.join(file).is_file())
+        .collect();
+    if root.join("package.json").is_file() {
+        let language = if root.join("tsconfig.json").is_file() {
+            "TypeScript"
+        } else {
+            "JavaScript"
+        };
+        found.push(("package.json", language));
+    }
+    found
+}
+
+/// Whether a manifest declares `dependency`
+///
+/// package.json is read properly; the others are matched line by line on a
+/// line starting with the dependency name, which covers `name = "1"` and
+/// `name = { ... }` (Cargo.toml), `"name>=1"` (pyproject.toml), `name==1`
+/// (requirements.txt), `gem 'name'` (Gemfile) and `path v1` (go.mod).
+fn declares_dependency(file: &str, manifest: &str, dependency: &str) -> bool {
+    if file == "package.json" {
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(manifest) else {
+            return false;
+        };
+        return ["dependencies", "devDependencies", "peerDependencies"]
+            .iter()
+            .any(|key| json.get(key).and_then(|deps| deps.get(dependency)).is_some());
+    }
+
+    manifest.lines().any(|line| {
+        let line = line
+            .trim()
+            .trim_start_matches("require ")
+            .trim_start_matches("gem ")
+            .trim_start_matches(['"', '\'']);
+        line.strip_prefix(dependency).is_some_and(|rest| {
+            !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect_from_changes() {
+        let changed = files(&[
+            "/p/src/main.rs",
+            "/p/src/lib.rs",
+            "/p/src/db.rs",
+            "/p/ui/app.tsx",
+            "/p/ui/index.ts",
+            "/p/README.md",
+            "/p/Cargo.lock",
+            "/p/scripts/release.sh",
+        ]);
+        // README.md and Cargo.lock aren't source files
+        let stack = detect_stack(&changed, Path::new("/nonexistent/project"));
+        assert_eq!(stack.languages, vec!["Rust", "TypeScript", "Shell"]);
+        assert!(stack.frameworks.is_empty());
+        assert!(stack.matches("rust"));
+        assert!(!stack.matches("Python"));
+
+        let mut changed = files(&["/p/a.py"; 20]);
+        changed.push("/p/tool.go".to_string());
+        let stack = detect_stack(&changed, Path::new("/nonexistent/project"));
+        assert_eq!(stack.languages, vec!["Python"], "one change in 21 is below the threshold");
+    }
+
+    #[test]
+    fn test_detect_from_root_files() {
+        let root = std::env::temp_dir().join(format!("ironhide-stack-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"app\"\n\n[dependencies]\naxum = \"0.8\"\ntauri-build = { version = \"2\" }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"dependencies": {"react": "^19"}, "devDependencies": {"@tauri-apps/api": "^2"}}"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("tsconfig.json"), "{}").unwrap();
+
+        let stack = detect_stack(&files(&["/p/src/App.tsx"]), &root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(stack.languages, vec!["TypeScript", "Rust"]);
+        // tauri-build doesn't count as tauri
+        assert_eq!(stack.frameworks, vec!["Axum", "React", "Tauri"]);
+    }
+
+    #[test]
+    fn test_declares_dependency() {
+        assert!(declares_dependency("requirements.txt", "flask==3.0\nrequests\n", "flask"));
+        assert!(!declares_dependency("requirements.txt", "flask-cors==4\n", "flask"));
+        assert!(declares_dependency("pyproject.toml", "dependencies = [\n  \"fastapi>=0.110\",\n]\n", "fastapi"));
+        assert!(declares_dependency("Gemfile", "gem 'rails', '~> 7.1'\n", "rails"));
+        assert!(declares_dependency("go.mod", "require github.com/gin-gonic/gin v1.9.1\n", "github.com/gin-gonic/gin"));
+        assert!(!declares_dependency("package.json", "not json", "react"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Distribution statistics
+//!
+//! Percentiles and equal-width histograms over a set of values, and the
+//! distributions of session duration, cost, turns and tokens built from
+//! them, so charts can show the spread of sessions and not just averages.
+
+use serde::Serialize;
+
+/// Histogram buckets used when none are asked for
+pub const DEFAULT_BUCKETS: usize = 20;
+
+/// Most buckets a histogram can be split into
+pub const MAX_BUCKETS: usize = 200;
+
+/// Values from `start` (inclusive) up to `end` (exclusive; inclusive for the
+/// last bucket)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct HistogramBucket {
+    pub start: f64,
+    pub end: f64,
+    pub count: u32,
+}
+
+/// Summary statistics and histogram of a set of values
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct Distribution {
+    pub count: u32,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+impl Distribution {
+    /// Describe `values` with a histogram of `bucket_count` equal-width
+    /// buckets between the smallest and largest value
+    ///
+    /// Non-finite values are left out. All values equal gives one bucket.
+    pub fn from_values(values: &[f64], bucket_count: usize) -> Self {
+        let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        if sorted.is_empty() {
+            return Self::default();
+        }
+        sorted.sort_by(f64::total_cmp);
+
+        let min = sorted[0];
+        let max = sorted[sorted.len() - 1];
+        Self {
+            count: sorted.len() as u32,
+            min,
+            max,
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            p99: percentile(&sorted, 99.0),
+            buckets: histogram(&sorted, min, max, bucket_count),
+        }
+    }
+}
+
+/// The `p`th percentile (0-100) of sorted values, interpolating linearly
+/// between the closest ranks
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        1 => sorted[0],
+        len => {
+            let rank = (p.clamp(0.0, 100.0) / 100.0) * (len - 1) as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+        }
+    }
+}
+
+fn histogram(sorted: &[f64], min: f64, max: f64, bucket_count: usize) -> Vec<HistogramBucket> {
+    if max <= min {
+        return vec![HistogramBucket {
+            start: min,
+            end: max,
+            count: sorted.len() as u32,
+        }];
+    }
+
+    let bucket_count = bucket_count.clamp(1, MAX_BUCKETS);
+    let width = (max - min) / bucket_count as f64;
+    let mut buckets: Vec<HistogramBucket> = (0..bucket_count)
+        .map(|i| HistogramBucket {
+            start: min + width * i as f64,
+            end: if i + 1 == bucket_count { max } else { min + width * (i + 1) as f64 },
+            count: 0,
+        })
+        .collect();
+    for value in sorted {
+        let index = (((value - min) / width) as usize).min(bucket_count - 1);
+        buckets[index].count += 1;
+    }
+    buckets
+}
+
+/// Totals of one session, as input to [`SessionDistributions`]
+#[derive(Debug, Clone, Default)]
+pub struct SessionTotals {
+    pub duration_ms: i64,
+    pub cost: f64,
+    pub turns: u32,
+    pub tokens: u64,
+}
+
+/// How session duration, cost, turn count and token use are spread
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SessionDistributions {
+    pub session_count: u32,
+    pub duration_ms: Distribution,
+    pub cost: Distribution,
+    pub turns: Distribution,
+    pub tokens: Distribution,
+}
+
+impl SessionDistributions {
+    pub fn from_sessions(sessions: &[SessionTotals], bucket_count: usize) -> Self {
+        let values = |f: fn(&SessionTotals) -> f64| sessions.iter().map(f).collect::<Vec<_>>();
+        Self {
+            session_count: sessions.len() as u32,
+            duration_ms: Distribution::from_values(&values(|s| s.duration_ms as f64), bucket_count),
+            cost: Distribution::from_values(&values(|s| s.cost), bucket_count),
+            turns: Distribution::from_values(&values(|s| s.turns as f64), bucket_count),
+            tokens: Distribution::from_values(&values(|s| s.tokens as f64), bucket_count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let sorted: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert!((percentile(&sorted, 50.0) - 50.5).abs() < 1e-9);
+        assert!((percentile(&sorted, 90.0) - 90.1).abs() < 1e-9);
+        assert!((percentile(&sorted, 99.0) - 99.01).abs() < 1e-9);
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 100.0), 100.0);
+        assert_eq!(percentile(&[7.0], 90.0), 7.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_distribution_histogram() {
+        let dist = Distribution::from_values(&[0.0, 1.0, 2.5, 9.0, 10.0, f64::NAN], 5);
+        assert_eq!(dist.count, 5);
+        assert_eq!((dist.min, dist.max), (0.0, 10.0));
+        assert!((dist.mean - 4.5).abs() < 1e-9);
+        assert_eq!(dist.p50, 2.5);
+        let counts: Vec<u32> = dist.buckets.iter().map(|b| b.count).collect();
+        // The maximum lands in the last bucket
+        assert_eq!(counts, vec![2, 1, 0, 0, 2]);
+        assert_eq!((dist.buckets[1].start, dist.buckets[1].end), (2.0, 4.0));
+
+        let flat = Distribution::from_values(&[3.0, 3.0], 5);
+        assert_eq!(flat.buckets.len(), 1);
+        assert_eq!(flat.buckets[0].count, 2);
+        assert_eq!(Distribution::from_values(&[], 5), Distribution::default());
+    }
+
+    #[test]
+    fn test_session_distributions() {
+        let sessions: Vec<SessionTotals> = (1..=10)
+            .map(|i| SessionTotals {
+                duration_ms: i * 60_000,
+                cost: i as f64 * 0.5,
+                turns: i as u32,
+                tokens: i as u64 * 1_000,
+            })
+            .collect();
+        let dist = SessionDistributions::from_sessions(&sessions, DEFAULT_BUCKETS);
+        assert_eq!(dist.session_count, 10);
+        assert!((dist.turns.p50 - 5.5).abs() < 1e-9);
+        assert_eq!(dist.duration_ms.max, 600_000.0);
+        assert_eq!(dist.cost.buckets.len(), DEFAULT_BUCKETS);
+        assert_eq!(dist.tokens.buckets.iter().map(|b| b.count).sum::<u32>(), 10);
+    }
+}
@@ -0,0 +1,187 @@
+//! Subagent hierarchy reconstruction
+//!
+//! Subagent logs for a session all live flat in `<session-id>/subagents/`,
+//! whatever spawned them. The parent of each agent is recovered from the
+//! Task tool calls recorded in the session and subagent logs, and costs and
+//! tokens are rolled up from the leaves to the session.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::parser::SubagentSpawn;
+
+/// A session or subagent in the subagent tree
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct SubagentNode {
+    /// Session ID for the root, agent ID for subagents
+    pub id: String,
+    pub is_subagent: bool,
+    /// Task description the agent was spawned with
+    pub description: Option<String>,
+    pub subagent_type: Option<String>,
+    pub started_at: Option<String>,
+    pub turn_count: u32,
+    pub tools_used: Vec<String>,
+    /// Cost of this node's own turns
+    pub cost: f64,
+    /// Tokens of this node's own turns
+    pub tokens: u64,
+    /// Cost including all descendants
+    pub total_cost: f64,
+    /// Tokens including all descendants
+    pub total_tokens: u64,
+    /// Number of descendants
+    pub descendant_count: u32,
+    pub children: Vec<SubagentNode>,
+}
+
+/// Assemble the subagent tree for a session
+///
+/// `spawns` maps the ID of a spawning node (the session or a subagent) to the
+/// agents it started. Agents whose parent can't be determined, or whose
+/// recorded parents form a cycle, are attached directly to the session.
+/// Children are ordered by start time.
+pub fn build_subagent_tree(
+    root: SubagentNode,
+    agents: Vec<SubagentNode>,
+    spawns: &HashMap<String, Vec<SubagentSpawn>>,
+) -> SubagentNode {
+    let mut agents: HashMap<String, SubagentNode> =
+        agents.into_iter().map(|a| (a.id.clone(), a)).collect();
+
+    let mut parent_of: HashMap<String, String> = HashMap::new();
+    for (parent, spawned) in spawns {
+        let parent_known = *parent == root.id || agents.contains_key(parent);
+        for spawn in spawned {
+            let agent = match agents.get_mut(&spawn.agent_id) {
+                Some(agent) if parent_known && spawn.agent_id != *parent => agent,
+                _ => continue,
+            };
+            if agent.description.is_none() {
+                agent.description = spawn.description.clone();
+            }
+            if agent.subagent_type.is_none() {
+                agent.subagent_type = spawn.subagent_type.clone();
+            }
+            // Spawns recorded by the session itself win over nested ones
+            if *parent == root.id || !parent_of.contains_key(&spawn.agent_id) {
+                parent_of.insert(spawn.agent_id.clone(), parent.clone());
+            }
+        }
+    }
+
+    // Reattach agents whose parent chain loops instead of reaching the session
+    let ids: Vec<String> = agents.keys().cloned().collect();
+    for id in ids {
+        let mut seen = HashSet::new();
+        let mut current = id.clone();
+        while let Some(parent) = parent_of.get(&current).filter(|p| **p != root.id) {
+            if !seen.insert(current.clone()) {
+                parent_of.insert(id, root.id.clone());
+                break;
+            }
+            current = parent.clone();
+        }
+    }
+
+    let mut children_of: HashMap<String, Vec<SubagentNode>> = HashMap::new();
+    for (id, agent) in agents {
+        let parent = parent_of.get(&id).cloned().unwrap_or_else(|| root.id.clone());
+        children_of.entry(parent).or_default().push(agent);
+    }
+
+    attach_children(root, &mut children_of)
+}
+
+/// Recursively attach children and compute rollups
+fn attach_children(
+    mut node: SubagentNode,
+    children_of: &mut HashMap<String, Vec<SubagentNode>>,
+) -> SubagentNode {
+    let mut children: Vec<SubagentNode> = children_of
+        .remove(&node.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|child| attach_children(child, children_of))
+        .collect();
+    children.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+
+    node.total_cost = node.cost + children.iter().map(|c| c.total_cost).sum::<f64>();
+    node.total_tokens = node.tokens + children.iter().map(|c| c.total_tokens).sum::<u64>();
+    node.descendant_count = children.iter().map(|c| 1 + c.descendant_count).sum();
+    node.children = children;
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, is_subagent: bool, cost: f64, tokens: u64, started_at: &str) -> SubagentNode {
+        SubagentNode {
+            id: id.to_string(),
+            is_subagent,
+            started_at: Some(started_at.to_string()),
+            cost,
+            tokens,
+            ..Default::default()
+        }
+    }
+
+    fn spawn(agent_id: &str, description: &str) -> SubagentSpawn {
+        SubagentSpawn {
+            agent_id: agent_id.to_string(),
+            description: Some(description.to_string()),
+            subagent_type: None,
+        }
+    }
+
+    #[test]
+    fn test_build_subagent_tree() {
+        let root = node("session", false, 1.0, 1000, "2026-01-14T07:00:00Z");
+        let agents = vec![
+            node("a2", true, 0.2, 200, "2026-01-14T07:05:00Z"),
+            node("a1", true, 0.5, 500, "2026-01-14T07:01:00Z"),
+            node("nested", true, 0.1, 100, "2026-01-14T07:02:00Z"),
+            node("orphan", true, 0.05, 50, "2026-01-14T07:10:00Z"),
+        ];
+        let mut spawns = HashMap::new();
+        spawns.insert("session".to_string(), vec![spawn("a1", "Explore"), spawn("a2", "Review")]);
+        spawns.insert("a1".to_string(), vec![spawn("nested", "Search")]);
+
+        let tree = build_subagent_tree(root, agents, &spawns);
+
+        assert_eq!(tree.descendant_count, 4);
+        assert!((tree.total_cost - 1.85).abs() < 1e-9);
+        assert_eq!(tree.total_tokens, 1850);
+
+        let ids: Vec<&str> = tree.children.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["a1", "a2", "orphan"]);
+
+        let a1 = &tree.children[0];
+        assert_eq!(a1.description.as_deref(), Some("Explore"));
+        assert_eq!(a1.children.len(), 1);
+        assert_eq!(a1.children[0].id, "nested");
+        assert!((a1.total_cost - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_build_subagent_tree_breaks_cycles() {
+        let root = node("session", false, 0.0, 0, "2026-01-14T07:00:00Z");
+        let agents = vec![
+            node("a1", true, 1.0, 10, "2026-01-14T07:01:00Z"),
+            node("a2", true, 1.0, 10, "2026-01-14T07:02:00Z"),
+        ];
+        let mut spawns = HashMap::new();
+        spawns.insert("a1".to_string(), vec![spawn("a2", "x")]);
+        spawns.insert("a2".to_string(), vec![spawn("a1", "y")]);
+
+        let tree = build_subagent_tree(root, agents, &spawns);
+
+        // Every agent is reachable exactly once
+        assert_eq!(tree.descendant_count, 2);
+        assert_eq!(tree.total_tokens, 20);
+    }
+}
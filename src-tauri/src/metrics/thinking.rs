@@ -0,0 +1,199 @@
+//! Extended thinking
+//!
+//! Thinking is billed as output, but usage doesn't break it out, so thinking
+//! tokens are estimated from the text of the thinking blocks when sessions
+//! are parsed. Here they are totalled per day and per thinking mode with
+//! their share of output tokens and their cost at the model's output rate.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::metrics::cost::{calculate_provider_turn_cost, CostProvider};
+use crate::metrics::tokens::TurnTokens;
+use crate::parser::CompletedTurn;
+
+/// Thinking mode label for turns whose prompt didn't record one
+pub const UNSPECIFIED_MODE: &str = "unspecified";
+
+/// Cost of `thinking_tokens` at the model's output rate on `date`
+pub fn thinking_cost(thinking_tokens: u64, model: Option<&str>, provider: CostProvider, date: Option<&str>) -> f64 {
+    let tokens = TurnTokens::new(0, thinking_tokens, 0, 0, 0);
+    calculate_provider_turn_cost(&tokens, model, provider, date).total_cost
+}
+
+/// Cost of a turn's thinking tokens
+pub fn turn_thinking_cost(turn: &CompletedTurn) -> f64 {
+    thinking_cost(turn.thinking_tokens, turn.model.as_deref(), turn.provider, Some(&turn.started_at))
+}
+
+/// Thinking tokens as a fraction (0-1) of output tokens
+pub fn thinking_share(thinking_tokens: u64, output_tokens: u64) -> f64 {
+    if output_tokens == 0 {
+        0.0
+    } else {
+        thinking_tokens as f64 / output_tokens as f64
+    }
+}
+
+/// Stored turns of one day, model and thinking mode
+#[derive(Debug, Clone)]
+pub struct ThinkingUsage {
+    pub date: String,
+    pub model: Option<String>,
+    pub provider: CostProvider,
+    pub thinking_level: Option<String>,
+    pub turns: u32,
+    pub turns_with_thinking: u32,
+    pub output_tokens: u64,
+    pub thinking_tokens: u64,
+    pub cost: f64,
+}
+
+/// Thinking totals of a set of turns
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ThinkingTotals {
+    pub turns: u32,
+    pub turns_with_thinking: u32,
+    pub output_tokens: u64,
+    pub thinking_tokens: u64,
+    /// Thinking tokens as a fraction (0-1) of output tokens
+    pub thinking_share: f64,
+    /// Cost of the thinking tokens
+    pub thinking_cost: f64,
+    /// Cost of the turns, thinking included
+    pub cost: f64,
+}
+
+impl ThinkingTotals {
+    fn add(&mut self, usage: &ThinkingUsage) {
+        self.turns += usage.turns;
+        self.turns_with_thinking += usage.turns_with_thinking;
+        self.output_tokens += usage.output_tokens;
+        self.thinking_tokens += usage.thinking_tokens;
+        self.thinking_cost += thinking_cost(usage.thinking_tokens, usage.model.as_deref(), usage.provider, Some(&usage.date));
+        self.cost += usage.cost;
+        self.thinking_share = thinking_share(self.thinking_tokens, self.output_tokens);
+    }
+}
+
+/// Thinking totals of one calendar day (reporting timezone)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DailyThinking {
+    pub date: String,
+    #[serde(flatten)]
+    pub totals: ThinkingTotals,
+}
+
+/// Thinking totals of the turns sent with one thinking mode
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ThinkingModeUsage {
+    /// Level such as "high", "off", or [`UNSPECIFIED_MODE`]
+    pub mode: String,
+    #[serde(flatten)]
+    pub totals: ThinkingTotals,
+    pub avg_output_per_turn: f64,
+    pub avg_cost_per_turn: f64,
+}
+
+/// Thinking totals overall, per day (oldest first) and per thinking mode
+/// (most turns first)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ThinkingTrend {
+    pub totals: ThinkingTotals,
+    pub by_day: Vec<DailyThinking>,
+    pub by_mode: Vec<ThinkingModeUsage>,
+}
+
+/// Total thinking usage by day and thinking mode
+pub fn summarize_thinking(usage: &[ThinkingUsage]) -> ThinkingTrend {
+    let mut totals = ThinkingTotals::default();
+    let mut by_day: BTreeMap<&str, ThinkingTotals> = BTreeMap::new();
+    let mut by_mode: BTreeMap<&str, ThinkingTotals> = BTreeMap::new();
+
+    for row in usage {
+        totals.add(row);
+        by_day.entry(&row.date).or_default().add(row);
+        by_mode
+            .entry(row.thinking_level.as_deref().unwrap_or(UNSPECIFIED_MODE))
+            .or_default()
+            .add(row);
+    }
+
+    let mut by_mode: Vec<ThinkingModeUsage> = by_mode
+        .into_iter()
+        .map(|(mode, totals)| {
+            let turns = totals.turns.max(1) as f64;
+            ThinkingModeUsage {
+                mode: mode.to_string(),
+                avg_output_per_turn: totals.output_tokens as f64 / turns,
+                avg_cost_per_turn: totals.cost / turns,
+                totals,
+            }
+        })
+        .collect();
+    by_mode.sort_by_key(|m| std::cmp::Reverse(m.totals.turns));
+
+    ThinkingTrend {
+        totals,
+        by_day: by_day
+            .into_iter()
+            .map(|(date, totals)| DailyThinking {
+                date: date.to_string(),
+                totals,
+            })
+            .collect(),
+        by_mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(date: &str, level: Option<&str>, turns: u32, output: u64, thinking: u64, cost: f64) -> ThinkingUsage {
+        ThinkingUsage {
+            date: date.to_string(),
+            model: Some("claude-sonnet-4-5-20250929".to_string()),
+            provider: CostProvider::Anthropic,
+            thinking_level: level.map(String::from),
+            turns,
+            turns_with_thinking: if thinking > 0 { turns } else { 0 },
+            output_tokens: output,
+            thinking_tokens: thinking,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_summarize_thinking() {
+        let rows = [
+            usage("2026-03-01", Some("high"), 4, 40_000, 30_000, 2.0),
+            usage("2026-03-01", None, 2, 10_000, 0, 0.5),
+            usage("2026-03-02", Some("high"), 2, 20_000, 10_000, 1.0),
+        ];
+        let trend = summarize_thinking(&rows);
+
+        assert_eq!(trend.totals.turns, 8);
+        assert_eq!(trend.totals.turns_with_thinking, 6);
+        assert_eq!(trend.totals.thinking_tokens, 40_000);
+        assert!((trend.totals.thinking_share - 40_000.0 / 70_000.0).abs() < 1e-9);
+        let expected_cost = thinking_cost(40_000, Some("claude-sonnet-4-5-20250929"), CostProvider::Anthropic, None);
+        assert!(expected_cost > 0.0);
+        assert!((trend.totals.thinking_cost - expected_cost).abs() < 1e-9);
+
+        assert_eq!(trend.by_day.len(), 2);
+        assert!((trend.by_day[0].totals.thinking_share - 0.6).abs() < 1e-9);
+        assert!((trend.by_day[1].totals.thinking_share - 0.5).abs() < 1e-9);
+
+        assert_eq!(trend.by_mode[0].mode, "high");
+        assert_eq!(trend.by_mode[0].avg_output_per_turn, 10_000.0);
+        assert_eq!(trend.by_mode[0].avg_cost_per_turn, 0.5);
+        assert_eq!(trend.by_mode[1].mode, UNSPECIFIED_MODE);
+        assert_eq!(trend.by_mode[1].totals.thinking_share, 0.0);
+    }
+}
@@ -4,8 +4,12 @@
 
 use serde::{Deserialize, Serialize};
 
+/// Rough characters per token of English text and code, for estimating
+/// tokens of content the API doesn't report usage for separately
+pub const CHARS_PER_TOKEN: u64 = 4;
+
 /// Token metrics for a single turn
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TurnTokens {
     pub input_tokens: u64,
     pub output_tokens: u64,
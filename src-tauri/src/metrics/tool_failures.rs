@@ -0,0 +1,123 @@
+//! Failed tool calls
+//!
+//! Lists the tool calls in a session whose result came back as an error,
+//! either flagged `is_error` or rejected with a `<tool_use_error>` message,
+//! together with what was attempted.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::parser::CompletedTurn;
+
+/// Longest error text kept per failure
+const MAX_ERROR_CHARS: usize = 2_000;
+
+/// A tool call that failed
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ToolFailure {
+    pub turn_number: u32,
+    pub timestamp: String,
+    pub tool_use_id: String,
+    pub tool_name: String,
+    /// What was attempted: the command, file path, pattern or URL, when the
+    /// tool has one
+    pub target: Option<String>,
+    pub input: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// Collect the failed tool calls of a session, in order
+pub fn extract_tool_failures(turns: &[CompletedTurn]) -> Vec<ToolFailure> {
+    turns
+        .iter()
+        .flat_map(|turn| {
+            turn.tool_uses.iter().filter(|tool| tool.is_error).map(|tool| ToolFailure {
+                turn_number: turn.turn_number,
+                timestamp: turn.started_at.clone(),
+                tool_use_id: tool.id.clone(),
+                tool_name: tool.name.clone(),
+                target: tool.input.as_ref().and_then(tool_target),
+                input: tool.input.clone(),
+                error: tool
+                    .error_message()
+                    .map(|e| e.chars().take(MAX_ERROR_CHARS).collect()),
+            })
+        })
+        .collect()
+}
+
+/// The main argument of a tool call
+pub fn tool_target(input: &Value) -> Option<String> {
+    ["command", "file_path", "notebook_path", "pattern", "url", "path"]
+        .iter()
+        .find_map(|key| input.get(key).and_then(|v| v.as_str()))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ToolUse;
+
+    fn tool(name: &str, input: Value, result: &str, is_error: bool) -> ToolUse {
+        ToolUse {
+            id: format!("toolu_{}", name),
+            name: name.to_string(),
+            input: Some(input),
+            result: Some(result.to_string()),
+            is_error,
+        }
+    }
+
+    #[test]
+    fn test_extract_tool_failures() {
+        let turn = CompletedTurn {
+            turn_number: 3,
+            started_at: "2026-02-05T10:00:00Z".to_string(),
+            ended_at: None,
+            duration_ms: None,
+            user_message: None,
+            assistant_message: None,
+            model: None,
+            stop_reason: None,
+            provider: Default::default(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_write_5m_tokens: 0,
+            cache_write_1h_tokens: 0,
+            total_tokens: 0,
+            total_context: 0,
+            tool_uses: vec![
+                tool("Bash", serde_json::json!({"command": "cargo tset"}), "error: no such command", true),
+                tool("Read", serde_json::json!({"file_path": "/src/lib.rs"}), "fn main() {}", false),
+                tool(
+                    "Edit",
+                    serde_json::json!({"file_path": "/src/main.rs", "old_string": "a"}),
+                    "<tool_use_error>String to replace not found</tool_use_error>",
+                    true,
+                ),
+            ],
+            tool_count: 3,
+            has_subagents: false,
+            subagent_ids: Vec::new(),
+            start_uuid: None,
+            end_uuid: None,
+            entry_count: 0,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_tokens: 0,
+            thinking_level: None,
+        };
+
+        let failures = extract_tool_failures(&[turn]);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].tool_name, "Bash");
+        assert_eq!(failures[0].target.as_deref(), Some("cargo tset"));
+        assert_eq!(failures[0].error.as_deref(), Some("error: no such command"));
+        assert_eq!(failures[1].turn_number, 3);
+        assert_eq!(failures[1].target.as_deref(), Some("/src/main.rs"));
+        assert_eq!(failures[1].error.as_deref(), Some("String to replace not found"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Tool usage across sessions, with MCP servers broken out
+//!
+//! Calls are counted per canonical tool name (see
+//! [`crate::parser::tool_names`]), then MCP tools are also grouped by the
+//! server that provides them, since `mcp__github__create_issue` and
+//! `mcp__github__list_pulls` are one integration in use.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::parser::tool_names::{canonical_tool_name, split_mcp_tool};
+
+/// Calls of one tool
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ToolCallCount {
+    pub tool: String,
+    pub calls: u32,
+    /// Calls whose result was an error
+    pub errors: u32,
+}
+
+/// Calls of the tools of one MCP server
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct McpServerUsage {
+    pub server: String,
+    pub calls: u32,
+    pub errors: u32,
+    /// By tool name without the server prefix, most called first
+    pub tools: Vec<ToolCallCount>,
+}
+
+/// Tool calls by tool and by MCP server
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ToolUsageStats {
+    pub total_calls: u32,
+    /// Calls of MCP tools, included in `total_calls`
+    pub mcp_calls: u32,
+    /// Every tool, MCP tools under their full name, most called first
+    pub tools: Vec<ToolCallCount>,
+    /// Most called first
+    pub mcp_servers: Vec<McpServerUsage>,
+}
+
+/// Merge call counts under canonical names and group MCP tools by server
+pub fn summarize_tool_usage(counts: impl IntoIterator<Item = ToolCallCount>) -> ToolUsageStats {
+    let mut by_tool: HashMap<String, ToolCallCount> = HashMap::new();
+    for count in counts {
+        let tool = canonical_tool_name(&count.tool);
+        let entry = by_tool.entry(tool.clone()).or_insert(ToolCallCount {
+            tool,
+            calls: 0,
+            errors: 0,
+        });
+        entry.calls += count.calls;
+        entry.errors += count.errors;
+    }
+    let mut tools: Vec<ToolCallCount> = by_tool.into_values().collect();
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.tool.cmp(&b.tool)));
+
+    let mut servers: HashMap<&str, McpServerUsage> = HashMap::new();
+    for count in &tools {
+        let Some((server, tool)) = split_mcp_tool(&count.tool) else {
+            continue;
+        };
+        let usage = servers.entry(server).or_insert_with(|| McpServerUsage {
+            server: server.to_string(),
+            calls: 0,
+            errors: 0,
+            tools: Vec::new(),
+        });
+        usage.calls += count.calls;
+        usage.errors += count.errors;
+        usage.tools.push(ToolCallCount {
+            tool: tool.to_string(),
+            ..count.clone()
+        });
+    }
+    let mut mcp_servers: Vec<McpServerUsage> = servers.into_values().collect();
+    mcp_servers.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.server.cmp(&b.server)));
+
+    ToolUsageStats {
+        total_calls: tools.iter().map(|t| t.calls).sum(),
+        mcp_calls: mcp_servers.iter().map(|s| s.calls).sum(),
+        tools,
+        mcp_servers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(tool: &str, calls: u32, errors: u32) -> ToolCallCount {
+        ToolCallCount {
+            tool: tool.to_string(),
+            calls,
+            errors,
+        }
+    }
+
+    #[test]
+    fn test_summarize_tool_usage() {
+        let stats = summarize_tool_usage([
+            count("Bash", 10, 2),
+            count("bash", 3, 1),
+            count("Read", 8, 0),
+            count("mcp__github__create_issue", 2, 0),
+            count("MCP__github__list_pulls", 4, 1),
+            count("mcp__linear__get_issue", 1, 0),
+        ]);
+
+        assert_eq!(stats.total_calls, 28);
+        assert_eq!(stats.mcp_calls, 7);
+        assert_eq!(stats.tools[0], count("Bash", 13, 3));
+        assert_eq!(stats.tools.len(), 5);
+
+        assert_eq!(stats.mcp_servers.len(), 2);
+        let github = &stats.mcp_servers[0];
+        assert_eq!((github.server.as_str(), github.calls, github.errors), ("github", 6, 1));
+        assert_eq!(github.tools, vec![count("list_pulls", 4, 1), count("create_issue", 2, 0)]);
+        assert_eq!(stats.mcp_servers[1].server, "linear");
+
+        assert_eq!(summarize_tool_usage([]), ToolUsageStats::default());
+    }
+}
@@ -0,0 +1,304 @@
+//! Rolling usage windows
+//!
+//! Claude plans meter usage over rolling windows (a 5-hour session window,
+//! plus longer daily and weekly ones). Each window here sums the tokens and
+//! cost of the turns started within the last N hours, and compares them to
+//! optional user-set thresholds so the app can warn before a limit is hit.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest window that can be configured: 90 days
+pub const MAX_WINDOW_HOURS: u32 = 24 * 90;
+
+/// A rolling window to track and its warning thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct UsageWindowConfig {
+    /// Window length in hours
+    pub hours: u32,
+    /// Warn once this many tokens are used within the window
+    #[serde(default)]
+    pub token_threshold: Option<u64>,
+    /// Warn once this much is spent within the window, in USD
+    #[serde(default)]
+    pub cost_threshold: Option<f64>,
+}
+
+impl UsageWindowConfig {
+    pub fn new(hours: u32) -> Self {
+        Self {
+            hours,
+            token_threshold: None,
+            cost_threshold: None,
+        }
+    }
+
+    /// Whether the window can be used: a length of 1 hour up to 90 days and
+    /// no negative or non-finite cost threshold
+    pub fn is_valid(&self) -> bool {
+        (1..=MAX_WINDOW_HOURS).contains(&self.hours)
+            && self.cost_threshold.is_none_or(|c| c.is_finite() && c >= 0.0)
+    }
+
+    /// Short name such as "5h", "24h" or "7d"
+    pub fn label(&self) -> String {
+        if self.hours > 24 && self.hours.is_multiple_of(24) {
+            format!("{}d", self.hours / 24)
+        } else {
+            format!("{}h", self.hours)
+        }
+    }
+}
+
+/// The 5-hour, 24-hour and 7-day windows, without thresholds
+pub fn default_usage_windows() -> Vec<UsageWindowConfig> {
+    [5, 24, 24 * 7].into_iter().map(UsageWindowConfig::new).collect()
+}
+
+/// Tokens and cost of one turn
+#[derive(Debug, Clone)]
+pub struct UsageTurn {
+    pub session_id: String,
+    pub started_at: DateTime<Utc>,
+    pub tokens: u64,
+    pub cost: f64,
+}
+
+/// Usage within one rolling window ending now
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct UsageWindow {
+    pub label: String,
+    pub hours: u32,
+    /// Start of the window (RFC 3339)
+    pub window_start: String,
+    pub tokens: u64,
+    pub cost: f64,
+    pub turns: u32,
+    pub sessions: u32,
+    pub token_threshold: Option<u64>,
+    pub cost_threshold: Option<f64>,
+    /// Tokens used as a percentage of the token threshold (0-100+)
+    pub token_pct: Option<f64>,
+    /// Cost as a percentage of the cost threshold (0-100+)
+    pub cost_pct: Option<f64>,
+    /// Whether either threshold has been reached
+    pub exceeded: bool,
+    /// When the oldest turn in the window drops out of it, freeing up usage
+    pub next_rollover: Option<String>,
+}
+
+/// Sum turns into each configured window ending at `now`
+pub fn compute_usage_windows(
+    turns: &[UsageTurn],
+    configs: &[UsageWindowConfig],
+    now: DateTime<Utc>,
+) -> Vec<UsageWindow> {
+    configs
+        .iter()
+        .map(|config| {
+            let window = Duration::hours(config.hours as i64);
+            let start = now - window;
+            let mut tokens = 0;
+            let mut cost = 0.0;
+            let mut turn_count = 0;
+            let mut sessions = HashSet::new();
+            let mut oldest: Option<DateTime<Utc>> = None;
+            for turn in turns.iter().filter(|t| t.started_at >= start && t.started_at <= now) {
+                tokens += turn.tokens;
+                cost += turn.cost;
+                turn_count += 1;
+                sessions.insert(turn.session_id.as_str());
+                oldest = Some(oldest.map_or(turn.started_at, |o| o.min(turn.started_at)));
+            }
+
+            let token_pct = config
+                .token_threshold
+                .filter(|t| *t > 0)
+                .map(|t| tokens as f64 / t as f64 * 100.0);
+            let cost_pct = config
+                .cost_threshold
+                .filter(|c| *c > 0.0)
+                .map(|c| cost / c * 100.0);
+            let exceeded = [token_pct, cost_pct].iter().flatten().any(|pct| *pct >= 100.0);
+
+            UsageWindow {
+                label: config.label(),
+                hours: config.hours,
+                window_start: start.to_rfc3339_opts(SecondsFormat::Secs, true),
+                tokens,
+                cost,
+                turns: turn_count,
+                sessions: sessions.len() as u32,
+                token_threshold: config.token_threshold,
+                cost_threshold: config.cost_threshold,
+                token_pct,
+                cost_pct,
+                exceeded,
+                next_rollover: oldest.map(|o| (o + window).to_rfc3339_opts(SecondsFormat::Secs, true)),
+            }
+        })
+        .collect()
+}
+
+/// What a threshold limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum UsageMetric {
+    Tokens,
+    Cost,
+}
+
+/// A window that has just reached one of its thresholds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct UsageWindowWarning {
+    pub label: String,
+    pub hours: u32,
+    pub metric: UsageMetric,
+    /// Tokens or USD used in the window
+    pub value: f64,
+    pub threshold: f64,
+    pub next_rollover: Option<String>,
+}
+
+/// Remembers which thresholds are already reached, so each crossing is
+/// reported once
+///
+/// A threshold is reported again only after usage has rolled back under it.
+#[derive(Debug, Default)]
+pub struct UsageThresholdTracker {
+    reached: HashSet<(u32, UsageMetric)>,
+}
+
+impl UsageThresholdTracker {
+    /// Warnings for thresholds reached since the previous check
+    pub fn check(&mut self, windows: &[UsageWindow]) -> Vec<UsageWindowWarning> {
+        let mut warnings = Vec::new();
+        for window in windows {
+            let limits = [
+                (
+                    UsageMetric::Tokens,
+                    window.token_pct,
+                    window.tokens as f64,
+                    window.token_threshold.map(|t| t as f64),
+                ),
+                (UsageMetric::Cost, window.cost_pct, window.cost, window.cost_threshold),
+            ];
+            for (metric, pct, value, threshold) in limits {
+                let key = (window.hours, metric);
+                match (pct, threshold) {
+                    (Some(pct), Some(threshold)) if pct >= 100.0 => {
+                        if self.reached.insert(key) {
+                            warnings.push(UsageWindowWarning {
+                                label: window.label.clone(),
+                                hours: window.hours,
+                                metric,
+                                value,
+                                threshold,
+                                next_rollover: window.next_rollover.clone(),
+                            });
+                        }
+                    }
+                    _ => {
+                        self.reached.remove(&key);
+                    }
+                }
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(session: &str, started_at: &str, tokens: u64, cost: f64) -> UsageTurn {
+        UsageTurn {
+            session_id: session.to_string(),
+            started_at: DateTime::parse_from_rfc3339(started_at).unwrap().with_timezone(&Utc),
+            tokens,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_usage_windows_and_warnings() {
+        let now = DateTime::parse_from_rfc3339("2026-03-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        let turns = [
+            turn("a", "2026-03-10T09:00:00Z", 40_000, 2.0),
+            turn("b", "2026-03-10T11:30:00Z", 60_000, 3.0),
+            turn("a", "2026-03-09T20:00:00Z", 100_000, 5.0),
+            turn("c", "2026-03-05T08:00:00Z", 500_000, 20.0),
+            turn("old", "2026-02-20T08:00:00Z", 1_000_000, 50.0),
+        ];
+        let configs = [
+            UsageWindowConfig {
+                hours: 5,
+                token_threshold: Some(100_000),
+                cost_threshold: None,
+            },
+            UsageWindowConfig {
+                hours: 24,
+                token_threshold: None,
+                cost_threshold: Some(20.0),
+            },
+            UsageWindowConfig::new(24 * 7),
+        ];
+
+        let windows = compute_usage_windows(&turns, &configs, now);
+        let five = &windows[0];
+        assert_eq!(five.label, "5h");
+        assert_eq!(five.window_start, "2026-03-10T07:00:00Z");
+        assert_eq!((five.tokens, five.turns, five.sessions), (100_000, 2, 2));
+        assert_eq!(five.token_pct, Some(100.0));
+        assert!(five.exceeded);
+        // The 09:00 turn is the first to roll out
+        assert_eq!(five.next_rollover.as_deref(), Some("2026-03-10T14:00:00Z"));
+
+        let day = &windows[1];
+        assert_eq!(day.label, "24h");
+        assert_eq!(day.tokens, 200_000);
+        assert!((day.cost - 10.0).abs() < 1e-9);
+        assert_eq!(day.cost_pct, Some(50.0));
+        assert!(!day.exceeded);
+
+        let week = &windows[2];
+        assert_eq!(week.label, "7d");
+        assert_eq!((week.tokens, week.sessions), (700_000, 3));
+        assert_eq!((week.token_pct, week.exceeded), (None, false));
+
+        // Reaching a threshold warns once until usage drops back under it
+        let mut tracker = UsageThresholdTracker::default();
+        let warnings = tracker.check(&windows);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!((warnings[0].hours, warnings[0].metric), (5, UsageMetric::Tokens));
+        assert_eq!(warnings[0].threshold, 100_000.0);
+        assert!(tracker.check(&windows).is_empty());
+
+        let later = now + Duration::hours(3);
+        assert!(tracker.check(&compute_usage_windows(&turns, &configs, later)).is_empty());
+        assert_eq!(tracker.check(&windows).len(), 1);
+    }
+
+    #[test]
+    fn test_usage_window_config() {
+        assert_eq!(
+            default_usage_windows().iter().map(|w| w.label()).collect::<Vec<_>>(),
+            vec!["5h", "24h", "7d"]
+        );
+        assert_eq!(UsageWindowConfig::new(36).label(), "36h");
+        assert!(!UsageWindowConfig::new(0).is_valid());
+        assert!(!UsageWindowConfig::new(MAX_WINDOW_HOURS + 1).is_valid());
+        let negative = UsageWindowConfig {
+            cost_threshold: Some(-1.0),
+            ..UsageWindowConfig::new(5)
+        };
+        assert!(!negative.is_valid());
+    }
+}
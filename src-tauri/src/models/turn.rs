@@ -98,6 +98,51 @@ impl std::fmt::Display for ChangeType {
     }
 }
 
+/// A user's verdict on a turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationLabel {
+    /// A dead end: the turn's cost bought nothing
+    Wasted,
+    /// A key insight or otherwise worth its cost
+    Valuable,
+}
+
+impl AnnotationLabel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnnotationLabel::Wasted => "wasted",
+            AnnotationLabel::Valuable => "valuable",
+        }
+    }
+
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "wasted" => Some(AnnotationLabel::Wasted),
+            "valuable" => Some(AnnotationLabel::Valuable),
+            _ => None,
+        }
+    }
+}
+
+/// A label and note a user attached to a turn
+///
+/// Keyed by session and turn number rather than turn ID, so annotations
+/// outlive the turn rows being rewritten when a session is stored again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TurnAnnotation {
+    pub session_id: String,
+    pub turn_number: u32,
+    pub label: AnnotationLabel,
+    pub note: Option<String>,
+    pub annotated_at: String,
+    /// Cost of the turn as stored; 0 while the turn isn't in the database
+    #[serde(default)]
+    pub cost: f64,
+}
+
 /// Builder for turn records
 pub struct TurnBuilder {
     turn_id: String,
@@ -0,0 +1,299 @@
+//! Webhook notifications
+//!
+//! Analytics events are POSTed as JSON to the webhook URLs configured in
+//! settings. The body has a `text` field, which is all a Slack incoming
+//! webhook needs, next to the structured event for other receivers.
+//!
+//! Each event is sent at most once per run: a session keeps producing the
+//! same anti-pattern, and a day stays over budget, on every sync. Failed
+//! deliveries are retried with exponential backoff, except for client errors
+//! other than 429, which resending the same body won't fix.
+//!
+//! The daily digest is sent by a scheduled job rather than a sync, and goes
+//! out once per day across runs; see `commands::schedule_daily_digest`.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::patterns::types::{AntiPatternType, Severity};
+use crate::trends::digest::DailyDigest;
+use crate::settings::{active_settings, NotificationSettings};
+
+/// Delivery attempts per webhook before giving up
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry; doubled for every further attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+lazy_static::lazy_static! {
+    /// Keys of the events already sent in this run
+    static ref SENT: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// An analytics event that can be sent to webhooks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NotificationEvent {
+    NewSession {
+        session_id: String,
+        project_path: Option<String>,
+    },
+    BudgetExceeded {
+        /// Date in YYYY-MM-DD format
+        date: String,
+        cost: f64,
+        budget: f64,
+    },
+    AnomalyDetected {
+        date: String,
+        cost: f64,
+        baseline_mean: f64,
+        z_score: f64,
+    },
+    AntiPatternFound {
+        session_id: String,
+        pattern_type: AntiPatternType,
+        severity: Severity,
+        turn_number: Option<u32>,
+        description: String,
+        impact_cost: f64,
+    },
+    DailyDigest(DailyDigest),
+}
+
+impl NotificationEvent {
+    /// Whether this kind of event is switched on in settings
+    pub fn is_enabled(&self, settings: &NotificationSettings) -> bool {
+        match self {
+            Self::NewSession { .. } => settings.new_session,
+            Self::BudgetExceeded { .. } => settings.budget_exceeded,
+            Self::AnomalyDetected { .. } => settings.anomaly_detected,
+            Self::AntiPatternFound { .. } => settings.antipattern_found,
+            Self::DailyDigest(_) => settings.daily_digest,
+        }
+    }
+
+    /// Identifies the event for once-per-run delivery
+    fn key(&self) -> String {
+        match self {
+            Self::NewSession { session_id, .. } => format!("session:{}", session_id),
+            Self::BudgetExceeded { date, .. } => format!("budget:{}", date),
+            Self::AnomalyDetected { date, .. } => format!("anomaly:{}", date),
+            Self::AntiPatternFound {
+                session_id,
+                pattern_type,
+                turn_number,
+                ..
+            } => format!("pattern:{}:{:?}:{:?}", session_id, pattern_type, turn_number),
+            Self::DailyDigest(digest) => format!("digest:{}", digest.date),
+        }
+    }
+
+    /// One-line human-readable message
+    pub fn text(&self) -> String {
+        match self {
+            Self::NewSession {
+                session_id,
+                project_path,
+            } => format!(
+                "New Claude Code session {} in {}",
+                session_id,
+                project_path.as_deref().unwrap_or("an unknown project")
+            ),
+            Self::BudgetExceeded { date, cost, budget } => format!(
+                "Daily budget exceeded on {}: ${:.2} spent of ${:.2}",
+                date, cost, budget
+            ),
+            Self::AnomalyDetected {
+                date,
+                cost,
+                baseline_mean,
+                z_score,
+            } => format!(
+                "Cost anomaly on {}: ${:.2} vs. a typical ${:.2} ({:.1}σ)",
+                date, cost, baseline_mean, z_score
+            ),
+            Self::AntiPatternFound {
+                session_id,
+                pattern_type,
+                severity,
+                description,
+                ..
+            } => format!(
+                "{} ({}) in session {}: {}",
+                pattern_type.display_name(),
+                severity.as_str(),
+                session_id,
+                description
+            ),
+            Self::DailyDigest(digest) => digest.text(),
+        }
+    }
+
+    /// JSON body POSTed to webhooks
+    pub fn payload(&self) -> serde_json::Value {
+        let mut body = serde_json::to_value(self).unwrap_or_default();
+        if let Some(fields) = body.as_object_mut() {
+            fields.insert("text".to_string(), serde_json::Value::String(self.text()));
+        }
+        body
+    }
+}
+
+/// Keep the events that are enabled and haven't been sent yet, marking them sent
+fn take_unsent(events: Vec<NotificationEvent>, settings: &NotificationSettings) -> Vec<NotificationEvent> {
+    let mut sent = match SENT.lock() {
+        Ok(sent) => sent,
+        Err(_) => return Vec::new(),
+    };
+    events
+        .into_iter()
+        .filter(|event| event.is_enabled(settings) && sent.insert(event.key()))
+        .collect()
+}
+
+/// Send events to the configured webhooks in the background
+///
+/// Delivery runs on its own thread so retries never hold up the caller. Does
+/// nothing when no webhook is configured.
+pub fn notify(events: Vec<NotificationEvent>) {
+    let settings = active_settings().notifications;
+    if settings.webhook_urls.is_empty() || events.is_empty() {
+        return;
+    }
+    let events = take_unsent(events, &settings);
+    if events.is_empty() {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build();
+        let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build();
+        let (runtime, client) = match (runtime, client) {
+            (Ok(runtime), Ok(client)) => (runtime, client),
+            _ => {
+                tracing::warn!("Failed to set up webhook delivery");
+                return;
+            }
+        };
+        runtime.block_on(async {
+            for event in &events {
+                let body = event.payload();
+                for url in &settings.webhook_urls {
+                    if let Err(e) = post_with_retry(&client, url.trim(), &body).await {
+                        tracing::warn!("Webhook delivery to {} failed: {}", webhook_host(url), e);
+                    }
+                }
+            }
+        });
+    });
+}
+
+/// Send an event to every configured webhook now, whether or not its kind
+/// is enabled, and wait for delivery
+pub async fn send(event: &NotificationEvent) -> Result<(), String> {
+    let urls = active_settings().notifications.webhook_urls;
+    if urls.is_empty() {
+        return Err("No webhook URLs configured".to_string());
+    }
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let body = event.payload();
+    let mut errors = Vec::new();
+    for url in &urls {
+        if let Err(e) = post_with_retry(&client, url.trim(), &body).await {
+            errors.push(format!("{}: {}", webhook_host(url), e));
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(format!("Webhook delivery failed for {}", errors.join("; "))),
+    }
+}
+
+/// POST a JSON body, retrying network errors, 429s and server errors
+pub async fn post_with_retry(
+    client: &reqwest::Client,
+    url: &str,
+    body: &serde_json::Value,
+) -> Result<(), String> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        let error = match client.post(url).json(body).send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    return Err(format!("rejected with {}", status));
+                }
+                format!("responded with {}", status)
+            }
+            Err(e) => e.without_url().to_string(),
+        };
+
+        if attempt >= MAX_ATTEMPTS {
+            return Err(format!("{} (after {} attempts)", error, attempt));
+        }
+        tracing::debug!("Webhook {} {}, retrying in {:?}", webhook_host(url), error, backoff);
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// Host of a webhook URL, for logs: the rest of the URL can be a
+/// credential, as with Slack incoming webhooks
+fn webhook_host(url: &str) -> String {
+    reqwest::Url::parse(url.trim())
+        .ok()
+        .and_then(|url| url.host_str().map(String::from))
+        .unwrap_or_else(|| "an invalid URL".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_payload_and_filtering() {
+        let budget = NotificationEvent::BudgetExceeded {
+            date: "2026-03-02".to_string(),
+            cost: 31.5,
+            budget: 25.0,
+        };
+        let payload = budget.payload();
+        assert_eq!(payload["event"], "budget_exceeded");
+        assert_eq!(payload["cost"], 31.5);
+        assert_eq!(
+            payload["text"],
+            "Daily budget exceeded on 2026-03-02: $31.50 spent of $25.00"
+        );
+
+        let new_session = NotificationEvent::NewSession {
+            session_id: "notify-test-session".to_string(),
+            project_path: None,
+        };
+        let settings = NotificationSettings::default();
+        assert!(!new_session.is_enabled(&settings));
+
+        // Disabled events are dropped and each event goes out once
+        let events = vec![budget.clone(), new_session, budget.clone()];
+        assert_eq!(take_unsent(events, &settings), vec![budget.clone()]);
+        assert!(take_unsent(vec![budget], &settings).is_empty());
+    }
+
+    #[test]
+    fn test_webhook_host() {
+        assert_eq!(webhook_host(" https://hooks.slack.com/services/T0/B0/secret "), "hooks.slack.com");
+        assert_eq!(webhook_host("not a url"), "an invalid URL");
+    }
+}
@@ -0,0 +1,194 @@
+//! Session archive
+//!
+//! Old session files can be moved out of `~/.claude/projects` into a
+//! gzip-compressed archive that mirrors the projects layout, so archived
+//! sessions keep their project and subagent structure. Their parsed metrics
+//! stay in the database, and every reader in the parser opens files through
+//! [`open_session_file`], which decompresses `*.jsonl.gz` on the fly. Logs
+//! gzipped in place under `~/.claude/projects` are read the same way.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+/// Extension appended to archived session files
+const ARCHIVE_EXTENSION: &str = "gz";
+
+/// Default archive location, next to the analytics database
+pub fn default_archive_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("ironhide")
+        .join("archive")
+}
+
+/// Whether a session file is a compressed archive
+pub fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == ARCHIVE_EXTENSION)
+}
+
+/// Whether a path names a session log, plain or archived
+pub fn is_session_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name.ends_with(".jsonl") || name.ends_with(".jsonl.gz")
+}
+
+/// File name of a session log without its `.jsonl` / `.jsonl.gz` extension
+pub fn session_file_stem(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    Some(name.strip_suffix(".jsonl").unwrap_or(name))
+}
+
+/// Open a session file for line reading, decompressing archived files
+pub fn open_session_file(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    open_session_file_at(path, 0)
+}
+
+/// Open a session file positioned `offset` bytes into its (decompressed) content
+///
+/// Plain files seek; archives can't, so the leading bytes are decompressed
+/// and discarded. Archives don't grow, so this only happens once per parse.
+pub fn open_session_file_at(path: &Path, offset: u64) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    if is_compressed(path) {
+        let mut reader = BufReader::new(GzDecoder::new(file));
+        io::copy(&mut (&mut reader).take(offset), &mut io::sink())?;
+        Ok(Box::new(reader))
+    } else {
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(offset))?;
+        Ok(Box::new(reader))
+    }
+}
+
+/// Compress a session file under `projects_dir` into `archive_dir`
+///
+/// The archive keeps the file's path relative to the projects directory and
+/// its mtime, so metrics cached against the mtime stay valid. The original
+/// is removed once the archive is written. Returns the archive path.
+pub fn archive_file(path: &Path, projects_dir: &Path, archive_dir: &Path) -> io::Result<PathBuf> {
+    let relative = path.strip_prefix(projects_dir).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not under {}", path.display(), projects_dir.display()),
+        )
+    })?;
+    let mut target = archive_dir.join(relative).into_os_string();
+    target.push(".");
+    target.push(ARCHIVE_EXTENSION);
+    let target = PathBuf::from(target);
+
+    let modified = std::fs::metadata(path)?.modified()?;
+    move_file(path, &target, modified, |src, dst| {
+        let mut encoder = GzEncoder::new(dst, Compression::default());
+        io::copy(src, &mut encoder)?;
+        encoder.finish().map(|_| ())
+    })?;
+
+    Ok(target)
+}
+
+/// Decompress an archived session back to its place under `projects_dir`
+///
+/// Returns the restored path. Fails if a file already exists there, since a
+/// live session with the same name is newer than the archive.
+pub fn restore_file(archived: &Path, archive_dir: &Path, projects_dir: &Path) -> io::Result<PathBuf> {
+    let relative = archived.strip_prefix(archive_dir).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not in the archive", archived.display()),
+        )
+    })?;
+    let target = projects_dir.join(relative).with_extension("");
+    if target.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", target.display()),
+        ));
+    }
+
+    let modified = std::fs::metadata(archived)?.modified()?;
+    move_file(archived, &target, modified, |src, dst| {
+        io::copy(&mut GzDecoder::new(src), dst).map(|_| ())
+    })?;
+
+    Ok(target)
+}
+
+/// Write `target` from `source` through `convert`, then remove `source`
+///
+/// The output goes to a temporary file that is renamed into place, so an
+/// interrupted move never leaves a truncated session behind.
+fn move_file(
+    source: &Path,
+    target: &Path,
+    modified: std::time::SystemTime,
+    convert: impl FnOnce(&mut File, &mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let partial = target.with_extension("partial");
+
+    let result = (|| {
+        let mut src = File::open(source)?;
+        let mut dst = File::create(&partial)?;
+        convert(&mut src, &mut dst)?;
+        dst.set_modified(modified)?;
+        dst.sync_all()?;
+        std::fs::rename(&partial, target)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result?;
+
+    std::fs::remove_file(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_and_restore_round_trip() {
+        let root = std::env::temp_dir().join("ironhide_archive_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let projects = root.join("projects");
+        let archive = root.join("archive");
+        let session = projects.join("-home-me-app").join("s1.jsonl");
+        std::fs::create_dir_all(session.parent().unwrap()).unwrap();
+        let content = "{\"type\":\"user\"}\n{\"type\":\"assistant\"}\n";
+        std::fs::write(&session, content).unwrap();
+        let modified = std::fs::metadata(&session).unwrap().modified().unwrap();
+
+        let archived = archive_file(&session, &projects, &archive).unwrap();
+        assert_eq!(archived, archive.join("-home-me-app").join("s1.jsonl.gz"));
+        assert!(!session.exists());
+        assert!(is_compressed(&archived) && is_session_file(&archived));
+        assert_eq!(session_file_stem(&archived), Some("s1"));
+        assert_eq!(std::fs::metadata(&archived).unwrap().modified().unwrap(), modified);
+
+        // Archived content reads back transparently, from any offset
+        let lines: Vec<String> = open_session_file(&archived).unwrap().lines().map_while(Result::ok).collect();
+        assert_eq!(lines, vec!["{\"type\":\"user\"}", "{\"type\":\"assistant\"}"]);
+        let mut tail = String::new();
+        open_session_file_at(&archived, 16).unwrap().read_to_string(&mut tail).unwrap();
+        assert_eq!(tail, "{\"type\":\"assistant\"}\n");
+
+        let restored = restore_file(&archived, &archive, &projects).unwrap();
+        assert_eq!(restored, session);
+        assert_eq!(std::fs::read_to_string(&session).unwrap(), content);
+        assert!(!archived.exists());
+
+        // Files outside the projects directory are refused
+        assert!(archive_file(&root.join("other.jsonl"), &projects, &archive).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
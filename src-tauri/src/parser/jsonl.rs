@@ -1,19 +1,23 @@
 //! JSONL line parser
 //!
 //! Handles streaming parsing of Claude Code JSONL files with full entry parsing
-//! for all entry types: user, assistant, progress, summary, file-history-snapshot
+//! for all entry types: user, assistant, progress, summary, file-history-snapshot,
+//! system. Entries of types added to Claude Code after this parser was written
+//! are kept as [`EntryType::Unknown`] with their raw payload, and readers
+//! record them in [`ParseDiagnostics`] instead of dropping them silently.
 
-use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::collections::BTreeMap;
+use std::io::BufRead;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use super::ParserResult;
+use super::{ParserError, ParserResult};
 
 /// Entry types in Claude Code JSONL files
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum EntryType {
     User,
@@ -21,6 +25,8 @@ pub enum EntryType {
     Progress,
     Summary,
     FileHistorySnapshot,
+    /// Session events such as compaction boundaries (`subtype` says which)
+    System,
     #[serde(other)]
     Unknown,
 }
@@ -33,6 +39,7 @@ impl From<&str> for EntryType {
             "progress" => EntryType::Progress,
             "summary" => EntryType::Summary,
             "file-history-snapshot" => EntryType::FileHistorySnapshot,
+            "system" => EntryType::System,
             _ => EntryType::Unknown,
         }
     }
@@ -46,6 +53,7 @@ impl std::fmt::Display for EntryType {
             EntryType::Progress => write!(f, "progress"),
             EntryType::Summary => write!(f, "summary"),
             EntryType::FileHistorySnapshot => write!(f, "file-history-snapshot"),
+            EntryType::System => write!(f, "system"),
             EntryType::Unknown => write!(f, "unknown"),
         }
     }
@@ -144,6 +152,45 @@ pub struct ToolResult {
     pub is_error: bool,
 }
 
+/// Tag Claude Code wraps around the message of a rejected tool call
+const TOOL_USE_ERROR_TAG: &str = "tool_use_error";
+
+impl ToolResult {
+    /// Result content as text; text blocks are joined, other content is
+    /// kept as JSON
+    pub fn text(&self) -> String {
+        match &self.content {
+            Value::String(s) => s.clone(),
+            Value::Array(blocks) => {
+                let texts: Vec<&str> = blocks
+                    .iter()
+                    .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                    .collect();
+                if texts.len() == blocks.len() {
+                    texts.join("\n")
+                } else {
+                    self.content.to_string()
+                }
+            }
+            v => v.to_string(),
+        }
+    }
+
+    /// Whether the tool call failed: flagged `is_error`, or rejected before
+    /// running with a `<tool_use_error>` message
+    pub fn is_failure(&self) -> bool {
+        self.is_error || tool_error_message(&self.text()).is_some()
+    }
+}
+
+/// Message of a `<tool_use_error>`-wrapped tool result, if it is one
+pub fn tool_error_message(text: &str) -> Option<&str> {
+    let open = format!("<{}>", TOOL_USE_ERROR_TAG);
+    let close = format!("</{}>", TOOL_USE_ERROR_TAG);
+    let inner = text.trim().strip_prefix(open.as_str())?;
+    Some(inner.strip_suffix(close.as_str()).unwrap_or(inner).trim())
+}
+
 /// Message content - can be string or array of blocks
 #[derive(Debug, Clone)]
 pub enum MessageContent {
@@ -157,6 +204,9 @@ pub enum MessageContent {
 
 impl MessageContent {
     /// Extract from JSON value
+    ///
+    /// Blocks are deserialized from the value by reference, without copying
+    /// it first.
     pub fn from_value(value: &Value) -> Option<Self> {
         if let Some(s) = value.as_str() {
             return Some(MessageContent::Text(s.to_string()));
@@ -167,7 +217,7 @@ impl MessageContent {
             if arr.first().and_then(|v| v.get("type")).and_then(|t| t.as_str()) == Some("tool_result") {
                 let results: Vec<ToolResult> = arr
                     .iter()
-                    .filter_map(|v| serde_json::from_value(v.clone()).ok())
+                    .filter_map(|v| ToolResult::deserialize(v).ok())
                     .collect();
                 if !results.is_empty() {
                     return Some(MessageContent::ToolResults(results));
@@ -177,7 +227,7 @@ impl MessageContent {
             // Otherwise it's content blocks
             let blocks: Vec<ContentBlock> = arr
                 .iter()
-                .filter_map(|v| serde_json::from_value(v.clone()).ok())
+                .filter_map(|v| ContentBlock::deserialize(v).ok())
                 .collect();
             if !blocks.is_empty() {
                 return Some(MessageContent::Blocks(blocks));
@@ -225,6 +275,20 @@ impl MessageContent {
         }
     }
 
+    /// Characters of thinking text across the thinking blocks
+    pub fn thinking_chars(&self) -> usize {
+        match self {
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .map(|b| match b {
+                    ContentBlock::Thinking { thinking } => thinking.chars().count(),
+                    _ => 0,
+                })
+                .sum(),
+            _ => 0,
+        }
+    }
+
     /// Check if content contains tool results (indicating this is a tool result message)
     pub fn is_tool_result(&self) -> bool {
         matches!(self, MessageContent::ToolResults(_))
@@ -239,6 +303,18 @@ pub struct ThinkingMetadata {
     pub disabled: bool,
 }
 
+impl ThinkingMetadata {
+    /// Thinking mode the prompt was sent with: the level, or "off" when
+    /// thinking was disabled
+    pub fn mode(&self) -> Option<String> {
+        if self.disabled {
+            Some("off".to_string())
+        } else {
+            self.level.clone()
+        }
+    }
+}
+
 /// A fully parsed JSONL entry
 #[derive(Debug, Clone)]
 pub struct Entry {
@@ -314,12 +390,357 @@ impl Entry {
             .map(|c| c.tool_uses())
             .unwrap_or_default()
     }
+
+    /// The compaction recorded by a `compact_boundary` system entry
+    pub fn compact_boundary(&self) -> Option<CompactBoundary> {
+        if self.entry_type != EntryType::System
+            || self.raw.get("subtype").and_then(|s| s.as_str()) != Some("compact_boundary")
+        {
+            return None;
+        }
+        let metadata = self.raw.get("compactMetadata");
+        Some(CompactBoundary {
+            timestamp: self.timestamp.clone(),
+            trigger: metadata
+                .and_then(|m| m.get("trigger"))
+                .and_then(|t| t.as_str())
+                .map(String::from),
+            pre_tokens: metadata.and_then(|m| m.get("preTokens")).and_then(|t| t.as_u64()),
+            post_tokens: None,
+        })
+    }
+
+    /// The API error recorded by an `api_error` system entry (written each
+    /// time Claude Code retries a failed request) or by the error message an
+    /// assistant entry shows once retries run out
+    pub fn api_error(&self) -> Option<ApiErrorEvent> {
+        match self.entry_type {
+            EntryType::System
+                if self.raw.get("subtype").and_then(|s| s.as_str()) == Some("api_error") =>
+            {
+                let error = self.raw.get("error");
+                let status = error
+                    .and_then(|e| e.get("status"))
+                    .and_then(|s| s.as_u64())
+                    .and_then(|s| u16::try_from(s).ok());
+                let text = error.map(Value::to_string).unwrap_or_default();
+                Some(ApiErrorEvent {
+                    timestamp: self.timestamp.clone(),
+                    kind: ApiErrorKind::classify(status, &text),
+                    status,
+                    retry_attempt: self
+                        .raw
+                        .get("retryAttempt")
+                        .and_then(|a| a.as_u64())
+                        .map(|a| a as u32),
+                    retry_in_ms: self.raw.get("retryInMs").and_then(|ms| ms.as_f64()).map(|ms| ms as u64),
+                    retries_exhausted: false,
+                })
+            }
+            EntryType::Assistant
+                if self.raw.get("isApiErrorMessage").and_then(|e| e.as_bool()) == Some(true) =>
+            {
+                let mut text = self
+                    .message_content
+                    .as_ref()
+                    .and_then(|c| c.as_text())
+                    .unwrap_or_default();
+                if let Some(error) = self.raw.get("error").and_then(|e| e.as_str()) {
+                    text.push(' ');
+                    text.push_str(error);
+                }
+                // "API Error: 529 {...}"
+                let status = text
+                    .strip_prefix("API Error: ")
+                    .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+                    .and_then(|code| code.parse().ok());
+                Some(ApiErrorEvent {
+                    timestamp: self.timestamp.clone(),
+                    kind: ApiErrorKind::classify(status, &text),
+                    status,
+                    retry_attempt: None,
+                    retry_in_ms: None,
+                    retries_exhausted: true,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The entry's `type` as written, with the `subtype` of system entries
+    /// (e.g. `system/compact_boundary`)
+    pub fn type_label(&self) -> String {
+        let entry_type = self.raw.get("type").and_then(|t| t.as_str()).unwrap_or("(none)");
+        match self.raw.get("subtype").and_then(|t| t.as_str()) {
+            Some(subtype) => format!("{}/{}", entry_type, subtype),
+            None => entry_type.to_string(),
+        }
+    }
+}
+
+/// A conversation compaction, from the boundary marker Claude Code writes
+/// when it summarizes the conversation to free up context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactBoundary {
+    pub timestamp: Option<String>,
+    /// "auto" when Claude Code hit the context limit, "manual" for /compact
+    pub trigger: Option<String>,
+    /// Context tokens just before compacting
+    pub pre_tokens: Option<u64>,
+    /// Context tokens of the first request after compacting; filled in by the
+    /// turn aggregator
+    pub post_tokens: Option<u64>,
+}
+
+impl CompactBoundary {
+    /// Context tokens dropped by the compaction, when both sides are known
+    pub fn tokens_lost(&self) -> Option<u64> {
+        Some(self.pre_tokens?.saturating_sub(self.post_tokens?))
+    }
+}
+
+/// What kind of API error a request failed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorKind {
+    /// 429 responses and plan usage limits
+    RateLimit,
+    /// 529 responses: the API is overloaded
+    Overloaded,
+    /// Server errors, timeouts and connection failures
+    Other,
+}
+
+impl ApiErrorKind {
+    /// Classify from the HTTP status, or failing that the error text
+    pub fn classify(status: Option<u16>, text: &str) -> Self {
+        let text = text.to_ascii_lowercase();
+        if status == Some(429)
+            || text.contains("rate_limit")
+            || text.contains("rate limit")
+            || text.contains("usage limit")
+        {
+            Self::RateLimit
+        } else if status == Some(529) || text.contains("overloaded") {
+            Self::Overloaded
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RateLimit => "rate_limit",
+            Self::Overloaded => "overloaded",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// A failed API request, as recorded in the session file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiErrorEvent {
+    pub timestamp: Option<String>,
+    pub kind: ApiErrorKind,
+    /// HTTP status, when the error carries one
+    pub status: Option<u16>,
+    /// Which retry this error led to (1 for the first)
+    pub retry_attempt: Option<u32>,
+    /// How long Claude Code waited before retrying
+    pub retry_in_ms: Option<u64>,
+    /// Whether Claude Code gave up and showed the error
+    pub retries_exhausted: bool,
+}
+
+/// Maximum entries kept in [`ParseDiagnostics::entries`]; counting goes on past it
+const MAX_DIAGNOSTIC_ENTRIES: usize = 100;
+
+/// Bytes of a malformed line kept as its payload
+const MAX_MALFORMED_PAYLOAD: usize = 2_000;
+
+/// Content block types that are valid but carry nothing the parser uses
+const UNMODELLED_BLOCK_TYPES: &[&str] = &["image", "document", "redacted_thinking"];
+
+/// Why an entry didn't make it into turn aggregation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum UnparsedKind {
+    /// The line isn't valid JSON
+    Malformed,
+    /// The entry has a `type` this parser doesn't know
+    UnknownType,
+    /// Message content blocks or tool results in a shape the parser doesn't know
+    UnknownContent,
+}
+
+/// An entry that was skipped or only partly understood
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct UnparsedEntry {
+    /// Byte offset of the line in the (decompressed) file
+    pub offset: u64,
+    pub kind: UnparsedKind,
+    /// See [`Entry::type_label`]; None for malformed lines
+    pub entry_type: Option<String>,
+    pub timestamp: Option<String>,
+    /// JSON error for malformed lines
+    pub error: Option<String>,
+    /// The entry as written, or the (truncated) line text if it isn't JSON
+    pub raw: Value,
+}
+
+/// Entries a reader couldn't use, collected while reading a session file
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ParseDiagnostics {
+    /// Non-empty lines read
+    pub total_lines: u32,
+    pub malformed_lines: u32,
+    pub unknown_entries: u32,
+    /// Entries of known types that hold no turn data (summaries, file
+    /// history snapshots, system events other than compactions and API errors)
+    pub ignored_entries: u32,
+    pub unknown_content_blocks: u32,
+    /// Count of unknown and ignored entries by type label
+    pub entry_types: BTreeMap<String, u32>,
+    /// Malformed, unknown and partly understood entries, up to
+    /// [`MAX_DIAGNOSTIC_ENTRIES`] of them
+    pub entries: Vec<UnparsedEntry>,
+}
+
+impl ParseDiagnostics {
+    /// Record an entry read from the line at `offset`
+    pub fn record_entry(&mut self, offset: u64, entry: &Entry) {
+        self.total_lines += 1;
+        let kind = match entry.entry_type {
+            EntryType::Unknown => {
+                self.unknown_entries += 1;
+                *self.entry_types.entry(entry.type_label()).or_insert(0) += 1;
+                UnparsedKind::UnknownType
+            }
+            // Compaction boundaries and API errors are attached to turns
+            EntryType::System if entry.compact_boundary().is_some() || entry.api_error().is_some() => {
+                return
+            }
+            EntryType::Summary | EntryType::FileHistorySnapshot | EntryType::System => {
+                self.ignored_entries += 1;
+                *self.entry_types.entry(entry.type_label()).or_insert(0) += 1;
+                return;
+            }
+            EntryType::User | EntryType::Assistant | EntryType::Progress => {
+                let unknown = count_unknown_content(&entry.raw);
+                if unknown == 0 {
+                    return;
+                }
+                self.unknown_content_blocks += unknown;
+                UnparsedKind::UnknownContent
+            }
+        };
+
+        if self.entries.len() < MAX_DIAGNOSTIC_ENTRIES {
+            self.entries.push(UnparsedEntry {
+                offset,
+                kind,
+                entry_type: Some(entry.type_label()),
+                timestamp: entry.timestamp.clone(),
+                error: None,
+                raw: entry.raw.clone(),
+            });
+        }
+    }
+
+    /// Record a line at `offset` that failed to parse
+    pub fn record_malformed(&mut self, offset: u64, line: &str, error: &ParserError) {
+        self.total_lines += 1;
+        self.malformed_lines += 1;
+        if self.entries.len() < MAX_DIAGNOSTIC_ENTRIES {
+            self.entries.push(UnparsedEntry {
+                offset,
+                kind: UnparsedKind::Malformed,
+                entry_type: None,
+                timestamp: None,
+                error: Some(error.to_string()),
+                raw: Value::String(super::truncate_str(line, MAX_MALFORMED_PAYLOAD).to_string()),
+            });
+        }
+    }
+
+    /// Whether every line was understood (ignored entry types aside)
+    pub fn is_clean(&self) -> bool {
+        self.malformed_lines == 0 && self.unknown_entries == 0 && self.unknown_content_blocks == 0
+    }
+}
+
+/// Count message content items that [`MessageContent::from_value`] drops or
+/// reads as [`ContentBlock::Unknown`]
+///
+/// Checks the fields the serde models require rather than deserializing
+/// again, since this runs on every entry.
+fn count_unknown_content(raw: &Value) -> u32 {
+    let items = match raw
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+    {
+        Some(items) => items,
+        None => return 0,
+    };
+    let has_str = |item: &Value, field: &str| item.get(field).is_some_and(Value::is_string);
+
+    items
+        .iter()
+        .filter(|item| match item.get("type").and_then(|t| t.as_str()) {
+            Some("text") => !has_str(item, "text"),
+            Some("thinking") => !has_str(item, "thinking"),
+            Some("tool_use") => !(has_str(item, "id") && has_str(item, "name") && item.get("input").is_some()),
+            Some("tool_result") => !(has_str(item, "tool_use_id") && item.get("content").is_some()),
+            Some(block_type) => !UNMODELLED_BLOCK_TYPES.contains(&block_type),
+            None => true,
+        })
+        .count() as u32
+}
+
+/// Decodes JSONL lines into JSON values
+///
+/// Uses serde_json, or simd-json with the `simd-json` feature. simd-json
+/// parses in place, so each line is first copied into a scratch buffer that
+/// is reused from line to line; the caller's line is left intact for
+/// diagnostics.
+#[derive(Debug, Default)]
+pub struct LineDecoder {
+    #[cfg(feature = "simd-json")]
+    scratch: Vec<u8>,
+}
+
+impl LineDecoder {
+    /// Decode one line (without its newline)
+    pub fn decode(&mut self, line: &[u8]) -> ParserResult<Value> {
+        #[cfg(feature = "simd-json")]
+        {
+            self.scratch.clear();
+            self.scratch.extend_from_slice(line);
+            Ok(simd_json::serde::from_slice(&mut self.scratch)?)
+        }
+        #[cfg(not(feature = "simd-json"))]
+        {
+            Ok(serde_json::from_slice(line)?)
+        }
+    }
+
+    /// Decode one line into a fully structured Entry
+    pub fn parse(&mut self, line: &[u8]) -> ParserResult<Entry> {
+        self.decode(line).map(entry_from_value)
+    }
 }
 
 /// Parse a single JSONL line into a fully structured Entry
 pub fn parse_line(line: &str) -> ParserResult<Entry> {
-    let value: Value = serde_json::from_str(line)?;
+    LineDecoder::default().parse(line.as_bytes())
+}
 
+/// Build an Entry from a decoded JSONL line
+fn entry_from_value(value: Value) -> Entry {
     let entry_type = value
         .get("type")
         .and_then(|t| t.as_str())
@@ -367,7 +788,7 @@ pub fn parse_line(line: &str) -> ParserResult<Entry> {
         .map(String::from);
     let thinking_metadata = value
         .get("thinkingMetadata")
-        .and_then(|v| serde_json::from_value(v.clone()).ok());
+        .and_then(|v| ThinkingMetadata::deserialize(v).ok());
 
     // Message fields
     let message = value.get("message");
@@ -421,7 +842,7 @@ pub fn parse_line(line: &str) -> ParserResult<Entry> {
     // File history snapshot
     let snapshot = value.get("snapshot").cloned();
 
-    Ok(Entry {
+    Entry {
         entry_type,
         uuid,
         parent_uuid,
@@ -449,13 +870,14 @@ pub fn parse_line(line: &str) -> ParserResult<Entry> {
         leaf_uuid,
         snapshot,
         raw: value,
-    })
+    }
 }
 
 /// Incremental JSONL reader for streaming large files
 pub struct IncrementalReader {
     path: std::path::PathBuf,
     position: u64,
+    diagnostics: ParseDiagnostics,
 }
 
 impl IncrementalReader {
@@ -464,6 +886,7 @@ impl IncrementalReader {
         Self {
             path: path.as_ref().to_path_buf(),
             position: 0,
+            diagnostics: ParseDiagnostics::default(),
         }
     }
 
@@ -472,39 +895,64 @@ impl IncrementalReader {
         Self {
             path: path.as_ref().to_path_buf(),
             position,
+            diagnostics: ParseDiagnostics::default(),
         }
     }
 
     /// Read new lines from the file since last read
     pub fn read_new_lines(&mut self) -> ParserResult<Vec<Entry>> {
-        let file = File::open(&self.path)?;
-        let mut reader = BufReader::new(file);
+        self.read_lines(None, false)
+    }
+
+    /// Read newly appended lines, leaving a trailing partial line unread
+    ///
+    /// Used when tailing a file that is still being written: a line without
+    /// its terminating newline is picked up whole on the next call. If `limit`
+    /// is given, reading stops once the position reaches that byte offset.
+    pub fn read_complete_lines(&mut self, limit: Option<u64>) -> ParserResult<Vec<Entry>> {
+        self.read_lines(limit, true)
+    }
 
-        // Seek to last known position
-        reader.seek(SeekFrom::Start(self.position))?;
+    fn read_lines(&mut self, limit: Option<u64>, complete_only: bool) -> ParserResult<Vec<Entry>> {
+        // Start at the last known position
+        let mut reader = super::archive::open_session_file_at(&self.path, self.position)?;
 
         let mut entries = Vec::new();
-        let mut line = String::new();
+        let mut decoder = LineDecoder::default();
+        let mut line = Vec::new();
 
         loop {
+            if limit.is_some_and(|limit| self.position >= limit) {
+                break;
+            }
+
             line.clear();
-            let bytes_read = reader.read_line(&mut line)?;
+            let bytes_read = reader.read_until(b'\n', &mut line)?;
 
             if bytes_read == 0 {
                 break;
             }
 
+            if complete_only && line.last() != Some(&b'\n') {
+                break;
+            }
+
+            let offset = self.position;
             self.position += bytes_read as u64;
 
-            let trimmed = line.trim();
+            let trimmed = line.trim_ascii();
             if trimmed.is_empty() {
                 continue;
             }
 
-            match parse_line(trimmed) {
-                Ok(entry) => entries.push(entry),
+            match decoder.parse(trimmed) {
+                Ok(entry) => {
+                    self.diagnostics.record_entry(offset, &entry);
+                    entries.push(entry);
+                }
                 Err(e) => {
                     tracing::warn!("Failed to parse line: {}", e);
+                    self.diagnostics.record_malformed(offset, &String::from_utf8_lossy(trimmed), &e);
                     continue;
                 }
             }
@@ -518,9 +966,20 @@ impl IncrementalReader {
         self.position
     }
 
+    /// Path of the file being read
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Unparsed and ignored entries among the lines read so far
+    pub fn diagnostics(&self) -> &ParseDiagnostics {
+        &self.diagnostics
+    }
+
     /// Reset position to beginning
     pub fn reset(&mut self) {
         self.position = 0;
+        self.diagnostics = ParseDiagnostics::default();
     }
 }
 
@@ -532,32 +991,169 @@ pub fn read_all_entries<P: AsRef<Path>>(path: P) -> ParserResult<Vec<Entry>> {
 
 /// Streaming parser that calls a callback for each entry
 /// This is memory-efficient for very large files
-pub fn parse_streaming<P, F>(path: P, mut callback: F) -> ParserResult<()>
+///
+/// Returns the diagnostics for lines that weren't passed to the callback or
+/// weren't fully understood.
+pub fn parse_streaming<P, F>(path: P, mut callback: F) -> ParserResult<ParseDiagnostics>
 where
     P: AsRef<Path>,
     F: FnMut(Entry),
 {
-    let file = File::open(path.as_ref())?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line?;
-        let trimmed = line.trim();
+    let mut reader = super::archive::open_session_file(path.as_ref())?;
+    let mut diagnostics = ParseDiagnostics::default();
+    let mut decoder = LineDecoder::default();
+    let mut line = Vec::new();
+    let mut offset = 0u64;
+
+    loop {
+        line.clear();
+        let bytes_read = reader.read_until(b'\n', &mut line)?;
+        if bytes_read == 0 {
+            break;
+        }
+        let line_offset = offset;
+        offset += bytes_read as u64;
 
+        let trimmed = line.trim_ascii();
         if trimmed.is_empty() {
             continue;
         }
 
-        match parse_line(trimmed) {
-            Ok(entry) => callback(entry),
+        match decoder.parse(trimmed) {
+            Ok(entry) => {
+                diagnostics.record_entry(line_offset, &entry);
+                callback(entry);
+            }
             Err(e) => {
                 tracing::warn!("Failed to parse line: {}", e);
+                diagnostics.record_malformed(line_offset, &String::from_utf8_lossy(trimmed), &e);
                 continue;
             }
         }
     }
 
-    Ok(())
+    Ok(diagnostics)
+}
+
+/// One line of a session file as written, for debugging
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct RawEntry {
+    /// Position among the file's non-empty lines, from 0
+    pub index: u32,
+    /// Byte offset of the line in the (decompressed) file
+    pub offset: u64,
+    /// How the parser classified the entry; None for malformed lines
+    pub entry_type: Option<EntryType>,
+    /// See [`Entry::type_label`]; None for malformed lines
+    pub type_label: Option<String>,
+    pub uuid: Option<String>,
+    pub parent_uuid: Option<String>,
+    pub timestamp: Option<String>,
+    /// JSON error for malformed lines
+    pub error: Option<String>,
+    /// The entry as written, or the (truncated) line text if it isn't JSON
+    pub raw: Value,
+}
+
+impl RawEntry {
+    /// Drop message text, tool payloads and summaries from the entry,
+    /// keeping its type, IDs and usage
+    pub fn scrub_content(&mut self) {
+        match &mut self.raw {
+            Value::Object(map) => {
+                for key in SCRUBBED_RAW_KEYS {
+                    if let Some(value) = map.get_mut(*key) {
+                        *value = Value::Null;
+                    }
+                }
+                if let Some(content) = map.get_mut("message").and_then(|m| m.get_mut("content")) {
+                    *content = Value::Null;
+                }
+            }
+            // Malformed lines are shown as text, which may hold anything
+            raw => *raw = Value::Null,
+        }
+    }
+}
+
+/// Top-level entry fields that can carry conversation text
+const SCRUBBED_RAW_KEYS: &[&str] = &["content", "toolUseResult", "summary", "data", "snapshot"];
+
+/// A page of a session file's entries
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct RawEntryPage {
+    /// Non-empty lines in the file
+    pub total: u32,
+    pub entries: Vec<RawEntry>,
+}
+
+/// Read `limit` entries of a session file, starting at the `skip`th
+/// non-empty line
+///
+/// Lines before and after the page are counted but not decoded. In privacy
+/// mode the entries' content is scrubbed.
+pub fn read_raw_entries<P: AsRef<Path>>(path: P, skip: usize, limit: usize) -> ParserResult<RawEntryPage> {
+    let scrub_content = crate::settings::privacy_mode();
+    let mut reader = super::archive::open_session_file(path.as_ref())?;
+    let mut page = RawEntryPage::default();
+    let mut decoder = LineDecoder::default();
+    let mut line = Vec::new();
+    let mut offset = 0u64;
+
+    loop {
+        line.clear();
+        let bytes_read = reader.read_until(b'\n', &mut line)?;
+        if bytes_read == 0 {
+            break;
+        }
+        let line_offset = offset;
+        offset += bytes_read as u64;
+
+        let trimmed = line.trim_ascii();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let index = page.total as usize;
+        page.total += 1;
+        if index < skip || index >= skip.saturating_add(limit) {
+            continue;
+        }
+
+        let mut entry = match decoder.parse(trimmed) {
+            Ok(entry) => RawEntry {
+                index: index as u32,
+                offset: line_offset,
+                type_label: Some(entry.type_label()),
+                entry_type: Some(entry.entry_type),
+                uuid: entry.uuid,
+                parent_uuid: entry.parent_uuid,
+                timestamp: entry.timestamp,
+                error: None,
+                raw: entry.raw,
+            },
+            Err(e) => RawEntry {
+                index: index as u32,
+                offset: line_offset,
+                entry_type: None,
+                type_label: None,
+                uuid: None,
+                parent_uuid: None,
+                timestamp: None,
+                error: Some(e.to_string()),
+                raw: Value::String(
+                    super::truncate_str(&String::from_utf8_lossy(trimmed), MAX_MALFORMED_PAYLOAD).to_string(),
+                ),
+            },
+        };
+        if scrub_content {
+            entry.scrub_content();
+        }
+        page.entries.push(entry);
+    }
+
+    Ok(page)
 }
 
 /// Legacy ParsedEntry type for backward compatibility
@@ -707,6 +1303,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tool_result_failures() {
+        let result = |content: Value, is_error: bool| ToolResult {
+            tool_use_id: "toolu_1".to_string(),
+            content,
+            is_error,
+        };
+
+        let failed = result(
+            serde_json::json!([{"type": "text", "text": "Exit code 1"}, {"type": "text", "text": "No such file"}]),
+            true,
+        );
+        assert_eq!(failed.text(), "Exit code 1\nNo such file");
+        assert!(failed.is_failure());
+
+        // Rejected calls aren't always flagged is_error
+        let rejected = result(
+            Value::String("<tool_use_error>File has not been read yet</tool_use_error>".to_string()),
+            false,
+        );
+        assert!(rejected.is_failure());
+        assert_eq!(tool_error_message(&rejected.text()), Some("File has not been read yet"));
+
+        let ok = result(Value::String("On branch main".to_string()), false);
+        assert!(!ok.is_failure());
+        let image = result(serde_json::json!([{"type": "image", "source": {}}]), false);
+        assert!(image.text().starts_with('['));
+    }
+
     #[test]
     fn test_parse_subagent_entry() {
         let line = r#"{
@@ -795,6 +1420,7 @@ mod tests {
             EntryType::from("file-history-snapshot"),
             EntryType::FileHistorySnapshot
         );
+        assert_eq!(EntryType::from("system"), EntryType::System);
         assert_eq!(EntryType::from("something-else"), EntryType::Unknown);
     }
 
@@ -819,4 +1445,119 @@ mod tests {
         let entry = parse_line(line).unwrap();
         assert_eq!(entry.entry_type, EntryType::Unknown);
     }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let path = std::env::temp_dir().join("ironhide_parse_diagnostics_test.jsonl");
+        let lines = [
+            r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"Hi"}}"#,
+            r#"{"type":"system","subtype":"compact_boundary","uuid":"s1"}"#,
+            r#"{"type":"future-entry","uuid":"f1","payload":{"x":1}}"#,
+            "{not json",
+            r#"{"type":"assistant","uuid":"a1","message":{"role":"assistant","content":[{"type":"text","text":"ok"},{"type":"image","source":{}},{"type":"hologram","data":"?"}]}}"#,
+            r#"{"type":"summary","summary":"Greeting","leafUuid":"a1"}"#,
+        ];
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let mut seen = Vec::new();
+        let diagnostics = parse_streaming(&path, |entry| seen.push(entry.entry_type)).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        // Unknown entries still reach the callback, with their payload
+        assert_eq!(seen.len(), 5);
+        assert!(seen.contains(&EntryType::Unknown) && seen.contains(&EntryType::System));
+
+        assert_eq!(diagnostics.total_lines, 6);
+        assert_eq!(diagnostics.malformed_lines, 1);
+        assert_eq!(diagnostics.unknown_entries, 1);
+        assert_eq!(diagnostics.ignored_entries, 1);
+        assert_eq!(diagnostics.unknown_content_blocks, 1);
+        assert_eq!(diagnostics.entry_types.get("summary"), Some(&1));
+        assert_eq!(diagnostics.entry_types.get("system/compact_boundary"), None);
+        assert_eq!(diagnostics.entry_types.get("future-entry"), Some(&1));
+        assert!(!diagnostics.is_clean());
+
+        let kinds: Vec<UnparsedKind> = diagnostics.entries.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![UnparsedKind::UnknownType, UnparsedKind::Malformed, UnparsedKind::UnknownContent]
+        );
+        let unknown = &diagnostics.entries[0];
+        assert_eq!(unknown.offset, (lines[0].len() + lines[1].len() + 2) as u64);
+        assert_eq!(unknown.raw["payload"]["x"], 1);
+        assert_eq!(diagnostics.entries[1].raw, "{not json");
+    }
+
+    #[test]
+    fn test_read_raw_entries() {
+        let path = std::env::temp_dir().join("ironhide_raw_entries_test.jsonl");
+        let lines = [
+            r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"Hi"}}"#,
+            "",
+            r#"{"type":"system","subtype":"compact_boundary","uuid":"s1","parentUuid":"u1"}"#,
+            "{not json",
+            r#"{"type":"assistant","uuid":"a1","timestamp":"2026-01-14T07:44:30.000Z"}"#,
+        ];
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let page = read_raw_entries(&path, 1, 2).unwrap();
+        let past_end = read_raw_entries(&path, 10, 5).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        // Empty lines aren't counted
+        assert_eq!(page.total, 4);
+        assert_eq!(page.entries.len(), 2);
+        let system = &page.entries[0];
+        assert_eq!(system.index, 1);
+        assert_eq!(system.offset, (lines[0].len() + 2) as u64);
+        assert_eq!(system.entry_type, Some(EntryType::System));
+        assert_eq!(system.type_label.as_deref(), Some("system/compact_boundary"));
+        assert_eq!(system.parent_uuid.as_deref(), Some("u1"));
+        let malformed = &page.entries[1];
+        assert_eq!(malformed.entry_type, None);
+        assert!(malformed.error.is_some());
+        assert_eq!(malformed.raw, "{not json");
+
+        assert_eq!(past_end.total, 4);
+        assert!(past_end.entries.is_empty());
+    }
+
+    #[test]
+    fn test_raw_entry_scrub_content() {
+        let path = std::env::temp_dir().join("ironhide_raw_entries_scrub_test.jsonl");
+        let lines = [
+            r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"Secret plan"},"toolUseResult":{"stdout":"ok"}}"#,
+            r#"{"type":"summary","summary":"Secret plan","leafUuid":"u1"}"#,
+            "{not json",
+        ];
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+        let mut page = read_raw_entries(&path, 0, 3).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        page.entries.iter_mut().for_each(RawEntry::scrub_content);
+        let user = &page.entries[0];
+        assert_eq!(user.uuid.as_deref(), Some("u1"));
+        assert_eq!(user.raw["message"]["role"], "user");
+        assert!(user.raw["message"]["content"].is_null());
+        assert!(user.raw["toolUseResult"].is_null());
+        assert!(page.entries[1].raw["summary"].is_null());
+        assert_eq!(page.entries[1].raw["leafUuid"], "u1");
+        assert!(page.entries[2].raw.is_null());
+    }
+
+    #[test]
+    fn test_invalid_utf8_line_is_malformed() {
+        let path = std::env::temp_dir().join("ironhide_parse_utf8_test.jsonl");
+        let mut bytes = br#"{"type":"user","uuid":"u1"}"#.to_vec();
+        bytes.extend_from_slice(b"\n{\"type\":\"user\",\"uuid\":\"\xff\"}\n");
+        bytes.extend_from_slice(br#"{"type":"summary","summary":"Done"}"#);
+        std::fs::write(&path, bytes).unwrap();
+
+        // One bad line doesn't end the read
+        let mut reader = IncrementalReader::new(&path);
+        let entries = reader.read_new_lines().unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(reader.diagnostics().malformed_lines, 1);
+    }
 }
@@ -5,9 +5,20 @@
 //! - Full entry type validation and field extraction
 //! - Turn aggregation from entries (user -> assistant cycles)
 //! - Session file discovery across Claude projects
+//! - Project path decoding for macOS, Linux and Windows
+//! - Registry of resolved project paths, for names decoding can't tell apart
+//! - Compressed archiving of old session files
+//! - Conversation transcripts for display
+//! - Canonical tool names and MCP server/tool splitting
 
+pub mod archive;
 pub mod jsonl;
+pub mod project_path;
+pub mod registry;
+pub mod scan;
 pub mod session;
+pub mod tool_names;
+pub mod transcript;
 
 use std::path::PathBuf;
 
@@ -15,10 +26,12 @@ use thiserror::Error;
 
 // Re-export commonly used types
 pub use jsonl::{
-    ContentBlock, Entry, EntryType, IncrementalReader, MessageContent, ParsedEntry,
-    ThinkingMetadata, ToolResult, Usage,
+    ApiErrorEvent, ApiErrorKind, CompactBoundary, ContentBlock, Entry, EntryType, IncrementalReader, MessageContent,
+    ParseDiagnostics, ParsedEntry, RawEntry, RawEntryPage, ThinkingMetadata, ToolResult, UnparsedEntry, UnparsedKind,
+    Usage,
 };
-pub use session::{CompletedTurn, SessionStats, ToolUse, TurnAggregator};
+pub use scan::{scan_claude_sessions, ImportSource};
+pub use session::{CompletedTurn, IncrementalSessionParser, SessionStats, ToolUse, TurnAggregator};
 
 /// Parser errors
 #[derive(Error, Debug)]
@@ -29,6 +42,10 @@ pub enum ParserError {
     #[error("JSON parse error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[cfg(feature = "simd-json")]
+    #[error("JSON parse error: {0}")]
+    SimdJson(#[from] simd_json::Error),
+
     #[error("Invalid entry: {0}")]
     InvalidEntry(String),
 
@@ -69,167 +86,12 @@ pub struct SessionFileInfo {
     pub size: u64,
     /// Whether this is a subagent file
     pub is_subagent: bool,
-}
-
-/// Scan for all Claude Code session files
-///
-/// Looks for JSONL files in:
-/// - ~/.claude/projects/*/*.jsonl (main session files)
-/// - ~/.claude/projects/*/<session-id>/subagents/*.jsonl (subagent files)
-/// - ~/.claude/history.jsonl (global history)
-///
-/// Returns files sorted by modification time (most recent first)
-pub fn scan_claude_sessions() -> Vec<SessionFileInfo> {
-    let mut sessions = Vec::new();
-
-    // Get home directory
-    let home = match dirs::home_dir() {
-        Some(h) => h,
-        None => {
-            tracing::warn!("Could not determine home directory");
-            return sessions;
-        }
-    };
-
-    let claude_dir = home.join(".claude");
-    if !claude_dir.exists() {
-        tracing::info!("Claude directory not found: {:?}", claude_dir);
-        return sessions;
-    }
-
-    // Scan projects directory
-    let projects_dir = claude_dir.join("projects");
-    if projects_dir.exists() {
-        scan_projects_directory(&projects_dir, &mut sessions);
-    }
-
-    // Check for global history
-    let history_file = claude_dir.join("history.jsonl");
-    if history_file.exists() {
-        if let Some(info) = create_session_info(&history_file, None, false) {
-            sessions.push(info);
-        }
-    }
-
-    // Sort by modification time (most recent first)
-    sessions.sort_by(|a, b| b.modified.cmp(&a.modified));
-
-    sessions
-}
-
-/// Scan the projects directory for session files
-fn scan_projects_directory(projects_dir: &std::path::Path, sessions: &mut Vec<SessionFileInfo>) {
-    let entries = match std::fs::read_dir(projects_dir) {
-        Ok(e) => e,
-        Err(e) => {
-            tracing::warn!("Failed to read projects directory: {}", e);
-            return;
-        }
-    };
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
-        }
-
-        // This is a project directory (encoded path)
-        let project_path = decode_project_path(&path);
-
-        // Look for session JSONL files in this directory
-        if let Ok(dir_entries) = std::fs::read_dir(&path) {
-            for file_entry in dir_entries.flatten() {
-                let file_path = file_entry.path();
-
-                if file_path.is_file() {
-                    // Main session file (UUID.jsonl)
-                    if let Some(ext) = file_path.extension() {
-                        if ext == "jsonl" {
-                            if let Some(info) =
-                                create_session_info(&file_path, project_path.clone(), false)
-                            {
-                                sessions.push(info);
-                            }
-                        }
-                    }
-                } else if file_path.is_dir() {
-                    // This might be a session directory with subagents
-                    let subagents_dir = file_path.join("subagents");
-                    if subagents_dir.exists() {
-                        scan_subagents_directory(&subagents_dir, project_path.clone(), sessions);
-                    }
-                }
-            }
-        }
-    }
-}
-
-/// Scan a subagents directory for agent session files
-fn scan_subagents_directory(
-    subagents_dir: &std::path::Path,
-    project_path: Option<String>,
-    sessions: &mut Vec<SessionFileInfo>,
-) {
-    if let Ok(entries) = std::fs::read_dir(subagents_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                if let Some(ext) = path.extension() {
-                    if ext == "jsonl" {
-                        if let Some(info) = create_session_info(&path, project_path.clone(), true) {
-                            sessions.push(info);
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
-
-/// Create session info from a file path
-fn create_session_info(
-    path: &std::path::Path,
-    project_path: Option<String>,
-    is_subagent: bool,
-) -> Option<SessionFileInfo> {
-    let metadata = std::fs::metadata(path).ok()?;
-
-    let session_id = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .map(|s| {
-            // Remove "agent-" prefix for subagent files
-            if is_subagent && s.starts_with("agent-") {
-                s.strip_prefix("agent-").unwrap_or(s).to_string()
-            } else {
-                s.to_string()
-            }
-        })
-        .unwrap_or_else(|| "unknown".to_string());
-
-    Some(SessionFileInfo {
-        path: path.to_path_buf(),
-        session_id,
-        project_path,
-        modified: metadata.modified().ok()?,
-        size: metadata.len(),
-        is_subagent,
-    })
-}
-
-/// Decode project path from encoded directory name
-///
-/// Claude encodes paths like /Users/user/Projects/myproject as
-/// -Users-user-Projects-myproject
-fn decode_project_path(dir_path: &std::path::Path) -> Option<String> {
-    let dir_name = dir_path.file_name()?.to_str()?;
-
-    // Replace dashes with slashes, handling the leading dash
-    if dir_name.starts_with('-') {
-        Some(dir_name.replace('-', "/"))
-    } else {
-        Some(format!("/{}", dir_name.replace('-', "/")))
-    }
+    /// Label of the extra Claude directory or import source the file was
+    /// found in; None for sessions of the default Claude directory
+    pub source: Option<String>,
+    /// Session that spawned this subagent, taken from the
+    /// `<session-id>/subagents/` directory the file is in
+    pub parent_session_id: Option<String>,
 }
 
 /// Find session file by ID
@@ -254,8 +116,9 @@ pub fn parse_session_by_id(
     let session_info = find_session_by_id(session_id)
         .ok_or_else(|| ParserError::SessionNotFound(session_id.to_string()))?;
 
-    let turns = session::parse_session_to_turns(&session_info.path)?;
-    let stats = SessionStats::from_turns(&turns, Some(session_id.to_string()));
+    let (turns, diagnostics) = session::parse_session_with_diagnostics(&session_info.path)?;
+    let stats = SessionStats::from_turns(&turns, Some(session_id.to_string()))
+        .with_diagnostics(&diagnostics);
 
     Ok((turns, stats))
 }
@@ -264,15 +127,12 @@ pub fn parse_session_by_id(
 pub fn parse_session_by_path(
     path: &std::path::Path,
 ) -> ParserResult<(Vec<CompletedTurn>, SessionStats)> {
-    let turns = session::parse_session_to_turns(path)?;
+    let (turns, diagnostics) = session::parse_session_with_diagnostics(path)?;
 
     // Extract session ID from filename
-    let session_id = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .map(String::from);
+    let session_id = archive::session_file_stem(path).map(String::from);
 
-    let stats = SessionStats::from_turns(&turns, session_id);
+    let stats = SessionStats::from_turns(&turns, session_id).with_diagnostics(&diagnostics);
 
     Ok((turns, stats))
 }
@@ -304,17 +164,18 @@ where
 ///
 /// This avoids full session parsing and is used to backfill the `summary` column
 /// for sessions that were cached before the summary feature was added.
+/// Returns None in privacy mode, where message text is never stored.
 pub fn extract_first_user_message(path: &std::path::Path) -> Option<String> {
-    use std::io::{BufRead, BufReader};
+    use std::io::BufRead;
 
-    let file = std::fs::File::open(path).ok()?;
-    let reader = BufReader::new(file);
+    if crate::settings::privacy_mode() {
+        return None;
+    }
 
-    for line in reader.lines() {
-        let line = match line {
-            Ok(l) => l,
-            Err(_) => continue,
-        };
+    let reader = archive::open_session_file(path).ok()?;
+
+    // Stop at the first read error: a truncated archive repeats it forever
+    for line in reader.lines().map_while(Result::ok) {
         if line.trim().is_empty() {
             continue;
         }
@@ -352,24 +213,184 @@ pub fn extract_first_user_message(path: &std::path::Path) -> Option<String> {
     None
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Lightweight extraction of the git branch a session was started on.
+///
+/// Claude Code records the working directory's branch as `gitBranch` on each
+/// entry. Returns the first non-empty value, reading only as far as needed.
+/// Detached HEADs are recorded as "HEAD" and treated as no branch.
+pub fn extract_git_branch(path: &std::path::Path) -> Option<String> {
+    use std::io::BufRead;
 
-    #[test]
-    fn test_decode_project_path() {
-        let path = PathBuf::from("/home/user/.claude/projects/-Users-user-Projects-myapp");
-        let decoded = decode_project_path(&path);
-        assert_eq!(decoded, Some("/Users/user/Projects/myapp".to_string()));
+    let reader = archive::open_session_file(path).ok()?;
+
+    for line in reader.lines().map_while(Result::ok) {
+        if !line.contains("\"gitBranch\"") {
+            continue;
+        }
+
+        let value: serde_json::Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        if let Some(branch) = value.get("gitBranch").and_then(|v| v.as_str()) {
+            let branch = branch.trim();
+            if !branch.is_empty() && branch != "HEAD" {
+                return Some(branch.to_string());
+            }
+        }
     }
 
-    #[test]
-    fn test_decode_project_path_no_leading_dash() {
-        let path = PathBuf::from("/home/user/.claude/projects/Users-user-Projects-myapp");
-        let decoded = decode_project_path(&path);
-        assert_eq!(decoded, Some("/Users/user/Projects/myapp".to_string()));
+    None
+}
+
+/// Extract the working directory a session ran in
+///
+/// Every Claude Code entry records `cwd`; returns the first one found.
+pub fn extract_cwd(path: &std::path::Path) -> Option<String> {
+    use std::io::BufRead;
+
+    archive::open_session_file(path)
+        .ok()?
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| line.contains("\"cwd\""))
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+        .find_map(|value| {
+            value
+                .get("cwd")
+                .and_then(|v| v.as_str())
+                .filter(|cwd| !cwd.is_empty())
+                .map(String::from)
+        })
+}
+
+/// Whether a session file has at least one assistant entry
+///
+/// Much cheaper than parsing the session: only lines mentioning
+/// `"assistant"` are decoded, and only their `type`, stopping at the first
+/// match. Files with no reply (snapshots and summaries only) have no turns.
+pub fn has_assistant_entry(path: &std::path::Path) -> bool {
+    use std::io::BufRead;
+
+    #[derive(serde::Deserialize)]
+    struct EntryTypeOnly<'a> {
+        #[serde(rename = "type", borrow)]
+        entry_type: Option<&'a str>,
+    }
+
+    let Ok(reader) = archive::open_session_file(path) else {
+        return false;
+    };
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| line.contains("\"assistant\""))
+        .any(|line| {
+            serde_json::from_str::<EntryTypeOnly>(&line).is_ok_and(|entry| entry.entry_type == Some("assistant"))
+        })
+}
+
+/// A subagent spawned via the Task tool
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubagentSpawn {
+    /// Agent ID (matches the `agent-<id>.jsonl` file name)
+    pub agent_id: String,
+    /// Task description passed to the tool
+    pub description: Option<String>,
+    /// Subagent type passed to the tool (e.g. "Explore")
+    pub subagent_type: Option<String>,
+}
+
+/// Lightweight extraction of the subagents a session file spawned.
+///
+/// Task tool_use blocks carry the description and subagent type; the agent
+/// they started is identified by `toolUseResult.agentId` on the matching
+/// tool_result, or by `data.agentId` on progress entries that reference the
+/// tool use via `parentToolUseID`. Spawns are returned in the order they
+/// first appear.
+pub fn extract_subagent_spawns(path: &std::path::Path) -> Vec<SubagentSpawn> {
+    use std::collections::HashMap;
+    use std::io::BufRead;
+
+    let reader = match archive::open_session_file(path) {
+        Ok(r) => r,
+        Err(_) => return Vec::new(),
+    };
+
+    // tool_use id -> (description, subagent_type)
+    let mut tasks: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
+    // (tool_use id, agent id) in order of appearance
+    let mut links: Vec<(String, String)> = Vec::new();
+
+    for line in reader.lines().map_while(Result::ok) {
+        if !line.contains("tool_use") && !line.contains("parentToolUseID") {
+            continue;
+        }
+        let value: serde_json::Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let str_field = |v: &serde_json::Value, key: &str| {
+            v.get(key).and_then(|f| f.as_str()).map(String::from)
+        };
+
+        let blocks = value
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_array());
+        for block in blocks.into_iter().flatten() {
+            match block.get("type").and_then(|t| t.as_str()) {
+                Some("tool_use") => {
+                    let name = block.get("name").and_then(|n| n.as_str());
+                    if matches!(name, Some("Task") | Some("Agent")) {
+                        if let (Some(id), Some(input)) = (str_field(block, "id"), block.get("input")) {
+                            tasks.insert(
+                                id,
+                                (str_field(input, "description"), str_field(input, "subagent_type")),
+                            );
+                        }
+                    }
+                }
+                Some("tool_result") => {
+                    let agent_id = value
+                        .get("toolUseResult")
+                        .and_then(|r| str_field(r, "agentId"));
+                    if let (Some(tool_use_id), Some(agent_id)) = (str_field(block, "tool_use_id"), agent_id) {
+                        links.push((tool_use_id, agent_id));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let (Some(tool_use_id), Some(agent_id)) = (
+            str_field(&value, "parentToolUseID"),
+            value.get("data").and_then(|d| str_field(d, "agentId")),
+        ) {
+            links.push((tool_use_id, agent_id));
+        }
     }
 
+    let mut spawns: Vec<SubagentSpawn> = Vec::new();
+    for (tool_use_id, agent_id) in links {
+        if spawns.iter().any(|s| s.agent_id == agent_id) {
+            continue;
+        }
+        let (description, subagent_type) = tasks.get(&tool_use_id).cloned().unwrap_or_default();
+        spawns.push(SubagentSpawn {
+            agent_id,
+            description,
+            subagent_type,
+        });
+    }
+
+    spawns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn test_parser_error_display() {
         let io_err = ParserError::Io(std::io::Error::new(
@@ -388,4 +409,75 @@ mod tests {
         assert_eq!(not_found.to_string(), "Session not found: abc123");
     }
 
+    #[test]
+    fn test_has_assistant_entry() {
+        let path = std::env::temp_dir().join(format!("ironhide_has_assistant_{}.jsonl", std::process::id()));
+        let user = r#"{"type":"user","message":{"role":"user","content":"ask the \"assistant\""}}"#;
+        std::fs::write(&path, format!("{}\n{}\n", r#"{"type":"file-history-snapshot"}"#, user)).unwrap();
+        assert!(!has_assistant_entry(&path));
+
+        let reply = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"hi"}]}}"#;
+        std::fs::write(&path, format!("{}\n{}\n", user, reply)).unwrap();
+        assert!(has_assistant_entry(&path));
+
+        std::fs::remove_file(&path).ok();
+        assert!(!has_assistant_entry(&path));
+    }
+
+    #[test]
+    fn test_truncated_archive_stops_reading() {
+        use std::io::Write;
+
+        let entry = r#"{"type":"user","gitBranch":"main","message":{"role":"user","content":"hello"}}"#;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        for _ in 0..200 {
+            writeln!(encoder, r#"{{"type":"file-history-snapshot"}}"#).unwrap();
+        }
+        writeln!(encoder, "{}", entry).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        // Cut the stream short so the decoder fails before reaching the entry
+        let path = std::env::temp_dir().join(format!("ironhide_truncated_{}.jsonl.gz", std::process::id()));
+        std::fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+
+        assert_eq!(extract_first_user_message(&path), None);
+        assert_eq!(extract_git_branch(&path), None);
+        assert_eq!(extract_cwd(&path), None);
+        assert!(!has_assistant_entry(&path));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_extract_subagent_spawns() {
+        let path = std::env::temp_dir().join("ironhide_subagent_spawns_test.jsonl");
+        let lines = [
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Task","input":{"description":"Explore parser","subagent_type":"Explore","prompt":"..."}}]}}"#,
+            r#"{"type":"progress","parentToolUseID":"toolu_1","data":{"type":"agent_progress","agentId":"a1"}}"#,
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"done"}]},"toolUseResult":{"agentId":"a1"}}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"toolu_2","name":"Read","input":{"file_path":"x"}}]}}"#,
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_3","content":"done"}]},"toolUseResult":{"agentId":"a2"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let spawns = extract_subagent_spawns(&path);
+        assert_eq!(
+            spawns,
+            vec![
+                SubagentSpawn {
+                    agent_id: "a1".to_string(),
+                    description: Some("Explore parser".to_string()),
+                    subagent_type: Some("Explore".to_string()),
+                },
+                // The Task call itself wasn't seen, but the result still links the agent
+                SubagentSpawn {
+                    agent_id: "a2".to_string(),
+                    description: None,
+                    subagent_type: None,
+                },
+            ]
+        );
+
+        std::fs::remove_file(&path).ok();
+    }
 }
@@ -0,0 +1,184 @@
+//! Project paths across platforms
+//!
+//! Claude Code names each directory under `~/.claude/projects/` after the
+//! project's working directory, with every separator (and the drive colon on
+//! Windows) replaced by a dash:
+//!
+//! - macOS: `/Users/me/dev/app` → `-Users-me-dev-app`
+//! - Linux: `/home/me/dev/app` → `-home-me-dev-app`
+//! - Windows: `C:\Users\me\dev\app` → `C--Users-me-dev-app`
+//!
+//! The encoding is lossy (dashes in directory names come back as
+//! separators), so the `cwd` recorded in a session file is preferred where
+//! the scanner has one. Either way, paths are normalized to forward slashes
+//! with an upper-case drive letter, so the same project gets the same path
+//! whichever way it was found and whichever OS reads it.
+
+/// The OS a path or set of defaults belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    MacOs,
+    Linux,
+    Windows,
+}
+
+impl Platform {
+    /// The platform this build runs on; other Unixes count as Linux
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else {
+            Self::Linux
+        }
+    }
+
+    /// Glob patterns for the temp and build-artifact locations tools run
+    /// Claude Code from, rather than real user projects
+    ///
+    /// Patterns are written against normalized paths.
+    pub fn temp_path_patterns(self) -> &'static [&'static str] {
+        match self {
+            Self::MacOs => &["/tmp/**", "/private/tmp/**", "/private/var/**", "/var/folders/**"],
+            Self::Linux => &["/tmp/**", "/var/tmp/**", "/dev/shm/**"],
+            Self::Windows => &["**/AppData/Local/Temp/**", "?:/Windows/Temp/**", "?:/Temp/**"],
+        }
+    }
+}
+
+/// Whether a path starts with a drive letter, like `C:\` or `c:/`
+pub fn has_drive_letter(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+/// Whether a path is a Windows path: a drive letter or a UNC share, with
+/// either separator
+pub fn is_windows_path(path: &str) -> bool {
+    has_drive_letter(path) || path.starts_with("\\\\") || path.starts_with("//")
+}
+
+/// Use forward slashes, an upper-case drive letter and no trailing separator
+///
+/// A UNC share keeps its leading `//`; other repeated separators collapse.
+pub fn normalize_project_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    let (prefix, rest) = match path.strip_prefix("//") {
+        Some(rest) => ("//", rest),
+        None => ("", path.as_str()),
+    };
+
+    let mut normalized = String::with_capacity(path.len());
+    normalized.push_str(prefix);
+    let mut last_was_slash = !prefix.is_empty();
+    for c in rest.chars() {
+        if c == '/' && last_was_slash {
+            continue;
+        }
+        last_was_slash = c == '/';
+        normalized.push(c);
+    }
+
+    if has_drive_letter(&normalized) {
+        normalized[..1].make_ascii_uppercase();
+    }
+    // Keep the root of "/" and "C:/"
+    let root_len = if has_drive_letter(&normalized) { 3 } else { 1 };
+    while normalized.len() > root_len && normalized.ends_with('/') {
+        normalized.pop();
+    }
+    normalized
+}
+
+/// Whether a projects subdirectory name is an encoded path: a leading dash
+/// for Unix paths, `X--` for Windows drive paths
+pub fn is_encoded_project_dir(name: &str) -> bool {
+    name.starts_with('-') || has_drive_letter_dashes(name)
+}
+
+/// Whether a projects subdirectory name starts with an encoded drive, `X--`
+pub fn has_drive_letter_dashes(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b'-' && bytes[2] == b'-'
+}
+
+/// Decode a projects subdirectory name back into a normalized path
+///
+/// `C--Users-me-app` becomes `C:/Users/me/app`; anything else is read as a
+/// Unix path, with the leading slash added if the name lacks its dash.
+pub fn decode_project_dir(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return None;
+    }
+    let decoded = if has_drive_letter_dashes(name) {
+        format!("{}:/{}", &name[..1], name[3..].replace('-', "/"))
+    } else if name.starts_with('-') {
+        name.replace('-', "/")
+    } else {
+        format!("/{}", name.replace('-', "/"))
+    };
+    Some(normalize_project_path(&decoded))
+}
+
+/// Last component of a project path, for either separator
+pub fn project_name(path: &str) -> Option<&str> {
+    path.rsplit(['/', '\\'])
+        .find(|part| !part.is_empty())
+        .filter(|part| !(part.len() == 2 && has_drive_letter(part)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_unix_project_dirs() {
+        // macOS
+        assert_eq!(
+            decode_project_dir("-Users-me-Projects-myapp").as_deref(),
+            Some("/Users/me/Projects/myapp")
+        );
+        // Linux
+        assert_eq!(decode_project_dir("-home-me-dev-app").as_deref(), Some("/home/me/dev/app"));
+        assert_eq!(decode_project_dir("home-me-app").as_deref(), Some("/home/me/app"));
+        assert!(is_encoded_project_dir("-home-me-app"));
+        assert!(!is_encoded_project_dir("home-me-app"));
+        assert_eq!(decode_project_dir(""), None);
+    }
+
+    #[test]
+    fn test_decode_windows_project_dirs() {
+        assert!(is_encoded_project_dir("C--Users-me-dev-app"));
+        assert_eq!(
+            decode_project_dir("C--Users-me-dev-app").as_deref(),
+            Some("C:/Users/me/dev/app")
+        );
+        assert_eq!(decode_project_dir("d--work-api").as_deref(), Some("D:/work/api"));
+        // A UNC share (\\server\share) encodes with two leading dashes
+        assert_eq!(decode_project_dir("--server-share-app").as_deref(), Some("//server/share/app"));
+    }
+
+    #[test]
+    fn test_normalize_project_path() {
+        assert_eq!(normalize_project_path("C:\\Users\\me\\app\\"), "C:/Users/me/app");
+        assert_eq!(normalize_project_path("c:/Users//me/app"), "C:/Users/me/app");
+        assert_eq!(normalize_project_path("C:\\"), "C:/");
+        assert_eq!(normalize_project_path("\\\\server\\share\\app"), "//server/share/app");
+        assert_eq!(normalize_project_path("/home/me/app/"), "/home/me/app");
+        assert_eq!(normalize_project_path("/"), "/");
+        assert!(is_windows_path("C:\\Users\\me"));
+        assert!(is_windows_path("\\\\server\\share"));
+        assert!(is_windows_path("//server/share"));
+        assert!(!is_windows_path("/home/me"));
+    }
+
+    #[test]
+    fn test_project_name() {
+        assert_eq!(project_name("/Users/me/Projects/myapp"), Some("myapp"));
+        assert_eq!(project_name("C:\\Users\\me\\app"), Some("app"));
+        assert_eq!(project_name("C:/Users/me/app/"), Some("app"));
+        assert_eq!(project_name("C:/"), None);
+        assert_eq!(project_name(""), None);
+    }
+}
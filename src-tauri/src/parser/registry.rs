@@ -0,0 +1,282 @@
+//! Resolved project paths of encoded project directories
+//!
+//! Decoding a directory name turns every dash back into a separator, so
+//! `-Users-me-my-app` comes back as `/Users/me/my/app`. Each directory is
+//! instead resolved once, in order of preference:
+//!
+//! 1. an override set by the user
+//! 2. the `cwd` of a session in the directory, when it encodes to the
+//!    directory's name
+//! 3. an existing directory on this machine that encodes to the name
+//! 4. the plain decoding
+//!
+//! Resolutions are kept in memory for the scanner and stored in the
+//! database, except plain decodings, which are retried on the next start in
+//! case the project turns up. When two real paths encode to the same name
+//! (`/work/my-app` and `/work/my/app`), the directory is marked ambiguous and
+//! each of its sessions is placed by its own `cwd`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::extract_cwd;
+use super::project_path::{decode_project_dir, has_drive_letter_dashes, normalize_project_path};
+
+/// Session files read for their `cwd` when resolving a directory
+const CWD_SAMPLE_FILES: usize = 3;
+
+lazy_static::lazy_static! {
+    /// Encoded directory name -> resolution
+    static ref REGISTRY: RwLock<HashMap<String, ProjectResolution>> = RwLock::new(HashMap::new());
+    /// Resolutions not yet stored in the database
+    static ref UNSAVED: RwLock<Vec<ProjectResolution>> = RwLock::new(Vec::new());
+}
+
+/// How a directory's project path was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionSource {
+    Override,
+    Cwd,
+    Filesystem,
+    Decoded,
+}
+
+impl ResolutionSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Override => "override",
+            Self::Cwd => "cwd",
+            Self::Filesystem => "filesystem",
+            Self::Decoded => "decoded",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "override" => Some(Self::Override),
+            "cwd" => Some(Self::Cwd),
+            "filesystem" => Some(Self::Filesystem),
+            "decoded" => Some(Self::Decoded),
+            _ => None,
+        }
+    }
+}
+
+/// Project path an encoded project directory resolves to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ProjectResolution {
+    /// Directory name under `projects/`, e.g. `-Users-me-my-app`
+    pub encoded_dir: String,
+    pub project_path: String,
+    pub source: ResolutionSource,
+    /// More than one path encodes to the directory's name; sessions are
+    /// placed by their own `cwd` where they have one
+    pub ambiguous: bool,
+}
+
+/// Encode a path the way Claude Code names project directories: every
+/// character other than an ASCII letter or digit becomes a dash
+pub fn encode_project_path(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Project path of a project directory, resolving and registering it the
+/// first time it is seen from the session `files` in it
+pub fn resolve_project_dir(dir: &Path, files: &[PathBuf]) -> Option<String> {
+    let name = dir.file_name()?.to_str()?;
+    if let Some(known) = lookup(name) {
+        return Some(known.project_path);
+    }
+
+    let resolution = resolve(name, files)?;
+    let path = resolution.project_path.clone();
+    if resolution.source != ResolutionSource::Decoded {
+        if let Ok(mut unsaved) = UNSAVED.write() {
+            unsaved.push(resolution.clone());
+        }
+    }
+    register(resolution);
+    Some(path)
+}
+
+/// Project path of one session in an ambiguous directory: its own `cwd` if
+/// that encodes to the directory's name, otherwise the directory's
+pub fn session_project_path(resolution: &ProjectResolution, file: &Path) -> String {
+    extract_cwd(file)
+        .map(|cwd| normalize_project_path(&cwd))
+        .filter(|cwd| encode_project_path(cwd) == resolution.encoded_dir)
+        .unwrap_or_else(|| resolution.project_path.clone())
+}
+
+/// Resolve a directory from scratch, ignoring the registry
+pub fn resolve(name: &str, files: &[PathBuf]) -> Option<ProjectResolution> {
+    let decoded = decode_project_dir(name)?;
+    let resolution = |project_path: String, source, ambiguous| ProjectResolution {
+        encoded_dir: name.to_string(),
+        project_path,
+        source,
+        ambiguous,
+    };
+
+    let mut cwds: Vec<String> = Vec::new();
+    for file in files.iter().take(CWD_SAMPLE_FILES) {
+        let cwd = extract_cwd(file).map(|cwd| normalize_project_path(&cwd));
+        if let Some(cwd) = cwd.filter(|cwd| encode_project_path(cwd) == name) {
+            if !cwds.contains(&cwd) {
+                cwds.push(cwd);
+            }
+        }
+    }
+    if !cwds.is_empty() {
+        let ambiguous = cwds.len() > 1;
+        return Some(resolution(cwds.swap_remove(0), ResolutionSource::Cwd, ambiguous));
+    }
+
+    let existing = existing_paths(name);
+    if let Some(first) = existing.first() {
+        return Some(resolution(first.clone(), ResolutionSource::Filesystem, existing.len() > 1));
+    }
+
+    Some(resolution(decoded, ResolutionSource::Decoded, false))
+}
+
+/// Directories on this machine whose path encodes to `name`, at most two
+fn existing_paths(name: &str) -> Vec<String> {
+    let (root, rest) = if has_drive_letter_dashes(name) {
+        (format!("{}:/", name[..1].to_ascii_uppercase()), &name[3..])
+    } else {
+        ("/".to_string(), name.strip_prefix('-').unwrap_or(name))
+    };
+
+    let mut found = Vec::new();
+    find_encoded(Path::new(&root), rest, &mut found);
+    found.into_iter().map(|p| normalize_project_path(&p.to_string_lossy())).collect()
+}
+
+fn find_encoded(dir: &Path, rest: &str, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if found.len() >= 2 {
+            return;
+        }
+        // Follows symlinks, e.g. a symlinked home directory
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let Some(file_name) = entry.file_name().to_str().map(encode_project_path) else {
+            continue;
+        };
+        if rest == file_name {
+            found.push(entry.path());
+        } else if let Some(remaining) = rest.strip_prefix(&file_name).and_then(|r| r.strip_prefix('-')) {
+            find_encoded(&entry.path(), remaining, found);
+        }
+    }
+}
+
+/// Registered resolution of a directory
+pub fn lookup(encoded_dir: &str) -> Option<ProjectResolution> {
+    REGISTRY.read().ok()?.get(encoded_dir).cloned()
+}
+
+/// Register a resolution, replacing the directory's previous one
+pub fn register(resolution: ProjectResolution) {
+    if let Ok(mut registry) = REGISTRY.write() {
+        registry.insert(resolution.encoded_dir.clone(), resolution);
+    }
+}
+
+/// Forget a directory's resolution so it is resolved again
+pub fn forget(encoded_dir: &str) {
+    if let Ok(mut registry) = REGISTRY.write() {
+        registry.remove(encoded_dir);
+    }
+}
+
+/// Replace the registry with resolutions loaded from the database
+pub fn set_registry(resolutions: Vec<ProjectResolution>) {
+    if let Ok(mut registry) = REGISTRY.write() {
+        *registry = resolutions.into_iter().map(|r| (r.encoded_dir.clone(), r)).collect();
+    }
+}
+
+/// Take the resolutions made since the last call, for storing
+pub fn take_unsaved() -> Vec<ProjectResolution> {
+    UNSAVED.write().map(|mut unsaved| std::mem::take(&mut *unsaved)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ironhide-registry-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn session_file(dir: &Path, name: &str, cwd: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, format!("{{\"type\":\"user\",\"cwd\":{:?}}}\n", cwd)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_encode_project_path() {
+        assert_eq!(encode_project_path("/Users/me/my-app"), "-Users-me-my-app");
+        assert_eq!(encode_project_path("/Users/me/.config/app_v2"), "-Users-me--config-app-v2");
+        assert_eq!(encode_project_path("C:/Users/me/app"), "C--Users-me-app");
+    }
+
+    #[test]
+    fn test_resolve_from_cwd() {
+        let dir = temp_dir("cwd");
+        let files = vec![
+            session_file(&dir, "a.jsonl", "/elsewhere/not-this"),
+            session_file(&dir, "b.jsonl", "/Users/me/my-app"),
+        ];
+
+        let resolution = resolve("-Users-me-my-app", &files).unwrap();
+        assert_eq!(resolution.project_path, "/Users/me/my-app");
+        assert_eq!(resolution.source, ResolutionSource::Cwd);
+        assert!(!resolution.ambiguous);
+
+        // Two real paths with the same encoding
+        let files = vec![
+            session_file(&dir, "c.jsonl", "/Users/me/my/app"),
+            session_file(&dir, "d.jsonl", "/Users/me/my-app"),
+        ];
+        let resolution = resolve("-Users-me-my-app", &files).unwrap();
+        assert!(resolution.ambiguous);
+        assert_eq!(session_project_path(&resolution, &files[1]), "/Users/me/my-app");
+        assert_eq!(session_project_path(&resolution, &files[0]), "/Users/me/my/app");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_from_filesystem() {
+        let root = temp_dir("fs");
+        let project = root.join("my-app.v2");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        let name = encode_project_path(&normalize_project_path(&project.to_string_lossy()));
+
+        let resolution = resolve(&name, &[]).unwrap();
+        assert_eq!(resolution.source, ResolutionSource::Filesystem);
+        assert_eq!(resolution.project_path, normalize_project_path(&project.to_string_lossy()));
+
+        let resolution = resolve("-nonexistent-ironhide-dir", &[]).unwrap();
+        assert_eq!(resolution.source, ResolutionSource::Decoded);
+        assert_eq!(resolution.project_path, "/nonexistent/ironhide/dir");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
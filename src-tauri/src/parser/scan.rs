@@ -0,0 +1,660 @@
+//! Session file discovery
+//!
+//! Walks `<claude dir>/projects` in parallel (one rayon task per project
+//! directory) and caches each directory's listing keyed by its mtime. A
+//! directory's mtime only changes when entries are added, removed or
+//! renamed, so an unchanged project directory is not listed again; the
+//! session files it contains are still stat'ed so size and mtime stay
+//! current for sessions that are being appended to.
+//!
+//! The default Claude directory is `$CLAUDE_CONFIG_DIR` or `~/.claude`; extra
+//! Claude directories configured in settings and directories imported by the
+//! user are scanned alongside it, and their sessions carry the root's or the
+//! import's source label. Archived sessions are listed from the archive
+//! directory, which mirrors the projects layout.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use super::archive::{self, is_session_file, session_file_stem};
+use super::project_path::{is_encoded_project_dir, normalize_project_path};
+use super::registry;
+use super::{extract_cwd, SessionFileInfo};
+
+/// A cached listing is only trusted if the directory was last modified at
+/// least this long before it was listed; filesystems with coarse mtimes
+/// could otherwise hide a change made in the same tick as the listing
+const MTIME_SETTLE: Duration = Duration::from_secs(2);
+
+lazy_static::lazy_static! {
+    static ref LISTING_CACHE: Mutex<HashMap<PathBuf, DirListing>> = Mutex::new(HashMap::new());
+    /// Project paths read from imported files' `cwd`, with the file mtime
+    static ref CWD_CACHE: Mutex<HashMap<PathBuf, (SystemTime, Option<String>)>> = Mutex::new(HashMap::new());
+    static ref IMPORT_SOURCES: RwLock<Vec<ImportSource>> = RwLock::new(Vec::new());
+}
+
+/// A directory of session files imported from outside `~/.claude`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportSource {
+    pub root: PathBuf,
+    /// Label stored with the sessions found under `root`
+    pub label: String,
+}
+
+/// A Claude data directory sessions are scanned from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanRoot {
+    pub path: PathBuf,
+    /// Label stored with the sessions found under `path`; None for the
+    /// default directory
+    pub label: Option<String>,
+}
+
+/// Entries of a directory relevant to session discovery
+#[derive(Debug, Clone)]
+struct DirListing {
+    /// Directory mtime when it was listed
+    mtime: SystemTime,
+    /// When the listing was taken
+    listed_at: SystemTime,
+    /// `*.jsonl` and archived `*.jsonl.gz` files
+    jsonl_files: Vec<PathBuf>,
+    /// Subdirectories
+    subdirs: Vec<PathBuf>,
+}
+
+impl DirListing {
+    fn is_fresh(&self, mtime: SystemTime) -> bool {
+        self.mtime == mtime
+            && self
+                .listed_at
+                .duration_since(mtime)
+                .is_ok_and(|age| age >= MTIME_SETTLE)
+    }
+}
+
+/// Scan for all Claude Code session files
+///
+/// Looks for JSONL files, plain or gzip-compressed (`*.jsonl.gz`), in every
+/// Claude data directory (see [`scan_roots`]):
+/// - <root>/projects/*/*.jsonl (main session files)
+/// - <root>/projects/*/<session-id>/subagents/*.jsonl (subagent files)
+/// - <root>/history.jsonl (global history)
+///
+/// plus the sessions of every registered import source and the session
+/// archive. A session found in more than one place is reported once, from
+/// the Claude data directories first (in order), then the import sources,
+/// then the archive.
+///
+/// Returns files sorted by modification time (most recent first)
+pub fn scan_claude_sessions() -> Vec<SessionFileInfo> {
+    let mut sessions = scan_root_dirs(&scan_roots());
+
+    let sources = import_sources();
+    let archive_dir = archive::default_archive_dir();
+    let has_archive = archive_dir.is_dir();
+    if sources.is_empty() && !has_archive {
+        return sessions;
+    }
+
+    // Forget imported files that were removed
+    if let Ok(mut cache) = CWD_CACHE.lock() {
+        cache.retain(|file, _| file.exists());
+    }
+
+    let mut seen: HashSet<String> = sessions.iter().map(|s| s.session_id.clone()).collect();
+    for source in &sources {
+        for info in scan_import_dir(source) {
+            if seen.insert(info.session_id.clone()) {
+                sessions.push(info);
+            }
+        }
+    }
+    if has_archive {
+        for info in scan_projects_directory(&archive_dir) {
+            if seen.insert(info.session_id.clone()) {
+                sessions.push(info);
+            }
+        }
+    }
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.modified));
+
+    sessions
+}
+
+/// The default Claude data directory: `$CLAUDE_CONFIG_DIR` if set, otherwise
+/// `~/.claude`
+pub fn default_claude_dir() -> Option<PathBuf> {
+    match std::env::var_os("CLAUDE_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => dirs::home_dir().map(|home| home.join(".claude")),
+    }
+}
+
+/// Claude data directories to scan: the default one, then the extra roots
+/// configured in settings
+///
+/// Sessions of the default directory have no source label; those of the
+/// extra roots carry the root's label.
+pub fn scan_roots() -> Vec<ScanRoot> {
+    let default = default_claude_dir();
+    if default.is_none() {
+        tracing::warn!("Could not determine home directory");
+    }
+    let mut roots: Vec<ScanRoot> = default
+        .into_iter()
+        .map(|path| ScanRoot { path, label: None })
+        .collect();
+    for root in crate::settings::claude_roots() {
+        let path = root.resolved_path();
+        if !roots.iter().any(|r| r.path == path) {
+            roots.push(ScanRoot {
+                path,
+                label: Some(root.source_label()),
+            });
+        }
+    }
+    roots
+}
+
+/// Scan Claude data directories in order, keeping the first copy of a
+/// session found in more than one
+fn scan_root_dirs(roots: &[ScanRoot]) -> Vec<SessionFileInfo> {
+    let mut sessions = Vec::new();
+    let mut seen = HashSet::new();
+    for root in roots {
+        if !root.path.exists() {
+            tracing::info!("Claude directory not found: {:?}", root.path);
+            continue;
+        }
+        for mut info in scan_claude_dir(&root.path) {
+            if seen.insert(info.session_id.clone()) {
+                info.source = root.label.clone();
+                sessions.push(info);
+            }
+        }
+    }
+    if roots.len() > 1 {
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.modified));
+    }
+    sessions
+}
+
+/// Scan a Claude data directory (the layout of `~/.claude`)
+pub fn scan_claude_dir(claude_dir: &Path) -> Vec<SessionFileInfo> {
+    let mut sessions = Vec::new();
+
+    let projects_dir = claude_dir.join("projects");
+    if projects_dir.exists() {
+        sessions = scan_projects_directory(&projects_dir);
+    }
+
+    // Check for global history
+    let history_file = claude_dir.join("history.jsonl");
+    if history_file.exists() {
+        if let Some(info) = create_session_info(&history_file, None, false) {
+            sessions.push(info);
+        }
+    }
+
+    // Sort by modification time (most recent first)
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.modified));
+
+    sessions
+}
+
+/// Registered import sources
+pub fn import_sources() -> Vec<ImportSource> {
+    IMPORT_SOURCES.read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Replace the registered import sources
+pub fn set_import_sources(sources: Vec<ImportSource>) {
+    if let Ok(mut active) = IMPORT_SOURCES.write() {
+        *active = sources;
+    }
+}
+
+/// Register an import source, replacing any with the same root
+pub fn add_import_source(source: ImportSource) {
+    if let Ok(mut active) = IMPORT_SOURCES.write() {
+        active.retain(|s| s.root != source.root);
+        active.push(source);
+    }
+}
+
+/// Collect every `*.jsonl` file under an import source's root
+///
+/// Files in a `subagents` directory are subagent logs. The project path is
+/// resolved from the directory name if the tree mirrors
+/// `~/.claude/projects`, and read from the session's `cwd` otherwise.
+/// Directories reached again through a symlink are walked only once.
+pub fn scan_import_dir(source: &ImportSource) -> Vec<SessionFileInfo> {
+    let mut sessions = Vec::new();
+    let mut pending = vec![source.root.clone()];
+    let mut visited = HashSet::new();
+
+    while let Some(dir) = pending.pop() {
+        if !visited.insert(dir.canonicalize().unwrap_or_else(|_| dir.clone())) {
+            continue;
+        }
+        let listing = match list_dir(&dir) {
+            Some(listing) => listing,
+            None => continue,
+        };
+        let is_subagent = dir.file_name().is_some_and(|name| name == "subagents");
+
+        // `<project>/<session-id>/subagents/` for subagents, `<project>/` otherwise
+        let project_dir = if is_subagent {
+            dir.parent().and_then(Path::parent)
+        } else {
+            Some(dir.as_path())
+        };
+        let encoded_project = project_dir
+            .filter(|d| d.file_name().and_then(|n| n.to_str()).is_some_and(is_encoded_project_dir))
+            .and_then(|d| ProjectPaths::resolve(d, &listing.jsonl_files));
+
+        for file_path in &listing.jsonl_files {
+            let project_path = match &encoded_project {
+                Some(paths) => Some(paths.for_file(file_path)),
+                None => cwd_project_path(file_path),
+            };
+            if let Some(mut info) = create_session_info(file_path, project_path, is_subagent) {
+                info.source = Some(source.label.clone());
+                sessions.push(info);
+            }
+        }
+        pending.extend(listing.subdirs);
+    }
+
+    sessions
+}
+
+/// Project path of a session file from its `cwd`, reusing the cached
+/// value while the file's mtime hasn't changed
+fn cwd_project_path(file_path: &Path) -> Option<String> {
+    let mtime = std::fs::metadata(file_path).and_then(|m| m.modified()).ok();
+
+    if let Some(mtime) = mtime {
+        if let Some((_, project_path)) = CWD_CACHE
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(file_path).filter(|(cached, _)| *cached == mtime).cloned())
+        {
+            return project_path;
+        }
+    }
+
+    let project_path = extract_cwd(file_path).map(|cwd| normalize_project_path(&cwd));
+    if let (Some(mtime), Ok(mut cache)) = (mtime, CWD_CACHE.lock()) {
+        cache.insert(file_path.to_path_buf(), (mtime, project_path.clone()));
+    }
+    project_path
+}
+
+/// Drop all cached directory listings and project paths
+pub fn clear_scan_cache() {
+    if let Ok(mut cache) = LISTING_CACHE.lock() {
+        cache.clear();
+    }
+    if let Ok(mut cache) = CWD_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+/// Scan the projects directory, one parallel task per project directory
+fn scan_projects_directory(projects_dir: &Path) -> Vec<SessionFileInfo> {
+    let project_dirs = match list_dir(projects_dir) {
+        Some(listing) => listing.subdirs,
+        None => {
+            tracing::warn!("Failed to read projects directory: {:?}", projects_dir);
+            return Vec::new();
+        }
+    };
+
+    // Forget directories that no longer exist so the cache doesn't grow
+    // unbounded as projects are removed
+    if let Ok(mut cache) = LISTING_CACHE.lock() {
+        cache.retain(|dir, _| dir == projects_dir || dir.exists());
+    }
+
+    project_dirs
+        .par_iter()
+        .flat_map_iter(|dir| scan_project_dir(dir))
+        .collect()
+}
+
+/// Collect the session and subagent files of one project directory
+fn scan_project_dir(dir: &Path) -> Vec<SessionFileInfo> {
+    let mut sessions = Vec::new();
+    let listing = match list_dir(dir) {
+        Some(listing) => listing,
+        None => return sessions,
+    };
+
+    // This is a project directory (encoded path)
+    let project_paths = ProjectPaths::resolve(dir, &listing.jsonl_files);
+    let project_path = |file: &Path| project_paths.as_ref().map(|paths| paths.for_file(file));
+
+    for file_path in &listing.jsonl_files {
+        if let Some(info) = create_session_info(file_path, project_path(file_path), false) {
+            sessions.push(info);
+        }
+    }
+
+    // Session directories may hold subagent logs
+    for session_dir in &listing.subdirs {
+        if let Some(subagents) = list_dir(&session_dir.join("subagents")) {
+            for file_path in &subagents.jsonl_files {
+                if let Some(info) = create_session_info(file_path, project_path(file_path), true) {
+                    sessions.push(info);
+                }
+            }
+        }
+    }
+
+    sessions
+}
+
+/// List a directory, reusing the cached listing if its mtime hasn't changed
+///
+/// Returns None if the directory doesn't exist or can't be read.
+fn list_dir(dir: &Path) -> Option<DirListing> {
+    let mtime = std::fs::metadata(dir).ok()?.modified().ok()?;
+
+    if let Some(cached) = LISTING_CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(dir).filter(|l| l.is_fresh(mtime)).cloned())
+    {
+        return Some(cached);
+    }
+
+    let listed_at = SystemTime::now();
+    let mut listing = DirListing {
+        mtime,
+        listed_at,
+        jsonl_files: Vec::new(),
+        subdirs: Vec::new(),
+    };
+
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            listing.subdirs.push(path);
+        } else if is_session_file(&path) {
+            listing.jsonl_files.push(path);
+        }
+    }
+    // A log gzipped next to its live file is a stale copy of it
+    let plain: HashSet<PathBuf> = listing
+        .jsonl_files
+        .iter()
+        .filter(|path| !archive::is_compressed(path))
+        .cloned()
+        .collect();
+    listing
+        .jsonl_files
+        .retain(|path| !archive::is_compressed(path) || !plain.contains(&path.with_extension("")));
+
+    if let Ok(mut cache) = LISTING_CACHE.lock() {
+        cache.insert(dir.to_path_buf(), listing.clone());
+    }
+
+    Some(listing)
+}
+
+/// Create session info from a file path
+fn create_session_info(
+    path: &Path,
+    project_path: Option<String>,
+    is_subagent: bool,
+) -> Option<SessionFileInfo> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+
+    let session_id = session_file_stem(path)
+        .map(|s| {
+            // Remove "agent-" prefix for subagent files
+            if is_subagent && s.starts_with("agent-") {
+                s.strip_prefix("agent-").unwrap_or(s).to_string()
+            } else {
+                s.to_string()
+            }
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    Some(SessionFileInfo {
+        path: path.to_path_buf(),
+        session_id,
+        project_path,
+        modified: metadata.modified().ok()?,
+        size: metadata.len(),
+        is_subagent,
+        source: None,
+        parent_session_id: if is_subagent { subagent_parent_id(path) } else { None },
+    })
+}
+
+/// Session ID of a subagent file's parent, for `<session-id>/subagents/*.jsonl`
+fn subagent_parent_id(path: &Path) -> Option<String> {
+    let subagents_dir = path.parent().filter(|d| d.file_name().is_some_and(|n| n == "subagents"))?;
+    let session_dir = subagents_dir.parent()?;
+    session_dir.file_name()?.to_str().map(String::from)
+}
+
+/// Project paths of the sessions in an encoded project directory
+///
+/// Claude encodes paths like /Users/user/Projects/myproject as
+/// -Users-user-Projects-myproject, which the registry resolves back to a
+/// path. Sessions in a directory several paths encode to are placed by
+/// their own `cwd`.
+enum ProjectPaths {
+    Resolved(String),
+    Ambiguous(registry::ProjectResolution),
+}
+
+impl ProjectPaths {
+    fn resolve(dir: &Path, files: &[PathBuf]) -> Option<Self> {
+        let path = registry::resolve_project_dir(dir, files)?;
+        let ambiguous = dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(registry::lookup)
+            .filter(|resolution| resolution.ambiguous);
+        Some(match ambiguous {
+            Some(resolution) => Self::Ambiguous(resolution),
+            None => Self::Resolved(path),
+        })
+    }
+
+    fn for_file(&self, file: &Path) -> String {
+        match self {
+            Self::Resolved(path) => path.clone(),
+            Self::Ambiguous(resolution) => registry::session_project_path(resolution, file),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_project_path() {
+        let path = PathBuf::from("/home/user/.claude/projects/-Users-user-Projects-myapp");
+        let decoded = registry::resolve_project_dir(&path, &[]);
+        assert_eq!(decoded, Some("/Users/user/Projects/myapp".to_string()));
+    }
+
+    #[test]
+    fn test_decode_project_path_no_leading_dash() {
+        let path = PathBuf::from("/home/user/.claude/projects/Users-user-Projects-myapp");
+        let decoded = registry::resolve_project_dir(&path, &[]);
+        assert_eq!(decoded, Some("/Users/user/Projects/myapp".to_string()));
+    }
+
+    #[test]
+    fn test_listing_freshness() {
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let listing = DirListing {
+            mtime,
+            listed_at: mtime + Duration::from_secs(5),
+            jsonl_files: Vec::new(),
+            subdirs: Vec::new(),
+        };
+        assert!(listing.is_fresh(mtime));
+        assert!(!listing.is_fresh(mtime + Duration::from_secs(1)));
+
+        // Listed in the same tick as the last change: can't be trusted
+        let listing = DirListing {
+            listed_at: mtime,
+            ..listing
+        };
+        assert!(!listing.is_fresh(mtime));
+    }
+
+    #[test]
+    fn test_scan_claude_dir() {
+        let claude_dir = std::env::temp_dir().join("ironhide_scan_test");
+        let _ = std::fs::remove_dir_all(&claude_dir);
+        let project = claude_dir.join("projects").join("-home-me-app");
+        std::fs::create_dir_all(project.join("s1").join("subagents")).unwrap();
+        std::fs::write(project.join("s1.jsonl"), "{}\n").unwrap();
+        std::fs::write(project.join("notes.txt"), "").unwrap();
+        std::fs::write(project.join("s1").join("subagents").join("agent-a1.jsonl"), "{}\n").unwrap();
+
+        let sessions = scan_claude_dir(&claude_dir);
+        assert_eq!(sessions.len(), 2);
+        let main = sessions.iter().find(|s| s.session_id == "s1").unwrap();
+        assert!(!main.is_subagent);
+        assert_eq!(main.project_path.as_deref(), Some("/home/me/app"));
+        let agent = sessions.iter().find(|s| s.session_id == "a1").unwrap();
+        assert!(agent.is_subagent);
+        assert_eq!(agent.parent_session_id.as_deref(), Some("s1"));
+        assert_eq!(main.parent_session_id, None);
+
+        // New files and appended content show up on the next scan
+        std::fs::write(project.join("s2.jsonl"), "{}\n{}\n").unwrap();
+        std::fs::write(project.join("s1.jsonl"), "{}\n{}\n{}\n").unwrap();
+        let sessions = scan_claude_dir(&claude_dir);
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions.iter().find(|s| s.session_id == "s1").unwrap().size, 9);
+
+        // Gzipped logs are listed, unless the live file is next to them
+        for id in ["s1", "s3"] {
+            let file = std::fs::File::create(project.join(format!("{}.jsonl.gz", id))).unwrap();
+            let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            std::io::Write::write_all(&mut encoder, b"{}\n").unwrap();
+            encoder.finish().unwrap();
+        }
+        let sessions = scan_claude_dir(&claude_dir);
+        assert_eq!(sessions.len(), 4);
+        assert!(!archive::is_compressed(&sessions.iter().find(|s| s.session_id == "s1").unwrap().path));
+        assert!(archive::is_compressed(&sessions.iter().find(|s| s.session_id == "s3").unwrap().path));
+
+        let _ = std::fs::remove_dir_all(&claude_dir);
+    }
+
+    #[test]
+    fn test_scan_root_dirs() {
+        let base = std::env::temp_dir().join("ironhide_scan_roots_test");
+        let _ = std::fs::remove_dir_all(&base);
+        let personal = base.join("personal");
+        let work = base.join("work");
+        for (root, ids) in [(&personal, ["s1", "s2"]), (&work, ["s2", "s3"])] {
+            let project = root.join("projects").join("-home-me-app");
+            std::fs::create_dir_all(&project).unwrap();
+            for id in ids {
+                std::fs::write(project.join(format!("{}.jsonl", id)), "{}\n").unwrap();
+            }
+        }
+
+        let roots = [
+            ScanRoot { path: personal.clone(), label: None },
+            ScanRoot { path: work.clone(), label: Some("work".to_string()) },
+            ScanRoot { path: base.join("missing"), label: Some("gone".to_string()) },
+        ];
+        let sessions = scan_root_dirs(&roots);
+        let mut tagged: Vec<(&str, Option<&str>)> = sessions
+            .iter()
+            .map(|s| (s.session_id.as_str(), s.source.as_deref()))
+            .collect();
+        tagged.sort();
+        // A session in both roots is reported from the first
+        assert_eq!(tagged, vec![("s1", None), ("s2", None), ("s3", Some("work"))]);
+        assert!(sessions
+            .iter()
+            .find(|s| s.session_id == "s2")
+            .unwrap()
+            .path
+            .starts_with(&personal));
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_scan_import_dir() {
+        let root = std::env::temp_dir().join("ironhide_import_scan_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let mirrored = root.join("-home-me-app");
+        let plain = root.join("backup").join("2026");
+        std::fs::create_dir_all(mirrored.join("s1").join("subagents")).unwrap();
+        std::fs::create_dir_all(&plain).unwrap();
+        std::fs::write(mirrored.join("s1.jsonl"), "{}\n").unwrap();
+        std::fs::write(mirrored.join("s1").join("subagents").join("agent-a1.jsonl"), "{}\n").unwrap();
+        std::fs::write(
+            plain.join("s2.jsonl"),
+            "{\"type\":\"summary\"}\n{\"type\":\"user\",\"cwd\":\"/home/me/api\"}\n",
+        )
+        .unwrap();
+
+        let source = ImportSource {
+            root: root.clone(),
+            label: "laptop".to_string(),
+        };
+        let sessions = scan_import_dir(&source);
+        assert_eq!(sessions.len(), 3);
+        assert!(sessions.iter().all(|s| s.source.as_deref() == Some("laptop")));
+
+        let project = |id: &str| {
+            let info = sessions.iter().find(|s| s.session_id == id).unwrap();
+            (info.project_path.clone(), info.is_subagent)
+        };
+        assert_eq!(project("s1"), (Some("/home/me/app".to_string()), false));
+        assert_eq!(project("a1"), (Some("/home/me/app".to_string()), true));
+        assert_eq!(project("s2"), (Some("/home/me/api".to_string()), false));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_import_dir_symlink_loop() {
+        let root = std::env::temp_dir().join("ironhide_import_symlink_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let nested = root.join("backup");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("s1.jsonl"), "{\"type\":\"user\",\"cwd\":\"/home/me/app\"}\n").unwrap();
+        // Two links back up the tree would otherwise nest without end
+        std::os::unix::fs::symlink(&root, nested.join("up")).unwrap();
+        std::os::unix::fs::symlink(&root, nested.join("again")).unwrap();
+
+        let source = ImportSource {
+            root: root.clone(),
+            label: "synced".to_string(),
+        };
+        let sessions = scan_import_dir(&source);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].project_path.as_deref(), Some("/home/me/app"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
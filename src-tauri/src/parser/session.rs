@@ -7,7 +7,13 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use super::jsonl::{Entry, EntryType, MessageContent, Usage};
+use super::jsonl::{
+    tool_error_message, ApiErrorEvent, CompactBoundary, Entry, EntryType, IncrementalReader, MessageContent,
+    ParseDiagnostics, Usage,
+};
+use super::tool_names::canonical_tool_name;
+use crate::metrics::cost::CostProvider;
+use crate::metrics::tokens::CHARS_PER_TOKEN;
 
 /// Tool use within a turn
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +25,17 @@ pub struct ToolUse {
     pub is_error: bool,
 }
 
+impl ToolUse {
+    /// Error text of a failed call, without the `<tool_use_error>` wrapper
+    pub fn error_message(&self) -> Option<&str> {
+        if !self.is_error {
+            return None;
+        }
+        let result = self.result.as_deref()?;
+        Some(tool_error_message(result).unwrap_or(result))
+    }
+}
+
 /// A completed turn (cycle) in a session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompletedTurn {
@@ -30,6 +47,9 @@ pub struct CompletedTurn {
     pub assistant_message: Option<String>,
     pub model: Option<String>,
     pub stop_reason: Option<String>,
+    /// Backend that billed the turn, detected along with the model
+    #[serde(default)]
+    pub provider: CostProvider,
 
     // Token usage (aggregated across all assistant responses in turn)
     pub input_tokens: u64,
@@ -56,6 +76,22 @@ pub struct CompletedTurn {
 
     // All entries in this turn (for detailed analysis)
     pub entry_count: u32,
+
+    // Compactions during the turn or just before it
+    #[serde(default)]
+    pub compactions: Vec<CompactBoundary>,
+
+    // API errors (rate limits, overloads) during the turn or just before it
+    #[serde(default)]
+    pub api_errors: Vec<ApiErrorEvent>,
+
+    // Extended thinking: output tokens spent on thinking blocks, estimated
+    // from their text since usage doesn't report them separately, and the
+    // thinking mode of the prompt
+    #[serde(default)]
+    pub thinking_tokens: u64,
+    #[serde(default)]
+    pub thinking_level: Option<String>,
 }
 
 impl CompletedTurn {
@@ -68,9 +104,60 @@ impl CompletedTurn {
             .and_then(|e| chrono::DateTime::parse_from_rfc3339(e).ok())?;
         Some((ended - started).num_milliseconds())
     }
+
+    /// Approximate heap footprint of the turn in bytes
+    ///
+    /// Counts the struct itself plus its strings and tool payloads; allocator
+    /// overhead and spare capacity are ignored.
+    pub fn estimated_size(&self) -> usize {
+        let opt_len = |s: &Option<String>| s.as_ref().map_or(0, String::len);
+        let strings = self.started_at.len()
+            + opt_len(&self.ended_at)
+            + opt_len(&self.user_message)
+            + opt_len(&self.assistant_message)
+            + opt_len(&self.model)
+            + opt_len(&self.stop_reason)
+            + opt_len(&self.thinking_level)
+            + opt_len(&self.start_uuid)
+            + opt_len(&self.end_uuid);
+        let tools: usize = self
+            .tool_uses
+            .iter()
+            .map(|t| {
+                std::mem::size_of::<ToolUse>()
+                    + t.id.len()
+                    + t.name.len()
+                    + t.input.as_ref().map_or(0, |v| v.to_string().len())
+                    + opt_len(&t.result)
+            })
+            .sum();
+        let subagents: usize = self
+            .subagent_ids
+            .iter()
+            .map(|id| std::mem::size_of::<String>() + id.len())
+            .sum();
+        std::mem::size_of::<Self>() + strings + tools + subagents
+    }
+
+    /// Context tokens dropped by the turn's compactions
+    pub fn tokens_lost_to_compaction(&self) -> u64 {
+        self.compactions.iter().filter_map(|c| c.tokens_lost()).sum()
+    }
+
+    /// Drop message text and tool payloads, keeping tokens, timing and which
+    /// tools ran and whether they failed
+    pub fn scrub_content(&mut self) {
+        self.user_message = None;
+        self.assistant_message = None;
+        for tool in &mut self.tool_uses {
+            tool.input = None;
+            tool.result = None;
+        }
+    }
 }
 
 /// A turn that is being built
+#[derive(Clone)]
 struct PartialTurn {
     turn_number: u32,
     started_at: String,
@@ -78,6 +165,7 @@ struct PartialTurn {
     user_message: Option<String>,
     assistant_messages: Vec<String>,
     model: Option<String>,
+    provider: CostProvider,
     stop_reason: Option<String>,
     ended_at: Option<String>,
     end_uuid: Option<String>,
@@ -98,6 +186,12 @@ struct PartialTurn {
 
     // Entry count
     entry_count: u32,
+
+    compactions: Vec<CompactBoundary>,
+    api_errors: Vec<ApiErrorEvent>,
+
+    thinking_chars: u64,
+    thinking_level: Option<String>,
 }
 
 impl PartialTurn {
@@ -109,6 +203,7 @@ impl PartialTurn {
             user_message: None,
             assistant_messages: Vec::new(),
             model: None,
+            provider: CostProvider::default(),
             stop_reason: None,
             ended_at: None,
             end_uuid: None,
@@ -121,6 +216,10 @@ impl PartialTurn {
             pending_tool_uses: Vec::new(),
             subagent_ids: Vec::new(),
             entry_count: 0,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_chars: 0,
+            thinking_level: None,
         }
     }
 
@@ -161,6 +260,7 @@ impl PartialTurn {
             assistant_message,
             model: self.model,
             stop_reason: self.stop_reason,
+            provider: self.provider,
             input_tokens: self.input_tokens,
             output_tokens: self.output_tokens,
             cache_read_tokens: self.cache_read_tokens,
@@ -175,6 +275,11 @@ impl PartialTurn {
             start_uuid: self.start_uuid,
             end_uuid: self.end_uuid,
             entry_count: self.entry_count,
+            compactions: self.compactions,
+            api_errors: self.api_errors,
+            // Thinking is billed as output, so the estimate can't exceed it
+            thinking_tokens: (self.thinking_chars / CHARS_PER_TOKEN).min(self.output_tokens),
+            thinking_level: self.thinking_level,
         };
 
         turn.tool_count = turn.tool_uses.len() as u32;
@@ -189,15 +294,24 @@ pub struct TurnAggregator {
     current_turn: Option<PartialTurn>,
     turn_number: u32,
     completed_turns: Vec<CompletedTurn>,
+    /// Compactions between turns, carried into the next turn
+    pending_compactions: Vec<CompactBoundary>,
+    /// API errors between turns, carried into the next turn
+    pending_api_errors: Vec<ApiErrorEvent>,
+    /// Scrub message content from completed turns (privacy mode)
+    scrub_content: bool,
 }
 
 impl TurnAggregator {
-    /// Create a new turn aggregator
+    /// Create a new turn aggregator, scrubbing content if privacy mode is on
     pub fn new() -> Self {
         Self {
             current_turn: None,
             turn_number: 0,
             completed_turns: Vec::new(),
+            pending_compactions: Vec::new(),
+            pending_api_errors: Vec::new(),
+            scrub_content: crate::settings::privacy_mode(),
         }
     }
 
@@ -208,10 +322,26 @@ impl TurnAggregator {
             turn.entry_count += 1;
         }
 
+        if let Some(error) = entry.api_error() {
+            match self.current_turn {
+                Some(ref mut turn) => turn.api_errors.push(error),
+                None => self.pending_api_errors.push(error),
+            }
+        }
+
         match entry.entry_type {
             EntryType::User => self.process_user_entry(entry),
             EntryType::Assistant => self.process_assistant_entry(entry),
             EntryType::Progress => self.process_progress_entry(entry),
+            EntryType::System => {
+                if let Some(boundary) = entry.compact_boundary() {
+                    match self.current_turn {
+                        Some(ref mut turn) => turn.compactions.push(boundary),
+                        None => self.pending_compactions.push(boundary),
+                    }
+                }
+                None
+            }
             _ => None,
         }
     }
@@ -230,16 +360,12 @@ impl TurnAggregator {
                             .position(|(id, _, _)| id == &result.tool_use_id)
                         {
                             let (id, name, input) = turn.pending_tool_uses.remove(pos);
-                            let result_str = match &result.content {
-                                Value::String(s) => Some(s.clone()),
-                                v => Some(v.to_string()),
-                            };
                             turn.tool_uses.push(ToolUse {
                                 id,
                                 name,
                                 input: Some(input),
-                                result: result_str,
-                                is_error: result.is_error,
+                                result: Some(result.text()),
+                                is_error: result.is_failure(),
                             });
                         }
                     }
@@ -259,10 +385,14 @@ impl TurnAggregator {
             entry.uuid.clone(),
         );
 
+        new_turn.compactions = std::mem::take(&mut self.pending_compactions);
+        new_turn.api_errors = std::mem::take(&mut self.pending_api_errors);
+
         // Extract user message
         if let Some(content) = &entry.message_content {
             new_turn.user_message = content.as_text();
         }
+        new_turn.thinking_level = entry.thinking_metadata.as_ref().and_then(|m| m.mode());
 
         new_turn.entry_count = 1;
         self.current_turn = Some(new_turn);
@@ -286,21 +416,30 @@ impl TurnAggregator {
                 if let Some(text) = content.as_text() {
                     turn.assistant_messages.push(text);
                 }
+                turn.thinking_chars += content.thinking_chars() as u64;
 
                 // Track tool uses
                 for (id, name, input) in content.tool_uses() {
-                    turn.pending_tool_uses.push((id, name, input));
+                    turn.pending_tool_uses.push((id, canonical_tool_name(&name), input));
                 }
             }
 
             // Extract model (first one seen)
-            if turn.model.is_none() {
+            if turn.model.is_none() && entry.model.is_some() {
                 turn.model = entry.model.clone();
+                turn.provider = CostProvider::detect(entry.model.as_deref(), entry.message_id.as_deref());
             }
 
             // Extract usage
             if let Some(usage) = &entry.usage {
                 turn.add_usage(usage);
+
+                // The first request after a compaction shows what context is left
+                if !entry.is_subagent() {
+                    if let Some(compaction) = turn.compactions.last_mut().filter(|c| c.post_tokens.is_none()) {
+                        compaction.post_tokens = Some(usage.total_context());
+                    }
+                }
             }
 
             // Extract stop reason
@@ -333,11 +472,7 @@ impl TurnAggregator {
         // Progress entries may contain tool use information from hooks
         if let Some(ref mut turn) = self.current_turn {
             if let Some(tool_use) = entry.tool_use {
-                let name = tool_use
-                    .get("name")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-                    .to_string();
+                let name = canonical_tool_name(tool_use.get("name").and_then(|v| v.as_str()).unwrap_or("unknown"));
 
                 // Check if we already have this tool use (from assistant message)
                 let already_tracked = turn.tool_uses.iter().any(|t| t.name == name)
@@ -377,7 +512,10 @@ impl TurnAggregator {
     /// Flush the current turn, returning it if it exists
     pub fn flush(&mut self) -> Option<CompletedTurn> {
         self.current_turn.take().map(|turn| {
-            let completed = turn.complete();
+            let mut completed = turn.complete();
+            if self.scrub_content {
+                completed.scrub_content();
+            }
             self.completed_turns.push(completed.clone());
             completed
         })
@@ -398,11 +536,39 @@ impl TurnAggregator {
         self.current_turn.is_some()
     }
 
+    /// Drop completed turns while keeping the in-progress turn and numbering
+    ///
+    /// Lets long-lived aggregators (e.g. live tailing) avoid holding the whole
+    /// session history in memory.
+    pub fn clear_completed(&mut self) {
+        self.completed_turns.clear();
+    }
+
+    /// Remove and return the completed turns, keeping the in-progress turn
+    pub fn take_completed(&mut self) -> Vec<CompletedTurn> {
+        std::mem::take(&mut self.completed_turns)
+    }
+
+    /// Copy the in-progress turn and numbering into a new aggregator with no
+    /// completed turns, so entries can be applied speculatively
+    pub fn fork_partial(&self) -> Self {
+        Self {
+            current_turn: self.current_turn.clone(),
+            turn_number: self.turn_number,
+            completed_turns: Vec::new(),
+            pending_compactions: self.pending_compactions.clone(),
+            pending_api_errors: self.pending_api_errors.clone(),
+            scrub_content: self.scrub_content,
+        }
+    }
+
     /// Reset the aggregator
     pub fn reset(&mut self) {
         self.current_turn = None;
         self.turn_number = 0;
         self.completed_turns.clear();
+        self.pending_compactions.clear();
+        self.pending_api_errors.clear();
     }
 }
 
@@ -428,6 +594,18 @@ pub struct SessionStats {
     pub first_timestamp: Option<String>,
     pub last_timestamp: Option<String>,
     pub total_duration_ms: Option<i64>,
+    /// Lines that weren't valid JSON
+    pub malformed_lines: u32,
+    /// Entries of types this parser doesn't know
+    pub unknown_entries: u32,
+    /// Entries of known types that hold no turn data
+    pub ignored_entries: u32,
+    pub compaction_count: u32,
+    /// Context tokens dropped by compactions
+    pub tokens_lost_to_compaction: u64,
+    /// Failed API requests, retried or not
+    #[serde(default)]
+    pub api_error_count: u32,
 }
 
 impl SessionStats {
@@ -449,6 +627,9 @@ impl SessionStats {
             stats.total_cache_read_tokens += turn.cache_read_tokens;
             stats.total_cache_write_tokens += turn.cache_write_5m_tokens + turn.cache_write_1h_tokens;
             stats.total_tool_uses += turn.tool_count;
+            stats.compaction_count += turn.compactions.len() as u32;
+            stats.tokens_lost_to_compaction += turn.tokens_lost_to_compaction();
+            stats.api_error_count += turn.api_errors.len() as u32;
 
             for tool in &turn.tool_uses {
                 unique_tools.insert(tool.name.clone());
@@ -487,11 +668,28 @@ impl SessionStats {
 
         stats
     }
+
+    /// Add the entry counts from the diagnostics of the parse
+    pub fn with_diagnostics(mut self, diagnostics: &ParseDiagnostics) -> Self {
+        self.malformed_lines = diagnostics.malformed_lines;
+        self.unknown_entries = diagnostics.unknown_entries;
+        self.ignored_entries = diagnostics.ignored_entries;
+        self
+    }
 }
 
 /// Parse a session file and return all turns
 pub fn parse_session_to_turns(path: &std::path::Path) -> super::ParserResult<Vec<CompletedTurn>> {
-    let entries = super::jsonl::read_all_entries(path)?;
+    parse_session_with_diagnostics(path).map(|(turns, _)| turns)
+}
+
+/// Parse a session file, returning its turns and the entries that didn't
+/// contribute to them
+pub fn parse_session_with_diagnostics(
+    path: &std::path::Path,
+) -> super::ParserResult<(Vec<CompletedTurn>, ParseDiagnostics)> {
+    let mut reader = IncrementalReader::new(path);
+    let entries = reader.read_new_lines()?;
     let mut aggregator = TurnAggregator::new();
 
     for entry in entries {
@@ -501,7 +699,68 @@ pub fn parse_session_to_turns(path: &std::path::Path) -> super::ParserResult<Vec
     // Flush any remaining partial turn
     aggregator.flush();
 
-    Ok(aggregator.completed_turns)
+    Ok((aggregator.completed_turns, reader.diagnostics().clone()))
+}
+
+/// Resumable session parser for files that only grow
+///
+/// Keeps the read offset and the in-progress turn between calls so that
+/// re-parsing a growing session only touches the appended lines.
+pub struct IncrementalSessionParser {
+    reader: IncrementalReader,
+    aggregator: TurnAggregator,
+    /// Number of leading turns in the last result that can no longer change
+    committed_turns: usize,
+}
+
+impl IncrementalSessionParser {
+    /// Create a parser positioned at the start of the file
+    pub fn new(path: &std::path::Path) -> Self {
+        Self {
+            reader: IncrementalReader::new(path),
+            aggregator: TurnAggregator::new(),
+            committed_turns: 0,
+        }
+    }
+
+    /// Byte offset just past the last complete line consumed
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Parse lines appended since the last call and return the full turn list
+    ///
+    /// `previous` must be the list returned by the previous call (empty on the
+    /// first call); its settled turns are reused rather than re-parsed. The
+    /// result matches `parse_session_to_turns` on the current file: a trailing
+    /// line without a newline is parsed speculatively and the in-progress turn
+    /// is included, but neither is committed to the parser state.
+    pub fn advance(&mut self, previous: &[CompletedTurn]) -> super::ParserResult<Vec<CompletedTurn>> {
+        for entry in self.reader.read_complete_lines(None)? {
+            self.aggregator.process_entry(entry);
+        }
+
+        let settled = self.committed_turns.min(previous.len());
+        let newly_completed = self.aggregator.take_completed();
+        let mut turns = Vec::with_capacity(settled + newly_completed.len() + 1);
+        turns.extend_from_slice(&previous[..settled]);
+        turns.extend(newly_completed);
+        self.committed_turns = turns.len();
+
+        // Apply the unterminated tail (if any) and the open turn to a throwaway copy
+        let mut tail = self.aggregator.fork_partial();
+        let tail_entries =
+            IncrementalReader::from_position(self.reader.path(), self.reader.position())
+                .read_new_lines()
+                .unwrap_or_default();
+        for entry in tail_entries {
+            tail.process_entry(entry);
+        }
+        tail.flush();
+        turns.extend(tail.take_completed());
+
+        Ok(turns)
+    }
 }
 
 /// Parse a session file with streaming for memory efficiency
@@ -515,7 +774,7 @@ where
     let mut aggregator = TurnAggregator::new();
     let mut session_id = None;
 
-    super::jsonl::parse_streaming(path, |entry| {
+    let diagnostics = super::jsonl::parse_streaming(path, |entry| {
         // Capture session ID from first entry that has it
         if session_id.is_none() {
             session_id = entry.session_id.clone();
@@ -531,13 +790,13 @@ where
         on_turn(&turn);
     }
 
-    Ok(SessionStats::from_turns(aggregator.turns(), session_id))
+    Ok(SessionStats::from_turns(aggregator.turns(), session_id).with_diagnostics(&diagnostics))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser::jsonl::parse_line;
+    use crate::parser::jsonl::{parse_line, ApiErrorKind};
 
     fn create_user_entry(uuid: &str, content: &str) -> Entry {
         let json = format!(
@@ -682,6 +941,143 @@ mod tests {
         assert!(!turn.tool_uses[0].is_error);
     }
 
+    #[test]
+    fn test_privacy_mode_scrubs_content() {
+        let mut aggregator = TurnAggregator::new();
+        aggregator.scrub_content = true;
+
+        aggregator.process_entry(create_user_entry("user-1", "Deploy to prod"));
+        aggregator.process_entry(
+            parse_line(
+                r#"{"type":"assistant","uuid":"asst-1","timestamp":"2026-01-14T07:44:30.000Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"./deploy.sh"}}],"stop_reason":"tool_use","usage":{"input_tokens":100,"output_tokens":50}}}"#,
+            )
+            .unwrap(),
+        );
+        aggregator.process_entry(
+            parse_line(
+                r#"{"type":"user","uuid":"tool-1","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"<tool_use_error>denied</tool_use_error>"}]}}"#,
+            )
+            .unwrap(),
+        );
+        let turn = aggregator
+            .process_entry(create_assistant_entry("asst-2", "tool-1", "end_turn"))
+            .unwrap();
+
+        assert_eq!(turn.user_message, None);
+        assert_eq!(turn.assistant_message, None);
+        assert_eq!(turn.tool_uses[0].name, "Bash");
+        assert_eq!(turn.tool_uses[0].input, None);
+        assert_eq!(turn.tool_uses[0].result, None);
+        // Failures are detected before the result is dropped
+        assert!(turn.tool_uses[0].is_error);
+        assert_eq!(turn.input_tokens, 200);
+        assert_eq!(aggregator.turns()[0].user_message, None);
+    }
+
+    #[test]
+    fn test_compaction_boundaries() {
+        let boundary = |trigger: &str, pre_tokens: u64| {
+            parse_line(&format!(
+                r#"{{"type":"system","subtype":"compact_boundary","compactMetadata":{{"trigger":"{}","preTokens":{}}}}}"#,
+                trigger, pre_tokens
+            ))
+            .unwrap()
+        };
+        let mut aggregator = TurnAggregator::new();
+
+        // Auto-compaction in the middle of turn 1 stays with that turn
+        aggregator.process_entry(create_user_entry("user-1", "Refactor"));
+        aggregator.process_entry(create_assistant_entry("asst-1", "user-1", "tool_use"));
+        aggregator.process_entry(boundary("auto", 150_000));
+        aggregator.process_entry(create_assistant_entry("asst-2", "asst-1", "end_turn"));
+        // A manual /compact between turns carries into turn 2
+        aggregator.process_entry(boundary("manual", 90_000));
+        aggregator.process_entry(create_user_entry("user-2", "Continue"));
+        aggregator.process_entry(create_assistant_entry("asst-3", "user-2", "end_turn"));
+        aggregator.flush();
+
+        let turns = aggregator.turns();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].compactions.len(), 1);
+        assert_eq!(turns[0].compactions[0].trigger.as_deref(), Some("auto"));
+        // The first request after the boundary had 1,600 tokens of context
+        assert_eq!(turns[0].compactions[0].post_tokens, Some(1_600));
+        assert_eq!(turns[0].tokens_lost_to_compaction(), 148_400);
+        assert_eq!(turns[1].compactions[0].trigger.as_deref(), Some("manual"));
+        assert_eq!(turns[1].tokens_lost_to_compaction(), 88_400);
+
+        let stats = SessionStats::from_turns(turns, None);
+        assert_eq!(stats.compaction_count, 2);
+        assert_eq!(stats.tokens_lost_to_compaction, 236_800);
+    }
+
+    #[test]
+    fn test_api_errors() {
+        let retry = parse_line(
+            r#"{"type":"system","subtype":"api_error","level":"error","timestamp":"2026-03-01T10:00:05Z","error":{"status":529,"error":{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}},"retryInMs":1127.4,"retryAttempt":1,"maxRetries":10}"#,
+        )
+        .unwrap();
+        let gave_up = parse_line(
+            r#"{"type":"assistant","uuid":"err-1","isApiErrorMessage":true,"timestamp":"2026-03-01T10:02:00Z","message":{"role":"assistant","model":"<synthetic>","content":[{"type":"text","text":"API Error: 429 {\"type\":\"error\",\"error\":{\"type\":\"rate_limit_error\"}}"}]}}"#,
+        )
+        .unwrap();
+        let mut aggregator = TurnAggregator::new();
+
+        // A retry before the first prompt carries into turn 1
+        aggregator.process_entry(retry.clone());
+        aggregator.process_entry(create_user_entry("user-1", "Refactor"));
+        aggregator.process_entry(retry);
+        aggregator.process_entry(gave_up);
+        aggregator.process_entry(create_assistant_entry("asst-1", "user-1", "end_turn"));
+        aggregator.flush();
+
+        let turns = aggregator.turns();
+        let errors = &turns[0].api_errors;
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].kind, ApiErrorKind::Overloaded);
+        assert_eq!((errors[0].status, errors[0].retry_attempt, errors[0].retry_in_ms), (Some(529), Some(1), Some(1127)));
+        assert_eq!(errors[2].kind, ApiErrorKind::RateLimit);
+        assert_eq!(errors[2].status, Some(429));
+        assert!(errors[2].retries_exhausted);
+        assert_eq!(SessionStats::from_turns(turns, None).api_error_count, 3);
+
+        assert_eq!(ApiErrorKind::classify(None, "Claude AI usage limit reached|1760000000"), ApiErrorKind::RateLimit);
+        assert_eq!(ApiErrorKind::classify(Some(500), "Internal server error"), ApiErrorKind::Other);
+    }
+
+    #[test]
+    fn test_thinking_tokens() {
+        let prompt = parse_line(
+            r#"{"type":"user","uuid":"user-1","timestamp":"2026-03-01T10:00:00Z","thinkingMetadata":{"level":"high","disabled":false},"message":{"role":"user","content":"Plan the migration"}}"#,
+        )
+        .unwrap();
+        let thinking = "x".repeat(400);
+        let reply = parse_line(&format!(
+            r#"{{"type":"assistant","uuid":"asst-1","timestamp":"2026-03-01T10:00:10Z","message":{{"role":"assistant","model":"claude-opus-4-5-20251101","content":[{{"type":"thinking","thinking":"{}"}},{{"type":"text","text":"Done"}}],"stop_reason":"end_turn","usage":{{"input_tokens":10,"output_tokens":150}}}}}}"#,
+            thinking
+        ))
+        .unwrap();
+        let mut aggregator = TurnAggregator::new();
+        aggregator.process_entry(prompt);
+        aggregator.process_entry(reply);
+
+        let turn = &aggregator.turns()[0];
+        assert_eq!(turn.thinking_tokens, 100);
+        assert_eq!(turn.thinking_level.as_deref(), Some("high"));
+        assert_eq!(turn.assistant_message.as_deref(), Some("Done"));
+
+        // Never more than the output tokens billed
+        let mut aggregator = TurnAggregator::new();
+        aggregator.process_entry(create_user_entry("user-2", "Again"));
+        aggregator.process_entry(parse_line(&format!(
+            r#"{{"type":"assistant","uuid":"asst-2","message":{{"role":"assistant","content":[{{"type":"thinking","thinking":"{}"}}],"stop_reason":"end_turn","usage":{{"input_tokens":10,"output_tokens":20}}}}}}"#,
+            thinking
+        ))
+        .unwrap());
+        assert_eq!(aggregator.turns()[0].thinking_tokens, 20);
+        assert_eq!(aggregator.turns()[0].thinking_level, None);
+    }
+
     #[test]
     fn test_subagent_detection() {
         let mut aggregator = TurnAggregator::new();
@@ -728,6 +1124,7 @@ mod tests {
                 assistant_message: Some("Hi".to_string()),
                 model: Some("claude-opus-4-5-20251101".to_string()),
                 stop_reason: Some("end_turn".to_string()),
+                provider: Default::default(),
                 input_tokens: 100,
                 output_tokens: 50,
                 cache_read_tokens: 1000,
@@ -748,6 +1145,10 @@ mod tests {
                 start_uuid: Some("u1".to_string()),
                 end_uuid: Some("a1".to_string()),
                 entry_count: 2,
+                compactions: Vec::new(),
+                api_errors: Vec::new(),
+                thinking_tokens: 0,
+                thinking_level: None,
             },
             CompletedTurn {
                 turn_number: 2,
@@ -758,6 +1159,7 @@ mod tests {
                 assistant_message: Some("Sure".to_string()),
                 model: Some("claude-opus-4-5-20251101".to_string()),
                 stop_reason: Some("end_turn".to_string()),
+                provider: Default::default(),
                 input_tokens: 200,
                 output_tokens: 100,
                 cache_read_tokens: 2000,
@@ -786,6 +1188,10 @@ mod tests {
                 subagent_ids: vec!["agent-1".to_string()],
                 start_uuid: Some("u2".to_string()),
                 end_uuid: Some("a2".to_string()),
+                compactions: Vec::new(),
+                api_errors: Vec::new(),
+                thinking_tokens: 0,
+                thinking_level: None,
                 entry_count: 5,
             },
         ];
@@ -803,4 +1209,64 @@ mod tests {
         assert!(stats.unique_tools.contains(&"Read".to_string()));
         assert_eq!(stats.subagent_count, 1);
     }
+
+    #[test]
+    fn test_incremental_parser_matches_full_parse() {
+        use std::io::Write;
+
+        fn user(uuid: &str) -> String {
+            format!(
+                r#"{{"type":"user","uuid":"{}","timestamp":"2026-01-14T07:44:28.531Z","message":{{"role":"user","content":"Hello"}}}}"#,
+                uuid
+            )
+        }
+        fn assistant(uuid: &str, stop_reason: &str) -> String {
+            format!(
+                r#"{{"type":"assistant","uuid":"{}","timestamp":"2026-01-14T07:44:30.000Z","message":{{"model":"claude-opus-4-5-20251101","role":"assistant","content":[{{"type":"text","text":"Hi"}}],"stop_reason":"{}","usage":{{"input_tokens":100,"output_tokens":50}}}}}}"#,
+                uuid, stop_reason
+            )
+        }
+        fn append(path: &std::path::Path, text: &str) {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        }
+        fn summary(turns: &[CompletedTurn]) -> Vec<(u32, u64, u32, Option<String>)> {
+            turns
+                .iter()
+                .map(|t| (t.turn_number, t.output_tokens, t.entry_count, t.end_uuid.clone()))
+                .collect()
+        }
+
+        let path = std::env::temp_dir().join("ironhide_incremental_parser_test.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        let mut parser = IncrementalSessionParser::new(&path);
+
+        // One finished turn and one still in progress
+        append(&path, &format!("{}\n{}\n{}\n{}\n", user("u1"), assistant("a1", "end_turn"), user("u2"), assistant("a2", "tool_use")));
+        let turns = parser.advance(&[]).unwrap();
+        assert_eq!(summary(&turns), summary(&parse_session_to_turns(&path).unwrap()));
+        assert_eq!(turns.len(), 2);
+
+        // The open turn finishes on a line that has no newline yet
+        append(&path, &assistant("a3", "end_turn"));
+        let position = parser.position();
+        let turns = parser.advance(&turns).unwrap();
+        assert_eq!(parser.position(), position);
+        assert_eq!(summary(&turns), summary(&parse_session_to_turns(&path).unwrap()));
+        assert_eq!(turns[1].output_tokens, 100);
+
+        // Once terminated, the line is committed and parsing continues from there
+        append(&path, &format!("\n{}\n", user("u3")));
+        let turns = parser.advance(&turns).unwrap();
+        assert!(parser.position() > position);
+        assert_eq!(summary(&turns), summary(&parse_session_to_turns(&path).unwrap()));
+        assert_eq!(turns.len(), 3);
+
+        let _ = std::fs::remove_file(&path);
+    }
 }
@@ -0,0 +1,98 @@
+//! Canonical tool names
+//!
+//! The same tool shows up under more than one name: older sessions and
+//! some clients write `bash` or `notebook_edit` for `Bash` and
+//! `NotebookEdit`, and MCP tools are named `mcp__<server>__<tool>`, sometimes
+//! with the prefix in another case. Names are canonicalized as turns are
+//! parsed and loaded, so tool counts don't split one tool in two.
+
+/// Built-in Claude Code tools, in their canonical spelling
+const BUILTIN_TOOLS: &[&str] = &[
+    "Agent",
+    "Bash",
+    "BashOutput",
+    "Edit",
+    "ExitPlanMode",
+    "Glob",
+    "Grep",
+    "KillShell",
+    "LS",
+    "MultiEdit",
+    "NotebookEdit",
+    "NotebookRead",
+    "Read",
+    "Skill",
+    "SlashCommand",
+    "Task",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
+
+const MCP_PREFIX: &str = "mcp__";
+
+/// Canonical name of a tool
+///
+/// Built-in tools match ignoring case, underscores and dashes; MCP tools get
+/// a lowercase `mcp__` prefix with server and tool kept as they are. Other
+/// names are only trimmed.
+pub fn canonical_tool_name(name: &str) -> String {
+    let name = name.trim();
+    if let Some((server, tool)) = split_mcp_tool(name) {
+        return format!("{}{}__{}", MCP_PREFIX, server, tool);
+    }
+
+    let folded: String = name
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    BUILTIN_TOOLS
+        .iter()
+        .find(|builtin| builtin.eq_ignore_ascii_case(&folded))
+        .map_or_else(|| name.to_string(), |builtin| builtin.to_string())
+}
+
+/// Server and tool of an MCP tool name (`mcp__<server>__<tool>`)
+///
+/// The server is everything up to the first `__` after the prefix, so tool
+/// names containing `__` stay whole.
+pub fn split_mcp_tool(name: &str) -> Option<(&str, &str)> {
+    let prefix = name.get(..MCP_PREFIX.len())?;
+    if !prefix.eq_ignore_ascii_case(MCP_PREFIX) {
+        return None;
+    }
+    let (server, tool) = name[MCP_PREFIX.len()..].split_once("__")?;
+    (!server.is_empty() && !tool.is_empty()).then_some((server, tool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_tool_name() {
+        assert_eq!(canonical_tool_name("Bash"), "Bash");
+        assert_eq!(canonical_tool_name("bash"), "Bash");
+        assert_eq!(canonical_tool_name(" todowrite "), "TodoWrite");
+        assert_eq!(canonical_tool_name("notebook_edit"), "NotebookEdit");
+        assert_eq!(canonical_tool_name("web-fetch"), "WebFetch");
+        assert_eq!(canonical_tool_name("MCP__github__create_issue"), "mcp__github__create_issue");
+        assert_eq!(canonical_tool_name("customTool"), "customTool");
+    }
+
+    #[test]
+    fn test_split_mcp_tool() {
+        assert_eq!(split_mcp_tool("mcp__github__create_issue"), Some(("github", "create_issue")));
+        assert_eq!(
+            split_mcp_tool("mcp__claude_ai_Linear__list__issues"),
+            Some(("claude_ai_Linear", "list__issues"))
+        );
+        assert_eq!(split_mcp_tool("Mcp__fs__read"), Some(("fs", "read")));
+        assert_eq!(split_mcp_tool("mcp__server"), None);
+        assert_eq!(split_mcp_tool("mcp____tool"), None);
+        assert_eq!(split_mcp_tool("Bash"), None);
+        assert_eq!(split_mcp_tool("mé"), None);
+    }
+}
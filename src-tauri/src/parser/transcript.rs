@@ -0,0 +1,380 @@
+//! Session transcripts
+//!
+//! Flattens a session's entries into the ordered list of messages a
+//! conversation view shows: user prompts, assistant text, tool calls and
+//! their results, and optionally thinking. Text can be run through secret
+//! redaction and truncated so a long tool output doesn't dominate the
+//! payload.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::jsonl::{ContentBlock, Entry, EntryType, MessageContent};
+use crate::redact::redact_secrets;
+
+/// What a transcript includes and how its text is shortened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptOptions {
+    /// Replace API keys, tokens and private keys with `[REDACTED]`
+    pub redact_secrets: bool,
+    /// Longest user or assistant text, in characters
+    pub max_message_chars: Option<usize>,
+    /// Longest tool result, in characters
+    pub max_tool_result_chars: Option<usize>,
+    pub include_tool_results: bool,
+    pub include_thinking: bool,
+    /// Include the messages of subagents (sidechains)
+    pub include_subagents: bool,
+    /// Messages to skip, for paging
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl Default for TranscriptOptions {
+    fn default() -> Self {
+        Self {
+            redact_secrets: true,
+            max_message_chars: None,
+            max_tool_result_chars: Some(4_000),
+            include_tool_results: true,
+            include_thinking: false,
+            include_subagents: false,
+            offset: 0,
+            limit: None,
+        }
+    }
+}
+
+/// Kind of transcript message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptRole {
+    User,
+    Assistant,
+    Thinking,
+    ToolUse,
+    ToolResult,
+}
+
+/// One message of a transcript
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct TranscriptMessage {
+    /// Position in the full transcript
+    pub index: u32,
+    pub role: TranscriptRole,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub text: Option<String>,
+    /// Tool calls and results: the tool call they belong to
+    pub tool_use_id: Option<String>,
+    pub tool_name: Option<String>,
+    pub tool_input: Option<Value>,
+    pub is_error: bool,
+    /// `text` was cut to the configured length
+    pub truncated: bool,
+    pub model: Option<String>,
+    pub is_sidechain: bool,
+}
+
+impl TranscriptMessage {
+    /// Drop the text and tool input, keeping the role, tool name and
+    /// whether it failed
+    pub fn scrub_content(&mut self) {
+        self.text = None;
+        self.tool_input = None;
+        self.truncated = false;
+    }
+}
+
+/// A session's conversation, or one page of it
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct Transcript {
+    pub session_id: String,
+    pub messages: Vec<TranscriptMessage>,
+    /// Messages in the full transcript, before paging
+    pub total_messages: u32,
+    /// Secrets replaced across the returned messages
+    pub redactions: u32,
+}
+
+/// Builds a [`Transcript`] from a session's entries in file order
+pub struct TranscriptBuilder {
+    options: TranscriptOptions,
+    messages: Vec<TranscriptMessage>,
+    /// Tool names by call ID, for labelling results
+    tool_names: Vec<(String, String)>,
+    total: u32,
+    redactions: u32,
+    /// Secrets replaced in the message being built
+    pending_redactions: u32,
+    /// Scrub message content (privacy mode)
+    scrub_content: bool,
+}
+
+impl TranscriptBuilder {
+    /// Create a builder, scrubbing content if privacy mode is on
+    pub fn new(options: TranscriptOptions) -> Self {
+        Self {
+            options,
+            messages: Vec::new(),
+            tool_names: Vec::new(),
+            total: 0,
+            redactions: 0,
+            pending_redactions: 0,
+            scrub_content: crate::settings::privacy_mode(),
+        }
+    }
+
+    /// Feed the next entry of the session
+    pub fn process_entry(&mut self, entry: Entry) {
+        if entry.is_subagent() && !self.options.include_subagents {
+            return;
+        }
+        // System-injected user entries aren't part of the conversation
+        if entry.raw.get("isMeta").and_then(|v| v.as_bool()) == Some(true) {
+            return;
+        }
+        let content = match &entry.message_content {
+            Some(content) => content,
+            None => return,
+        };
+
+        match (&entry.entry_type, content) {
+            (EntryType::User, MessageContent::ToolResults(results)) => {
+                if !self.options.include_tool_results {
+                    return;
+                }
+                for result in results {
+                    let tool_name = self
+                        .tool_names
+                        .iter()
+                        .find(|(id, _)| *id == result.tool_use_id)
+                        .map(|(_, name)| name.clone());
+                    let mut message = self.message(&entry, TranscriptRole::ToolResult);
+                    message.tool_use_id = Some(result.tool_use_id.clone());
+                    message.tool_name = tool_name;
+                    message.is_error = result.is_failure();
+                    self.set_text(&mut message, &result.text(), self.options.max_tool_result_chars);
+                    self.push(message);
+                }
+            }
+            (EntryType::User, content) => {
+                if let Some(text) = content.as_text() {
+                    let mut message = self.message(&entry, TranscriptRole::User);
+                    self.set_text(&mut message, &text, self.options.max_message_chars);
+                    self.push(message);
+                }
+            }
+            (EntryType::Assistant, MessageContent::Blocks(blocks)) => {
+                for block in blocks {
+                    match block {
+                        ContentBlock::Text { text } => {
+                            let mut message = self.message(&entry, TranscriptRole::Assistant);
+                            self.set_text(&mut message, text, self.options.max_message_chars);
+                            self.push(message);
+                        }
+                        ContentBlock::Thinking { thinking } if self.options.include_thinking => {
+                            let mut message = self.message(&entry, TranscriptRole::Thinking);
+                            self.set_text(&mut message, thinking, self.options.max_message_chars);
+                            self.push(message);
+                        }
+                        ContentBlock::ToolUse { id, name, input } => {
+                            self.tool_names.push((id.clone(), name.clone()));
+                            let mut message = self.message(&entry, TranscriptRole::ToolUse);
+                            message.tool_use_id = Some(id.clone());
+                            message.tool_name = Some(name.clone());
+                            message.tool_input = Some(self.redact_value(input));
+                            self.push(message);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            (EntryType::Assistant, MessageContent::Text(text)) => {
+                let mut message = self.message(&entry, TranscriptRole::Assistant);
+                self.set_text(&mut message, text, self.options.max_message_chars);
+                self.push(message);
+            }
+            _ => {}
+        }
+    }
+
+    /// The requested page of the transcript
+    pub fn finish(self, session_id: String) -> Transcript {
+        Transcript {
+            session_id,
+            messages: self.messages,
+            total_messages: self.total,
+            redactions: self.redactions,
+        }
+    }
+
+    fn message(&self, entry: &Entry, role: TranscriptRole) -> TranscriptMessage {
+        TranscriptMessage {
+            index: self.total,
+            role,
+            uuid: entry.uuid.clone(),
+            timestamp: entry.timestamp.clone(),
+            text: None,
+            tool_use_id: None,
+            tool_name: None,
+            tool_input: None,
+            is_error: false,
+            truncated: false,
+            model: entry.model.clone(),
+            is_sidechain: entry.is_sidechain,
+        }
+    }
+
+    /// Keep the message if it falls on the requested page
+    fn push(&mut self, mut message: TranscriptMessage) {
+        if self.scrub_content {
+            message.scrub_content();
+            self.pending_redactions = 0;
+        }
+        let index = self.total as usize;
+        self.total += 1;
+        let page_end = self.options.limit.map(|limit| self.options.offset + limit);
+        let redactions = std::mem::take(&mut self.pending_redactions);
+        if index >= self.options.offset && page_end.is_none_or(|end| index < end) {
+            self.redactions += redactions;
+            self.messages.push(message);
+        }
+    }
+
+    /// Redact, then truncate, the message text
+    fn set_text(&mut self, message: &mut TranscriptMessage, text: &str, max_chars: Option<usize>) {
+        let text = self.redact(text);
+        let (text, truncated) = truncate_chars(text, max_chars);
+        message.text = Some(text);
+        message.truncated = truncated;
+    }
+
+    fn redact(&mut self, text: &str) -> String {
+        if !self.options.redact_secrets {
+            return text.to_string();
+        }
+        let (redacted, count) = redact_secrets(text);
+        self.pending_redactions += count;
+        redacted.into_owned()
+    }
+
+    /// Redact the string values of a tool input
+    fn redact_value(&mut self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact(s)),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.redact_value(v)).collect()),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, v)| (key.clone(), self.redact_value(v)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+/// Cut `text` to at most `max_chars` characters
+fn truncate_chars(text: String, max_chars: Option<usize>) -> (String, bool) {
+    match max_chars {
+        Some(max) if text.chars().count() > max => (text.chars().take(max).collect(), true),
+        _ => (text, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::jsonl::parse_line;
+
+    fn entries() -> Vec<Entry> {
+        [
+            r#"{"type":"user","uuid":"u1","timestamp":"2026-02-05T10:00:00Z","message":{"role":"user","content":"Deploy with key sk-ant-REDACTED"}}"#,
+            r#"{"type":"user","uuid":"m1","isMeta":true,"message":{"role":"user","content":"<local-command-caveat>"}}"#,
+            r#"{"type":"assistant","uuid":"a1","message":{"role":"assistant","model":"claude-opus-4-5","content":[{"type":"thinking","thinking":"Plan it"},{"type":"text","text":"Running the deploy."},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"TOKEN=abcdef0123456789 ./deploy.sh"}}]}}"#,
+            r#"{"type":"user","uuid":"r1","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"deploy failed: permission denied","is_error":true}]}}"#,
+            r#"{"type":"assistant","uuid":"s1","isSidechain":true,"agentId":"a1b2","message":{"role":"assistant","content":[{"type":"text","text":"Subagent work"}]}}"#,
+            r#"{"type":"assistant","uuid":"a2","message":{"role":"assistant","content":[{"type":"text","text":"The deploy needs more permissions."}]}}"#,
+        ]
+        .iter()
+        .map(|line| parse_line(line).unwrap())
+        .collect()
+    }
+
+    fn build(options: TranscriptOptions) -> Transcript {
+        let mut builder = TranscriptBuilder::new(options);
+        for entry in entries() {
+            builder.process_entry(entry);
+        }
+        builder.finish("s1".to_string())
+    }
+
+    #[test]
+    fn test_transcript() {
+        let transcript = build(TranscriptOptions::default());
+        let roles: Vec<TranscriptRole> = transcript.messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                TranscriptRole::User,
+                TranscriptRole::Assistant,
+                TranscriptRole::ToolUse,
+                TranscriptRole::ToolResult,
+                TranscriptRole::Assistant,
+            ]
+        );
+        assert_eq!(transcript.messages[0].text.as_deref(), Some("Deploy with key [REDACTED]"));
+        assert_eq!(
+            transcript.messages[2].tool_input,
+            Some(serde_json::json!({"command": "TOKEN=[REDACTED] ./deploy.sh"}))
+        );
+        let result = &transcript.messages[3];
+        assert!(result.is_error);
+        assert_eq!(result.tool_name.as_deref(), Some("Bash"));
+        assert_eq!(transcript.redactions, 2);
+        assert_eq!(transcript.total_messages, 5);
+    }
+
+    #[test]
+    fn test_transcript_options() {
+        let transcript = build(TranscriptOptions {
+            redact_secrets: false,
+            max_message_chars: Some(6),
+            include_tool_results: false,
+            include_thinking: true,
+            include_subagents: true,
+            offset: 1,
+            limit: Some(3),
+            ..TranscriptOptions::default()
+        });
+        // Thinking and the subagent's message are in, the tool result is out
+        assert_eq!(transcript.total_messages, 6);
+        let texts: Vec<Option<&str>> = transcript.messages.iter().map(|m| m.text.as_deref()).collect();
+        assert_eq!(texts, vec![Some("Plan i"), Some("Runnin"), None]);
+        assert!(transcript.messages[0].truncated);
+        assert_eq!(transcript.messages[0].index, 1);
+        assert_eq!(transcript.redactions, 0);
+    }
+
+    #[test]
+    fn test_transcript_scrubbed() {
+        let mut builder = TranscriptBuilder::new(TranscriptOptions::default());
+        builder.scrub_content = true;
+        for entry in entries() {
+            builder.process_entry(entry);
+        }
+        let transcript = builder.finish("s1".to_string());
+
+        assert_eq!(transcript.total_messages, 5);
+        assert!(transcript.messages.iter().all(|m| m.text.is_none() && m.tool_input.is_none()));
+        let result = &transcript.messages[3];
+        assert_eq!(result.role, TranscriptRole::ToolResult);
+        assert_eq!(result.tool_name.as_deref(), Some("Bash"));
+        assert!(result.is_error);
+        assert_eq!(transcript.redactions, 0);
+    }
+}
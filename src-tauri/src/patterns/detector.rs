@@ -3,10 +3,10 @@
 //! Implements detection algorithms for various anti-patterns
 //! in Claude Code sessions.
 
-use std::collections::HashMap;
-
-use crate::metrics::cost::calculate_turn_cost;
+use crate::metrics::api_errors::find_retry_storms;
+use crate::metrics::cost::calculate_provider_turn_cost;
 use crate::metrics::efficiency::{calculate_cer_raw, calculate_sei_f64};
+use crate::metrics::rework::{analyze_rework, extract_code_changes};
 use crate::metrics::session::estimate_deliverable_units;
 use crate::metrics::tokens::TurnTokens;
 use crate::parser::{find_session_by_id, parse_session_by_id, scan_claude_sessions, CompletedTurn};
@@ -61,30 +61,59 @@ pub fn detect_antipatterns(
             continue;
         }
 
-        // Run each detector
-        for pattern_type in &patterns_to_check {
-            let patterns = match pattern_type {
-                AntiPatternType::SubagentSprawl => {
-                    detect_subagent_sprawl(&session_id, &turns, &thresholds)
-                }
-                AntiPatternType::ContextChurn => {
-                    detect_context_churn(&session_id, &turns, &thresholds)
-                }
-                AntiPatternType::CostSpike => detect_cost_spike(&session_id, &turns, &thresholds),
-                AntiPatternType::LongTurn => detect_long_turn(&session_id, &turns, &thresholds),
-                AntiPatternType::ToolFailureSpree => {
-                    detect_tool_failure_spree(&session_id, &turns, &thresholds)
-                }
-                AntiPatternType::HighReworkRatio => {
-                    detect_high_rework_ratio(&session_id, &turns, &thresholds)
-                }
-            };
+        detected.extend(detect_in_turns(
+            &session_id,
+            &turns,
+            &patterns_to_check,
+            &thresholds,
+        ));
+    }
 
-            detected.extend(patterns);
-        }
+    sort_by_severity(&mut detected);
+    Ok(detected)
+}
+
+/// Run the given detectors over already-parsed turns of a single session
+///
+/// Results are sorted by severity (critical first) then impact cost.
+pub fn detect_in_turns(
+    session_id: &str,
+    turns: &[CompletedTurn],
+    pattern_types: &[AntiPatternType],
+    thresholds: &DetectionThresholds,
+) -> Vec<DetectedPattern> {
+    let mut detected = Vec::new();
+    if turns.is_empty() {
+        return detected;
+    }
+
+    for pattern_type in pattern_types {
+        let patterns = match pattern_type {
+            AntiPatternType::SubagentSprawl => detect_subagent_sprawl(session_id, turns, thresholds),
+            AntiPatternType::ContextChurn => detect_context_churn(session_id, turns, thresholds),
+            AntiPatternType::CostSpike => detect_cost_spike(session_id, turns, thresholds),
+            AntiPatternType::LongTurn => detect_long_turn(session_id, turns, thresholds),
+            AntiPatternType::ToolFailureSpree => {
+                detect_tool_failure_spree(session_id, turns, thresholds)
+            }
+            AntiPatternType::HighReworkRatio => {
+                detect_high_rework_ratio(session_id, turns, thresholds)
+            }
+            AntiPatternType::CompactionHeavy => {
+                detect_compaction_heavy(session_id, turns, thresholds)
+            }
+            AntiPatternType::RetryStorm => detect_retry_storm(session_id, turns, thresholds),
+        };
+
+        detected.extend(patterns);
     }
 
-    // Sort by severity (critical first), then by impact cost (highest first)
+    sort_by_severity(&mut detected);
+    detected
+}
+
+/// Sort by severity (critical first), then by impact cost (highest first)
+fn sort_by_severity(detected: &mut [DetectedPattern]) {
     detected.sort_by(|a, b| {
         let sev_order = |s: &Severity| match s {
             Severity::Critical => 0,
@@ -102,8 +131,6 @@ pub fn detect_antipatterns(
             .partial_cmp(&a.impact_cost)
             .unwrap_or(std::cmp::Ordering::Equal)
     });
-
-    Ok(detected)
 }
 
 /// Detect SubagentSprawl: SEI < threshold
@@ -130,8 +157,8 @@ fn detect_subagent_sprawl(
             turn.cache_write_5m_tokens,
             turn.cache_write_1h_tokens,
         );
-        let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-        total_cost += calculate_turn_cost(&turn_tokens, model).total_cost;
+        let model = turn.model.as_deref();
+        total_cost += calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at)).total_cost;
     }
 
     if subagent_count == 0 {
@@ -203,8 +230,8 @@ fn detect_context_churn(
             turn.cache_write_5m_tokens,
             turn.cache_write_1h_tokens,
         );
-        let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-        total_cost += calculate_turn_cost(&turn_tokens, model).total_cost;
+        let model = turn.model.as_deref();
+        total_cost += calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at)).total_cost;
     }
 
     // Need some cache activity to analyze
@@ -270,8 +297,8 @@ fn detect_cost_spike(
                 turn.cache_write_5m_tokens,
                 turn.cache_write_1h_tokens,
             );
-            let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-            let cost = calculate_turn_cost(&turn_tokens, model).total_cost;
+            let model = turn.model.as_deref();
+            let cost = calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at)).total_cost;
             (turn.turn_number, cost)
         })
         .collect();
@@ -335,8 +362,8 @@ fn detect_long_turn(
                 turn.cache_write_5m_tokens,
                 turn.cache_write_1h_tokens,
             );
-            let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-            calculate_turn_cost(&turn_tokens, model).total_cost
+            let model = turn.model.as_deref();
+            calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at)).total_cost
         })
         .sum();
 
@@ -430,8 +457,8 @@ fn detect_tool_failure_spree(
                 turn.cache_write_5m_tokens,
                 turn.cache_write_1h_tokens,
             );
-            let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-            calculate_turn_cost(&turn_tokens, model).total_cost
+            let model = turn.model.as_deref();
+            calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at)).total_cost
         })
         .sum();
     let avg_turn_cost = total_cost / turns.len().max(1) as f64;
@@ -465,7 +492,7 @@ fn detect_tool_failure_spree(
     patterns
 }
 
-/// Detect HighReworkRatio: Many edits to same files
+/// Detect HighReworkRatio: Share of edits revisiting files changed in earlier turns
 fn detect_high_rework_ratio(
     session_id: &str,
     turns: &[CompletedTurn],
@@ -473,40 +500,24 @@ fn detect_high_rework_ratio(
 ) -> Vec<DetectedPattern> {
     let mut patterns = Vec::new();
 
-    // Track file edit counts
-    let mut file_edits: HashMap<String, u32> = HashMap::new();
-    let mut total_edits = 0u32;
-
-    for turn in turns {
-        for tool_use in &turn.tool_uses {
-            // Check for Write or Edit tools
-            if matches!(tool_use.name.as_str(), "Write" | "Edit" | "write" | "edit") {
-                if let Some(input) = &tool_use.input {
-                    if let Some(file_path) = input.get("file_path").and_then(|v| v.as_str()) {
-                        *file_edits.entry(file_path.to_string()).or_insert(0) += 1;
-                        total_edits += 1;
-                    }
-                }
-            }
-        }
-    }
+    let analysis = analyze_rework(&extract_code_changes(turns));
+    let total_edits = analysis.total_edits;
+    let rework_edits = analysis.rework_edits;
 
     if total_edits < 3 {
         return patterns; // Not enough edits to analyze
     }
 
-    // Calculate rework: edits beyond the first to each file
-    let rework_edits: u32 = file_edits.values().map(|&count| count.saturating_sub(1)).sum();
-    let rework_ratio = rework_edits as f64 / total_edits as f64;
+    let rework_ratio = analysis.rework_ratio();
 
     if rework_ratio > thresholds.rework_ratio_max {
-        // Find the most reworked files
-        let mut files: Vec<(&String, &u32)> = file_edits.iter().filter(|(_, &c)| c > 1).collect();
-        files.sort_by(|a, b| b.1.cmp(a.1));
-        let top_files: Vec<String> = files
+        // Files with the most churn
+        let top_files: Vec<String> = analysis
+            .files
             .iter()
+            .filter(|f| f.rework_edits > 0)
             .take(3)
-            .map(|(f, c)| format!("{} ({}x)", f, c))
+            .map(|f| format!("{} ({}x over {} turns)", f.file_path, f.edits, f.turns.len()))
             .collect();
 
         // Calculate cost impact
@@ -520,8 +531,8 @@ fn detect_high_rework_ratio(
                     turn.cache_write_5m_tokens,
                     turn.cache_write_1h_tokens,
                 );
-                let model = turn.model.as_deref().unwrap_or("claude-opus-4-5-20251101");
-                calculate_turn_cost(&turn_tokens, model).total_cost
+                let model = turn.model.as_deref();
+                calculate_provider_turn_cost(&turn_tokens, model, turn.provider, Some(&turn.started_at)).total_cost
             })
             .sum();
 
@@ -540,7 +551,7 @@ fn detect_high_rework_ratio(
             session_id.to_string(),
             None,
             format!(
-                "High rework detected: {:.0}% of edits ({}/{}) are to previously edited files. Top files: {}",
+                "High rework detected: {:.0}% of edits ({}/{}) revisit files changed in earlier turns. Top files: {}",
                 rework_ratio * 100.0, rework_edits, total_edits, top_files.join(", ")
             ),
             impact_cost,
@@ -553,10 +564,104 @@ fn detect_high_rework_ratio(
     patterns
 }
 
+/// Detect CompactionHeavy: Compactions per session > threshold
+fn detect_compaction_heavy(
+    session_id: &str,
+    turns: &[CompletedTurn],
+    thresholds: &DetectionThresholds,
+) -> Vec<DetectedPattern> {
+    let mut patterns = Vec::new();
+
+    let compacted: Vec<&CompletedTurn> = turns.iter().filter(|t| !t.compactions.is_empty()).collect();
+    let compactions: u32 = compacted.iter().map(|t| t.compactions.len() as u32).sum();
+    if compactions <= thresholds.compactions_max {
+        return patterns;
+    }
+
+    // The discarded context had been paid for as cache writes; building it
+    // again after each compaction costs about as much
+    let mut tokens_lost = 0u64;
+    let mut impact_cost = 0.0f64;
+    for turn in &compacted {
+        let lost = turn.tokens_lost_to_compaction();
+        tokens_lost += lost;
+        let model = turn.model.as_deref();
+        let lost_tokens = TurnTokens::new(0, 0, 0, lost, 0);
+        impact_cost += calculate_provider_turn_cost(&lost_tokens, model, turn.provider, Some(&turn.started_at)).total_cost;
+    }
+
+    let severity = if compactions > thresholds.compactions_max * 2 {
+        Severity::Critical
+    } else {
+        Severity::Warning
+    };
+
+    patterns.push(DetectedPattern::new(
+        AntiPatternType::CompactionHeavy,
+        severity,
+        session_id.to_string(),
+        compacted.first().map(|t| t.turn_number),
+        format!(
+            "Session was compacted {} times, dropping {} tokens of context",
+            compactions, tokens_lost
+        ),
+        impact_cost,
+        "Split long-running work into separate sessions, and keep large file reads and command output out of the main conversation (e.g. delegate them to subagents).".to_string(),
+        compactions as f64,
+        thresholds.compactions_max as f64,
+    ));
+
+    patterns
+}
+
+/// Detect RetryStorm: API errors within the window >= threshold
+fn detect_retry_storm(
+    session_id: &str,
+    turns: &[CompletedTurn],
+    thresholds: &DetectionThresholds,
+) -> Vec<DetectedPattern> {
+    let storms = find_retry_storms(turns, thresholds.retry_storm_window_secs, thresholds.retry_storm_errors);
+
+    storms
+        .into_iter()
+        .map(|storm| {
+            let counts = &storm.counts;
+            let severity = if counts.total >= thresholds.retry_storm_errors * 2 || counts.retries_exhausted > 0 {
+                Severity::Critical
+            } else {
+                Severity::Warning
+            };
+            let span_secs = (storm.ended_at - storm.started_at).num_seconds();
+
+            // Rejected requests aren't billed; the cost is the time spent waiting
+            DetectedPattern::new(
+                AntiPatternType::RetryStorm,
+                severity,
+                session_id.to_string(),
+                Some(storm.turn_number),
+                format!(
+                    "{} API errors in {}s from turn {} ({} rate limited, {} overloaded), {:.0}s spent waiting to retry",
+                    counts.total,
+                    span_secs,
+                    storm.turn_number,
+                    counts.rate_limit,
+                    counts.overloaded,
+                    counts.retry_wait_ms as f64 / 1000.0
+                ),
+                0.0,
+                "Back off when limits are hit: run fewer sessions and subagents in parallel, or move heavy work to a less busy model or time of day.".to_string(),
+                counts.total as f64,
+                thresholds.retry_storm_errors as f64,
+            )
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::parser::session::ToolUse;
+    use crate::parser::{ApiErrorEvent, ApiErrorKind, CompactBoundary};
 
     fn create_test_turn(turn_number: u32, input_tokens: u64, output_tokens: u64) -> CompletedTurn {
         CompletedTurn {
@@ -568,6 +673,7 @@ mod tests {
             assistant_message: Some("Response".to_string()),
             model: Some("claude-opus-4-5-20251101".to_string()),
             stop_reason: Some("end_turn".to_string()),
+            provider: Default::default(),
             input_tokens,
             output_tokens,
             cache_read_tokens: 1000,
@@ -582,6 +688,10 @@ mod tests {
             start_uuid: None,
             end_uuid: None,
             entry_count: 2,
+            compactions: Vec::new(),
+            api_errors: Vec::new(),
+            thinking_tokens: 0,
+            thinking_level: None,
         }
     }
 
@@ -717,6 +827,25 @@ mod tests {
         assert_eq!(patterns[0].pattern_type, AntiPatternType::HighReworkRatio);
     }
 
+    #[test]
+    fn test_detect_high_rework_ratio_same_turn_edits() {
+        // Several edits to one file within a single turn are one change, not rework
+        let mut turn = create_test_turn(1, 1000, 500);
+        turn.tool_uses = (1..=5)
+            .map(|i| ToolUse {
+                id: format!("t{}", i),
+                name: "Edit".to_string(),
+                input: Some(serde_json::json!({"file_path": "/path/to/file.rs"})),
+                result: Some("ok".to_string()),
+                is_error: false,
+            })
+            .collect();
+        turn.tool_count = 5;
+
+        let thresholds = DetectionThresholds::default();
+        assert!(detect_high_rework_ratio("test-session", &[turn], &thresholds).is_empty());
+    }
+
     #[test]
     fn test_no_false_positives_for_healthy_session() {
         let turns: Vec<CompletedTurn> = (1..=3)
@@ -748,6 +877,63 @@ mod tests {
         assert!(detect_long_turn("test-session", &turns, &thresholds).is_empty());
         assert!(detect_tool_failure_spree("test-session", &turns, &thresholds).is_empty());
         assert!(detect_high_rework_ratio("test-session", &turns, &thresholds).is_empty());
+        assert!(detect_compaction_heavy("test-session", &turns, &thresholds).is_empty());
+        assert!(detect_retry_storm("test-session", &turns, &thresholds).is_empty());
+    }
+
+    #[test]
+    fn test_detect_compaction_heavy() {
+        let compaction = |pre, post| CompactBoundary {
+            timestamp: None,
+            trigger: Some("auto".to_string()),
+            pre_tokens: Some(pre),
+            post_tokens: Some(post),
+        };
+        let mut turns: Vec<CompletedTurn> = (1..=4).map(|i| create_test_turn(i, 1000, 500)).collect();
+        turns[1].compactions = vec![compaction(180_000, 30_000)];
+        turns[2].compactions = vec![compaction(175_000, 25_000)];
+        let thresholds = DetectionThresholds::default();
+
+        // Two compactions are within the default allowance
+        assert!(detect_compaction_heavy("test-session", &turns, &thresholds).is_empty());
+
+        turns[3].compactions = vec![compaction(190_000, 40_000)];
+        let patterns = detect_compaction_heavy("test-session", &turns, &thresholds);
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].pattern_type, AntiPatternType::CompactionHeavy);
+        assert_eq!(patterns[0].severity, Severity::Warning);
+        assert_eq!(patterns[0].turn_number, Some(2));
+        assert!((patterns[0].metric_value - 3.0).abs() < 0.001);
+        assert!(patterns[0].description.contains("450000 tokens"));
+        assert!(patterns[0].impact_cost > 0.0);
+    }
+
+    #[test]
+    fn test_detect_retry_storm() {
+        let overloaded = |minute: u32, second: u32| ApiErrorEvent {
+            timestamp: Some(format!("2026-03-01T10:{:02}:{:02}Z", minute, second)),
+            kind: ApiErrorKind::Overloaded,
+            status: Some(529),
+            retry_attempt: Some(1),
+            retry_in_ms: Some(2_000),
+            retries_exhausted: false,
+        };
+        let mut turns: Vec<CompletedTurn> = (1..=3).map(|i| create_test_turn(i, 1000, 500)).collect();
+        // Spread out errors don't count
+        turns[0].api_errors = (0..4).map(|i| overloaded(i * 10, 0)).collect();
+        turns[1].api_errors = vec![overloaded(41, 0), overloaded(41, 30)];
+        let thresholds = DetectionThresholds::default();
+        assert!(detect_retry_storm("test-session", &turns, &thresholds).is_empty());
+
+        // Five within five minutes, spanning two turns
+        turns[2].api_errors = vec![overloaded(42, 0), overloaded(43, 0), overloaded(44, 0)];
+        let patterns = detect_retry_storm("test-session", &turns, &thresholds);
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].pattern_type, AntiPatternType::RetryStorm);
+        assert_eq!(patterns[0].severity, Severity::Warning);
+        assert_eq!(patterns[0].turn_number, Some(2));
+        assert!((patterns[0].metric_value - 5.0).abs() < 0.001);
+        assert!(patterns[0].description.contains("5 API errors in 180s"));
     }
 
     #[test]
@@ -7,9 +7,11 @@
 //! - LongTurn: Turn duration exceeds threshold
 //! - ToolFailureSpree: Consecutive tool failures
 //! - HighReworkRatio: Many edits to same files
+//! - CompactionHeavy: Context compacted repeatedly
+//! - RetryStorm: Bursts of rate-limit and overload retries
 
 pub mod detector;
 pub mod types;
 
-pub use detector::detect_antipatterns;
+pub use detector::{detect_antipatterns, detect_in_turns};
 pub use types::{AntiPatternType, DetectedPattern};
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 
 /// Types of anti-patterns that can be detected in Claude Code sessions
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum AntiPatternType {
     /// SEI < 0.1 (too many subagents for output)
@@ -21,6 +22,10 @@ pub enum AntiPatternType {
     ToolFailureSpree,
     /// Many edits to same files (rework)
     HighReworkRatio,
+    /// Conversation compacted more than 2 times
+    CompactionHeavy,
+    /// 5+ API errors (rate limits, overloads) within 5 minutes
+    RetryStorm,
 }
 
 impl AntiPatternType {
@@ -33,6 +38,8 @@ impl AntiPatternType {
             Self::LongTurn => "Long Turn",
             Self::ToolFailureSpree => "Tool Failure Spree",
             Self::HighReworkRatio => "High Rework Ratio",
+            Self::CompactionHeavy => "Compaction Heavy",
+            Self::RetryStorm => "Retry Storm",
         }
     }
 
@@ -45,6 +52,8 @@ impl AntiPatternType {
             Self::LongTurn => "Turn took longer than expected",
             Self::ToolFailureSpree => "Multiple consecutive tool failures detected",
             Self::HighReworkRatio => "High ratio of repeated edits to same files",
+            Self::CompactionHeavy => "Session repeatedly ran out of context and was compacted",
+            Self::RetryStorm => "Requests were retried again and again against rate limits or an overloaded API",
         }
     }
 
@@ -57,9 +66,31 @@ impl AntiPatternType {
             Self::LongTurn,
             Self::ToolFailureSpree,
             Self::HighReworkRatio,
+            Self::CompactionHeavy,
+            Self::RetryStorm,
         ]
     }
 
+    /// Snake-case name, as serialized and stored
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SubagentSprawl => "subagent_sprawl",
+            Self::ContextChurn => "context_churn",
+            Self::CostSpike => "cost_spike",
+            Self::LongTurn => "long_turn",
+            Self::ToolFailureSpree => "tool_failure_spree",
+            Self::HighReworkRatio => "high_rework_ratio",
+            Self::CompactionHeavy => "compaction_heavy",
+            Self::RetryStorm => "retry_storm",
+        }
+    }
+
+    /// Whether the pattern is detected when the metric falls below the
+    /// threshold rather than above it
+    pub fn below_threshold(&self) -> bool {
+        matches!(self, Self::SubagentSprawl | Self::ContextChurn)
+    }
+
     /// Parse from string
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
@@ -69,6 +100,8 @@ impl AntiPatternType {
             "long_turn" | "longturn" => Some(Self::LongTurn),
             "tool_failure_spree" | "toolfailurespree" => Some(Self::ToolFailureSpree),
             "high_rework_ratio" | "highreworkratio" => Some(Self::HighReworkRatio),
+            "compaction_heavy" | "compactionheavy" => Some(Self::CompactionHeavy),
+            "retry_storm" | "retrystorm" => Some(Self::RetryStorm),
             _ => None,
         }
     }
@@ -76,6 +109,7 @@ impl AntiPatternType {
 
 /// Severity level for detected patterns
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     /// Informational - might indicate inefficiency
@@ -95,10 +129,23 @@ impl Severity {
             Self::Critical => "critical",
         }
     }
+
+    /// Base of the severity score
+    pub fn weight(&self) -> f64 {
+        match self {
+            Self::Info => 1.0,
+            Self::Warning => 2.0,
+            Self::Critical => 3.0,
+        }
+    }
 }
 
+/// Furthest past its threshold a metric counts towards the severity score
+const MAX_THRESHOLD_OVERSHOOT: f64 = 3.0;
+
 /// A detected anti-pattern instance
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct DetectedPattern {
     /// Type of anti-pattern detected
     pub pattern_type: AntiPatternType,
@@ -146,6 +193,25 @@ impl DetectedPattern {
         }
     }
 
+    /// Severity weight times how far the metric went past its threshold
+    ///
+    /// Ranges from 1 (info, just past the threshold) to 9 (critical, three
+    /// or more times past it), so patterns of the same severity still rank.
+    pub fn severity_score(&self) -> f64 {
+        let overshoot = if self.pattern_type.below_threshold() {
+            if self.metric_value > 0.0 {
+                self.threshold / self.metric_value
+            } else {
+                MAX_THRESHOLD_OVERSHOOT
+            }
+        } else if self.threshold > 0.0 {
+            self.metric_value / self.threshold
+        } else {
+            1.0
+        };
+        self.severity.weight() * overshoot.clamp(1.0, MAX_THRESHOLD_OVERSHOOT)
+    }
+
     /// Get severity as string (for backwards compatibility)
     pub fn severity_str(&self) -> &'static str {
         self.severity.as_str()
@@ -167,6 +233,12 @@ pub struct DetectionThresholds {
     pub consecutive_failures: u32,
     /// Rework ratio threshold for HighReworkRatio (default: 0.4)
     pub rework_ratio_max: f64,
+    /// Compactions per session allowed before CompactionHeavy (default: 2)
+    pub compactions_max: u32,
+    /// API errors within the window for RetryStorm (default: 5)
+    pub retry_storm_errors: u32,
+    /// RetryStorm window in seconds (default: 300 = 5 min)
+    pub retry_storm_window_secs: i64,
 }
 
 impl Default for DetectionThresholds {
@@ -178,6 +250,9 @@ impl Default for DetectionThresholds {
             long_turn_ms: 300_000, // 5 minutes
             consecutive_failures: 3,
             rework_ratio_max: 0.4,
+            compactions_max: 2,
+            retry_storm_errors: 5,
+            retry_storm_window_secs: 300, // 5 minutes
         }
     }
 }
@@ -194,6 +269,7 @@ mod tests {
         assert_eq!(AntiPatternType::LongTurn.display_name(), "Long Turn");
         assert_eq!(AntiPatternType::ToolFailureSpree.display_name(), "Tool Failure Spree");
         assert_eq!(AntiPatternType::HighReworkRatio.display_name(), "High Rework Ratio");
+        assert_eq!(AntiPatternType::CompactionHeavy.display_name(), "Compaction Heavy");
     }
 
     #[test]
@@ -216,7 +292,7 @@ mod tests {
     #[test]
     fn test_antipattern_type_all() {
         let all = AntiPatternType::all();
-        assert_eq!(all.len(), 6);
+        assert_eq!(all.len(), 8);
     }
 
     #[test]
@@ -279,6 +355,9 @@ mod tests {
         assert_eq!(thresholds.long_turn_ms, 300_000);
         assert_eq!(thresholds.consecutive_failures, 3);
         assert!((thresholds.rework_ratio_max - 0.4).abs() < 0.001);
+        assert_eq!(thresholds.compactions_max, 2);
+        assert_eq!(thresholds.retry_storm_errors, 5);
+        assert_eq!(thresholds.retry_storm_window_secs, 300);
     }
 
     #[test]
@@ -289,6 +368,7 @@ mod tests {
         assert!(!AntiPatternType::LongTurn.description().is_empty());
         assert!(!AntiPatternType::ToolFailureSpree.description().is_empty());
         assert!(!AntiPatternType::HighReworkRatio.description().is_empty());
+        assert!(!AntiPatternType::CompactionHeavy.description().is_empty());
     }
 
     #[test]
@@ -337,6 +417,36 @@ mod tests {
         assert_eq!(pattern.severity_str(), "critical");
     }
 
+    #[test]
+    fn test_severity_score() {
+        let pattern = |pattern_type, severity, metric_value, threshold| {
+            DetectedPattern::new(
+                pattern_type,
+                severity,
+                "s1".to_string(),
+                None,
+                String::new(),
+                0.0,
+                String::new(),
+                metric_value,
+                threshold,
+            )
+        };
+
+        // Cost 4x the average against a 3x multiplier
+        let spike = pattern(AntiPatternType::CostSpike, Severity::Warning, 4.0, 3.0);
+        assert!((spike.severity_score() - 2.0 * 4.0 / 3.0).abs() < 1e-9);
+        // CER of 0.1 is four times short of 0.4, capped at three
+        let churn = pattern(AntiPatternType::ContextChurn, Severity::Critical, 0.1, 0.4);
+        assert!((churn.severity_score() - 9.0).abs() < 1e-9);
+        let sprawl = pattern(AntiPatternType::SubagentSprawl, Severity::Warning, 0.0, 0.1);
+        assert!((sprawl.severity_score() - 6.0).abs() < 1e-9);
+
+        for pattern_type in AntiPatternType::all() {
+            assert_eq!(AntiPatternType::from_str(pattern_type.as_str()), Some(pattern_type));
+        }
+    }
+
     #[test]
     fn test_detected_pattern_deserialization() {
         let json = r#"{
@@ -367,6 +477,9 @@ mod tests {
             long_turn_ms: 600_000,
             consecutive_failures: 5,
             rework_ratio_max: 0.6,
+            compactions_max: 4,
+            retry_storm_errors: 10,
+            retry_storm_window_secs: 600,
         };
 
         assert!((thresholds.sei_min - 0.2).abs() < 0.001);
@@ -375,5 +488,7 @@ mod tests {
         assert_eq!(thresholds.long_turn_ms, 600_000);
         assert_eq!(thresholds.consecutive_failures, 5);
         assert!((thresholds.rework_ratio_max - 0.6).abs() < 0.001);
+        assert_eq!(thresholds.compactions_max, 4);
+        assert_eq!(thresholds.retry_storm_errors, 10);
     }
 }
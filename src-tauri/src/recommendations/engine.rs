@@ -2,8 +2,17 @@
 //!
 //! Core logic for analyzing session metrics and generating actionable recommendations.
 
-use super::types::{Recommendation, RecommendationInput, RecommendationSummary, RecommendationType};
-use crate::metrics::cost::{find_pricing, get_default_pricing};
+use chrono::{DateTime, Utc};
+
+use super::types::{
+    confidence_rank, recommendation_id, Recommendation, RecommendationFeedback, RecommendationInput,
+    RecommendationSummary, RecommendationType,
+};
+use crate::metrics::cost::find_pricing;
+use crate::metrics::mcp::McpServerStats;
+
+/// Days of MCP calls aggregate recommendations look at
+pub const MCP_WINDOW_DAYS: u32 = 7;
 
 /// Thresholds for recommendation triggers
 mod thresholds {
@@ -26,6 +35,20 @@ mod thresholds {
     pub const HIGH_AVG_COST_PER_TURN: f64 = 0.75;
     /// Subagent cost percentage threshold (subagent_cost / total_cost)
     pub const HIGH_SUBAGENT_COST_RATIO: f64 = 0.4;
+    /// Sessions shorter than this rarely pause long enough for a 1-hour
+    /// cache entry to outlive a 5-minute one, which reads keep refreshing
+    pub const SHORT_SESSION_MS: u64 = 15 * 60 * 1000;
+    /// Share of cache write tokens on the 1-hour TTL that counts as excessive
+    pub const HIGH_1H_CACHE_WRITE_RATIO: f64 = 0.5;
+    /// Minimum 1-hour cache write premium worth recommending against
+    pub const MIN_1H_CACHE_PREMIUM: f64 = 0.05;
+    /// Share of failed calls at which an MCP server is flagged
+    pub const HIGH_MCP_FAILURE_RATE: f64 = 0.25;
+    /// Calls an MCP server needs before its failure rate means anything
+    pub const MIN_MCP_CALLS: u32 = 10;
+    /// A dismissed recommendation comes back once its potential savings grow
+    /// past this multiple of the savings when it was dismissed
+    pub const MATERIAL_SAVINGS_INCREASE: f64 = 1.5;
 }
 
 /// Generate recommendations based on session analysis
@@ -72,6 +95,15 @@ pub fn generate_recommendations(input: &RecommendationInput) -> RecommendationSu
         recommendations.push(rec);
     }
 
+    // Check 1-hour cache writes in short sessions
+    if let Some(rec) = check_cache_ttl(input) {
+        recommendations.push(rec);
+    }
+
+    for rec in &mut recommendations {
+        rec.id = recommendation_id(rec.rec_type, input.session_id.as_deref());
+    }
+
     RecommendationSummary::from_recommendations(
         recommendations,
         input.session_id.clone(),
@@ -407,17 +439,129 @@ fn check_subagent_cost_ratio(input: &RecommendationInput) -> Option<Recommendati
     ))
 }
 
+/// Check for 1-hour cache writes in sessions too short to benefit from them
+///
+/// A 1-hour cache write costs more than a 5-minute one and only pays off when
+/// the cache is read again after more than five idle minutes.
+fn check_cache_ttl(input: &RecommendationInput) -> Option<Recommendation> {
+    if input.duration_ms == 0
+        || input.duration_ms >= thresholds::SHORT_SESSION_MS
+        || input.cache_write_tokens == 0
+    {
+        return None;
+    }
+
+    let ratio = input.cache_write_1h_tokens as f64 / input.cache_write_tokens as f64;
+    if ratio < thresholds::HIGH_1H_CACHE_WRITE_RATIO {
+        return None;
+    }
+
+    // Without a price for the model there's no premium to report
+    let pricing = find_pricing(&input.primary_model)?;
+    let premium = input.cache_write_1h_tokens as f64 / 1_000_000.0
+        * (pricing.cache_write_1h_per_million - pricing.cache_write_5m_per_million);
+    if premium < thresholds::MIN_1H_CACHE_PREMIUM {
+        return None;
+    }
+
+    let minutes = input.duration_ms as f64 / 60_000.0;
+    // Without a 5-minute pause the 1-hour entries can't have been needed
+    let confidence = if input.duration_ms <= 5 * 60 * 1000 { 0.9 } else { 0.7 };
+
+    Some(Recommendation::new(
+        RecommendationType::CacheOptimization,
+        "Use the 5-minute cache for short sessions".to_string(),
+        format!(
+            "{:.0}% of your cache writes use the 1-hour TTL, but sessions last {:.1} minutes. \
+            The 5-minute cache is cheaper to write and stays warm as long as requests keep reading it.",
+            ratio * 100.0,
+            minutes
+        ),
+        premium,
+        false,
+        confidence,
+        vec![
+            "Reserve the 1-hour cache TTL for long sessions with breaks between requests".to_string(),
+            "Check whether a tool or setting requests the extended cache TTL by default".to_string(),
+        ],
+        format!(
+            "1h cache write tokens: {} of {} ({:.0}%), Duration: {:.1} min (short: <{} min)",
+            input.cache_write_1h_tokens,
+            input.cache_write_tokens,
+            ratio * 100.0,
+            minutes,
+            thresholds::SHORT_SESSION_MS / 60_000
+        ),
+    ))
+}
+
 /// Helper to estimate cache write cost
 fn estimate_cache_write_cost(input: &RecommendationInput) -> f64 {
-    let pricing = find_pricing(&input.primary_model)
-        .unwrap_or_else(|| get_default_pricing()[0].clone());
+    find_pricing(&input.primary_model).map_or(0.0, |pricing| {
+        input.cache_write_tokens as f64 / 1_000_000.0 * pricing.cache_write_5m_per_million
+    })
+}
 
-    input.cache_write_tokens as f64 / 1_000_000.0 * pricing.cache_write_5m_per_million
+/// Check for an MCP server failing a large share of its calls
+///
+/// Flags the least reliable server with enough calls; the savings are the
+/// estimated cost of the failed calls' results.
+fn check_mcp_reliability(servers: &[McpServerStats]) -> Option<Recommendation> {
+    let server = servers
+        .iter()
+        .filter(|s| s.calls >= thresholds::MIN_MCP_CALLS && s.failure_rate >= thresholds::HIGH_MCP_FAILURE_RATE)
+        .max_by(|a, b| a.failure_rate.total_cmp(&b.failure_rate).then(a.calls.cmp(&b.calls)))?;
+
+    let mut action_items = vec![
+        format!("Check that the {} server starts and is authenticated (`claude mcp list`)", server.server),
+        "Read the error results of its failed calls for the cause".to_string(),
+        "Disable the server in projects that don't use it".to_string(),
+    ];
+    if let Some(tool) = server
+        .tools
+        .iter()
+        .filter(|t| t.errors > 0)
+        .max_by(|a, b| a.errors.cmp(&b.errors).then(b.tool.cmp(&a.tool)))
+    {
+        action_items.insert(
+            1,
+            format!("Start with {}, which failed {} of {} calls", tool.tool, tool.errors, tool.calls),
+        );
+    }
+
+    Some(Recommendation::new(
+        RecommendationType::ToolReliability,
+        format!("MCP server {} is failing", server.server),
+        format!(
+            "MCP server {} failed {:.0}% of calls this week ({} of {}). Each failed call \
+            still costs a model round trip and often a retry.",
+            server.server,
+            server.failure_rate * 100.0,
+            server.errors,
+            server.calls
+        ),
+        server.failed_cost,
+        false,
+        if server.calls >= thresholds::MIN_MCP_CALLS * 5 { 0.85 } else { 0.7 },
+        action_items,
+        format!(
+            "{}: {} calls in {} sessions, {} failed ({:.0}%, threshold {:.0}%)",
+            server.server,
+            server.calls,
+            server.sessions,
+            server.errors,
+            server.failure_rate * 100.0,
+            thresholds::HIGH_MCP_FAILURE_RATE * 100.0
+        ),
+    ))
 }
 
 /// Generate aggregate recommendations from multiple sessions
+///
+/// `mcp_servers` is MCP usage over the last [`MCP_WINDOW_DAYS`] days.
 pub fn generate_aggregate_recommendations(
     inputs: &[RecommendationInput],
+    mcp_servers: &[McpServerStats],
 ) -> RecommendationSummary {
     if inputs.is_empty() {
         return RecommendationSummary::from_recommendations(Vec::new(), None, 0);
@@ -445,13 +589,15 @@ pub fn generate_aggregate_recommendations(
     let total_output_tokens: u64 = inputs.iter().map(|i| i.output_tokens).sum();
     let total_cache_read_tokens: u64 = inputs.iter().map(|i| i.cache_read_tokens).sum();
     let total_cache_write_tokens: u64 = inputs.iter().map(|i| i.cache_write_tokens).sum();
+    let total_cache_write_1h_tokens: u64 = inputs.iter().map(|i| i.cache_write_1h_tokens).sum();
+    let avg_duration_ms = inputs.iter().map(|i| i.duration_ms).sum::<u64>() / inputs.len() as u64;
 
     // Find most common model
     let primary_model = inputs
         .iter()
         .map(|i| i.primary_model.clone())
         .max_by_key(|m| inputs.iter().filter(|i| i.primary_model == *m).count())
-        .unwrap_or_else(|| "claude-opus-4-5-20251101".to_string());
+        .unwrap_or_default();
 
     // Create aggregate input
     let aggregate_input = RecommendationInput {
@@ -470,6 +616,8 @@ pub fn generate_aggregate_recommendations(
         output_tokens: total_output_tokens,
         cache_read_tokens: total_cache_read_tokens,
         cache_write_tokens: total_cache_write_tokens,
+        cache_write_1h_tokens: total_cache_write_1h_tokens,
+        duration_ms: avg_duration_ms,
         project_path: None,
         branch: None,
         avg_cost_per_turn: if total_turns > 0 {
@@ -479,10 +627,78 @@ pub fn generate_aggregate_recommendations(
         },
     };
 
-    let mut summary = generate_recommendations(&aggregate_input);
-    summary.sessions_analyzed = inputs.len() as u32;
+    let mut recommendations = generate_recommendations(&aggregate_input).recommendations;
+    if let Some(mut rec) = check_mcp_reliability(mcp_servers) {
+        rec.id = recommendation_id(rec.rec_type, None);
+        recommendations.push(rec);
+    }
+
+    RecommendationSummary::from_recommendations(recommendations, None, inputs.len() as u32)
+}
+
+/// Drop the recommendations the user dismissed or snoozed
+///
+/// A session's recommendation is hidden by feedback on the same type from
+/// that session or another session of `project_path`; an aggregate one only
+/// by feedback on aggregate recommendations. It shows again when the snooze
+/// runs out, or when its potential savings or confidence level have grown
+/// materially since it was dismissed.
+pub fn apply_feedback(
+    summary: RecommendationSummary,
+    feedback: &[RecommendationFeedback],
+    project_path: Option<&str>,
+    now: DateTime<Utc>,
+) -> RecommendationSummary {
+    if feedback.is_empty() {
+        return summary;
+    }
+
+    let session_id = summary.session_id.clone();
+    let sessions_analyzed = summary.sessions_analyzed;
+    let recommendations = summary
+        .recommendations
+        .into_iter()
+        .filter(|rec| {
+            !feedback
+                .iter()
+                .any(|fb| suppresses(fb, rec, session_id.as_deref(), project_path, now))
+        })
+        .collect();
+
+    RecommendationSummary::from_recommendations(recommendations, session_id, sessions_analyzed)
+}
+
+fn suppresses(
+    feedback: &RecommendationFeedback,
+    rec: &Recommendation,
+    session_id: Option<&str>,
+    project_path: Option<&str>,
+    now: DateTime<Utc>,
+) -> bool {
+    if feedback.rec_type != rec.rec_type {
+        return false;
+    }
 
-    summary
+    let in_scope = match session_id {
+        Some(id) => {
+            feedback.session_id.as_deref() == Some(id)
+                || (project_path.is_some() && feedback.project_path.as_deref() == project_path)
+        }
+        None => feedback.session_id.is_none(),
+    };
+    if !in_scope {
+        return false;
+    }
+
+    let snooze_over = feedback
+        .snoozed_until
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .is_some_and(|until| until <= now);
+    let savings_grew = rec.potential_savings > feedback.potential_savings * thresholds::MATERIAL_SAVINGS_INCREASE;
+    let confidence_grew = confidence_rank(rec.confidence) > confidence_rank(feedback.confidence);
+
+    !(snooze_over || savings_grew || confidence_grew)
 }
 
 #[cfg(test)]
@@ -506,6 +722,8 @@ mod tests {
             output_tokens: 100_000,
             cache_read_tokens: 400_000,
             cache_write_tokens: 100_000,
+            cache_write_1h_tokens: 0,
+            duration_ms: 1_800_000,
             project_path: Some("/path/to/project".to_string()),
             branch: Some("main".to_string()),
             avg_cost_per_turn: 0.5,
@@ -652,6 +870,38 @@ mod tests {
         assert!(ratio_rec.is_some());
     }
 
+    #[test]
+    fn test_short_session_1h_cache_writes() {
+        let mut input = create_test_input();
+        input.cache_write_tokens = 1_000_000;
+        input.cache_write_1h_tokens = 800_000;
+        input.duration_ms = 4 * 60 * 1000;
+
+        let ttl_rec = |input: &RecommendationInput| {
+            generate_recommendations(input)
+                .recommendations
+                .into_iter()
+                .find(|r| r.title.contains("5-minute cache"))
+        };
+
+        let rec = ttl_rec(&input).unwrap();
+        assert_eq!(rec.rec_type, RecommendationType::CacheOptimization);
+        assert!((rec.confidence - 0.9).abs() < 1e-9);
+        // Opus 4.5: $10/M for 1h writes against $6.25/M for 5m writes
+        let pricing = find_pricing(&input.primary_model).unwrap();
+        let expected = 0.8 * (pricing.cache_write_1h_per_million - pricing.cache_write_5m_per_million);
+        assert!((rec.potential_savings - expected).abs() < 1e-9);
+
+        // Long sessions can make use of the longer TTL
+        input.duration_ms = 2 * 60 * 60 * 1000;
+        assert!(ttl_rec(&input).is_none());
+
+        // Mostly 5-minute writes are fine
+        input.duration_ms = 4 * 60 * 1000;
+        input.cache_write_1h_tokens = 200_000;
+        assert!(ttl_rec(&input).is_none());
+    }
+
     #[test]
     fn test_aggregate_recommendations() {
         let input1 = create_test_input();
@@ -659,15 +909,54 @@ mod tests {
         input2.session_id = Some("test-session-2".to_string());
         input2.cer = 0.2; // Different metrics
 
-        let summary = generate_aggregate_recommendations(&[input1, input2]);
+        let summary = generate_aggregate_recommendations(&[input1, input2], &[]);
 
         assert_eq!(summary.sessions_analyzed, 2);
         assert!(summary.session_id.is_none()); // Aggregate has no single session ID
     }
 
+    #[test]
+    fn test_mcp_reliability_recommendation() {
+        use crate::metrics::mcp::McpToolStats;
+
+        let server = |name: &str, calls: u32, errors: u32| McpServerStats {
+            server: name.to_string(),
+            calls,
+            errors,
+            failure_rate: errors as f64 / calls as f64,
+            sessions: 3,
+            result_tokens: 50_000,
+            estimated_cost: 0.15,
+            failed_cost: 0.06,
+            tools: vec![McpToolStats {
+                tool: "search".to_string(),
+                calls,
+                errors,
+                failure_rate: errors as f64 / calls as f64,
+                result_tokens: 50_000,
+                estimated_cost: 0.15,
+            }],
+        };
+        let servers = [server("github", 40, 2), server("linear", 20, 8), server("flaky", 4, 4)];
+
+        let summary = generate_aggregate_recommendations(&[create_test_input()], &servers);
+        let rec = summary
+            .recommendations
+            .iter()
+            .find(|r| r.rec_type == RecommendationType::ToolReliability)
+            .expect("linear fails 40% of its calls");
+        assert_eq!(rec.id, "tool_reliability:all");
+        assert!(rec.description.starts_with("MCP server linear failed 40% of calls this week"));
+        assert!((rec.potential_savings - 0.06).abs() < 1e-9);
+
+        // Too few calls to judge, or reliable enough
+        let summary = generate_aggregate_recommendations(&[create_test_input()], &servers[..1]);
+        assert!(summary.recommendations.iter().all(|r| r.rec_type != RecommendationType::ToolReliability));
+    }
+
     #[test]
     fn test_empty_aggregate() {
-        let summary = generate_aggregate_recommendations(&[]);
+        let summary = generate_aggregate_recommendations(&[], &[]);
         assert_eq!(summary.sessions_analyzed, 0);
         assert!(summary.recommendations.is_empty());
     }
@@ -690,6 +979,8 @@ mod tests {
             output_tokens: 50_000,
             cache_read_tokens: 80_000,
             cache_write_tokens: 20_000,
+            cache_write_1h_tokens: 0,
+            duration_ms: 600_000,
             project_path: None,
             branch: None,
             avg_cost_per_turn: 0.05,
@@ -719,4 +1010,67 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_apply_feedback() {
+        let mut input = create_test_input();
+        input.cer = 0.25; // Cache optimization, high confidence
+        let summary = generate_recommendations(&input);
+        let rec = summary
+            .recommendations
+            .iter()
+            .find(|r| r.rec_type == RecommendationType::CacheOptimization)
+            .unwrap()
+            .clone();
+        assert_eq!(rec.id, "cache_optimization:test-session");
+
+        let now = Utc::now();
+        let dismissed = RecommendationFeedback {
+            id: rec.id.clone(),
+            rec_type: rec.rec_type,
+            session_id: Some("other-session".to_string()),
+            project_path: Some("/path/to/project".to_string()),
+            reason: Some("Known".to_string()),
+            potential_savings: rec.potential_savings,
+            confidence: rec.confidence,
+            dismissed_at: now.to_rfc3339(),
+            snoozed_until: None,
+        };
+        let is_shown = |fb: &RecommendationFeedback, project: Option<&str>| {
+            apply_feedback(summary.clone(), std::slice::from_ref(fb), project, now)
+                .recommendations
+                .iter()
+                .any(|r| r.id == rec.id)
+        };
+
+        // Dismissed in another session of the same project
+        assert!(!is_shown(&dismissed, Some("/path/to/project")));
+        assert!(is_shown(&dismissed, Some("/path/to/other")));
+        assert_eq!(
+            apply_feedback(summary.clone(), std::slice::from_ref(&dismissed), Some("/path/to/project"), now)
+                .recommendations
+                .len(),
+            summary.recommendations.len() - 1
+        );
+
+        // Savings have since grown well past what was dismissed
+        let smaller = RecommendationFeedback { potential_savings: rec.potential_savings / 2.0, ..dismissed.clone() };
+        assert!(is_shown(&smaller, Some("/path/to/project")));
+
+        // Snoozes run out
+        let snoozed = RecommendationFeedback {
+            snoozed_until: Some((now + chrono::Duration::days(1)).to_rfc3339()),
+            ..dismissed.clone()
+        };
+        assert!(!is_shown(&snoozed, Some("/path/to/project")));
+        let expired = RecommendationFeedback {
+            snoozed_until: Some((now - chrono::Duration::days(1)).to_rfc3339()),
+            ..dismissed.clone()
+        };
+        assert!(is_shown(&expired, Some("/path/to/project")));
+
+        // Aggregate dismissals don't hide session recommendations
+        let aggregate = RecommendationFeedback { session_id: None, project_path: None, ..dismissed };
+        assert!(is_shown(&aggregate, Some("/path/to/project")));
+    }
 }
@@ -7,5 +7,5 @@ pub mod engine;
 pub mod types;
 
 // Re-export commonly used types
-pub use engine::generate_recommendations;
-pub use types::{Recommendation, RecommendationSummary, RecommendationType};
+pub use engine::{apply_feedback, generate_recommendations};
+pub use types::{Recommendation, RecommendationFeedback, RecommendationSummary, RecommendationType};
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// Type of recommendation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum RecommendationType {
     /// Recommendation to reduce costs
@@ -20,6 +21,8 @@ pub enum RecommendationType {
     SubagentStrategy,
     /// Recommendation about model selection
     ModelSelection,
+    /// Recommendation about unreliable tools, such as a failing MCP server
+    ToolReliability,
 }
 
 impl RecommendationType {
@@ -32,6 +35,7 @@ impl RecommendationType {
             Self::CacheOptimization => "Cache Optimization",
             Self::SubagentStrategy => "Subagent Strategy",
             Self::ModelSelection => "Model Selection",
+            Self::ToolReliability => "Tool Reliability",
         }
     }
 
@@ -44,9 +48,38 @@ impl RecommendationType {
             Self::CacheOptimization => "database",
             Self::SubagentStrategy => "users",
             Self::ModelSelection => "cpu",
+            Self::ToolReliability => "plug",
         }
     }
 
+    /// Identifier used in recommendation ids (the serialized name)
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::CostSaving => "cost_saving",
+            Self::EfficiencyImprovement => "efficiency_improvement",
+            Self::WorkflowOptimization => "workflow_optimization",
+            Self::CacheOptimization => "cache_optimization",
+            Self::SubagentStrategy => "subagent_strategy",
+            Self::ModelSelection => "model_selection",
+            Self::ToolReliability => "tool_reliability",
+        }
+    }
+
+    /// Parse a type from its [`key`](Self::key)
+    pub fn from_key(key: &str) -> Option<Self> {
+        [
+            Self::CostSaving,
+            Self::EfficiencyImprovement,
+            Self::WorkflowOptimization,
+            Self::CacheOptimization,
+            Self::SubagentStrategy,
+            Self::ModelSelection,
+            Self::ToolReliability,
+        ]
+        .into_iter()
+        .find(|t| t.key() == key)
+    }
+
     /// Get priority weight (higher = more important)
     pub fn priority_weight(&self) -> f64 {
         match self {
@@ -54,15 +87,40 @@ impl RecommendationType {
             Self::ModelSelection => 0.95,
             Self::CacheOptimization => 0.85,
             Self::EfficiencyImprovement => 0.8,
+            Self::ToolReliability => 0.75,
             Self::SubagentStrategy => 0.7,
             Self::WorkflowOptimization => 0.6,
         }
     }
 }
 
+/// Scope of recommendations generated across all sessions
+pub const AGGREGATE_SCOPE: &str = "all";
+
+/// Id of a recommendation: its type and the session it was generated for
+/// (`cost_saving:<session id>`, or `cost_saving:all` for aggregate ones)
+pub fn recommendation_id(rec_type: RecommendationType, session_id: Option<&str>) -> String {
+    format!("{}:{}", rec_type.key(), session_id.unwrap_or(AGGREGATE_SCOPE))
+}
+
+/// Split a recommendation id into its type and session (None if aggregate)
+pub fn parse_recommendation_id(id: &str) -> Option<(RecommendationType, Option<String>)> {
+    let (key, scope) = id.split_once(':')?;
+    let rec_type = RecommendationType::from_key(key)?;
+    match scope {
+        "" => None,
+        AGGREGATE_SCOPE => Some((rec_type, None)),
+        session_id => Some((rec_type, Some(session_id.to_string()))),
+    }
+}
+
 /// A single recommendation
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct Recommendation {
+    /// Stable id, see [`recommendation_id`]
+    #[serde(default)]
+    pub id: String,
     /// Type of recommendation
     pub rec_type: RecommendationType,
     /// Short title for the recommendation
@@ -105,6 +163,7 @@ impl Recommendation {
         let priority_score = rec_type.priority_weight() * confidence * (0.5 + 0.5 * savings_factor);
 
         Self {
+            id: rec_type.key().to_string(),
             rec_type,
             title,
             description,
@@ -138,8 +197,44 @@ impl Recommendation {
     }
 }
 
+/// Confidence level order (Low < Medium < High)
+pub(crate) fn confidence_rank(confidence: f64) -> u8 {
+    if confidence >= 0.8 {
+        2
+    } else if confidence >= 0.5 {
+        1
+    } else {
+        0
+    }
+}
+
+/// A dismissed or snoozed recommendation
+///
+/// Dismissing a session's recommendation hides that type of recommendation
+/// for every session of the same project. The savings and confidence at the
+/// time are kept so the recommendation can come back if things get
+/// noticeably worse.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct RecommendationFeedback {
+    /// Id of the dismissed recommendation
+    pub id: String,
+    pub rec_type: RecommendationType,
+    /// Session the recommendation was dismissed in (None if aggregate)
+    pub session_id: Option<String>,
+    /// Project of that session
+    pub project_path: Option<String>,
+    pub reason: Option<String>,
+    pub potential_savings: f64,
+    pub confidence: f64,
+    pub dismissed_at: String,
+    /// Shown again after this time; None dismisses until things change
+    pub snoozed_until: Option<String>,
+}
+
 /// Summary of all recommendations for a session or aggregate analysis
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct RecommendationSummary {
     /// All recommendations sorted by priority
     pub recommendations: Vec<Recommendation>,
@@ -270,6 +365,11 @@ pub struct RecommendationInput {
     pub cache_read_tokens: u64,
     /// Cache write tokens
     pub cache_write_tokens: u64,
+    /// Cache write tokens with the 1-hour TTL (part of `cache_write_tokens`)
+    pub cache_write_1h_tokens: u64,
+    /// Active session time in ms, without long idle pauses (average per
+    /// session when aggregated)
+    pub duration_ms: u64,
     /// Project path for context
     pub project_path: Option<String>,
     /// Branch information (for comparison insights)
@@ -291,6 +391,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_recommendation_id_roundtrip() {
+        let id = recommendation_id(RecommendationType::CacheOptimization, Some("abc-123"));
+        assert_eq!(id, "cache_optimization:abc-123");
+        assert_eq!(
+            parse_recommendation_id(&id),
+            Some((RecommendationType::CacheOptimization, Some("abc-123".to_string())))
+        );
+
+        let id = recommendation_id(RecommendationType::ModelSelection, None);
+        assert_eq!(parse_recommendation_id(&id), Some((RecommendationType::ModelSelection, None)));
+
+        assert_eq!(parse_recommendation_id("cache_optimization"), None);
+        assert_eq!(parse_recommendation_id("unknown:abc"), None);
+        assert_eq!(parse_recommendation_id("cost_saving:"), None);
+    }
+
     #[test]
     fn test_recommendation_priority_computation() {
         let rec = Recommendation::new(
@@ -537,6 +654,7 @@ mod tests {
         assert_eq!(RecommendationType::CacheOptimization.icon(), "database");
         assert_eq!(RecommendationType::SubagentStrategy.icon(), "users");
         assert_eq!(RecommendationType::ModelSelection.icon(), "cpu");
+        assert_eq!(RecommendationType::ToolReliability.icon(), "plug");
     }
 
     #[test]
@@ -549,6 +667,8 @@ mod tests {
         assert!(RecommendationType::CacheOptimization.priority_weight() >=
                 RecommendationType::EfficiencyImprovement.priority_weight());
         assert!(RecommendationType::EfficiencyImprovement.priority_weight() >=
+                RecommendationType::ToolReliability.priority_weight());
+        assert!(RecommendationType::ToolReliability.priority_weight() >=
                 RecommendationType::SubagentStrategy.priority_weight());
         assert!(RecommendationType::SubagentStrategy.priority_weight() >=
                 RecommendationType::WorkflowOptimization.priority_weight());
@@ -735,6 +855,8 @@ mod tests {
             output_tokens: 150_000,
             cache_read_tokens: 500_000,
             cache_write_tokens: 120_000,
+            cache_write_1h_tokens: 0,
+            duration_ms: 1_800_000,
             project_path: Some("/path/to/project".to_string()),
             branch: Some("feature-branch".to_string()),
             avg_cost_per_turn: 0.6,
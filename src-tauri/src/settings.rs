@@ -0,0 +1,728 @@
+//! User settings
+//!
+//! Settings are stored in the `settings` table as one JSON value per key.
+//! The project path filter built from them is kept in a global so both the
+//! Rust-side filtering in commands and the `is_real_user_project()` SQL
+//! function used by the aggregate queries apply the same rules. The settings
+//! themselves are kept alongside so session caching can honor the retention
+//! period and cache limits, aggregates can tell how to count subagent
+//! sessions, notifications know where to go, dates are bucketed in the
+//! configured timezone, and the parser knows whether to drop message content
+//! and which Claude directories to scan.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use chrono::{Duration as ChronoDuration, SecondsFormat, Utc};
+use glob::{MatchOptions, Pattern, PatternError};
+use serde::{Deserialize, Serialize};
+
+use crate::export::template::SummaryTemplate;
+use crate::metrics::deliverables::DeliverableEstimator;
+use crate::metrics::health::HealthWeights;
+use crate::metrics::usage_windows::{default_usage_windows, UsageWindowConfig};
+use crate::parser::project_path::{is_windows_path, normalize_project_path, Platform};
+use crate::timezone::ReportingTimezone;
+use crate::updates::UpdateChannel;
+
+lazy_static::lazy_static! {
+    static ref PROJECT_FILTER: RwLock<ProjectFilter> =
+        RwLock::new(ProjectFilter::new(&AppSettings::default()).expect("default patterns are valid"));
+    static ref ACTIVE_SETTINGS: RwLock<AppSettings> = RwLock::new(AppSettings::default());
+    static ref REPORTING_TIMEZONE: RwLock<ReportingTimezone> = RwLock::new(ReportingTimezone::Local);
+}
+
+/// User-editable application settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct AppSettings {
+    /// Glob patterns a project path must match to be counted; empty means all
+    pub include_project_patterns: Vec<String>,
+    /// Glob patterns for project paths to leave out, applied after includes
+    pub exclude_project_patterns: Vec<String>,
+    /// Delete cached sessions inactive for longer than this many days;
+    /// None (or 0) keeps everything
+    pub retention_days: Option<u32>,
+    /// Roll subagent sessions up into the session that spawned them when
+    /// counting sessions in dashboard and project aggregates
+    pub attribute_subagent_costs: bool,
+    /// In-memory cache limits
+    pub cache: CacheConfig,
+    /// Rescan intervals used when file watching isn't available
+    pub polling: PollingConfig,
+    /// Webhook notifications for analytics events
+    pub notifications: NotificationSettings,
+    /// Weights of the session health score components
+    pub health_weights: HealthWeights,
+    /// Timezone days and hours are reported in: None follows the OS,
+    /// otherwise "UTC" or a fixed offset such as "+02:00"
+    pub timezone: Option<String>,
+    /// Metrics-only ingestion: message text, tool inputs and tool results
+    /// are dropped as sessions are parsed, so only token, cost and tool
+    /// metadata reach the caches, the database and exports. Code change
+    /// and rework analysis need tool inputs and come up empty.
+    pub privacy_mode: bool,
+    /// Store message text, tool inputs and results, session summaries and
+    /// annotation notes encrypted, with a key kept in the OS keychain
+    /// (macOS and Linux only). Project and changed-file paths, token counts
+    /// and costs stay plaintext.
+    pub encrypt_content: bool,
+    /// Claude data directories scanned besides the default one, e.g. a
+    /// second CLAUDE_CONFIG_DIR for work sessions
+    pub claude_roots: Vec<ClaudeRoot>,
+    /// How deliverable units are estimated for CPDU and CPD
+    pub deliverable_estimator: DeliverableEstimator,
+    /// Rolling windows usage is summed over, with optional warning
+    /// thresholds, e.g. to stay within a plan's 5-hour limit
+    pub usage_windows: Vec<UsageWindowConfig>,
+    /// Saved templates for session summaries, alongside the built-in ones
+    pub summary_templates: Vec<SummaryTemplate>,
+    /// Releases offered by the update check
+    pub update_channel: UpdateChannel,
+    /// Clients allowed to connect to the local IPC socket (see [`crate::ipc`])
+    pub ipc_tokens: Vec<IpcToken>,
+}
+
+/// A Claude data directory laid out like ~/.claude
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ClaudeRoot {
+    /// Directory path; a leading `~` is the home directory
+    pub path: String,
+    /// Source label stored with the root's sessions; defaults to the
+    /// directory name
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl ClaudeRoot {
+    pub fn resolved_path(&self) -> PathBuf {
+        PathBuf::from(expand_home(self.path.trim()))
+    }
+
+    pub fn source_label(&self) -> String {
+        self.label
+            .as_deref()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .or_else(|| {
+                self.resolved_path()
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| self.path.clone())
+    }
+}
+
+/// An allowlisted client of the local IPC socket, e.g. an editor extension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct IpcToken {
+    /// Shown in the settings and logged when the client connects
+    pub name: String,
+    pub token: String,
+}
+
+impl IpcToken {
+    /// Shortest token accepted; generated ones are 43 characters
+    pub const MIN_TOKEN_LEN: usize = 16;
+
+    pub fn is_valid(&self) -> bool {
+        !self.name.trim().is_empty() && self.token.len() >= Self::MIN_TOKEN_LEN
+    }
+}
+
+/// Sizes and lifetimes of the in-memory caches
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Memory budget for parsed session turns, in megabytes; the least
+    /// recently used sessions are evicted once it is exceeded
+    pub session_cache_budget_mb: u64,
+    /// How long a scanned session list is reused before rescanning
+    pub session_list_ttl_secs: u64,
+    pub dashboard_ttl_secs: u64,
+    pub daily_metrics_ttl_secs: u64,
+    pub project_metrics_ttl_secs: u64,
+    /// Keep parsing sessions beyond the first preload batch in the background
+    pub aggressive_preload: bool,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            session_cache_budget_mb: 512,
+            session_list_ttl_secs: 30,
+            dashboard_ttl_secs: 30,
+            daily_metrics_ttl_secs: 60,
+            project_metrics_ttl_secs: 60,
+            aggressive_preload: true,
+        }
+    }
+}
+
+/// How often session files are rescanned when file watching isn't available
+///
+/// Scans run at the active interval while any session file changed within
+/// the active window, and back off to the idle interval otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct PollingConfig {
+    pub active_interval_secs: u64,
+    pub idle_interval_secs: u64,
+    /// How long after the last change a session counts as active
+    pub active_window_secs: u64,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        Self {
+            active_interval_secs: 5,
+            idle_interval_secs: 60,
+            active_window_secs: 120,
+        }
+    }
+}
+
+impl PollingConfig {
+    /// Intervals must be at least a second, and the active one no longer
+    /// than the idle one
+    pub fn is_valid(&self) -> bool {
+        self.active_interval_secs >= 1 && self.active_interval_secs <= self.idle_interval_secs
+    }
+
+    /// Time until the next scan, given how long ago a session file last
+    /// changed (None if none has since polling started)
+    pub fn interval(&self, since_last_change: Option<Duration>) -> Duration {
+        let active = since_last_change.is_some_and(|age| age.as_secs() < self.active_window_secs);
+        let secs = if active {
+            self.active_interval_secs
+        } else {
+            self.idle_interval_secs
+        };
+        Duration::from_secs(secs.max(1))
+    }
+}
+
+/// Where analytics events are sent and which ones
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// URLs every enabled event is POSTed to, e.g. Slack incoming webhooks
+    pub webhook_urls: Vec<String>,
+    pub new_session: bool,
+    pub budget_exceeded: bool,
+    pub anomaly_detected: bool,
+    pub antipattern_found: bool,
+    /// Post a summary of the previous day once a day
+    pub daily_digest: bool,
+    /// Daily spend in USD above which `budget_exceeded` fires
+    pub daily_budget: Option<f64>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            webhook_urls: Vec::new(),
+            new_session: false,
+            budget_exceeded: true,
+            anomaly_detected: true,
+            antipattern_found: true,
+            daily_digest: false,
+            daily_budget: None,
+        }
+    }
+}
+
+impl NotificationSettings {
+    /// First webhook URL that isn't an http(s) URL, if any
+    pub fn invalid_webhook_url(&self) -> Option<&str> {
+        self.webhook_urls
+            .iter()
+            .map(|url| url.trim())
+            .find(|url| !(url.starts_with("https://") || url.starts_with("http://")))
+    }
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            include_project_patterns: Vec::new(),
+            exclude_project_patterns: Platform::current()
+                .temp_path_patterns()
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            retention_days: None,
+            attribute_subagent_costs: false,
+            cache: CacheConfig::default(),
+            polling: PollingConfig::default(),
+            notifications: NotificationSettings::default(),
+            health_weights: HealthWeights::default(),
+            timezone: None,
+            privacy_mode: false,
+            encrypt_content: false,
+            claude_roots: Vec::new(),
+            deliverable_estimator: DeliverableEstimator::default(),
+            usage_windows: default_usage_windows(),
+            summary_templates: Vec::new(),
+            update_channel: UpdateChannel::default(),
+            ipc_tokens: Vec::new(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Build settings from stored key/value rows, falling back to defaults for
+    /// missing or unreadable keys
+    pub fn from_entries(entries: &HashMap<String, String>) -> Self {
+        let mut value = serde_json::to_value(Self::default()).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            for (key, raw) in entries {
+                if !fields.contains_key(key) {
+                    continue;
+                }
+                match serde_json::from_str(raw) {
+                    Ok(parsed) => {
+                        fields.insert(key.clone(), parsed);
+                    }
+                    Err(e) => tracing::warn!("Ignoring unreadable setting {}: {}", key, e),
+                }
+            }
+        }
+
+        serde_json::from_value(value).unwrap_or_else(|e| {
+            tracing::warn!("Invalid stored settings, using defaults: {}", e);
+            Self::default()
+        })
+    }
+
+    /// Reset the project patterns to their defaults if they don't compile,
+    /// keeping every other setting; returns the pattern error if they didn't
+    pub fn reset_invalid_patterns(&mut self) -> Option<PatternError> {
+        let error = ProjectFilter::new(self).err()?;
+        let defaults = Self::default();
+        self.include_project_patterns = defaults.include_project_patterns;
+        self.exclude_project_patterns = defaults.exclude_project_patterns;
+        Some(error)
+    }
+
+    /// Cutoff (RFC 3339) before which sessions fall outside the retention
+    /// period, if one is set
+    pub fn retention_cutoff(&self) -> Option<String> {
+        let days = self.retention_days.filter(|d| *d > 0)?;
+        let cutoff = Utc::now() - ChronoDuration::days(days as i64);
+        Some(cutoff.to_rfc3339_opts(SecondsFormat::Micros, true))
+    }
+
+    /// The configured reporting timezone, or None if the setting can't be
+    /// parsed
+    pub fn reporting_timezone(&self) -> Option<ReportingTimezone> {
+        ReportingTimezone::parse(self.timezone.as_deref().unwrap_or_default())
+    }
+
+    /// Split settings into key/value rows for storage
+    pub fn to_entries(&self) -> Vec<(String, String)> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(fields)) => fields
+                .into_iter()
+                .map(|(key, value)| (key, value.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Compiled include/exclude patterns for project paths
+#[derive(Debug)]
+pub struct ProjectFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl ProjectFilter {
+    /// Compile the project patterns from settings
+    pub fn new(settings: &AppSettings) -> Result<Self, PatternError> {
+        Ok(Self {
+            include: compile_patterns(&settings.include_project_patterns)?,
+            exclude: compile_patterns(&settings.exclude_project_patterns)?,
+        })
+    }
+
+    /// Check whether a project path passes the filter
+    ///
+    /// Windows paths match case-insensitively, whatever OS this runs on.
+    pub fn matches(&self, project_path: &str) -> bool {
+        let path = normalize_project_path(project_path);
+        let options = MatchOptions {
+            case_sensitive: !is_windows_path(&path),
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        let included = self.include.is_empty()
+            || self.include.iter().any(|p| p.matches_with(&path, options));
+        included && !self.exclude.iter().any(|p| p.matches_with(&path, options))
+    }
+}
+
+/// Make `settings` the active configuration
+///
+/// Fails without changing anything if a pattern doesn't compile.
+pub fn apply_settings(settings: &AppSettings) -> Result<(), PatternError> {
+    let filter = ProjectFilter::new(settings)?;
+    if let Ok(mut active) = PROJECT_FILTER.write() {
+        *active = filter;
+    }
+    if let Ok(mut active) = ACTIVE_SETTINGS.write() {
+        *active = settings.clone();
+    }
+    if let Ok(mut active) = REPORTING_TIMEZONE.write() {
+        *active = settings.reporting_timezone().unwrap_or_else(|| {
+            tracing::warn!("Unknown timezone setting {:?}, using the OS timezone", settings.timezone);
+            ReportingTimezone::Local
+        });
+    }
+    Ok(())
+}
+
+/// The settings last made active with `apply_settings`
+pub fn active_settings() -> AppSettings {
+    ACTIVE_SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// The active cache limits
+pub fn cache_config() -> CacheConfig {
+    ACTIVE_SETTINGS
+        .read()
+        .map(|settings| settings.cache)
+        .unwrap_or_default()
+}
+
+/// The active fallback polling intervals
+pub fn polling_config() -> PollingConfig {
+    ACTIVE_SETTINGS
+        .read()
+        .map(|settings| settings.polling)
+        .unwrap_or_default()
+}
+
+/// Whether message content must be dropped at parse time
+pub fn privacy_mode() -> bool {
+    ACTIVE_SETTINGS
+        .read()
+        .map(|settings| settings.privacy_mode)
+        .unwrap_or_default()
+}
+
+/// Whether message content is stored encrypted
+pub fn encrypt_content() -> bool {
+    ACTIVE_SETTINGS
+        .read()
+        .map(|settings| settings.encrypt_content)
+        .unwrap_or_default()
+}
+
+/// Clients currently allowed to connect to the IPC socket
+pub fn ipc_tokens() -> Vec<IpcToken> {
+    ACTIVE_SETTINGS
+        .read()
+        .map(|settings| settings.ipc_tokens.clone())
+        .unwrap_or_default()
+}
+
+/// The configured deliverable unit estimator
+pub fn deliverable_estimator() -> DeliverableEstimator {
+    ACTIVE_SETTINGS
+        .read()
+        .map(|settings| settings.deliverable_estimator)
+        .unwrap_or_default()
+}
+
+/// The configured extra Claude data directories
+pub fn claude_roots() -> Vec<ClaudeRoot> {
+    ACTIVE_SETTINGS
+        .read()
+        .map(|settings| settings.claude_roots.clone())
+        .unwrap_or_default()
+}
+
+/// The timezone dates and hours are reported in
+pub fn reporting_timezone() -> ReportingTimezone {
+    REPORTING_TIMEZONE
+        .read()
+        .map(|tz| *tz)
+        .unwrap_or_default()
+}
+
+/// Check whether a timestamp (RFC 3339, UTC) is older than the active
+/// retention period
+pub fn is_past_retention(timestamp: &str) -> bool {
+    active_settings()
+        .retention_cutoff()
+        .is_some_and(|cutoff| timestamp < cutoff.as_str())
+}
+
+/// Check if a project path represents a real user project.
+///
+/// Claude Code stores session files under `~/.claude/projects/` in directories
+/// whose names are encoded filesystem paths (e.g., `-Users-nadavbarkai-dev-ironhide`
+/// decodes to `/Users/nadavbarkai/dev/ironhide`, `C--Users-me-app` to `C:/Users/me/app`).
+///
+/// Some sessions live under temporary/artifact paths like `/private/tmp/madrox-logs-...`
+/// which are not real user projects. Those are filtered out by the configured
+/// exclude patterns, which default to the temp locations of the running OS;
+/// include patterns narrow the set further.
+pub fn is_real_user_project(project_path: &str) -> bool {
+    if project_path.is_empty() {
+        return false;
+    }
+    PROJECT_FILTER
+        .read()
+        .map(|filter| filter.matches(project_path))
+        .unwrap_or(true)
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Pattern>, PatternError> {
+    patterns
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(|p| Pattern::new(&normalize_path(&expand_home(p))))
+        .collect()
+}
+
+/// Expand a leading `~` to the home directory
+pub(crate) fn expand_home(pattern: &str) -> String {
+    match (pattern.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{}{}", home.to_string_lossy(), rest)
+        }
+        _ => pattern.to_string(),
+    }
+}
+
+/// Use forward slashes so patterns work the same for Windows paths
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> ProjectFilter {
+        ProjectFilter::new(&AppSettings {
+            include_project_patterns: include.iter().map(|p| p.to_string()).collect(),
+            exclude_project_patterns: exclude.iter().map(|p| p.to_string()).collect(),
+            retention_days: None,
+            attribute_subagent_costs: false,
+            cache: CacheConfig::default(),
+            polling: PollingConfig::default(),
+            notifications: NotificationSettings::default(),
+            health_weights: HealthWeights::default(),
+            timezone: None,
+            privacy_mode: false,
+            encrypt_content: false,
+            claude_roots: Vec::new(),
+            deliverable_estimator: DeliverableEstimator::default(),
+            usage_windows: default_usage_windows(),
+            summary_templates: Vec::new(),
+            update_channel: UpdateChannel::Stable,
+            ipc_tokens: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_default_filter_excludes_temp_paths() {
+        let f = ProjectFilter::new(&AppSettings::default()).unwrap();
+        assert!(f.matches("/Users/me/dev/ironhide"));
+        assert!(f.matches("/home/me/dev/ironhide"));
+        assert!(f.matches("C:\\Users\\me\\dev\\ironhide"));
+        if cfg!(windows) {
+            assert!(!f.matches("C:\\Users\\me\\AppData\\Local\\Temp\\run"));
+        } else {
+            assert!(!f.matches("/tmp/build/app"));
+        }
+    }
+
+    fn platform_filter(platform: Platform) -> ProjectFilter {
+        let exclude: Vec<&str> = platform.temp_path_patterns().to_vec();
+        filter(&[], &exclude)
+    }
+
+    #[test]
+    fn test_macos_temp_paths() {
+        let f = platform_filter(Platform::MacOs);
+        assert!(f.matches("/Users/me/dev/ironhide"));
+        assert!(!f.matches("/private/tmp/madrox-logs-123"));
+        assert!(!f.matches("/var/folders/x1/abc/T/run"));
+        assert!(!f.matches("/tmp/build/app"));
+    }
+
+    #[test]
+    fn test_linux_temp_paths() {
+        let f = platform_filter(Platform::Linux);
+        assert!(f.matches("/home/me/dev/ironhide"));
+        assert!(f.matches("/var/www/site"));
+        assert!(!f.matches("/tmp/build/app"));
+        assert!(!f.matches("/var/tmp/run"));
+        assert!(!f.matches("/dev/shm/run"));
+    }
+
+    #[test]
+    fn test_windows_temp_paths() {
+        let f = platform_filter(Platform::Windows);
+        assert!(f.matches("C:\\Users\\me\\dev\\ironhide"));
+        assert!(f.matches("D:/work/api"));
+        // Either separator and any case
+        assert!(!f.matches("C:\\Users\\me\\AppData\\Local\\Temp\\run"));
+        assert!(!f.matches("c:/users/me/appdata/local/temp/run"));
+        assert!(!f.matches("C:\\Windows\\Temp\\build"));
+        assert!(!f.matches("E:\\Temp\\x"));
+    }
+
+    #[test]
+    fn test_include_and_exclude_patterns() {
+        let f = filter(&["/home/me/work/**"], &["**/scratch"]);
+        assert!(f.matches("/home/me/work/api"));
+        assert!(!f.matches("/home/me/work/scratch"));
+        assert!(!f.matches("/home/me/personal/blog"));
+
+        // `*` doesn't cross directories
+        let f = filter(&["/home/*/app"], &[]);
+        assert!(f.matches("/home/me/app"));
+        assert!(!f.matches("/home/me/src/app"));
+
+        assert!(ProjectFilter::new(&AppSettings {
+            include_project_patterns: vec!["[".to_string()],
+            exclude_project_patterns: Vec::new(),
+            retention_days: None,
+            attribute_subagent_costs: false,
+            cache: CacheConfig::default(),
+            polling: PollingConfig::default(),
+            notifications: NotificationSettings::default(),
+            health_weights: HealthWeights::default(),
+            timezone: None,
+            privacy_mode: false,
+            encrypt_content: false,
+            claude_roots: Vec::new(),
+            deliverable_estimator: DeliverableEstimator::default(),
+            usage_windows: default_usage_windows(),
+            summary_templates: Vec::new(),
+            update_channel: UpdateChannel::Stable,
+            ipc_tokens: Vec::new(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_settings_entries_round_trip() {
+        let settings = AppSettings {
+            include_project_patterns: vec!["~/dev/**".to_string()],
+            exclude_project_patterns: Vec::new(),
+            retention_days: Some(90),
+            attribute_subagent_costs: true,
+            cache: CacheConfig {
+                session_cache_budget_mb: 64,
+                aggressive_preload: false,
+                ..CacheConfig::default()
+            },
+            polling: PollingConfig {
+                idle_interval_secs: 300,
+                ..PollingConfig::default()
+            },
+            notifications: NotificationSettings {
+                webhook_urls: vec!["https://hooks.slack.com/services/T/B/X".to_string()],
+                daily_budget: Some(25.0),
+                ..NotificationSettings::default()
+            },
+            health_weights: HealthWeights {
+                rework: 0.5,
+                ..HealthWeights::default()
+            },
+            timezone: Some("+05:30".to_string()),
+            privacy_mode: true,
+            encrypt_content: true,
+            claude_roots: vec![ClaudeRoot {
+                path: "~/work/.claude".to_string(),
+                label: Some("work".to_string()),
+            }],
+            deliverable_estimator: DeliverableEstimator::OutputTokens,
+            usage_windows: vec![UsageWindowConfig {
+                hours: 5,
+                token_threshold: Some(2_000_000),
+                cost_threshold: Some(40.0),
+            }],
+            summary_templates: vec![SummaryTemplate {
+                name: "short".to_string(),
+                template: "{project}: ${cost}".to_string(),
+            }],
+            update_channel: UpdateChannel::Beta,
+            ipc_tokens: vec![IpcToken {
+                name: "vscode".to_string(),
+                token: "kX9fQ2mZ7rT4wB1nL8cV5yH3".to_string(),
+            }],
+        };
+        let entries: HashMap<String, String> = settings.to_entries().into_iter().collect();
+        assert_eq!(AppSettings::from_entries(&entries), settings);
+
+        // Missing keys fall back to defaults, unknown or broken ones are ignored
+        let mut entries = HashMap::new();
+        entries.insert("include_project_patterns".to_string(), "not json".to_string());
+        entries.insert("unknown".to_string(), "1".to_string());
+        assert_eq!(AppSettings::from_entries(&entries), AppSettings::default());
+
+        // Cache limits missing from a stored config keep their defaults
+        let mut entries = HashMap::new();
+        entries.insert("cache".to_string(), r#"{"dashboard_ttl_secs":5}"#.to_string());
+        let cache = AppSettings::from_entries(&entries).cache;
+        assert_eq!(cache.dashboard_ttl_secs, 5);
+        assert_eq!(cache.session_cache_budget_mb, 512);
+    }
+
+    #[test]
+    fn test_reset_invalid_patterns() {
+        let mut settings = AppSettings {
+            include_project_patterns: vec!["~/dev/[".to_string()],
+            encrypt_content: true,
+            privacy_mode: true,
+            retention_days: Some(30),
+            ..AppSettings::default()
+        };
+        assert!(settings.reset_invalid_patterns().is_some());
+        assert_eq!(settings.include_project_patterns, AppSettings::default().include_project_patterns);
+        // Only the patterns are reset; encryption in particular stays on
+        assert!(settings.encrypt_content);
+        assert!(settings.privacy_mode);
+        assert_eq!(settings.retention_days, Some(30));
+
+        assert!(settings.reset_invalid_patterns().is_none());
+    }
+
+    #[test]
+    fn test_retention_cutoff() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.retention_cutoff(), None);
+
+        settings.retention_days = Some(0);
+        assert_eq!(settings.retention_cutoff(), None);
+
+        settings.retention_days = Some(30);
+        let cutoff = settings.retention_cutoff().unwrap();
+        assert!(cutoff.ends_with('Z'));
+        assert!("2020-01-01T00:00:00.000000Z" < cutoff.as_str());
+        assert!(Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true) > cutoff);
+    }
+}
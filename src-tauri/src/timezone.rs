@@ -0,0 +1,149 @@
+//! Reporting timezone
+//!
+//! Session logs carry UTC timestamps. Anything that buckets them by calendar
+//! day or hour of day (daily/weekly/monthly trends, the hourly heatmap,
+//! date-range filters) converts them to the reporting timezone first, so a
+//! session at 1am local time lands on the day it was actually worked on.
+//!
+//! The reporting timezone is the OS timezone unless the `timezone` setting
+//! pins a fixed UTC offset.
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, Offset, Utc};
+
+/// Timezone dates and hours are reported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportingTimezone {
+    /// The OS timezone, including its daylight saving rules
+    #[default]
+    Local,
+    /// A fixed offset from UTC
+    Fixed(FixedOffset),
+}
+
+impl ReportingTimezone {
+    /// Parse a `timezone` setting
+    ///
+    /// Empty, "local" and "auto" follow the OS timezone; "UTC" (or "Z") and
+    /// offsets such as "+05:30", "-0800" or "+02" pin a fixed offset.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value.to_ascii_lowercase().as_str() {
+            "" | "local" | "auto" => return Some(Self::Local),
+            "utc" | "z" | "gmt" => return Some(Self::Fixed(Utc.fix())),
+            _ => {}
+        }
+
+        let (sign, rest) = match (value.strip_prefix('+'), value.strip_prefix('-')) {
+            (Some(rest), _) => (1, rest),
+            (_, Some(rest)) => (-1, rest),
+            _ => return None,
+        };
+        let digits: String = rest.chars().filter(|c| *c != ':').collect();
+        if !digits.chars().all(|c| c.is_ascii_digit()) || !matches!(digits.len(), 2 | 4) {
+            return None;
+        }
+        let hours: i32 = digits[..2].parse().ok()?;
+        let minutes: i32 = digits.get(2..).filter(|m| !m.is_empty()).map_or(Ok(0), str::parse).ok()?;
+        if minutes >= 60 {
+            return None;
+        }
+
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).map(Self::Fixed)
+    }
+
+    /// Wall-clock time of a UTC instant in this timezone
+    pub fn to_local(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Local => at.with_timezone(&Local).naive_local(),
+            Self::Fixed(offset) => at.with_timezone(offset).naive_local(),
+        }
+    }
+
+    /// Current calendar date in this timezone
+    pub fn today(&self) -> NaiveDate {
+        self.to_local(Utc::now()).date()
+    }
+
+    /// Offset from UTC in effect right now, e.g. "+02:00"
+    pub fn current_offset(&self) -> String {
+        let offset = match self {
+            Self::Local => Local::now().offset().fix(),
+            Self::Fixed(offset) => *offset,
+        };
+        offset.to_string()
+    }
+
+    /// Wall-clock time of an RFC 3339 timestamp in this timezone
+    pub fn local_datetime(&self, timestamp: &str) -> Option<NaiveDateTime> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .ok()
+            .map(|dt| self.to_local(dt.with_timezone(&Utc)))
+    }
+
+    /// Calendar date (YYYY-MM-DD) of a timestamp in this timezone
+    ///
+    /// Bare YYYY-MM-DD dates are taken to be local dates already.
+    pub fn local_date(&self, timestamp: &str) -> Option<String> {
+        if let Some(local) = self.local_datetime(timestamp) {
+            return Some(local.format("%Y-%m-%d").to_string());
+        }
+        let date = timestamp.get(..10)?;
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .map(|_| date.to_string())
+    }
+}
+
+/// Calendar date of a timestamp in the active reporting timezone
+pub fn local_date(timestamp: &str) -> Option<String> {
+    crate::settings::reporting_timezone().local_date(timestamp)
+}
+
+/// Wall-clock time of a timestamp in the active reporting timezone
+pub fn local_datetime(timestamp: &str) -> Option<NaiveDateTime> {
+    crate::settings::reporting_timezone().local_datetime(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(ReportingTimezone::parse(""), Some(ReportingTimezone::Local));
+        assert_eq!(ReportingTimezone::parse("Local"), Some(ReportingTimezone::Local));
+        let utc = FixedOffset::east_opt(0).unwrap();
+        assert_eq!(ReportingTimezone::parse("UTC"), Some(ReportingTimezone::Fixed(utc)));
+
+        let ist = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+        assert_eq!(ReportingTimezone::parse("+05:30"), Some(ReportingTimezone::Fixed(ist)));
+        assert_eq!(ReportingTimezone::parse("+0530"), Some(ReportingTimezone::Fixed(ist)));
+        let pst = FixedOffset::west_opt(8 * 3600).unwrap();
+        assert_eq!(ReportingTimezone::parse("-08"), Some(ReportingTimezone::Fixed(pst)));
+
+        assert_eq!(ReportingTimezone::parse("Europe/Berlin"), None);
+        assert_eq!(ReportingTimezone::parse("+5:30"), None);
+        assert_eq!(ReportingTimezone::parse("+05:75"), None);
+        assert_eq!(ReportingTimezone::parse("+25:00"), None);
+    }
+
+    #[test]
+    fn test_local_date() {
+        let pst = ReportingTimezone::parse("-08:00").unwrap();
+        // 1am UTC is still the previous evening on the US west coast
+        assert_eq!(pst.local_date("2026-02-05T01:30:00.000Z"), Some("2026-02-04".to_string()));
+        assert_eq!(pst.local_date("2026-02-05T09:30:00Z"), Some("2026-02-05".to_string()));
+        assert_eq!(
+            pst.local_datetime("2026-02-05T01:30:00Z").map(|t| t.to_string()),
+            Some("2026-02-04 17:30:00".to_string())
+        );
+
+        let tokyo = ReportingTimezone::parse("+09:00").unwrap();
+        assert_eq!(tokyo.local_date("2026-02-05T20:00:00Z"), Some("2026-02-06".to_string()));
+
+        // Bare dates pass through; anything else isn't a date
+        assert_eq!(pst.local_date("2026-02-05"), Some("2026-02-05".to_string()));
+        assert_eq!(pst.local_date("unknown"), None);
+        assert_eq!(tokyo.current_offset(), "+09:00");
+    }
+}
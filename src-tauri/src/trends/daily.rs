@@ -1,42 +1,41 @@
 //! Daily trend calculations
 //!
 //! Aggregates session data by day for trend analysis.
+//!
+//! Session dates are calendar days in the reporting timezone. Days without
+//! sessions can be backfilled with empty entries so charts get one point per
+//! day.
 
 use std::collections::HashMap;
-use chrono::{NaiveDate, Utc, Duration as ChronoDuration};
+use chrono::{NaiveDate, Duration as ChronoDuration};
 
 use super::{DailyTrend, TrendSummary};
+use crate::settings::reporting_timezone;
+use crate::timezone::{self, ReportingTimezone};
 
 /// Parse a date string in YYYY-MM-DD format
 pub fn parse_date(date_str: &str) -> Option<NaiveDate> {
     NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()
 }
 
-/// Extract date from ISO 8601 timestamp
+/// Extract the local date from an ISO 8601 timestamp
 ///
-/// Handles formats like:
+/// The date is taken in the reporting timezone. Handles formats like:
 /// - "2026-02-05T10:30:00.000Z"
 /// - "2026-02-05T10:30:00Z"
 /// - "2026-02-05"
 pub fn extract_date_from_timestamp(timestamp: &str) -> Option<String> {
-    // Try to extract just the date portion
-    if timestamp.len() >= 10 {
-        let date_part = &timestamp[0..10];
-        if parse_date(date_part).is_some() {
-            return Some(date_part.to_string());
-        }
-    }
-    None
+    timezone::local_date(timestamp)
 }
 
-/// Get current date as YYYY-MM-DD string
+/// Get the current local date as YYYY-MM-DD string
 pub fn today() -> String {
-    Utc::now().format("%Y-%m-%d").to_string()
+    days_ago(0)
 }
 
-/// Get date N days ago as YYYY-MM-DD string
+/// Get the local date N days ago as YYYY-MM-DD string
 pub fn days_ago(n: i64) -> String {
-    let date = Utc::now() - ChronoDuration::days(n);
+    let date = reporting_timezone().today() - ChronoDuration::days(n);
     date.format("%Y-%m-%d").to_string()
 }
 
@@ -106,18 +105,33 @@ pub fn filter_by_date_range(
 }
 
 /// Get daily trends for the last N days
+///
+/// With `backfill`, every day in the range gets an entry, empty ones included.
 pub fn get_daily_trends(
     sessions: &[SessionData],
     days: u32,
     start_date: Option<&str>,
     end_date: Option<&str>,
+    backfill: bool,
 ) -> Vec<DailyTrend> {
     // Aggregate by day
     let daily_map = aggregate_by_day(sessions);
 
     // Convert to sorted vector
-    let mut trends = daily_map_to_sorted_vec(daily_map);
+    let trends = daily_map_to_sorted_vec(daily_map);
 
+    select_daily_trends(trends, days, start_date, end_date, backfill)
+}
+
+/// Restrict sorted daily trends to a date range (or the last N days),
+/// optionally filling in missing days with empty trends
+pub fn select_daily_trends(
+    mut trends: Vec<DailyTrend>,
+    days: u32,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+    backfill: bool,
+) -> Vec<DailyTrend> {
     // Apply date range filter if provided
     if start_date.is_some() || end_date.is_some() {
         trends = filter_by_date_range(trends, start_date, end_date);
@@ -129,31 +143,33 @@ pub fn get_daily_trends(
             .collect();
     }
 
-    // Fill in missing days with empty trends
-    fill_missing_days(&mut trends, start_date, end_date, days);
+    if backfill {
+        backfill_days(&mut trends, start_date, end_date, days, reporting_timezone());
+    }
 
     trends
 }
 
 /// Fill in missing days with zero values
-fn fill_missing_days(
+///
+/// Open ends of the range default to today and `days` before it, taken as
+/// calendar dates in `tz`.
+pub fn backfill_days(
     trends: &mut Vec<DailyTrend>,
     start_date: Option<&str>,
     end_date: Option<&str>,
     days: u32,
+    tz: ReportingTimezone,
 ) {
     // Determine date range
+    let today = tz.today();
     let start = start_date
         .and_then(parse_date)
-        .unwrap_or_else(|| {
-            parse_date(&days_ago(days as i64)).unwrap()
-        });
+        .unwrap_or_else(|| today - ChronoDuration::days(days as i64));
 
     let end = end_date
         .and_then(parse_date)
-        .unwrap_or_else(|| {
-            parse_date(&today()).unwrap()
-        });
+        .unwrap_or(today);
 
     // Create set of existing dates
     let existing: std::collections::HashSet<String> = trends.iter()
@@ -181,7 +197,7 @@ pub fn calculate_trend_summary(
     end_date: Option<&str>,
     days: u32,
 ) -> TrendSummary {
-    let daily = get_daily_trends(sessions, days, start_date, end_date);
+    let daily = get_daily_trends(sessions, days, start_date, end_date, true);
     let current = TrendSummary::from_daily(daily);
 
     // Calculate previous period for comparison
@@ -218,6 +234,7 @@ pub fn calculate_trend_summary(
         days,
         prev_start.as_deref(),
         prev_end.as_deref(),
+        true,
     );
     let previous = TrendSummary::from_daily(prev_daily);
 
@@ -369,7 +386,7 @@ mod tests {
             },
         ];
 
-        let daily = get_daily_trends(&sessions, 1, Some("2026-02-01"), Some("2026-02-01"));
+        let daily = get_daily_trends(&sessions, 1, Some("2026-02-01"), Some("2026-02-01"), true);
         assert!(!daily.is_empty());
 
         let first = &daily[0];
@@ -476,4 +493,32 @@ mod tests {
         assert_eq!(sorted[0].sessions, 5);
         assert_eq!(sorted[0].total_cost, 10.0);
     }
+
+    #[test]
+    fn test_backfill_days() {
+        let session = |started_at: &str| SessionData {
+            started_at: started_at.to_string(),
+            turns: 1,
+            tokens: 100,
+            cost: 0.5,
+            efficiency: 0.5,
+        };
+        let sessions = vec![session("2026-02-01T10:00:00Z"), session("2026-02-04T10:00:00Z")];
+
+        let sparse = get_daily_trends(&sessions, 7, Some("2026-02-01"), Some("2026-02-05"), false);
+        assert_eq!(sparse.len(), 2);
+
+        let filled = get_daily_trends(&sessions, 7, Some("2026-02-01"), Some("2026-02-05"), true);
+        let dates: Vec<&str> = filled.iter().map(|t| t.date.as_str()).collect();
+        assert_eq!(dates, vec!["2026-02-01", "2026-02-02", "2026-02-03", "2026-02-04", "2026-02-05"]);
+        assert_eq!(filled[1].sessions, 0);
+        assert_eq!(filled[3].total_cost, 0.5);
+
+        // Open ranges end on today's date in the requested timezone
+        let tz = ReportingTimezone::parse("+14:00").unwrap();
+        let mut trends = Vec::new();
+        backfill_days(&mut trends, None, None, 2, tz);
+        assert_eq!(trends.len(), 3);
+        assert_eq!(parse_date(&trends[2].date), Some(tz.today()));
+    }
 }
@@ -0,0 +1,228 @@
+//! Daily digest
+//!
+//! Summarizes one day (usually yesterday) against the seven days before it:
+//! sessions and cost next to their daily averages, the costliest project and
+//! session, and the anti-patterns found that day. Days without sessions count
+//! as zero in the averages, so a quiet week doesn't make a normal day look
+//! expensive.
+
+use serde::Serialize;
+
+use super::daily::parse_date;
+use super::DailyTrend;
+use crate::patterns::AntiPatternType;
+
+/// Days the digest's averages are taken over
+pub const BASELINE_DAYS: i64 = 7;
+
+/// Project with the highest cost on the digest's day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DigestProject {
+    pub project_path: String,
+    pub project_name: String,
+    pub sessions: u32,
+    pub cost: f64,
+}
+
+/// Session with the highest cost among those started on the digest's day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DigestSession {
+    pub session_id: String,
+    pub project_path: String,
+    pub turns: u32,
+    pub cost: f64,
+}
+
+/// Anti-patterns of one type found on the digest's day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DigestAntiPattern {
+    pub pattern_type: AntiPatternType,
+    pub occurrences: u32,
+    pub sessions: u32,
+    pub impact_cost: f64,
+    /// Occurrences per day over the baseline week
+    pub avg_occurrences_7d: f64,
+}
+
+impl DigestAntiPattern {
+    /// Whether the type didn't occur at all in the baseline week
+    pub fn is_new(&self) -> bool {
+        self.avg_occurrences_7d == 0.0
+    }
+}
+
+/// Summary of a day compared to the week before it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DailyDigest {
+    /// Date in YYYY-MM-DD format
+    pub date: String,
+    pub sessions: u32,
+    pub turns: u32,
+    pub cost: f64,
+    pub avg_sessions_7d: f64,
+    pub avg_cost_7d: f64,
+    /// Change from the daily averages in percent; None without a baseline
+    pub sessions_change_percent: Option<f64>,
+    pub cost_change_percent: Option<f64>,
+    pub top_project: Option<DigestProject>,
+    pub most_expensive_session: Option<DigestSession>,
+    /// Most frequent first
+    pub antipatterns: Vec<DigestAntiPattern>,
+}
+
+impl DailyDigest {
+    /// Total the day and its baseline week from `daily`
+    pub fn new(
+        date: &str,
+        daily: &[DailyTrend],
+        top_project: Option<DigestProject>,
+        most_expensive_session: Option<DigestSession>,
+        mut antipatterns: Vec<DigestAntiPattern>,
+    ) -> Self {
+        let day = daily.iter().find(|d| d.date == date);
+        let baseline: Vec<&DailyTrend> = match parse_date(date) {
+            Some(end) => {
+                let start = (end - chrono::Duration::days(BASELINE_DAYS)).format("%Y-%m-%d").to_string();
+                daily.iter().filter(|d| d.date.as_str() >= start.as_str() && d.date.as_str() < date).collect()
+            }
+            None => Vec::new(),
+        };
+        let sessions = day.map_or(0, |d| d.sessions);
+        let cost = day.map_or(0.0, |d| d.total_cost);
+        // An empty f64 sum is -0.0, which would be reported as such
+        let daily_average = |value: fn(&DailyTrend) -> f64| match baseline.is_empty() {
+            true => 0.0,
+            false => baseline.iter().map(|d| value(d)).sum::<f64>() / BASELINE_DAYS as f64,
+        };
+        let avg_sessions_7d = daily_average(|d| d.sessions as f64);
+        let avg_cost_7d = daily_average(|d| d.total_cost);
+        antipatterns.sort_by(|a, b| {
+            b.occurrences.cmp(&a.occurrences).then_with(|| a.pattern_type.as_str().cmp(b.pattern_type.as_str()))
+        });
+
+        Self {
+            date: date.to_string(),
+            sessions,
+            turns: day.map_or(0, |d| d.turns),
+            cost,
+            avg_sessions_7d,
+            avg_cost_7d,
+            sessions_change_percent: change_percent(sessions as f64, avg_sessions_7d),
+            cost_change_percent: change_percent(cost, avg_cost_7d),
+            top_project,
+            most_expensive_session,
+            antipatterns,
+        }
+    }
+
+    /// Human-readable summary, one line per section
+    pub fn text(&self) -> String {
+        let mut lines = vec![format!(
+            "Daily digest for {}: {} sessions, {} turns, ${:.2}{}",
+            self.date,
+            self.sessions,
+            self.turns,
+            self.cost,
+            match self.cost_change_percent {
+                Some(change) => format!(" ({:+.0}% vs. the 7-day average of ${:.2})", change, self.avg_cost_7d),
+                None => String::new(),
+            }
+        )];
+        if let Some(project) = &self.top_project {
+            lines.push(format!(
+                "Top project: {} (${:.2} over {} sessions)",
+                project.project_name, project.cost, project.sessions
+            ));
+        }
+        if let Some(session) = &self.most_expensive_session {
+            lines.push(format!(
+                "Most expensive session: {} (${:.2}, {} turns)",
+                session.session_id, session.cost, session.turns
+            ));
+        }
+        if !self.antipatterns.is_empty() {
+            let patterns: Vec<String> = self
+                .antipatterns
+                .iter()
+                .map(|p| {
+                    let new = if p.is_new() { ", new" } else { "" };
+                    format!("{} ({}×{})", p.pattern_type.display_name(), p.occurrences, new)
+                })
+                .collect();
+            lines.push(format!("Anti-patterns: {}", patterns.join(", ")));
+        }
+        lines.join("\n")
+    }
+}
+
+fn change_percent(value: f64, average: f64) -> Option<f64> {
+    (average > 0.0).then(|| (value - average) / average * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: &str, sessions: u32, cost: f64) -> DailyTrend {
+        DailyTrend {
+            sessions,
+            turns: sessions * 10,
+            total_cost: cost,
+            ..DailyTrend::new(date.to_string())
+        }
+    }
+
+    fn pattern(pattern_type: AntiPatternType, occurrences: u32, avg_occurrences_7d: f64) -> DigestAntiPattern {
+        DigestAntiPattern { pattern_type, occurrences, sessions: 1, impact_cost: 0.5, avg_occurrences_7d }
+    }
+
+    #[test]
+    fn test_daily_digest() {
+        // The 7 days before the 15th average 2 sessions and $10; the 7th is
+        // outside the baseline and the 16th after the day
+        let daily = vec![
+            day("2026-03-07", 50, 500.0),
+            day("2026-03-08", 7, 35.0),
+            day("2026-03-12", 7, 35.0),
+            day("2026-03-15", 3, 15.0),
+            day("2026-03-16", 9, 90.0),
+        ];
+        let session = DigestSession {
+            session_id: "s1".to_string(),
+            project_path: "/work/app".to_string(),
+            turns: 12,
+            cost: 9.5,
+        };
+        let digest = DailyDigest::new(
+            "2026-03-15",
+            &daily,
+            None,
+            Some(session),
+            vec![pattern(AntiPatternType::RetryStorm, 1, 0.0), pattern(AntiPatternType::ContextChurn, 4, 2.0)],
+        );
+
+        assert_eq!(digest.sessions, 3);
+        assert_eq!(digest.turns, 30);
+        assert!((digest.avg_sessions_7d - 2.0).abs() < 1e-9);
+        assert!((digest.avg_cost_7d - 10.0).abs() < 1e-9);
+        assert!((digest.cost_change_percent.unwrap() - 50.0).abs() < 1e-9);
+        assert_eq!(digest.antipatterns[0].pattern_type, AntiPatternType::ContextChurn);
+        assert!(digest.antipatterns[1].is_new());
+
+        let text = digest.text();
+        assert!(text.starts_with("Daily digest for 2026-03-15: 3 sessions, 30 turns, $15.00 (+50% vs."));
+        assert!(text.contains("Most expensive session: s1 ($9.50, 12 turns)"));
+        assert!(!text.contains("Top project"));
+
+        // A day without sessions or history has no baseline to compare to
+        let empty = DailyDigest::new("2026-01-01", &daily, None, None, Vec::new());
+        assert_eq!(empty.cost, 0.0);
+        assert_eq!(empty.cost_change_percent, None);
+        assert!(empty.avg_cost_7d == 0.0 && empty.avg_cost_7d.is_sign_positive());
+        assert!(empty.avg_sessions_7d.is_sign_positive());
+    }
+}
@@ -0,0 +1,262 @@
+//! Cost forecasting
+//!
+//! Fits a least-squares line to recent daily cost and extrapolates it to
+//! project spend for the coming days and the rest of the current month.
+
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::daily::parse_date;
+use super::DailyTrend;
+
+/// Number of completed days the regression is fitted to
+pub const DEFAULT_HISTORY_DAYS: usize = 30;
+
+/// Minimum number of days of history needed to fit a trend line; with less,
+/// the forecast falls back to the flat daily average
+const MIN_FIT_DAYS: usize = 7;
+
+/// Least-squares line `y = intercept + slope * x` over `x = 0..n`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+}
+
+impl LinearFit {
+    /// Fit a line to evenly spaced values; None with fewer than two values
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        if values.len() < 2 {
+            return None;
+        }
+        let n = values.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = values.iter().sum::<f64>() / n;
+
+        let (mut covariance, mut variance) = (0.0, 0.0);
+        for (i, y) in values.iter().enumerate() {
+            let dx = i as f64 - mean_x;
+            covariance += dx * (y - mean_y);
+            variance += dx * dx;
+        }
+
+        let slope = covariance / variance;
+        Some(Self {
+            slope,
+            intercept: mean_y - slope * mean_x,
+        })
+    }
+
+    /// Value of the line at `x`
+    pub fn predict(&self, x: f64) -> f64 {
+        self.intercept + self.slope * x
+    }
+}
+
+/// Projected cost for a single future day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ForecastPoint {
+    /// Date in YYYY-MM-DD format
+    pub date: String,
+    pub projected_cost: f64,
+    /// Month-to-date cost including this day's projection
+    pub cumulative_cost: f64,
+}
+
+/// Projected spend for the coming days and the current month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CostForecast {
+    /// Date the forecast was made (YYYY-MM-DD); projections start the day after
+    pub as_of: String,
+    /// Month being projected (YYYY-MM)
+    pub month: String,
+    /// Actual cost so far this month, including the forecast date
+    pub month_to_date_cost: f64,
+    /// Actual cost so far plus the projection for the remaining days
+    pub projected_month_cost: f64,
+    /// Mean daily cost over the fitted history
+    pub daily_average: f64,
+    /// Change in daily cost per day (0 when there's too little history)
+    pub daily_slope: f64,
+    /// Number of days of history the projection is based on
+    pub history_days: u32,
+    pub points: Vec<ForecastPoint>,
+}
+
+/// Project daily cost after `as_of`
+///
+/// `daily` must be sorted by date with no gaps. The line is fitted to the
+/// `history` days before `as_of`, whose own cost is left out of the fit
+/// since that day is usually still in progress. Projected costs never go
+/// below zero. `days_ahead` defaults to the rest of the month; the month
+/// total is always projected to the end of the month.
+pub fn forecast_costs(
+    daily: &[DailyTrend],
+    as_of: NaiveDate,
+    days_ahead: Option<u32>,
+    history: usize,
+) -> CostForecast {
+    let as_of_str = as_of.format("%Y-%m-%d").to_string();
+    let month = as_of.format("%Y-%m").to_string();
+
+    let past: Vec<f64> = daily
+        .iter()
+        .filter(|d| d.date < as_of_str)
+        .map(|d| d.total_cost)
+        .collect();
+    let past = &past[past.len().saturating_sub(history)..];
+
+    let daily_average = if past.is_empty() {
+        0.0
+    } else {
+        past.iter().sum::<f64>() / past.len() as f64
+    };
+    let fit = if past.len() >= MIN_FIT_DAYS {
+        LinearFit::from_values(past)
+    } else {
+        None
+    };
+    let project = |offset: i64| match fit {
+        // The fitted values sit at x = 0..len; `as_of` is x = len
+        Some(fit) => fit.predict((past.len() as i64 + offset) as f64).max(0.0),
+        None => daily_average,
+    };
+
+    let month_to_date_cost: f64 = daily
+        .iter()
+        .filter(|d| d.date.starts_with(&month) && d.date <= as_of_str)
+        .map(|d| d.total_cost)
+        .sum();
+
+    let remaining_in_month = (last_day_of_month(as_of) - as_of).num_days();
+    let days_ahead = days_ahead.map_or(remaining_in_month, i64::from);
+
+    let mut cumulative = month_to_date_cost;
+    let mut points = Vec::new();
+    for offset in 1..=days_ahead {
+        let date = as_of + ChronoDuration::days(offset);
+        if date.day() == 1 {
+            // Cumulative cost is month-to-date, so it starts over each month
+            cumulative = 0.0;
+        }
+        let projected_cost = project(offset);
+        cumulative += projected_cost;
+        points.push(ForecastPoint {
+            date: date.format("%Y-%m-%d").to_string(),
+            projected_cost,
+            cumulative_cost: cumulative,
+        });
+    }
+
+    let projected_month_cost =
+        month_to_date_cost + (1..=remaining_in_month).map(project).sum::<f64>();
+
+    CostForecast {
+        as_of: as_of_str,
+        month,
+        month_to_date_cost,
+        projected_month_cost,
+        daily_average,
+        daily_slope: fit.map_or(0.0, |f| f.slope),
+        history_days: past.len() as u32,
+        points,
+    }
+}
+
+/// Last day of the month containing `date`
+fn last_day_of_month(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    parse_date(&format!("{:04}-{:02}-01", year, month))
+        .and_then(|first| first.pred_opt())
+        .unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(start: &str, costs: &[f64]) -> Vec<DailyTrend> {
+        let start = parse_date(start).unwrap();
+        costs
+            .iter()
+            .enumerate()
+            .map(|(i, cost)| {
+                let date = start + ChronoDuration::days(i as i64);
+                let mut t = DailyTrend::new(date.format("%Y-%m-%d").to_string());
+                t.add_session(10, 1000, *cost, 0.8);
+                t
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_fit() {
+        let fit = LinearFit::from_values(&[1.0, 3.0, 5.0, 7.0]).unwrap();
+        assert!((fit.slope - 2.0).abs() < 1e-9);
+        assert!((fit.intercept - 1.0).abs() < 1e-9);
+        assert!((fit.predict(4.0) - 9.0).abs() < 1e-9);
+
+        assert!(LinearFit::from_values(&[5.0]).is_none());
+        assert_eq!(LinearFit::from_values(&[2.0, 2.0]).unwrap().slope, 0.0);
+    }
+
+    #[test]
+    fn test_forecast_rest_of_month() {
+        // Jan 1..=Jan 20, cost rising by 1/day; Jan 20 is today (partial)
+        let costs: Vec<f64> = (1..=20).map(|d| d as f64).collect();
+        let trends = daily("2026-01-01", &costs);
+        let as_of = parse_date("2026-01-20").unwrap();
+
+        let forecast = forecast_costs(&trends, as_of, None, DEFAULT_HISTORY_DAYS);
+        assert_eq!(forecast.month, "2026-01");
+        assert_eq!(forecast.history_days, 19);
+        assert!((forecast.daily_slope - 1.0).abs() < 1e-9);
+        assert!((forecast.month_to_date_cost - 210.0).abs() < 1e-9);
+
+        // Jan 21..=Jan 31 project as 21..=31
+        assert_eq!(forecast.points.len(), 11);
+        assert_eq!(forecast.points[0].date, "2026-01-21");
+        assert!((forecast.points[0].projected_cost - 21.0).abs() < 1e-9);
+        assert!((forecast.projected_month_cost - 496.0).abs() < 1e-9);
+        assert!((forecast.points[10].cumulative_cost - 496.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_forecast_falls_back_to_average() {
+        let trends = daily("2026-03-01", &[2.0, 4.0, 6.0]);
+        let as_of = parse_date("2026-03-04").unwrap();
+
+        let forecast = forecast_costs(&trends, as_of, Some(3), DEFAULT_HISTORY_DAYS);
+        assert_eq!(forecast.daily_slope, 0.0);
+        assert!(forecast.points.iter().all(|p| (p.projected_cost - 4.0).abs() < 1e-9));
+        // 12 spent + 27 remaining days at 4.0
+        assert!((forecast.projected_month_cost - 120.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_forecast_never_negative() {
+        let costs: Vec<f64> = (0..10).map(|d| 10.0 - d as f64).collect();
+        let trends = daily("2026-04-01", &costs);
+        let as_of = parse_date("2026-04-11").unwrap();
+
+        let forecast = forecast_costs(&trends, as_of, Some(30), DEFAULT_HISTORY_DAYS);
+        assert_eq!(forecast.points.len(), 30);
+        assert!(forecast.points.iter().all(|p| p.projected_cost >= 0.0));
+        // Cumulative restarts with May
+        assert_eq!(forecast.points[19].date, "2026-05-01");
+        assert_eq!(forecast.points[19].cumulative_cost, forecast.points[19].projected_cost);
+    }
+
+    #[test]
+    fn test_last_day_of_month() {
+        let d = |s| parse_date(s).unwrap();
+        assert_eq!(last_day_of_month(d("2026-02-10")), d("2026-02-28"));
+        assert_eq!(last_day_of_month(d("2026-12-31")), d("2026-12-31"));
+    }
+}
@@ -1,18 +1,29 @@
 //! Trend analysis module
 //!
 //! This module handles historical trend calculations:
-//! - Daily/weekly/monthly aggregation of session data
-//! - Period-over-period comparisons
+//! - Daily/weekly/monthly aggregation of session data, optionally of only
+//!   user or only subagent sessions
+//! - Period-over-period comparisons and comparisons of arbitrary date ranges
+//! - Rolling statistics and cost anomaly detection
+//! - Cost forecasting
+//! - Daily digests and weekly reports comparing a day or week with the
+//!   week before it
 //! - Time-series data for charts and visualization
 
 pub mod daily;
 pub mod weekly;
 pub mod monthly;
+pub mod statistics;
+pub mod forecast;
+pub mod periods;
+pub mod digest;
+pub mod report;
 
 use serde::{Deserialize, Serialize};
 
 /// Represents a single day's aggregated data
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct DailyTrend {
     /// Date in YYYY-MM-DD format
     pub date: String,
@@ -55,6 +66,7 @@ impl DailyTrend {
 
 /// Represents a weekly aggregation
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct WeeklyTrend {
     /// Week start date in YYYY-MM-DD format (Monday)
     pub week_start: String,
@@ -72,12 +84,16 @@ pub struct WeeklyTrend {
     pub total_cost: f64,
     /// Average efficiency score
     pub avg_efficiency: f64,
+    /// Change vs the previous week
+    #[serde(default)]
+    pub comparison: Option<PeriodComparison>,
     /// Daily breakdown
     pub daily: Vec<DailyTrend>,
 }
 
 /// Represents a monthly aggregation
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
 pub struct MonthlyTrend {
     /// Month in YYYY-MM format
     pub month: String,
@@ -91,10 +107,66 @@ pub struct MonthlyTrend {
     pub total_cost: f64,
     /// Average efficiency score
     pub avg_efficiency: f64,
+    /// Change vs the previous month
+    #[serde(default)]
+    pub comparison: Option<PeriodComparison>,
     /// Weekly breakdown
     pub weekly: Vec<WeeklyTrend>,
 }
 
+/// Percentage changes of a period vs the one before it
+/// Positive = increase, Negative = decrease
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct PeriodComparison {
+    pub sessions_change_percent: f64,
+    pub tokens_change_percent: f64,
+    pub cost_change_percent: f64,
+    pub efficiency_change_percent: f64,
+}
+
+impl PeriodComparison {
+    /// Compare period totals, each given as (current, previous)
+    pub fn new(
+        sessions: (u32, u32),
+        tokens: (u64, u64),
+        cost: (f64, f64),
+        efficiency: (f64, f64),
+    ) -> Self {
+        Self {
+            sessions_change_percent: percent_change(sessions.0 as f64, sessions.1 as f64),
+            tokens_change_percent: percent_change(tokens.0 as f64, tokens.1 as f64),
+            cost_change_percent: percent_change(cost.0, cost.1),
+            efficiency_change_percent: percent_change(efficiency.0, efficiency.1),
+        }
+    }
+}
+
+/// Trend data at the requested granularity
+///
+/// Serialized as a bare array so daily results keep their existing shape.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum TrendSeries {
+    Daily(Vec<DailyTrend>),
+    Weekly(Vec<WeeklyTrend>),
+    Monthly(Vec<MonthlyTrend>),
+}
+
+/// Percentage change from `previous` to `current`
+///
+/// Growth from zero counts as +100%; zero to zero is no change.
+pub fn percent_change(current: f64, previous: f64) -> f64 {
+    if previous > 0.0 {
+        ((current - previous) / previous) * 100.0
+    } else if current > 0.0 {
+        100.0
+    } else {
+        0.0
+    }
+}
+
 /// Summary of trend data with period-over-period comparisons
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendSummary {
@@ -119,7 +191,11 @@ impl TrendSummary {
     /// Create a new TrendSummary from daily data
     pub fn from_daily(daily: Vec<DailyTrend>) -> Self {
         let total_sessions: u32 = daily.iter().map(|d| d.sessions).sum();
-        let total_cost: f64 = daily.iter().map(|d| d.total_cost).sum();
+        // An empty f64 sum is -0.0; a range without days costs plain zero
+        let total_cost: f64 = match daily.is_empty() {
+            true => 0.0,
+            false => daily.iter().map(|d| d.total_cost).sum(),
+        };
         let total_tokens: u64 = daily.iter().map(|d| d.total_tokens).sum();
 
         // Calculate weighted average efficiency
@@ -145,23 +221,9 @@ impl TrendSummary {
 
     /// Calculate period-over-period changes by comparing with previous period data
     pub fn with_comparison(mut self, previous: &TrendSummary) -> Self {
-        // Cost change percentage
-        self.cost_change_percent = if previous.total_cost > 0.0 {
-            ((self.total_cost - previous.total_cost) / previous.total_cost) * 100.0
-        } else if self.total_cost > 0.0 {
-            100.0 // New spending when there was none before
-        } else {
-            0.0
-        };
-
-        // Efficiency change percentage
-        self.efficiency_change_percent = if previous.avg_efficiency > 0.0 {
-            ((self.avg_efficiency - previous.avg_efficiency) / previous.avg_efficiency) * 100.0
-        } else if self.avg_efficiency > 0.0 {
-            100.0
-        } else {
-            0.0
-        };
+        self.cost_change_percent = percent_change(self.total_cost, previous.total_cost);
+        self.efficiency_change_percent =
+            percent_change(self.avg_efficiency, previous.avg_efficiency);
 
         self
     }
@@ -201,6 +263,35 @@ impl From<Option<String>> for Granularity {
     }
 }
 
+/// Which sessions trend queries count
+///
+/// Subagent sessions are those of subagent transcript files, the same ones
+/// `subagent_session_count` counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Segment {
+    #[default]
+    All,
+    UserOnly,
+    SubagentOnly,
+}
+
+impl Segment {
+    /// The `subagent` flag of the sessions counted; None for all of them
+    pub fn subagent(self) -> Option<bool> {
+        match self {
+            Segment::All => None,
+            Segment::UserOnly => Some(false),
+            Segment::SubagentOnly => Some(true),
+        }
+    }
+
+    /// Whether a session is counted
+    pub fn includes(self, is_subagent: bool) -> bool {
+        self.subagent().is_none_or(|subagent| subagent == is_subagent)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -431,6 +522,15 @@ mod tests {
         assert!((with_comparison.efficiency_change_percent - (-12.5)).abs() < 0.001);
     }
 
+    #[test]
+    fn test_segment() {
+        assert!(Segment::All.includes(true) && Segment::All.includes(false));
+        assert!(Segment::UserOnly.includes(false) && !Segment::UserOnly.includes(true));
+        assert!(Segment::SubagentOnly.includes(true) && !Segment::SubagentOnly.includes(false));
+        assert_eq!(serde_json::from_str::<Segment>("\"user_only\"").unwrap(), Segment::UserOnly);
+        assert!(serde_json::from_str::<Segment>("\"main\"").is_err());
+    }
+
     #[test]
     fn test_granularity_default() {
         let default = Granularity::default();
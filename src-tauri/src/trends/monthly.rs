@@ -5,7 +5,7 @@
 use std::collections::HashMap;
 use chrono::{Datelike, NaiveDate};
 
-use super::{DailyTrend, MonthlyTrend};
+use super::{DailyTrend, MonthlyTrend, PeriodComparison};
 #[cfg(test)]
 use super::WeeklyTrend;
 use super::daily::parse_date;
@@ -36,6 +36,7 @@ pub fn aggregate_to_monthly(daily_trends: Vec<DailyTrend>) -> Vec<MonthlyTrend>
                     total_tokens: 0,
                     total_cost: 0.0,
                     avg_efficiency: 0.0,
+                    comparison: None,
                     weekly: Vec::new(),
                 }
             });
@@ -77,9 +78,38 @@ pub fn aggregate_to_monthly(daily_trends: Vec<DailyTrend>) -> Vec<MonthlyTrend>
     // Sort months
     months.sort_by(|a, b| a.month.cmp(&b.month));
 
+    add_comparisons(&mut months);
+
     months
 }
 
+/// Compare each month with the calendar month before it
+///
+/// A month with no data counts as zero. The earliest month has nothing to
+/// compare against and is left without a comparison.
+fn add_comparisons(months: &mut [MonthlyTrend]) {
+    let totals: HashMap<String, (u32, u64, f64, f64)> = months
+        .iter()
+        .map(|m| (m.month.clone(), (m.sessions, m.total_tokens, m.total_cost, m.avg_efficiency)))
+        .collect();
+
+    for month in months.iter_mut().skip(1) {
+        let previous_key = parse_date(&format!("{}-01", month.month))
+            .and_then(|first| first.pred_opt())
+            .map(month_key);
+        let (sessions, tokens, cost, efficiency) = previous_key
+            .and_then(|key| totals.get(&key).copied())
+            .unwrap_or_default();
+
+        month.comparison = Some(PeriodComparison::new(
+            (month.sessions, sessions),
+            (month.total_tokens, tokens),
+            (month.total_cost, cost),
+            (month.avg_efficiency, efficiency),
+        ));
+    }
+}
+
 /// Get the month key for a date string
 pub fn get_month_key(date_str: &str) -> Option<String> {
     parse_date(date_str).map(|d| month_key(d))
@@ -149,6 +179,12 @@ mod tests {
         let feb = &monthly[1];
         assert_eq!(feb.month, "2026-02");
         assert_eq!(feb.sessions, 1);
+
+        // Compared with January
+        assert!(jan.comparison.is_none());
+        let change = feb.comparison.as_ref().unwrap();
+        assert!((change.cost_change_percent + 83.333).abs() < 0.01);
+        assert!((change.sessions_change_percent + 50.0).abs() < 0.001);
     }
 
     #[test]
@@ -187,6 +223,7 @@ mod tests {
             total_tokens: 100000,
             total_cost: 50.0,
             avg_efficiency: 0.82,
+            comparison: None,
             weekly: vec![
                 WeeklyTrend {
                     week_start: "2026-02-02".to_string(),
@@ -197,6 +234,7 @@ mod tests {
                     total_tokens: 50000,
                     total_cost: 25.0,
                     avg_efficiency: 0.80,
+                    comparison: None,
                     daily: vec![],
                 },
             ],
@@ -0,0 +1,191 @@
+//! Comparison of two arbitrary date ranges
+//!
+//! Unlike the fixed period-over-period changes of `TrendSummary`, the two
+//! ranges can be any length and needn't be adjacent, e.g. the week before a
+//! workflow change against the week after it. Range A is the baseline that
+//! range B is measured against. Totals of ranges of different length are
+//! also compared per day.
+
+use serde::{Deserialize, Serialize};
+
+use super::daily::parse_date;
+use super::{DailyTrend, TrendSummary};
+use crate::metrics::comparison::{diff_tool_usage, MetricDelta, ToolUsageDelta};
+
+/// Inclusive range of calendar days (YYYY-MM-DD, reporting timezone)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DateRange {
+    pub start_date: String,
+    pub end_date: String,
+}
+
+impl DateRange {
+    /// Number of days in the range, or why the range is invalid
+    pub fn days(&self) -> Result<u32, String> {
+        let parse = |date: &str| parse_date(date).ok_or_else(|| format!("Invalid date {:?}, expected YYYY-MM-DD", date));
+        let (start, end) = (parse(&self.start_date)?, parse(&self.end_date)?);
+        if end < start {
+            return Err(format!("Range {} to {} ends before it starts", self.start_date, self.end_date));
+        }
+        Ok((end - start).num_days() as u32 + 1)
+    }
+
+    fn contains(&self, date: &str) -> bool {
+        date >= self.start_date.as_str() && date <= self.end_date.as_str()
+    }
+}
+
+/// Totals of the sessions started within a date range
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct PeriodTotals {
+    #[serde(flatten)]
+    pub range: DateRange,
+    pub days: u32,
+    pub sessions: u32,
+    pub turns: u32,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    /// Session-weighted average efficiency (cache efficiency ratio)
+    pub avg_efficiency: f64,
+    /// Tool calls of the range's stored turns
+    pub tool_calls: u32,
+    pub cost_per_day: f64,
+}
+
+impl PeriodTotals {
+    /// Total the days of `daily` that fall within `range`
+    pub fn new(range: DateRange, days: u32, daily: &[DailyTrend], tool_usage: &[(String, u32)]) -> Self {
+        let summary = TrendSummary::from_daily(
+            daily.iter().filter(|d| range.contains(&d.date)).cloned().collect(),
+        );
+        Self {
+            range,
+            days,
+            sessions: summary.total_sessions,
+            turns: summary.daily.iter().map(|d| d.turns).sum(),
+            total_tokens: summary.total_tokens,
+            total_cost: summary.total_cost,
+            avg_efficiency: summary.avg_efficiency,
+            tool_calls: tool_usage.iter().map(|(_, count)| count).sum(),
+            cost_per_day: summary.total_cost / days.max(1) as f64,
+        }
+    }
+}
+
+/// Changes from range A to range B
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct PeriodDeltas {
+    pub cost: MetricDelta,
+    pub cost_per_day: MetricDelta,
+    pub tokens: MetricDelta,
+    pub sessions: MetricDelta,
+    pub turns: MetricDelta,
+    pub efficiency: MetricDelta,
+    pub tool_calls: MetricDelta,
+}
+
+/// Two date ranges side by side
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct DateRangeComparison {
+    pub range_a: PeriodTotals,
+    pub range_b: PeriodTotals,
+    /// Range B measured against range A
+    pub deltas: PeriodDeltas,
+    /// Every tool used in either range, largest absolute change first
+    pub tool_usage: Vec<ToolUsageDelta>,
+}
+
+/// Compare range B against range A, with the tool call counts of each
+pub fn compare_periods(
+    a: PeriodTotals,
+    a_tools: &[(String, u32)],
+    b: PeriodTotals,
+    b_tools: &[(String, u32)],
+) -> DateRangeComparison {
+    let delta = |f: fn(&PeriodTotals) -> f64| MetricDelta::new(f(&a), f(&b));
+    let deltas = PeriodDeltas {
+        cost: delta(|p| p.total_cost),
+        cost_per_day: delta(|p| p.cost_per_day),
+        tokens: delta(|p| p.total_tokens as f64),
+        sessions: delta(|p| p.sessions as f64),
+        turns: delta(|p| p.turns as f64),
+        efficiency: delta(|p| p.avg_efficiency),
+        tool_calls: delta(|p| p.tool_calls as f64),
+    };
+
+    DateRangeComparison {
+        tool_usage: diff_tool_usage(a_tools, b_tools),
+        range_a: a,
+        range_b: b,
+        deltas,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: &str, end: &str) -> DateRange {
+        DateRange {
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+        }
+    }
+
+    fn day(date: &str, sessions: u32, cost: f64, efficiency: f64) -> DailyTrend {
+        DailyTrend {
+            date: date.to_string(),
+            sessions,
+            turns: sessions * 10,
+            total_tokens: sessions as u64 * 1_000,
+            total_cost: cost,
+            avg_efficiency: efficiency,
+        }
+    }
+
+    #[test]
+    fn test_compare_periods() {
+        let daily = [
+            day("2026-03-01", 2, 4.0, 0.5),
+            day("2026-03-02", 2, 2.0, 0.7),
+            day("2026-03-10", 3, 9.0, 0.8),
+            day("2026-03-20", 1, 100.0, 0.1),
+        ];
+        let (a_range, b_range) = (range("2026-03-01", "2026-03-02"), range("2026-03-08", "2026-03-11"));
+        assert_eq!(a_range.days(), Ok(2));
+        assert_eq!(b_range.days(), Ok(4));
+        let a_tools = vec![("Read".to_string(), 10), ("Edit".to_string(), 4)];
+        let b_tools = vec![("Read".to_string(), 5), ("Bash".to_string(), 6)];
+
+        let a = PeriodTotals::new(a_range, 2, &daily, &a_tools);
+        let b = PeriodTotals::new(b_range, 4, &daily, &b_tools);
+        assert_eq!((a.sessions, a.turns, a.total_cost), (4, 40, 6.0));
+        assert!((a.avg_efficiency - 0.6).abs() < 1e-9);
+        assert_eq!(b.tool_calls, 11);
+
+        let comparison = compare_periods(a, &a_tools, b, &b_tools);
+        assert_eq!(comparison.deltas.cost.diff, 3.0);
+        assert_eq!(comparison.deltas.cost.percent_change, 50.0);
+        // Range B is twice as long, so it spent less per day
+        assert_eq!(comparison.deltas.cost_per_day.percent_change, -25.0);
+        assert_eq!(comparison.deltas.sessions.percent_change, -25.0);
+        assert_eq!(comparison.tool_usage[0].tool, "Bash");
+        assert_eq!(comparison.tool_usage.len(), 3);
+
+        // A range with no days at all totals a positive zero
+        let empty = PeriodTotals::new(range("2025-01-01", "2025-01-02"), 2, &daily, &[]);
+        assert!(empty.total_cost == 0.0 && empty.total_cost.is_sign_positive());
+        assert!(empty.cost_per_day.is_sign_positive());
+    }
+
+    #[test]
+    fn test_invalid_date_range() {
+        assert!(range("2026-03-02", "2026-03-01").days().is_err());
+        assert!(range("March 1", "2026-03-01").days().is_err());
+        assert_eq!(range("2026-02-28", "2026-03-01").days(), Ok(2));
+    }
+}
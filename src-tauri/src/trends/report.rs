@@ -0,0 +1,190 @@
+//! Weekly report
+//!
+//! Puts one week next to the week before it: the totals and their deltas,
+//! the days of the week for efficiency and cost charts, the costliest
+//! sessions, how often each anti-pattern came up in either week, and the
+//! recommendations most worth acting on. A week is any 7 days from its
+//! start date, so weeks can start on whichever day suits the reader.
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+use super::daily::parse_date;
+use super::periods::{DateRange, DateRangeComparison};
+use super::DailyTrend;
+use crate::patterns::AntiPatternType;
+use crate::recommendations::Recommendation;
+
+/// Days in a report week
+pub const WEEK_DAYS: i64 = 7;
+
+/// Sessions listed in a report, by cost
+pub const TOP_SESSIONS: usize = 5;
+
+/// Recommendations listed in a report, by priority
+pub const TOP_RECOMMENDATIONS: usize = 3;
+
+/// A session started in the report week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ReportSession {
+    pub session_id: String,
+    pub project_path: String,
+    pub project_name: String,
+    pub started_at: String,
+    pub turns: u32,
+    pub cost: f64,
+}
+
+/// Anti-patterns of one type in the report week and the week before
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct ReportAntiPattern {
+    pub pattern_type: AntiPatternType,
+    pub occurrences: u32,
+    pub previous_occurrences: u32,
+    /// Sessions of the report week the pattern was found in
+    pub sessions: u32,
+    pub impact_cost: f64,
+}
+
+/// A week compared to the week before it
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct WeeklyReport {
+    pub week: DateRange,
+    /// Totals of the previous week (range A) and this week (range B)
+    pub comparison: DateRangeComparison,
+    /// Every day of the week, including days without sessions
+    pub daily: Vec<DailyTrend>,
+    pub top_sessions: Vec<ReportSession>,
+    /// Types found in either week, most frequent this week first
+    pub antipatterns: Vec<ReportAntiPattern>,
+    pub recommendations: Vec<Recommendation>,
+}
+
+impl WeeklyReport {
+    /// Assemble a report for the week of `comparison`'s range B
+    ///
+    /// `sessions` are sorted by cost and `recommendations` by priority, and
+    /// each is cut to its top entries.
+    pub fn new(
+        comparison: DateRangeComparison,
+        daily: &[DailyTrend],
+        mut sessions: Vec<ReportSession>,
+        mut antipatterns: Vec<ReportAntiPattern>,
+        mut recommendations: Vec<Recommendation>,
+    ) -> Self {
+        let week = comparison.range_b.range.clone();
+        let days = match parse_date(&week.start_date) {
+            Some(start) => (0..WEEK_DAYS)
+                .map(|offset| format_date(start + chrono::Duration::days(offset)))
+                .map(|date| daily.iter().find(|d| d.date == date).cloned().unwrap_or_else(|| DailyTrend::new(date)))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        sessions.sort_by(|a, b| b.cost.total_cmp(&a.cost).then_with(|| a.session_id.cmp(&b.session_id)));
+        sessions.truncate(TOP_SESSIONS);
+        antipatterns.sort_by(|a, b| {
+            b.occurrences
+                .cmp(&a.occurrences)
+                .then_with(|| b.previous_occurrences.cmp(&a.previous_occurrences))
+                .then_with(|| a.pattern_type.as_str().cmp(b.pattern_type.as_str()))
+        });
+        recommendations.sort_by(|a, b| b.priority_score.total_cmp(&a.priority_score));
+        recommendations.truncate(TOP_RECOMMENDATIONS);
+
+        Self {
+            week,
+            comparison,
+            daily: days,
+            top_sessions: sessions,
+            antipatterns,
+            recommendations,
+        }
+    }
+}
+
+/// The report week starting at `start` and the week before it
+pub fn week_ranges(start: NaiveDate) -> (DateRange, DateRange) {
+    let range = |start: NaiveDate| DateRange {
+        start_date: format_date(start),
+        end_date: format_date(start + chrono::Duration::days(WEEK_DAYS - 1)),
+    };
+    (range(start - chrono::Duration::days(WEEK_DAYS)), range(start))
+}
+
+/// Monday of the last full week before `today`
+pub fn last_full_week(today: NaiveDate) -> NaiveDate {
+    today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64 + WEEK_DAYS)
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trends::periods::{compare_periods, PeriodTotals};
+
+    fn session(id: &str, cost: f64) -> ReportSession {
+        ReportSession {
+            session_id: id.to_string(),
+            project_path: "/work/app".to_string(),
+            project_name: "app".to_string(),
+            started_at: "2026-03-10T09:00:00Z".to_string(),
+            turns: 10,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_weekly_report() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 19).unwrap();
+        let start = last_full_week(today);
+        assert_eq!(format_date(start), "2026-03-09");
+        let (previous, week) = week_ranges(start);
+        assert_eq!((previous.start_date.as_str(), previous.end_date.as_str()), ("2026-03-02", "2026-03-08"));
+        assert_eq!(week.end_date, "2026-03-15");
+
+        let daily = vec![
+            DailyTrend { sessions: 2, total_cost: 4.0, ..DailyTrend::new("2026-03-03".to_string()) },
+            DailyTrend { sessions: 3, total_cost: 9.0, ..DailyTrend::new("2026-03-10".to_string()) },
+        ];
+        let comparison = compare_periods(
+            PeriodTotals::new(previous, 7, &daily, &[]),
+            &[],
+            PeriodTotals::new(week, 7, &daily, &[]),
+            &[],
+        );
+        let sessions = (1..=7).map(|i| session(&format!("s{}", i), i as f64)).collect();
+        let antipatterns = vec![
+            ReportAntiPattern {
+                pattern_type: AntiPatternType::CostSpike,
+                occurrences: 0,
+                previous_occurrences: 4,
+                sessions: 0,
+                impact_cost: 0.0,
+            },
+            ReportAntiPattern {
+                pattern_type: AntiPatternType::ContextChurn,
+                occurrences: 2,
+                previous_occurrences: 1,
+                sessions: 2,
+                impact_cost: 1.5,
+            },
+        ];
+        let report = WeeklyReport::new(comparison, &daily, sessions, antipatterns, Vec::new());
+
+        assert_eq!(report.week.start_date, "2026-03-09");
+        assert_eq!(report.daily.len(), 7);
+        assert_eq!(report.daily[1].total_cost, 9.0);
+        assert_eq!(report.daily[0].sessions, 0);
+        assert_eq!(report.comparison.deltas.cost.percent_change, 125.0);
+        assert_eq!(report.top_sessions.len(), TOP_SESSIONS);
+        assert_eq!(report.top_sessions[0].session_id, "s7");
+        assert_eq!(report.antipatterns[0].pattern_type, AntiPatternType::ContextChurn);
+    }
+}
@@ -0,0 +1,158 @@
+//! Trend statistics
+//!
+//! Rolling mean/standard deviation over daily trend data and detection of
+//! days whose cost stands out from the days before them.
+
+use serde::{Deserialize, Serialize};
+
+use super::DailyTrend;
+
+/// Number of preceding days used as the baseline for a day
+pub const DEFAULT_WINDOW_DAYS: usize = 14;
+
+/// Standard deviations above the baseline mean for a day to be anomalous
+pub const DEFAULT_SIGMA: f64 = 2.0;
+
+/// Minimum number of baseline days before a day can be flagged
+const MIN_BASELINE_DAYS: usize = 7;
+
+/// Mean and standard deviation of a set of values
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RollingStats {
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+impl RollingStats {
+    /// Compute the mean and population standard deviation; None if empty
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        Some(Self {
+            mean,
+            std_dev: variance.sqrt(),
+        })
+    }
+
+    /// Number of standard deviations `value` lies above the mean
+    pub fn z_score(&self, value: f64) -> Option<f64> {
+        if self.std_dev > f64::EPSILON {
+            Some((value - self.mean) / self.std_dev)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stats of the `window` values preceding each value (the value itself is
+/// excluded so a spike doesn't inflate its own baseline)
+///
+/// Entries with fewer than `window` predecessors use what is available.
+pub fn trailing_stats(values: &[f64], window: usize) -> Vec<Option<RollingStats>> {
+    (0..values.len())
+        .map(|i| RollingStats::from_values(&values[i.saturating_sub(window)..i]))
+        .collect()
+}
+
+/// A day whose cost is unusually high compared to the days before it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct CostAnomaly {
+    /// Date in YYYY-MM-DD format
+    pub date: String,
+    pub cost: f64,
+    pub sessions: u32,
+    /// Mean daily cost over the baseline window
+    pub baseline_mean: f64,
+    /// Standard deviation of daily cost over the baseline window
+    pub baseline_std_dev: f64,
+    /// Cost above which the day counts as anomalous
+    pub threshold: f64,
+    /// Standard deviations above the baseline mean
+    pub z_score: f64,
+}
+
+/// Flag days whose cost exceeds the trailing mean by more than `sigma`
+/// standard deviations
+///
+/// `daily` must be sorted by date with no gaps (days without usage present
+/// with zero cost), so the window covers calendar days. Days with fewer than
+/// a week of history or a perfectly flat baseline are never flagged.
+pub fn detect_cost_anomalies(daily: &[DailyTrend], window: usize, sigma: f64) -> Vec<CostAnomaly> {
+    let costs: Vec<f64> = daily.iter().map(|d| d.total_cost).collect();
+    let stats = trailing_stats(&costs, window);
+
+    daily
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i >= MIN_BASELINE_DAYS.min(window))
+        .filter_map(|(i, day)| {
+            let baseline = stats[i]?;
+            let z_score = baseline.z_score(day.total_cost)?;
+            (z_score > sigma).then(|| CostAnomaly {
+                date: day.date.clone(),
+                cost: day.total_cost,
+                sessions: day.sessions,
+                baseline_mean: baseline.mean,
+                baseline_std_dev: baseline.std_dev,
+                threshold: baseline.mean + sigma * baseline.std_dev,
+                z_score,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(costs: &[f64]) -> Vec<DailyTrend> {
+        costs
+            .iter()
+            .enumerate()
+            .map(|(i, cost)| {
+                let mut t = DailyTrend::new(format!("2026-02-{:02}", i + 1));
+                t.add_session(10, 1000, *cost, 0.8);
+                t
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rolling_stats() {
+        let stats = RollingStats::from_values(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).unwrap();
+        assert!((stats.mean - 5.0).abs() < 1e-9);
+        assert!((stats.std_dev - 2.0).abs() < 1e-9);
+        assert!((stats.z_score(9.0).unwrap() - 2.0).abs() < 1e-9);
+
+        assert!(RollingStats::from_values(&[]).is_none());
+        assert!(RollingStats::from_values(&[3.0, 3.0]).unwrap().z_score(5.0).is_none());
+    }
+
+    #[test]
+    fn test_trailing_stats_excludes_current_value() {
+        let stats = trailing_stats(&[1.0, 3.0, 100.0], 2);
+        assert!(stats[0].is_none());
+        assert_eq!(stats[1].unwrap().mean, 1.0);
+        assert_eq!(stats[2].unwrap().mean, 2.0);
+    }
+
+    #[test]
+    fn test_detect_cost_anomalies() {
+        let trends = daily(&[1.0, 2.0, 1.5, 1.0, 2.0, 1.5, 1.0, 2.0, 12.0, 1.5]);
+
+        let anomalies = detect_cost_anomalies(&trends, DEFAULT_WINDOW_DAYS, DEFAULT_SIGMA);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].date, "2026-02-09");
+        assert!(anomalies[0].z_score > DEFAULT_SIGMA);
+        assert!(anomalies[0].threshold < 12.0);
+
+        // Not enough history to judge the early spike
+        let trends = daily(&[1.0, 1.5, 20.0, 1.0]);
+        assert!(detect_cost_anomalies(&trends, DEFAULT_WINDOW_DAYS, DEFAULT_SIGMA).is_empty());
+    }
+}
@@ -5,7 +5,7 @@
 use std::collections::HashMap;
 use chrono::{Datelike, NaiveDate};
 
-use super::{DailyTrend, WeeklyTrend};
+use super::{DailyTrend, PeriodComparison, WeeklyTrend};
 use super::daily::parse_date;
 
 /// Get the Monday of the week containing the given date
@@ -45,6 +45,7 @@ pub fn aggregate_to_weekly(daily_trends: Vec<DailyTrend>) -> Vec<WeeklyTrend> {
                     total_tokens: 0,
                     total_cost: 0.0,
                     avg_efficiency: 0.0,
+                    comparison: None,
                     daily: Vec::new(),
                 }
             });
@@ -77,9 +78,37 @@ pub fn aggregate_to_weekly(daily_trends: Vec<DailyTrend>) -> Vec<WeeklyTrend> {
     // Sort weeks by start date
     weeks.sort_by(|a, b| a.week_start.cmp(&b.week_start));
 
+    add_comparisons(&mut weeks);
+
     weeks
 }
 
+/// Compare each week with the calendar week before it
+///
+/// A week with no data counts as zero. The earliest week has nothing to
+/// compare against and is left without a comparison.
+fn add_comparisons(weeks: &mut [WeeklyTrend]) {
+    let totals: HashMap<String, (u32, u64, f64, f64)> = weeks
+        .iter()
+        .map(|w| (w.week_start.clone(), (w.sessions, w.total_tokens, w.total_cost, w.avg_efficiency)))
+        .collect();
+
+    for week in weeks.iter_mut().skip(1) {
+        let previous_start = parse_date(&week.week_start)
+            .map(|d| (d - chrono::Duration::days(7)).format("%Y-%m-%d").to_string());
+        let (sessions, tokens, cost, efficiency) = previous_start
+            .and_then(|start| totals.get(&start).copied())
+            .unwrap_or_default();
+
+        week.comparison = Some(PeriodComparison::new(
+            (week.sessions, sessions),
+            (week.total_tokens, tokens),
+            (week.total_cost, cost),
+            (week.avg_efficiency, efficiency),
+        ));
+    }
+}
+
 /// Get the week start date for a given date string
 pub fn get_week_start(date_str: &str) -> Option<String> {
     parse_date(date_str).map(|d| week_start(d).format("%Y-%m-%d").to_string())
@@ -166,6 +195,38 @@ mod tests {
         assert_eq!(week2.sessions, 1);
     }
 
+    #[test]
+    fn test_weekly_comparisons() {
+        let daily = vec![
+            {
+                let mut t = DailyTrend::new("2026-02-02".to_string());
+                t.add_session(10, 4000, 2.00, 0.80);
+                t
+            },
+            {
+                let mut t = DailyTrend::new("2026-02-10".to_string());
+                t.add_session(10, 5000, 3.00, 0.80);
+                t
+            },
+            {
+                // Skips a week: compared against an empty week
+                let mut t = DailyTrend::new("2026-02-24".to_string());
+                t.add_session(10, 1000, 1.00, 0.80);
+                t
+            },
+        ];
+
+        let weekly = aggregate_to_weekly(daily);
+
+        assert!(weekly[0].comparison.is_none());
+        let second = weekly[1].comparison.as_ref().unwrap();
+        assert!((second.cost_change_percent - 50.0).abs() < 0.001);
+        assert!((second.tokens_change_percent - 25.0).abs() < 0.001);
+        assert_eq!(second.sessions_change_percent, 0.0);
+        let third = weekly[2].comparison.as_ref().unwrap();
+        assert_eq!(third.cost_change_percent, 100.0);
+    }
+
     #[test]
     fn test_get_week_start_end() {
         assert_eq!(get_week_start("2026-02-05"), Some("2026-02-02".to_string()));
@@ -191,6 +252,7 @@ mod tests {
             total_tokens: 25000,
             total_cost: 10.0,
             avg_efficiency: 0.85,
+            comparison: None,
             daily: vec![DailyTrend::new("2026-02-02".to_string())],
         };
 
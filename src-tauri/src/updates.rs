@@ -0,0 +1,173 @@
+//! Update checks
+//!
+//! Releases are read from the GitHub releases of the repository the app is
+//! built from. The stable channel only offers full releases; beta also
+//! offers pre-releases, whichever of the two is newer. Versions are compared
+//! as semver, so a beta user on `0.6.0-beta.2` is offered `0.6.0` once it
+//! ships. Installing the update is left to the frontend.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/barkain/ironhide/releases?per_page=30";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Which releases are offered as updates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Pre-releases as well as full releases
+    Beta,
+}
+
+/// A release as listed by the GitHub API
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitHubRelease {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    pub html_url: String,
+    pub published_at: Option<String>,
+}
+
+impl GitHubRelease {
+    /// Version of the release, from its tag (`v1.2.3` or `1.2.3`)
+    fn version(&self) -> Option<semver::Version> {
+        parse_version(&self.tag_name)
+    }
+}
+
+/// Result of an update check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "bindings", derive(schemars::JsonSchema))]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub update_available: bool,
+    /// Newest release on the channel, whether or not it's newer than the
+    /// running version; None if the channel has no releases
+    pub latest_version: Option<String>,
+    pub prerelease: bool,
+    /// Release title and notes (Markdown)
+    pub release_name: Option<String>,
+    pub release_notes: Option<String>,
+    pub release_url: Option<String>,
+    pub published_at: Option<String>,
+}
+
+/// Newest release offered on `channel`; drafts and unversioned tags are
+/// skipped
+pub fn latest_release(releases: &[GitHubRelease], channel: UpdateChannel) -> Option<&GitHubRelease> {
+    releases
+        .iter()
+        .filter(|r| !r.draft && (channel == UpdateChannel::Beta || !r.prerelease))
+        .filter_map(|r| r.version().map(|v| (v, r)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, r)| r)
+}
+
+/// Compare the running version against the releases on `channel`
+pub fn update_info(current_version: &str, channel: UpdateChannel, releases: &[GitHubRelease]) -> UpdateInfo {
+    let latest = latest_release(releases, channel);
+    let update_available = match (latest.and_then(GitHubRelease::version), parse_version(current_version)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    };
+
+    UpdateInfo {
+        current_version: current_version.to_string(),
+        channel,
+        update_available,
+        latest_version: latest.and_then(|r| r.version()).map(|v| v.to_string()),
+        prerelease: latest.is_some_and(|r| r.prerelease),
+        release_name: latest.and_then(|r| r.name.clone()).filter(|n| !n.is_empty()),
+        release_notes: latest.and_then(|r| r.body.clone()).filter(|b| !b.is_empty()),
+        release_url: latest.map(|r| r.html_url.clone()),
+        published_at: latest.and_then(|r| r.published_at.clone()),
+    }
+}
+
+/// Fetch the published releases
+pub async fn fetch_releases() -> Result<Vec<GitHubRelease>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let resp = client
+        .get(RELEASES_URL)
+        .header("User-Agent", "ironhide")
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("GitHub releases API error: {}", e))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("GitHub releases API returned {}", resp.status()));
+    }
+
+    resp.json().await.map_err(|e| format!("JSON parse error: {}", e))
+}
+
+fn parse_version(tag: &str) -> Option<semver::Version> {
+    let tag = tag.trim();
+    semver::Version::parse(tag.strip_prefix('v').unwrap_or(tag)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> GitHubRelease {
+        GitHubRelease {
+            tag_name: tag.to_string(),
+            name: Some(format!("Ironhide {}", tag)),
+            body: Some("- Faster scans".to_string()),
+            draft: false,
+            prerelease,
+            html_url: format!("https://github.com/barkain/ironhide/releases/tag/{}", tag),
+            published_at: Some("2026-10-01T12:00:00Z".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_update_info() {
+        let mut releases = vec![
+            release("v0.5.0", false),
+            release("v0.6.0-beta.1", true),
+            release("v0.5.1", false),
+            release("nightly", true),
+            release("v0.7.0", false),
+        ];
+        releases[4].draft = true;
+
+        let stable = update_info("0.5.0", UpdateChannel::Stable, &releases);
+        assert!(stable.update_available);
+        assert_eq!(stable.latest_version.as_deref(), Some("0.5.1"));
+        assert_eq!(stable.release_notes.as_deref(), Some("- Faster scans"));
+        assert!(!stable.prerelease);
+
+        let beta = update_info("0.5.0", UpdateChannel::Beta, &releases);
+        assert_eq!(beta.latest_version.as_deref(), Some("0.6.0-beta.1"));
+        assert!(beta.prerelease);
+
+        // A full release supersedes its pre-releases
+        releases.push(release("v0.6.0", false));
+        let beta = update_info("0.6.0-beta.1", UpdateChannel::Beta, &releases);
+        assert!(beta.update_available);
+        assert_eq!(beta.latest_version.as_deref(), Some("0.6.0"));
+
+        assert!(!update_info("0.6.0", UpdateChannel::Stable, &releases).update_available);
+        let none = update_info("0.6.0", UpdateChannel::Stable, &[]);
+        assert!(!none.update_available);
+        assert_eq!(none.latest_version, None);
+    }
+}
@@ -75,11 +75,9 @@ fn handle_remove(path: &Path) -> Option<WatchEvent> {
     })
 }
 
-/// Check if a path is a JSONL file
+/// Check if a path is a JSONL file, plain or gzipped
 fn is_jsonl_file(path: &Path) -> bool {
-    path.extension()
-        .map(|ext| ext == "jsonl")
-        .unwrap_or(false)
+    crate::parser::archive::is_session_file(path)
 }
 
 /// Check if a path is a subagent log
@@ -119,7 +117,9 @@ mod tests {
     #[test]
     fn test_is_jsonl_file() {
         assert!(is_jsonl_file(Path::new("/path/to/session.jsonl")));
+        assert!(is_jsonl_file(Path::new("/path/to/session.jsonl.gz")));
         assert!(!is_jsonl_file(Path::new("/path/to/session.json")));
+        assert!(!is_jsonl_file(Path::new("/path/to/session.gz")));
         assert!(!is_jsonl_file(Path::new("/path/to/session.txt")));
     }
 
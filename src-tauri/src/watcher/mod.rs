@@ -4,8 +4,14 @@
 //! - New session files
 //! - Updates to existing sessions
 //! - Subagent creation
+//! - Live tailing of appended turns
+//! - Batching changed files for database sync
+//! - Polling with adaptive intervals when notifications aren't available
 
 pub mod handler;
+pub mod live;
+pub mod poll;
+pub mod sync;
 
 use std::path::PathBuf;
 use std::sync::mpsc::{channel, Receiver};
@@ -56,6 +62,8 @@ pub enum WatchEvent {
 pub struct SessionWatcher {
     watcher: RecommendedWatcher,
     sessions_path: PathBuf,
+    /// Projects directories of other Claude directories watched as well
+    extra_paths: Vec<PathBuf>,
     rx: Receiver<Result<Event, notify::Error>>,
 }
 
@@ -74,6 +82,7 @@ impl SessionWatcher {
         Ok(Self {
             watcher,
             sessions_path,
+            extra_paths: Vec::new(),
             rx,
         })
     }
@@ -91,9 +100,28 @@ impl SessionWatcher {
         Ok(())
     }
 
+    /// Watch another sessions directory through the same event stream
+    pub fn watch_also(&mut self, path: PathBuf) -> Result<(), WatcherError> {
+        if !path.exists() {
+            return Err(WatcherError::PathNotFound(path));
+        }
+        if path == self.sessions_path || self.extra_paths.contains(&path) {
+            return Ok(());
+        }
+
+        self.watcher.watch(&path, RecursiveMode::Recursive)?;
+        tracing::info!("Started watching: {:?}", path);
+        self.extra_paths.push(path);
+
+        Ok(())
+    }
+
     /// Stop watching
     pub fn stop(&mut self) -> Result<(), WatcherError> {
         self.watcher.unwatch(&self.sessions_path)?;
+        for path in self.extra_paths.drain(..) {
+            self.watcher.unwatch(&path)?;
+        }
         Ok(())
     }
 
@@ -120,9 +148,9 @@ impl SessionWatcher {
 
 /// Get the default Claude sessions path
 pub fn default_sessions_path() -> Option<PathBuf> {
-    // Try ~/.claude/projects first
-    if let Some(home) = dirs::home_dir() {
-        let claude_path = home.join(".claude").join("projects");
+    // Try $CLAUDE_CONFIG_DIR or ~/.claude first
+    if let Some(claude_dir) = crate::parser::scan::default_claude_dir() {
+        let claude_path = claude_dir.join("projects");
         if claude_path.exists() {
             return Some(claude_path);
         }
@@ -0,0 +1,135 @@
+//! Polling fallback
+//!
+//! When file system notifications aren't available, session files are found
+//! by rescanning. Comparing each scan with the previous one turns changed
+//! sizes and modification times into the same watch events the notify
+//! watcher produces, and the time of the last change decides how soon the
+//! next scan runs.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::WatchEvent;
+use crate::parser::SessionFileInfo;
+use crate::settings::PollingConfig;
+
+/// Session files as of the last scan
+#[derive(Debug, Default)]
+pub struct SessionPoller {
+    /// Path -> (size, modified)
+    files: HashMap<PathBuf, (u64, SystemTime)>,
+    last_change: Option<Instant>,
+}
+
+impl SessionPoller {
+    /// Start from `sessions` without reporting them as changed
+    pub fn new(sessions: &[SessionFileInfo]) -> Self {
+        Self {
+            files: sessions
+                .iter()
+                .map(|s| (s.path.clone(), (s.size, s.modified)))
+                .collect(),
+            last_change: None,
+        }
+    }
+
+    /// Compare a scan made at `now` with the previous one
+    pub fn poll(&mut self, sessions: &[SessionFileInfo], now: Instant) -> Vec<WatchEvent> {
+        let mut events = Vec::new();
+        let mut previous = std::mem::take(&mut self.files);
+
+        for session in sessions {
+            let current = (session.size, session.modified);
+            let event = match previous.remove(&session.path) {
+                Some(seen) if seen == current => None,
+                Some(_) => Some(WatchEvent::SessionUpdated {
+                    session_id: session.session_id.clone(),
+                    path: session.path.clone(),
+                }),
+                None if session.is_subagent => Some(WatchEvent::SubagentCreated {
+                    session_id: session
+                        .parent_session_id
+                        .clone()
+                        .unwrap_or_else(|| session.session_id.clone()),
+                    agent_id: session.session_id.clone(),
+                    path: session.path.clone(),
+                }),
+                None => Some(WatchEvent::NewSession {
+                    session_id: session.session_id.clone(),
+                    path: session.path.clone(),
+                }),
+            };
+            events.extend(event);
+            self.files.insert(session.path.clone(), current);
+        }
+        events.extend(previous.into_keys().map(|path| WatchEvent::FileDeleted { path }));
+
+        if !events.is_empty() {
+            self.last_change = Some(now);
+        }
+        events
+    }
+
+    /// Time until the next scan after one at `now`
+    pub fn interval(&self, config: &PollingConfig, now: Instant) -> Duration {
+        config.interval(self.last_change.map(|at| now.duration_since(at)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(path: &str, size: u64) -> SessionFileInfo {
+        SessionFileInfo {
+            path: PathBuf::from(path),
+            session_id: path.trim_end_matches(".jsonl").trim_start_matches("/p/").to_string(),
+            project_path: None,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(size),
+            size,
+            is_subagent: false,
+            source: None,
+            parent_session_id: None,
+        }
+    }
+
+    #[test]
+    fn test_poll_events_and_interval() {
+        let config = PollingConfig::default();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let mut poller = SessionPoller::new(&[session("/p/a.jsonl", 10), session("/p/b.jsonl", 10)]);
+        assert!(poller.poll(&[session("/p/a.jsonl", 10), session("/p/b.jsonl", 10)], at(0)).is_empty());
+        // Nothing has changed since polling started
+        assert_eq!(poller.interval(&config, at(0)), Duration::from_secs(60));
+
+        let events = poller.poll(&[session("/p/a.jsonl", 20), session("/p/c.jsonl", 5)], at(60));
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], WatchEvent::SessionUpdated { session_id, .. } if session_id == "a"));
+        assert!(matches!(&events[1], WatchEvent::NewSession { session_id, .. } if session_id == "c"));
+        assert!(matches!(&events[2], WatchEvent::FileDeleted { path } if path == &PathBuf::from("/p/b.jsonl")));
+
+        // Fast while the change is recent, slow again once the window has passed
+        assert_eq!(poller.interval(&config, at(65)), Duration::from_secs(5));
+        assert_eq!(poller.interval(&config, at(180)), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_polling_config() {
+        assert!(PollingConfig::default().is_valid());
+        let config = PollingConfig {
+            active_interval_secs: 90,
+            ..PollingConfig::default()
+        };
+        assert!(!config.is_valid(), "active interval longer than the idle one");
+        let config = PollingConfig {
+            active_interval_secs: 0,
+            idle_interval_secs: 0,
+            ..PollingConfig::default()
+        };
+        assert!(!config.is_valid());
+        assert_eq!(config.interval(None), Duration::from_secs(1));
+    }
+}
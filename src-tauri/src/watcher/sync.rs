@@ -0,0 +1,122 @@
+//! Database sync batching
+//!
+//! Collects the session files touched by watch events so they can be
+//! re-parsed and stored in batches. A file that is still being written keeps
+//! producing events; it is synced once it has been quiet for the debounce
+//! period, or after the maximum delay so an active session doesn't wait
+//! forever.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::WatchEvent;
+
+/// Quiet period after the last change before a file is synced
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Longest a changed file waits for its sync, however busy it is
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(15);
+
+/// Session files waiting to be synced
+#[derive(Debug)]
+pub struct SyncQueue {
+    /// Path -> (first change, last change) since the last sync
+    pending: HashMap<PathBuf, (Instant, Instant)>,
+    debounce: Duration,
+    max_delay: Duration,
+}
+
+impl SyncQueue {
+    pub fn new(debounce: Duration, max_delay: Duration) -> Self {
+        Self {
+            pending: HashMap::new(),
+            debounce,
+            max_delay,
+        }
+    }
+
+    /// Record a watch event seen at `now`
+    pub fn push(&mut self, event: &WatchEvent, now: Instant) {
+        match event {
+            WatchEvent::NewSession { path, .. }
+            | WatchEvent::SessionUpdated { path, .. }
+            | WatchEvent::SubagentCreated { path, .. } => {
+                self.pending
+                    .entry(path.clone())
+                    .and_modify(|(_, last)| *last = now)
+                    .or_insert((now, now));
+            }
+            WatchEvent::FileDeleted { path } => {
+                self.pending.remove(path);
+            }
+        }
+    }
+
+    /// Take the files that are due for a sync at `now`
+    pub fn take_ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let (debounce, max_delay) = (self.debounce, self.max_delay);
+        let ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, (first, last))| {
+                now.duration_since(*last) >= debounce || now.duration_since(*first) >= max_delay
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl Default for SyncQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEBOUNCE, DEFAULT_MAX_DELAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn updated(path: &str) -> WatchEvent {
+        WatchEvent::SessionUpdated {
+            session_id: "s".to_string(),
+            path: PathBuf::from(path),
+        }
+    }
+
+    #[test]
+    fn test_sync_queue_debounce() {
+        let mut queue = SyncQueue::new(Duration::from_secs(2), Duration::from_secs(10));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        queue.push(&updated("/p/a.jsonl"), at(0));
+        queue.push(&updated("/p/b.jsonl"), at(0));
+        queue.push(&updated("/p/a.jsonl"), at(1));
+        assert!(queue.take_ready(at(1)).is_empty());
+
+        // b has been quiet for 2s, a only for 1s
+        assert_eq!(queue.take_ready(at(2)), vec![PathBuf::from("/p/b.jsonl")]);
+        assert_eq!(queue.take_ready(at(3)), vec![PathBuf::from("/p/a.jsonl")]);
+        assert!(queue.is_empty());
+
+        // A file that keeps changing is synced after the maximum delay
+        for secs in 0..=10 {
+            queue.push(&updated("/p/c.jsonl"), at(10 + secs));
+        }
+        assert_eq!(queue.take_ready(at(20)), vec![PathBuf::from("/p/c.jsonl")]);
+
+        // Deleted files are dropped
+        queue.push(&updated("/p/d.jsonl"), at(30));
+        queue.push(&WatchEvent::FileDeleted { path: PathBuf::from("/p/d.jsonl") }, at(30));
+        assert!(queue.take_ready(at(40)).is_empty());
+    }
+}
@@ -17,6 +17,7 @@ const Trends = React.lazy(() => import('./pages/Trends'));
 const Compare = React.lazy(() => import('./pages/Compare'));
 const Timeline = React.lazy(() => import('./pages/Timeline'));
 const Performance = React.lazy(() => import('./pages/Performance'));
+const Team = React.lazy(() => import('./pages/Team'));
 const Settings = React.lazy(() => import('./pages/Settings'));
 
 const queryClient = new QueryClient({
@@ -78,6 +79,7 @@ function App() {
                 <Route path="performance" element={<Performance />} />
                 <Route path="compare" element={<Compare />} />
                 <Route path="compare/:sessionIds" element={<Compare />} />
+                <Route path="team" element={<Team />} />
                 <Route path="settings" element={<Settings />} />
               </Route>
             </Routes>
@@ -1,12 +1,13 @@
 import { useState, useRef, useEffect, useCallback } from 'react';
 import { Button } from '../ui/Button';
 import { ExportDialog } from './ExportDialog';
-import { exportSessions, exportTrends, type ExportOptions } from '../../lib/tauri';
+import { exportSessions, exportTrends, type ExportFormat, type ExportOptions } from '../../lib/tauri';
 import { cn } from '../../lib/utils';
 import {
   Download,
   ChevronDown,
   FileJson,
+  FileText,
   FileSpreadsheet,
   Settings,
   Check,
@@ -71,7 +72,7 @@ export function ExportButton({
     setNotification({ show: true, success, message });
   }, []);
 
-  const quickExport = useCallback(async (format: 'csv' | 'json') => {
+  const quickExport = useCallback(async (format: ExportFormat) => {
     setIsOpen(false);
     setIsExporting(true);
 
@@ -154,6 +155,13 @@ export function ExportButton({
               <FileJson className="h-4 w-4 text-gray-400" />
               Export as JSON
             </button>
+            <button
+              onClick={() => quickExport('markdown')}
+              className="flex w-full items-center gap-3 px-4 py-2 text-left text-sm text-gray-300 hover:bg-gray-800 hover:text-white transition-colors"
+            >
+              <FileText className="h-4 w-4 text-gray-400" />
+              Export as Markdown
+            </button>
             <div className="my-1 border-t border-gray-700" />
             <button
               onClick={handleOpenDialog}
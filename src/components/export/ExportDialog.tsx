@@ -1,13 +1,16 @@
 import { useState, useCallback } from 'react';
 import { Button } from '../ui/Button';
 import { Card, CardHeader, CardTitle, CardContent } from '../ui/Card';
-import { exportSessions, exportTrends, type ExportOptions } from '../../lib/tauri';
+import { exportSessions, exportTrends, type ExportFormat, type ExportOptions } from '../../lib/tauri';
 import { cn } from '../../lib/utils';
 import {
   X,
   Download,
   FileJson,
   FileSpreadsheet,
+  FileText,
+  Database,
+  Table,
   Check,
   AlertCircle,
   Calendar,
@@ -29,7 +32,7 @@ export function ExportDialog({
   mode = 'sessions',
   defaultDays = 30,
 }: ExportDialogProps) {
-  const [format, setFormat] = useState<'csv' | 'json'>('csv');
+  const [format, setFormat] = useState<ExportFormat>('csv');
   const [includeTurns, setIncludeTurns] = useState(false);
   const [includeMetrics, setIncludeMetrics] = useState(true);
   const [useDateRange, setUseDateRange] = useState(false);
@@ -142,7 +145,7 @@ export function ExportDialog({
             <label className="text-sm font-medium text-gray-300">
               Export Format
             </label>
-            <div className="grid grid-cols-2 gap-3">
+            <div className="grid grid-cols-3 gap-3">
               <button
                 onClick={() => setFormat('csv')}
                 className={cn(
@@ -185,6 +188,90 @@ export function ExportDialog({
                   <p className="text-xs text-gray-500">Structured format</p>
                 </div>
               </button>
+              <button
+                onClick={() => setFormat('markdown')}
+                className={cn(
+                  'flex items-center gap-3 rounded-lg border p-4 transition-all',
+                  format === 'markdown'
+                    ? 'border-[var(--color-primary-500)] bg-[var(--color-primary-600)]/10'
+                    : 'border-gray-700 hover:border-gray-600'
+                )}
+              >
+                <FileText className={cn(
+                  'h-5 w-5',
+                  format === 'markdown' ? 'text-[var(--color-primary-400)]' : 'text-gray-400'
+                )} />
+                <div className="text-left">
+                  <p className={cn(
+                    'font-medium',
+                    format === 'markdown' ? 'text-white' : 'text-gray-300'
+                  )}>Markdown</p>
+                  <p className="text-xs text-gray-500">Readable report</p>
+                </div>
+              </button>
+              <button
+                onClick={() => setFormat('sqlite')}
+                className={cn(
+                  'flex items-center gap-3 rounded-lg border p-4 transition-all',
+                  format === 'sqlite'
+                    ? 'border-[var(--color-primary-500)] bg-[var(--color-primary-600)]/10'
+                    : 'border-gray-700 hover:border-gray-600'
+                )}
+              >
+                <Database className={cn(
+                  'h-5 w-5',
+                  format === 'sqlite' ? 'text-[var(--color-primary-400)]' : 'text-gray-400'
+                )} />
+                <div className="text-left">
+                  <p className={cn(
+                    'font-medium',
+                    format === 'sqlite' ? 'text-white' : 'text-gray-300'
+                  )}>SQLite</p>
+                  <p className="text-xs text-gray-500">Query with SQL</p>
+                </div>
+              </button>
+              <button
+                onClick={() => setFormat('parquet')}
+                className={cn(
+                  'flex items-center gap-3 rounded-lg border p-4 transition-all',
+                  format === 'parquet'
+                    ? 'border-[var(--color-primary-500)] bg-[var(--color-primary-600)]/10'
+                    : 'border-gray-700 hover:border-gray-600'
+                )}
+              >
+                <Table className={cn(
+                  'h-5 w-5',
+                  format === 'parquet' ? 'text-[var(--color-primary-400)]' : 'text-gray-400'
+                )} />
+                <div className="text-left">
+                  <p className={cn(
+                    'font-medium',
+                    format === 'parquet' ? 'text-white' : 'text-gray-300'
+                  )}>Parquet</p>
+                  <p className="text-xs text-gray-500">For DuckDB and pandas</p>
+                </div>
+              </button>
+              <button
+                onClick={() => setFormat('ical')}
+                className={cn(
+                  'flex items-center gap-3 rounded-lg border p-4 transition-all',
+                  format === 'ical'
+                    ? 'border-[var(--color-primary-500)] bg-[var(--color-primary-600)]/10'
+                    : 'border-gray-700 hover:border-gray-600'
+                )}
+              >
+                <Calendar className={cn(
+                  'h-5 w-5',
+                  format === 'ical' ? 'text-[var(--color-primary-400)]' : 'text-gray-400'
+                )} />
+                <div className="text-left">
+                  <p className={cn(
+                    'font-medium',
+                    format === 'ical' ? 'text-white' : 'text-gray-300'
+                  )}>iCalendar</p>
+                  <p className="text-xs text-gray-500">Overlay on your calendar</p>
+                </div>
+              </button>
             </div>
           </div>
 
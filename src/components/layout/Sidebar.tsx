@@ -10,6 +10,7 @@ import {
   TrendingUp,
   CalendarRange,
   Radar,
+  Users,
 } from 'lucide-react';
 import { cn } from '../../lib/utils';
 import { useAppStore } from '../../lib/store';
@@ -38,6 +39,7 @@ export function Sidebar() {
       label: 'Compare',
       badge: selectedForComparison.length > 0 ? selectedForComparison.length : undefined,
     },
+    { to: '/team', icon: Users, label: 'Team' },
     { to: '/settings', icon: Settings, label: 'Settings' },
   ];
 
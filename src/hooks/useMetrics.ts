@@ -5,6 +5,14 @@ import {
   getProjectMetrics,
   getDeveloperMetrics,
   refreshData,
+  getTeamMembers,
+  getTeamDashboard,
+  getTeamTrends,
+  importTeamExport,
+  removeTeamMember,
+  type TeamMember,
+  type TeamDashboard,
+  type TeamDailyTrend,
 } from '../lib/tauri';
 import { useAppStore, type PresetRange } from '../lib/store';
 import type {
@@ -183,3 +191,69 @@ export function useLastSyncTime() {
     refetchInterval: 60000,
   });
 }
+
+// ============================================================================
+// Team Roll-up
+// ============================================================================
+
+/** Fetch the teammates with imported sessions */
+export function useTeamMembers() {
+  return useQuery<TeamMember[]>({
+    queryKey: ['teamMembers'],
+    queryFn: getTeamMembers,
+    staleTime: METRICS_STALE_TIME,
+    gcTime: METRICS_GC_TIME,
+  });
+}
+
+/** Fetch team totals per teammate for the selected range */
+export function useTeamDashboard() {
+  const presetRange = useAppStore((state) => state.presetRange);
+  const days = presetToDays(presetRange);
+
+  return useQuery<TeamDashboard>({
+    queryKey: ['teamDashboard', days],
+    queryFn: () => getTeamDashboard(days),
+    staleTime: METRICS_STALE_TIME,
+    gcTime: METRICS_GC_TIME,
+    placeholderData: keepPreviousData,
+  });
+}
+
+/** Fetch daily team usage per teammate for the selected range */
+export function useTeamTrends() {
+  const presetRange = useAppStore((state) => state.presetRange);
+  const days = presetToDays(presetRange) ?? 365;
+
+  return useQuery<TeamDailyTrend[]>({
+    queryKey: ['teamTrends', days],
+    queryFn: () => getTeamTrends(days),
+    staleTime: METRICS_STALE_TIME,
+    gcTime: METRICS_GC_TIME,
+    placeholderData: keepPreviousData,
+  });
+}
+
+function invalidateTeamQueries(queryClient: ReturnType<typeof useQueryClient>) {
+  queryClient.invalidateQueries({ queryKey: ['teamMembers'] });
+  queryClient.invalidateQueries({ queryKey: ['teamDashboard'] });
+  queryClient.invalidateQueries({ queryKey: ['teamTrends'] });
+}
+
+/** Import a teammate's session export, then refresh the team views */
+export function useImportTeamExport() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: ({ path, userName }: { path: string; userName: string }) => importTeamExport(path, userName),
+    onSuccess: () => invalidateTeamQueries(queryClient),
+  });
+}
+
+/** Remove a teammate from the roll-up, then refresh the team views */
+export function useRemoveTeamMember() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: removeTeamMember,
+    onSuccess: () => invalidateTeamQueries(queryClient),
+  });
+}
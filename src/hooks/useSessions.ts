@@ -1,4 +1,4 @@
-import { useQuery, useQueryClient } from '@tanstack/react-query';
+import { useQuery, useQueryClient, useMutation } from '@tanstack/react-query';
 import { useEffect, useCallback } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import {
@@ -11,9 +11,10 @@ import {
   getSessionsFiltered,
   getSessionsByProject,
   preloadAllSessions,
+  recomputeSession,
 } from '../lib/tauri';
 import { useAppStore } from '../lib/store';
-import type { SessionSummary, SessionDetail, SessionMetrics, TurnSummary, DateRange } from '../types';
+import type { SessionSummary, SessionDetail, SessionMetrics, TurnSummary, DateRange, TurnAppendedEvent, PreloadProgressEvent } from '../types';
 
 // ============================================================================
 // Cache Constants
@@ -161,6 +162,24 @@ export function useInvalidateSessions() {
   };
 }
 
+/** Mutation hook re-parsing one session, e.g. after a pricing change */
+export function useRecomputeSession() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: recomputeSession,
+    onSuccess: (detail, sessionId) => {
+      queryClient.setQueryData(['session', sessionId], detail);
+      queryClient.invalidateQueries({ queryKey: ['session-metrics', sessionId] });
+      queryClient.invalidateQueries({ queryKey: ['turns', sessionId] });
+      queryClient.invalidateQueries({ queryKey: ['sessions'] });
+      queryClient.invalidateQueries({ queryKey: ['dashboardSummary'] });
+      queryClient.invalidateQueries({ queryKey: ['dailyMetrics'] });
+      queryClient.invalidateQueries({ queryKey: ['projectMetrics'] });
+    },
+  });
+}
+
 // ============================================================================
 // Real-time Updates Hook
 // ============================================================================
@@ -180,8 +199,44 @@ export function useSessionUpdates() {
       queryClient.invalidateQueries({ queryKey: ['projectMetrics'] });
     });
 
+    // Listen for live turn updates streamed as session files grow
+    const unlistenTurnsPromise = listen<TurnAppendedEvent>('turn-appended', (event) => {
+      const sessionId = event.payload.session_id;
+      queryClient.invalidateQueries({ queryKey: ['session', sessionId] });
+      queryClient.invalidateQueries({ queryKey: ['session-metrics', sessionId] });
+      queryClient.invalidateQueries({ queryKey: ['turns', sessionId] });
+    });
+
+    // Listen for sessions re-parsed and stored after their files changed
+    const unlistenSessionPromise = listen<SessionSummary>('session-updated', (event) => {
+      const sessionId = event.payload.id;
+      queryClient.invalidateQueries({ queryKey: ['sessions'] });
+      queryClient.invalidateQueries({ queryKey: ['session', sessionId] });
+      queryClient.invalidateQueries({ queryKey: ['session-metrics', sessionId] });
+    });
+
+    // Aggregates change once per synced batch
+    const unlistenMetricsPromise = listen<string[]>('metrics-updated', () => {
+      queryClient.invalidateQueries({ queryKey: ['dashboardSummary'] });
+      queryClient.invalidateQueries({ queryKey: ['dailyMetrics'] });
+      queryClient.invalidateQueries({ queryKey: ['projectMetrics'] });
+    });
+
+    // Dashboard totals stay on one snapshot while a preload phase runs; the
+    // fresh totals are published when it finishes
+    const unlistenPreloadPromise = listen<PreloadProgressEvent>('preload-progress', (event) => {
+      if (!event.payload.done) return;
+      queryClient.invalidateQueries({ queryKey: ['dashboardSummary'] });
+      queryClient.invalidateQueries({ queryKey: ['dailyMetrics'] });
+      queryClient.invalidateQueries({ queryKey: ['projectMetrics'] });
+    });
+
     return () => {
       unlistenPromise.then(unlisten => unlisten());
+      unlistenTurnsPromise.then(unlisten => unlisten());
+      unlistenSessionPromise.then(unlisten => unlisten());
+      unlistenMetricsPromise.then(unlisten => unlisten());
+      unlistenPreloadPromise.then(unlisten => unlisten());
     };
   }, [queryClient]);
 }
@@ -4,10 +4,12 @@ import type {
   SessionDetail,
   SessionMetrics,
   TurnSummary,
+  SubagentNode,
   DashboardSummary,
   DailyMetrics,
   ProjectMetrics,
   DeveloperPerformanceMetrics,
+  DeliverableEstimator,
 } from '../types';
 
 // ============================================================================
@@ -34,148 +36,1651 @@ export async function getTurns(sessionId: string, limit = 100, offset = 0): Prom
   return invoke('get_turns', { sessionId, limit, offset });
 }
 
+/** One turn on a session's cost curve (matches Rust CostCurvePoint) */
+export interface CostCurvePoint {
+  turn_number: number;
+  started_at: string;
+  model: string | null;
+  cost: number;
+  tokens: number;
+  /** Session cost up to and including this turn */
+  cumulative_cost: number;
+  cumulative_tokens: number;
+}
+
+/** Get cumulative cost and tokens per turn of a session */
+export async function getSessionCostCurve(sessionId: string): Promise<CostCurvePoint[]> {
+  return invoke('get_session_cost_curve', { sessionId });
+}
+
+/** A user's verdict on a turn (matches Rust AnnotationLabel) */
+export type AnnotationLabel = 'wasted' | 'valuable';
+
+/** Label and note attached to a turn (matches Rust TurnAnnotation) */
+export interface TurnAnnotation {
+  session_id: string;
+  turn_number: number;
+  label: AnnotationLabel;
+  note: string | null;
+  annotated_at: string;
+  /** Cost of the turn; 0 while the turn isn't stored in the database */
+  cost: number;
+}
+
+/** Mark a turn as wasted or valuable, replacing its earlier annotation */
+export async function annotateTurn(
+  sessionId: string,
+  turnNumber: number,
+  label: AnnotationLabel,
+  note?: string
+): Promise<TurnAnnotation> {
+  return invoke('annotate_turn', { sessionId, turnNumber, label, note });
+}
+
+/** Remove a turn's annotation; returns whether it had one */
+export async function removeTurnAnnotation(sessionId: string, turnNumber: number): Promise<boolean> {
+  return invoke('remove_turn_annotation', { sessionId, turnNumber });
+}
+
+/** Get turn annotations of a session (or all sessions), optionally with one label */
+export async function getTurnAnnotations(sessionId?: string, label?: AnnotationLabel): Promise<TurnAnnotation[]> {
+  return invoke('get_turn_annotations', { sessionId, label });
+}
+
+/** Entry of a session file the parser skipped or only partly understood */
+export interface UnparsedEntry {
+  /** Byte offset of the line in the session file */
+  offset: number;
+  kind: 'malformed' | 'unknown_type' | 'unknown_content';
+  /** Entry `type`, with the subtype for system entries; null for malformed lines */
+  entry_type: string | null;
+  timestamp: string | null;
+  error: string | null;
+  /** The entry as written, or the line text if it isn't JSON */
+  raw: unknown;
+}
+
+export interface ParseDiagnostics {
+  total_lines: number;
+  malformed_lines: number;
+  unknown_entries: number;
+  /** Summaries, file history snapshots and system events other than compactions */
+  ignored_entries: number;
+  unknown_content_blocks: number;
+  /** Unknown and ignored entries by type */
+  entry_types: Record<string, number>;
+  /** Up to 100 malformed, unknown and partly understood entries */
+  entries: UnparsedEntry[];
+}
+
+/** List the entries of a session the parser skipped or didn't fully understand */
+export async function getParseDiagnostics(sessionId: string): Promise<ParseDiagnostics> {
+  return invoke('get_parse_diagnostics', { sessionId });
+}
+
+/** A line of a session file as written (matches Rust RawEntry) */
+export interface RawEntry {
+  /** Position among the file's non-empty lines, from 0 */
+  index: number;
+  /** Byte offset of the line in the session file */
+  offset: number;
+  /** How the parser classified the entry; null for malformed lines */
+  entry_type: 'user' | 'assistant' | 'progress' | 'summary' | 'file-history-snapshot' | 'system' | 'unknown' | null;
+  /** Entry `type`, with the subtype for system entries */
+  type_label: string | null;
+  uuid: string | null;
+  parent_uuid: string | null;
+  timestamp: string | null;
+  error: string | null;
+  /** The entry as written, or the line text if it isn't JSON */
+  raw: unknown;
+}
+
+export interface RawEntryPage {
+  /** Non-empty lines in the file */
+  total: number;
+  entries: RawEntry[];
+}
+
+/** Page through a session file's entries as written, for debugging (limit defaults to 100, at most 1000) */
+export async function getSessionRawEntries(sessionId: string, offset = 0, limit = 100): Promise<RawEntryPage> {
+  return invoke('get_session_raw_entries', { sessionId, offset, limit });
+}
+
+/** Context usage of one turn */
+export interface ContextPoint {
+  turn_number: number;
+  started_at: string;
+  model: string | null;
+  /** Largest input + cache read tokens of a single request in the turn */
+  context_tokens: number;
+  context_limit: number;
+  utilization_pct: number;
+}
+
+export interface CompactionEvent {
+  /** First turn to run on the compacted context */
+  turn_number: number;
+  timestamp: string | null;
+  /** 'auto' or 'manual'; null when inferred */
+  trigger: string | null;
+  pre_tokens: number | null;
+  /** Detected from a drop in context usage rather than recorded by Claude Code */
+  inferred: boolean;
+}
+
+export interface ContextTimeline {
+  session_id: string;
+  points: ContextPoint[];
+  compactions: CompactionEvent[];
+  peak_utilization_pct: number;
+}
+
+/** Get a session's context window usage per turn, with its compactions */
+export async function getContextTimeline(sessionId: string): Promise<ContextTimeline> {
+  return invoke('get_context_timeline', { sessionId });
+}
+
+/** What a transcript includes and how its text is shortened; omitted fields use the defaults */
+export interface TranscriptOptions {
+  /** Replace API keys, tokens and private keys with [REDACTED] (default true) */
+  redact_secrets?: boolean;
+  max_message_chars?: number | null;
+  /** Default 4000 */
+  max_tool_result_chars?: number | null;
+  /** Default true */
+  include_tool_results?: boolean;
+  include_thinking?: boolean;
+  include_subagents?: boolean;
+  offset?: number;
+  limit?: number | null;
+}
+
+export type TranscriptRole = 'user' | 'assistant' | 'thinking' | 'tool_use' | 'tool_result';
+
+/** One message of a session transcript */
+export interface TranscriptMessage {
+  /** Position in the full transcript */
+  index: number;
+  role: TranscriptRole;
+  uuid: string | null;
+  timestamp: string | null;
+  text: string | null;
+  tool_use_id: string | null;
+  tool_name: string | null;
+  tool_input: unknown;
+  is_error: boolean;
+  truncated: boolean;
+  model: string | null;
+  is_sidechain: boolean;
+}
+
+/** A session's conversation, or one page of it */
+export interface Transcript {
+  session_id: string;
+  messages: TranscriptMessage[];
+  total_messages: number;
+  redactions: number;
+}
+
+/** Get a session's conversation as ordered user, assistant and tool messages */
+export async function getSessionTranscript(
+  sessionId: string,
+  options?: TranscriptOptions
+): Promise<Transcript> {
+  return invoke('get_session_transcript', { sessionId, options });
+}
+
+/** Change history of a file edited more than once */
+export interface FileChurn {
+  file_path: string;
+  edits: number;
+  /** Turns that changed the file, in order */
+  turns: number[];
+  /** Edits made after the turn that first changed the file */
+  rework_edits: number;
+  churn_score: number;
+}
+
+export interface ReworkAnalysis {
+  total_edits: number;
+  rework_edits: number;
+  /** Turns that changed a file already changed in an earlier turn */
+  rework_cycles: number;
+  /** Highest churn first */
+  files: FileChurn[];
+}
+
+/** Get the files a session changed repeatedly, with churn scores */
+export async function getSessionRework(sessionId: string): Promise<ReworkAnalysis> {
+  return invoke('get_session_rework', { sessionId });
+}
+
+/** How often one file of a project was changed (matches Rust FileChangeSummary) */
+export interface FileChangeSummary {
+  file_path: string;
+  /** Write, Edit and NotebookEdit operations */
+  changes: number;
+  sessions: number;
+  last_changed_at: string;
+}
+
+/** Get the files of a project its sessions changed, most changed first */
+export async function getProjectFileChanges(projectPath: string): Promise<FileChangeSummary[]> {
+  return invoke('get_project_file_changes', { projectPath });
+}
+
+/** One change to a file (matches Rust FileChangeEntry) */
+export interface FileChangeEntry {
+  session_id: string;
+  project_path: string;
+  project_name: string;
+  branch: string | null;
+  /** Import source or team label; null for this machine */
+  source: string | null;
+  turn_number: number;
+  timestamp: string;
+  tool_name: string | null;
+  change_type: 'create' | 'edit' | 'delete';
+}
+
+/** Every stored change to a file across sessions (matches Rust FileHistory) */
+export interface FileHistory {
+  file_path: string;
+  total_changes: number;
+  creates: number;
+  edits: number;
+  deletes: number;
+  sessions: number;
+  turns: number;
+  first_changed_at: string | null;
+  last_changed_at: string | null;
+  /** Cost of the turns that changed the file, split between the files each changed */
+  estimated_cost: number;
+  /** Newest first */
+  changes: FileChangeEntry[];
+}
+
+/** Get every change made to a file across sessions */
+export async function getFileHistory(filePath: string): Promise<FileHistory> {
+  return invoke('get_file_history', { filePath });
+}
+
+/** A tool call that came back as an error */
+export interface ToolFailure {
+  turn_number: number;
+  timestamp: string;
+  tool_use_id: string;
+  tool_name: string;
+  /** The command, file path, pattern or URL the call was about */
+  target: string | null;
+  input: unknown;
+  error: string | null;
+}
+
+/** Get the tool calls of a session that failed */
+export async function getToolFailures(sessionId: string): Promise<ToolFailure[]> {
+  return invoke('get_tool_failures', { sessionId });
+}
+
+/** Cache usage of one turn; ratios are shares of its input side (0-1) */
+export interface CacheTurn {
+  turn_number: number;
+  started_at: string;
+  fresh_input_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  fresh_ratio: number;
+  cache_read_ratio: number;
+  cache_write_ratio: number;
+  /** Cache read / (read + write); null when the turn didn't touch the cache */
+  cer: number | null;
+}
+
+/** A turn whose cache efficiency fell sharply from the one before */
+export interface CacheDrop {
+  turn_number: number;
+  previous_cer: number;
+  cer: number;
+  cause: 'compaction' | 'model_switch' | 'cache_expired' | 'large_file_read' | 'unknown';
+  detail: string | null;
+}
+
+export interface CacheBreakdown {
+  session_id: string;
+  turns: CacheTurn[];
+  drops: CacheDrop[];
+  session_cer: number | null;
+}
+
+/** Get a session's per-turn cache usage and where its efficiency dropped */
+export async function getCacheBreakdown(sessionId: string): Promise<CacheBreakdown> {
+  return invoke('get_cache_breakdown', { sessionId });
+}
+
+/** One turn with cost and CER averaged over the window ending at it */
+export interface EfficiencyCurvePoint {
+  turn_number: number;
+  started_at: string;
+  context_tokens: number;
+  cost: number;
+  rolling_cost_per_turn: number;
+  /** null when no turn in the window used the cache */
+  rolling_cer: number | null;
+  /** Fewer than the window size at the start of the session */
+  window_turns: number;
+}
+
+export interface SessionEfficiencyCurve {
+  session_id: string;
+  window: number;
+  points: EfficiencyCurvePoint[];
+  /** Rolling cost per turn of the first full window */
+  baseline_cost_per_turn: number | null;
+  /** First turn whose rolling cost per turn reached twice the baseline */
+  degraded_at_turn: number | null;
+  cost_since_degraded: number;
+}
+
+/** Get a session's rolling cost per turn and CER, and where it degraded */
+export async function getSessionEfficiencyCurve(
+  sessionId: string,
+  window?: number
+): Promise<SessionEfficiencyCurve> {
+  return invoke('get_session_efficiency_curve', { sessionId, window });
+}
+
+/** One component of the health score */
+export interface HealthComponent {
+  name: 'efficiency' | 'antipatterns' | 'tool_failures' | 'rework';
+  label: string;
+  /** OES, anti-pattern count, tool failure rate or rework ratio */
+  value: number;
+  /** 0-100, higher is healthier */
+  score: number;
+  /** Share of the health score */
+  weight: number;
+}
+
+export interface SessionHealth {
+  session_id: string;
+  /** 0-100 */
+  score: number;
+  grade: string;
+  components: HealthComponent[];
+}
+
+/** Get a session's health score with its component breakdown */
+export async function getSessionHealth(sessionId: string): Promise<SessionHealth> {
+  return invoke('get_session_health', { sessionId });
+}
+
+export interface ActiveSession {
+  session_id: string;
+  project_path: string | null;
+  last_modified: string;
+  turn_count: number;
+  total_tokens: number;
+  total_cost: number;
+}
+
+/**
+ * Get the session currently being written to, or null if none changed in
+ * the last couple of minutes. Follow it with `active-session-cost` events.
+ */
+export async function getActiveSession(): Promise<ActiveSession | null> {
+  return invoke('get_active_session');
+}
+
+/** Get the subagent hierarchy of a session with cost/token rollups */
+export async function getSubagentTree(sessionId: string): Promise<SubagentNode> {
+  return invoke('get_subagent_tree', { sessionId });
+}
+
 /** Get total session count */
 export async function getSessionCount(): Promise<number> {
   return invoke('get_session_count');
 }
 
-/** Scan for new sessions (returns only newly discovered ones) */
-export async function scanNewSessions(knownIds: string[]): Promise<SessionSummary[]> {
-  return invoke('scan_new_sessions', { knownIds });
+/** Scan for new sessions (returns only newly discovered ones) */
+export async function scanNewSessions(knownIds: string[]): Promise<SessionSummary[]> {
+  return invoke('scan_new_sessions', { knownIds });
+}
+
+/** Preload all sessions into cache at startup - call this once when app starts */
+export async function preloadAllSessions(): Promise<number> {
+  return invoke('preload_all_sessions');
+}
+
+/** Stop a running preload; resolves to whether one was running */
+export async function cancelPreload(): Promise<boolean> {
+  return invoke('cancel_preload');
+}
+
+/** Outcome of warming up a project's sessions (matches Rust ProjectWarmupStats) */
+export interface ProjectWarmupStats {
+  project_path: string;
+  /** Session files of the project, subagents included */
+  sessions: number;
+  /** Sessions whose turns were already in memory */
+  already_cached: number;
+  /** Sessions loaded from the database or parsed */
+  loaded: number;
+  /** Sessions whose file couldn't be read */
+  failed: number;
+  elapsed_ms: number;
+}
+
+/**
+ * Load every session of a project into the caches so its views open
+ * without parsing; follow it with `project-warmup-progress` events
+ */
+export async function warmProjectCache(projectPath: string): Promise<ProjectWarmupStats> {
+  return invoke('warm_project_cache', { projectPath });
+}
+
+/**
+ * Re-parse sessions missing rows added by later schema changes, in the
+ * background. Resolves to the number of sessions queued; follow them with
+ * `backfill-progress` events.
+ */
+export async function backfillDatabase(): Promise<number> {
+  return invoke('backfill_database');
+}
+
+/** Stop a running backfill; resolves to whether one was running */
+export async function cancelBackfill(): Promise<boolean> {
+  return invoke('cancel_backfill');
+}
+
+export type JobKind = 'backfill' | 'preload_remaining' | 'export';
+
+export type JobState = 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled';
+
+/** A background job from the persistent queue */
+export interface Job {
+  id: number;
+  kind: JobKind;
+  /** Kind-specific arguments, as JSON */
+  payload: string | null;
+  state: JobState;
+  priority: number;
+  attempts: number;
+  max_attempts: number;
+  last_error: string | null;
+  created_at: string;
+  started_at: string | null;
+  finished_at: string | null;
+  /** Earliest time a retry may run */
+  run_after: string | null;
+}
+
+/**
+ * List background jobs, newest first: every queued or running job plus up to
+ * `limit` recent ones. Changes are announced with `job-updated` events.
+ */
+export async function listJobs(limit?: number): Promise<Job[]> {
+  return invoke('list_jobs', { limit });
+}
+
+/** Cancel a queued or running job; resolves to whether it was still active */
+export async function cancelJob(jobId: number): Promise<boolean> {
+  return invoke('cancel_job', { jobId });
+}
+
+/** Get all sessions for a specific project path (server-side filtering) */
+export async function getSessionsByProject(projectPath: string): Promise<SessionSummary[]> {
+  return invoke('get_sessions_by_project', { projectPath });
+}
+
+/** Get sessions filtered by date range (uses cached data for efficiency) */
+export async function getSessionsFiltered(
+  startDate?: string,
+  endDate?: string,
+  limit = 100,
+  offset = 0
+): Promise<SessionSummary[]> {
+  return invoke('get_sessions_filtered', {
+    startDate: startDate ?? null,
+    endDate: endDate ?? null,
+    limit,
+    offset,
+  });
+}
+
+// ============================================================================
+// Dashboard/Aggregate Commands
+// ============================================================================
+
+/** Get dashboard summary metrics (using efficient backend command) */
+export async function getDashboardSummary(days?: number): Promise<DashboardSummary> {
+  // Pass days to backend for time-range filtering (undefined = all time)
+  return invoke('get_dashboard_summary', { days: days ?? null });
+}
+
+/** Which sessions trend commands count (matches Rust Segment) */
+export type TrendSegment = 'all' | 'user_only' | 'subagent_only';
+
+/** Get daily metrics for charts (using efficient backend command), optionally of only user or subagent sessions */
+export async function getDailyMetrics(days?: number, segment?: TrendSegment): Promise<DailyMetrics[]> {
+  // Pass days to backend for time-range filtering (undefined = 30 days default)
+  return invoke('get_daily_metrics', { days: days ?? null, segment: segment ?? null });
+}
+
+/** Get project-level metrics, optionally only projects using a language or framework such as "Rust" */
+export async function getProjectMetrics(days?: number, stack?: string): Promise<ProjectMetrics[]> {
+  // Pass days to backend for time-range filtering (undefined = all time)
+  return invoke('get_project_metrics', { days: days ?? null, stack: stack ?? null });
+}
+
+/** Averages over a group of sessions */
+export interface BenchmarkStats {
+  session_count: number;
+  cost_per_turn: number;
+  cer: number;
+  oes: number;
+  turns_per_session: number;
+}
+
+export interface BenchmarkDeltas {
+  cost_per_turn: MetricDelta;
+  cer: MetricDelta;
+  oes: MetricDelta;
+  turns_per_session: MetricDelta;
+}
+
+export interface ProjectBenchmark {
+  project_path: string;
+  project_name: string;
+  stats: BenchmarkStats;
+  vs_baseline: BenchmarkDeltas;
+  /** Against all other projects combined (only with compareProjects) */
+  vs_peers: BenchmarkDeltas | null;
+  poor_cache_efficiency: boolean;
+}
+
+export interface ProjectBenchmarks {
+  baseline: BenchmarkStats;
+  /** Worst cache efficiency relative to the baseline first */
+  projects: ProjectBenchmark[];
+}
+
+/** Benchmark each project against all sessions, and optionally against the other projects */
+export async function getProjectBenchmarks(days?: number, compareProjects = false): Promise<ProjectBenchmarks> {
+  return invoke('get_project_benchmarks', { days: days ?? null, compareProjects });
+}
+
+export interface ProjectTimelineSession {
+  session_id: string;
+  started_at: string;
+  last_activity_at: string | null;
+  model: string | null;
+  total_turns: number;
+  duration_ms: number;
+  total_cost: number;
+  total_tokens: number;
+  /** Cache efficiency ratio (CER) */
+  efficiency_score: number | null;
+  /** Files changed with Write, Edit or NotebookEdit */
+  code_changes: number;
+  /** Idle time since the previous session ended; null for the first session */
+  gap_ms: number | null;
+}
+
+export interface ProjectTimeline {
+  project_path: string;
+  project_name: string;
+  /** Oldest first, subagent sessions excluded */
+  sessions: ProjectTimelineSession[];
+  total_cost: number;
+  total_code_changes: number;
+  longest_gap_ms: number;
+}
+
+/** Get a project's sessions in chronological order with the gaps between them */
+export async function getProjectTimeline(projectPath: string): Promise<ProjectTimeline> {
+  return invoke('get_project_timeline', { projectPath });
+}
+
+/** Usage grid indexed [dayOfWeek][hour]: Monday = 0, hours in local time */
+export interface HourlyUsage {
+  tokens: number[][];
+  cost: number[][];
+  sessions: number[][];
+  turns: number[][];
+  total_tokens: number;
+  total_cost: number;
+  total_turns: number;
+}
+
+/** Get usage by day of week and hour of day (dates are YYYY-MM-DD, omit for all time) */
+export async function getHourlyUsage(startDate?: string, endDate?: string): Promise<HourlyUsage> {
+  return invoke('get_hourly_usage', { startDate: startDate ?? null, endDate: endDate ?? null });
+}
+
+/** Usage within one rolling window ending now (matches Rust UsageWindow) */
+export interface UsageWindow {
+  /** e.g. "5h", "24h", "7d" */
+  label: string;
+  hours: number;
+  window_start: string;
+  tokens: number;
+  cost: number;
+  turns: number;
+  sessions: number;
+  token_threshold: number | null;
+  cost_threshold: number | null;
+  /** Percent of the threshold used, 0-100+ */
+  token_pct: number | null;
+  cost_pct: number | null;
+  exceeded: boolean;
+  /** When the oldest turn in the window rolls out of it */
+  next_rollover: string | null;
+}
+
+/** Payload of the `usage-window-warning` event, emitted as a window reaches a threshold */
+export interface UsageWindowWarning {
+  label: string;
+  hours: number;
+  metric: 'tokens' | 'cost';
+  value: number;
+  threshold: number;
+  next_rollover: string | null;
+}
+
+/** Get tokens and cost used within each configured rolling window (5h, 24h and 7d by default) */
+export async function getUsageWindows(): Promise<UsageWindow[]> {
+  return invoke('get_usage_windows');
+}
+
+/** Usage within one 5-hour billing block (matches Rust BillingBlock) */
+export interface BillingBlock {
+  /** On the hour */
+  start: string;
+  end: string;
+  last_activity: string;
+  is_active: boolean;
+  tokens: number;
+  cost: number;
+  turns: number;
+  sessions: number;
+}
+
+/** What the open block has left of the 5-hour window's thresholds (matches Rust BlockHeadroom) */
+export interface BlockHeadroom {
+  token_threshold: number | null;
+  cost_threshold: number | null;
+  token_pct: number | null;
+  cost_pct: number | null;
+  tokens_remaining: number | null;
+  cost_remaining: number | null;
+  minutes_remaining: number;
+  /** Usage by the end of the block at the pace so far */
+  projected_tokens: number;
+  projected_cost: number;
+}
+
+/** Billing blocks over a period (matches Rust BillingBlocks) */
+export interface BillingBlocks {
+  /** Oldest first */
+  blocks: BillingBlock[];
+  /** Set while the last block is open */
+  current: BlockHeadroom | null;
+}
+
+/** Get usage grouped into 5-hour billing blocks, as reported by ccusage */
+export async function getBillingBlocks(days?: number): Promise<BillingBlocks> {
+  return invoke('get_billing_blocks', { days: days ?? null });
+}
+
+/** Calls of one tool (matches Rust ToolCallCount) */
+export interface ToolCallCount {
+  tool: string;
+  calls: number;
+  /** Calls whose result was an error */
+  errors: number;
+}
+
+/** Calls of one MCP server's tools (matches Rust McpServerUsage) */
+export interface McpServerUsage {
+  server: string;
+  calls: number;
+  errors: number;
+  /** Tool names without the server prefix, most called first */
+  tools: ToolCallCount[];
+}
+
+/** Tool calls by tool and by MCP server (matches Rust ToolUsageStats) */
+export interface ToolUsageStats {
+  total_calls: number;
+  /** Calls of MCP tools, included in total_calls */
+  mcp_calls: number;
+  /** Most called first */
+  tools: ToolCallCount[];
+  /** Most called first */
+  mcp_servers: McpServerUsage[];
+}
+
+/** Get tool calls of the last `days` days by tool and by MCP server */
+export async function getToolUsage(days?: number): Promise<ToolUsageStats> {
+  return invoke('get_tool_usage', { days: days ?? null });
+}
+
+/** A hypothetical change to past usage (matches Rust SavingsScenario) */
+export type SavingsScenario =
+  /** Bill models matching `from` (an ID or a family like "opus") at the rates of `to` */
+  | { kind: 'switch_model'; from: string; to: string }
+  /** Sessions with a CER below `target` (0-1] reach it */
+  | { kind: 'improve_cer'; target: number };
+
+/** Savings within one project (matches Rust ProjectSavings) */
+export interface ProjectSavings {
+  project_path: string;
+  actual_cost: number;
+  projected_cost: number;
+  savings: number;
+}
+
+/** Result of a savings simulation (matches Rust SavingsSimulation) */
+export interface SavingsSimulation {
+  scenarios: SavingsScenario[];
+  days: number | null;
+  session_count: number;
+  /** Sessions a scenario changed */
+  affected_sessions: number;
+  actual_cost: number;
+  projected_cost: number;
+  /** Negative when the scenarios would have cost more */
+  savings: number;
+  savings_percent: number;
+  /** Savings per 30 days over the simulated period */
+  monthly_savings: number | null;
+  /** Projects with the largest savings */
+  top_projects: ProjectSavings[];
+}
+
+/** Reprice the last `days` days under scenarios applied in order */
+export async function simulateSavings(scenarios: SavingsScenario[], days?: number): Promise<SavingsSimulation> {
+  return invoke('simulate_savings', { scenarios, days: days ?? null });
+}
+
+/** Calls of one tool of an MCP server (matches Rust McpToolStats) */
+export interface McpToolStats {
+  /** Tool name without the server prefix */
+  tool: string;
+  calls: number;
+  errors: number;
+  /** Errors as a share of calls (0-1) */
+  failure_rate: number;
+  /** Estimated tokens of the tool's results */
+  result_tokens: number;
+  /** Result tokens priced as input */
+  estimated_cost: number;
+}
+
+/** Usage of one MCP server (matches Rust McpServerStats) */
+export interface McpServerStats {
+  server: string;
+  calls: number;
+  errors: number;
+  /** Errors as a share of calls (0-1) */
+  failure_rate: number;
+  sessions: number;
+  result_tokens: number;
+  estimated_cost: number;
+  /** Estimated cost of the results of failed calls */
+  failed_cost: number;
+  /** Most called first */
+  tools: McpToolStats[];
+}
+
+/** MCP usage over a period (matches Rust McpStats) */
+export interface McpStats {
+  total_calls: number;
+  total_errors: number;
+  result_tokens: number;
+  estimated_cost: number;
+  /** Busiest server first */
+  servers: McpServerStats[];
+}
+
+/** Get MCP server calls, failure rates and result cost of the last `days` days */
+export async function getMcpStats(days?: number): Promise<McpStats> {
+  return invoke('get_mcp_stats', { days: days ?? null });
+}
+
+/** Time window with two or more sessions generating at once (matches Rust ConcurrencyWindow) */
+export interface ConcurrencyWindow {
+  started_at: string;
+  ended_at: string;
+  duration_ms: number;
+  /** Most sessions running at the same moment */
+  peak_sessions: number;
+  session_ids: string[];
+  subagent_sessions: number;
+  projects: string[];
+  /** Turn cost prorated to the part of each turn inside the window */
+  cost: number;
+}
+
+/** Concurrent session windows over a period (matches Rust ConcurrencyReport) */
+export interface ConcurrencyReport {
+  /** Costliest windows first, at most 100 */
+  windows: ConcurrencyWindow[];
+  window_count: number;
+  peak_concurrency: number;
+  concurrent_ms: number;
+  concurrent_cost: number;
+  total_cost: number;
+  /** Fraction of total cost spent in concurrent windows (0-1) */
+  concurrent_cost_share: number;
+}
+
+/** Get the windows where multiple sessions (subagents included) ran at once */
+export async function getConcurrentSessions(days?: number): Promise<ConcurrencyReport> {
+  return invoke('get_concurrent_sessions', { days: days ?? null });
+}
+
+/** One histogram bucket, from start up to end (matches Rust HistogramBucket) */
+export interface HistogramBucket {
+  start: number;
+  end: number;
+  count: number;
+}
+
+/** Percentiles and histogram of a set of values (matches Rust Distribution) */
+export interface Distribution {
+  count: number;
+  min: number;
+  max: number;
+  mean: number;
+  p50: number;
+  p90: number;
+  p99: number;
+  buckets: HistogramBucket[];
+}
+
+/** Spread of session totals over a period (matches Rust SessionDistributions) */
+export interface SessionDistributions {
+  session_count: number;
+  duration_ms: Distribution;
+  cost: Distribution;
+  turns: Distribution;
+  tokens: Distribution;
+}
+
+/** Get percentiles and histograms of session duration, cost, turns and tokens */
+export async function getSessionDistributions(days?: number, buckets?: number): Promise<SessionDistributions> {
+  return invoke('get_session_distributions', { days: days ?? null, buckets: buckets ?? null });
+}
+
+/** API errors counted by kind (matches Rust ApiErrorCounts) */
+export interface ApiErrorCounts {
+  total: number;
+  rate_limit: number;
+  overloaded: number;
+  other: number;
+  /** Errors Claude Code stopped retrying and showed */
+  retries_exhausted: number;
+  retry_wait_ms: number;
+}
+
+/** API errors of one day (matches Rust DailyApiErrors) */
+export interface DailyApiErrors extends ApiErrorCounts {
+  date: string;
+}
+
+/** API errors of one session (matches Rust SessionApiErrors) */
+export interface SessionApiErrors extends ApiErrorCounts {
+  session_id: string;
+  project_name: string;
+  first_error_at: string | null;
+  last_error_at: string | null;
+}
+
+/** API error totals over a period (matches Rust ApiErrorStats) */
+export interface ApiErrorStats {
+  totals: ApiErrorCounts;
+  by_day: DailyApiErrors[];
+  /** Most errors first */
+  by_session: SessionApiErrors[];
+}
+
+/** Get rate-limit and overload errors per day and per session */
+export async function getErrorStats(days?: number): Promise<ApiErrorStats> {
+  return invoke('get_error_stats', { days: days ?? null });
+}
+
+/** Extended thinking totals of a set of turns (matches Rust ThinkingTotals) */
+export interface ThinkingTotals {
+  turns: number;
+  turns_with_thinking: number;
+  output_tokens: number;
+  /** Estimated from the text of thinking blocks */
+  thinking_tokens: number;
+  /** Thinking tokens as a fraction (0-1) of output tokens */
+  thinking_share: number;
+  thinking_cost: number;
+  /** Cost of the turns, thinking included */
+  cost: number;
+}
+
+/** Thinking totals of one day (matches Rust DailyThinking) */
+export interface DailyThinking extends ThinkingTotals {
+  date: string;
+}
+
+/** Thinking totals of one thinking mode (matches Rust ThinkingModeUsage) */
+export interface ThinkingModeUsage extends ThinkingTotals {
+  /** Level such as "high", "off", or "unspecified" */
+  mode: string;
+  avg_output_per_turn: number;
+  avg_cost_per_turn: number;
+}
+
+/** Thinking usage over a period (matches Rust ThinkingTrend) */
+export interface ThinkingTrend {
+  totals: ThinkingTotals;
+  by_day: DailyThinking[];
+  /** Most turns first */
+  by_mode: ThinkingModeUsage[];
+}
+
+/** Get extended thinking tokens, share of output and cost per day and per thinking mode */
+export async function getThinkingTrend(days?: number): Promise<ThinkingTrend> {
+  return invoke('get_thinking_trend', { days: days ?? null });
+}
+
+/** Cost and active time of a set of turns (matches Rust BurnRate) */
+export interface BurnRate {
+  active_hours: number;
+  cost: number;
+  /** Cost divided by active hours; 0 without active time */
+  cost_per_hour: number;
+}
+
+/** Burn rate of one day (matches Rust DailyBurnRate) */
+export interface DailyBurnRate extends BurnRate {
+  date: string;
+}
+
+/** Burn rate of one project (matches Rust ProjectBurnRate) */
+export interface ProjectBurnRate extends BurnRate {
+  project_path: string;
+  project_name: string;
+}
+
+/** Burn rate over a period (matches Rust BurnRateReport) */
+export interface BurnRateReport {
+  totals: BurnRate;
+  by_day: DailyBurnRate[];
+  /** Costliest first */
+  by_project: ProjectBurnRate[];
+  /** Change in cost per hour per day; positive when rising */
+  trend_per_day: number;
+}
+
+/** Get cost per active working hour per day and per project */
+export async function getBurnRate(days?: number): Promise<BurnRateReport> {
+  return invoke('get_burn_rate', { days: days ?? null });
+}
+
+// ============================================================================
+// Developer Performance Commands
+// ============================================================================
+
+/** GitHub auto-detected configuration */
+export interface GitHubConfig {
+  has_token: boolean;
+  token_source: string | null;
+  username: string | null;
+}
+
+/** Auto-detect GitHub configuration from environment */
+export async function detectGitHubConfig(): Promise<GitHubConfig> {
+  return invoke('detect_github_config');
+}
+
+/** Get developer performance metrics (3-axis AI adoption) */
+export async function getDeveloperMetrics(settings: {
+  githubUsername: string;
+  sprintDays?: number;
+  numSprints?: number;
+}): Promise<DeveloperPerformanceMetrics> {
+  return invoke('get_developer_metrics', {
+    githubUsername: settings.githubUsername,
+    sprintDays: settings.sprintDays ?? null,
+    numSprints: settings.numSprints ?? null,
+  });
+}
+
+// ============================================================================
+// Git Commands
+// ============================================================================
+
+/** Commit authored in the project repository */
+export interface GitCommit {
+  sha: string;
+  short_sha: string;
+  author: string;
+  authored_at: string;
+  subject: string;
+}
+
+/** Branch and commits associated with a session */
+export interface SessionGitContext {
+  session_id: string;
+  project_path: string;
+  is_git_repo: boolean;
+  branch: string | null;
+  window_start: string | null;
+  window_end: string | null;
+  commits: GitCommit[];
+}
+
+/** Get the git branch and commits made while a session was active */
+export async function getSessionGitContext(sessionId: string): Promise<SessionGitContext> {
+  return invoke('get_session_git_context', { sessionId });
+}
+
+// ============================================================================
+// Pricing Commands
+// ============================================================================
+
+/** Per-million-token pricing for a model */
+/** Backend that bills a request */
+export type CostProvider = 'anthropic' | 'bedrock' | 'vertex';
+
+export interface ModelPricing {
+  model_id: string;
+  display_name: string;
+  input_price_per_million: number;
+  output_price_per_million: number;
+  cache_write_5m_per_million: number;
+  cache_write_1h_per_million: number;
+  cache_read_per_million: number;
+  max_context_tokens: number | null;
+  /** Defaults to 'anthropic' when omitted */
+  provider?: CostProvider;
+  /** First day (YYYY-MM-DD, UTC) the rates apply, until the model's next entry; omit for rates without a start */
+  effective_from?: string | null;
+}
+
+/** Pricing entry with whether it is a user override */
+export interface ModelPricingEntry extends ModelPricing {
+  is_custom: boolean;
+}
+
+/** Get the pricing table used for cost calculations */
+export async function getModelPricing(): Promise<ModelPricingEntry[]> {
+  return invoke('get_model_pricing');
+}
+
+/** Add or update a pricing override; session costs are recalculated */
+export async function setModelPricing(pricing: ModelPricing): Promise<void> {
+  return invoke('set_model_pricing', { pricing });
+}
+
+/** Remove a pricing override (the undated one unless `effectiveFrom` is given), reverting to the built-in price */
+export async function resetModelPricing(
+  modelId: string,
+  provider?: CostProvider,
+  effectiveFrom?: string,
+): Promise<boolean> {
+  return invoke('reset_model_pricing', { modelId, provider, effectiveFrom });
+}
+
+/** Models seen in sessions without pricing; their turns count at zero cost until a rate is set */
+export async function getUnpricedModels(): Promise<string[]> {
+  return invoke('get_unpriced_models');
+}
+
+/**
+ * Reprice stored sessions and turns with the rates in effect on their date
+ * and rebuild daily aggregates; resolves to the number of sessions repriced
+ */
+export async function recomputeCosts(): Promise<number> {
+  return invoke('recompute_costs');
+}
+
+// ============================================================================
+// Data Management Commands
+// ============================================================================
+
+/** Force refresh of session cache */
+export async function refreshData(): Promise<void> {
+  return invoke('refresh_sessions');
+}
+
+/** Re-parse one session and store its metrics, turns and anti-patterns again */
+export async function recomputeSession(sessionId: string): Promise<SessionDetail> {
+  return invoke('recompute_session', { sessionId });
+}
+
+/** Fill a summary template (a built-in or saved template name, or the template text) with a session's numbers */
+export async function summarizeSession(sessionId: string, template: string): Promise<string> {
+  return invoke('summarize_session', { sessionId, template });
+}
+
+/** List the built-in summary templates followed by the saved ones */
+export async function getSummaryTemplates(): Promise<SummaryTemplate[]> {
+  return invoke('get_summary_templates');
+}
+
+/** Get the database path */
+export async function getDbPath(): Promise<string> {
+  return invoke('get_db_path');
+}
+
+/**
+ * Move the database to a file or directory and reopen it there; remembered
+ * for later starts. Fails while IRONHIDE_DB_PATH is set or a background job runs.
+ */
+export async function migrateDbTo(path: string): Promise<string> {
+  return invoke('migrate_db_to', { path });
+}
+
+// ============================================================================
+// Settings Types and Commands
+// ============================================================================
+
+export interface AppSettings {
+  claudeHomePath: string;
+  autoRefresh: boolean;
+  refreshIntervalMinutes: number;
+  theme: 'light' | 'dark';
+  githubUsername: string;
+  sprintDays: number;
+  numSprints: number;
+}
+
+// Settings are managed client-side for now
+const DEFAULT_SETTINGS: AppSettings = {
+  claudeHomePath: '~/.claude',
+  autoRefresh: true,
+  refreshIntervalMinutes: 5,
+  theme: 'dark',
+  githubUsername: '',
+  sprintDays: 14,
+  numSprints: 4,
+};
+
+export async function getSettings(): Promise<AppSettings> {
+  // Load from localStorage
+  const stored = localStorage.getItem('ironhide-settings');
+  if (stored) {
+    try {
+      return { ...DEFAULT_SETTINGS, ...JSON.parse(stored) };
+    } catch {
+      return DEFAULT_SETTINGS;
+    }
+  }
+  return DEFAULT_SETTINGS;
+}
+
+export async function updateSettings(settings: Partial<AppSettings>): Promise<void> {
+  const current = await getSettings();
+  const updated = { ...current, ...settings };
+  localStorage.setItem('ironhide-settings', JSON.stringify(updated));
+}
+
+/** Settings stored in the backend database */
+export interface BackendSettings {
+  /** Glob patterns a project path must match to be counted (empty = all) */
+  include_project_patterns: string[];
+  /** Glob patterns for project paths to leave out (temp dirs by default) */
+  exclude_project_patterns: string[];
+  /** Delete cached sessions inactive for longer than this many days (null = keep all) */
+  retention_days: number | null;
+  /** Roll subagent sessions into the sessions that spawned them in dashboard and project aggregates */
+  attribute_subagent_costs: boolean;
+  /** In-memory cache limits */
+  cache: CacheConfig;
+  /** Rescan intervals used when file watching isn't available */
+  polling: PollingConfig;
+  /** Webhook notifications for analytics events */
+  notifications: NotificationSettings;
+  /** Weights of the session health score components */
+  health_weights: HealthWeights;
+  /** Timezone days and hours are reported in: null follows the OS, otherwise "UTC" or an offset like "+02:00" */
+  timezone: string | null;
+  /** Metrics-only ingestion: drop message text and tool inputs/results when parsing */
+  privacy_mode: boolean;
+  /** Store message text, tool inputs/results, session summaries and annotation notes encrypted, with the key in the OS keychain (macOS and Linux only); project and file paths stay plaintext */
+  encrypt_content: boolean;
+  /** Claude data directories scanned besides $CLAUDE_CONFIG_DIR or ~/.claude */
+  claude_roots: ClaudeRoot[];
+  /** How deliverable units are estimated for CPDU and CPD */
+  deliverable_estimator: DeliverableEstimator;
+  /** Rolling windows usage is summed over, with optional warning thresholds */
+  usage_windows: UsageWindowConfig[];
+  /** Saved session summary templates, besides the built-in ones */
+  summary_templates: SummaryTemplate[];
+  /** Releases offered by the update check */
+  update_channel: UpdateChannel;
+  /** Clients allowed to connect to the local IPC socket */
+  ipc_tokens: IpcToken[];
+}
+
+/** An allowlisted IPC client, e.g. an editor extension */
+export interface IpcToken {
+  name: string;
+  token: string;
+}
+
+/** A named session summary template (matches Rust SummaryTemplate) */
+export interface SummaryTemplate {
+  name: string;
+  /** Text with {placeholder} fields, e.g. "{project} — {turns} turns, ${cost}, OES {oes}"; {{ and }} are literal braces */
+  template: string;
+}
+
+/** A rolling usage window and its warning thresholds */
+export interface UsageWindowConfig {
+  /** Window length in hours (1 to 2160) */
+  hours: number;
+  token_threshold?: number | null;
+  /** USD */
+  cost_threshold?: number | null;
+}
+
+/** A Claude data directory laid out like ~/.claude */
+export interface ClaudeRoot {
+  /** Directory path; a leading ~ is the home directory */
+  path: string;
+  /** Source label for its sessions (default: the directory name) */
+  label?: string | null;
+}
+
+/** Relative weights of the health score components (at least one above zero) */
+export interface HealthWeights {
+  efficiency: number;
+  antipatterns: number;
+  tool_failures: number;
+  rework: number;
+}
+
+/** Webhooks that analytics events are POSTed to, and which events are sent */
+export interface NotificationSettings {
+  /** e.g. Slack incoming webhook URLs */
+  webhook_urls: string[];
+  new_session: boolean;
+  budget_exceeded: boolean;
+  anomaly_detected: boolean;
+  antipattern_found: boolean;
+  /** Post a summary of the previous day once a day */
+  daily_digest: boolean;
+  /** Daily spend (USD) above which budget_exceeded fires (null = no budget) */
+  daily_budget: number | null;
+}
+
+/** Sizes and lifetimes of the backend's in-memory caches */
+export interface CacheConfig {
+  /** Memory budget (MB) for parsed sessions; least recently used are evicted past it */
+  session_cache_budget_mb: number;
+  session_list_ttl_secs: number;
+  dashboard_ttl_secs: number;
+  daily_metrics_ttl_secs: number;
+  project_metrics_ttl_secs: number;
+  /** Keep parsing sessions beyond the first preload batch in the background */
+  aggressive_preload: boolean;
+}
+
+/** How often session files are rescanned without file watching (matches Rust PollingConfig) */
+export interface PollingConfig {
+  /** Seconds between scans while a session file changed within the active window */
+  active_interval_secs: number;
+  /** Seconds between scans otherwise */
+  idle_interval_secs: number;
+  /** How long after its last change a session counts as active (seconds) */
+  active_window_secs: number;
+}
+
+/** Get settings stored in the backend */
+export async function getBackendSettings(): Promise<BackendSettings> {
+  return invoke('get_settings');
+}
+
+/** Save backend settings; invalid glob patterns and webhook URLs are rejected */
+export async function setBackendSettings(settings: BackendSettings): Promise<BackendSettings> {
+  return invoke('set_settings', { settings });
+}
+
+/** Re-read the stored cache limits and apply them without restarting */
+export async function reloadCacheConfig(): Promise<CacheConfig> {
+  return invoke('reload_cache_config');
+}
+
+/** Release channel: beta also offers pre-releases (matches Rust UpdateChannel) */
+export type UpdateChannel = 'stable' | 'beta';
+
+/** Result of an update check (matches Rust UpdateInfo) */
+export interface UpdateInfo {
+  current_version: string;
+  channel: UpdateChannel;
+  update_available: boolean;
+  /** Newest release on the channel, even if not newer than the running version */
+  latest_version: string | null;
+  prerelease: boolean;
+  release_name: string | null;
+  /** Markdown */
+  release_notes: string | null;
+  release_url: string | null;
+  published_at: string | null;
+}
+
+/** Get the channel update checks look at */
+export async function getUpdateChannel(): Promise<UpdateChannel> {
+  return invoke('get_update_channel');
+}
+
+/** Switch the update channel; stored with the other settings */
+export async function setUpdateChannel(channel: UpdateChannel): Promise<UpdateChannel> {
+  return invoke('set_update_channel', { channel });
+}
+
+/** Check GitHub releases on the configured channel for a newer version */
+export async function checkForUpdates(): Promise<UpdateInfo> {
+  return invoke('check_for_updates');
+}
+
+/** What an editor extension needs to connect to the IPC socket */
+export interface IpcConnectionInfo {
+  name: string;
+  token: string;
+  /** Socket path, or the pipe name on Windows */
+  endpoint: string;
+}
+
+/** Allowlist a new IPC client and get its token */
+export async function createIpcToken(name: string): Promise<IpcConnectionInfo> {
+  return invoke('create_ipc_token', { name });
+}
+
+/** Remove an IPC client from the allowlist; false if it wasn't on it */
+export async function revokeIpcToken(name: string): Promise<boolean> {
+  return invoke('revoke_ipc_token', { name });
+}
+
+/** Rows removed by a prune */
+export interface PruneStats {
+  sessions_deleted: number;
+  turns_deleted: number;
+}
+
+/** Database size in bytes before and after a vacuum */
+export interface VacuumStats {
+  size_before: number;
+  size_after: number;
+}
+
+/** Delete cached sessions inactive for more than N days (session files are kept) */
+export async function pruneSessionsOlderThan(days: number): Promise<PruneStats> {
+  return invoke('prune_sessions_older_than', { days });
+}
+
+/** Compact the analytics database */
+export async function vacuumDatabase(): Promise<VacuumStats> {
+  return invoke('vacuum_database');
+}
+
+/** Recompute the daily rollups behind trends and daily metrics; returns the rows written */
+export async function rebuildDailyRollups(): Promise<number> {
+  return invoke('rebuild_daily_rollups');
+}
+
+/** Sessions merged from a database snapshot */
+export interface SnapshotImportStats {
+  sessions_added: number;
+  sessions_updated: number;
+  /** Sessions whose local copy is as recent or more */
+  sessions_skipped: number;
+  dismissals_merged: number;
+}
+
+/** Snapshots exchanged through a sync folder */
+export interface SnapshotSyncReport {
+  exported_path: string;
+  imported: string[];
+  /** Snapshots that couldn't be merged, with the reason */
+  failed: string[];
+  stats: SnapshotImportStats;
+}
+
+/** Write a snapshot of the analytics database; returns the file path */
+export async function exportSnapshot(path?: string): Promise<string> {
+  return invoke('export_snapshot', { path });
+}
+
+/** Merge another machine's snapshot; the more recently active copy of each session wins */
+export async function importSnapshot(path: string): Promise<SnapshotImportStats> {
+  return invoke('import_snapshot', { path });
+}
+
+/** Outcome of bootstrapping from a snapshot (matches Rust BootstrapStats) */
+export interface BootstrapStats {
+  sessions_imported: number;
+  /** Sessions whose files are also on this machine */
+  sessions_local: number;
+  /** Sessions shown from stored metrics only, their files being on another machine */
+  sessions_remote: number;
+  dismissals_merged: number;
+}
+
+/** Fill an empty database from a snapshot (default: bootstrap.db next to the database) instead of a full first preload */
+export async function bootstrapFromSnapshot(path?: string): Promise<BootstrapStats> {
+  return invoke('bootstrap_from_snapshot', { path: path ?? null });
+}
+
+/** Merge every other machine's snapshot in a shared folder, then write this machine's */
+export async function syncSnapshotFolder(folder: string): Promise<SnapshotSyncReport> {
+  return invoke('sync_snapshot_folder', { folder });
+}
+
+// ============================================================================
+// Team Roll-up Commands
+// ============================================================================
+
+/** Result of importing a teammate's session export */
+export interface TeamImportStats {
+  user_name: string;
+  sessions_added: number;
+  /** Sessions imported before for this user, replaced by the new export */
+  sessions_updated: number;
 }
 
-/** Preload all sessions into cache at startup - call this once when app starts */
-export async function preloadAllSessions(): Promise<number> {
-  return invoke('preload_all_sessions');
+/** A teammate with imported sessions */
+export interface TeamMember {
+  user_name: string;
+  session_count: number;
+  total_cost: number;
+  total_tokens: number;
+  first_session_at: string | null;
+  last_session_at: string | null;
+  last_imported_at: string | null;
 }
 
-/** Get all sessions for a specific project path (server-side filtering) */
-export async function getSessionsByProject(projectPath: string): Promise<SessionSummary[]> {
-  return invoke('get_sessions_by_project', { projectPath });
+/** One teammate's share of the team totals */
+export interface TeamMemberStats {
+  user_name: string;
+  session_count: number;
+  total_turns: number;
+  total_tokens: number;
+  total_cost: number;
+  /** Fraction of the team's cost (0-1) */
+  cost_share: number;
+  avg_cost_per_session: number;
+  avg_efficiency: number | null;
 }
 
-/** Get sessions filtered by date range (uses cached data for efficiency) */
-export async function getSessionsFiltered(
-  startDate?: string,
-  endDate?: string,
-  limit = 100,
-  offset = 0
-): Promise<SessionSummary[]> {
-  return invoke('get_sessions_filtered', {
-    startDate: startDate ?? null,
-    endDate: endDate ?? null,
-    limit,
-    offset,
-  });
+/** Team totals over a period, costliest teammates first */
+export interface TeamDashboard {
+  total_sessions: number;
+  total_turns: number;
+  total_tokens: number;
+  total_cost: number;
+  avg_efficiency: number | null;
+  members: TeamMemberStats[];
 }
 
-// ============================================================================
-// Dashboard/Aggregate Commands
-// ============================================================================
+/** Team usage on one day, with each teammate's part */
+export interface TeamDailyTrend {
+  date: string;
+  session_count: number;
+  total_tokens: number;
+  total_cost: number;
+  by_user: { user_name: string; session_count: number; total_tokens: number; total_cost: number }[];
+}
 
-/** Get dashboard summary metrics (using efficient backend command) */
-export async function getDashboardSummary(days?: number): Promise<DashboardSummary> {
-  // Pass days to backend for time-range filtering (undefined = all time)
-  return invoke('get_dashboard_summary', { days: days ?? null });
+/** Import a teammate's JSON session export (from exportSessions) under their name */
+export async function importTeamExport(path: string, userName: string): Promise<TeamImportStats> {
+  return invoke('import_team_export', { path, userName });
 }
 
-/** Get daily metrics for charts (using efficient backend command) */
-export async function getDailyMetrics(days?: number): Promise<DailyMetrics[]> {
-  // Pass days to backend for time-range filtering (undefined = 30 days default)
-  return invoke('get_daily_metrics', { days: days ?? null });
+/** Get the teammates with imported sessions */
+export async function getTeamMembers(): Promise<TeamMember[]> {
+  return invoke('get_team_members');
 }
 
-/** Get project-level metrics (using efficient backend command) */
-export async function getProjectMetrics(days?: number): Promise<ProjectMetrics[]> {
-  // Pass days to backend for time-range filtering (undefined = all time)
-  return invoke('get_project_metrics', { days: days ?? null });
+/** Remove a teammate's imported sessions; returns how many were removed */
+export async function removeTeamMember(userName: string): Promise<number> {
+  return invoke('remove_team_member', { userName });
 }
 
-// ============================================================================
-// Developer Performance Commands
-// ============================================================================
+/** Get team totals per teammate for the last `days` days (all time when omitted) */
+export async function getTeamDashboard(days?: number): Promise<TeamDashboard> {
+  return invoke('get_team_dashboard', { days });
+}
 
-/** GitHub auto-detected configuration */
-export interface GitHubConfig {
-  has_token: boolean;
-  token_source: string | null;
-  username: string | null;
+/** Get daily team usage split by teammate (default: last 30 days) */
+export async function getTeamTrends(days?: number): Promise<TeamDailyTrend[]> {
+  return invoke('get_team_trends', { days });
 }
 
-/** Auto-detect GitHub configuration from environment */
-export async function detectGitHubConfig(): Promise<GitHubConfig> {
-  return invoke('detect_github_config');
+/** Session files moved into the compressed archive */
+export interface ArchiveReport {
+  sessions_archived: number;
+  /** Size of the archived files before and after compression */
+  bytes_before: number;
+  bytes_after: number;
+  /** Files that couldn't be archived, with the reason */
+  failed: string[];
 }
 
-/** Get developer performance metrics (3-axis AI adoption) */
-export async function getDeveloperMetrics(settings: {
-  githubUsername: string;
-  sprintDays?: number;
-  numSprints?: number;
-}): Promise<DeveloperPerformanceMetrics> {
-  return invoke('get_developer_metrics', {
-    githubUsername: settings.githubUsername,
-    sprintDays: settings.sprintDays ?? null,
-    numSprints: settings.numSprints ?? null,
-  });
+/** Compress session files inactive for more than N days (metrics stay in the database) */
+export async function archiveSessionsOlderThan(days: number): Promise<ArchiveReport> {
+  return invoke('archive_sessions_older_than', { days });
 }
 
-// ============================================================================
-// Data Management Commands
-// ============================================================================
+/** Move an archived session back to ~/.claude/projects; returns the restored path */
+export async function restoreSession(sessionId: string): Promise<string> {
+  return invoke('restore_session', { sessionId });
+}
 
-/** Force refresh of session cache */
-export async function refreshData(): Promise<void> {
-  return invoke('refresh_sessions');
+/** Result of importing sessions from a directory */
+export interface ImportResult {
+  /** Source label the sessions were stored under */
+  source: string;
+  path: string;
+  files_found: number;
+  sessions_imported: number;
+  /** Sessions already tracked from ~/.claude or another source */
+  duplicates: number;
+  /** Files that aren't valid Claude Code sessions */
+  invalid_files: string[];
 }
 
-/** Get the database path */
-export async function getDbPath(): Promise<string> {
-  return invoke('get_db_path');
+/** Import sessions from a directory outside ~/.claude (label defaults to the directory name) */
+export async function importSessionsFromPath(path: string, label?: string): Promise<ImportResult> {
+  return invoke('import_sessions_from_path', { path, label: label ?? null });
+}
+
+/** Project path an encoded project directory resolves to */
+export interface ProjectResolution {
+  encoded_dir: string;
+  project_path: string;
+  source: 'override' | 'cwd' | 'filesystem' | 'decoded';
+  ambiguous: boolean;
+}
+
+/** Set the project path of a directory under projects/, or clear the override with null */
+export async function setProjectPathOverride(
+  projectDir: string,
+  projectPath: string | null
+): Promise<ProjectResolution> {
+  return invoke('set_project_path_override', { projectDir, projectPath });
 }
 
 // ============================================================================
-// Settings Types and Commands
+// Diagnostics Commands
 // ============================================================================
 
-export interface AppSettings {
-  claudeHomePath: string;
-  autoRefresh: boolean;
-  refreshIntervalMinutes: number;
-  theme: 'light' | 'dark';
-  githubUsername: string;
-  sprintDays: number;
-  numSprints: number;
+/** How session files are followed (matches Rust WatcherStatus) */
+export type WatcherStatus = 'not_started' | 'watching' | 'polling';
+
+/** Whether stored content is encrypted (matches Rust ContentEncryptionStatus) */
+export type ContentEncryptionStatus = 'off' | 'on' | 'locked';
+
+/** A startup step that failed (matches Rust InitError) */
+export interface InitError {
+  /** What was being set up, e.g. "database schema" */
+  stage: string;
+  message: string;
+  at: string;
 }
 
-// Settings are managed client-side for now
-const DEFAULT_SETTINGS: AppSettings = {
-  claudeHomePath: '~/.claude',
-  autoRefresh: true,
-  refreshIntervalMinutes: 5,
-  theme: 'dark',
-  githubUsername: '',
-  sprintDays: 14,
-  numSprints: 4,
-};
+/** The analytics database (matches Rust DatabaseDiagnostics) */
+export interface DatabaseDiagnostics {
+  path: string;
+  size_bytes: number;
+  schema_version: number;
+  /** Higher than schema_version when a migration failed */
+  latest_schema_version: number;
+}
 
-export async function getSettings(): Promise<AppSettings> {
-  // Load from localStorage
-  const stored = localStorage.getItem('ironhide-settings');
-  if (stored) {
-    try {
-      return { ...DEFAULT_SETTINGS, ...JSON.parse(stored) };
-    } catch {
-      return DEFAULT_SETTINGS;
-    }
-  }
-  return DEFAULT_SETTINGS;
+/** Fill of the in-memory caches (matches Rust CacheDiagnostics) */
+export interface CacheDiagnostics {
+  listed_sessions: number;
+  cached_summaries: number;
+  cached_sessions: number;
+  cached_bytes: number;
+  budget_bytes: number;
 }
 
-export async function updateSettings(settings: Partial<AppSettings>): Promise<void> {
-  const current = await getSettings();
-  const updated = { ...current, ...settings };
-  localStorage.setItem('ironhide-settings', JSON.stringify(updated));
+/** Environment health (matches Rust DiagnosticsReport) */
+export interface DiagnosticsReport {
+  claude_dir: string | null;
+  claude_dir_found: boolean;
+  scan_roots: number;
+  /** null when the database couldn't be opened */
+  database: DatabaseDiagnostics | null;
+  cache: CacheDiagnostics;
+  last_preload_at: string | null;
+  watcher: WatcherStatus;
+  /** 'locked' when encrypt_content is on but the keychain has no usable key: content is neither shown nor stored */
+  content_encryption: ContentEncryptionStatus;
+  /** Startup steps that failed; the app runs without them */
+  init_errors: InitError[];
+}
+
+/** Get the health of the backend for the diagnostics panel */
+export async function getDiagnostics(): Promise<DiagnosticsReport> {
+  return invoke('get_diagnostics');
+}
+
+/** A non-fatal error the backend worked around (matches Rust BackendError) */
+export interface BackendError {
+  /** What failed, e.g. "load dashboard summary" */
+  source: string;
+  count: number;
+  last_message: string;
+  first_at: string;
+  last_at: string;
+}
+
+/** Get the errors commands recovered from since startup, most recent first */
+export async function getBackendErrors(): Promise<BackendError[]> {
+  return invoke('get_backend_errors');
 }
 
 // ============================================================================
@@ -190,10 +1695,47 @@ export interface MetricsComparison {
   duration_diff: number;
 }
 
+/** A metric in the baseline session and the compared session */
+export interface MetricDelta {
+  baseline: number;
+  value: number;
+  diff: number;
+  percent_change: number;
+}
+
+/** Calls of one tool in each session */
+export interface ToolUsageDelta {
+  tool: string;
+  baseline_count: number;
+  count: number;
+  diff: number;
+}
+
+/** Structured diff of a session against the baseline (first) session */
+export interface MetricsComparisonDetailed {
+  baseline_id: string;
+  session_id: string;
+  efficiency: { cer: MetricDelta; cgr: MetricDelta; wfs: MetricDelta; oes: MetricDelta };
+  totals: {
+    cost: MetricDelta;
+    tokens: MetricDelta;
+    duration_ms: MetricDelta;
+    turns: MetricDelta;
+    tool_calls: MetricDelta;
+  };
+  /** Averages per turn, comparable across sessions of different length */
+  per_turn: { cost: MetricDelta; tokens: MetricDelta; duration_ms: MetricDelta; tool_calls: MetricDelta };
+  /** Largest absolute change first */
+  tool_usage: ToolUsageDelta[];
+  models: { common: string[]; only_baseline: string[]; only_compared: string[] };
+}
+
 /** Session comparison result */
 export interface SessionComparisonResult {
   sessions: SessionSummary[];
   metrics_comparison: MetricsComparison;
+  /** Diff of each session after the first against the first */
+  detailed_comparisons: MetricsComparisonDetailed[];
 }
 
 /** Compare multiple sessions by IDs (2-3 sessions) */
@@ -218,6 +1760,8 @@ export interface DetectedPattern {
 
 /** Recommendation for improving Claude usage */
 export interface Recommendation {
+  /** Stable id: `<rec_type>:<session id>`, or `<rec_type>:all` for aggregate ones */
+  id: string;
   rec_type: string;
   title: string;
   description: string;
@@ -242,19 +1786,103 @@ export async function detectAntipatterns(sessionId?: string): Promise<DetectedPa
   return invoke('detect_antipatterns', { sessionId });
 }
 
-/** Get recommendations for improving Claude usage */
+/** Anti-pattern occurrences of one type on one day */
+export interface AntiPatternHistoryPoint {
+  date: string;
+  pattern_type: string;
+  occurrences: number;
+  sessions: number;
+  /** Mean severity score, 1 (info) to 9 (far past a critical threshold) */
+  avg_severity_score: number;
+  impact_cost: number;
+}
+
+/** Get daily anti-pattern counts per type for trend charts */
+export async function getAntipatternHistory(days?: number): Promise<AntiPatternHistoryPoint[]> {
+  return invoke('get_antipattern_history', { days });
+}
+
+/** Summary of a day compared to the 7 days before it (matches Rust DailyDigest) */
+export interface DailyDigest {
+  date: string;
+  sessions: number;
+  turns: number;
+  cost: number;
+  avg_sessions_7d: number;
+  avg_cost_7d: number;
+  /** Change from the 7-day averages in percent; null without a baseline */
+  sessions_change_percent: number | null;
+  cost_change_percent: number | null;
+  top_project: { project_path: string; project_name: string; sessions: number; cost: number } | null;
+  most_expensive_session: { session_id: string; project_path: string; turns: number; cost: number } | null;
+  /** Anti-pattern types found that day, most frequent first */
+  antipatterns: {
+    pattern_type: string;
+    occurrences: number;
+    sessions: number;
+    impact_cost: number;
+    avg_occurrences_7d: number;
+  }[];
+}
+
+/** Summarize a day (default yesterday); with post, also send it to the webhooks */
+export async function generateDailyDigest(date?: string, post?: boolean): Promise<DailyDigest> {
+  return invoke('generate_daily_digest', { date, post });
+}
+
+/** Get recommendations for improving Claude usage (dismissed ones excluded) */
 export async function getRecommendations(sessionId?: string, limit?: number): Promise<RecommendationSummary> {
   return invoke('get_recommendations', { sessionId, limit });
 }
 
+/** A dismissed or snoozed recommendation */
+export interface RecommendationFeedback {
+  id: string;
+  rec_type: string;
+  session_id: string | null;
+  project_path: string | null;
+  reason: string | null;
+  /** Savings and confidence when dismissed; a material increase shows it again */
+  potential_savings: number;
+  confidence: number;
+  dismissed_at: string;
+  /** Shown again after this time; null dismisses until things change */
+  snoozed_until: string | null;
+}
+
+/**
+ * Dismiss a recommendation, or snooze it for a number of days.
+ * Session recommendations are hidden for the whole project.
+ */
+export async function dismissRecommendation(
+  id: string,
+  reason?: string,
+  snoozeDays?: number
+): Promise<RecommendationFeedback> {
+  return invoke('dismiss_recommendation', { id, reason: reason ?? null, snoozeDays: snoozeDays ?? null });
+}
+
+/** List dismissed and snoozed recommendations */
+export async function listDismissed(): Promise<RecommendationFeedback[]> {
+  return invoke('list_dismissed');
+}
+
+/** Show a dismissed recommendation again */
+export async function restoreRecommendation(id: string): Promise<boolean> {
+  return invoke('restore_recommendation', { id });
+}
+
 // ============================================================================
 // Export Commands
 // ============================================================================
 
+/** Supported export formats */
+export type ExportFormat = 'csv' | 'json' | 'markdown' | 'sqlite' | 'parquet' | 'ical';
+
 /** Options for export operations */
 export interface ExportOptions {
-  /** Export format (csv or json) */
-  format: 'csv' | 'json';
+  /** Export format */
+  format: ExportFormat;
   /** Include turn-level details */
   include_turns: boolean;
   /** Include calculated metrics */
@@ -263,30 +1891,106 @@ export interface ExportOptions {
   date_range?: [string, string];
 }
 
+/** Progress or outcome of a background export, also sent as `export-progress` events */
+export interface ExportJobStatus {
+  job_id: string;
+  state: 'running' | 'completed' | 'failed' | 'cancelled';
+  processed: number;
+  total: number;
+  /** Exported file (or Parquet directory), once written; cancelled jobs keep what they processed */
+  path: string | null;
+  error: string | null;
+}
+
 /**
- * Export sessions to CSV or JSON format.
+ * Start exporting sessions to CSV, JSON, a Markdown report, a SQLite database or Parquet files.
+ * SQLite and Parquet exports include a daily_metrics table; Parquet writes a
+ * directory with one file per table.
  * @param sessionIds - Optional array of session IDs to export. If not provided, exports all sessions.
  * @param options - Export options including format, whether to include turns and metrics.
- * @returns The file path of the exported file.
+ * @returns The ID of the background export job.
  */
-export async function exportSessions(
+export async function startSessionExport(
   sessionIds?: string[],
   options: ExportOptions = { format: 'csv', include_turns: false, include_metrics: true }
 ): Promise<string> {
   return invoke('export_sessions', { sessionIds, options });
 }
 
+/** Status of an export job started with startSessionExport */
+export async function getExportStatus(jobId: string): Promise<ExportJobStatus> {
+  return invoke('get_export_status', { jobId });
+}
+
+/** Stop a running export; resolves to whether it was still running */
+export async function cancelExport(jobId: string): Promise<boolean> {
+  return invoke('cancel_export', { jobId });
+}
+
+/**
+ * Export sessions and wait for the export job to finish.
+ * @returns The path of the exported file (a directory for Parquet).
+ */
+export async function exportSessions(
+  sessionIds?: string[],
+  options: ExportOptions = { format: 'csv', include_turns: false, include_metrics: true }
+): Promise<string> {
+  const jobId = await startSessionExport(sessionIds, options);
+  for (;;) {
+    const status = await getExportStatus(jobId);
+    if (status.state === 'failed') {
+      throw new Error(status.error ?? 'Export failed');
+    }
+    if (status.state !== 'running') {
+      return status.path ?? '';
+    }
+    await new Promise((resolve) => setTimeout(resolve, 250));
+  }
+}
+
 /**
- * Export usage trends to CSV or JSON format.
+ * Export usage trends to CSV, JSON, Markdown, SQLite, Parquet or iCalendar format.
  * Aggregates session data by day for the specified number of days.
  * @param days - Number of days to include in the export.
- * @param format - Export format (csv or json).
+ * @param format - Export format (csv, json, markdown, sqlite, parquet or ical).
  * @returns The file path of the exported file.
  */
-export async function exportTrends(days: number, format: 'csv' | 'json'): Promise<string> {
+export async function exportTrends(days: number, format: ExportFormat): Promise<string> {
   return invoke('export_trends', { days, format });
 }
 
+/**
+ * Export a weekly report. Markdown and JSON hold the whole report; the other
+ * formats hold the week's daily rows.
+ * @returns The file path of the exported file.
+ */
+export async function exportWeeklyReport(format: ExportFormat, weekStart?: string): Promise<string> {
+  return invoke('export_weekly_report', { weekStart, format });
+}
+
+/**
+ * Export a session as a standalone HTML replay page to share.
+ * @param path - Where to write it; defaults to a timestamped file in the export directory.
+ * @returns The file path of the exported page.
+ */
+export async function exportSessionReplay(sessionId: string, path?: string): Promise<string> {
+  return invoke('export_session_replay', { sessionId, path });
+}
+
+/**
+ * Export a session as a zip bundle (JSONL, computed metrics and anti-patterns) for bug reports.
+ * @param anonymize - Replace message text, tool payloads and paths; always on in privacy mode.
+ * @param path - Where to write it; defaults to a timestamped file in the export directory.
+ * @returns The file path of the bundle.
+ */
+export async function exportSessionBundle(
+  sessionId: string,
+  anonymize: boolean,
+  path?: string
+): Promise<string> {
+  return invoke('export_session_bundle', { sessionId, anonymize, path });
+}
+
 // ============================================================================
 // Trends Commands
 // ============================================================================
@@ -301,6 +2005,78 @@ export interface DailyTrend {
   avg_efficiency: number;
 }
 
+/** Percentage changes vs the previous period */
+export interface PeriodComparison {
+  sessions_change_percent: number;
+  tokens_change_percent: number;
+  cost_change_percent: number;
+  efficiency_change_percent: number;
+}
+
+/** Weekly trend with daily breakdown (weeks start on Monday) */
+export interface WeeklyTrend {
+  week_start: string;
+  week_end: string;
+  week_number: number;
+  sessions: number;
+  turns: number;
+  total_tokens: number;
+  total_cost: number;
+  avg_efficiency: number;
+  /** Null for the earliest week on record */
+  comparison: PeriodComparison | null;
+  daily: DailyTrend[];
+}
+
+/** Monthly trend with weekly breakdown */
+export interface MonthlyTrend {
+  month: string;
+  sessions: number;
+  turns: number;
+  total_tokens: number;
+  total_cost: number;
+  avg_efficiency: number;
+  /** Null for the earliest month on record */
+  comparison: PeriodComparison | null;
+  weekly: WeeklyTrend[];
+}
+
+/** Day with unusually high spend (matches Rust CostAnomalyResponse) */
+export interface CostAnomaly {
+  date: string;
+  cost: number;
+  sessions: number;
+  baseline_mean: number;
+  baseline_std_dev: number;
+  threshold: number;
+  z_score: number;
+  /** Sessions started that day, most expensive first */
+  top_sessions: SessionSummary[];
+}
+
+/** Projected cost for a future day */
+export interface ForecastPoint {
+  date: string;
+  projected_cost: number;
+  /** Month-to-date cost including this day's projection */
+  cumulative_cost: number;
+}
+
+/** Projected spend for the coming days and the current month */
+export interface CostForecast {
+  /** Date the forecast was made; projections start the day after */
+  as_of: string;
+  /** YYYY-MM */
+  month: string;
+  month_to_date_cost: number;
+  projected_month_cost: number;
+  daily_average: number;
+  /** Change in daily cost per day */
+  daily_slope: number;
+  history_days: number;
+  points: ForecastPoint[];
+}
+
 /** Cost trend data point */
 export interface CostTrendPoint {
   date: string;
@@ -315,17 +2091,25 @@ export interface EfficiencyTrendPoint {
   sessions: number;
 }
 
-/** Get daily trends within a date range */
+/**
+ * Get daily trends within a date range; days without sessions are zero-filled unless `backfill` is false.
+ * A `stack` (language or framework) counts only the projects using it, a `segment` only user or subagent sessions.
+ */
 export async function getTrends(
   startDate?: string,
   endDate?: string,
-  granularity?: string
+  backfill = true,
+  stack?: string,
+  segment?: TrendSegment
 ): Promise<DailyTrend[]> {
   try {
     return await invoke('get_trends', {
       startDate: startDate ?? null,
       endDate: endDate ?? null,
-      granularity: granularity ?? 'daily',
+      granularity: 'daily',
+      backfill,
+      stack: stack ?? null,
+      segment: segment ?? null,
     });
   } catch (error) {
     // Fallback to computing from sessions if backend doesn't support this command
@@ -334,20 +2118,140 @@ export async function getTrends(
   }
 }
 
-/** Get cost trend over specified number of days */
-export async function getCostTrend(days?: number): Promise<CostTrendPoint[]> {
+/** Get weekly trends for the weeks overlapping a date range */
+export async function getWeeklyTrends(startDate?: string, endDate?: string, stack?: string): Promise<WeeklyTrend[]> {
+  return invoke('get_trends', {
+    startDate: startDate ?? null,
+    endDate: endDate ?? null,
+    granularity: 'weekly',
+    stack: stack ?? null,
+  });
+}
+
+/** Get monthly trends for the months overlapping a date range */
+export async function getMonthlyTrends(startDate?: string, endDate?: string, stack?: string): Promise<MonthlyTrend[]> {
+  return invoke('get_trends', {
+    startDate: startDate ?? null,
+    endDate: endDate ?? null,
+    granularity: 'monthly',
+    stack: stack ?? null,
+  });
+}
+
+/** Get days in the last N days whose spend is more than `sigma` standard deviations above normal */
+export async function getCostAnomalies(days?: number, sigma?: number): Promise<CostAnomaly[]> {
+  return invoke('get_cost_anomalies', { days: days ?? null, sigma: sigma ?? null });
+}
+
+/** Project spend for the next N days (defaults to the rest of the month) */
+export async function getCostForecast(daysAhead?: number): Promise<CostForecast> {
+  return invoke('get_cost_forecast', { daysAhead: daysAhead ?? null });
+}
+
+/** Inclusive range of calendar days, YYYY-MM-DD (matches Rust DateRange) */
+export interface DateRange {
+  start_date: string;
+  end_date: string;
+}
+
+/** Totals of the sessions started within a date range (matches Rust PeriodTotals) */
+export interface PeriodTotals extends DateRange {
+  days: number;
+  sessions: number;
+  turns: number;
+  total_tokens: number;
+  total_cost: number;
+  /** Session-weighted cache efficiency ratio */
+  avg_efficiency: number;
+  /** Tool calls of the range's stored turns */
+  tool_calls: number;
+  cost_per_day: number;
+}
+
+/** Range B measured against range A (matches Rust DateRangeComparison) */
+export interface DateRangeComparison {
+  range_a: PeriodTotals;
+  range_b: PeriodTotals;
+  deltas: {
+    cost: MetricDelta;
+    cost_per_day: MetricDelta;
+    tokens: MetricDelta;
+    sessions: MetricDelta;
+    turns: MetricDelta;
+    efficiency: MetricDelta;
+    tool_calls: MetricDelta;
+  };
+  /** Every tool used in either range, largest absolute change first */
+  tool_usage: ToolUsageDelta[];
+}
+
+/** Compare the sessions of two arbitrary date ranges; range A is the baseline */
+export async function comparePeriods(rangeA: DateRange, rangeB: DateRange): Promise<DateRangeComparison> {
+  return invoke('compare_periods', { rangeA, rangeB });
+}
+
+/** A week next to the week before it (matches Rust WeeklyReport) */
+export interface WeeklyReport {
+  week: DateRange;
+  /** Previous week as range A, this week as range B */
+  comparison: DateRangeComparison;
+  /** Every day of the week, including days without sessions */
+  daily: DailyTrend[];
+  /** Costliest sessions of the week, subagents excluded */
+  top_sessions: {
+    session_id: string;
+    project_path: string;
+    project_name: string;
+    started_at: string;
+    turns: number;
+    cost: number;
+  }[];
+  /** Anti-pattern types found in either week, most frequent this week first */
+  antipatterns: {
+    pattern_type: string;
+    occurrences: number;
+    previous_occurrences: number;
+    sessions: number;
+    impact_cost: number;
+  }[];
+  /** Top recommendations for the week's sessions */
+  recommendations: Recommendation[];
+}
+
+/** Report on a week (default the last full week, Monday to Sunday) against the week before */
+export async function generateWeeklyReport(weekStart?: string): Promise<WeeklyReport> {
+  return invoke('generate_weekly_report', { weekStart });
+}
+
+/** Get cost trend over specified number of days, optionally of projects using a language or framework or of one segment */
+export async function getCostTrend(
+  days?: number,
+  backfill = true,
+  stack?: string,
+  segment?: TrendSegment
+): Promise<CostTrendPoint[]> {
   try {
-    return await invoke('get_cost_trend', { days: days ?? 30 });
+    return await invoke('get_cost_trend', { days: days ?? 30, backfill, stack: stack ?? null, segment: segment ?? null });
   } catch (error) {
     console.warn('get_cost_trend not available, falling back to computed data:', error);
     return computeCostTrendFromSessions(days ?? 30);
   }
 }
 
-/** Get efficiency trend over specified number of days */
-export async function getEfficiencyTrend(days?: number): Promise<EfficiencyTrendPoint[]> {
+/** Get efficiency trend over specified number of days, optionally of projects using a language or framework or of one segment */
+export async function getEfficiencyTrend(
+  days?: number,
+  backfill = true,
+  stack?: string,
+  segment?: TrendSegment
+): Promise<EfficiencyTrendPoint[]> {
   try {
-    return await invoke('get_efficiency_trend', { days: days ?? 30 });
+    return await invoke('get_efficiency_trend', {
+      days: days ?? 30,
+      backfill,
+      stack: stack ?? null,
+      segment: segment ?? null,
+    });
   } catch (error) {
     console.warn('get_efficiency_trend not available, falling back to computed data:', error);
     return computeEfficiencyTrendFromSessions(days ?? 30);
@@ -224,7 +224,7 @@ function Dashboard() {
             <CardTitle>Cost Efficiency</CardTitle>
           </CardHeader>
           <CardContent>
-            <div className="grid grid-cols-2 gap-4 sm:grid-cols-4">
+            <div className="grid grid-cols-2 gap-4 sm:grid-cols-3 lg:grid-cols-5">
               <div className="rounded-lg bg-[var(--color-background)] p-4">
                 <p className="text-sm text-gray-400">Avg Cost/Session</p>
                 {summaryLoading ? (
@@ -265,6 +265,21 @@ function Dashboard() {
                   </p>
                 )}
               </div>
+              <div className="rounded-lg bg-[var(--color-background)] p-4">
+                <p className="text-sm text-gray-400">Concurrent Cost</p>
+                {summaryLoading ? (
+                  <div className="mt-1 h-6 w-20 animate-pulse rounded bg-gray-700" />
+                ) : (
+                  <p className="mt-1 text-xl font-semibold text-white">
+                    {summary ? `${(summary.concurrent_cost_share * 100).toFixed(0)}%` : '0%'}
+                  </p>
+                )}
+                {summary && summary.peak_concurrent_sessions > 1 && (
+                  <p className="mt-1 text-xs text-gray-500">
+                    Up to {summary.peak_concurrent_sessions} sessions at once
+                  </p>
+                )}
+              </div>
             </div>
           </CardContent>
         </Card>
@@ -11,6 +11,7 @@ import { ComponentLoadingSpinner } from '../components/ui/PageLoadingSpinner';
 import { Button } from '../components/ui/Button';
 import type { ToolUsageData } from '../components/charts/ToolUsagePieChart';
 import { useSession, useTurns } from '../hooks/useSessions';
+import { exportSessionReplay } from '../lib/tauri';
 
 // Lazy load heavy chart components
 const TokenStackedAreaChart = React.lazy(() => import('../components/charts/TokenStackedAreaChart'));
@@ -43,6 +44,7 @@ import {
   GitBranch,
   Heart,
   Layers,
+  Share2,
 } from 'lucide-react';
 import type { TurnSummary, SubagentSummary } from '../types';
 
@@ -82,6 +84,10 @@ function SessionDetail() {
   const { data: turns, isLoading: turnsLoading } = useTurns(id || '', 500, 0);
 
   const [activeTab, setActiveTab] = useState<TabId>('overview');
+  const [replayExport, setReplayExport] = useState<{ exporting: boolean; message: string | null }>({
+    exporting: false,
+    message: null,
+  });
 
   // Handle turn selection via query params (deep linking support)
   const selectedTurnNumber = searchParams.get('turn') ? parseInt(searchParams.get('turn')!, 10) : null;
@@ -178,6 +184,20 @@ function SessionDetail() {
     setSearchParams({ turn: turnNumber.toString() });
   }, [setSearchParams]);
 
+  const handleExportReplay = useCallback(async () => {
+    if (!id) return;
+    setReplayExport({ exporting: true, message: null });
+    try {
+      const path = await exportSessionReplay(id);
+      setReplayExport({ exporting: false, message: `Replay saved to ${path}` });
+    } catch (error) {
+      setReplayExport({
+        exporting: false,
+        message: error instanceof Error ? error.message : 'Replay export failed',
+      });
+    }
+  }, [id]);
+
   // Loading state
   if (sessionLoading) {
     return (
@@ -227,16 +247,34 @@ function SessionDetail() {
       <Header title={getProjectDisplayName(session.project_path)} subtitle={session.project_path} />
 
       <div className="flex-1 p-6 space-y-6">
-        {/* Back button */}
-        <Button
-          variant="ghost"
-          size="sm"
-          onClick={() => navigate('/sessions')}
-          className="gap-1.5"
-        >
-          <ArrowLeft className="h-4 w-4" />
-          Back to Sessions
-        </Button>
+        {/* Back button and replay export */}
+        <div className="flex items-center justify-between gap-4">
+          <Button
+            variant="ghost"
+            size="sm"
+            onClick={() => navigate('/sessions')}
+            className="gap-1.5"
+          >
+            <ArrowLeft className="h-4 w-4" />
+            Back to Sessions
+          </Button>
+          <div className="flex items-center gap-3 min-w-0">
+            {replayExport.message && (
+              <span className="text-xs text-gray-400 truncate">{replayExport.message}</span>
+            )}
+            <Button
+              variant="secondary"
+              size="sm"
+              onClick={handleExportReplay}
+              isLoading={replayExport.exporting}
+              disabled={replayExport.exporting}
+              className="gap-1.5 shrink-0"
+            >
+              <Share2 className="h-4 w-4" />
+              Share Replay
+            </Button>
+          </div>
+        </div>
 
         {/* Breadcrumb navigation */}
         <DrillDownBreadcrumb />
@@ -704,11 +742,19 @@ function TokensTab({ turns, turnsLoading, metrics }: TokensTabProps) {
               color="cyan"
             />
             <CostBreakdownRow
-              label="Cache Write Cost"
-              value={metrics.cost.cache_write_cost}
+              label="Cache Write Cost (5m)"
+              value={metrics.cost.cache_write_5m_cost}
               total={metrics.cost.total_cost}
               color="orange"
             />
+            {metrics.cost.cache_write_1h_cost > 0 && (
+              <CostBreakdownRow
+                label="Cache Write Cost (1h)"
+                value={metrics.cost.cache_write_1h_cost}
+                total={metrics.cost.total_cost}
+                color="amber"
+              />
+            )}
             <div className="border-t border-gray-700 pt-3 flex justify-between items-center">
               <span className="text-white font-medium">Total</span>
               <span className="text-white font-semibold">
@@ -755,7 +801,7 @@ interface CostBreakdownRowProps {
   label: string;
   value: number;
   total: number;
-  color: 'blue' | 'green' | 'cyan' | 'orange';
+  color: 'blue' | 'green' | 'cyan' | 'orange' | 'amber';
 }
 
 const costBarColors: Record<CostBreakdownRowProps['color'], string> = {
@@ -763,6 +809,7 @@ const costBarColors: Record<CostBreakdownRowProps['color'], string> = {
   green: 'bg-green-500',
   cyan: 'bg-cyan-500',
   orange: 'bg-orange-500',
+  amber: 'bg-amber-400',
 };
 
 function CostBreakdownRow({ label, value, total, color }: CostBreakdownRowProps) {
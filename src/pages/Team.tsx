@@ -0,0 +1,243 @@
+import { useState } from 'react';
+import { Header } from '../components/layout/Header';
+import { Card, CardHeader, CardTitle, CardContent, CardDescription } from '../components/ui/Card';
+import { Button } from '../components/ui/Button';
+import {
+  useTeamMembers,
+  useTeamDashboard,
+  useTeamTrends,
+  useImportTeamExport,
+  useRemoveTeamMember,
+} from '../hooks/useMetrics';
+import { formatCurrency, formatCompactNumber, formatRelativeTime } from '../lib/utils';
+import { Users, Upload, Trash2, DollarSign, MessageSquare, Zap } from 'lucide-react';
+
+const inputClass =
+  'w-full rounded-lg border border-[var(--color-border)] bg-[var(--color-background)] px-4 py-2 text-[var(--color-text-primary)] placeholder-[var(--color-text-muted)] focus:border-[var(--color-primary-500)] focus:outline-none focus:ring-1 focus:ring-[var(--color-primary-500)]';
+
+/** Days shown in the per-teammate daily table */
+const TREND_ROWS = 14;
+
+function ImportCard() {
+  const [userName, setUserName] = useState('');
+  const [path, setPath] = useState('');
+  const { mutate: importExport, isPending, data, error, reset } = useImportTeamExport();
+
+  const handleImport = () => {
+    reset();
+    importExport(
+      { path: path.trim(), userName: userName.trim() },
+      { onSuccess: () => setPath('') }
+    );
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <div className="flex items-center gap-2">
+          <Upload className="h-5 w-5 text-[var(--color-primary-400)]" />
+          <CardTitle>Import Teammate Export</CardTitle>
+        </div>
+        <CardDescription>
+          Each teammate exports their sessions as JSON from Ironhide. Imported sessions only appear on this page,
+          never in your own dashboard.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-3">
+        <div className="grid grid-cols-1 gap-4 md:grid-cols-[1fr_2fr_auto] md:items-end">
+          <div>
+            <label className="block text-sm font-medium text-[var(--color-text-secondary)] mb-2">Teammate</label>
+            <input
+              type="text"
+              value={userName}
+              onChange={(e) => setUserName(e.target.value)}
+              className={inputClass}
+              placeholder="alice"
+            />
+          </div>
+          <div>
+            <label className="block text-sm font-medium text-[var(--color-text-secondary)] mb-2">Export File</label>
+            <input
+              type="text"
+              value={path}
+              onChange={(e) => setPath(e.target.value)}
+              className={inputClass}
+              placeholder="~/Downloads/claude_sessions_20260205_101500.json"
+            />
+          </div>
+          <Button
+            onClick={handleImport}
+            isLoading={isPending}
+            disabled={isPending || !userName.trim() || !path.trim()}
+          >
+            Import
+          </Button>
+        </div>
+        {data && (
+          <p className="text-sm text-green-400">
+            {data.user_name}: {data.sessions_added} sessions added, {data.sessions_updated} updated
+          </p>
+        )}
+        {error && (
+          <p className="text-sm text-red-400">{error instanceof Error ? error.message : String(error)}</p>
+        )}
+      </CardContent>
+    </Card>
+  );
+}
+
+function Team() {
+  const { data: members } = useTeamMembers();
+  const { data: dashboard, isLoading } = useTeamDashboard();
+  const { data: trends } = useTeamTrends();
+  const { mutate: removeMember } = useRemoveTeamMember();
+
+  const lastImports = new Map((members ?? []).map((m) => [m.user_name, m.last_imported_at]));
+  const userNames = dashboard?.members.map((m) => m.user_name) ?? [];
+  const recentDays = (trends ?? []).slice(-TREND_ROWS).reverse();
+
+  const stats = [
+    { label: 'Team Cost', value: formatCurrency(dashboard?.total_cost ?? 0), icon: DollarSign },
+    { label: 'Sessions', value: String(dashboard?.total_sessions ?? 0), icon: MessageSquare },
+    { label: 'Tokens', value: formatCompactNumber(dashboard?.total_tokens ?? 0), icon: Zap },
+    { label: 'Teammates', value: String(members?.length ?? 0), icon: Users },
+  ];
+
+  return (
+    <div className="flex flex-col">
+      <Header title="Team" subtitle="Roll-up of your teammates' session exports" showQuickFilter />
+
+      <div className="flex-1 p-6 space-y-6">
+        <ImportCard />
+
+        <div className="grid grid-cols-2 gap-4 lg:grid-cols-4">
+          {stats.map(({ label, value, icon: Icon }) => (
+            <Card key={label}>
+              <CardContent className="flex items-start justify-between">
+                <div>
+                  <p className="text-sm font-medium text-gray-400">{label}</p>
+                  {isLoading ? (
+                    <div className="mt-1 h-8 w-24 animate-pulse rounded bg-gray-700" />
+                  ) : (
+                    <p className="mt-1 text-2xl font-bold text-white">{value}</p>
+                  )}
+                </div>
+                <div className="rounded-lg bg-[var(--color-primary-600)]/20 p-3">
+                  <Icon className="h-6 w-6 text-[var(--color-primary-400)]" />
+                </div>
+              </CardContent>
+            </Card>
+          ))}
+        </div>
+
+        <Card>
+          <CardHeader>
+            <CardTitle>By Teammate</CardTitle>
+            <CardDescription>Costliest first, for the selected time range</CardDescription>
+          </CardHeader>
+          <CardContent>
+            {userNames.length === 0 ? (
+              <p className="text-sm text-gray-400">No teammate sessions in this range.</p>
+            ) : (
+              <table className="w-full text-sm">
+                <thead>
+                  <tr className="border-b border-gray-700 text-left text-gray-400">
+                    <th className="py-2">Teammate</th>
+                    <th className="py-2 text-right">Sessions</th>
+                    <th className="py-2 text-right">Tokens</th>
+                    <th className="py-2 text-right">Cost</th>
+                    <th className="py-2 pl-6">Share</th>
+                    <th className="py-2 text-right">Avg / Session</th>
+                    <th className="py-2 text-right">CER</th>
+                    <th className="py-2 text-right">Imported</th>
+                    <th className="py-2" />
+                  </tr>
+                </thead>
+                <tbody>
+                  {dashboard?.members.map((member) => {
+                    const imported = lastImports.get(member.user_name);
+                    return (
+                      <tr key={member.user_name} className="border-b border-gray-800 text-gray-200">
+                        <td className="py-2 font-medium text-white">{member.user_name}</td>
+                        <td className="py-2 text-right">{member.session_count}</td>
+                        <td className="py-2 text-right">{formatCompactNumber(member.total_tokens)}</td>
+                        <td className="py-2 text-right">{formatCurrency(member.total_cost)}</td>
+                        <td className="py-2 pl-6">
+                          <div className="flex items-center gap-2">
+                            <div className="h-2 w-24 rounded bg-gray-700">
+                              <div
+                                className="h-2 rounded bg-[var(--color-primary-500)]"
+                                style={{ width: `${member.cost_share * 100}%` }}
+                              />
+                            </div>
+                            <span className="text-xs text-gray-400">{(member.cost_share * 100).toFixed(0)}%</span>
+                          </div>
+                        </td>
+                        <td className="py-2 text-right">{formatCurrency(member.avg_cost_per_session)}</td>
+                        <td className="py-2 text-right">
+                          {member.avg_efficiency != null ? `${(member.avg_efficiency * 100).toFixed(0)}%` : '-'}
+                        </td>
+                        <td className="py-2 text-right text-gray-400">
+                          {imported ? formatRelativeTime(imported) : '-'}
+                        </td>
+                        <td className="py-2 text-right">
+                          <button
+                            onClick={() => removeMember(member.user_name)}
+                            className="text-gray-500 hover:text-red-400"
+                            title={`Remove ${member.user_name}'s sessions`}
+                          >
+                            <Trash2 className="h-4 w-4" />
+                          </button>
+                        </td>
+                      </tr>
+                    );
+                  })}
+                </tbody>
+              </table>
+            )}
+          </CardContent>
+        </Card>
+
+        {recentDays.length > 0 && (
+          <Card>
+            <CardHeader>
+              <CardTitle>Daily Cost by Teammate</CardTitle>
+              <CardDescription>The last {recentDays.length} days with team activity</CardDescription>
+            </CardHeader>
+            <CardContent>
+              <table className="w-full text-sm">
+                <thead>
+                  <tr className="border-b border-gray-700 text-left text-gray-400">
+                    <th className="py-2">Date</th>
+                    {userNames.map((name) => (
+                      <th key={name} className="py-2 text-right">{name}</th>
+                    ))}
+                    <th className="py-2 text-right">Total</th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {recentDays.map((day) => (
+                    <tr key={day.date} className="border-b border-gray-800 text-gray-200">
+                      <td className="py-2">{day.date}</td>
+                      {userNames.map((name) => {
+                        const user = day.by_user.find((u) => u.user_name === name);
+                        return (
+                          <td key={name} className="py-2 text-right">
+                            {user ? formatCurrency(user.total_cost) : '-'}
+                          </td>
+                        );
+                      })}
+                      <td className="py-2 text-right font-medium text-white">{formatCurrency(day.total_cost)}</td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            </CardContent>
+          </Card>
+        )}
+      </div>
+    </div>
+  );
+}
+
+export default Team;
@@ -0,0 +1,2163 @@
+// Generated by `ironhide-bindings` from the Rust types; do not edit.
+// Regenerate with: cargo run --no-default-features --features bindings --bin ironhide-bindings
+
+/** Session summary for list views */
+export interface SessionSummary {
+  id: string;
+  project_path: string;
+  project_name: string;
+  started_at: string;
+  last_activity_at?: string | null;
+  model?: string | null;
+  total_cost: number;
+  /** Own cost plus the cost of the subagent sessions it spawned */
+  total_cost_including_subagents: number;
+  total_turns: number;
+  total_tokens: number;
+  duration_ms: number;
+  is_subagent: boolean;
+  file_path: string;
+  summary?: string | null;
+}
+
+/** Full session detail with all metrics */
+export interface SessionDetail {
+  id: string;
+  project_path: string;
+  project_name: string;
+  started_at: string;
+  last_activity_at?: string | null;
+  model?: string | null;
+  is_subagent: boolean;
+  file_path: string;
+  metrics: SessionMetricsResponse;
+  /** Own cost plus the cost of the subagent sessions it spawned */
+  total_cost_including_subagents: number;
+  /** Cost of the turns annotated as wasted */
+  wasted_cost: number;
+}
+
+/** Session metrics response */
+export interface SessionMetricsResponse {
+  tokens: TokenSummaryResponse;
+  cost: CostSummaryResponse;
+  efficiency: EfficiencyResponse;
+  /** Wall-clock time from the first turn to the last */
+  duration_ms: number;
+  /** Time spent in turns and in pauses of up to 15 minutes between them */
+  active_duration_ms: number;
+  /** Pauses between turns longer than 15 minutes */
+  idle_duration_ms: number;
+  turn_count: number;
+  tool_count: number;
+  unique_tools: string[];
+  models_used: string[];
+  subagent_count: number;
+  /** Times the conversation was compacted to free up context */
+  compaction_count: number;
+  /** Context tokens dropped by those compactions */
+  tokens_lost_to_compaction: number;
+}
+
+/** Token summary response */
+export interface TokenSummaryResponse {
+  input: number;
+  output: number;
+  cache_read: number;
+  cache_write_5m: number;
+  cache_write_1h: number;
+  total: number;
+  context_used_pct: number;
+  /** Output tokens spent on extended thinking (estimated) */
+  thinking: number;
+  /** Thinking tokens as a fraction (0-1) of output tokens */
+  thinking_share: number;
+}
+
+/** Cost summary response */
+export interface CostSummaryResponse {
+  input_cost: number;
+  output_cost: number;
+  cache_read_cost: number;
+  /** 5-minute and 1-hour cache writes together */
+  cache_write_cost: number;
+  cache_write_5m_cost: number;
+  cache_write_1h_cost: number;
+  total_cost: number;
+  avg_cost_per_turn: number;
+  /** Part of the output cost spent on extended thinking */
+  thinking_cost: number;
+}
+
+/** Efficiency metrics response */
+export interface EfficiencyResponse {
+  cer: number;
+  cgr: number;
+  sei?: number | null;
+  wfs: number;
+  cpdu: number;
+  cpd: number;
+  oes_score: number;
+  oes_grade: string;
+  /** Deliverable units CPDU and CPD are based on */
+  deliverable_units: number;
+  /** Estimator that produced them; code changes fall back to tool usage for sessions that changed no files */
+  deliverable_estimator: DeliverableEstimator;
+}
+
+/** How deliverable units are estimated */
+export type DeliverableEstimator = 'output_tokens' | 'tool_usage' | 'code_changes';
+
+/** Outcome of `warm_project_cache` */
+export interface ProjectWarmupStats {
+  project_path: string;
+  /** Session files of the project, subagents included */
+  sessions: number;
+  /** Sessions whose turns were already in memory */
+  already_cached: number;
+  /** Sessions loaded from the database or parsed */
+  loaded: number;
+  /** Sessions whose file couldn't be read */
+  failed: number;
+  elapsed_ms: number;
+}
+
+/** A queued, running or finished job */
+export interface Job {
+  id: number;
+  kind: JobKind;
+  /** Kind-specific arguments, as JSON */
+  payload?: string | null;
+  state: JobState;
+  /** Higher runs first */
+  priority: number;
+  attempts: number;
+  max_attempts: number;
+  /** Error of the last failed attempt */
+  last_error?: string | null;
+  created_at: string;
+  started_at?: string | null;
+  finished_at?: string | null;
+  /** Earliest time a retry may run */
+  run_after?: string | null;
+}
+
+/** What a job does */
+export type JobKind = 'backfill' | 'preload_remaining' | 'export' | 'daily_digest';
+
+/** Where a job is in its lifecycle */
+export type JobState = 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled';
+
+/** Turn summary for list views */
+export interface TurnSummary {
+  turn_number: number;
+  started_at: string;
+  ended_at?: string | null;
+  duration_ms?: number | null;
+  user_message?: string | null;
+  assistant_message?: string | null;
+  model?: string | null;
+  tokens: TurnTokensResponse;
+  cost: number;
+  tool_count: number;
+  tools_used: string[];
+  has_subagents: boolean;
+  stop_reason?: string | null;
+}
+
+/** Turn tokens response */
+export interface TurnTokensResponse {
+  input: number;
+  output: number;
+  cache_read: number;
+  cache_write: number;
+  total: number;
+  /** Output tokens spent on extended thinking (estimated) */
+  thinking: number;
+}
+
+/** One turn on a session's cost curve */
+export interface CostCurvePoint {
+  turn_number: number;
+  started_at: string;
+  model?: string | null;
+  cost: number;
+  tokens: number;
+  /** Session cost up to and including this turn */
+  cumulative_cost: number;
+  cumulative_tokens: number;
+}
+
+/**
+ * A label and note a user attached to a turn
+ *
+ * Keyed by session and turn number rather than turn ID, so annotations outlive the turn rows being rewritten when a session is stored again.
+ */
+export interface TurnAnnotation {
+  session_id: string;
+  turn_number: number;
+  label: AnnotationLabel;
+  note?: string | null;
+  annotated_at: string;
+  /** Cost of the turn as stored; 0 while the turn isn't in the database */
+  cost?: number;
+}
+
+/** A user's verdict on a turn */
+export type AnnotationLabel = 'wasted' | 'valuable';
+
+/** Entries a reader couldn't use, collected while reading a session file */
+export interface ParseDiagnostics {
+  /** Non-empty lines read */
+  total_lines: number;
+  malformed_lines: number;
+  unknown_entries: number;
+  /** Entries of known types that hold no turn data (summaries, file history snapshots, system events other than compactions and API errors) */
+  ignored_entries: number;
+  unknown_content_blocks: number;
+  /** Count of unknown and ignored entries by type label */
+  entry_types: Record<string, number>;
+  /** Malformed, unknown and partly understood entries, up to [`MAX_DIAGNOSTIC_ENTRIES`] of them */
+  entries: UnparsedEntry[];
+}
+
+/** An entry that was skipped or only partly understood */
+export interface UnparsedEntry {
+  /** Byte offset of the line in the (decompressed) file */
+  offset: number;
+  kind: UnparsedKind;
+  /** See [`Entry::type_label`]; None for malformed lines */
+  entry_type?: string | null;
+  timestamp?: string | null;
+  /** JSON error for malformed lines */
+  error?: string | null;
+  /** The entry as written, or the (truncated) line text if it isn't JSON */
+  raw: unknown;
+}
+
+/** Why an entry didn't make it into turn aggregation */
+export type UnparsedKind = 'malformed' | 'unknown_type' | 'unknown_content';
+
+/** A page of a session file's entries */
+export interface RawEntryPage {
+  /** Non-empty lines in the file */
+  total: number;
+  entries: RawEntry[];
+}
+
+/** One line of a session file as written, for debugging */
+export interface RawEntry {
+  /** Position among the file's non-empty lines, from 0 */
+  index: number;
+  /** Byte offset of the line in the (decompressed) file */
+  offset: number;
+  /** How the parser classified the entry; None for malformed lines */
+  entry_type?: EntryType | null;
+  /** See [`Entry::type_label`]; None for malformed lines */
+  type_label?: string | null;
+  uuid?: string | null;
+  parent_uuid?: string | null;
+  timestamp?: string | null;
+  /** JSON error for malformed lines */
+  error?: string | null;
+  /** The entry as written, or the (truncated) line text if it isn't JSON */
+  raw: unknown;
+}
+
+/** Entry types in Claude Code JSONL files */
+export type EntryType = 'user' | 'assistant' | 'progress' | 'summary' | 'file-history-snapshot' | 'unknown' | 'system';
+
+/** Per-turn context usage of a session */
+export interface ContextTimeline {
+  session_id: string;
+  points: ContextPoint[];
+  compactions: CompactionEvent[];
+  peak_utilization_pct: number;
+}
+
+/** Context usage of one turn */
+export interface ContextPoint {
+  turn_number: number;
+  started_at: string;
+  model?: string | null;
+  /** Largest input + cache read tokens of a single request in the turn */
+  context_tokens: number;
+  /** Context window of the turn's model */
+  context_limit: number;
+  utilization_pct: number;
+}
+
+/** A point where the conversation was summarized to free up context */
+export interface CompactionEvent {
+  /** First turn to run on the compacted context */
+  turn_number: number;
+  timestamp?: string | null;
+  /** "auto" or "manual" as recorded by Claude Code; None when inferred */
+  trigger?: string | null;
+  /** Context tokens just before compacting */
+  pre_tokens?: number | null;
+  /** Detected from a drop in context usage rather than a boundary entry */
+  inferred: boolean;
+}
+
+/** A session's conversation, or one page of it */
+export interface Transcript {
+  session_id: string;
+  messages: TranscriptMessage[];
+  /** Messages in the full transcript, before paging */
+  total_messages: number;
+  /** Secrets replaced across the returned messages */
+  redactions: number;
+}
+
+/** One message of a transcript */
+export interface TranscriptMessage {
+  /** Position in the full transcript */
+  index: number;
+  role: TranscriptRole;
+  uuid?: string | null;
+  timestamp?: string | null;
+  text?: string | null;
+  /** Tool calls and results: the tool call they belong to */
+  tool_use_id?: string | null;
+  tool_name?: string | null;
+  tool_input?: unknown;
+  is_error: boolean;
+  /** `text` was cut to the configured length */
+  truncated: boolean;
+  model?: string | null;
+  is_sidechain: boolean;
+}
+
+/** Kind of transcript message */
+export type TranscriptRole = 'user' | 'assistant' | 'thinking' | 'tool_use' | 'tool_result';
+
+/** Subagent summary for detailed subagent tracking */
+export interface SubagentSummary {
+  agent_id: string;
+  slug?: string | null;
+  turn_count: number;
+  total_cost: number;
+  total_tokens: number;
+  tools_used: string[];
+}
+
+/** A session or subagent in the subagent tree */
+export interface SubagentNode {
+  /** Session ID for the root, agent ID for subagents */
+  id: string;
+  is_subagent: boolean;
+  /** Task description the agent was spawned with */
+  description?: string | null;
+  subagent_type?: string | null;
+  started_at?: string | null;
+  turn_count: number;
+  tools_used: string[];
+  /** Cost of this node's own turns */
+  cost: number;
+  /** Tokens of this node's own turns */
+  tokens: number;
+  /** Cost including all descendants */
+  total_cost: number;
+  /** Tokens including all descendants */
+  total_tokens: number;
+  /** Number of descendants */
+  descendant_count: number;
+  children: SubagentNode[];
+}
+
+/** Session comparison result */
+export interface SessionComparison {
+  sessions: SessionSummary[];
+  metrics_comparison: MetricsComparison;
+  /** Structured diff of each session after the first against the first */
+  detailed_comparisons: MetricsComparisonDetailed[];
+}
+
+/** Metrics comparison between sessions */
+export interface MetricsComparison {
+  cost_diff: number;
+  token_diff: number;
+  efficiency_diff: number;
+  duration_diff: number;
+}
+
+/** Structured diff of a session against a baseline session */
+export interface MetricsComparisonDetailed {
+  baseline_id: string;
+  session_id: string;
+  efficiency: EfficiencyDeltas;
+  totals: TotalDeltas;
+  per_turn: PerTurnDeltas;
+  /** Every tool used by either session, largest absolute change first */
+  tool_usage: ToolUsageDelta[];
+  models: ModelDiff;
+}
+
+/** Efficiency score deltas */
+export interface EfficiencyDeltas {
+  cer: MetricDelta;
+  cgr: MetricDelta;
+  wfs: MetricDelta;
+  oes: MetricDelta;
+}
+
+/** A metric in the baseline and the compared session */
+export interface MetricDelta {
+  baseline: number;
+  value: number;
+  /** `value - baseline` */
+  diff: number;
+  /** Change relative to the baseline, in percent */
+  percent_change: number;
+}
+
+/** Total deltas */
+export interface TotalDeltas {
+  cost: MetricDelta;
+  tokens: MetricDelta;
+  duration_ms: MetricDelta;
+  turns: MetricDelta;
+  tool_calls: MetricDelta;
+}
+
+/** Per-turn average deltas */
+export interface PerTurnDeltas {
+  cost: MetricDelta;
+  tokens: MetricDelta;
+  duration_ms: MetricDelta;
+  tool_calls: MetricDelta;
+}
+
+/** Calls of one tool in each session */
+export interface ToolUsageDelta {
+  tool: string;
+  baseline_count: number;
+  count: number;
+  diff: number;
+}
+
+/** Models used by each session */
+export interface ModelDiff {
+  common: string[];
+  only_baseline: string[];
+  only_compared: string[];
+}
+
+/** Code change tracked during a session */
+export interface CodeChange {
+  file_path: string;
+  change_type: string;
+  tool_name: string;
+  turn_number: number;
+  timestamp: string;
+}
+
+/** Every stored change to a file across sessions */
+export interface FileHistory {
+  file_path: string;
+  total_changes: number;
+  creates: number;
+  edits: number;
+  deletes: number;
+  sessions: number;
+  turns: number;
+  first_changed_at?: string | null;
+  last_changed_at?: string | null;
+  /** Cost of the turns that changed the file, each split evenly between the files the turn changed */
+  estimated_cost: number;
+  /** Newest first */
+  changes: FileChangeEntry[];
+}
+
+/** One change to a file, with where it was made */
+export interface FileChangeEntry {
+  session_id: string;
+  project_path: string;
+  project_name: string;
+  branch?: string | null;
+  /** Import source or team label of the session; None for this machine */
+  source?: string | null;
+  turn_number: number;
+  timestamp: string;
+  /** "Write", "Edit", "NotebookEdit" or "Bash" */
+  tool_name?: string | null;
+  /** "create", "edit" or "delete" */
+  change_type: string;
+}
+
+/** How often one file of a project was changed */
+export interface FileChangeSummary {
+  file_path: string;
+  /** Write, Edit and NotebookEdit operations on the file */
+  changes: number;
+  sessions: number;
+  last_changed_at: string;
+}
+
+/** File-level rework in a session */
+export interface ReworkAnalysis {
+  total_edits: number;
+  rework_edits: number;
+  /** Turns that changed a file already changed in an earlier turn */
+  rework_cycles: number;
+  /** Files changed more than once, highest churn first */
+  files: FileChurn[];
+}
+
+/** Change history of one file */
+export interface FileChurn {
+  file_path: string;
+  /** Write, Edit and NotebookEdit operations on the file */
+  edits: number;
+  /** Turns that changed the file, in order */
+  turns: number[];
+  /** Edits made after the turn that first changed the file */
+  rework_edits: number;
+  /** Later turns that changed the file again, plus a quarter point for each extra edit within a turn */
+  churn_score: number;
+}
+
+/** A tool call that failed */
+export interface ToolFailure {
+  turn_number: number;
+  timestamp: string;
+  tool_use_id: string;
+  tool_name: string;
+  /** What was attempted: the command, file path, pattern or URL, when the tool has one */
+  target?: string | null;
+  input?: unknown;
+  error?: string | null;
+}
+
+/** Per-turn cache usage of a session and where its efficiency dropped */
+export interface CacheBreakdown {
+  session_id: string;
+  turns: CacheTurn[];
+  drops: CacheDrop[];
+  /** CER of the whole session */
+  session_cer?: number | null;
+}
+
+/** Cache usage of one turn */
+export interface CacheTurn {
+  turn_number: number;
+  started_at: string;
+  fresh_input_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  /** Shares of the turn's input side (0-1); zero when it had no input */
+  fresh_ratio: number;
+  cache_read_ratio: number;
+  cache_write_ratio: number;
+  /** None when the turn neither read nor wrote the cache */
+  cer?: number | null;
+}
+
+/** A turn whose CER fell sharply from the turn before */
+export interface CacheDrop {
+  turn_number: number;
+  previous_cer: number;
+  cer: number;
+  cause: CacheDropCause;
+  /** e.g. the models switched between, the pause length or the files read */
+  detail?: string | null;
+}
+
+/** Why cache efficiency dropped at a turn */
+export type CacheDropCause = 'compaction' | 'model_switch' | 'cache_expired' | 'large_file_read' | 'unknown';
+
+/** Rolling cost per turn and CER of a session, and where it degraded */
+export interface SessionEfficiencyCurve {
+  session_id: string;
+  window: number;
+  points: EfficiencyCurvePoint[];
+  /** Rolling cost per turn of the first full window; None for sessions shorter than the window */
+  baseline_cost_per_turn?: number | null;
+  /** First turn whose rolling cost per turn reached twice the baseline */
+  degraded_at_turn?: number | null;
+  /** Cost of the turns from `degraded_at_turn` on */
+  cost_since_degraded: number;
+}
+
+/** One turn with the averages of the window ending at it */
+export interface EfficiencyCurvePoint {
+  turn_number: number;
+  started_at: string;
+  /** Context sent with the turn, in tokens */
+  context_tokens: number;
+  cost: number;
+  /** Average cost of the turns in the window */
+  rolling_cost_per_turn: number;
+  /** CER of the turns in the window; None when none of them used the cache */
+  rolling_cer?: number | null;
+  /** Turns in the window; fewer than the window size at the session start */
+  window_turns: number;
+}
+
+/** Health score of a session and its components */
+export interface SessionHealth {
+  session_id: string;
+  /** Weighted mean of the component scores, 0-100 */
+  score: number;
+  grade: string;
+  components: HealthComponent[];
+}
+
+/** One component of the health score */
+export interface HealthComponent {
+  /** "efficiency", "antipatterns", "tool_failures" or "rework" */
+  name: string;
+  label: string;
+  /** The measurement: OES, pattern count, failure rate or rework ratio */
+  value: number;
+  /** Component score, 0-100 */
+  score: number;
+  /** Share of the health score, after normalizing the weights */
+  weight: number;
+}
+
+/** The session currently being worked in */
+export interface ActiveSession {
+  session_id: string;
+  project_path?: string | null;
+  last_modified: string;
+  turn_count: number;
+  total_tokens: number;
+  total_cost: number;
+}
+
+/** A named template, built in or saved in settings */
+export interface SummaryTemplate {
+  name: string;
+  template: string;
+}
+
+/** Git context for a session */
+export interface SessionGitContext {
+  session_id: string;
+  project_path: string;
+  /** Whether the project path is inside a git work tree */
+  is_git_repo: boolean;
+  /** Branch recorded for the session, if any */
+  branch?: string | null;
+  /** Start of the window commits were matched against (RFC 3339) */
+  window_start?: string | null;
+  /** End of the window commits were matched against (RFC 3339) */
+  window_end?: string | null;
+  /** Commits authored during the session, oldest first */
+  commits: GitCommit[];
+}
+
+/** A commit made in the project repository */
+export interface GitCommit {
+  sha: string;
+  short_sha: string;
+  author: string;
+  /** Author date (RFC 3339) */
+  authored_at: string;
+  subject: string;
+}
+
+/** Dashboard summary response */
+export interface DashboardSummaryResponse {
+  total_sessions: number;
+  user_sessions: number;
+  subagent_sessions: number;
+  total_cost: number;
+  total_turns: number;
+  total_tokens: number;
+  avg_cost_per_session: number;
+  avg_turns_per_session: number;
+  avg_efficiency_score?: number | null;
+  active_projects: number;
+  /** Anti-pattern found most often in the period, if any were found */
+  most_frequent_antipattern?: AntiPatternFrequency | null;
+  /** Most sessions (subagents included) generating at the same moment */
+  peak_concurrent_sessions: number;
+  /** Fraction of the period's turn cost spent while sessions overlapped (0-1) */
+  concurrent_cost_share: number;
+}
+
+/** How often an anti-pattern type occurred */
+export interface AntiPatternFrequency {
+  pattern_type: AntiPatternType;
+  display_name: string;
+  occurrences: number;
+  /** Sessions it was found in */
+  sessions: number;
+}
+
+/** Types of anti-patterns that can be detected in Claude Code sessions */
+export type AntiPatternType = 'subagent_sprawl' | 'context_churn' | 'cost_spike' | 'long_turn' | 'tool_failure_spree' | 'high_rework_ratio' | 'compaction_heavy' | 'retry_storm';
+
+/** Daily metrics response */
+export interface DailyMetricsResponse {
+  date: string;
+  session_count: number;
+  user_session_count: number;
+  subagent_session_count: number;
+  total_turns: number;
+  total_cost: number;
+  total_tokens: number;
+  avg_efficiency_score?: number | null;
+}
+
+/** Project metrics response */
+export interface ProjectMetricsResponse {
+  project_path: string;
+  project_name: string;
+  session_count: number;
+  total_cost: number;
+  total_turns: number;
+  total_tokens: number;
+  avg_cost_per_session: number;
+  last_activity: string;
+  /** Cost of the turns annotated as wasted */
+  wasted_cost: number;
+  /** Most changed first, then those only known from root files */
+  languages: string[];
+  /** Sorted by name */
+  frameworks: string[];
+}
+
+/** Benchmarks of every project against the global baseline */
+export interface ProjectBenchmarks {
+  baseline: BenchmarkStats;
+  /** Worst cache efficiency relative to the baseline first */
+  projects: ProjectBenchmark[];
+}
+
+/** Averages over a group of sessions */
+export interface BenchmarkStats {
+  session_count: number;
+  cost_per_turn: number;
+  /** Cache Efficiency Ratio over all of the sessions' cache tokens */
+  cer: number;
+  /** Mean Overall Efficiency Score */
+  oes: number;
+  turns_per_session: number;
+}
+
+/** Benchmark of one project */
+export interface ProjectBenchmark {
+  project_path: string;
+  project_name: string;
+  stats: BenchmarkStats;
+  /** Compared with all sessions */
+  vs_baseline: BenchmarkDeltas;
+  /** Compared with all other projects combined, when requested and there are other projects */
+  vs_peers?: BenchmarkDeltas | null;
+  /** CER at least `POOR_CER_MARGIN` below the baseline, over at least `MIN_SESSIONS_FOR_FLAG` sessions */
+  poor_cache_efficiency: boolean;
+}
+
+/** A project's averages next to those of a reference group */
+export interface BenchmarkDeltas {
+  cost_per_turn: MetricDelta;
+  cer: MetricDelta;
+  oes: MetricDelta;
+  turns_per_session: MetricDelta;
+}
+
+/** Chronological activity of a project */
+export interface ProjectTimeline {
+  project_path: string;
+  project_name: string;
+  sessions: ProjectTimelineSession[];
+  total_cost: number;
+  total_code_changes: number;
+  longest_gap_ms: number;
+}
+
+/** One session on a project's activity timeline */
+export interface ProjectTimelineSession {
+  session_id: string;
+  started_at: string;
+  last_activity_at?: string | null;
+  model?: string | null;
+  total_turns: number;
+  duration_ms: number;
+  total_cost: number;
+  total_tokens: number;
+  /** Cache efficiency ratio (CER) of the session */
+  efficiency_score?: number | null;
+  /** Files changed with Write, Edit or NotebookEdit */
+  code_changes: number;
+  /** Idle time since the previous session ended; None for the first session, 0 when it overlaps the previous one */
+  gap_ms?: number | null;
+}
+
+/**
+ * Usage grid indexed as `[day_of_week][hour]`
+ *
+ * Days run Monday (0) to Sunday (6); hours are local time.
+ */
+export interface HourlyUsage {
+  tokens: number[][];
+  cost: number[][];
+  /** Distinct sessions with at least one turn in the slot */
+  sessions: number[][];
+  turns: number[][];
+  total_tokens: number;
+  total_cost: number;
+  total_turns: number;
+}
+
+/** Usage within one rolling window ending now */
+export interface UsageWindow {
+  label: string;
+  hours: number;
+  /** Start of the window (RFC 3339) */
+  window_start: string;
+  tokens: number;
+  cost: number;
+  turns: number;
+  sessions: number;
+  token_threshold?: number | null;
+  cost_threshold?: number | null;
+  /** Tokens used as a percentage of the token threshold (0-100+) */
+  token_pct?: number | null;
+  /** Cost as a percentage of the cost threshold (0-100+) */
+  cost_pct?: number | null;
+  /** Whether either threshold has been reached */
+  exceeded: boolean;
+  /** When the oldest turn in the window drops out of it, freeing up usage */
+  next_rollover?: string | null;
+}
+
+/** Billing blocks over a period, oldest first, and the open block's headroom */
+export interface BillingBlocks {
+  blocks: BillingBlock[];
+  /** Set while the last block is open */
+  current?: BlockHeadroom | null;
+}
+
+/** Usage within one billing block */
+export interface BillingBlock {
+  /** Start of the block (RFC 3339), on the hour */
+  start: string;
+  /** When the block closes (RFC 3339) */
+  end: string;
+  /** Start of the block's last turn (RFC 3339) */
+  last_activity: string;
+  /** Whether the block is still open */
+  is_active: boolean;
+  tokens: number;
+  cost: number;
+  turns: number;
+  sessions: number;
+}
+
+/** How much of the plan's 5-hour limits the open block has left */
+export interface BlockHeadroom {
+  token_threshold?: number | null;
+  cost_threshold?: number | null;
+  /** Tokens used as a percentage of the token threshold (0-100+) */
+  token_pct?: number | null;
+  /** Cost as a percentage of the cost threshold (0-100+) */
+  cost_pct?: number | null;
+  /** Tokens left before the threshold, 0 once reached */
+  tokens_remaining?: number | null;
+  /** USD left before the threshold, 0 once reached */
+  cost_remaining?: number | null;
+  minutes_remaining: number;
+  /** Tokens by the end of the block if usage keeps its pace so far */
+  projected_tokens: number;
+  /** Cost by the end of the block if usage keeps its pace so far */
+  projected_cost: number;
+}
+
+/** Tool calls by tool and by MCP server */
+export interface ToolUsageStats {
+  total_calls: number;
+  /** Calls of MCP tools, included in `total_calls` */
+  mcp_calls: number;
+  /** Every tool, MCP tools under their full name, most called first */
+  tools: ToolCallCount[];
+  /** Most called first */
+  mcp_servers: McpServerUsage[];
+}
+
+/** Calls of one tool */
+export interface ToolCallCount {
+  tool: string;
+  calls: number;
+  /** Calls whose result was an error */
+  errors: number;
+}
+
+/** Calls of the tools of one MCP server */
+export interface McpServerUsage {
+  server: string;
+  calls: number;
+  errors: number;
+  /** By tool name without the server prefix, most called first */
+  tools: ToolCallCount[];
+}
+
+/** Result of a savings simulation */
+export interface SavingsSimulation {
+  scenarios: SavingsScenario[];
+  /** Days of history simulated; None for all of it */
+  days?: number | null;
+  session_count: number;
+  /** Sessions with at least one turn a scenario changed */
+  affected_sessions: number;
+  actual_cost: number;
+  projected_cost: number;
+  /** Negative when the scenarios would have cost more */
+  savings: number;
+  savings_percent: number;
+  /** Savings per 30 days over the simulated period */
+  monthly_savings?: number | null;
+  /** Projects with the largest savings */
+  top_projects: ProjectSavings[];
+}
+
+/** A hypothetical change to past usage */
+export type SavingsScenario = {
+  kind: 'switch_model';
+  from: string;
+  to: string;
+} | {
+  kind: 'improve_cer';
+  target: number;
+};
+
+/** Savings within one project */
+export interface ProjectSavings {
+  project_path: string;
+  actual_cost: number;
+  projected_cost: number;
+  savings: number;
+}
+
+/** MCP usage over a period, busiest server first */
+export interface McpStats {
+  total_calls: number;
+  total_errors: number;
+  result_tokens: number;
+  estimated_cost: number;
+  servers: McpServerStats[];
+}
+
+/** Usage of one MCP server */
+export interface McpServerStats {
+  server: string;
+  calls: number;
+  errors: number;
+  /** Errors as a share of calls (0-1) */
+  failure_rate: number;
+  /** Sessions that called the server */
+  sessions: number;
+  result_tokens: number;
+  estimated_cost: number;
+  /** Estimated cost of the results of failed calls */
+  failed_cost: number;
+  /** Most called first */
+  tools: McpToolStats[];
+}
+
+/** Calls of one tool of an MCP server */
+export interface McpToolStats {
+  /** Tool name without the server prefix */
+  tool: string;
+  calls: number;
+  errors: number;
+  /** Errors as a share of calls (0-1) */
+  failure_rate: number;
+  /** Estimated tokens of the tool's results */
+  result_tokens: number;
+  /** Result tokens priced as input */
+  estimated_cost: number;
+}
+
+/** Concurrent windows over a period and how much of its cost they hold */
+export interface ConcurrencyReport {
+  /** Costliest windows first, at most [`MAX_WINDOWS`] */
+  windows: ConcurrencyWindow[];
+  window_count: number;
+  peak_concurrency: number;
+  /** Time with two or more sessions running */
+  concurrent_ms: number;
+  concurrent_cost: number;
+  total_cost: number;
+  /** Fraction of total cost spent in concurrent windows (0-1) */
+  concurrent_cost_share: number;
+}
+
+/** A stretch of time with turns from two or more sessions running at once */
+export interface ConcurrencyWindow {
+  started_at: string;
+  ended_at: string;
+  duration_ms: number;
+  /** Most sessions running at the same moment */
+  peak_sessions: number;
+  /** Every session with a turn running during the window */
+  session_ids: string[];
+  /** How many of those sessions are subagents */
+  subagent_sessions: number;
+  projects: string[];
+  /** Turn cost prorated to the part of each turn inside the window */
+  cost: number;
+}
+
+/** API error totals, per day (oldest first) and per session (most errors first) */
+export interface ApiErrorStats {
+  totals: ApiErrorCounts;
+  by_day: DailyApiErrors[];
+  by_session: SessionApiErrors[];
+}
+
+/** API errors counted by kind */
+export interface ApiErrorCounts {
+  total: number;
+  rate_limit: number;
+  overloaded: number;
+  other: number;
+  /** Errors Claude Code gave up on and showed */
+  retries_exhausted: number;
+  /** Time spent waiting to retry */
+  retry_wait_ms: number;
+}
+
+/** API errors of one calendar day (reporting timezone) */
+export interface DailyApiErrors {
+  date: string;
+  total: number;
+  rate_limit: number;
+  overloaded: number;
+  other: number;
+  /** Errors Claude Code gave up on and showed */
+  retries_exhausted: number;
+  /** Time spent waiting to retry */
+  retry_wait_ms: number;
+}
+
+/** API errors of one session */
+export interface SessionApiErrors {
+  session_id: string;
+  project_name: string;
+  first_error_at?: string | null;
+  last_error_at?: string | null;
+  total: number;
+  rate_limit: number;
+  overloaded: number;
+  other: number;
+  /** Errors Claude Code gave up on and showed */
+  retries_exhausted: number;
+  /** Time spent waiting to retry */
+  retry_wait_ms: number;
+}
+
+/** Thinking totals overall, per day (oldest first) and per thinking mode (most turns first) */
+export interface ThinkingTrend {
+  totals: ThinkingTotals;
+  by_day: DailyThinking[];
+  by_mode: ThinkingModeUsage[];
+}
+
+/** Thinking totals of a set of turns */
+export interface ThinkingTotals {
+  turns: number;
+  turns_with_thinking: number;
+  output_tokens: number;
+  thinking_tokens: number;
+  /** Thinking tokens as a fraction (0-1) of output tokens */
+  thinking_share: number;
+  /** Cost of the thinking tokens */
+  thinking_cost: number;
+  /** Cost of the turns, thinking included */
+  cost: number;
+}
+
+/** Thinking totals of one calendar day (reporting timezone) */
+export interface DailyThinking {
+  date: string;
+  turns: number;
+  turns_with_thinking: number;
+  output_tokens: number;
+  thinking_tokens: number;
+  /** Thinking tokens as a fraction (0-1) of output tokens */
+  thinking_share: number;
+  /** Cost of the thinking tokens */
+  thinking_cost: number;
+  /** Cost of the turns, thinking included */
+  cost: number;
+}
+
+/** Thinking totals of the turns sent with one thinking mode */
+export interface ThinkingModeUsage {
+  /** Level such as "high", "off", or [`UNSPECIFIED_MODE`] */
+  mode: string;
+  avg_output_per_turn: number;
+  avg_cost_per_turn: number;
+  turns: number;
+  turns_with_thinking: number;
+  output_tokens: number;
+  thinking_tokens: number;
+  /** Thinking tokens as a fraction (0-1) of output tokens */
+  thinking_share: number;
+  /** Cost of the thinking tokens */
+  thinking_cost: number;
+  /** Cost of the turns, thinking included */
+  cost: number;
+}
+
+/** Burn rate overall, per day (oldest first) and per project (costliest first) */
+export interface BurnRateReport {
+  totals: BurnRate;
+  by_day: DailyBurnRate[];
+  by_project: ProjectBurnRate[];
+  /** Change in cost per hour per day, fitted over the days with active time; positive when an hour of work is getting more expensive */
+  trend_per_day: number;
+}
+
+/** Cost and active time of a set of turns */
+export interface BurnRate {
+  active_hours: number;
+  cost: number;
+  /** Cost divided by active hours; 0 without active time */
+  cost_per_hour: number;
+}
+
+/** Burn rate of one calendar day (reporting timezone) */
+export interface DailyBurnRate {
+  date: string;
+  active_hours: number;
+  cost: number;
+  /** Cost divided by active hours; 0 without active time */
+  cost_per_hour: number;
+}
+
+/** Burn rate of one project */
+export interface ProjectBurnRate {
+  project_path: string;
+  project_name: string;
+  active_hours: number;
+  cost: number;
+  /** Cost divided by active hours; 0 without active time */
+  cost_per_hour: number;
+}
+
+/** How session duration, cost, turn count and token use are spread */
+export interface SessionDistributions {
+  session_count: number;
+  duration_ms: Distribution;
+  cost: Distribution;
+  turns: Distribution;
+  tokens: Distribution;
+}
+
+/** Summary statistics and histogram of a set of values */
+export interface Distribution {
+  count: number;
+  min: number;
+  max: number;
+  mean: number;
+  p50: number;
+  p90: number;
+  p99: number;
+  buckets: HistogramBucket[];
+}
+
+/** Values from `start` (inclusive) up to `end` (exclusive; inclusive for the last bucket) */
+export interface HistogramBucket {
+  start: number;
+  end: number;
+  count: number;
+}
+
+/**
+ * Trend data at the requested granularity
+ *
+ * Serialized as a bare array so daily results keep their existing shape.
+ */
+export type TrendSeries = DailyTrend[] | WeeklyTrend[] | MonthlyTrend[];
+
+/** Represents a single day's aggregated data */
+export interface DailyTrend {
+  /** Date in YYYY-MM-DD format */
+  date: string;
+  /** Number of sessions on this day */
+  sessions: number;
+  /** Total number of turns across all sessions */
+  turns: number;
+  /** Total tokens used */
+  total_tokens: number;
+  /** Total cost in USD */
+  total_cost: number;
+  /** Average efficiency score (OES) for the day */
+  avg_efficiency: number;
+}
+
+/** Represents a weekly aggregation */
+export interface WeeklyTrend {
+  /** Week start date in YYYY-MM-DD format (Monday) */
+  week_start: string;
+  /** Week end date in YYYY-MM-DD format (Sunday) */
+  week_end: string;
+  /** ISO week number */
+  week_number: number;
+  /** Number of sessions this week */
+  sessions: number;
+  /** Total number of turns */
+  turns: number;
+  /** Total tokens used */
+  total_tokens: number;
+  /** Total cost in USD */
+  total_cost: number;
+  /** Average efficiency score */
+  avg_efficiency: number;
+  /** Change vs the previous week */
+  comparison?: PeriodComparison | null;
+  /** Daily breakdown */
+  daily: DailyTrend[];
+}
+
+/** Percentage changes of a period vs the one before it Positive = increase, Negative = decrease */
+export interface PeriodComparison {
+  sessions_change_percent: number;
+  tokens_change_percent: number;
+  cost_change_percent: number;
+  efficiency_change_percent: number;
+}
+
+/** Represents a monthly aggregation */
+export interface MonthlyTrend {
+  /** Month in YYYY-MM format */
+  month: string;
+  /** Number of sessions this month */
+  sessions: number;
+  /** Total number of turns */
+  turns: number;
+  /** Total tokens used */
+  total_tokens: number;
+  /** Total cost in USD */
+  total_cost: number;
+  /** Average efficiency score */
+  avg_efficiency: number;
+  /** Change vs the previous month */
+  comparison?: PeriodComparison | null;
+  /** Weekly breakdown */
+  weekly: WeeklyTrend[];
+}
+
+/** A day of unusual spend with the sessions that drove it */
+export interface CostAnomalyResponse {
+  /** Sessions started that day, most expensive first */
+  top_sessions: SessionSummary[];
+  /** Date in YYYY-MM-DD format */
+  date: string;
+  cost: number;
+  sessions: number;
+  /** Mean daily cost over the baseline window */
+  baseline_mean: number;
+  /** Standard deviation of daily cost over the baseline window */
+  baseline_std_dev: number;
+  /** Cost above which the day counts as anomalous */
+  threshold: number;
+  /** Standard deviations above the baseline mean */
+  z_score: number;
+}
+
+/** Projected spend for the coming days and the current month */
+export interface CostForecast {
+  /** Date the forecast was made (YYYY-MM-DD); projections start the day after */
+  as_of: string;
+  /** Month being projected (YYYY-MM) */
+  month: string;
+  /** Actual cost so far this month, including the forecast date */
+  month_to_date_cost: number;
+  /** Actual cost so far plus the projection for the remaining days */
+  projected_month_cost: number;
+  /** Mean daily cost over the fitted history */
+  daily_average: number;
+  /** Change in daily cost per day (0 when there's too little history) */
+  daily_slope: number;
+  /** Number of days of history the projection is based on */
+  history_days: number;
+  points: ForecastPoint[];
+}
+
+/** Projected cost for a single future day */
+export interface ForecastPoint {
+  /** Date in YYYY-MM-DD format */
+  date: string;
+  projected_cost: number;
+  /** Month-to-date cost including this day's projection */
+  cumulative_cost: number;
+}
+
+/** Two date ranges side by side */
+export interface DateRangeComparison {
+  range_a: PeriodTotals;
+  range_b: PeriodTotals;
+  /** Range B measured against range A */
+  deltas: PeriodDeltas;
+  /** Every tool used in either range, largest absolute change first */
+  tool_usage: ToolUsageDelta[];
+}
+
+/** Totals of the sessions started within a date range */
+export interface PeriodTotals {
+  days: number;
+  sessions: number;
+  turns: number;
+  total_tokens: number;
+  total_cost: number;
+  /** Session-weighted average efficiency (cache efficiency ratio) */
+  avg_efficiency: number;
+  /** Tool calls of the range's stored turns */
+  tool_calls: number;
+  cost_per_day: number;
+  start_date: string;
+  end_date: string;
+}
+
+/** Changes from range A to range B */
+export interface PeriodDeltas {
+  cost: MetricDelta;
+  cost_per_day: MetricDelta;
+  tokens: MetricDelta;
+  sessions: MetricDelta;
+  turns: MetricDelta;
+  efficiency: MetricDelta;
+  tool_calls: MetricDelta;
+}
+
+/** A week compared to the week before it */
+export interface WeeklyReport {
+  week: DateRange;
+  /** Totals of the previous week (range A) and this week (range B) */
+  comparison: DateRangeComparison;
+  /** Every day of the week, including days without sessions */
+  daily: DailyTrend[];
+  top_sessions: ReportSession[];
+  /** Types found in either week, most frequent this week first */
+  antipatterns: ReportAntiPattern[];
+  recommendations: Recommendation[];
+}
+
+/** Inclusive range of calendar days (YYYY-MM-DD, reporting timezone) */
+export interface DateRange {
+  start_date: string;
+  end_date: string;
+}
+
+/** A session started in the report week */
+export interface ReportSession {
+  session_id: string;
+  project_path: string;
+  project_name: string;
+  started_at: string;
+  turns: number;
+  cost: number;
+}
+
+/** Anti-patterns of one type in the report week and the week before */
+export interface ReportAntiPattern {
+  pattern_type: AntiPatternType;
+  occurrences: number;
+  previous_occurrences: number;
+  /** Sessions of the report week the pattern was found in */
+  sessions: number;
+  impact_cost: number;
+}
+
+/** A single recommendation */
+export interface Recommendation {
+  /** Stable id, see [`recommendation_id`] */
+  id?: string;
+  /** Type of recommendation */
+  rec_type: RecommendationType;
+  /** Short title for the recommendation */
+  title: string;
+  /** Detailed description explaining the recommendation */
+  description: string;
+  /** Potential savings (in dollars or percentage improvement) */
+  potential_savings: number;
+  /** Whether potential_savings is a dollar amount or percentage */
+  savings_is_percentage: boolean;
+  /** Confidence level (0.0 - 1.0) */
+  confidence: number;
+  /** Specific action items to implement this recommendation */
+  action_items: string[];
+  /** What data/metrics this recommendation is based on */
+  based_on: string;
+  /** Priority score for sorting (computed from type + savings + confidence) */
+  priority_score: number;
+}
+
+/** Type of recommendation */
+export type RecommendationType = 'cost_saving' | 'efficiency_improvement' | 'workflow_optimization' | 'cache_optimization' | 'subagent_strategy' | 'model_selection' | 'tool_reliability';
+
+/** Cost trend data point for charts */
+export interface CostTrendPoint {
+  date: string;
+  cost: number;
+  cumulative_cost: number;
+}
+
+/** Efficiency trend data point for charts */
+export interface EfficiencyTrendPoint {
+  date: string;
+  efficiency: number;
+  sessions: number;
+}
+
+/** Progress or outcome of an export job, also the `export-progress` payload */
+export interface ExportJobStatus {
+  job_id: string;
+  state: ExportJobState;
+  processed: number;
+  /** Sessions selected for export, known once the date filter has run */
+  total: number;
+  /** Exported file (or Parquet directory), once written */
+  path?: string | null;
+  error?: string | null;
+}
+
+/** State of a background export */
+export type ExportJobState = 'running' | 'completed' | 'failed' | 'cancelled';
+
+/** Summary of all recommendations for a session or aggregate analysis */
+export interface RecommendationSummary {
+  /** All recommendations sorted by priority */
+  recommendations: Recommendation[];
+  /** Total potential dollar savings across all recommendations */
+  total_potential_savings: number;
+  /** Top priority recommendation (if any) */
+  top_priority?: Recommendation | null;
+  /** Number of high confidence recommendations */
+  high_confidence_count: number;
+  /** Average confidence across all recommendations */
+  avg_confidence: number;
+  /** Session ID analyzed (None if aggregate) */
+  session_id?: string | null;
+  /** Number of sessions analyzed (1 for single, N for aggregate) */
+  sessions_analyzed: number;
+}
+
+/**
+ * A dismissed or snoozed recommendation
+ *
+ * Dismissing a session's recommendation hides that type of recommendation for every session of the same project. The savings and confidence at the time are kept so the recommendation can come back if things get noticeably worse.
+ */
+export interface RecommendationFeedback {
+  /** Id of the dismissed recommendation */
+  id: string;
+  rec_type: RecommendationType;
+  /** Session the recommendation was dismissed in (None if aggregate) */
+  session_id?: string | null;
+  /** Project of that session */
+  project_path?: string | null;
+  reason?: string | null;
+  potential_savings: number;
+  confidence: number;
+  dismissed_at: string;
+  /** Shown again after this time; None dismisses until things change */
+  snoozed_until?: string | null;
+}
+
+/** A detected anti-pattern instance */
+export interface DetectedPattern {
+  /** Type of anti-pattern detected */
+  pattern_type: AntiPatternType;
+  /** Severity of the pattern */
+  severity: Severity;
+  /** Session where pattern was detected */
+  session_id: string;
+  /** Specific turn number (if applicable) */
+  turn_number?: number | null;
+  /** Human-readable description */
+  description: string;
+  /** Estimated cost impact in dollars */
+  impact_cost: number;
+  /** Suggestion for improvement */
+  suggestion: string;
+  /** The metric value that triggered detection */
+  metric_value: number;
+  /** The threshold that was exceeded */
+  threshold: number;
+}
+
+/** Severity level for detected patterns */
+export type Severity = 'info' | 'warning' | 'critical';
+
+/** Anti-pattern occurrences of one type on one day */
+export interface AntiPatternHistoryPoint {
+  date: string;
+  pattern_type: AntiPatternType;
+  occurrences: number;
+  /** Sessions the pattern was found in that day */
+  sessions: number;
+  /** Mean severity score, from 1 (info) to 9 (far past a critical threshold) */
+  avg_severity_score: number;
+  impact_cost: number;
+}
+
+/** Summary of a day compared to the week before it */
+export interface DailyDigest {
+  /** Date in YYYY-MM-DD format */
+  date: string;
+  sessions: number;
+  turns: number;
+  cost: number;
+  avg_sessions_7d: number;
+  avg_cost_7d: number;
+  /** Change from the daily averages in percent; None without a baseline */
+  sessions_change_percent?: number | null;
+  cost_change_percent?: number | null;
+  top_project?: DigestProject | null;
+  most_expensive_session?: DigestSession | null;
+  /** Most frequent first */
+  antipatterns: DigestAntiPattern[];
+}
+
+/** Project with the highest cost on the digest's day */
+export interface DigestProject {
+  project_path: string;
+  project_name: string;
+  sessions: number;
+  cost: number;
+}
+
+/** Session with the highest cost among those started on the digest's day */
+export interface DigestSession {
+  session_id: string;
+  project_path: string;
+  turns: number;
+  cost: number;
+}
+
+/** Anti-patterns of one type found on the digest's day */
+export interface DigestAntiPattern {
+  pattern_type: AntiPatternType;
+  occurrences: number;
+  sessions: number;
+  impact_cost: number;
+  /** Occurrences per day over the baseline week */
+  avg_occurrences_7d: number;
+}
+
+/** GitHub configuration auto-detected from environment */
+export interface GitHubConfigResponse {
+  has_token: boolean;
+  token_source?: string | null;
+  username?: string | null;
+}
+
+/** Developer performance metrics response (3-axis AI adoption metrics) */
+export interface DeveloperPerformanceResponse {
+  throughput_velocity: number;
+  parallelism_ratio: number;
+  ai_roi: number;
+  throughput_velocity_score: number;
+  parallelism_ratio_score: number;
+  ai_roi_score: number;
+  archetype: string;
+  overall_score: number;
+  sprint_count: number;
+  prs_merged: number;
+  total_cc_spend: number;
+  sprints: SprintScoreResponse[];
+  baseline?: DeveloperPerformanceResponse | null;
+}
+
+/** Per-sprint scores for bubble rendering */
+export interface SprintScoreResponse {
+  index: number;
+  start_date: string;
+  end_date: string;
+  throughput_velocity: number;
+  parallelism_ratio: number;
+  ai_roi: number;
+  throughput_velocity_score: number;
+  parallelism_ratio_score: number;
+  ai_roi_score: number;
+}
+
+/** Model pricing entry as shown in settings */
+export interface ModelPricingResponse {
+  /** Whether this entry is a user override rather than a built-in default */
+  is_custom: boolean;
+  model_id: string;
+  display_name: string;
+  input_price_per_million: number;
+  output_price_per_million: number;
+  cache_write_5m_per_million: number;
+  cache_write_1h_per_million: number;
+  cache_read_per_million: number;
+  max_context_tokens?: number | null;
+  /** Backend these rates apply to */
+  provider?: CostProvider;
+  /** First day (YYYY-MM-DD, UTC) these rates apply; they last until the next entry for the same model and provider starts. None for rates with no known start. */
+  effective_from?: string | null;
+}
+
+/** Backend that bills a request */
+export type CostProvider = 'anthropic' | 'bedrock' | 'vertex';
+
+/** User-editable application settings */
+export interface AppSettings {
+  /** Glob patterns a project path must match to be counted; empty means all */
+  include_project_patterns?: string[];
+  /** Glob patterns for project paths to leave out, applied after includes */
+  exclude_project_patterns?: string[];
+  /** Delete cached sessions inactive for longer than this many days; None (or 0) keeps everything */
+  retention_days?: number | null;
+  /** Roll subagent sessions up into the session that spawned them when counting sessions in dashboard and project aggregates */
+  attribute_subagent_costs?: boolean;
+  /** In-memory cache limits */
+  cache?: CacheConfig;
+  /** Rescan intervals used when file watching isn't available */
+  polling?: PollingConfig;
+  /** Webhook notifications for analytics events */
+  notifications?: NotificationSettings;
+  /** Weights of the session health score components */
+  health_weights?: HealthWeights;
+  /** Timezone days and hours are reported in: None follows the OS, otherwise "UTC" or a fixed offset such as "+02:00" */
+  timezone?: string | null;
+  /** Metrics-only ingestion: message text, tool inputs and tool results are dropped as sessions are parsed, so only token, cost and tool metadata reach the caches, the database and exports. Code change and rework analysis need tool inputs and come up empty. */
+  privacy_mode?: boolean;
+  /** Store message text, tool inputs and results, session summaries and annotation notes encrypted, with a key kept in the OS keychain (macOS and Linux only). Project and changed-file paths, token counts and costs stay plaintext. */
+  encrypt_content?: boolean;
+  /** Claude data directories scanned besides the default one, e.g. a second CLAUDE_CONFIG_DIR for work sessions */
+  claude_roots?: ClaudeRoot[];
+  /** How deliverable units are estimated for CPDU and CPD */
+  deliverable_estimator?: DeliverableEstimator;
+  /** Rolling windows usage is summed over, with optional warning thresholds, e.g. to stay within a plan's 5-hour limit */
+  usage_windows?: UsageWindowConfig[];
+  /** Saved templates for session summaries, alongside the built-in ones */
+  summary_templates?: SummaryTemplate[];
+  /** Releases offered by the update check */
+  update_channel?: UpdateChannel;
+  /** Clients allowed to connect to the local IPC socket (see [`crate::ipc`]) */
+  ipc_tokens?: IpcToken[];
+}
+
+/** Sizes and lifetimes of the in-memory caches */
+export interface CacheConfig {
+  /** Memory budget for parsed session turns, in megabytes; the least recently used sessions are evicted once it is exceeded */
+  session_cache_budget_mb?: number;
+  /** How long a scanned session list is reused before rescanning */
+  session_list_ttl_secs?: number;
+  dashboard_ttl_secs?: number;
+  daily_metrics_ttl_secs?: number;
+  project_metrics_ttl_secs?: number;
+  /** Keep parsing sessions beyond the first preload batch in the background */
+  aggressive_preload?: boolean;
+}
+
+/**
+ * How often session files are rescanned when file watching isn't available
+ *
+ * Scans run at the active interval while any session file changed within the active window, and back off to the idle interval otherwise.
+ */
+export interface PollingConfig {
+  active_interval_secs?: number;
+  idle_interval_secs?: number;
+  /** How long after the last change a session counts as active */
+  active_window_secs?: number;
+}
+
+/** Where analytics events are sent and which ones */
+export interface NotificationSettings {
+  /** URLs every enabled event is POSTed to, e.g. Slack incoming webhooks */
+  webhook_urls?: string[];
+  new_session?: boolean;
+  budget_exceeded?: boolean;
+  anomaly_detected?: boolean;
+  antipattern_found?: boolean;
+  /** Post a summary of the previous day once a day */
+  daily_digest?: boolean;
+  /** Daily spend in USD above which `budget_exceeded` fires */
+  daily_budget?: number | null;
+}
+
+/** Relative weights of the health score components */
+export interface HealthWeights {
+  efficiency?: number;
+  antipatterns?: number;
+  tool_failures?: number;
+  rework?: number;
+}
+
+/** A Claude data directory laid out like ~/.claude */
+export interface ClaudeRoot {
+  /** Directory path; a leading `~` is the home directory */
+  path: string;
+  /** Source label stored with the root's sessions; defaults to the directory name */
+  label?: string | null;
+}
+
+/** A rolling window to track and its warning thresholds */
+export interface UsageWindowConfig {
+  /** Window length in hours */
+  hours: number;
+  /** Warn once this many tokens are used within the window */
+  token_threshold?: number | null;
+  /** Warn once this much is spent within the window, in USD */
+  cost_threshold?: number | null;
+}
+
+/** Which releases are offered as updates */
+export type UpdateChannel = 'stable' | 'beta';
+
+/** An allowlisted client of the local IPC socket, e.g. an editor extension */
+export interface IpcToken {
+  /** Shown in the settings and logged when the client connects */
+  name: string;
+  token: string;
+}
+
+/** Result of an update check */
+export interface UpdateInfo {
+  current_version: string;
+  channel: UpdateChannel;
+  update_available: boolean;
+  /** Newest release on the channel, whether or not it's newer than the running version; None if the channel has no releases */
+  latest_version?: string | null;
+  prerelease: boolean;
+  /** Release title and notes (Markdown) */
+  release_name?: string | null;
+  release_notes?: string | null;
+  release_url?: string | null;
+  published_at?: string | null;
+}
+
+/** What an editor extension needs to connect to the IPC socket */
+export interface IpcConnectionInfo {
+  name: string;
+  token: string;
+  /** Socket path, or the pipe name on Windows */
+  endpoint: string;
+}
+
+/** Rows removed by a prune */
+export interface PruneStats {
+  sessions_deleted: number;
+  turns_deleted: number;
+}
+
+/** Database size before and after a VACUUM */
+export interface VacuumStats {
+  size_before: number;
+  size_after: number;
+}
+
+/** Outcome of merging a snapshot */
+export interface SnapshotImportStats {
+  /** Sessions that weren't in the local database */
+  sessions_added: number;
+  /** Local sessions replaced by a more recent copy */
+  sessions_updated: number;
+  /** Sessions whose local copy is as recent or more */
+  sessions_skipped: number;
+  /** Dismissed or snoozed recommendations taken from the snapshot */
+  dismissals_merged: number;
+}
+
+/** Snapshots exchanged through a sync folder */
+export interface SnapshotSyncReport {
+  /** The snapshot written for this machine */
+  exported_path: string;
+  /** Other machines' snapshots that were merged */
+  imported: string[];
+  /** Snapshots that could not be merged, with the reason */
+  failed: string[];
+  stats: SnapshotImportStats;
+}
+
+/** Outcome of bootstrapping from a snapshot */
+export interface BootstrapStats {
+  sessions_imported: number;
+  /** Sessions whose files are also on this machine */
+  sessions_local: number;
+  /** Sessions whose files are only on the machine the snapshot came from */
+  sessions_remote: number;
+  dismissals_merged: number;
+}
+
+/** Outcome of importing one user's export */
+export interface TeamImportStats {
+  user_name: string;
+  sessions_added: number;
+  /** Sessions already imported for this user, replaced by the new export */
+  sessions_updated: number;
+}
+
+/** A user with imported sessions */
+export interface TeamMember {
+  user_name: string;
+  session_count: number;
+  total_cost: number;
+  total_tokens: number;
+  first_session_at?: string | null;
+  last_session_at?: string | null;
+  last_imported_at?: string | null;
+}
+
+/** Team totals over a period, broken down by user */
+export interface TeamDashboard {
+  total_sessions: number;
+  total_turns: number;
+  total_tokens: number;
+  total_cost: number;
+  avg_efficiency?: number | null;
+  /** Costliest users first */
+  members: TeamMemberStats[];
+}
+
+/** One user's share of the team totals */
+export interface TeamMemberStats {
+  user_name: string;
+  session_count: number;
+  total_turns: number;
+  total_tokens: number;
+  total_cost: number;
+  /** Fraction of the team's cost (0-1) */
+  cost_share: number;
+  avg_cost_per_session: number;
+  avg_efficiency?: number | null;
+}
+
+/** Team usage on one day, with each user's part */
+export interface TeamDailyTrend {
+  date: string;
+  session_count: number;
+  total_tokens: number;
+  total_cost: number;
+  by_user: TeamUserDay[];
+}
+
+/** One user's usage on one day */
+export interface TeamUserDay {
+  user_name: string;
+  session_count: number;
+  total_tokens: number;
+  total_cost: number;
+}
+
+/** Session files moved into the archive */
+export interface ArchiveReport {
+  sessions_archived: number;
+  /** Size of the archived files before compression */
+  bytes_before: number;
+  /** Size of the archived files after compression */
+  bytes_after: number;
+  /** Files that could not be archived, with the reason */
+  failed: string[];
+}
+
+/** Result of importing sessions from a directory */
+export interface ImportResult {
+  /** Source label the sessions were stored under */
+  source: string;
+  /** Canonical path of the imported directory */
+  path: string;
+  /** JSONL files found under the directory */
+  files_found: number;
+  /** Sessions parsed and stored */
+  sessions_imported: number;
+  /** Sessions skipped because they are already tracked from ~/.claude or another source */
+  duplicates: number;
+  /** Files that don't parse as Claude Code sessions */
+  invalid_files: string[];
+}
+
+/** Project path an encoded project directory resolves to */
+export interface ProjectResolution {
+  /** Directory name under `projects/`, e.g. `-Users-me-my-app` */
+  encoded_dir: string;
+  project_path: string;
+  source: ResolutionSource;
+  /** More than one path encodes to the directory's name; sessions are placed by their own `cwd` where they have one */
+  ambiguous: boolean;
+}
+
+/** How a directory's project path was found */
+export type ResolutionSource = 'override' | 'cwd' | 'filesystem' | 'decoded';
+
+/** Environment health, for the diagnostics panel */
+export interface DiagnosticsReport {
+  /** Claude data directory ($CLAUDE_CONFIG_DIR or ~/.claude) */
+  claude_dir?: string | null;
+  /** Whether it has a `projects` directory */
+  claude_dir_found: boolean;
+  /** The Claude directory plus extra roots from settings */
+  scan_roots: number;
+  /** None when the database couldn't be opened */
+  database?: DatabaseDiagnostics | null;
+  cache: CacheDiagnostics;
+  /** When a preload last finished or stopped (RFC 3339) */
+  last_preload_at?: string | null;
+  watcher: WatcherStatus;
+  /** Locked when `encrypt_content` is on but the keychain has no usable key; message content is then neither shown nor stored */
+  content_encryption: ContentEncryptionStatus;
+  /** Startup steps that failed; the app runs without them */
+  init_errors: InitError[];
+}
+
+/** The analytics database's file, schema and size */
+export interface DatabaseDiagnostics {
+  path: string;
+  size_bytes: number;
+  schema_version: number;
+  /** Latest schema version this build knows; higher than `schema_version` when a migration failed */
+  latest_schema_version: number;
+}
+
+/** Fill of the in-memory caches */
+export interface CacheDiagnostics {
+  /** Session files in the cached scan */
+  listed_sessions: number;
+  cached_summaries: number;
+  /** Sessions whose parsed turns are held in memory */
+  cached_sessions: number;
+  cached_bytes: number;
+  budget_bytes: number;
+}
+
+/** How the background task follows session files */
+export type WatcherStatus = 'not_started' | 'watching' | 'polling';
+
+/** Whether stored content is encrypted, for reporting */
+export type ContentEncryptionStatus = 'off' | 'on' | 'locked';
+
+/** A startup step that failed */
+export interface InitError {
+  /** What was being set up, e.g. "database schema" */
+  stage: string;
+  message: string;
+  /** When it failed (RFC 3339) */
+  at: string;
+}
+
+/** A non-fatal error the backend worked around, with how often it happened */
+export interface BackendError {
+  /** What failed, e.g. "load dashboard summary" */
+  source: string;
+  count: number;
+  /** The most recent error message */
+  last_message: string;
+  /** When it first and last happened (RFC 3339) */
+  first_at: string;
+  last_at: string;
+}
+
+/** Payload of the `preload-progress` event */
+export interface PreloadProgress {
+  /** 1 for the sessions loaded before the command returns, 2 for the rest parsed in the background */
+  phase: number;
+  processed: number;
+  total: number;
+  /** Sessions served from the database cache instead of being parsed */
+  cache_hits: number;
+  elapsed_ms: number;
+  /** Estimated time left in this phase, once anything has been processed */
+  eta_ms?: number | null;
+  done: boolean;
+  cancelled: boolean;
+}
+
+/** Payload of the `backfill-progress` event */
+export interface BackfillProgress {
+  processed: number;
+  total: number;
+  /** Sessions whose file couldn't be parsed */
+  failed: number;
+  /** Sessions stored before the app was closed, when resuming a run */
+  previously_processed: number;
+  elapsed_ms: number;
+  done: boolean;
+  cancelled: boolean;
+}
+
+/** Payload of the `project-warmup-progress` event */
+export interface ProjectWarmupProgress {
+  project_path: string;
+  processed: number;
+  total: number;
+  elapsed_ms: number;
+  done: boolean;
+}
+
+/** Payload of the `turn-appended` event */
+export interface TurnAppended {
+  session_id: string;
+  is_subagent: boolean;
+  turn_number: number;
+  started_at: string;
+  ended_at?: string | null;
+  model?: string | null;
+  tool_count: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  total_tokens: number;
+  /** Cost delta contributed by this turn (USD) */
+  cost: number;
+  session_total_tokens: number;
+  session_total_cost: number;
+}
+
+/** Payload of the `active-session-cost` event */
+export interface ActiveSessionCost {
+  session_id: string;
+  /** Tokens and cost so far, including the turn still in progress */
+  total_tokens: number;
+  total_cost: number;
+  /** Growth since the previous tick for this session */
+  tokens_delta: number;
+  cost_delta: number;
+  turn_in_progress: boolean;
+}
+
+/** A window that has just reached one of its thresholds */
+export interface UsageWindowWarning {
+  label: string;
+  hours: number;
+  metric: UsageMetric;
+  /** Tokens or USD used in the window */
+  value: number;
+  threshold: number;
+  next_rollover?: string | null;
+}
+
+/** What a threshold limits */
+export type UsageMetric = 'tokens' | 'cost';
+
+/** Response type of each command */
+export interface Commands {
+  get_sessions: SessionSummary[];
+  get_session: SessionDetail | null;
+  get_session_metrics: SessionMetricsResponse | null;
+  get_session_count: number;
+  get_sessions_filtered: SessionSummary[];
+  get_sessions_by_project: SessionSummary[];
+  preload_all_sessions: number;
+  cancel_preload: boolean;
+  warm_project_cache: ProjectWarmupStats;
+  backfill_database: number;
+  cancel_backfill: boolean;
+  list_jobs: Job[];
+  cancel_job: boolean;
+  get_turns: TurnSummary[];
+  get_session_cost_curve: CostCurvePoint[];
+  annotate_turn: TurnAnnotation;
+  remove_turn_annotation: boolean;
+  get_turn_annotations: TurnAnnotation[];
+  get_parse_diagnostics: ParseDiagnostics;
+  get_session_raw_entries: RawEntryPage;
+  get_context_timeline: ContextTimeline;
+  get_session_transcript: Transcript;
+  get_session_subagents: SubagentSummary[];
+  get_subagent_tree: SubagentNode;
+  compare_sessions: SessionComparison;
+  get_session_code_changes: CodeChange[];
+  get_file_history: FileHistory;
+  get_project_file_changes: FileChangeSummary[];
+  get_session_rework: ReworkAnalysis;
+  get_tool_failures: ToolFailure[];
+  get_cache_breakdown: CacheBreakdown;
+  get_session_efficiency_curve: SessionEfficiencyCurve;
+  get_session_health: SessionHealth;
+  get_active_session: ActiveSession | null;
+  refresh_sessions: null;
+  recompute_session: SessionDetail;
+  summarize_session: string;
+  get_summary_templates: SummaryTemplate[];
+  scan_new_sessions: SessionSummary[];
+  get_session_git_context: SessionGitContext;
+  get_dashboard_summary: DashboardSummaryResponse;
+  get_daily_metrics: DailyMetricsResponse[];
+  get_project_metrics: ProjectMetricsResponse[];
+  get_project_benchmarks: ProjectBenchmarks;
+  get_project_timeline: ProjectTimeline;
+  get_hourly_usage: HourlyUsage;
+  get_usage_windows: UsageWindow[];
+  get_billing_blocks: BillingBlocks;
+  get_tool_usage: ToolUsageStats;
+  simulate_savings: SavingsSimulation;
+  get_mcp_stats: McpStats;
+  get_concurrent_sessions: ConcurrencyReport;
+  get_error_stats: ApiErrorStats;
+  get_thinking_trend: ThinkingTrend;
+  get_burn_rate: BurnRateReport;
+  get_session_distributions: SessionDistributions;
+  get_trends: TrendSeries;
+  get_cost_anomalies: CostAnomalyResponse[];
+  get_cost_forecast: CostForecast;
+  compare_periods: DateRangeComparison;
+  generate_weekly_report: WeeklyReport;
+  get_cost_trend: CostTrendPoint[];
+  get_efficiency_trend: EfficiencyTrendPoint[];
+  export_sessions: string;
+  get_export_status: ExportJobStatus;
+  cancel_export: boolean;
+  export_trends: string;
+  export_weekly_report: string;
+  export_session_replay: string;
+  export_session_bundle: string;
+  get_recommendations: RecommendationSummary;
+  dismiss_recommendation: RecommendationFeedback;
+  list_dismissed: RecommendationFeedback[];
+  restore_recommendation: boolean;
+  detect_antipatterns: DetectedPattern[];
+  get_antipattern_history: AntiPatternHistoryPoint[];
+  generate_daily_digest: DailyDigest;
+  detect_github_config: GitHubConfigResponse;
+  get_developer_metrics: DeveloperPerformanceResponse;
+  get_model_pricing: ModelPricingResponse[];
+  set_model_pricing: null;
+  reset_model_pricing: boolean;
+  get_unpriced_models: string[];
+  recompute_costs: number;
+  get_settings: AppSettings;
+  set_settings: AppSettings;
+  reload_cache_config: CacheConfig;
+  get_update_channel: UpdateChannel;
+  set_update_channel: UpdateChannel;
+  check_for_updates: UpdateInfo;
+  create_ipc_token: IpcConnectionInfo;
+  revoke_ipc_token: boolean;
+  get_db_path: string;
+  migrate_db_to: string;
+  prune_sessions_older_than: PruneStats;
+  vacuum_database: VacuumStats;
+  rebuild_daily_rollups: number;
+  export_snapshot: string;
+  import_snapshot: SnapshotImportStats;
+  sync_snapshot_folder: SnapshotSyncReport;
+  bootstrap_from_snapshot: BootstrapStats;
+  import_team_export: TeamImportStats;
+  get_team_members: TeamMember[];
+  remove_team_member: number;
+  get_team_dashboard: TeamDashboard;
+  get_team_trends: TeamDailyTrend[];
+  archive_sessions_older_than: ArchiveReport;
+  restore_session: string;
+  import_sessions_from_path: ImportResult;
+  set_project_path_override: ProjectResolution;
+  get_diagnostics: DiagnosticsReport;
+  get_backend_errors: BackendError[];
+}
+
+/** Payload type of each event */
+export interface Events {
+  'preload-progress': PreloadProgress;
+  'export-progress': ExportJobStatus;
+  'backfill-progress': BackfillProgress;
+  'job-updated': Job;
+  'project-warmup-progress': ProjectWarmupProgress;
+  'turn-appended': TurnAppended;
+  'active-session-cost': ActiveSessionCost;
+  'sessions-updated': string[];
+  'session-updated': SessionSummary;
+  'metrics-updated': string[];
+  'usage-window-warning': UsageWindowWarning;
+}
@@ -13,6 +13,8 @@ export interface SessionSummary {
   last_activity_at: string | null;
   model: string | null;
   total_cost: number;
+  /** Own cost plus the cost of the subagent sessions it spawned */
+  total_cost_including_subagents: number;
   total_turns: number;
   total_tokens: number;
   duration_ms: number;
@@ -32,6 +34,10 @@ export interface SessionDetail {
   is_subagent: boolean;
   file_path: string;
   metrics: SessionMetrics;
+  /** Own cost plus the cost of the subagent sessions it spawned */
+  total_cost_including_subagents: number;
+  /** Cost of the turns annotated as wasted */
+  wasted_cost: number;
 }
 
 // ============================================================================
@@ -43,12 +49,21 @@ export interface SessionMetrics {
   tokens: TokenSummary;
   cost: CostSummary;
   efficiency: EfficiencyMetrics;
+  /** Wall-clock time from the first turn to the last */
   duration_ms: number;
+  /** Time in turns and in pauses of up to 15 minutes between them */
+  active_duration_ms: number;
+  /** Pauses between turns longer than 15 minutes */
+  idle_duration_ms: number;
   turn_count: number;
   tool_count: number;
   unique_tools: string[];
   models_used: string[];
   subagent_count: number;
+  /** Times the conversation was compacted to free up context */
+  compaction_count: number;
+  /** Context tokens dropped by those compactions */
+  tokens_lost_to_compaction: number;
 }
 
 /** Token summary (matches Rust TokenSummaryResponse) */
@@ -60,6 +75,10 @@ export interface TokenSummary {
   cache_write_1h: number;
   total: number;
   context_used_pct: number;
+  /** Output tokens spent on extended thinking (estimated) */
+  thinking: number;
+  /** Thinking tokens as a fraction (0-1) of output tokens */
+  thinking_share: number;
 }
 
 /** Cost summary (matches Rust CostSummaryResponse) */
@@ -67,9 +86,14 @@ export interface CostSummary {
   input_cost: number;
   output_cost: number;
   cache_read_cost: number;
+  /** 5-minute and 1-hour cache writes together */
   cache_write_cost: number;
+  cache_write_5m_cost: number;
+  cache_write_1h_cost: number;
   total_cost: number;
   avg_cost_per_turn: number;
+  /** Part of the output cost spent on extended thinking */
+  thinking_cost: number;
 }
 
 /** Efficiency metrics (matches Rust EfficiencyResponse) */
@@ -82,8 +106,15 @@ export interface EfficiencyMetrics {
   cpd: number;         // Cycles per Deliverable
   oes_score: number;   // Overall Efficiency Score
   oes_grade: string;   // e.g., "A", "B", "C"
+  /** Deliverable units CPDU and CPD are based on */
+  deliverable_units: number;
+  /** Estimator used; code_changes falls back to tool_usage when no files changed */
+  deliverable_estimator: DeliverableEstimator;
 }
 
+/** How deliverable units are estimated (backend setting) */
+export type DeliverableEstimator = 'output_tokens' | 'tool_usage' | 'code_changes';
+
 // ============================================================================
 // Turn Types
 // ============================================================================
@@ -112,6 +143,64 @@ export interface TurnTokens {
   cache_read: number;
   cache_write: number;
   total: number;
+  /** Output tokens spent on extended thinking (estimated) */
+  thinking: number;
+}
+
+/** Payload of the `turn-appended` live event (matches Rust TurnAppended) */
+export interface TurnAppendedEvent {
+  session_id: string;
+  is_subagent: boolean;
+  turn_number: number;
+  started_at: string;
+  ended_at: string | null;
+  model: string | null;
+  tool_count: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  total_tokens: number;
+  cost: number;
+  session_total_tokens: number;
+  session_total_cost: number;
+}
+
+/** Payload of the `active-session-cost` live event (matches Rust ActiveSessionCost) */
+export interface ActiveSessionCostEvent {
+  session_id: string;
+  total_tokens: number;
+  total_cost: number;
+  /** Growth since the previous event for this session */
+  tokens_delta: number;
+  cost_delta: number;
+  turn_in_progress: boolean;
+}
+
+/** Payload of the `preload-progress` event */
+export interface PreloadProgressEvent {
+  /** 1 while loading the first batch, 2 for the background phase */
+  phase: 1 | 2;
+  processed: number;
+  total: number;
+  cache_hits: number;
+  elapsed_ms: number;
+  eta_ms: number | null;
+  done: boolean;
+  cancelled: boolean;
+}
+
+/** Payload of the `backfill-progress` event */
+export interface BackfillProgressEvent {
+  processed: number;
+  total: number;
+  /** Sessions whose file couldn't be parsed */
+  failed: number;
+  /** Sessions stored before the app was closed, when resuming a run */
+  previously_processed: number;
+  elapsed_ms: number;
+  done: boolean;
+  cancelled: boolean;
 }
 
 // ============================================================================
@@ -130,6 +219,21 @@ export interface DashboardSummary {
   avg_turns_per_session: number;
   avg_efficiency_score: number | null;
   active_projects: number;
+  /** Anti-pattern found most often in the period, null if none were found */
+  most_frequent_antipattern: AntiPatternFrequency | null;
+  /** Most sessions (subagents included) generating at the same moment */
+  peak_concurrent_sessions: number;
+  /** Fraction of turn cost spent while sessions overlapped (0-1) */
+  concurrent_cost_share: number;
+}
+
+/** How often an anti-pattern type occurred */
+export interface AntiPatternFrequency {
+  pattern_type: string;
+  display_name: string;
+  occurrences: number;
+  /** Sessions it was found in */
+  sessions: number;
 }
 
 /** Daily aggregated metrics */
@@ -154,6 +258,12 @@ export interface ProjectMetrics {
   total_tokens: number;
   avg_cost_per_session: number;
   last_activity: string;
+  /** Cost of the turns annotated as wasted */
+  wasted_cost: number;
+  /** Detected languages, most changed first */
+  languages: string[];
+  /** Frameworks from the project's manifests */
+  frameworks: string[];
 }
 
 /** Model usage metrics */
@@ -183,6 +293,26 @@ export interface SubagentSummary {
   tools_used: string[];
 }
 
+/** Node in a session's subagent hierarchy (matches Rust SubagentNode) */
+export interface SubagentNode {
+  /** Session ID for the root, agent ID for subagents */
+  id: string;
+  is_subagent: boolean;
+  description: string | null;
+  subagent_type: string | null;
+  started_at: string | null;
+  turn_count: number;
+  tools_used: string[];
+  /** Cost and tokens of this node's own turns */
+  cost: number;
+  tokens: number;
+  /** Cost and tokens including all descendants */
+  total_cost: number;
+  total_tokens: number;
+  descendant_count: number;
+  children: SubagentNode[];
+}
+
 // ============================================================================
 // Developer Performance Types
 // ============================================================================